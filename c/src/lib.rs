@@ -15,6 +15,7 @@ use gl;
 use pathfinder_canvas::{Canvas, CanvasFontContext, CanvasRenderingContext2D, FillStyle, LineJoin};
 use pathfinder_canvas::{Path2D, TextAlign, TextMetrics};
 use pathfinder_color::{ColorF, ColorU};
+use pathfinder_content::effects::SubpixelLayout;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::ArcDirection;
 use pathfinder_content::stroke::LineCap;
@@ -32,9 +33,8 @@ use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererLevel};
 use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
 use pathfinder_renderer::gpu::renderer::Renderer;
-use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::options::{AntialiasingMode, BuildOptions, RenderTransform};
 use pathfinder_renderer::scene::Scene;
-use pathfinder_simd::default::F32x4;
 use pathfinder_svg::SVGScene;
 use std::ffi::CString;
 use std::os::raw::{c_char, c_void};
@@ -82,10 +82,18 @@ pub const PF_GL_VERSION_GL4:    u8 = 2;
 
 pub const PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR: u8 = 0x1;
 pub const PF_RENDERER_OPTIONS_FLAGS_SHOW_DEBUG_UI: u8 = 0x2;
+/// Blends in linear light. Only supported by `PF_RENDERER_LEVEL_D3D9`; creating a D3D11 renderer
+/// with this flag fails.
+pub const PF_RENDERER_OPTIONS_FLAGS_LINEAR_COMPOSITING: u8 = 0x4;
 
 pub const PF_RENDERER_LEVEL_D3D9: u8 = 0x1;
 pub const PF_RENDERER_LEVEL_D3D11: u8 = 0x2;
 
+pub const PF_SUBPIXEL_LAYOUT_RGB:  u8 = 0;
+pub const PF_SUBPIXEL_LAYOUT_BGR:  u8 = 1;
+pub const PF_SUBPIXEL_LAYOUT_VRGB: u8 = 2;
+pub const PF_SUBPIXEL_LAYOUT_VBGR: u8 = 3;
+
 // Types
 
 // External: `font-kit`
@@ -207,6 +215,7 @@ pub type PFRendererOptionsFlags = u8;
 pub type PFBuildOptionsRef = *mut BuildOptions;
 pub type PFRenderTransformRef = *mut RenderTransform;
 pub type PFRendererLevel = u8;
+pub type PFSubpixelLayout = u8;
 
 // `svg`
 pub type PFSVGSceneRef = *mut SVGScene;
@@ -545,6 +554,7 @@ pub unsafe extern "C" fn PFGLLoadWith(loader: PFGLFunctionLoader, userdata: *mut
     });
 }
 
+/// Returns `NULL` on failure.
 #[no_mangle]
 pub unsafe extern "C" fn PFGLDeviceCreate(version: PFGLVersion, default_framebuffer: u32)
                                           -> PFGLDeviceRef {
@@ -554,7 +564,10 @@ pub unsafe extern "C" fn PFGLDeviceCreate(version: PFGLVersion, default_framebuf
         PF_GL_VERSION_GL4   => GLVersion::GL4,
         _ => panic!("Invalid Pathfinder OpenGL version!"),
     };
-    Box::into_raw(Box::new(GLDevice::new(version, default_framebuffer)))
+    match GLDevice::new(version, default_framebuffer) {
+        Ok(device) => Box::into_raw(Box::new(device)),
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
@@ -578,16 +591,21 @@ pub unsafe extern "C" fn PFGLDestFramebufferDestroy(dest_framebuffer: PFGLDestFr
 /// This function takes ownership of and automatically takes responsibility for destroying `device`
 /// and `dest_framebuffer`. However, it does not take ownership of `resources`; therefore, if you
 /// created the resource loader, you must destroy it yourself to avoid a memory leak.
+///
+/// Returns `NULL` on failure. `device` is destroyed either way.
 #[no_mangle]
 pub unsafe extern "C" fn PFGLRendererCreate(device: PFGLDeviceRef,
                                             resources: PFResourceLoaderRef,
                                             mode: *const PFRendererMode,
                                             options: *const PFRendererOptions)
                                             -> PFGLRendererRef {
-    Box::into_raw(Box::new(Renderer::new(*Box::from_raw(device),
-                                         &*((*resources).0),
-                                         (*mode).to_rust(),
-                                         (*options).to_rust())))
+    match Renderer::new(*Box::from_raw(device),
+                        &*((*resources).0),
+                        (*mode).to_rust(),
+                        (*options).to_rust()) {
+        Ok(renderer) => Box::into_raw(Box::new(renderer)),
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[no_mangle]
@@ -617,6 +635,8 @@ pub unsafe extern "C" fn PFMetalDestFramebufferDestroy(dest_framebuffer:
 /// This function takes ownership of and automatically takes responsibility for destroying `device`
 /// and `dest_framebuffer`. However, it does not take ownership of `resources`; therefore, if you
 /// created the resource loader, you must destroy it yourself to avoid a memory leak.
+///
+/// Returns `NULL` on failure. `device` is destroyed either way.
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFMetalRendererCreate(device: PFMetalDeviceRef,
@@ -624,10 +644,13 @@ pub unsafe extern "C" fn PFMetalRendererCreate(device: PFMetalDeviceRef,
                                                mode: *const PFRendererMode,
                                                options: *const PFRendererOptions)
                                                -> PFMetalRendererRef {
-    Box::into_raw(Box::new(Renderer::new(*Box::from_raw(device),
-                                         &*((*resources).0),
-                                         (*mode).to_rust(),
-                                         (*options).to_rust())))
+    match Renderer::new(*Box::from_raw(device),
+                        &*((*resources).0),
+                        (*mode).to_rust(),
+                        (*options).to_rust()) {
+        Ok(renderer) => Box::into_raw(Box::new(renderer)),
+        Err(_) => ptr::null_mut(),
+    }
 }
 
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
@@ -648,21 +671,27 @@ pub unsafe extern "C" fn PFMetalRendererGetDevice(renderer: PFMetalRendererRef)
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns `false` if rendering failed, for example because the GPU ran out of memory.
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderGL(scene_proxy: PFSceneProxyRef,
                                                       renderer: PFGLRendererRef,
-                                                      build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                      build_options: PFBuildOptionsRef)
+                                                      -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 /// This function does not take ownership of `renderer` or `build_options`. Therefore, if you
 /// created the renderer and/or options, you must destroy them yourself to avoid a leak.
+///
+/// Returns `false` if rendering failed, for example because the GPU ran out of memory.
 #[cfg(all(target_os = "macos", not(feature = "pf-gl")))]
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneProxyBuildAndRenderMetal(scene_proxy: PFSceneProxyRef,
                                                          renderer: PFMetalRendererRef,
-                                                         build_options: PFBuildOptionsRef) {
-    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone())
+                                                         build_options: PFBuildOptionsRef)
+                                                         -> bool {
+    (*scene_proxy).build_and_render(&mut *renderer, (*build_options).clone()).is_ok()
 }
 
 // `metal`
@@ -731,6 +760,30 @@ pub unsafe extern "C" fn PFBuildOptionsSetSubpixelAAEnabled(options: PFBuildOpti
     (*options).subpixel_aa_enabled = subpixel_aa_enabled
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetSubpixelLayout(options: PFBuildOptionsRef,
+                                                         subpixel_layout: PFSubpixelLayout) {
+    (*options).subpixel_layout = to_rust_subpixel_layout(subpixel_layout)
+}
+
+/// Tolerances below 0.01, and tolerances that aren't finite, are treated as 0.01.
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetFlatteningTolerance(options: PFBuildOptionsRef,
+                                                              tolerance: f32) {
+    (*options).flattening_tolerance = tolerance
+}
+
+/// A sample count of zero selects analytic coverage, and one selects aliased rendering.
+#[no_mangle]
+pub unsafe extern "C" fn PFBuildOptionsSetAntialiasingSampleCount(options: PFBuildOptionsRef,
+                                                                  sample_count: u32) {
+    (*options).antialiasing_mode = match sample_count {
+        0 => AntialiasingMode::Analytic,
+        1 => AntialiasingMode::Aliased,
+        sample_count => AntialiasingMode::Supersampled(sample_count),
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn PFSceneDestroy(scene: PFSceneRef) {
     drop(Box::from_raw(scene))
@@ -808,7 +861,7 @@ impl TextMetricsExt for TextMetrics {
 impl PFColorF {
     #[inline]
     pub fn to_rust(&self) -> ColorF {
-        ColorF(F32x4::new(self.r, self.g, self.b, self.a))
+        ColorF::new(self.r, self.g, self.b, self.a)
     }
 }
 
@@ -897,6 +950,8 @@ impl PFRendererOptions {
     pub fn to_rust<D>(&self) -> RendererOptions<D> where D: Device {
         let has_background_color = self.flags & PF_RENDERER_OPTIONS_FLAGS_HAS_BACKGROUND_COLOR;
         let show_debug_ui = (self.flags & PF_RENDERER_OPTIONS_FLAGS_SHOW_DEBUG_UI) != 0;
+        let linear_compositing =
+            (self.flags & PF_RENDERER_OPTIONS_FLAGS_LINEAR_COMPOSITING) != 0;
         unsafe {
             RendererOptions {
                 background_color: if has_background_color != 0 {
@@ -906,6 +961,8 @@ impl PFRendererOptions {
                 },
                 dest: *Box::from_raw(self.dest as *mut DestFramebuffer<D>),
                 show_debug_ui,
+                linear_compositing,
+                ..RendererOptions::default()
             }
         }
     }
@@ -918,3 +975,13 @@ fn to_rust_renderer_level(level: PFRendererLevel) -> RendererLevel {
         _                       => panic!("Invalid Pathfinder renderer level!"),
     }
 }
+
+fn to_rust_subpixel_layout(subpixel_layout: PFSubpixelLayout) -> SubpixelLayout {
+    match subpixel_layout {
+        PF_SUBPIXEL_LAYOUT_RGB  => SubpixelLayout::RGB,
+        PF_SUBPIXEL_LAYOUT_BGR  => SubpixelLayout::BGR,
+        PF_SUBPIXEL_LAYOUT_VRGB => SubpixelLayout::VRGB,
+        PF_SUBPIXEL_LAYOUT_VBGR => SubpixelLayout::VBGR,
+        _                       => panic!("Invalid Pathfinder subpixel layout!"),
+    }
+}
@@ -11,6 +11,7 @@
 use pathfinder_simd::default::F32x4;
 use std::f32::consts::PI;
 use std::fmt::{self, Debug, Formatter};
+use std::mem;
 use std::slice;
 
 // TODO(pcwalton): Maybe this should be a u32? Need to be aware of endianness issues if we do that.
@@ -67,7 +68,7 @@ impl ColorU {
     #[inline]
     pub fn to_f32(&self) -> ColorF {
         let color = F32x4::new(self.r as f32, self.g as f32, self.b as f32, self.a as f32);
-        ColorF(color * F32x4::splat(1.0 / 255.0))
+        ColorF(color * F32x4::splat(1.0 / 255.0), ColorSpace::SRGB)
     }
 
     #[inline]
@@ -98,15 +99,50 @@ impl Debug for ColorU {
     }
 }
 
+/// The color space that the components of a `ColorF` are expressed in.
+///
+/// Display P3 shares the sRGB transfer function and white point but has wider primaries.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ColorSpace {
+    /// Gamma-encoded sRGB. This is the default, and the space of every `ColorU`.
+    SRGB,
+    /// Gamma-encoded Display P3.
+    DisplayP3,
+    /// sRGB primaries without gamma encoding, so that components are proportional to light.
+    LinearSRGB,
+}
+
+impl Default for ColorSpace {
+    #[inline]
+    fn default() -> ColorSpace {
+        ColorSpace::SRGB
+    }
+}
+
+// Convert between linear Display P3 and linear sRGB. Rows are output components.
+const DISPLAY_P3_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [ 1.2249401, -0.2249404,  0.0000000],
+    [-0.0420569,  1.0420571,  0.0000000],
+    [-0.0196376, -0.0786361,  1.0982735],
+];
+const LINEAR_SRGB_TO_DISPLAY_P3: [[f32; 3]; 3] = [
+    [0.8224621, 0.1775380, 0.0000000],
+    [0.0331941, 0.9668058, 0.0000000],
+    [0.0170827, 0.0723974, 0.9105199],
+];
+
+/// A color with floating-point components and straight (not premultiplied) alpha, tagged with
+/// the color space that the red, green, and blue components are in.
 #[derive(Clone, Copy, PartialEq, Default)]
-pub struct ColorF(pub F32x4);
+pub struct ColorF(pub F32x4, pub ColorSpace);
 
 impl ColorF {
     // Constructors
 
+    /// Creates a new sRGB color.
     #[inline]
     pub fn new(r: f32, g: f32, b: f32, a: f32) -> ColorF {
-        ColorF(F32x4::new(r, g, b, a))
+        ColorF(F32x4::new(r, g, b, a), ColorSpace::SRGB)
     }
 
     #[inline]
@@ -134,7 +170,7 @@ impl ColorF {
             _     => xc.zzzw(),
         };
         let m = l - 0.5 * c;
-        ColorF(rgba + F32x4::new(m, m, m, 0.0))
+        ColorF(rgba + F32x4::new(m, m, m, 0.0), ColorSpace::SRGB)
     }
 
     #[inline]
@@ -149,23 +185,61 @@ impl ColorF {
 
     #[inline]
     pub fn black() -> ColorF {
-        ColorF(F32x4::new(0.0, 0.0, 0.0, 1.0))
+        ColorF(F32x4::new(0.0, 0.0, 0.0, 1.0), ColorSpace::SRGB)
     }
 
     #[inline]
     pub fn white() -> ColorF {
-        ColorF(F32x4::splat(1.0))
+        ColorF(F32x4::splat(1.0), ColorSpace::SRGB)
     }
 
+    /// Converts this color to sRGB and quantizes it to 8 bits per component.
     #[inline]
     pub fn to_u8(&self) -> ColorU {
-        let color = (self.0 * F32x4::splat(255.0)).to_i32x4();
+        let srgb = self.to_color_space(ColorSpace::SRGB);
+        let color = (srgb.0 * F32x4::splat(255.0)).to_i32x4();
         ColorU { r: color[0] as u8, g: color[1] as u8, b: color[2] as u8, a: color[3] as u8 }
     }
 
+    /// Interpolates between this color and `other` in this color's space.
     #[inline]
     pub fn lerp(&self, other: ColorF, t: f32) -> ColorF {
-        ColorF(self.0 + (other.0 - self.0) * F32x4::splat(t))
+        let other = other.to_color_space(self.1);
+        ColorF(self.0 + (other.0 - self.0) * F32x4::splat(t), self.1)
+    }
+
+    // Color spaces
+
+    #[inline]
+    pub fn color_space(&self) -> ColorSpace {
+        self.1
+    }
+
+    /// Returns the same color expressed in `color_space`. Alpha is unchanged.
+    ///
+    /// Colors outside the gamut of `color_space` get components below zero or above one rather
+    /// than being clipped.
+    pub fn to_color_space(&self, color_space: ColorSpace) -> ColorF {
+        if self.1 == color_space {
+            return *self;
+        }
+
+        let linear_srgb = match self.1 {
+            ColorSpace::SRGB => map_rgb(self.0, srgb_to_linear),
+            ColorSpace::DisplayP3 => {
+                transform_rgb(map_rgb(self.0, srgb_to_linear), &DISPLAY_P3_TO_LINEAR_SRGB)
+            }
+            ColorSpace::LinearSRGB => self.0,
+        };
+
+        let rgba = match color_space {
+            ColorSpace::SRGB => map_rgb(linear_srgb, linear_to_srgb),
+            ColorSpace::DisplayP3 => {
+                map_rgb(transform_rgb(linear_srgb, &LINEAR_SRGB_TO_DISPLAY_P3), linear_to_srgb)
+            }
+            ColorSpace::LinearSRGB => linear_srgb,
+        };
+        ColorF(rgba, color_space)
     }
 
     #[inline]
@@ -218,10 +292,49 @@ impl Debug for ColorF {
             self.g() * 255.0,
             self.b() * 255.0,
             self.a()
-        )
+        )?;
+        if self.1 != ColorSpace::SRGB {
+            write!(formatter, " in {:?}", self.1)?;
+        }
+        Ok(())
     }
 }
 
+// The sRGB transfer functions, extended to negative values by symmetry so that out-of-gamut
+// colors survive round trips.
+#[inline]
+fn srgb_to_linear(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let linear = if magnitude <= 0.04045 {
+        magnitude / 12.92
+    } else {
+        ((magnitude + 0.055) / 1.055).powf(2.4)
+    };
+    linear.copysign(value)
+}
+
+#[inline]
+fn linear_to_srgb(value: f32) -> f32 {
+    let magnitude = value.abs();
+    let encoded = if magnitude <= 0.0031308 {
+        magnitude * 12.92
+    } else {
+        1.055 * magnitude.powf(1.0 / 2.4) - 0.055
+    };
+    encoded.copysign(value)
+}
+
+#[inline]
+fn map_rgb<F>(rgba: F32x4, f: F) -> F32x4 where F: Fn(f32) -> f32 {
+    F32x4::new(f(rgba[0]), f(rgba[1]), f(rgba[2]), rgba[3])
+}
+
+#[inline]
+fn transform_rgb(rgba: F32x4, matrix: &[[f32; 3]; 3]) -> F32x4 {
+    let row = |row: &[f32; 3]| row[0] * rgba[0] + row[1] * rgba[1] + row[2] * rgba[2];
+    F32x4::new(row(&matrix[0]), row(&matrix[1]), row(&matrix[2]), rgba[3])
+}
+
 #[inline]
 pub fn color_slice_to_u8_slice(slice: &[ColorU]) -> &[u8] {
     unsafe {
@@ -237,6 +350,16 @@ pub fn u8_slice_to_color_slice(slice: &[u8]) -> &[ColorU] {
     }
 }
 
+/// Reinterprets a vector of colors as RGBA8 bytes without copying.
+#[inline]
+pub fn color_vec_to_u8_vec(buffer: Vec<ColorU>) -> Vec<u8> {
+    // `ColorU` is four `u8`s with alignment 1, so the allocation's layout is unchanged.
+    let mut buffer = mem::ManuallyDrop::new(buffer);
+    unsafe {
+        Vec::from_raw_parts(buffer.as_mut_ptr() as *mut u8, buffer.len() * 4, buffer.capacity() * 4)
+    }
+}
+
 // TODO(pcwalton): Do this without a copy?
 #[inline]
 pub fn u8_vec_to_color_vec(buffer: Vec<u8>) -> Vec<ColorU> {
@@ -270,3 +393,43 @@ pub fn rgbf(r: f32, g: f32, b: f32) -> ColorF {
 pub fn rgbaf(r: f32, g: f32, b: f32, a: f32) -> ColorF {
     ColorF::new(r, g, b, a)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ColorF, ColorSpace};
+
+    fn assert_approx_eq(color: ColorF, expected: [f32; 4]) {
+        for (index, &expected) in expected.iter().enumerate() {
+            assert!((color.0[index] - expected).abs() < 0.0005, "{:?} != {:?}", color, expected);
+        }
+    }
+
+    #[test]
+    fn test_srgb_linear_round_trip() {
+        let color = ColorF::new(0.5, 0.04, 1.0, 0.25);
+        let linear = color.to_color_space(ColorSpace::LinearSRGB);
+        assert_eq!(linear.color_space(), ColorSpace::LinearSRGB);
+        assert_approx_eq(linear, [0.2140, 0.0031, 1.0, 0.25]);
+
+        let srgb = linear.to_color_space(ColorSpace::SRGB);
+        assert_eq!(srgb.color_space(), ColorSpace::SRGB);
+        assert_approx_eq(srgb, [0.5, 0.04, 1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_display_p3_round_trip() {
+        // sRGB red is inside the Display P3 gamut.
+        let red = ColorF::new(1.0, 0.0, 0.0, 1.0).to_color_space(ColorSpace::DisplayP3);
+        assert_approx_eq(red, [0.9175, 0.2003, 0.1387, 1.0]);
+        assert_approx_eq(red.to_color_space(ColorSpace::SRGB), [1.0, 0.0, 0.0, 1.0]);
+
+        // Display P3 red isn't inside the sRGB gamut, so it must survive the trip unclipped.
+        let red = ColorF(ColorF::new(1.0, 0.0, 0.0, 0.5).0, ColorSpace::DisplayP3);
+        let srgb = red.to_color_space(ColorSpace::SRGB);
+        assert_approx_eq(srgb, [1.0931, -0.2267, -0.1501, 0.5]);
+        assert_approx_eq(srgb.to_color_space(ColorSpace::DisplayP3), [1.0, 0.0, 0.0, 0.5]);
+        assert_approx_eq(red.to_color_space(ColorSpace::LinearSRGB)
+                            .to_color_space(ColorSpace::DisplayP3),
+                         [1.0, 0.0, 0.0, 0.5]);
+    }
+}
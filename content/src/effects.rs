@@ -12,7 +12,7 @@
 
 use pathfinder_color::ColorF;
 use pathfinder_geometry::line_segment::LineSegment2F;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
 use pathfinder_simd::default::F32x2;
 
 /// This intentionally does not precisely match what Core Graphics does (a
@@ -60,6 +60,8 @@ pub enum PatternFilter {
         /// The background color of the text.
         bg_color: ColorF,
         /// The kernel used for defringing, if subpixel AA is enabled.
+        ///
+        /// Defringing targets the subpixel layout in the build options.
         defringing_kernel: Option<DefringingKernel>,
         /// Whether gamma correction is used when compositing.
         ///
@@ -115,6 +117,19 @@ pub enum BlendMode {
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct DefringingKernel(pub [f32; 4]);
 
+/// The order and orientation of the color subpixels of a display, for subpixel antialiasing.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SubpixelLayout {
+    /// Vertical stripes, red on the left.
+    RGB,
+    /// Vertical stripes, blue on the left.
+    BGR,
+    /// Horizontal stripes, red on top, as on a rotated display.
+    VRGB,
+    /// Horizontal stripes, blue on top, as on a rotated display.
+    VBGR,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum BlurDirection {
     X,
@@ -128,6 +143,43 @@ impl Default for BlendMode {
     }
 }
 
+impl Default for SubpixelLayout {
+    #[inline]
+    fn default() -> SubpixelLayout {
+        SubpixelLayout::RGB
+    }
+}
+
+impl SubpixelLayout {
+    /// Whether the subpixels are stacked vertically rather than side by side.
+    #[inline]
+    pub fn is_vertical(self) -> bool {
+        match self {
+            SubpixelLayout::RGB | SubpixelLayout::BGR => false,
+            SubpixelLayout::VRGB | SubpixelLayout::VBGR => true,
+        }
+    }
+
+    /// Whether the subpixels run from blue to red rather than from red to blue.
+    #[inline]
+    pub fn is_bgr(self) -> bool {
+        match self {
+            SubpixelLayout::RGB | SubpixelLayout::VRGB => false,
+            SubpixelLayout::BGR | SubpixelLayout::VBGR => true,
+        }
+    }
+
+    /// The scale that gives each subpixel its own pixel when rendering.
+    #[inline]
+    pub fn scale(self) -> Vector2F {
+        if self.is_vertical() {
+            vec2f(1.0, 3.0)
+        } else {
+            vec2f(3.0, 1.0)
+        }
+    }
+}
+
 impl Default for Filter {
     #[inline]
     fn default() -> Filter {
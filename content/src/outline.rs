@@ -293,6 +293,18 @@ impl Contour {
         }
     }
 
+    /// Creates a contour from the raw points and point flags returned by `position_of()` and
+    /// `flags_of()`.
+    pub fn from_points_and_flags(points: Vec<Vector2F>, flags: Vec<PointFlags>, closed: bool)
+                                 -> Contour {
+        debug_assert_eq!(points.len(), flags.len());
+        let mut bounds = RectF::default();
+        for (point_index, &point) in points.iter().enumerate() {
+            union_rect(&mut bounds, point, point_index == 0);
+        }
+        Contour { points, flags, bounds, closed }
+    }
+
     #[inline]
     pub fn from_rect(rect: RectF) -> Contour {
         let mut contour = Contour::with_capacity(4);
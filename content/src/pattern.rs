@@ -13,9 +13,11 @@
 use crate::effects::PatternFilter;
 use crate::render_target::RenderTargetId;
 use crate::util;
-use pathfinder_color::{self as color, ColorU};
+use pathfinder_color::{self as color, ColorF, ColorSpace, ColorU};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
+use pathfinder_simd::default::F32x4;
+use half::f16;
 use std::collections::hash_map::DefaultHasher;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
@@ -42,17 +44,39 @@ pub enum PatternSource {
     }
 }
 
-/// RGBA, non-premultiplied.
-// FIXME(pcwalton): Hash the pixel contents so that we don't have to compare every pixel!
-// TODO(pcwalton): Should the pixels be premultiplied?
+/// Raster image data, tightly packed in rows from top to bottom, in one of the formats of
+/// `ImageFormat`.
 #[derive(Clone, PartialEq, Eq)]
 pub struct Image {
     size: Vector2I,
-    pixels: Arc<Vec<ColorU>>,
-    pixels_hash: u64,
+    format: ImageFormat,
+    alpha_mode: AlphaMode,
+    data: Arc<Vec<u8>>,
+    data_hash: u64,
     is_opaque: bool,
 }
 
+/// The layout of the pixels in an `Image`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ImageFormat {
+    /// 8-bit sRGB red, green, blue, and alpha.
+    RGBA8,
+    /// 8-bit sRGB blue, green, red, and alpha, as many video decoders and window systems
+    /// produce.
+    BGRA8,
+    /// 8-bit coverage only. The image is white, with this as its alpha.
+    R8,
+    /// Little-endian half-float red, green, blue, and alpha, in linear sRGB.
+    RGBA16F,
+}
+
+/// Whether the color channels of an `Image` have already been multiplied by alpha.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AlphaMode {
+    Straight,
+    Premultiplied,
+}
+
 bitflags! {
     pub struct PatternFlags: u8 {
         const REPEAT_X      = 0x01;
@@ -152,23 +176,48 @@ impl Pattern {
 }
 
 impl Image {
+    /// Creates an image from straight-alpha RGBA8 pixels.
     #[inline]
     pub fn new(size: Vector2I, pixels: Arc<Vec<ColorU>>) -> Image {
-        assert_eq!(size.x() as usize * size.y() as usize, pixels.len());
-        let is_opaque = pixels.iter().all(|pixel| pixel.is_opaque());
+        let pixels = Arc::try_unwrap(pixels).unwrap_or_else(|pixels| (*pixels).clone());
+        let data = color::color_vec_to_u8_vec(pixels);
+        Image::from_data(size, ImageFormat::RGBA8, AlphaMode::Straight, Arc::new(data))
+    }
 
-        let mut pixels_hasher = DefaultHasher::new();
-        pixels.hash(&mut pixels_hasher);
-        let pixels_hash = pixels_hasher.finish();
+    /// Creates an image from raw pixel data, which is uploaded as is wherever the GPU can
+    /// sample the format directly.
+    pub fn from_data(size: Vector2I,
+                     format: ImageFormat,
+                     alpha_mode: AlphaMode,
+                     data: Arc<Vec<u8>>)
+                     -> Image {
+        assert_eq!(size.x() as usize * size.y() as usize * format.bytes_per_pixel(), data.len());
+        let is_opaque = match format {
+            ImageFormat::RGBA8 | ImageFormat::BGRA8 => {
+                data.chunks_exact(4).all(|pixel| pixel[3] == !0)
+            }
+            ImageFormat::R8 => data.iter().all(|&alpha| alpha == !0),
+            ImageFormat::RGBA16F => {
+                data.chunks_exact(8).all(|pixel| {
+                    f16::from_bits(u16::from_le_bytes([pixel[6], pixel[7]])).to_f32() >= 1.0
+                })
+            }
+        };
 
-        Image { size, pixels, pixels_hash, is_opaque }
+        let mut data_hasher = DefaultHasher::new();
+        data.hash(&mut data_hasher);
+        let data_hash = data_hasher.finish();
+
+        Image { size, format, alpha_mode, data, data_hash, is_opaque }
     }
 
     #[cfg(feature = "pf-image")]
     pub fn from_image_buffer(image_buffer: RgbaImage) -> Image {
         let (width, height) = image_buffer.dimensions();
-        let pixels = color::u8_vec_to_color_vec(image_buffer.into_raw());
-        Image::new(vec2i(width as i32, height as i32), Arc::new(pixels))
+        Image::from_data(vec2i(width as i32, height as i32),
+                         ImageFormat::RGBA8,
+                         AlphaMode::Straight,
+                         Arc::new(image_buffer.into_raw()))
     }
 
     #[inline]
@@ -177,8 +226,53 @@ impl Image {
     }
 
     #[inline]
-    pub fn pixels(&self) -> &Arc<Vec<ColorU>> {
-        &self.pixels
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    #[inline]
+    pub fn data(&self) -> &Arc<Vec<u8>> {
+        &self.data
+    }
+
+    /// Converts the pixels to straight-alpha sRGB RGBA8, for devices that can't sample this
+    /// image's format.
+    pub fn to_rgba8(&self) -> Vec<ColorU> {
+        let premultiplied = self.alpha_mode == AlphaMode::Premultiplied;
+        match self.format {
+            ImageFormat::RGBA8 if !premultiplied => {
+                color::u8_slice_to_color_slice(&self.data).to_vec()
+            }
+            ImageFormat::RGBA8 | ImageFormat::BGRA8 => {
+                let swizzle = self.format == ImageFormat::BGRA8;
+                self.data.chunks_exact(4).map(|pixel| {
+                    let (r, b) = if swizzle { (pixel[2], pixel[0]) } else { (pixel[0], pixel[2]) };
+                    let color = ColorU::new(r, pixel[1], b, pixel[3]);
+                    if premultiplied { unpremultiply(color) } else { color }
+                }).collect()
+            }
+            ImageFormat::R8 => self.data.iter().map(|&a| ColorU::new(!0, !0, !0, a)).collect(),
+            ImageFormat::RGBA16F => {
+                self.data.chunks_exact(8).map(|pixel| {
+                    let channel = |i: usize| {
+                        let bits = u16::from_le_bytes([pixel[i * 2], pixel[i * 2 + 1]]);
+                        f16::from_bits(bits).to_f32()
+                    };
+                    let mut rgba = F32x4::new(channel(0), channel(1), channel(2), channel(3));
+                    rgba = rgba.clamp(F32x4::default(), F32x4::splat(1.0));
+                    let alpha = rgba[3];
+                    if premultiplied && alpha > 0.0 {
+                        rgba = rgba / F32x4::new(alpha, alpha, alpha, 1.0);
+                    }
+                    ColorF(rgba, ColorSpace::LinearSRGB).to_u8()
+                }).collect()
+            }
+        }
     }
 
     #[inline]
@@ -200,17 +294,43 @@ impl PatternSource {
     }
 }
 
+impl ImageFormat {
+    #[inline]
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            ImageFormat::RGBA8 | ImageFormat::BGRA8 => 4,
+            ImageFormat::R8 => 1,
+            ImageFormat::RGBA16F => 8,
+        }
+    }
+}
+
+fn unpremultiply(color: ColorU) -> ColorU {
+    if color.a == 0 || color.a == !0 {
+        return color;
+    }
+    let scale = |c: u8| ((c as u32 * 255 + color.a as u32 / 2) / color.a as u32).min(255) as u8;
+    ColorU::new(scale(color.r), scale(color.g), scale(color.b), color.a)
+}
+
 impl Debug for Image {
     #[inline]
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
-        write!(formatter, "(image {}×{} px)", self.size.x(), self.size.y())
+        write!(formatter,
+               "(image {}×{} px, {:?} {:?})",
+               self.size.x(),
+               self.size.y(),
+               self.format,
+               self.alpha_mode)
     }
 }
 
 impl Hash for Image {
     fn hash<H>(&self, hasher: &mut H) where H: Hasher {
         self.size.hash(hasher);
-        self.pixels_hash.hash(hasher);
+        self.format.hash(hasher);
+        self.alpha_mode.hash(hasher);
+        self.data_hash.hash(hasher);
         self.is_opaque.hash(hasher);
     }
 }
@@ -224,3 +344,47 @@ impl Hash for Pattern {
         self.flags.hash(state);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{AlphaMode, Image, ImageFormat};
+    use pathfinder_color::ColorU;
+    use pathfinder_geometry::vector::vec2i;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_to_rgba8() {
+        let bgra = Image::from_data(vec2i(2, 1),
+                                    ImageFormat::BGRA8,
+                                    AlphaMode::Premultiplied,
+                                    Arc::new(vec![0, 0, 128, 128, 30, 20, 10, 255]));
+        assert_eq!(bgra.to_rgba8(),
+                   vec![ColorU::new(255, 0, 0, 128), ColorU::new(10, 20, 30, 255)]);
+        assert!(!bgra.is_opaque());
+
+        let mask = Image::from_data(vec2i(2, 1), ImageFormat::R8, AlphaMode::Straight,
+                                    Arc::new(vec![0, 255]));
+        assert_eq!(mask.to_rgba8(), vec![ColorU::new(255, 255, 255, 0), ColorU::white()]);
+
+        // Linear 0.5 is sRGB 188.
+        let half = [0x00, 0x38];
+        let one = [0x00, 0x3c];
+        let float = Image::from_data(vec2i(1, 1), ImageFormat::RGBA16F, AlphaMode::Straight,
+                                     Arc::new([half, one, [0, 0], one].concat()));
+        assert_eq!(float.to_rgba8(), vec![ColorU::new(188, 255, 0, 255)]);
+        assert!(float.is_opaque());
+    }
+
+    #[test]
+    fn test_new() {
+        let pixels = vec![ColorU::new(1, 2, 3, 4), ColorU::new(5, 6, 7, 255)];
+        let image = Image::new(vec2i(2, 1), Arc::new(pixels.clone()));
+        assert_eq!(image.format(), ImageFormat::RGBA8);
+        assert_eq!(**image.data(), vec![1, 2, 3, 4, 5, 6, 7, 255]);
+
+        // Pixels that are still shared elsewhere are copied.
+        let shared_pixels = Arc::new(pixels);
+        let image = Image::new(vec2i(2, 1), shared_pixels.clone());
+        assert_eq!(image.to_rgba8(), *shared_pixels);
+    }
+}
@@ -10,7 +10,8 @@
 
 //! GPU rendering code specifically for the demo.
 
-use pathfinder_gpu::{BufferTarget, Device, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::{BufferTarget, Device, DeviceError, VertexAttrClass, VertexAttrDescriptor};
+use pathfinder_gpu::VertexAttrType;
 use pathfinder_resources::ResourceLoader;
 
 pub struct GroundProgram<D>
@@ -28,19 +29,20 @@ impl<D> GroundProgram<D>
 where
     D: Device,
 {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> GroundProgram<D> {
-        let program = device.create_raster_program(resources, "demo_ground");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<GroundProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "demo_ground")?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let gridline_count_uniform = device.get_uniform(&program, "GridlineCount");
         let ground_color_uniform = device.get_uniform(&program, "GroundColor");
         let gridline_color_uniform = device.get_uniform(&program, "GridlineColor");
-        GroundProgram {
+        Ok(GroundProgram {
             program,
             transform_uniform,
             gridline_count_uniform,
             ground_color_uniform,
             gridline_color_uniform,
-        }
+        })
     }
 }
 
@@ -60,8 +62,8 @@ where
         ground_program: &GroundProgram<D>,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
-    ) -> GroundVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+    ) -> Result<GroundVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let position_attr = device.get_vertex_attr(&ground_program.program, "Position").unwrap();
 
@@ -77,6 +79,6 @@ where
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        GroundVertexArray { vertex_array }
+        Ok(GroundVertexArray { vertex_array })
     }
 }
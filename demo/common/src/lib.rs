@@ -25,6 +25,7 @@ use clap::{App, Arg};
 use pathfinder_content::effects::DEFRINGING_KERNEL_CORE_GRAPHICS;
 use pathfinder_content::effects::PatternFilter;
 use pathfinder_content::effects::STEM_DARKENING_FACTORS;
+use pathfinder_content::effects::SubpixelLayout;
 use pathfinder_content::outline::Outline;
 use pathfinder_content::pattern::Pattern;
 use pathfinder_content::render_target::RenderTargetId;
@@ -38,7 +39,8 @@ use pathfinder_renderer::concurrent::scene_proxy::SceneProxy;
 use pathfinder_renderer::gpu::options::{DestFramebuffer, RendererLevel};
 use pathfinder_renderer::gpu::options::{RendererMode, RendererOptions};
 use pathfinder_renderer::gpu::renderer::{DebugUIPresenterInfo, Renderer};
-use pathfinder_renderer::options::{BuildOptions, RenderTransform};
+use pathfinder_renderer::options::{AntialiasingMode, BuildOptions, DEFAULT_FLATTENING_TOLERANCE};
+use pathfinder_renderer::options::RenderTransform;
 use pathfinder_renderer::paint::Paint;
 use pathfinder_renderer::scene::{DrawPath, RenderTarget, Scene};
 use pathfinder_resources::ResourceLoader;
@@ -73,6 +75,9 @@ const APPROX_FONT_SIZE: f32 = 16.0;
 
 const MESSAGE_TIMEOUT_SECS: u64 = 5;
 
+// The subpixel arrangement that subpixel AA renders and defringes for.
+const SUBPIXEL_LAYOUT: SubpixelLayout = SubpixelLayout::RGB;
+
 pub mod window;
 
 mod camera;
@@ -126,7 +131,8 @@ impl<W> DemoApp<W> where W: Window {
         }
         #[cfg(any(not(target_os = "macos"), feature = "pf-gl"))]
         {
-            device = DeviceImpl::new(window.gl_version(), window.gl_default_framebuffer());
+            device = DeviceImpl::new(window.gl_version(), window.gl_default_framebuffer())
+                .expect("Failed to create the device!");
         }
 
         let resources = window.resource_loader();
@@ -150,6 +156,7 @@ impl<W> DemoApp<W> where W: Window {
             dest: dest_framebuffer,
             background_color: None,
             show_debug_ui: true,
+            ..RendererOptions::default()
         };
 
         let filter = build_filter(&ui_model);
@@ -161,7 +168,8 @@ impl<W> DemoApp<W> where W: Window {
 
         let message = get_svg_building_message(&built_svg);
 
-        let renderer = Renderer::new(device, resources, render_mode, render_options);
+        let renderer = Renderer::new(device, resources, render_mode, render_options)
+            .expect("Failed to create the renderer!");
 
         let scene_metadata = SceneMetadata::new_clipping_view_box(&mut built_svg.scene,
                                                                   viewport.size());
@@ -169,11 +177,13 @@ impl<W> DemoApp<W> where W: Window {
 
         let scene_proxy = SceneProxy::from_scene(built_svg.scene, level, executor);
 
-        let ground_program = GroundProgram::new(renderer.device(), resources);
+        let ground_program = GroundProgram::new(renderer.device(), resources)
+            .expect("Failed to create the ground program!");
         let ground_vertex_array = GroundVertexArray::new(renderer.device(),
                                                          &ground_program,
                                                          &renderer.quad_vertex_positions_buffer(),
-                                                         &renderer.quad_vertex_indices_buffer());
+                                                         &renderer.quad_vertex_indices_buffer())
+            .expect("Failed to create the ground vertex array!");
 
         let mut message_epoch = 0;
         emit_message::<W>(
@@ -183,7 +193,8 @@ impl<W> DemoApp<W> where W: Window {
             message,
         );
 
-        let ui_presenter = DemoUIPresenter::new(renderer.device(), resources);
+        let ui_presenter = DemoUIPresenter::new(renderer.device(), resources)
+            .expect("Failed to create the demo UI!");
 
         DemoApp {
             window,
@@ -268,6 +279,11 @@ impl<W> DemoApp<W> where W: Window {
                 Vector2F::zero()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
+            subpixel_layout: SUBPIXEL_LAYOUT,
+            damage_rects: vec![],
+            flattening_tolerance: DEFAULT_FLATTENING_TOLERANCE,
+            antialiasing_mode: AntialiasingMode::Analytic,
+            profiler: None,
         };
 
         self.scene_proxy.build(build_options);
@@ -782,7 +798,9 @@ fn build_svg_tree(tree: &Tree, viewport_size: Vector2I, filter: Option<PatternFi
     let mut scene = Scene::new();
     let filter_info = filter.map(|filter| {
         let scale = match filter {
-            PatternFilter::Text { defringing_kernel: Some(_), .. } => vec2i(3, 1),
+            PatternFilter::Text { defringing_kernel: Some(_), .. } => {
+                SUBPIXEL_LAYOUT.scale().to_i32()
+            }
             _ => vec2i(1, 1),
         };
         let name = "Text".to_owned();
@@ -819,12 +837,16 @@ fn center_of_window(window_size: &WindowSize) -> Vector2F {
 }
 
 fn get_svg_building_message(built_svg: &SVGScene) -> String {
-    if built_svg.result_flags.is_empty() {
+    if built_svg.diagnostics.is_empty() {
         return String::new();
     }
+    for diagnostic in &built_svg.diagnostics {
+        warn!("{}", diagnostic);
+    }
     format!(
-        "Warning: These features in the SVG are unsupported: {}.",
-        built_svg.result_flags
+        "Warning: These features in the SVG are unsupported: {} ({} element(s); see the log).",
+        built_svg.result_flags,
+        built_svg.diagnostics.len()
     )
 }
 
@@ -905,6 +927,6 @@ fn build_filter(ui_model: &DemoUIModel) -> Option<PatternFilter> {
             Some(DEFRINGING_KERNEL_CORE_GRAPHICS)
         } else {
             None
-        }
+        },
     })
 }
@@ -70,14 +70,19 @@ impl<W> DemoApp<W> where W: Window {
                     let scene_texture = self
                         .renderer
                         .device()
-                        .create_texture(TextureFormat::RGBA8, viewport.size());
-                    self.scene_framebuffer =
-                        Some(self.renderer.device().create_framebuffer(scene_texture));
+                        .create_texture(TextureFormat::RGBA8, viewport.size())
+                        .expect("Failed to create the VR scene texture!");
+                    let scene_framebuffer = self.renderer
+                                                .device()
+                                                .create_framebuffer(scene_texture)
+                                                .expect("Failed to create the VR framebuffer!");
+                    self.scene_framebuffer = Some(scene_framebuffer);
                 }
                 *self.renderer.options_mut() = RendererOptions {
                     dest: DestFramebuffer::Other(self.scene_framebuffer.take().unwrap()),
                     background_color: clear_color,
                     show_debug_ui: self.options.ui != UIVisibility::None,
+                    ..RendererOptions::default()
                 };
                 2
             }
@@ -89,6 +94,7 @@ impl<W> DemoApp<W> where W: Window {
                     },
                     background_color: clear_color,
                     show_debug_ui: self.options.ui != UIVisibility::None,
+                    ..RendererOptions::default()
                 };
                 1
             }
@@ -263,7 +269,7 @@ impl<W> DemoApp<W> where W: Window {
         }
 
         // Issue render commands!
-        self.scene_proxy.render(&mut self.renderer);
+        self.scene_proxy.render(&mut self.renderer).expect("Failed to render the scene!");
     }
 
     pub fn take_raster_screenshot(&mut self, path: PathBuf) {
@@ -15,7 +15,7 @@ use pathfinder_color::ColorU;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_gpu::allocator::GPUMemoryAllocator;
-use pathfinder_gpu::{Device, TextureFormat};
+use pathfinder_gpu::{Device, DeviceError, TextureFormat};
 use pathfinder_renderer::gpu::debug::DebugUIPresenter;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_ui::{BUTTON_HEIGHT, BUTTON_TEXT_OFFSET, BUTTON_WIDTH, FONT_ASCENT, PADDING};
@@ -115,37 +115,38 @@ pub struct DemoUIPresenter<D> where D: Device {
 }
 
 impl<D> DemoUIPresenter<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> DemoUIPresenter<D> {
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<DemoUIPresenter<D>, DeviceError> {
         device.begin_commands();
 
         let effects_texture = device.create_texture_from_png(resources,
                                                              EFFECTS_PNG_NAME,
-                                                             TextureFormat::R8);
+                                                             TextureFormat::R8)?;
         let open_texture = device.create_texture_from_png(resources,
                                                           OPEN_PNG_NAME,
-                                                          TextureFormat::R8);
+                                                          TextureFormat::R8)?;
         let rotate_texture = device.create_texture_from_png(resources,
                                                             ROTATE_PNG_NAME,
-                                                            TextureFormat::R8);
+                                                            TextureFormat::R8)?;
         let zoom_in_texture = device.create_texture_from_png(resources,
                                                              ZOOM_IN_PNG_NAME,
-                                                             TextureFormat::R8);
+                                                             TextureFormat::R8)?;
         let zoom_actual_size_texture = device.create_texture_from_png(resources,
                                                                       ZOOM_ACTUAL_SIZE_PNG_NAME,
-                                                                      TextureFormat::R8);
+                                                                      TextureFormat::R8)?;
         let zoom_out_texture = device.create_texture_from_png(resources,
                                                               ZOOM_OUT_PNG_NAME,
-                                                              TextureFormat::R8);
+                                                              TextureFormat::R8)?;
         let background_texture = device.create_texture_from_png(resources,
                                                                 BACKGROUND_PNG_NAME,
-                                                                TextureFormat::R8);
+                                                                TextureFormat::R8)?;
         let screenshot_texture = device.create_texture_from_png(resources,
                                                                 SCREENSHOT_PNG_NAME,
-                                                                TextureFormat::R8);
+                                                                TextureFormat::R8)?;
 
         device.end_commands();
 
-        DemoUIPresenter {
+        Ok(DemoUIPresenter {
             effects_texture,
             open_texture,
             rotate_texture,
@@ -159,7 +160,7 @@ impl<D> DemoUIPresenter<D> where D: Device {
             background_panel_visible: false,
             screenshot_panel_visible: false,
             rotate_panel_visible: false,
-        }
+        })
     }
 
     pub fn update<W>(&mut self,
@@ -228,7 +228,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
         };
 
         let scene_proxy = SceneProxy::from_scene(svg.scene.clone(), SequentialExecutor);
-        scene_proxy.build_and_render(renderer, render_options);
+        scene_proxy.build_and_render(renderer, render_options).unwrap();
     }
 }
 
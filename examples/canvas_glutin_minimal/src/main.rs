@@ -48,14 +48,14 @@ fn main() {
     gl::load_with(|name| gl_context.get_proc_address(name) as *const _);
 
     // Create a Pathfinder renderer.
-    let device = GLDevice::new(GLVersion::GL3, 0);
+    let device = GLDevice::new(GLVersion::GL3, 0).unwrap();
     let mode = RendererMode::default_for_device(&device);
     let options = RendererOptions {
         background_color: Some(ColorF::white()),
         dest: DestFramebuffer::full_window(window_size),
         ..RendererOptions::default()
     };
-    let mut renderer = Renderer::new(device, &EmbeddedResourceLoader, mode, options);
+    let mut renderer = Renderer::new(device, &EmbeddedResourceLoader, mode, options).unwrap();
 
     // Make a canvas. We're going to draw a house.
     let font_context = CanvasFontContext::from_system_source();
@@ -82,7 +82,7 @@ fn main() {
     let mut scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(),
                                            renderer.mode().level,
                                            RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     gl_context.swap_buffers().unwrap();
 
     // Wait for a keypress.
@@ -53,7 +53,7 @@ fn main() {
         background_color: Some(ColorF::white()),
         ..RendererOptions::default()
     };
-    let mut renderer = Renderer::new(device, &EmbeddedResourceLoader, mode, options);
+    let mut renderer = Renderer::new(device, &EmbeddedResourceLoader, mode, options).unwrap();
 
     // Make a canvas. We're going to draw a house.
     let canvas = Canvas::new(window_size.to_f32());
@@ -80,7 +80,7 @@ fn main() {
     let mut scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(),
                                            renderer.mode().level,
                                            RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     renderer.device().present_drawable();
 
     // Wait for a keypress.
@@ -69,7 +69,7 @@ fn main() {
                                     .unwrap()
                                     .unwrap()
                                     .framebuffer_object;
-    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
+    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer).unwrap();
 
     // Create a Pathfinder renderer.
     let mode = RendererMode::default_for_device(&pathfinder_device);
@@ -79,7 +79,7 @@ fn main() {
         ..RendererOptions::default()
     };
     let resource_loader = EmbeddedResourceLoader::new();
-    let mut renderer = Renderer::new(pathfinder_device, &resource_loader, mode, options);
+    let mut renderer = Renderer::new(pathfinder_device, &resource_loader, mode, options).unwrap();
 
     // Make a canvas. We're going to draw a house.
     let font_context = CanvasFontContext::from_system_source();
@@ -106,7 +106,7 @@ fn main() {
     let mut scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(),
                                            renderer.mode().level,
                                            RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
 
     // Present the surface.
     let mut surface = device.unbind_surface_from_context(&mut context).unwrap().unwrap();
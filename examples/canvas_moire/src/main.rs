@@ -82,7 +82,7 @@ fn main() {
                                     .unwrap()
                                     .unwrap()
                                     .framebuffer_object;
-    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
+    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer).unwrap();
 
     // Create our renderers.
     let mode = RendererMode::default_for_device(&pathfinder_device);
@@ -91,7 +91,8 @@ fn main() {
         dest: DestFramebuffer::full_window(framebuffer_size),
         ..RendererOptions::default()
     };
-    let renderer = Renderer::new(pathfinder_device, &EmbeddedResourceLoader, mode, options);
+    let renderer = Renderer::new(pathfinder_device, &EmbeddedResourceLoader, mode, options)
+        .unwrap();
     let window_size = vec2i(window_size.width, window_size.height);
     let mut moire_renderer = MoireRenderer::new(renderer, window_size, framebuffer_size);
 
@@ -171,7 +172,7 @@ impl MoireRenderer {
 
         // Build and render scene.
         self.scene.replace_scene(canvas.into_canvas().into_scene());
-        self.scene.build_and_render(&mut self.renderer, BuildOptions::default());
+        self.scene.build_and_render(&mut self.renderer, BuildOptions::default()).unwrap();
 
         self.frame += 1;
     }
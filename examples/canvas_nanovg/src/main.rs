@@ -1514,7 +1514,7 @@ fn main() {
                                     .unwrap()
                                     .unwrap()
                                     .framebuffer_object;
-    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer);
+    let pathfinder_device = GLDevice::new(GLVersion::GL3, default_framebuffer).unwrap();
 
     // Create a Pathfinder renderer.
     let renderer_options = RendererOptions {
@@ -1524,7 +1524,7 @@ fn main() {
     let mut renderer = Renderer::new(pathfinder_device,
                                      &resources,
                                      DestFramebuffer::full_window(framebuffer_size),
-                                     renderer_options);
+                                     renderer_options).unwrap();
 
     // Initialize font state.
     let font_source = Arc::new(MemSource::from_fonts(font_data.into_iter()).unwrap());
@@ -1570,7 +1570,7 @@ fn main() {
         let mut scene = SceneProxy::from_scene(canvas.into_scene(),
                                                renderer.level(),
                                                RayonExecutor);
-        scene.build_and_render(&mut renderer, BuildOptions::default());
+        scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
 
         // Present the rendered canvas via `surfman`.
         let mut surface = device.unbind_surface_from_context(&mut gl_context).unwrap().unwrap();
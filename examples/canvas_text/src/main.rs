@@ -50,14 +50,14 @@ fn main() {
 
     // Create a Pathfinder renderer.
     let resource_loader = FilesystemResourceLoader::locate();
-    let device = GLDevice::new(GLVersion::GL3, 0);
+    let device = GLDevice::new(GLVersion::GL3, 0).unwrap();
     let mode = RendererMode::default_for_device(&device);
     let options = RendererOptions {
         background_color: Some(ColorF::white()),
         dest: DestFramebuffer::full_window(window_size),
         ..RendererOptions::default()
     };
-    let mut renderer = Renderer::new(device, &resource_loader, mode, options);
+    let mut renderer = Renderer::new(device, &resource_loader, mode, options).unwrap();
 
     // Load a font.
     let font_data = Arc::new(resource_loader.slurp("fonts/Overpass-Regular.otf").unwrap());
@@ -78,7 +78,7 @@ fn main() {
     let mut scene = SceneProxy::from_scene(canvas.into_canvas().into_scene(),
                                            renderer.mode().level,
                                            RayonExecutor);
-    scene.build_and_render(&mut renderer, BuildOptions::default());
+    scene.build_and_render(&mut renderer, BuildOptions::default()).unwrap();
     window.gl_swap_window();
 
     // Wait for a keypress.
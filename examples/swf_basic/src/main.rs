@@ -98,14 +98,14 @@ fn main() {
     window.gl_make_current(&gl_context).unwrap();
 
     // Create a Pathfinder renderer.
-    let device = GLDevice::new(GLVersion::GL3, 0);
+    let device = GLDevice::new(GLVersion::GL3, 0).unwrap();
     let mode = RendererMode::default_for_device(&device);
     let options = RendererOptions {
         background_color: Some(stage.background_color()),
         dest: DestFramebuffer::full_window(pixel_size),
         ..RendererOptions::default()
     };
-    let mut renderer = Renderer::new(device, &resource_loader, mode, options);
+    let mut renderer = Renderer::new(device, &resource_loader, mode, options).unwrap();
 
     // Clear to swf stage background color.
     let mut scene = Scene::new();
@@ -119,7 +119,7 @@ fn main() {
     let mut build_options = BuildOptions::default();
     let scale_transform = Transform2F::from_scale(device_pixel_ratio);
     build_options.transform = RenderTransform::Transform2D(scale_transform);
-    scene.build_and_render(&mut renderer, build_options);
+    scene.build_and_render(&mut renderer, build_options).unwrap();
 
     window.gl_swap_window();
     // Wait for a keypress.
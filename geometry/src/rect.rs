@@ -377,6 +377,14 @@ impl RectI {
         self.0[3]
     }
 
+    #[inline]
+    pub fn union_rect(self, other: RectI) -> RectI {
+        RectI::from_points(
+            self.origin().min(other.origin()),
+            self.lower_right().max(other.lower_right()),
+        )
+    }
+
     #[inline]
     pub fn intersects(self, other: RectI) -> bool {
         // self.origin < other.lower_right && other.origin < self.lower_right
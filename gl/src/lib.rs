@@ -19,14 +19,15 @@ use half::f16;
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceError};
+use pathfinder_gpu::FeatureLevel;
 use pathfinder_gpu::{ImageAccess, ImageBinding, Primitive, ProgramKind, RenderOptions};
 use pathfinder_gpu::{RenderState, RenderTarget, ShaderKind, StencilFunc, TextureBinding};
 use pathfinder_gpu::{TextureData, TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData};
 use pathfinder_gpu::{VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x4;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ffi::{CStr, CString};
 use std::mem;
 use std::ops::Range;
@@ -42,25 +43,37 @@ pub struct GLDevice {
     version: GLVersion,
     default_framebuffer: GLuint,
     dummy_texture: GLTexture,
+    // Set once a reset is detected, since the driver only reports it once.
+    device_lost: Cell<bool>,
 }
 
 impl GLDevice {
     #[inline]
-    pub fn new(version: GLVersion, default_framebuffer: GLuint) -> GLDevice {
+    pub fn new(version: GLVersion, default_framebuffer: GLuint)
+               -> Result<GLDevice, DeviceError> {
         let dummy_texture = GLTexture {
             gl_texture: 0,
             size: Vector2I::zero(),
             format: TextureFormat::RGBA8,
         };
 
-        let mut device = GLDevice { version, default_framebuffer, dummy_texture };
+        let mut device = GLDevice {
+            version,
+            default_framebuffer,
+            dummy_texture,
+            device_lost: Cell::new(false),
+        };
+        if device.is_device_lost() {
+            return Err(DeviceError::DeviceLost);
+        }
+
         let dummy_texture_data =
             [0; DUMMY_TEXTURE_LENGTH as usize * DUMMY_TEXTURE_LENGTH as usize * 4];
         device.dummy_texture =
             device.create_texture_from_data(TextureFormat::RGBA8,
                                             Vector2I::splat(DUMMY_TEXTURE_LENGTH),
-                                            TextureDataRef::U8(&dummy_texture_data));
-        device
+                                            TextureDataRef::U8(&dummy_texture_data))?;
+        Ok(device)
     }
 
     pub fn set_default_framebuffer(&mut self, framebuffer: GLuint) {
@@ -73,6 +86,22 @@ impl GLDevice {
         unsafe {
             let (origin, size) = (render_state.viewport.origin(), render_state.viewport.size());
             gl::Viewport(origin.x(), origin.y(), size.x(), size.y());
+
+            // Set the scissor before clearing so that clears are restricted too.
+            match render_state.options.scissor {
+                None => {
+                    gl::Disable(gl::SCISSOR_TEST); ck();
+                }
+                Some(scissor) => {
+                    // OpenGL window coordinates have the origin at the lower left.
+                    let bottom = origin.y() + size.y() - scissor.max_y();
+                    gl::Scissor(origin.x() + scissor.origin_x(),
+                                bottom,
+                                scissor.width(),
+                                scissor.height()); ck();
+                    gl::Enable(gl::SCISSOR_TEST); ck();
+                }
+            }
         }
 
         if render_state.options.clear_ops.has_ops() {
@@ -314,6 +343,10 @@ impl GLDevice {
                 gl::Disable(gl::STENCIL_TEST); ck();
             }
 
+            if render_options.scissor.is_some() {
+                gl::Disable(gl::SCISSOR_TEST); ck();
+            }
+
             gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE); ck();
         }
     }
@@ -356,7 +389,26 @@ impl Device for GLDevice {
         }
     }
 
-    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> GLTexture {
+    fn supports_texture_format(&self, format: TextureFormat) -> bool {
+        // OpenGL ES only has BGRA uploads as an extension.
+        match (self.version, format) {
+            (GLVersion::GLES3, TextureFormat::BGRA8) => false,
+            _ => true,
+        }
+    }
+
+    fn is_device_lost(&self) -> bool {
+        // Without robustness support there's no way to tell, so assume the context is fine.
+        if !self.device_lost.get() && gl::GetGraphicsResetStatus::is_loaded() {
+            unsafe {
+                self.device_lost.set(gl::GetGraphicsResetStatus() != gl::NO_ERROR);
+            }
+        }
+        self.device_lost.get()
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I)
+                      -> Result<GLTexture, DeviceError> {
         let mut texture = GLTexture { gl_texture: 0, size, format };
         unsafe {
             gl::GenTextures(1, &mut texture.gl_texture); ck();
@@ -369,15 +421,18 @@ impl Device for GLDevice {
                            0,
                            format.gl_format(),
                            format.gl_type(),
-                           ptr::null()); ck();
+                           ptr::null());
         }
+        check_allocation(texture.gl_texture, |gl_texture| unsafe {
+            gl::DeleteTextures(1, &gl_texture);
+        })?;
 
         self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
-        texture
+        Ok(texture)
     }
 
     fn create_texture_from_data(&self, format: TextureFormat, size: Vector2I, data: TextureDataRef)
-                                -> GLTexture {
+                                -> Result<GLTexture, DeviceError> {
         let data_ptr = data.check_and_extract_data_ptr(size, format);
         let mut texture = GLTexture { gl_texture: 0, size, format: TextureFormat::R8 };
         unsafe {
@@ -393,12 +448,16 @@ impl Device for GLDevice {
                            format.gl_type(),
                            data_ptr)
         }
+        check_allocation(texture.gl_texture, |gl_texture| unsafe {
+            gl::DeleteTextures(1, &gl_texture);
+        })?;
 
         self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
-        texture
+        Ok(texture)
     }
 
-    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind) -> GLShader {
+    fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind)
+                                 -> Result<GLShader, DeviceError> {
         // FIXME(pcwalton): Do this once and cache it.
         let glsl_version_spec = self.version.to_glsl_version_spec();
 
@@ -430,11 +489,13 @@ impl Device for GLDevice {
                                      info_log.len() as GLint,
                                      ptr::null_mut(),
                                      info_log.as_mut_ptr() as *mut GLchar); ck();
-                error!("Shader info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("{:?} shader '{}' compilation failed", kind, name);
+                gl::DeleteShader(gl_shader); ck();
+                let log = String::from_utf8_lossy(&info_log).trim_end_matches('\0').to_owned();
+                error!("{:?} shader '{}' compilation failed:\n{}", kind, name, log);
+                return Err(DeviceError::ShaderCompilationFailed { name: name.to_owned(), log });
             }
 
-            GLShader { gl_shader }
+            Ok(GLShader { gl_shader })
         }
     }
 
@@ -442,7 +503,7 @@ impl Device for GLDevice {
                                    _resources: &dyn ResourceLoader,
                                    name: &str,
                                    shaders: ProgramKind<GLShader>)
-                                   -> GLProgram {
+                                   -> Result<GLProgram, DeviceError> {
         let gl_program;
         unsafe {
             gl_program = gl::CreateProgram(); ck();
@@ -470,14 +531,16 @@ impl Device for GLDevice {
                                       info_log.len() as GLint,
                                       ptr::null_mut(),
                                       info_log.as_mut_ptr() as *mut GLchar); ck();
-                eprintln!("Program info log:\n{}", String::from_utf8_lossy(&info_log));
-                panic!("Program '{}' linking failed", name);
+                gl::DeleteProgram(gl_program); ck();
+                let log = String::from_utf8_lossy(&info_log).trim_end_matches('\0').to_owned();
+                error!("Program '{}' linking failed:\n{}", name, log);
+                return Err(DeviceError::ProgramLinkingFailed { name: name.to_owned(), log });
             }
         }
 
         let parameters = GLProgramParameters { textures: vec![], images: vec![] };
 
-        GLProgram { gl_program, shaders, parameters: RefCell::new(parameters) }
+        Ok(GLProgram { gl_program, shaders, parameters: RefCell::new(parameters) })
     }
 
     #[inline]
@@ -486,11 +549,11 @@ impl Device for GLDevice {
     }
 
     #[inline]
-    fn create_vertex_array(&self) -> GLVertexArray {
+    fn create_vertex_array(&self) -> Result<GLVertexArray, DeviceError> {
         unsafe {
             let mut array = GLVertexArray { gl_vertex_array: 0 };
             gl::GenVertexArrays(1, &mut array.gl_vertex_array); ck();
-            array
+            Ok(array)
         }
     }
 
@@ -586,8 +649,9 @@ impl Device for GLDevice {
         self.unbind_vertex_array();
     }
 
-    fn create_framebuffer(&self, texture: GLTexture) -> GLFramebuffer {
+    fn create_framebuffer(&self, texture: GLTexture) -> Result<GLFramebuffer, DeviceError> {
         let mut gl_framebuffer = 0;
+        let status;
         unsafe {
             gl::GenFramebuffers(1, &mut gl_framebuffer); ck();
             gl::BindFramebuffer(gl::FRAMEBUFFER, gl_framebuffer); ck();
@@ -597,18 +661,42 @@ impl Device for GLDevice {
                                      gl::TEXTURE_2D,
                                      texture.gl_texture,
                                      0); ck();
-            assert_eq!(gl::CheckFramebufferStatus(gl::FRAMEBUFFER), gl::FRAMEBUFFER_COMPLETE);
+            status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER); ck();
+        }
+
+        let framebuffer = GLFramebuffer { gl_framebuffer, texture };
+
+        // A lost context reports every framebuffer as incomplete. The framebuffer is returned
+        // anyway, so that the renderer can limp along until the loss is noticed.
+        if status != gl::FRAMEBUFFER_COMPLETE && !self.is_device_lost() {
+            let reason = match status {
+                gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => "FRAMEBUFFER_INCOMPLETE_ATTACHMENT",
+                gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
+                    "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT"
+                }
+                gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => "FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER",
+                gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => "FRAMEBUFFER_INCOMPLETE_READ_BUFFER",
+                gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => "FRAMEBUFFER_INCOMPLETE_MULTISAMPLE",
+                gl::FRAMEBUFFER_UNSUPPORTED => "FRAMEBUFFER_UNSUPPORTED",
+                gl::FRAMEBUFFER_UNDEFINED => "FRAMEBUFFER_UNDEFINED",
+                _ => "unknown status",
+            };
+            unsafe {
+                gl::DeleteFramebuffers(1, &framebuffer.gl_framebuffer); ck();
+                gl::DeleteTextures(1, &framebuffer.texture.gl_texture); ck();
+            }
+            return Err(DeviceError::FramebufferIncomplete(format!("0x{:x} ({})", status, reason)));
         }
 
-        GLFramebuffer { gl_framebuffer, texture }
+        Ok(framebuffer)
     }
 
-    fn create_buffer(&self, mode: BufferUploadMode) -> GLBuffer {
+    fn create_buffer(&self, mode: BufferUploadMode) -> Result<GLBuffer, DeviceError> {
         unsafe {
             let mut gl_buffer = 0;
             gl::GenBuffers(1, &mut gl_buffer); ck();
             let object = Rc::new(GLBufferObject { gl_buffer });
-            GLBuffer { object, mode }
+            Ok(GLBuffer { object, mode })
         }
     }
 
@@ -772,10 +860,10 @@ impl Device for GLDevice {
     }
 
     fn read_buffer(&self, buffer: &GLBuffer, target: BufferTarget, range: Range<usize>)
-                   -> GLBufferDataReceiver {
+                   -> Result<GLBufferDataReceiver, DeviceError> {
         unsafe {
             let gl_sync = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
-            GLBufferDataReceiver { object: buffer.object.clone(), gl_sync, range, target }
+            Ok(GLBufferDataReceiver { object: buffer.object.clone(), gl_sync, range, target })
         }
     }
 
@@ -833,12 +921,12 @@ impl Device for GLDevice {
     }
 
     #[inline]
-    fn create_timer_query(&self) -> GLTimerQuery {
+    fn create_timer_query(&self) -> Result<GLTimerQuery, DeviceError> {
         let mut query = GLTimerQuery { gl_query: 0 };
         unsafe {
             gl::GenQueries(1, &mut query.gl_query); ck();
         }
-        query
+        Ok(query)
     }
 
     #[inline]
@@ -932,10 +1020,10 @@ impl Device for GLDevice {
 
     #[inline]
     fn create_shader(&self, resources: &dyn ResourceLoader, name: &str, kind: ShaderKind)
-                     -> Self::Shader {
+                     -> Result<Self::Shader, DeviceError> {
         match (self.version, kind) {
             (GLVersion::GL3, ShaderKind::Compute) | (GLVersion::GLES3, ShaderKind::Compute) => {
-                panic!("Compute shaders are not supported on OpenGL versions prior to 4!")
+                return Err(DeviceError::Unsupported("compute shaders before OpenGL 4"));
             }
             (GLVersion::GL3, ShaderKind::Vertex) |
             (GLVersion::GL3, ShaderKind::Fragment) |
@@ -953,7 +1041,8 @@ impl Device for GLDevice {
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/{}/{}.{}s.glsl", directory, name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = resources.slurp(&path).map_err(|_| DeviceError::MissingResource(path))?;
+        self.create_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> Self::Fence {
@@ -1107,7 +1196,7 @@ impl GLDevice {
             let channels = format.channels();
             let (mut texture_data, texture_data_ptr, texture_data_len);
             match format {
-                TextureFormat::R8 | TextureFormat::RGBA8 => {
+                TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => {
                     let mut pixels: Vec<u8> =
                         vec![0; size.x() as usize * size.y() as usize * channels];
                     texture_data_ptr = pixels.as_mut_ptr();
@@ -1468,7 +1557,7 @@ impl TextureFormatExt for TextureFormat {
         match self {
             TextureFormat::R8 => gl::R8 as GLint,
             TextureFormat::R16F => gl::R16F as GLint,
-            TextureFormat::RGBA8 => gl::RGBA8 as GLint,
+            TextureFormat::RGBA8 | TextureFormat::BGRA8 => gl::RGBA8 as GLint,
             TextureFormat::RGBA16F => gl::RGBA16F as GLint,
             TextureFormat::RGBA32F => gl::RGBA32F as GLint,
         }
@@ -1478,12 +1567,13 @@ impl TextureFormatExt for TextureFormat {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => gl::RED,
             TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => gl::RGBA,
+            TextureFormat::BGRA8 => gl::BGRA,
         }
     }
 
     fn gl_type(self) -> GLuint {
         match self {
-            TextureFormat::R8 | TextureFormat::RGBA8 => gl::UNSIGNED_BYTE,
+            TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => gl::UNSIGNED_BYTE,
             TextureFormat::R16F | TextureFormat::RGBA16F => gl::HALF_FLOAT,
             TextureFormat::RGBA32F => gl::FLOAT,
         }
@@ -1554,23 +1644,30 @@ impl GLVersion {
 
 // Error checking
 
+// Checks whether the storage for a new object was allocated, deleting the object if it wasn't.
+fn check_allocation<F>(object: GLuint, delete: F) -> Result<(), DeviceError>
+                       where F: FnOnce(GLuint) {
+    let error = match unsafe { gl::GetError() } {
+        gl::NO_ERROR => return Ok(()),
+        gl::OUT_OF_MEMORY => DeviceError::OutOfMemory,
+        gl::CONTEXT_LOST => DeviceError::DeviceLost,
+        err => DeviceError::Other(format!("GL error: 0x{:x} ({})", err, error_name(err))),
+    };
+    delete(object);
+    Err(error)
+}
+
 #[cfg(debug_assertions)]
 fn ck() {
     unsafe {
         // Note that ideally we should be calling gl::GetError() in a loop until it
         // returns gl::NO_ERROR, but for now we'll just report the first one we find.
+        //
+        // Running out of memory and losing the context aren't bugs, so they're left to resource
+        // creation and `is_device_lost()` to report.
         let err = gl::GetError();
-        if err != gl::NO_ERROR {
-            panic!("GL error: 0x{:x} ({})", err, match err {
-                gl::INVALID_ENUM => "INVALID_ENUM",
-                gl::INVALID_VALUE => "INVALID_VALUE",
-                gl::INVALID_OPERATION => "INVALID_OPERATION",
-                gl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
-                gl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
-                gl::STACK_UNDERFLOW => "STACK_UNDERFLOW",
-                gl::STACK_OVERFLOW => "STACK_OVERFLOW",
-                _ => "Unknown"
-            });
+        if err != gl::NO_ERROR && err != gl::OUT_OF_MEMORY && err != gl::CONTEXT_LOST {
+            report_error(err);
         }
     }
 }
@@ -1578,6 +1675,25 @@ fn ck() {
 #[cfg(not(debug_assertions))]
 fn ck() {}
 
+#[cfg(debug_assertions)]
+fn report_error(err: GLenum) {
+    panic!("GL error: 0x{:x} ({})", err, error_name(err));
+}
+
+fn error_name(err: GLenum) -> &'static str {
+    match err {
+        gl::INVALID_ENUM => "INVALID_ENUM",
+        gl::INVALID_VALUE => "INVALID_VALUE",
+        gl::INVALID_OPERATION => "INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
+        gl::CONTEXT_LOST => "CONTEXT_LOST",
+        gl::STACK_UNDERFLOW => "STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "STACK_OVERFLOW",
+        _ => "Unknown"
+    }
+}
+
 // Utilities
 
 // Flips a buffer of image data upside-down.
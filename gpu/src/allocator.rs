@@ -10,28 +10,29 @@
 
 //! GPU memory management.
 
-use crate::{BufferData, BufferTarget, BufferUploadMode, Device, TextureFormat};
+use crate::{BufferData, BufferTarget, BufferUploadMode, Device, DeviceError, TextureFormat};
 use fxhash::FxHashMap;
 use pathfinder_geometry::vector::Vector2I;
 use std::collections::VecDeque;
 use std::default::Default;
 use std::mem;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 // Everything above 16 MB is allocated exactly.
-const MAX_BUFFER_SIZE_CLASS: u64 = 16 * 1024 * 1024;
+const DEFAULT_MAX_BUFFER_SIZE_CLASS: u64 = 16 * 1024 * 1024;
 
-// Number of seconds before unused memory is purged.
+// Milliseconds before unused memory is purged.
 //
 // TODO(pcwalton): jemalloc uses a sigmoidal decay curve here. Consider something similar.
-const DECAY_TIME: f32 = 0.250;
+const DEFAULT_DECAY_TIME_MS: u64 = 250;
 
-// Number of seconds before we can reuse an object buffer.
+// Milliseconds before we can reuse an object buffer.
 //
 // This helps avoid stalls. This is admittedly a bit of a hack.
-const REUSE_TIME: f32 = 0.015;
+const DEFAULT_REUSE_TIME_MS: u64 = 15;
 
 pub struct GPUMemoryAllocator<D> where D: Device {
+    policy: AllocatorPolicy,
     buffers_in_use: FxHashMap<BufferID, BufferAllocation<D>>,
     textures_in_use: FxHashMap<TextureID, TextureAllocation<D>>,
     framebuffers_in_use: FxHashMap<FramebufferID, FramebufferAllocation<D>>,
@@ -43,6 +44,56 @@ pub struct GPUMemoryAllocator<D> where D: Device {
     bytes_allocated: u64,
 }
 
+/// Tunes how a `GPUMemoryAllocator` recycles and releases memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AllocatorPolicy {
+    /// How long freed objects are kept around for reuse before they're released.
+    pub decay_time: Duration,
+    /// How long a freed buffer must wait before it's reused, to avoid stalling on the GPU.
+    pub reuse_time: Duration,
+    /// Buffers smaller than this are rounded up to a power of two so that they're easier to
+    /// reuse. Larger buffers are allocated exactly.
+    pub max_buffer_size_class: u64,
+    /// The most memory, in bytes, that may be allocated at once, including freed objects awaiting
+    /// reuse.
+    ///
+    /// When an allocation would exceed this, freed objects are released early, oldest first. If
+    /// that isn't enough, the allocation fails with `DeviceError::BudgetExceeded`.
+    pub budget: Option<u64>,
+}
+
+/// A summary of the memory that a `GPUMemoryAllocator` holds, suitable for telemetry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GPUMemorySnapshot {
+    /// The bytes allocated, including freed objects awaiting reuse.
+    pub bytes_allocated: u64,
+    /// The bytes in use.
+    pub bytes_committed: u64,
+    /// The budget in effect, if any.
+    pub budget: Option<u64>,
+    /// Buffers in use, grouped by `BufferTag` and sorted by tag.
+    pub buffers: Vec<TagMemoryUsage>,
+    /// Textures in use, grouped by `TextureTag` and sorted by tag.
+    pub textures: Vec<TagMemoryUsage>,
+    /// Framebuffers in use, grouped by `FramebufferTag` and sorted by tag.
+    pub framebuffers: Vec<TagMemoryUsage>,
+    /// The number of freed objects awaiting reuse.
+    pub free_object_count: usize,
+    /// The bytes held by freed objects awaiting reuse.
+    pub free_bytes: u64,
+}
+
+/// The objects in use that share a tag.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TagMemoryUsage {
+    /// The name of the tag.
+    pub tag: &'static str,
+    /// The number of objects with this tag.
+    pub count: usize,
+    /// The total size of those objects in bytes.
+    pub bytes: u64,
+}
+
 struct BufferAllocation<D> where D: Device {
     buffer: D::Buffer,
     size: u64,
@@ -100,9 +151,27 @@ pub struct TextureTag(pub &'static str);
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct FramebufferTag(pub &'static str);
 
+impl Default for AllocatorPolicy {
+    #[inline]
+    fn default() -> AllocatorPolicy {
+        AllocatorPolicy {
+            decay_time: Duration::from_millis(DEFAULT_DECAY_TIME_MS),
+            reuse_time: Duration::from_millis(DEFAULT_REUSE_TIME_MS),
+            max_buffer_size_class: DEFAULT_MAX_BUFFER_SIZE_CLASS,
+            budget: None,
+        }
+    }
+}
+
 impl<D> GPUMemoryAllocator<D> where D: Device {
+    #[inline]
     pub fn new() -> GPUMemoryAllocator<D> {
+        GPUMemoryAllocator::with_policy(AllocatorPolicy::default())
+    }
+
+    pub fn with_policy(policy: AllocatorPolicy) -> GPUMemoryAllocator<D> {
         GPUMemoryAllocator {
+            policy,
             buffers_in_use: FxHashMap::default(),
             textures_in_use: FxHashMap::default(),
             framebuffers_in_use: FxHashMap::default(),
@@ -115,21 +184,32 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
         }
     }
 
+    /// Like `try_allocate_buffer()`, but panics if the device fails to create the buffer.
     pub fn allocate_buffer<T>(&mut self, device: &D, size: u64, tag: BufferTag) -> BufferID {
+        match self.try_allocate_buffer::<T>(device, size, tag) {
+            Ok(id) => id,
+            Err(error) => panic!("Failed to allocate buffer {:?}: {}", tag, error),
+        }
+    }
+
+    /// Returns a buffer with room for at least `size` values of type `T`, reusing a freed one if
+    /// possible.
+    pub fn try_allocate_buffer<T>(&mut self, device: &D, size: u64, tag: BufferTag)
+                                  -> Result<BufferID, DeviceError> {
         let mut byte_size = size * mem::size_of::<T>() as u64;
-        if byte_size < MAX_BUFFER_SIZE_CLASS {
+        if byte_size < self.policy.max_buffer_size_class {
             byte_size = byte_size.next_power_of_two();
         }
 
         let now = Instant::now();
+        let reuse_time = self.policy.reuse_time;
 
         for free_object_index in 0..self.free_objects.len() {
             match self.free_objects[free_object_index] {
                 FreeObject {
                     ref timestamp,
                     kind: FreeObjectKind::Buffer { ref allocation, .. },
-                } if allocation.size == byte_size &&
-                    (now - *timestamp).as_secs_f32() >= REUSE_TIME => {}
+                } if allocation.size == byte_size && now - *timestamp >= reuse_time => {}
                 _ => continue,
             }
 
@@ -143,10 +223,11 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
             allocation.tag = tag;
             self.bytes_committed += allocation.size;
             self.buffers_in_use.insert(id, allocation);
-            return id;
+            return Ok(id);
         }
 
-        let buffer = device.create_buffer(BufferUploadMode::Dynamic);
+        self.make_room_for(byte_size)?;
+        let buffer = device.create_buffer(BufferUploadMode::Dynamic)?;
         device.allocate_buffer::<u8>(&buffer,
                                      BufferData::Uninitialized(byte_size as usize),
                                      BufferTarget::Vertex);
@@ -165,15 +246,29 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
         self.bytes_allocated += byte_size;
         self.bytes_committed += byte_size;
 
-        id
+        Ok(id)
     }
 
+    /// Like `try_allocate_texture()`, but panics if the device fails to create the texture.
     pub fn allocate_texture(&mut self,
                             device: &D,
                             size: Vector2I,
                             format: TextureFormat,
                             tag: TextureTag)
                             -> TextureID {
+        match self.try_allocate_texture(device, size, format, tag) {
+            Ok(id) => id,
+            Err(error) => panic!("Failed to allocate texture {:?}: {}", tag, error),
+        }
+    }
+
+    /// Returns a texture of the given size and format, reusing a freed one if possible.
+    pub fn try_allocate_texture(&mut self,
+                                device: &D,
+                                size: Vector2I,
+                                format: TextureFormat,
+                                tag: TextureTag)
+                                -> Result<TextureID, DeviceError> {
         let descriptor = TextureDescriptor {
             width: size.x() as u32,
             height: size.y() as u32,
@@ -198,12 +293,13 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
             allocation.tag = tag;
             self.bytes_committed += allocation.descriptor.byte_size();
             self.textures_in_use.insert(id, allocation);
-            return id;
+            return Ok(id);
         }
 
         debug!("mapping texture: {:?} {:?}", descriptor, tag);
 
-        let texture = device.create_texture(format, size);
+        self.make_room_for(byte_size)?;
+        let texture = device.create_texture(format, size)?;
         let id = self.next_texture_id;
         self.next_texture_id.0 += 1;
 
@@ -212,15 +308,30 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
         self.bytes_allocated += byte_size;
         self.bytes_committed += byte_size;
 
-        id
+        Ok(id)
     }
 
+    /// Like `try_allocate_framebuffer()`, but panics if the device fails to create the
+    /// framebuffer.
     pub fn allocate_framebuffer(&mut self,
                                 device: &D,
                                 size: Vector2I,
                                 format: TextureFormat,
                                 tag: FramebufferTag)
                                 -> FramebufferID {
+        match self.try_allocate_framebuffer(device, size, format, tag) {
+            Ok(id) => id,
+            Err(error) => panic!("Failed to allocate framebuffer {:?}: {}", tag, error),
+        }
+    }
+
+    /// Returns a framebuffer of the given size and format, reusing a freed one if possible.
+    pub fn try_allocate_framebuffer(&mut self,
+                                    device: &D,
+                                    size: Vector2I,
+                                    format: TextureFormat,
+                                    tag: FramebufferTag)
+                                    -> Result<FramebufferID, DeviceError> {
         let descriptor = TextureDescriptor {
             width: size.x() as u32,
             height: size.y() as u32,
@@ -245,13 +356,14 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
             allocation.tag = tag;
             self.bytes_committed += allocation.descriptor.byte_size();
             self.framebuffers_in_use.insert(id, allocation);
-            return id;
+            return Ok(id);
         }
 
         debug!("mapping framebuffer: {:?} {:?}", descriptor, tag);
 
-        let texture = device.create_texture(format, size);
-        let framebuffer = device.create_framebuffer(texture);
+        self.make_room_for(byte_size)?;
+        let texture = device.create_texture(format, size)?;
+        let framebuffer = device.create_framebuffer(texture)?;
         let id = self.next_framebuffer_id;
         self.next_framebuffer_id.0 += 1;
 
@@ -264,33 +376,57 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
         self.bytes_allocated += byte_size;
         self.bytes_committed += byte_size;
 
-        id
+        Ok(id)
     }
 
     pub fn purge_if_needed(&mut self) {
         let now = Instant::now();
         loop {
             match self.free_objects.front() {
-                Some(FreeObject { timestamp, .. }) if (now - *timestamp).as_secs_f32() >=
-                    DECAY_TIME => {}
+                Some(FreeObject { timestamp, .. }) if now - *timestamp >=
+                    self.policy.decay_time => {}
                 _ => break,
             }
-            match self.free_objects.pop_front() {
-                None => break,
-                Some(FreeObject { kind: FreeObjectKind::Buffer { allocation, .. }, .. }) => {
-                    debug!("purging buffer: {}", allocation.size);
-                    self.bytes_allocated -= allocation.size;
-                }
-                Some(FreeObject { kind: FreeObjectKind::Texture { allocation, .. }, .. }) => {
-                    debug!("purging texture: {:?}", allocation.descriptor);
-                    self.bytes_allocated -= allocation.descriptor.byte_size();
-                }
-                Some(FreeObject { kind: FreeObjectKind::Framebuffer { allocation, .. }, .. }) => {
-                    debug!("purging framebuffer: {:?}", allocation.descriptor);
-                    self.bytes_allocated -= allocation.descriptor.byte_size();
-                }
+            self.purge_oldest();
+        }
+    }
+
+    // Releases freed objects early, oldest first, until `byte_size` more bytes fit in the budget.
+    fn make_room_for(&mut self, byte_size: u64) -> Result<(), DeviceError> {
+        let budget = match self.policy.budget {
+            None => return Ok(()),
+            Some(budget) => budget,
+        };
+        while self.bytes_allocated + byte_size > budget {
+            if !self.purge_oldest() {
+                return Err(DeviceError::BudgetExceeded {
+                    requested: byte_size,
+                    allocated: self.bytes_allocated,
+                    budget,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Returns false if there was nothing to purge.
+    fn purge_oldest(&mut self) -> bool {
+        match self.free_objects.pop_front() {
+            None => return false,
+            Some(FreeObject { kind: FreeObjectKind::Buffer { allocation, .. }, .. }) => {
+                debug!("purging buffer: {}", allocation.size);
+                self.bytes_allocated -= allocation.size;
+            }
+            Some(FreeObject { kind: FreeObjectKind::Texture { allocation, .. }, .. }) => {
+                debug!("purging texture: {:?}", allocation.descriptor);
+                self.bytes_allocated -= allocation.descriptor.byte_size();
+            }
+            Some(FreeObject { kind: FreeObjectKind::Framebuffer { allocation, .. }, .. }) => {
+                debug!("purging framebuffer: {:?}", allocation.descriptor);
+                self.bytes_allocated -= allocation.descriptor.byte_size();
             }
         }
+        true
     }
 
     pub fn free_buffer(&mut self, id: BufferID) {
@@ -350,6 +486,43 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
         self.bytes_committed
     }
 
+    #[inline]
+    pub fn policy(&self) -> &AllocatorPolicy {
+        &self.policy
+    }
+
+    /// Changes the allocator policy.
+    ///
+    /// A lower budget takes effect at the next allocation; memory already in use isn't released.
+    #[inline]
+    pub fn set_policy(&mut self, new_policy: AllocatorPolicy) {
+        self.policy = new_policy;
+    }
+
+    /// Returns the memory in use, grouped by tag.
+    pub fn snapshot(&self) -> GPUMemorySnapshot {
+        let buffers = group_by_tag(self.buffers_in_use.values().map(|allocation| {
+            (allocation.tag.0, allocation.size)
+        }));
+        let textures = group_by_tag(self.textures_in_use.values().map(|allocation| {
+            (allocation.tag.0, allocation.descriptor.byte_size())
+        }));
+        let framebuffers = group_by_tag(self.framebuffers_in_use.values().map(|allocation| {
+            (allocation.tag.0, allocation.descriptor.byte_size())
+        }));
+
+        GPUMemorySnapshot {
+            bytes_allocated: self.bytes_allocated,
+            bytes_committed: self.bytes_committed,
+            budget: self.policy.budget,
+            buffers,
+            textures,
+            framebuffers,
+            free_object_count: self.free_objects.len(),
+            free_bytes: self.bytes_allocated - self.bytes_committed,
+        }
+    }
+
     #[allow(dead_code)]
     pub fn dump(&self) {
         println!("GPU memory dump");
@@ -393,8 +566,89 @@ impl<D> GPUMemoryAllocator<D> where D: Device {
     }
 }
 
+fn group_by_tag<I>(allocations: I) -> Vec<TagMemoryUsage>
+                   where I: Iterator<Item = (&'static str, u64)> {
+    let mut usage: Vec<TagMemoryUsage> = vec![];
+    for (tag, bytes) in allocations {
+        match usage.iter_mut().find(|usage| usage.tag == tag) {
+            Some(usage) => {
+                usage.count += 1;
+                usage.bytes += bytes;
+            }
+            None => usage.push(TagMemoryUsage { tag, count: 1, bytes }),
+        }
+    }
+    usage.sort_by_key(|usage| usage.tag);
+    usage
+}
+
 impl TextureDescriptor {
     fn byte_size(&self) -> u64 {
         self.width as u64 * self.height as u64 * self.format.bytes_per_pixel() as u64
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::recording::RecordingDevice;
+    use crate::{DeviceError, FeatureLevel, TextureFormat};
+    use pathfinder_geometry::vector::vec2i;
+    use std::time::Duration;
+
+    use super::{AllocatorPolicy, BufferTag, GPUMemoryAllocator, TagMemoryUsage, TextureTag};
+
+    #[test]
+    fn test_budget() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut allocator = GPUMemoryAllocator::with_policy(AllocatorPolicy {
+            decay_time: Duration::from_secs(60),
+            budget: Some(64 * 64 * 4 * 2),
+            ..AllocatorPolicy::default()
+        });
+
+        let first = allocator.allocate_texture(&device,
+                                               vec2i(64, 64),
+                                               TextureFormat::RGBA8,
+                                               TextureTag("First"));
+        allocator.free_texture(first);
+        allocator.allocate_texture(&device, vec2i(64, 64), TextureFormat::R8, TextureTag("Second"));
+        assert_eq!(device.live_texture_count(), 2);
+
+        // The freed texture is released early to make room.
+        let third = allocator.try_allocate_texture(&device,
+                                                   vec2i(64, 64),
+                                                   TextureFormat::RGBA8,
+                                                   TextureTag("Third"));
+        assert!(third.is_ok());
+        assert_eq!(device.live_texture_count(), 2);
+
+        // Nothing else can be released, so this fails without touching the device.
+        let fourth = allocator.try_allocate_texture(&device,
+                                                    vec2i(64, 64),
+                                                    TextureFormat::RGBA8,
+                                                    TextureTag("Fourth"));
+        assert_eq!(fourth.err(), Some(DeviceError::BudgetExceeded {
+            requested: 64 * 64 * 4,
+            allocated: 64 * 64 * 5,
+            budget: 64 * 64 * 4 * 2,
+        }));
+        assert_eq!(device.live_texture_count(), 2);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut allocator = GPUMemoryAllocator::new();
+        allocator.allocate_buffer::<u32>(&device, 3, BufferTag("Vertices"));
+        allocator.allocate_buffer::<u32>(&device, 100, BufferTag("Vertices"));
+        let indices = allocator.allocate_buffer::<u8>(&device, 16, BufferTag("Indices"));
+        allocator.free_buffer(indices);
+
+        let snapshot = allocator.snapshot();
+        let vertices = TagMemoryUsage { tag: "Vertices", count: 2, bytes: 528 };
+        assert_eq!(snapshot.buffers, vec![vertices]);
+        assert!(snapshot.textures.is_empty() && snapshot.framebuffers.is_empty());
+        assert_eq!((snapshot.free_object_count, snapshot.free_bytes), (1, 16));
+        assert_eq!((snapshot.bytes_committed, snapshot.bytes_allocated), (528, 544));
+    }
+}
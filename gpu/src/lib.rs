@@ -16,6 +16,7 @@ extern crate bitflags;
 extern crate log;
 
 pub mod allocator;
+pub mod recording;
 
 use half::f16;
 use image::ImageFormat;
@@ -25,6 +26,8 @@ use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::ops::Range;
 use std::os::raw::c_void;
 use std::time::Duration;
@@ -49,19 +52,29 @@ pub trait Device: Sized {
     fn backend_name(&self) -> &'static str;
     fn device_name(&self) -> String;
     fn feature_level(&self) -> FeatureLevel;
-    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> Self::Texture;
+    /// Returns true if textures in the given format can be created, uploaded to, and sampled.
+    fn supports_texture_format(&self, format: TextureFormat) -> bool;
+    /// Returns true if the device has been lost, for example because the GPU was reset or its
+    /// driver was updated.
+    ///
+    /// A lost device never recovers. Objects created from it keep working as far as Rust is
+    /// concerned, but nothing they do reaches the screen. To recover, drop the renderer and
+    /// create a new device and renderer.
+    fn is_device_lost(&self) -> bool;
+    fn create_texture(&self, format: TextureFormat, size: Vector2I)
+                      -> Result<Self::Texture, DeviceError>;
     fn create_texture_from_data(&self, format: TextureFormat, size: Vector2I, data: TextureDataRef)
-                                -> Self::Texture;
+                                -> Result<Self::Texture, DeviceError>;
     fn create_shader(&self, resources: &dyn ResourceLoader, name: &str, kind: ShaderKind)
-                     -> Self::Shader;
+                     -> Result<Self::Shader, DeviceError>;
     fn create_shader_from_source(&self, name: &str, source: &[u8], kind: ShaderKind)
-                                 -> Self::Shader;
-    fn create_vertex_array(&self) -> Self::VertexArray;
+                                 -> Result<Self::Shader, DeviceError>;
+    fn create_vertex_array(&self) -> Result<Self::VertexArray, DeviceError>;
     fn create_program_from_shaders(&self,
                                    resources: &dyn ResourceLoader,
                                    name: &str,
                                    shaders: ProgramKind<Self::Shader>)
-                                   -> Self::Program;
+                                   -> Result<Self::Program, DeviceError>;
     fn set_compute_program_local_size(&self,
                                       program: &mut Self::Program,
                                       local_size: ComputeDimensions);
@@ -79,8 +92,8 @@ pub trait Device: Sized {
                              vertex_array: &Self::VertexArray,
                              attr: &Self::VertexAttr,
                              descriptor: &VertexAttrDescriptor);
-    fn create_framebuffer(&self, texture: Self::Texture) -> Self::Framebuffer;
-    fn create_buffer(&self, mode: BufferUploadMode) -> Self::Buffer;
+    fn create_framebuffer(&self, texture: Self::Texture) -> Result<Self::Framebuffer, DeviceError>;
+    fn create_buffer(&self, mode: BufferUploadMode) -> Result<Self::Buffer, DeviceError>;
     fn allocate_buffer<T>(&self,
                           buffer: &Self::Buffer,
                           data: BufferData<T>,
@@ -99,7 +112,7 @@ pub trait Device: Sized {
     fn read_pixels(&self, target: &RenderTarget<Self>, viewport: RectI)
                    -> Self::TextureDataReceiver;
     fn read_buffer(&self, buffer: &Self::Buffer, target: BufferTarget, range: Range<usize>)
-                   -> Self::BufferDataReceiver;
+                   -> Result<Self::BufferDataReceiver, DeviceError>;
     fn begin_commands(&self);
     fn end_commands(&self);
     fn draw_arrays(&self, index_count: u32, render_state: &RenderState<Self>);
@@ -111,7 +124,7 @@ pub trait Device: Sized {
     fn dispatch_compute(&self, dimensions: ComputeDimensions, state: &ComputeState<Self>);
     fn add_fence(&self) -> Self::Fence;
     fn wait_for_fence(&self, fence: &Self::Fence);
-    fn create_timer_query(&self) -> Self::TimerQuery;
+    fn create_timer_query(&self) -> Result<Self::TimerQuery, DeviceError>;
     fn begin_timer_query(&self, query: &Self::TimerQuery);
     fn end_timer_query(&self, query: &Self::TimerQuery);
     fn try_recv_timer_query(&self, query: &Self::TimerQuery) -> Option<Duration>;
@@ -125,9 +138,12 @@ pub trait Device: Sized {
                                resources: &dyn ResourceLoader,
                                name: &str,
                                format: TextureFormat)
-                               -> Self::Texture {
-        let data = resources.slurp(&format!("textures/{}.png", name)).unwrap();
-        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+                               -> Result<Self::Texture, DeviceError> {
+        let path = format!("textures/{}.png", name);
+        let data = resources.slurp(&path).map_err(|_| DeviceError::MissingResource(path))?;
+        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).map_err(|error| {
+            DeviceError::InvalidResource { name: name.to_owned(), reason: error.to_string() }
+        })?;
         match format {
             TextureFormat::R8 => {
                 let image = image.to_luma();
@@ -139,7 +155,7 @@ pub trait Device: Sized {
                 let size = vec2i(image.width() as i32, image.height() as i32);
                 self.create_texture_from_data(format, size, TextureDataRef::U8(&image))
             }
-            _ => unimplemented!(),
+            _ => Err(DeviceError::Unsupported("PNG textures in this format")),
         }
     }
 
@@ -147,23 +163,29 @@ pub trait Device: Sized {
                              resources: &dyn ResourceLoader,
                              name: &str,
                              texture: &Self::Texture,
-                             format: TextureFormat) {
-        let data = resources.slurp(&format!("textures/{}.png", name)).unwrap();
-        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).unwrap();
+                             format: TextureFormat)
+                             -> Result<(), DeviceError> {
+        let path = format!("textures/{}.png", name);
+        let data = resources.slurp(&path).map_err(|_| DeviceError::MissingResource(path))?;
+        let image = image::load_from_memory_with_format(&data, ImageFormat::Png).map_err(|error| {
+            DeviceError::InvalidResource { name: name.to_owned(), reason: error.to_string() }
+        })?;
         match format {
             TextureFormat::R8 => {
                 let image = image.to_luma();
                 let size = vec2i(image.width() as i32, image.height() as i32);
                 let rect = RectI::new(Vector2I::default(), size);
-                self.upload_to_texture(&texture, rect, TextureDataRef::U8(&image))
+                self.upload_to_texture(&texture, rect, TextureDataRef::U8(&image));
+                Ok(())
             }
             TextureFormat::RGBA8 => {
                 let image = image.to_rgba();
                 let size = vec2i(image.width() as i32, image.height() as i32);
                 let rect = RectI::new(Vector2I::default(), size);
-                self.upload_to_texture(&texture, rect, TextureDataRef::U8(&image))
+                self.upload_to_texture(&texture, rect, TextureDataRef::U8(&image));
+                Ok(())
             }
-            _ => unimplemented!(),
+            _ => Err(DeviceError::Unsupported("PNG textures in this format")),
         }
     }
 
@@ -172,27 +194,29 @@ pub trait Device: Sized {
         resources: &dyn ResourceLoader,
         program_name: &str,
         shader_names: ProgramKind<&str>,
-    ) -> Self::Program {
+    ) -> Result<Self::Program, DeviceError> {
         let shaders = match shader_names {
             ProgramKind::Raster { vertex, fragment } => {
                 ProgramKind::Raster {
-                    vertex: self.create_shader(resources, vertex, ShaderKind::Vertex),
-                    fragment: self.create_shader(resources, fragment, ShaderKind::Fragment),
+                    vertex: self.create_shader(resources, vertex, ShaderKind::Vertex)?,
+                    fragment: self.create_shader(resources, fragment, ShaderKind::Fragment)?,
                 }
             }
             ProgramKind::Compute(compute) => {
-                ProgramKind::Compute(self.create_shader(resources, compute, ShaderKind::Compute))
+                ProgramKind::Compute(self.create_shader(resources, compute, ShaderKind::Compute)?)
             }
         };
         self.create_program_from_shaders(resources, program_name, shaders)
     }
 
-    fn create_raster_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+    fn create_raster_program(&self, resources: &dyn ResourceLoader, name: &str)
+                             -> Result<Self::Program, DeviceError> {
         let shaders = ProgramKind::Raster { vertex: name, fragment: name };
         self.create_program_from_shader_names(resources, name, shaders)
     }
 
-    fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str) -> Self::Program {
+    fn create_compute_program(&self, resources: &dyn ResourceLoader, name: &str)
+                              -> Result<Self::Program, DeviceError> {
         let shaders = ProgramKind::Compute(name);
         self.create_program_from_shader_names(resources, name, shaders)
     }
@@ -211,6 +235,8 @@ pub enum TextureFormat {
     R8,
     R16F,
     RGBA8,
+    /// Like `RGBA8`, but uploaded with the blue and red channels swapped. Shaders see RGBA.
+    BGRA8,
     RGBA16F,
     RGBA32F,
 }
@@ -267,7 +293,7 @@ pub struct ComputeDimensions {
     pub z: u32,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum UniformData {
     Float(f32),
     IVec2(I32x2),
@@ -280,7 +306,7 @@ pub enum UniformData {
     Vec4(F32x4),
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Primitive {
     Triangles,
     Lines,
@@ -322,6 +348,9 @@ pub struct RenderOptions {
     pub stencil: Option<StencilState>,
     pub clear_ops: ClearOps,
     pub color_mask: bool,
+    /// If present, restricts rasterization to this rectangle. The rectangle is relative to the
+    /// viewport origin, with the Y axis pointing down (i.e. toward NDC -1).
+    pub scissor: Option<RectI>,
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -401,6 +430,7 @@ impl Default for RenderOptions {
             stencil: None,
             clear_ops: ClearOps::default(),
             color_mask: true,
+            scissor: None,
         }
     }
 }
@@ -483,7 +513,10 @@ impl TextureFormat {
     pub fn channels(self) -> usize {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => 1,
-            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => 4,
+            TextureFormat::RGBA8 |
+            TextureFormat::BGRA8 |
+            TextureFormat::RGBA16F |
+            TextureFormat::RGBA32F => 4,
         }
     }
 
@@ -492,7 +525,7 @@ impl TextureFormat {
         match self {
             TextureFormat::R8 => 1,
             TextureFormat::R16F => 2,
-            TextureFormat::RGBA8 => 4,
+            TextureFormat::RGBA8 | TextureFormat::BGRA8 => 4,
             TextureFormat::RGBA16F => 8,
             TextureFormat::RGBA32F => 16,
         }
@@ -528,6 +561,54 @@ bitflags! {
     }
 }
 
+/// An error that a device reported while creating a GPU object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceError {
+    /// A shader failed to compile.
+    ShaderCompilationFailed {
+        /// The name of the shader.
+        name: String,
+        /// The compiler's log.
+        log: String,
+    },
+    /// A program failed to link.
+    ProgramLinkingFailed {
+        /// The name of the program.
+        name: String,
+        /// The linker's log.
+        log: String,
+    },
+    /// A framebuffer can't be rendered to. Contains the backend's description of the problem.
+    FramebufferIncomplete(String),
+    /// The device ran out of memory.
+    OutOfMemory,
+    /// An allocation would have exceeded the memory budget set in the allocator's policy, even
+    /// after releasing all freed objects.
+    BudgetExceeded {
+        /// The size of the allocation, in bytes.
+        requested: u64,
+        /// The bytes already allocated.
+        allocated: u64,
+        /// The budget, in bytes.
+        budget: u64,
+    },
+    /// The device doesn't support a feature that was needed.
+    Unsupported(&'static str),
+    /// The device was lost. See `Device::is_device_lost()`.
+    DeviceLost,
+    /// A shader or texture couldn't be loaded. Contains its path.
+    MissingResource(String),
+    /// A shader or texture was loaded but couldn't be decoded.
+    InvalidResource {
+        /// The name of the resource.
+        name: String,
+        /// Why decoding failed.
+        reason: String,
+    },
+    /// Some other error reported by the backend.
+    Other(String),
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ImageAccess {
     Read,
@@ -541,7 +622,8 @@ impl<'a> TextureDataRef<'a> {
                                       -> *const c_void {
         let channels = match (format, self) {
             (TextureFormat::R8, TextureDataRef::U8(_)) => 1,
-            (TextureFormat::RGBA8, TextureDataRef::U8(_)) => 4,
+            (TextureFormat::RGBA8, TextureDataRef::U8(_)) |
+            (TextureFormat::BGRA8, TextureDataRef::U8(_)) => 4,
             (TextureFormat::RGBA16F, TextureDataRef::F16(_)) => 4,
             (TextureFormat::RGBA32F, TextureDataRef::F32(_)) => 4,
             _ => panic!("Unimplemented texture format!"),
@@ -565,3 +647,38 @@ impl<'a> TextureDataRef<'a> {
         }
     }
 }
+
+impl Display for DeviceError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            DeviceError::ShaderCompilationFailed { ref name, ref log } => {
+                write!(formatter, "shader '{}' failed to compile: {}", name, log)
+            }
+            DeviceError::ProgramLinkingFailed { ref name, ref log } => {
+                write!(formatter, "program '{}' failed to link: {}", name, log)
+            }
+            DeviceError::FramebufferIncomplete(ref reason) => {
+                write!(formatter, "framebuffer incomplete: {}", reason)
+            }
+            DeviceError::OutOfMemory => formatter.write_str("out of GPU memory"),
+            DeviceError::BudgetExceeded { requested, allocated, budget } => {
+                write!(formatter,
+                       "allocating {} B with {} B already allocated would exceed the {} B budget",
+                       requested,
+                       allocated,
+                       budget)
+            }
+            DeviceError::Unsupported(feature) => write!(formatter, "unsupported: {}", feature),
+            DeviceError::DeviceLost => formatter.write_str("device lost"),
+            DeviceError::MissingResource(ref path) => {
+                write!(formatter, "resource '{}' not found", path)
+            }
+            DeviceError::InvalidResource { ref name, ref reason } => {
+                write!(formatter, "resource '{}' is invalid: {}", name, reason)
+            }
+            DeviceError::Other(ref message) => formatter.write_str(message),
+        }
+    }
+}
+
+impl Error for DeviceError {}
@@ -0,0 +1,857 @@
+// pathfinder/gpu/src/recording.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A device that records what it's asked to do instead of doing it.
+//!
+//! This needs no GPU, so it can be used to test code that drives a `Device`, such as the
+//! renderer, in headless environments like CI. Objects are tracked in memory, draw calls and
+//! compute dispatches are logged along with their state, and reads return deterministic
+//! placeholder data.
+
+use crate::{BufferData, BufferTarget, BufferUploadMode, ComputeDimensions, ComputeState, Device};
+use crate::{DeviceError, FeatureLevel, ImageAccess, Primitive, ProgramKind, RenderOptions};
+use crate::{RenderState, RenderTarget, ShaderKind, TextureData, TextureDataRef, TextureFormat};
+use crate::{TextureSamplingFlags, UniformData, VertexAttrDescriptor};
+use half::f16;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector2I;
+use pathfinder_resources::ResourceLoader;
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::ops::Range;
+use std::rc::{Rc, Weak};
+use std::slice;
+use std::time::Duration;
+
+/// A device that records commands without executing them.
+pub struct RecordingDevice {
+    feature_level: FeatureLevel,
+    next_object_id: Cell<u64>,
+    commands: RefCell<Vec<RecordedCommand>>,
+    program_names: RefCell<Vec<String>>,
+    live_textures: LiveObjects,
+    live_buffers: LiveObjects,
+    live_framebuffers: LiveObjects,
+    out_of_memory: Cell<bool>,
+    device_lost: Cell<bool>,
+    unsupported_texture_formats: RefCell<Vec<TextureFormat>>,
+}
+
+/// Uniquely identifies an object created by a `RecordingDevice`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectId(pub u64);
+
+/// A command that a `RecordingDevice` was given.
+#[derive(Clone, Debug)]
+pub enum RecordedCommand {
+    BeginCommands,
+    EndCommands,
+    AllocateBuffer { buffer: ObjectId, byte_size: usize },
+    UploadToBuffer { buffer: ObjectId, position: usize, byte_size: usize },
+    UploadToTexture { texture: ObjectId, rect: RectI },
+    ReadPixels { target: RecordedTarget, viewport: RectI },
+    ReadBuffer { buffer: ObjectId, range: Range<usize> },
+    Draw(RecordedDraw),
+    DispatchCompute(RecordedDispatch),
+}
+
+/// A draw call, along with the render state it was made with.
+///
+/// Objects are referred to by ID, and programs and parameters by name.
+#[derive(Clone, Debug)]
+pub struct RecordedDraw {
+    pub program: String,
+    pub target: RecordedTarget,
+    pub vertex_array: ObjectId,
+    pub primitive: Primitive,
+    pub index_count: u32,
+    /// The number of instances drawn, if this was an instanced draw call.
+    pub instance_count: Option<u32>,
+    pub uniforms: Vec<(String, UniformData)>,
+    pub textures: Vec<(String, ObjectId)>,
+    pub images: Vec<(String, ObjectId, ImageAccess)>,
+    pub storage_buffers: Vec<(String, ObjectId)>,
+    pub viewport: RectI,
+    pub options: RenderOptions,
+}
+
+/// A compute dispatch, along with the state it was made with.
+#[derive(Clone, Debug)]
+pub struct RecordedDispatch {
+    pub program: String,
+    pub dimensions: ComputeDimensions,
+    pub uniforms: Vec<(String, UniformData)>,
+    pub textures: Vec<(String, ObjectId)>,
+    pub images: Vec<(String, ObjectId, ImageAccess)>,
+    pub storage_buffers: Vec<(String, ObjectId)>,
+}
+
+/// The render target of a recorded command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordedTarget {
+    Default,
+    Framebuffer(ObjectId),
+}
+
+pub struct RecordingTexture {
+    id: ObjectId,
+    format: TextureFormat,
+    size: Vector2I,
+    sampling_flags: Cell<TextureSamplingFlags>,
+    _live: Rc<()>,
+}
+
+pub struct RecordingFramebuffer {
+    id: ObjectId,
+    texture: RecordingTexture,
+    _live: Rc<()>,
+}
+
+pub struct RecordingBuffer {
+    id: ObjectId,
+    mode: BufferUploadMode,
+    // The buffer's contents are kept so that reading them back returns what was uploaded.
+    data: RefCell<Vec<u8>>,
+    _live: Rc<()>,
+}
+
+pub struct RecordingShader {
+    name: String,
+    kind: ShaderKind,
+}
+
+pub struct RecordingProgram {
+    name: String,
+    shaders: ProgramKind<RecordingShader>,
+    local_size: Option<ComputeDimensions>,
+}
+
+pub struct RecordingVertexArray {
+    id: ObjectId,
+}
+
+/// A named program parameter: a uniform, texture, image, or storage buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordingParameter {
+    name: String,
+}
+
+pub struct RecordingTimerQuery {
+    ended: Cell<bool>,
+}
+
+pub struct RecordingTextureDataReceiver(TextureData);
+
+pub struct RecordingBufferDataReceiver(Vec<u8>);
+
+#[derive(Default)]
+struct LiveObjects {
+    objects: RefCell<Vec<Weak<()>>>,
+}
+
+impl RecordingDevice {
+    /// Creates a new recording device that claims to support the given feature level.
+    pub fn new(feature_level: FeatureLevel) -> RecordingDevice {
+        RecordingDevice {
+            feature_level,
+            next_object_id: Cell::new(0),
+            commands: RefCell::new(vec![]),
+            program_names: RefCell::new(vec![]),
+            live_textures: LiveObjects::default(),
+            live_buffers: LiveObjects::default(),
+            live_framebuffers: LiveObjects::default(),
+            out_of_memory: Cell::new(false),
+            device_lost: Cell::new(false),
+            unsupported_texture_formats: RefCell::new(vec![]),
+        }
+    }
+
+    /// Returns every command recorded so far.
+    #[inline]
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.commands.borrow().clone()
+    }
+
+    /// Returns every command recorded so far and clears the log.
+    #[inline]
+    pub fn take_commands(&self) -> Vec<RecordedCommand> {
+        mem::take(&mut *self.commands.borrow_mut())
+    }
+
+    /// Returns the draw calls recorded so far, in order.
+    pub fn draw_calls(&self) -> Vec<RecordedDraw> {
+        self.commands.borrow().iter().filter_map(|command| {
+            match *command {
+                RecordedCommand::Draw(ref draw) => Some(draw.clone()),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    /// Returns the compute dispatches recorded so far, in order.
+    pub fn compute_dispatches(&self) -> Vec<RecordedDispatch> {
+        self.commands.borrow().iter().filter_map(|command| {
+            match *command {
+                RecordedCommand::DispatchCompute(ref dispatch) => Some(dispatch.clone()),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    /// Returns the names of every program created so far, in order.
+    #[inline]
+    pub fn program_names(&self) -> Vec<String> {
+        self.program_names.borrow().clone()
+    }
+
+    /// Returns the number of textures that haven't been dropped yet, including those owned by
+    /// framebuffers.
+    #[inline]
+    pub fn live_texture_count(&self) -> usize {
+        self.live_textures.count()
+    }
+
+    #[inline]
+    pub fn live_buffer_count(&self) -> usize {
+        self.live_buffers.count()
+    }
+
+    #[inline]
+    pub fn live_framebuffer_count(&self) -> usize {
+        self.live_framebuffers.count()
+    }
+
+    /// If true, all subsequent texture, buffer, and framebuffer creation fails with
+    /// `DeviceError::OutOfMemory`.
+    #[inline]
+    pub fn set_out_of_memory(&self, out_of_memory: bool) {
+        self.out_of_memory.set(out_of_memory)
+    }
+
+    /// Simulates losing the device. Afterward, `is_device_lost()` returns true and all object
+    /// creation fails with `DeviceError::DeviceLost`.
+    #[inline]
+    pub fn lose_device(&self) {
+        self.device_lost.set(true)
+    }
+
+    /// Controls what `supports_texture_format()` reports for the given format. Every format is
+    /// supported initially.
+    pub fn set_texture_format_supported(&self, format: TextureFormat, supported: bool) {
+        let mut unsupported_texture_formats = self.unsupported_texture_formats.borrow_mut();
+        unsupported_texture_formats.retain(|&unsupported| unsupported != format);
+        if !supported {
+            unsupported_texture_formats.push(format);
+        }
+    }
+
+    fn next_object_id(&self) -> ObjectId {
+        let id = self.next_object_id.get();
+        self.next_object_id.set(id + 1);
+        ObjectId(id)
+    }
+
+    fn record(&self, command: RecordedCommand) {
+        self.commands.borrow_mut().push(command);
+    }
+
+    fn check_device(&self) -> Result<(), DeviceError> {
+        if self.device_lost.get() {
+            Err(DeviceError::DeviceLost)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_allocation(&self) -> Result<(), DeviceError> {
+        self.check_device()?;
+        if self.out_of_memory.get() {
+            Err(DeviceError::OutOfMemory)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn record_target(&self, target: &RenderTarget<RecordingDevice>) -> RecordedTarget {
+        match *target {
+            RenderTarget::Default => RecordedTarget::Default,
+            RenderTarget::Framebuffer(framebuffer) => RecordedTarget::Framebuffer(framebuffer.id),
+        }
+    }
+
+    fn record_draw(&self,
+                   index_count: u32,
+                   instance_count: Option<u32>,
+                   render_state: &RenderState<RecordingDevice>) {
+        self.record(RecordedCommand::Draw(RecordedDraw {
+            program: render_state.program.name.clone(),
+            target: self.record_target(render_state.target),
+            vertex_array: render_state.vertex_array.id,
+            primitive: render_state.primitive,
+            index_count,
+            instance_count,
+            uniforms: record_uniforms(render_state.uniforms),
+            textures: record_textures(render_state.textures),
+            images: record_images(render_state.images),
+            storage_buffers: record_storage_buffers(render_state.storage_buffers),
+            viewport: render_state.viewport,
+            options: render_state.options.clone(),
+        }));
+    }
+}
+
+impl Device for RecordingDevice {
+    type Buffer = RecordingBuffer;
+    type BufferDataReceiver = RecordingBufferDataReceiver;
+    type Fence = ();
+    type Framebuffer = RecordingFramebuffer;
+    type ImageParameter = RecordingParameter;
+    type Program = RecordingProgram;
+    type Shader = RecordingShader;
+    type StorageBuffer = RecordingParameter;
+    type Texture = RecordingTexture;
+    type TextureDataReceiver = RecordingTextureDataReceiver;
+    type TextureParameter = RecordingParameter;
+    type TimerQuery = RecordingTimerQuery;
+    type Uniform = RecordingParameter;
+    type VertexArray = RecordingVertexArray;
+    type VertexAttr = RecordingParameter;
+
+    #[inline]
+    fn backend_name(&self) -> &'static str {
+        "Recording"
+    }
+
+    #[inline]
+    fn device_name(&self) -> String {
+        "Recording device".to_owned()
+    }
+
+    #[inline]
+    fn feature_level(&self) -> FeatureLevel {
+        self.feature_level
+    }
+
+    fn supports_texture_format(&self, format: TextureFormat) -> bool {
+        !self.unsupported_texture_formats.borrow().contains(&format)
+    }
+
+    #[inline]
+    fn is_device_lost(&self) -> bool {
+        self.device_lost.get()
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I)
+                      -> Result<RecordingTexture, DeviceError> {
+        self.check_allocation()?;
+        Ok(RecordingTexture {
+            id: self.next_object_id(),
+            format,
+            size,
+            sampling_flags: Cell::new(TextureSamplingFlags::empty()),
+            _live: self.live_textures.track(),
+        })
+    }
+
+    fn create_texture_from_data(&self, format: TextureFormat, size: Vector2I, data: TextureDataRef)
+                                -> Result<RecordingTexture, DeviceError> {
+        let texture = self.create_texture(format, size)?;
+        self.upload_to_texture(&texture, RectI::new(Vector2I::default(), size), data);
+        Ok(texture)
+    }
+
+    fn create_shader(&self, _: &dyn ResourceLoader, name: &str, kind: ShaderKind)
+                     -> Result<RecordingShader, DeviceError> {
+        // There's nothing to compile, so the source isn't loaded.
+        self.create_shader_from_source(name, &[], kind)
+    }
+
+    fn create_shader_from_source(&self, name: &str, _: &[u8], kind: ShaderKind)
+                                 -> Result<RecordingShader, DeviceError> {
+        self.check_device()?;
+        if kind == ShaderKind::Compute && self.feature_level == FeatureLevel::D3D10 {
+            return Err(DeviceError::Unsupported("compute shaders at the D3D10 feature level"));
+        }
+        Ok(RecordingShader { name: name.to_owned(), kind })
+    }
+
+    fn create_vertex_array(&self) -> Result<RecordingVertexArray, DeviceError> {
+        self.check_device()?;
+        Ok(RecordingVertexArray { id: self.next_object_id() })
+    }
+
+    fn create_program_from_shaders(&self,
+                                   _: &dyn ResourceLoader,
+                                   name: &str,
+                                   shaders: ProgramKind<RecordingShader>)
+                                   -> Result<RecordingProgram, DeviceError> {
+        self.check_device()?;
+        let linkable = match shaders {
+            ProgramKind::Raster { ref vertex, ref fragment } => {
+                vertex.kind == ShaderKind::Vertex && fragment.kind == ShaderKind::Fragment
+            }
+            ProgramKind::Compute(ref compute) => compute.kind == ShaderKind::Compute,
+        };
+        if !linkable {
+            return Err(DeviceError::ProgramLinkingFailed {
+                name: name.to_owned(),
+                log: "shader kinds don't match the program kind".to_owned(),
+            });
+        }
+        self.program_names.borrow_mut().push(name.to_owned());
+        Ok(RecordingProgram { name: name.to_owned(), shaders, local_size: None })
+    }
+
+    fn set_compute_program_local_size(&self,
+                                      program: &mut RecordingProgram,
+                                      local_size: ComputeDimensions) {
+        match program.shaders {
+            ProgramKind::Compute(_) => program.local_size = Some(local_size),
+            ProgramKind::Raster { .. } => panic!("Program was not a compute program!"),
+        }
+    }
+
+    #[inline]
+    fn get_vertex_attr(&self, _: &RecordingProgram, name: &str) -> Option<RecordingParameter> {
+        Some(RecordingParameter { name: name.to_owned() })
+    }
+
+    #[inline]
+    fn get_uniform(&self, _: &RecordingProgram, name: &str) -> RecordingParameter {
+        RecordingParameter { name: name.to_owned() }
+    }
+
+    #[inline]
+    fn get_texture_parameter(&self, _: &RecordingProgram, name: &str) -> RecordingParameter {
+        RecordingParameter { name: name.to_owned() }
+    }
+
+    #[inline]
+    fn get_image_parameter(&self, _: &RecordingProgram, name: &str) -> RecordingParameter {
+        RecordingParameter { name: name.to_owned() }
+    }
+
+    #[inline]
+    fn get_storage_buffer(&self, _: &RecordingProgram, name: &str, _: u32)
+                          -> RecordingParameter {
+        RecordingParameter { name: name.to_owned() }
+    }
+
+    #[inline]
+    fn bind_buffer(&self, _: &RecordingVertexArray, _: &RecordingBuffer, _: BufferTarget) {}
+
+    #[inline]
+    fn configure_vertex_attr(&self,
+                             _: &RecordingVertexArray,
+                             _: &RecordingParameter,
+                             _: &VertexAttrDescriptor) {}
+
+    fn create_framebuffer(&self, texture: RecordingTexture)
+                          -> Result<RecordingFramebuffer, DeviceError> {
+        self.check_allocation()?;
+        Ok(RecordingFramebuffer {
+            id: self.next_object_id(),
+            texture,
+            _live: self.live_framebuffers.track(),
+        })
+    }
+
+    fn create_buffer(&self, mode: BufferUploadMode) -> Result<RecordingBuffer, DeviceError> {
+        self.check_allocation()?;
+        Ok(RecordingBuffer {
+            id: self.next_object_id(),
+            mode,
+            data: RefCell::new(vec![]),
+            _live: self.live_buffers.track(),
+        })
+    }
+
+    fn allocate_buffer<T>(&self,
+                          buffer: &RecordingBuffer,
+                          data: BufferData<T>,
+                          _: BufferTarget) {
+        let new_data = match data {
+            BufferData::Uninitialized(len) => vec![0; len * mem::size_of::<T>()],
+            BufferData::Memory(data) => as_bytes(data).to_vec(),
+        };
+        self.record(RecordedCommand::AllocateBuffer {
+            buffer: buffer.id,
+            byte_size: new_data.len(),
+        });
+        *buffer.data.borrow_mut() = new_data;
+    }
+
+    fn upload_to_buffer<T>(&self,
+                           buffer: &RecordingBuffer,
+                           position: usize,
+                           data: &[T],
+                           _: BufferTarget) {
+        let data = as_bytes(data);
+        buffer.data.borrow_mut()[position..(position + data.len())].copy_from_slice(data);
+        self.record(RecordedCommand::UploadToBuffer {
+            buffer: buffer.id,
+            position,
+            byte_size: data.len(),
+        });
+    }
+
+    #[inline]
+    fn framebuffer_texture<'f>(&self, framebuffer: &'f RecordingFramebuffer)
+                               -> &'f RecordingTexture {
+        &framebuffer.texture
+    }
+
+    #[inline]
+    fn destroy_framebuffer(&self, framebuffer: RecordingFramebuffer) -> RecordingTexture {
+        framebuffer.texture
+    }
+
+    #[inline]
+    fn texture_format(&self, texture: &RecordingTexture) -> TextureFormat {
+        texture.format
+    }
+
+    #[inline]
+    fn texture_size(&self, texture: &RecordingTexture) -> Vector2I {
+        texture.size
+    }
+
+    #[inline]
+    fn set_texture_sampling_mode(&self, texture: &RecordingTexture, flags: TextureSamplingFlags) {
+        texture.sampling_flags.set(flags)
+    }
+
+    fn upload_to_texture(&self, texture: &RecordingTexture, rect: RectI, data: TextureDataRef) {
+        assert!(rect.min_x() >= 0 && rect.min_y() >= 0 &&
+                rect.max_x() <= texture.size.x() && rect.max_y() <= texture.size.y(),
+                "Texture upload out of bounds!");
+        data.check_and_extract_data_ptr(rect.size(), texture.format);
+        self.record(RecordedCommand::UploadToTexture { texture: texture.id, rect });
+    }
+
+    fn read_pixels(&self, target: &RenderTarget<RecordingDevice>, viewport: RectI)
+                   -> RecordingTextureDataReceiver {
+        let format = match *target {
+            RenderTarget::Default => TextureFormat::RGBA8,
+            RenderTarget::Framebuffer(framebuffer) => framebuffer.texture.format,
+        };
+        self.record(RecordedCommand::ReadPixels { target: self.record_target(target), viewport });
+
+        // Nothing is ever rendered, so every pixel reads back as zero.
+        let length = viewport.size().x() as usize * viewport.size().y() as usize *
+            format.channels();
+        RecordingTextureDataReceiver(match format {
+            TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => {
+                TextureData::U8(vec![0; length])
+            }
+            TextureFormat::R16F | TextureFormat::RGBA16F => {
+                TextureData::F16(vec![f16::from_f32(0.0); length])
+            }
+            TextureFormat::RGBA32F => TextureData::F32(vec![0.0; length]),
+        })
+    }
+
+    fn read_buffer(&self, buffer: &RecordingBuffer, _: BufferTarget, range: Range<usize>)
+                   -> Result<RecordingBufferDataReceiver, DeviceError> {
+        self.record(RecordedCommand::ReadBuffer { buffer: buffer.id, range: range.clone() });
+        Ok(RecordingBufferDataReceiver(buffer.data.borrow()[range].to_vec()))
+    }
+
+    #[inline]
+    fn begin_commands(&self) {
+        self.record(RecordedCommand::BeginCommands);
+    }
+
+    #[inline]
+    fn end_commands(&self) {
+        self.record(RecordedCommand::EndCommands);
+    }
+
+    #[inline]
+    fn draw_arrays(&self, index_count: u32, render_state: &RenderState<RecordingDevice>) {
+        self.record_draw(index_count, None, render_state);
+    }
+
+    #[inline]
+    fn draw_elements(&self, index_count: u32, render_state: &RenderState<RecordingDevice>) {
+        self.record_draw(index_count, None, render_state);
+    }
+
+    #[inline]
+    fn draw_elements_instanced(&self,
+                               index_count: u32,
+                               instance_count: u32,
+                               render_state: &RenderState<RecordingDevice>) {
+        self.record_draw(index_count, Some(instance_count), render_state);
+    }
+
+    fn dispatch_compute(&self,
+                        dimensions: ComputeDimensions,
+                        compute_state: &ComputeState<RecordingDevice>) {
+        debug_assert!(compute_state.program.local_size.is_some(),
+                      "Compute program local size wasn't set!");
+        self.record(RecordedCommand::DispatchCompute(RecordedDispatch {
+            program: compute_state.program.name.clone(),
+            dimensions,
+            uniforms: record_uniforms(compute_state.uniforms),
+            textures: record_textures(compute_state.textures),
+            images: record_images(compute_state.images),
+            storage_buffers: record_storage_buffers(compute_state.storage_buffers),
+        }));
+    }
+
+    #[inline]
+    fn add_fence(&self) {}
+
+    #[inline]
+    fn wait_for_fence(&self, _: &()) {}
+
+    fn create_timer_query(&self) -> Result<RecordingTimerQuery, DeviceError> {
+        self.check_device()?;
+        Ok(RecordingTimerQuery { ended: Cell::new(false) })
+    }
+
+    #[inline]
+    fn begin_timer_query(&self, query: &RecordingTimerQuery) {
+        query.ended.set(false)
+    }
+
+    #[inline]
+    fn end_timer_query(&self, query: &RecordingTimerQuery) {
+        query.ended.set(true)
+    }
+
+    #[inline]
+    fn try_recv_timer_query(&self, query: &RecordingTimerQuery) -> Option<Duration> {
+        if query.ended.get() {
+            Some(Duration::default())
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn recv_timer_query(&self, _: &RecordingTimerQuery) -> Duration {
+        Duration::default()
+    }
+
+    #[inline]
+    fn try_recv_texture_data(&self, receiver: &RecordingTextureDataReceiver)
+                             -> Option<TextureData> {
+        Some(receiver.0.clone())
+    }
+
+    #[inline]
+    fn recv_texture_data(&self, receiver: &RecordingTextureDataReceiver) -> TextureData {
+        receiver.0.clone()
+    }
+
+    #[inline]
+    fn try_recv_buffer(&self, receiver: &RecordingBufferDataReceiver) -> Option<Vec<u8>> {
+        Some(receiver.0.clone())
+    }
+
+    #[inline]
+    fn recv_buffer(&self, receiver: &RecordingBufferDataReceiver) -> Vec<u8> {
+        receiver.0.clone()
+    }
+}
+
+impl RecordingTexture {
+    #[inline]
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    #[inline]
+    pub fn sampling_flags(&self) -> TextureSamplingFlags {
+        self.sampling_flags.get()
+    }
+}
+
+impl RecordingFramebuffer {
+    #[inline]
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+}
+
+impl RecordingBuffer {
+    #[inline]
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    #[inline]
+    pub fn mode(&self) -> BufferUploadMode {
+        self.mode
+    }
+
+    /// Returns a copy of the buffer's current contents.
+    #[inline]
+    pub fn data(&self) -> Vec<u8> {
+        self.data.borrow().clone()
+    }
+}
+
+impl RecordingShader {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl RecordingProgram {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl RecordingVertexArray {
+    #[inline]
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+}
+
+impl LiveObjects {
+    fn track(&self) -> Rc<()> {
+        let token = Rc::new(());
+        let mut objects = self.objects.borrow_mut();
+        objects.retain(|object| object.upgrade().is_some());
+        objects.push(Rc::downgrade(&token));
+        token
+    }
+
+    fn count(&self) -> usize {
+        self.objects.borrow().iter().filter(|object| object.upgrade().is_some()).count()
+    }
+}
+
+fn record_uniforms(uniforms: &[(&RecordingParameter, UniformData)])
+                   -> Vec<(String, UniformData)> {
+    uniforms.iter().map(|&(uniform, data)| (uniform.name.clone(), data)).collect()
+}
+
+fn record_textures(textures: &[(&RecordingParameter, &RecordingTexture)])
+                   -> Vec<(String, ObjectId)> {
+    textures.iter().map(|&(parameter, texture)| (parameter.name.clone(), texture.id)).collect()
+}
+
+fn record_images(images: &[(&RecordingParameter, &RecordingTexture, ImageAccess)])
+                 -> Vec<(String, ObjectId, ImageAccess)> {
+    images.iter().map(|&(parameter, texture, access)| {
+        (parameter.name.clone(), texture.id, access)
+    }).collect()
+}
+
+fn record_storage_buffers(storage_buffers: &[(&RecordingParameter, &RecordingBuffer)])
+                          -> Vec<(String, ObjectId)> {
+    storage_buffers.iter().map(|&(parameter, buffer)| {
+        (parameter.name.clone(), buffer.id)
+    }).collect()
+}
+
+fn as_bytes<T>(data: &[T]) -> &[u8] {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RecordedCommand, RecordedTarget, RecordingDevice};
+    use crate::{BufferData, BufferTarget, BufferUploadMode, Device, DeviceError, FeatureLevel};
+    use crate::{Primitive, ProgramKind, RenderOptions, RenderState, RenderTarget, ShaderKind};
+    use crate::{TextureData, TextureFormat, UniformData};
+    use pathfinder_geometry::rect::RectI;
+    use pathfinder_geometry::vector::vec2i;
+    use pathfinder_resources::embedded::EmbeddedResourceLoader;
+
+    #[test]
+    fn test_objects_and_call_log() {
+        let device = RecordingDevice::new(FeatureLevel::D3D11);
+        let resources = EmbeddedResourceLoader::new();
+
+        let texture = device.create_texture(TextureFormat::RGBA8, vec2i(4, 4)).unwrap();
+        let framebuffer = device.create_framebuffer(texture).unwrap();
+        let buffer = device.create_buffer(BufferUploadMode::Static).unwrap();
+        device.allocate_buffer::<u32>(&buffer, BufferData::Uninitialized(2), BufferTarget::Vertex);
+        device.upload_to_buffer(&buffer, 4, &[7u32], BufferTarget::Vertex);
+        assert_eq!((device.live_texture_count(), device.live_framebuffer_count()), (1, 1));
+
+        let program = device.create_program_from_shader_names(&resources,
+                                                              "blit",
+                                                              ProgramKind::Raster {
+                                                                  vertex: "blit",
+                                                                  fragment: "blit",
+                                                              }).unwrap();
+        let vertex_array = device.create_vertex_array().unwrap();
+        let color_uniform = device.get_uniform(&program, "Color");
+        let target = RenderTarget::Framebuffer(&framebuffer);
+        device.begin_commands();
+        device.draw_elements(6, &RenderState {
+            target: &target,
+            program: &program,
+            vertex_array: &vertex_array,
+            primitive: Primitive::Triangles,
+            uniforms: &[(&color_uniform, UniformData::Float(0.5))],
+            textures: &[],
+            images: &[],
+            storage_buffers: &[],
+            viewport: RectI::new(vec2i(0, 0), vec2i(4, 4)),
+            options: RenderOptions::default(),
+        });
+        device.end_commands();
+
+        let draws = device.draw_calls();
+        assert_eq!(draws.len(), 1);
+        assert_eq!(draws[0].program, "blit");
+        assert_eq!(draws[0].target, RecordedTarget::Framebuffer(framebuffer.id()));
+        assert_eq!(draws[0].index_count, 6);
+        assert_eq!(draws[0].uniforms[0].0, "Color");
+        assert_eq!(device.program_names(), vec!["blit".to_owned()]);
+
+        // Buffer contents survive the round trip, while pixels read back as zero.
+        let receiver = device.read_buffer(&buffer, BufferTarget::Vertex, 4..8).unwrap();
+        assert_eq!(device.recv_buffer(&receiver), 7u32.to_ne_bytes().to_vec());
+        let receiver = device.read_pixels(&target, RectI::new(vec2i(0, 0), vec2i(2, 1)));
+        match device.recv_texture_data(&receiver) {
+            TextureData::U8(pixels) => assert_eq!(pixels, vec![0; 8]),
+            _ => panic!("Expected 8-bit pixels!"),
+        }
+        match device.take_commands().last() {
+            Some(RecordedCommand::ReadPixels { target: RecordedTarget::Framebuffer(id), .. }) => {
+                assert_eq!(*id, framebuffer.id())
+            }
+            _ => panic!("Expected the pixel read to be recorded last!"),
+        }
+        assert!(device.commands().is_empty());
+
+        drop(framebuffer);
+        assert_eq!((device.live_texture_count(), device.live_framebuffer_count()), (0, 0));
+    }
+
+    #[test]
+    fn test_failures() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let resources = EmbeddedResourceLoader::new();
+        assert!(device.create_shader(&resources, "bound", ShaderKind::Compute).is_err());
+
+        device.set_out_of_memory(true);
+        assert_eq!(device.create_texture(TextureFormat::R8, vec2i(1, 1)).err(),
+                   Some(DeviceError::OutOfMemory));
+        device.set_out_of_memory(false);
+
+        device.lose_device();
+        assert!(device.is_device_lost());
+        assert_eq!(device.create_buffer(BufferUploadMode::Dynamic).err(),
+                   Some(DeviceError::DeviceLost));
+    }
+}
@@ -37,6 +37,7 @@ use metal::{MTLBlitOption, MTLClearColor, MTLColorWriteMask, MTLCompareFunction,
 use metal::{MTLDataType, MTLDevice, MTLIndexType, MTLLoadAction, MTLOrigin, MTLPixelFormat};
 use metal::{MTLPrimitiveType, MTLRegion, MTLRenderPipelineReflection, MTLRenderPipelineState};
 use metal::{MTLResourceOptions, MTLResourceUsage, MTLSamplerAddressMode, MTLSamplerMinMagFilter};
+use metal::MTLScissorRect;
 use metal::{MTLSize, MTLStencilOperation, MTLStorageMode, MTLStoreAction, MTLTextureType};
 use metal::{MTLTextureUsage, MTLVertexFormat, MTLVertexStepFunction, MTLViewport};
 use metal::{RenderCommandEncoder, RenderCommandEncoderRef, RenderPassDescriptor};
@@ -49,7 +50,8 @@ use objc::runtime::{Class, Object};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceError};
+use pathfinder_gpu::FeatureLevel;
 use pathfinder_gpu::{ImageAccess, Primitive, ProgramKind, RenderState, RenderTarget, ShaderKind};
 use pathfinder_gpu::{StencilFunc, TextureData, TextureDataRef, TextureFormat};
 use pathfinder_gpu::{TextureSamplingFlags, UniformData, VertexAttrClass};
@@ -364,45 +366,72 @@ impl Device for MetalDevice {
         FeatureLevel::D3D11
     }
 
+    #[inline]
+    fn supports_texture_format(&self, _: TextureFormat) -> bool {
+        true
+    }
+
+    #[inline]
+    fn is_device_lost(&self) -> bool {
+        // Metal only reports device removal through system notifications, which we don't
+        // subscribe to, so the device is assumed to stay alive.
+        false
+    }
+
     // TODO: Add texture usage hint.
-    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> MetalTexture {
+    fn create_texture(&self, format: TextureFormat, size: Vector2I)
+                      -> Result<MetalTexture, DeviceError> {
         let descriptor = create_texture_descriptor(format, size);
         descriptor.set_storage_mode(MTLStorageMode::Private);
-        MetalTexture {
-            private_texture: self.device.new_texture(&descriptor),
+        let private_texture = self.device.new_texture(&descriptor);
+        if private_texture.as_ptr().is_null() {
+            return Err(DeviceError::OutOfMemory);
+        }
+        Ok(MetalTexture {
+            private_texture,
             shared_buffer: RefCell::new(None),
             sampling_flags: Cell::new(TextureSamplingFlags::empty()),
-        }
+        })
     }
 
     fn create_texture_from_data(&self, format: TextureFormat, size: Vector2I, data: TextureDataRef)
-                                -> MetalTexture {
-        let texture = self.create_texture(format, size);
+                                -> Result<MetalTexture, DeviceError> {
+        let texture = self.create_texture(format, size)?;
         self.upload_to_texture(&texture, RectI::new(Vector2I::default(), size), data);
-        texture
+        Ok(texture)
     }
 
-    fn create_shader_from_source(&self, name: &str, source: &[u8], _: ShaderKind) -> MetalShader {
-        let source = String::from_utf8(source.to_vec()).expect("Source wasn't valid UTF-8!");
+    fn create_shader_from_source(&self, name: &str, source: &[u8], _: ShaderKind)
+                                 -> Result<MetalShader, DeviceError> {
+        let source = String::from_utf8(source.to_vec()).map_err(|_| {
+            DeviceError::InvalidResource {
+                name: name.to_owned(),
+                reason: "source wasn't valid UTF-8".to_owned(),
+            }
+        })?;
 
         let compile_options = CompileOptions::new();
-        let library = self.device.new_library_with_source(&source, &compile_options).unwrap();
-        let function = library.get_function("main0", None).unwrap();
-
-        MetalShader {
+        let library = self.device.new_library_with_source(&source, &compile_options).map_err(|log| {
+            DeviceError::ShaderCompilationFailed { name: name.to_owned(), log }
+        })?;
+        let function = library.get_function("main0", None).map_err(|log| {
+            DeviceError::ShaderCompilationFailed { name: name.to_owned(), log }
+        })?;
+
+        Ok(MetalShader {
             library,
             function,
             name: name.to_owned(),
             arguments: RefCell::new(None),
-        }
+        })
     }
 
-    fn create_vertex_array(&self) -> MetalVertexArray {
-        MetalVertexArray {
+    fn create_vertex_array(&self) -> Result<MetalVertexArray, DeviceError> {
+        Ok(MetalVertexArray {
             descriptor: VertexDescriptor::new().retain(),
             vertex_buffers: RefCell::new(vec![]),
             index_buffer: RefCell::new(None),
-        }
+        })
     }
 
     fn bind_buffer(&self,
@@ -424,14 +453,14 @@ impl Device for MetalDevice {
                                    _: &dyn ResourceLoader,
                                    _: &str,
                                    shaders: ProgramKind<MetalShader>)
-                                   -> MetalProgram {
+                                   -> Result<MetalProgram, DeviceError> {
         match shaders {
             ProgramKind::Raster { vertex: vertex_shader, fragment: fragment_shader } => {
-                MetalProgram::Raster(MetalRasterProgram { vertex_shader, fragment_shader })
+                Ok(MetalProgram::Raster(MetalRasterProgram { vertex_shader, fragment_shader }))
             }
             ProgramKind::Compute(shader) => {
                 let local_size = MTLSize { width: 0, height: 0, depth: 0 };
-                MetalProgram::Compute(MetalComputeProgram { shader, local_size })
+                Ok(MetalProgram::Compute(MetalComputeProgram { shader, local_size }))
             }
         }
     }
@@ -588,19 +617,20 @@ impl Device for MetalDevice {
         layout.set_stride(descriptor.stride as u64);
     }
 
-    fn create_framebuffer(&self, texture: MetalTexture) -> MetalFramebuffer {
-        MetalFramebuffer(texture)
+    fn create_framebuffer(&self, texture: MetalTexture)
+                          -> Result<MetalFramebuffer, DeviceError> {
+        Ok(MetalFramebuffer(texture))
     }
 
-    fn create_buffer(&self, mode: BufferUploadMode) -> MetalBuffer {
-        MetalBuffer {
+    fn create_buffer(&self, mode: BufferUploadMode) -> Result<MetalBuffer, DeviceError> {
+        Ok(MetalBuffer {
             allocations: Rc::new(RefCell::new(BufferAllocations {
                 private: None,
                 shared: None,
                 byte_size: 0,
             })),
             mode,
-        }
+        })
     }
 
     fn allocate_buffer<T>(&self,
@@ -716,6 +746,7 @@ impl Device for MetalDevice {
             MTLPixelFormat::R8Unorm => TextureFormat::R8,
             MTLPixelFormat::R16Float => TextureFormat::R16F,
             MTLPixelFormat::RGBA8Unorm => TextureFormat::RGBA8,
+            MTLPixelFormat::BGRA8Unorm => TextureFormat::BGRA8,
             MTLPixelFormat::RGBA16Float => TextureFormat::RGBA16F,
             MTLPixelFormat::RGBA32Float => TextureFormat::RGBA32F,
             _ => panic!("Unexpected Metal texture format!"),
@@ -814,7 +845,7 @@ impl Device for MetalDevice {
     }
 
     fn read_buffer(&self, src_buffer: &MetalBuffer, _: BufferTarget, range: Range<usize>)
-                   -> MetalBufferDataReceiver {
+                   -> Result<MetalBufferDataReceiver, DeviceError> {
         let buffer_data_receiver;
         {
             let command_buffers = self.command_buffers.borrow();
@@ -860,7 +891,7 @@ impl Device for MetalDevice {
         self.end_commands();
         self.begin_commands();
 
-        buffer_data_receiver
+        Ok(buffer_data_receiver)
     }
 
     fn try_recv_buffer(&self, buffer_data_receiver: &MetalBufferDataReceiver) -> Option<Vec<u8>> {
@@ -992,7 +1023,7 @@ impl Device for MetalDevice {
         encoder.end_encoding();
     }
 
-    fn create_timer_query(&self) -> MetalTimerQuery {
+    fn create_timer_query(&self) -> Result<MetalTimerQuery, DeviceError> {
         let query = MetalTimerQuery(Arc::new(MetalTimerQueryInfo {
             mutex: Mutex::new(MetalTimerQueryData {
                 start_time: None,
@@ -1022,7 +1053,7 @@ impl Device for MetalDevice {
             query.cond.notify_all();
         }).copy());
 
-        query
+        Ok(query)
     }
 
     fn begin_timer_query(&self, query: &MetalTimerQuery) {
@@ -1090,14 +1121,15 @@ impl Device for MetalDevice {
         resources: &dyn ResourceLoader,
         name: &str,
         kind: ShaderKind,
-    ) -> Self::Shader {
+    ) -> Result<Self::Shader, DeviceError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/metal/{}.{}s.metal", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = resources.slurp(&path).map_err(|_| DeviceError::MissingResource(path))?;
+        self.create_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> MetalFence {
@@ -1348,6 +1380,9 @@ impl MetalDevice {
         }
 
         self.set_viewport(&encoder, &render_state.viewport);
+        if let Some(scissor) = render_state.options.scissor {
+            self.set_scissor(&encoder, &render_state.viewport, scissor);
+        }
 
         let program = match render_state.program {
             MetalProgram::Raster(ref raster_program) => raster_program,
@@ -1871,6 +1906,20 @@ impl MetalDevice {
         })
     }
 
+    fn set_scissor(&self, encoder: &RenderCommandEncoderRef, viewport: &RectI, scissor: RectI) {
+        // Metal requires the scissor rect to lie within the render target, and its origin is at
+        // the upper left like ours.
+        let scissor = RectI::new(viewport.origin() + scissor.origin(), scissor.size());
+        let scissor = scissor.intersection(*viewport)
+                             .unwrap_or_default();
+        encoder.set_scissor_rect(MTLScissorRect {
+            x: scissor.origin_x() as u64,
+            y: scissor.origin_y() as u64,
+            width: scissor.width() as u64,
+            height: scissor.height() as u64,
+        })
+    }
+
     fn synchronize_texture(&self, texture: &Texture, block: RcBlock<(*mut Object,), ()>) {
         {
             let command_buffers = self.command_buffers.borrow();
@@ -2125,10 +2174,7 @@ impl TextureFormatExt for TextureFormat {
             MTLPixelFormat::R8Unorm => Some(TextureFormat::R8),
             MTLPixelFormat::R16Float => Some(TextureFormat::R16F),
             MTLPixelFormat::RGBA8Unorm => Some(TextureFormat::RGBA8),
-            MTLPixelFormat::BGRA8Unorm => {
-                // FIXME(pcwalton): This is wrong! But it prevents a crash for now.
-                Some(TextureFormat::RGBA8)
-            }
+            MTLPixelFormat::BGRA8Unorm => Some(TextureFormat::BGRA8),
             MTLPixelFormat::RGBA16Float => Some(TextureFormat::RGBA16F),
             MTLPixelFormat::RGBA32Float => Some(TextureFormat::RGBA32F),
             _ => None,
@@ -2157,7 +2203,7 @@ impl MetalTextureDataReceiver {
         let format = format.expect("Unexpected framebuffer texture format!");
 
         let texture_data = match format {
-            TextureFormat::R8 | TextureFormat::RGBA8 => {
+            TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => {
                 let channels = format.channels();
                 let stride = size.x() as usize * channels;
                 let mut pixels = vec![0; stride * size.y() as usize];
@@ -2670,6 +2716,7 @@ fn create_texture_descriptor(format: TextureFormat, size: Vector2I) -> TextureDe
         TextureFormat::R8 => descriptor.set_pixel_format(MTLPixelFormat::R8Unorm),
         TextureFormat::R16F => descriptor.set_pixel_format(MTLPixelFormat::R16Float),
         TextureFormat::RGBA8 => descriptor.set_pixel_format(MTLPixelFormat::RGBA8Unorm),
+        TextureFormat::BGRA8 => descriptor.set_pixel_format(MTLPixelFormat::BGRA8Unorm),
         TextureFormat::RGBA16F => descriptor.set_pixel_format(MTLPixelFormat::RGBA16Float),
         TextureFormat::RGBA32F => descriptor.set_pixel_format(MTLPixelFormat::RGBA32Float),
     }
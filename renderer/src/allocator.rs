@@ -10,7 +10,7 @@
 
 //! A simple quadtree-based texture allocator.
 
-use crate::gpu_data::{TextureLocation, TexturePageId};
+use crate::gpu_data::{TextureLocation, TexturePageId, TexturePageUsage};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 
@@ -25,6 +25,11 @@ pub struct TextureAllocator {
 pub struct TexturePage {
     allocator: TexturePageAllocator,
     is_new: bool,
+    // Pages that have been freed and can be handed out again by `allocate()` or
+    // `allocate_image()`.
+    is_free: bool,
+    // Atlas pages only take allocations of the group they were created for.
+    group: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -49,8 +54,14 @@ enum TreeNode {
     Parent([Box<TreeNode>; 4]),
 }
 
+/// A texture allocation that `TextureAllocator::compact()` moved to a different place.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureMove {
+    pub old: TextureLocation,
+    pub new: TextureLocation,
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
-#[allow(dead_code)]
 pub enum AllocationMode {
     Atlas,
     OwnPage,
@@ -62,16 +73,29 @@ impl TextureAllocator {
         TextureAllocator { pages: vec![] }
     }
 
+    #[inline]
     pub fn allocate(&mut self, requested_size: Vector2I, mode: AllocationMode) -> TextureLocation {
-        // If requested, or if the image is too big, use a separate page.
-        if mode == AllocationMode::OwnPage ||
-                requested_size.x() > ATLAS_TEXTURE_LENGTH as i32 ||
+        match mode {
+            AllocationMode::Atlas => self.allocate_in_group(requested_size, 0),
+            AllocationMode::OwnPage => self.allocate_image(requested_size),
+        }
+    }
+
+    /// Allocates space in an atlas page that holds only allocations of the given group, or in a
+    /// page of its own if the requested size is too big for an atlas.
+    ///
+    /// `allocate()` with `AllocationMode::Atlas` uses group 0.
+    pub fn allocate_in_group(&mut self, requested_size: Vector2I, group: u32) -> TextureLocation {
+        if requested_size.x() > ATLAS_TEXTURE_LENGTH as i32 ||
                 requested_size.y() > ATLAS_TEXTURE_LENGTH as i32 {
             return self.allocate_image(requested_size);
         }
 
         // Try to add to each atlas.
         for (page_index, page) in self.pages.iter_mut().enumerate() {
+            if page.is_free || page.group != group {
+                continue;
+            }
             match page.allocator {
                 TexturePageAllocator::Image { .. } => {}
                 TexturePageAllocator::Atlas(ref mut allocator) => {
@@ -83,33 +107,164 @@ impl TextureAllocator {
         }
 
         // Add a new atlas.
-        let page = TexturePageId(self.pages.len() as u32);
         let mut allocator = TextureAtlasAllocator::new();
         let rect = allocator.allocate(requested_size).expect("Allocation failed!");
-        self.pages.push(TexturePage {
-            is_new: true,
-            allocator: TexturePageAllocator::Atlas(allocator),
-        });
+        let page = self.push_page(TexturePageAllocator::Atlas(allocator), group);
         TextureLocation { page, rect }
     }
 
     pub fn allocate_image(&mut self, requested_size: Vector2I) -> TextureLocation {
-        let page = TexturePageId(self.pages.len() as u32);
         let rect = RectI::new(Vector2I::default(), requested_size);
-        self.pages.push(TexturePage {
-            is_new: true,
-            allocator: TexturePageAllocator::Image { size: rect.size() },
-        });
+        let page = self.push_page(TexturePageAllocator::Image { size: rect.size() }, 0);
         TextureLocation { page, rect }
     }
 
-    pub fn page_size(&self, page_id: TexturePageId) -> Vector2I {
-        match self.pages[page_id.0 as usize].allocator {
-            TexturePageAllocator::Atlas(ref atlas) => Vector2I::splat(atlas.size as i32),
-            TexturePageAllocator::Image { size, .. } => size,
+    // Reuses a freed page if there is one, or appends a new page otherwise. The page is marked
+    // new, so that the renderer (re)allocates its texture, unless a freed page of the same size
+    // was reused. That way, rebuilding a scene doesn't reallocate the pages it just freed.
+    fn push_page(&mut self, allocator: TexturePageAllocator, group: u32) -> TexturePageId {
+        let mut new_page = TexturePage { is_new: true, is_free: false, allocator, group };
+        match self.pages.iter().position(|page| page.is_free) {
+            Some(page_index) => {
+                let old_page = &self.pages[page_index];
+                new_page.is_new = old_page.is_new ||
+                    old_page.allocator.size() != new_page.allocator.size();
+                self.pages[page_index] = new_page;
+                TexturePageId(page_index as u32)
+            }
+            None => {
+                self.pages.push(new_page);
+                TexturePageId(self.pages.len() as u32 - 1)
+            }
+        }
+    }
+
+    /// Returns the space at `location`, as returned by `allocate()`, to the allocator.
+    ///
+    /// Space in atlas pages is reclaimed immediately, and atlas pages that become empty are freed
+    /// along with image pages. Freed pages are reused by later allocations.
+    pub fn free(&mut self, location: TextureLocation) {
+        let page = &mut self.pages[location.page.0 as usize];
+        debug_assert!(!page.is_free);
+        match page.allocator {
+            TexturePageAllocator::Atlas(ref mut allocator) => {
+                allocator.free(location.rect);
+                page.is_free = allocator.is_empty();
+            }
+            TexturePageAllocator::Image { .. } => page.is_free = true,
         }
     }
 
+    /// Moves allocations out of sparsely-used atlas pages into the free space of denser ones,
+    /// freeing the pages that end up empty.
+    ///
+    /// The caller is responsible for copying the texels of each returned move. Moves must be
+    /// applied in order, because a later move may read from a location written by an earlier one.
+    pub fn compact(&mut self) -> Vec<TextureMove> {
+        let mut atlas_page_indices: Vec<usize> = (0..self.pages.len()).filter(|&page_index| {
+            let page = &self.pages[page_index];
+            match page.allocator {
+                TexturePageAllocator::Atlas(_) => !page.is_free,
+                TexturePageAllocator::Image { .. } => false,
+            }
+        }).collect();
+        atlas_page_indices.sort_by_key(|&page_index| self.page_used_area(page_index));
+
+        let mut moves = vec![];
+        for (source_index, &source_page_index) in atlas_page_indices.iter().enumerate() {
+            let mut rects = match self.pages[source_page_index].allocator {
+                TexturePageAllocator::Atlas(ref allocator) => allocator.allocated_rects(),
+                TexturePageAllocator::Image { .. } => unreachable!(),
+            };
+            rects.sort_by_key(|rect| -rect.width());
+
+            // Try to fit everything into the pages of the same group that are denser than this
+            // one. Work on copies so that nothing changes if the page can't be emptied entirely.
+            let group = self.pages[source_page_index].group;
+            let mut dest_pages: Vec<(usize, TextureAtlasAllocator)> =
+                atlas_page_indices[(source_index + 1)..].iter().filter(|&&page_index| {
+                    self.pages[page_index].group == group
+                }).map(|&page_index| {
+                    match self.pages[page_index].allocator {
+                        TexturePageAllocator::Atlas(ref allocator) => {
+                            (page_index, allocator.clone())
+                        }
+                        TexturePageAllocator::Image { .. } => unreachable!(),
+                    }
+                }).collect();
+
+            let mut page_moves = vec![];
+            for &rect in &rects {
+                let new_location = dest_pages.iter_mut().filter_map(|(page_index, allocator)| {
+                    allocator.allocate(rect.size()).map(|new_rect| {
+                        TextureLocation { page: TexturePageId(*page_index as u32), rect: new_rect }
+                    })
+                }).next();
+                match new_location {
+                    None => break,
+                    Some(new) => {
+                        let old = TextureLocation {
+                            page: TexturePageId(source_page_index as u32),
+                            rect,
+                        };
+                        page_moves.push(TextureMove { old, new });
+                    }
+                }
+            }
+            if page_moves.len() < rects.len() {
+                continue;
+            }
+
+            for (page_index, allocator) in dest_pages {
+                self.pages[page_index].allocator = TexturePageAllocator::Atlas(allocator);
+            }
+            let source_page = &mut self.pages[source_page_index];
+            source_page.allocator = TexturePageAllocator::Atlas(TextureAtlasAllocator::new());
+            source_page.is_free = true;
+            moves.extend(page_moves);
+        }
+        moves
+    }
+
+    /// Returns the number of texels in use and available for each page, for memory debugging.
+    ///
+    /// Freed pages are reported with no used area.
+    pub fn page_usage(&self) -> Vec<TexturePageUsage> {
+        (0..self.pages.len()).map(|page_index| {
+            let page = &self.pages[page_index];
+            let size = self.page_size(TexturePageId(page_index as u32));
+            let total_area = size.x() as u32 * size.y() as u32;
+            let used_area = self.page_used_area(page_index);
+            TexturePageUsage {
+                page: TexturePageId(page_index as u32),
+                size,
+                is_atlas: match page.allocator {
+                    TexturePageAllocator::Atlas(_) => true,
+                    TexturePageAllocator::Image { .. } => false,
+                },
+                is_free: page.is_free,
+                used_area,
+                free_area: total_area - used_area,
+            }
+        }).collect()
+    }
+
+    fn page_used_area(&self, page_index: usize) -> u32 {
+        let page = &self.pages[page_index];
+        if page.is_free {
+            return 0;
+        }
+        match page.allocator {
+            TexturePageAllocator::Atlas(ref allocator) => allocator.used_area(),
+            TexturePageAllocator::Image { size } => size.x() as u32 * size.y() as u32,
+        }
+    }
+
+    #[inline]
+    pub fn page_size(&self, page_id: TexturePageId) -> Vector2I {
+        self.pages[page_id.0 as usize].allocator.size()
+    }
+
     pub fn page_scale(&self, page_id: TexturePageId) -> Vector2F {
         vec2f(1.0, 1.0) / self.page_size(page_id).to_f32()
     }
@@ -128,6 +283,15 @@ impl TextureAllocator {
     }
 }
 
+impl TexturePageAllocator {
+    fn size(&self) -> Vector2I {
+        match *self {
+            TexturePageAllocator::Atlas(ref atlas) => Vector2I::splat(atlas.size as i32),
+            TexturePageAllocator::Image { size, .. } => size,
+        }
+    }
+}
+
 impl TextureAtlasAllocator {
     #[inline]
     fn new() -> TextureAtlasAllocator {
@@ -147,20 +311,28 @@ impl TextureAtlasAllocator {
     }
 
     #[inline]
-    #[allow(dead_code)]
     fn free(&mut self, rect: RectI) {
         let requested_length = rect.width() as u32;
         self.root.free(Vector2I::default(), self.size, rect.origin(), requested_length)
     }
 
     #[inline]
-    #[allow(dead_code)]
     fn is_empty(&self) -> bool {
         match self.root {
             TreeNode::EmptyLeaf => true,
             _ => false,
         }
     }
+
+    fn allocated_rects(&self) -> Vec<RectI> {
+        let mut rects = vec![];
+        self.root.collect_allocated_rects(Vector2I::default(), self.size, &mut rects);
+        rects
+    }
+
+    fn used_area(&self) -> u32 {
+        self.root.used_area(self.size)
+    }
 }
 
 impl TreeNode {
@@ -223,7 +395,6 @@ impl TreeNode {
         }
     }
 
-    #[allow(dead_code)]
     fn free(&mut self,
             this_origin: Vector2I,
             this_size: u32,
@@ -268,6 +439,38 @@ impl TreeNode {
         }
     }
 
+    fn collect_allocated_rects(&self,
+                               this_origin: Vector2I,
+                               this_size: u32,
+                               rects: &mut Vec<RectI>) {
+        match *self {
+            TreeNode::EmptyLeaf => {}
+            TreeNode::FullLeaf => {
+                rects.push(RectI::new(this_origin, Vector2I::splat(this_size as i32)));
+            }
+            TreeNode::Parent(ref kids) => {
+                let kid_size = this_size / 2;
+                let kid_offsets = [
+                    vec2i(0, 0),
+                    vec2i(kid_size as i32, 0),
+                    vec2i(0, kid_size as i32),
+                    Vector2I::splat(kid_size as i32),
+                ];
+                for (kid, &kid_offset) in kids.iter().zip(kid_offsets.iter()) {
+                    kid.collect_allocated_rects(this_origin + kid_offset, kid_size, rects);
+                }
+            }
+        }
+    }
+
+    fn used_area(&self, this_size: u32) -> u32 {
+        match *self {
+            TreeNode::EmptyLeaf => 0,
+            TreeNode::FullLeaf => this_size * this_size,
+            TreeNode::Parent(ref kids) => kids.iter().map(|kid| kid.used_area(this_size / 2)).sum(),
+        }
+    }
+
     fn merge_if_necessary(&mut self) {
         match *self {
             TreeNode::Parent(ref mut kids) => {
@@ -291,7 +494,7 @@ mod test {
     use quickcheck;
     use std::u32;
 
-    use super::TextureAtlasAllocator;
+    use super::{AllocationMode, TextureAllocator, TextureAtlasAllocator};
 
     #[test]
     fn test_allocation_and_freeing() {
@@ -324,4 +527,79 @@ mod test {
             true
         }
     }
+
+    #[test]
+    fn test_freed_pages_are_reused() {
+        let mut allocator = TextureAllocator::new();
+        let image = allocator.allocate(vec2i(2048, 2048), AllocationMode::OwnPage);
+        let tile = allocator.allocate(vec2i(64, 64), AllocationMode::Atlas);
+        assert_eq!(allocator.page_count(), 2);
+
+        // An atlas page that becomes empty is freed, and so can hold an image next.
+        allocator.free(tile);
+        let other_image = allocator.allocate(vec2i(512, 512), AllocationMode::OwnPage);
+        assert_eq!(other_image.page, tile.page);
+        allocator.free(image);
+        let other_tile = allocator.allocate(vec2i(64, 64), AllocationMode::Atlas);
+        assert_eq!(other_tile.page, image.page);
+        assert_eq!(allocator.page_count(), 2);
+
+        // A page that's reused at the same size keeps its texture, once it has one.
+        allocator.mark_page_as_allocated(other_tile.page);
+        allocator.free(other_tile);
+        let last_tile = allocator.allocate(vec2i(128, 128), AllocationMode::Atlas);
+        assert!(!allocator.page_is_new(last_tile.page));
+        assert!(allocator.page_is_new(other_image.page));
+    }
+
+    #[test]
+    fn test_compaction() {
+        let mut allocator = TextureAllocator::new();
+
+        // Fill two atlas pages, then free most of both.
+        let mut locations = vec![];
+        for _ in 0..32 {
+            locations.push(allocator.allocate(vec2i(256, 256), AllocationMode::Atlas));
+        }
+        assert_eq!(allocator.page_count(), 2);
+        for (index, &location) in locations.iter().enumerate() {
+            if index % 16 >= 2 {
+                allocator.free(location);
+            }
+        }
+
+        let moves = allocator.compact();
+        assert_eq!(moves.len(), 2);
+        for texture_move in &moves {
+            assert_ne!(texture_move.old.page, texture_move.new.page);
+            assert_eq!(texture_move.old.rect.size(), texture_move.new.rect.size());
+        }
+
+        let usage = allocator.page_usage();
+        let used_pages: Vec<_> = usage.iter().filter(|page_usage| !page_usage.is_free).collect();
+        assert_eq!(used_pages.len(), 1);
+        assert_eq!(used_pages[0].used_area, 4 * 256 * 256);
+        assert_eq!(used_pages[0].free_area, 12 * 256 * 256);
+
+        // Nothing more can be done.
+        assert!(allocator.compact().is_empty());
+    }
+
+    #[test]
+    fn test_groups_get_separate_pages() {
+        let mut allocator = TextureAllocator::new();
+        let tile = allocator.allocate(vec2i(64, 64), AllocationMode::Atlas);
+        let first = allocator.allocate_in_group(vec2i(64, 64), 1);
+        let second = allocator.allocate_in_group(vec2i(64, 64), 2);
+        assert_ne!(first.page, tile.page);
+        assert_ne!(second.page, first.page);
+        assert_eq!(allocator.allocate_in_group(vec2i(64, 64), 1).page, first.page);
+        assert_eq!(allocator.allocate(vec2i(64, 64), AllocationMode::Atlas).page, tile.page);
+
+        // Compaction doesn't move allocations across groups.
+        allocator.free(tile);
+        let other_tile = allocator.allocate(vec2i(64, 64), AllocationMode::Atlas);
+        assert!(allocator.compact().is_empty());
+        assert_ne!(other_tile.page, first.page);
+    }
 }
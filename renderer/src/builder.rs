@@ -15,14 +15,16 @@ use crate::gpu::blend::BlendModeExt;
 use crate::gpu::options::RendererLevel;
 use crate::gpu_data::{AlphaTileId, BackdropInfoD3D11, Clip, ClippedPathInfo, DiceMetadataD3D11};
 use crate::gpu_data::{DrawTileBatch, DrawTileBatchD3D9, DrawTileBatchD3D11, Fill, PathBatchIndex};
-use crate::gpu_data::{PathSource, PrepareTilesInfoD3D11};
+use crate::gpu_data::{PathSource, PathTransformD3D11, PrepareTilesInfoD3D11};
 use crate::gpu_data::{PropagateMetadataD3D11, RenderCommand, SegmentIndicesD3D11, SegmentsD3D11};
 use crate::gpu_data::{TileBatchDataD3D11, TileBatchId, TileBatchTexture};
 use crate::gpu_data::{TileObjectPrimitive, TilePathInfoD3D11};
 use crate::options::{PrepareMode, PreparedBuildOptions, PreparedRenderTransform};
 use crate::paint::{PaintId, PaintInfo, PaintMetadata};
+use crate::path_cache::{CachedBuildResult, CachedPath};
+use crate::profile::{self, ProfileCategory};
 use crate::scene::{ClipPathId, DisplayItem, DrawPath, DrawPathId, LastSceneInfo, PathId};
-use crate::scene::{Scene, SceneSink};
+use crate::scene::{Scene, SceneSink, SymbolId};
 use crate::tile_map::DenseTileMap;
 use crate::tiler::Tiler;
 use crate::tiles::{self, DrawTilingPathInfo, TILE_HEIGHT, TILE_WIDTH, TilingPathInfo};
@@ -34,7 +36,7 @@ use pathfinder_content::outline::{Outline, PointFlags};
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2I, vec2i};
+use pathfinder_geometry::vector::{Vector2I, vec2f, vec2i};
 use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::F32x4;
 use std::borrow::Cow;
@@ -50,7 +52,7 @@ const CURVE_IS_CUBIC:     u32 = 0x40000000;
 
 pub(crate) struct SceneBuilder<'a, 'b, 'c, 'd> {
     scene: &'a mut Scene,
-    built_options: &'b PreparedBuildOptions,
+    pub(crate) built_options: &'b PreparedBuildOptions,
     next_alpha_tile_indices: [AtomicUsize; ALPHA_TILE_LEVEL_COUNT],
     pub(crate) sink: &'c mut SceneSink<'d>,
 }
@@ -70,6 +72,7 @@ struct BuiltDrawPath {
     blend_mode: BlendMode,
     filter: Filter,
     color_texture: Option<TileBatchTexture>,
+    paint_mask_texture: Option<TileBatchTexture>,
     sampling_flags_1: TextureSamplingFlags,
     mask_0_fill_rule: FillRule,
     occludes: bool,
@@ -85,6 +88,7 @@ impl BuiltDrawPath {
             clip_path_id: path_object.clip_path(),
             filter: paint_metadata.filter(),
             color_texture: paint_metadata.tile_batch_texture(),
+            paint_mask_texture: paint_metadata.mask_tile_batch_texture(),
             sampling_flags_1: TextureSamplingFlags::empty(),
             mask_0_fill_rule: path_object.fill_rule(),
             blend_mode,
@@ -146,6 +150,8 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
 
     pub fn build<E>(&mut self, executor: &E) where E: Executor {
         let start_time = Instant::now();
+        let profiler = &self.built_options.profiler;
+        let _build_span = profile::span(profiler, ProfileCategory::Build, "Build", 0, None);
 
         // Send the start rendering command.
         let bounding_quad = self.built_options.bounding_quad();
@@ -160,6 +166,9 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             bounding_quad,
             path_count: total_path_count,
             needs_readable_framebuffer,
+            damage_rects: self.built_options.damage_rects(),
+            antialiasing_mode: self.built_options.antialiasing_mode,
+            subpixel_layout: self.built_options.subpixel_layout,
         });
 
         let prepare_mode = self.built_options.to_prepare_mode(self.sink.renderer_level);
@@ -170,11 +179,13 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         };
 
         // Build paint data.
+        let paint_span = profile::span(profiler, ProfileCategory::Build, "PaintInfo", 0, None);
         let PaintInfo {
             render_commands,
             paint_metadata,
             render_target_metadata: _,
-        } = self.scene.build_paint_info(render_transform);
+        } = self.scene.build_paint_info(render_transform, self.sink.renderer_level);
+        drop(paint_span);
         for render_command in render_commands {
             self.sink.listener.send(render_command);
         }
@@ -208,6 +219,7 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                 scene_epoch: self.scene.epoch(),
                 draw_segment_ranges: built_segments.draw_segment_ranges,
                 clip_segment_ranges: built_segments.clip_segment_ranges,
+                draw_segment_transforms: built_segments.draw_segment_transforms,
             });
         }
 
@@ -227,37 +239,122 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         let draw_path_count = self.scene.draw_paths().len();
         let effective_view_box = self.scene.effective_view_box(self.built_options);
 
-        let built_clip_paths = executor.build_vector(clip_path_count, |path_index| {
+        let use_cache = self.sink.path_cache.prepare(&self.scene,
+                                                     self.built_options,
+                                                     prepare_mode,
+                                                     effective_view_box);
+
+        let profiler = &self.built_options.profiler;
+        let clip_span =
+            profile::span(profiler, ProfileCategory::Build, "TileClipPaths", 0, None);
+        let clip_results = executor.build_vector(clip_path_count, |path_index| {
+            let _span = profile::span(profiler,
+                                      ProfileCategory::Build,
+                                      "TileClipPath",
+                                      E::current_thread_index(),
+                                      Some(path_index as u32));
             self.build_clip_path_on_cpu(PathBuildParams {
                 path_id: PathId(path_index as u32),
                 view_box: effective_view_box,
                 prepare_mode: *prepare_mode,
                 built_options: &self.built_options,
                 scene: &self.scene,
+                use_cache,
             })
         });
+        drop(clip_span);
+
+        let mut built_clip_paths = Vec::with_capacity(clip_path_count);
+        let mut clip_alpha_tile_ids = Vec::with_capacity(clip_path_count);
+        let mut new_clip_entries = Vec::with_capacity(clip_path_count);
+        for clip_result in clip_results {
+            built_clip_paths.push(clip_result.built_path);
+            clip_alpha_tile_ids.push(clip_result.alpha_tile_ids);
+            new_clip_entries.push(clip_result.new_entry);
+        }
 
-        let built_draw_paths = executor.build_vector(draw_path_count, |path_index| {
-            self.build_draw_path_on_cpu(DrawPathBuildParams {
+        // Symbol instances that share a mask with an earlier instance are skipped here and
+        // derived from that instance afterward.
+        let shared_instances = self.find_shared_instances(prepare_mode, effective_view_box);
+        let draw_span =
+            profile::span(profiler, ProfileCategory::Build, "TileDrawPaths", 0, None);
+        let draw_results = executor.build_vector(draw_path_count, |path_index| {
+            if let Some(Some(_)) = shared_instances.get(path_index) {
+                return None;
+            }
+            let _span = profile::span(profiler,
+                                      ProfileCategory::Build,
+                                      "TileDrawPath",
+                                      E::current_thread_index(),
+                                      Some(path_index as u32));
+            Some(self.build_draw_path_on_cpu(DrawPathBuildParams {
                 path_build_params: PathBuildParams {
                     path_id: PathId(path_index as u32),
                     view_box: effective_view_box,
                     prepare_mode: *prepare_mode,
                     built_options: &self.built_options,
                     scene: &self.scene,
+                    use_cache,
                 },
                 paint_metadata: &paint_metadata,
                 built_clip_paths: &built_clip_paths,
-            })
+                clip_alpha_tile_ids: &clip_alpha_tile_ids,
+            }))
         });
+        drop(draw_span);
+
+        let mut built_draw_paths: Vec<BuiltDrawPath> = Vec::with_capacity(draw_path_count);
+        let mut new_draw_entries = Vec::with_capacity(draw_path_count);
+        for (path_index, draw_result) in draw_results.into_iter().enumerate() {
+            let (built_draw_path, new_entry) = match draw_result {
+                Some(draw_result) => draw_result,
+                None => {
+                    let (source_index, tile_offset) = shared_instances[path_index].unwrap();
+                    let path_id = PathId(path_index as u32);
+                    let path_object = self.scene.get_draw_path(path_id.to_draw_path_id());
+                    let built_path = share_instance_tiles(&built_draw_paths[source_index].path,
+                                                          path_id,
+                                                          path_object,
+                                                          tile_offset);
+                    let paint_metadata = &paint_metadata[path_object.paint().0 as usize];
+                    (BuiltDrawPath::new(built_path, path_object, paint_metadata), None)
+                }
+            };
+            built_draw_paths.push(built_draw_path);
+            new_draw_entries.push(new_entry);
+        }
+
+        if use_cache {
+            self.sink.path_cache.update(&self.scene, new_clip_entries, new_draw_entries);
+        }
 
         BuiltPaths { draw: built_draw_paths }
     }
 
-    fn build_clip_path_on_cpu(&self, params: PathBuildParams) -> BuiltPath {
-        let PathBuildParams { path_id, view_box, built_options, scene, prepare_mode } = params;
+    fn build_clip_path_on_cpu(&self, params: PathBuildParams) -> CachedBuildResult {
+        let PathBuildParams {
+            path_id,
+            view_box,
+            built_options,
+            scene,
+            prepare_mode,
+            use_cache,
+        } = params;
+
+        let use_cache = use_cache && !scene.is_layer_clip_path(path_id.to_clip_path_id());
+        if use_cache {
+            if let Some(cached_path) = self.sink.path_cache.get_clip_path(path_id) {
+                let (built_path, fills, alpha_tile_ids) =
+                    cached_path.instantiate(self, path_id, None);
+                self.send_fills(fills);
+                return CachedBuildResult { built_path, alpha_tile_ids, new_entry: None };
+            }
+        }
+
         let path_object = &scene.get_clip_path(path_id.to_clip_path_id());
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
+        let outline = scene.apply_render_options(path_object.outline(),
+                                                 Transform2F::default(),
+                                                 built_options);
 
         let mut tiler = Tiler::new(self,
                                    path_id,
@@ -269,11 +366,12 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                                    TilingPathInfo::Clip);
 
         tiler.generate_tiles();
-        self.send_fills(tiler.object_builder.fills);
-        tiler.object_builder.built_path
+        let ObjectBuilder { built_path, fills, .. } = tiler.object_builder;
+        self.finish_cpu_path(built_path, fills, None, use_cache)
     }
 
-    fn build_draw_path_on_cpu(&self, params: DrawPathBuildParams) -> BuiltDrawPath {
+    fn build_draw_path_on_cpu(&self, params: DrawPathBuildParams)
+                              -> (BuiltDrawPath, Option<CachedPath>) {
         let DrawPathBuildParams {
             path_build_params: PathBuildParams {
                 path_id,
@@ -281,16 +379,47 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                 built_options,
                 prepare_mode,
                 scene,
+                use_cache,
             },
             paint_metadata,
             built_clip_paths,
+            clip_alpha_tile_ids,
         } = params;
 
         let path_object = scene.get_draw_path(path_id.to_draw_path_id());
-        let outline = scene.apply_render_options(path_object.outline(), built_options);
-
         let paint_id = path_object.paint();
         let paint_metadata = &paint_metadata[paint_id.0 as usize];
+        let clip_alpha_tile_ids = path_object.clip_path().map(|clip_path_id| {
+            &clip_alpha_tile_ids[clip_path_id.0 as usize][..]
+        });
+
+        // Clip paths made for paths in layers aren't cached, and don't report their alpha tiles,
+        // so the paths they clip can't be cached either.
+        let use_cache = use_cache && !path_object.clip_path().map_or(false, |clip_path_id| {
+            scene.is_layer_clip_path(clip_path_id)
+        });
+        if use_cache {
+            if let Some(cached_path) = self.sink.path_cache.get_draw_path(scene, path_id) {
+                let (mut built_path, fills, _) =
+                    cached_path.instantiate(self, path_id, clip_alpha_tile_ids);
+                self.send_fills(fills);
+
+                // Paints and clip paths made for the build may have different IDs this time.
+                built_path.paint_id = paint_id;
+                built_path.clip_path_id = path_object.clip_path();
+                if let BuiltPathData::CPU(ref mut data) = built_path.data {
+                    for tile in &mut data.tiles.data {
+                        tile.color = paint_id.0;
+                    }
+                }
+                let built_draw_path = BuiltDrawPath::new(built_path, path_object, paint_metadata);
+                return (built_draw_path, None);
+            }
+        }
+
+        let outline = scene.apply_render_options(path_object.outline(),
+                                                 path_object.layer_transform(),
+                                                 built_options);
 
         let mut tiler = Tiler::new(self,
                                    path_id,
@@ -307,9 +436,95 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
         }));
 
         tiler.generate_tiles();
-        self.send_fills(tiler.object_builder.fills);
+        let ObjectBuilder { built_path, fills, .. } = tiler.object_builder;
+        let CachedBuildResult { built_path, new_entry, .. } =
+            self.finish_cpu_path(built_path, fills, clip_alpha_tile_ids, use_cache);
+
+        (BuiltDrawPath::new(built_path, path_object, paint_metadata), new_entry)
+    }
+
+    // Finds symbol instances whose masks are identical to those of an earlier instance, up to a
+    // whole number of tiles. That's the case when both instances draw the same symbol with the
+    // same device-space linear transform and the same subtile position, and tiling doesn't clip
+    // either of them against the view box.
+    //
+    // Returns, for each draw path, the index of the draw path to take the mask from and the
+    // offset in tiles to apply to it, or an empty vector if no sharing is possible.
+    fn find_shared_instances(&self, prepare_mode: &PrepareMode, view_box: RectF)
+                             -> Vec<Option<(usize, Vector2I)>> {
+        let render_transform = match (prepare_mode, &self.built_options.transform) {
+            (&PrepareMode::CPU, &PreparedRenderTransform::None) => Transform2F::default(),
+            (&PrepareMode::CPU, &PreparedRenderTransform::Transform2D(transform)) => transform,
+            _ => return vec![],
+        };
+        if self.built_options.subpixel_aa_enabled {
+            return vec![];
+        }
+
+        let inner_view_box = view_box.contract(self.built_options.dilation + vec2f(1.0, 1.0));
+        let phase_size = vec2i(TILE_WIDTH as i32, TILE_HEIGHT as i32) * SUBTILE_PHASE_SCALE;
+        let mut sources = FxHashMap::default();
+        self.scene.draw_paths().iter().enumerate().map(|(path_index, draw_path)| {
+            let instance = draw_path.instance()?;
+            if draw_path.clip_path().is_some() || draw_path.blend_mode().is_destructive() {
+                return None;
+            }
+            let path_transform = render_transform * draw_path.layer_transform();
+            if !inner_view_box.contains_rect(path_transform * draw_path.outline().bounds()) {
+                return None;
+            }
+
+            let transform = path_transform * instance.transform;
+            let position = (transform.vector * SUBTILE_PHASE_SCALE as f32).round().to_i32();
+            let phase = vec2i(position.x().rem_euclid(phase_size.x()),
+                              position.y().rem_euclid(phase_size.y()));
+            let tile_position = vec2i((position.x() - phase.x()) / phase_size.x(),
+                                      (position.y() - phase.y()) / phase_size.y());
+            let key = SharedInstanceKey {
+                symbol: instance.symbol,
+                matrix: [
+                    transform.m11().to_bits(),
+                    transform.m12().to_bits(),
+                    transform.m21().to_bits(),
+                    transform.m22().to_bits(),
+                ],
+                phase: (phase.x(), phase.y()),
+                even_odd: draw_path.fill_rule() == FillRule::EvenOdd,
+            };
+            match sources.get(&key) {
+                Some(&(source_index, source_tile_position)) => {
+                    Some((source_index, tile_position - source_tile_position))
+                }
+                None => {
+                    sources.insert(key, (path_index, tile_position));
+                    None
+                }
+            }
+        }).collect()
+    }
+
+    // Sends the fills for a freshly tiled path and, if caching, creates a cache entry for it.
+    fn finish_cpu_path(&self,
+                       built_path: BuiltPath,
+                       fills: Vec<Fill>,
+                       clip_alpha_tile_ids: Option<&[AlphaTileId]>,
+                       use_cache: bool)
+                       -> CachedBuildResult {
+        if !use_cache {
+            self.send_fills(fills);
+            return CachedBuildResult { built_path, alpha_tile_ids: vec![], new_entry: None };
+        }
+
+        let (cached_path, alpha_tile_ids) = CachedPath::new(&built_path,
+                                                            &fills,
+                                                            clip_alpha_tile_ids);
+        self.send_fills(fills);
+        CachedBuildResult { built_path, alpha_tile_ids, new_entry: cached_path }
+    }
 
-        BuiltDrawPath::new(tiler.object_builder.built_path, path_object, paint_metadata)
+    #[inline]
+    pub(crate) fn allocate_alpha_tile_id(&self) -> AlphaTileId {
+        AlphaTileId::new(&self.next_alpha_tile_indices, 0)
     }
 
     fn send_fills(&self, fills: Vec<Fill>) {
@@ -322,24 +537,37 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
                           paint_metadata: &[PaintMetadata],
                           prepare_mode: &PrepareMode,
                           built_paths: Option<BuiltPaths>) {
-        let mut tile_batch_builder = TileBatchBuilder::new(&prepare_mode, built_paths);
+        let mut tile_batch_builder = TileBatchBuilder::new(&prepare_mode,
+                                                           self.built_options.flattening_tolerance,
+                                                           built_paths);
 
         // Prepare display items.
+        let mut render_target_depth = 0;
         for display_item in self.scene.display_list() {
             match *display_item {
                 DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_depth += 1;
                     tile_batch_builder.draw_commands
                                       .push(RenderCommand::PushRenderTarget(render_target_id))
                 }
                 DisplayItem::PopRenderTarget => {
+                    render_target_depth -= 1;
                     tile_batch_builder.draw_commands.push(RenderCommand::PopRenderTarget)
                 }
                 DisplayItem::DrawPaths(ref path_id_range) => {
+                    // Damage rects only apply to the destination framebuffer. Render targets are
+                    // always redrawn in full.
+                    let damage_tile_rects = if render_target_depth == 0 {
+                        &self.built_options.damage_tile_rects[..]
+                    } else {
+                        &[]
+                    };
                     tile_batch_builder.build_tile_batches_for_draw_path_display_item(
                         &self.scene,
                         &self.sink,
                         self.built_options,
                         path_id_range.start..path_id_range.end,
+                        damage_tile_rects,
                         paint_metadata,
                         prepare_mode);
                 }
@@ -359,6 +587,8 @@ impl<'a, 'b, 'c, 'd> SceneBuilder<'a, 'b, 'c, 'd> {
             RendererLevel::D3D11 => {}
         }
 
+        let profiler = &self.built_options.profiler;
+        let _span = profile::span(profiler, ProfileCategory::Build, "BuildBatches", 0, None);
         self.build_tile_batches(paint_metadata, prepare_mode, built_paths);
     }
 
@@ -396,12 +626,14 @@ struct PathBuildParams<'a> {
     built_options: &'a PreparedBuildOptions,
     prepare_mode: PrepareMode,
     scene: &'a Scene,
+    use_cache: bool,
 }
 
 struct DrawPathBuildParams<'a> {
     path_build_params: PathBuildParams<'a>,
     paint_metadata: &'a [PaintMetadata],
     built_clip_paths: &'a [BuiltPath],
+    clip_alpha_tile_ids: &'a [Vec<AlphaTileId>],
 }
 
 impl BuiltPath {
@@ -483,6 +715,51 @@ impl BuiltPath {
     }
 }
 
+// Symbol instance translations are compared in units of 1/256 of a pixel, the precision of fills.
+const SUBTILE_PHASE_SCALE: i32 = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct SharedInstanceKey {
+    symbol: SymbolId,
+    matrix: [u32; 4],
+    phase: (i32, i32),
+    even_odd: bool,
+}
+
+// Makes a copy of a symbol instance's tiles for another instance of the same symbol, offset by
+// `tile_offset`. The copy reuses the alpha tiles of the original, so it needs no fills.
+fn share_instance_tiles(source: &BuiltPath,
+                        path_id: PathId,
+                        path_object: &DrawPath,
+                        tile_offset: Vector2I)
+                        -> BuiltPath {
+    let tiling_path_info = TilingPathInfo::Draw(DrawTilingPathInfo {
+        paint_id: path_object.paint(),
+        blend_mode: path_object.blend_mode(),
+        clip_path_id: None,
+        fill_rule: path_object.fill_rule(),
+    });
+    let ctrl_byte = tiling_path_info.to_ctrl();
+    let paint_id = path_object.paint();
+
+    let mut built_path = source.clone();
+    built_path.tile_bounds = RectI::new(source.tile_bounds.origin() + tile_offset,
+                                        source.tile_bounds.size());
+    built_path.ctrl_byte = ctrl_byte;
+    built_path.paint_id = paint_id;
+    if let BuiltPathData::CPU(ref mut data) = built_path.data {
+        data.tiles.rect = built_path.tile_bounds;
+        for tile in &mut data.tiles.data {
+            tile.tile_x += tile_offset.x() as i16;
+            tile.tile_y += tile_offset.y() as i16;
+            tile.path_id = path_id;
+            tile.color = paint_id.0;
+            tile.ctrl = ctrl_byte;
+        }
+    }
+    built_path
+}
+
 // Utilities for built objects
 
 impl ObjectBuilder {
@@ -567,7 +844,7 @@ impl ObjectBuilder {
             return alpha_tile_id;
         }
 
-        let alpha_tile_id = AlphaTileId::new(&scene_builder.next_alpha_tile_indices, 0);
+        let alpha_tile_id = scene_builder.allocate_alpha_tile_id();
         tiles.data[local_tile_index].alpha_tile_id = alpha_tile_id;
         alpha_tile_id
     }
@@ -614,7 +891,10 @@ impl ObjectBuilder {
 }
 
 impl TileBatchDataD3D11 {
-    fn new(batch_id: TileBatchId, mode: &PrepareMode, path_source: PathSource)
+    fn new(batch_id: TileBatchId,
+           mode: &PrepareMode,
+           flattening_tolerance: f32,
+           path_source: PathSource)
            -> TileBatchDataD3D11 {
         TileBatchDataD3D11 {
             batch_id,
@@ -629,8 +909,10 @@ impl TileBatchDataD3D11 {
                         backdrops: vec![],
                         propagate_metadata: vec![],
                         dice_metadata: vec![],
+                        path_transforms: vec![],
                         tile_path_info: vec![],
                         transform: Transform2F::default(),
+                        flattening_tolerance,
                     }
                 }
                 PrepareMode::GPU { ref transform } => {
@@ -638,8 +920,10 @@ impl TileBatchDataD3D11 {
                         backdrops: vec![],
                         propagate_metadata: vec![],
                         dice_metadata: vec![],
+                        path_transforms: vec![],
                         tile_path_info: vec![],
                         transform: *transform,
+                        flattening_tolerance,
                     }
                 }
             },
@@ -694,12 +978,19 @@ impl TileBatchDataD3D11 {
             PathSource::Clip => &last_scene.clip_segment_ranges,
         };
         let segment_range = &segment_ranges[global_path_id.0 as usize];
+        let segment_transform = match self.path_source {
+            PathSource::Draw => last_scene.draw_segment_transforms[global_path_id.0 as usize],
+            PathSource::Clip => Transform2F::default(),
+        };
         self.prepare_info.dice_metadata.push(DiceMetadataD3D11 {
             first_batch_segment_index: self.segment_count, 
             first_global_segment_index: segment_range.start,
             global_path_id,
             pad: 0,
         });
+        self.prepare_info
+            .path_transforms
+            .push(PathTransformD3D11::from_transform(&segment_transform));
         self.prepare_info.tile_path_info.push(TilePathInfoD3D11 {
             tile_min_x: path.tile_bounds.min_x() as i16,
             tile_min_y: path.tile_bounds.min_y() as i16,
@@ -765,6 +1056,7 @@ struct BuiltSegments {
     clip_segments: SegmentsD3D11,
     draw_segment_ranges: Vec<Range<u32>>,
     clip_segment_ranges: Vec<Range<u32>>,
+    draw_segment_transforms: Vec<Transform2F>,
 }
 
 impl BuiltSegments {
@@ -774,15 +1066,32 @@ impl BuiltSegments {
             clip_segments: SegmentsD3D11::new(),
             draw_segment_ranges: Vec::with_capacity(scene.draw_paths().len()),
             clip_segment_ranges: Vec::with_capacity(scene.clip_paths().len()),
+            draw_segment_transforms: Vec::with_capacity(scene.draw_paths().len()),
         };
 
         for clip_path in scene.clip_paths() {
             let range = built_segments.clip_segments.add_path(clip_path.outline());
             built_segments.clip_segment_ranges.push(range);
         }
+
+        // Symbol instances share the segments of their symbol, which are uploaded only once.
+        let mut symbol_segment_ranges = FxHashMap::default();
         for draw_path in scene.draw_paths() {
-            let range = built_segments.draw_segments.add_path(draw_path.outline());
+            let (range, transform) = match draw_path.instance() {
+                None => {
+                    (built_segments.draw_segments.add_path(draw_path.outline()),
+                     draw_path.layer_transform())
+                }
+                Some(instance) => {
+                    let draw_segments = &mut built_segments.draw_segments;
+                    let range = symbol_segment_ranges.entry(instance.symbol).or_insert_with(|| {
+                        draw_segments.add_path(scene.symbol_outline(instance.symbol))
+                    });
+                    (range.clone(), draw_path.layer_transform() * instance.transform)
+                }
+            };
             built_segments.draw_segment_ranges.push(range);
+            built_segments.draw_segment_transforms.push(transform);
         }
 
         built_segments
@@ -847,7 +1156,10 @@ enum TileBatchBuilderLevel {
 }
 
 impl TileBatchBuilder {
-    fn new(prepare_mode: &PrepareMode, built_paths: Option<BuiltPaths>) -> TileBatchBuilder {
+    fn new(prepare_mode: &PrepareMode,
+           flattening_tolerance: f32,
+           built_paths: Option<BuiltPaths>)
+           -> TileBatchBuilder {
         TileBatchBuilder {
             prepare_commands: vec![],
             draw_commands: vec![],
@@ -858,6 +1170,7 @@ impl TileBatchBuilder {
                     TileBatchBuilderLevel::D3D11 {
                         clip_prepare_batch: TileBatchDataD3D11::new(TileBatchId(0),
                                                                     &prepare_mode,
+                                                                    flattening_tolerance,
                                                                     PathSource::Clip),
                     }
                 }
@@ -871,6 +1184,7 @@ impl TileBatchBuilder {
                                                      sink: &SceneSink,
                                                      built_options: &PreparedBuildOptions,
                                                      draw_path_id_range: Range<DrawPathId>,
+                                                     damage_tile_rects: &[RectI],
                                                      paint_metadata: &[PaintMetadata],
                                                      prepare_mode: &PrepareMode) {
         let mut draw_tile_batch = None;
@@ -884,6 +1198,11 @@ impl TileBatchBuilder {
                                                                  prepare_mode,
                                                                  paint_metadata) {
                         None => continue,
+                        // The GPU tiles whole paths, so skip the ones that miss the damage.
+                        Some(ref built_draw_path) if !damage_tile_rects.is_empty() &&
+                                !damage_tile_rects.iter().any(|tile_rect| {
+                                    tile_rect.intersects(built_draw_path.path.tile_bounds)
+                                }) => continue,
                         Some(built_draw_path) => Cow::Owned(built_draw_path),
                     }
                 }
@@ -896,10 +1215,12 @@ impl TileBatchBuilder {
             let flush_needed = match draw_tile_batch {
                 Some(DrawTileBatch::D3D11(ref mut existing_batch)) => {
                     !fixup_batch_for_new_path_if_possible(&mut existing_batch.color_texture,
+                                                          &mut existing_batch.paint_mask_texture,
                                                           &draw_path)
                 }
                 Some(DrawTileBatch::D3D9(ref mut existing_batch)) => {
                     !fixup_batch_for_new_path_if_possible(&mut existing_batch.color_texture,
+                                                          &mut existing_batch.paint_mask_texture,
                                                           &draw_path)
                 }
                 None => false,
@@ -928,6 +1249,7 @@ impl TileBatchBuilder {
                             clips: vec![],
                             z_buffer_data: DenseTileMap::from_builder(|_| 0, tile_bounds),
                             color_texture: draw_path.color_texture,
+                            paint_mask_texture: draw_path.paint_mask_texture,
                             filter: draw_path.filter,
                             blend_mode: draw_path.blend_mode,
                         }))
@@ -936,8 +1258,11 @@ impl TileBatchBuilder {
                         Some(DrawTileBatch::D3D11(DrawTileBatchD3D11 {
                             tile_batch_data: TileBatchDataD3D11::new(self.next_batch_id,
                                                                      &prepare_mode,
+                                                                     built_options
+                                                                        .flattening_tolerance,
                                                                      PathSource::Draw),
                             color_texture: draw_path.color_texture,
+                            paint_mask_texture: draw_path.paint_mask_texture,
                         }))
                     }
                 };
@@ -1003,13 +1328,20 @@ impl TileBatchBuilder {
                             continue;
                         }
 
+                        let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
+                        if !damage_tile_rects.is_empty() &&
+                                !damage_tile_rects.iter().any(|tile_rect| {
+                                    tile_rect.contains_point(tile_coords)
+                                }) {
+                            continue;
+                        }
+
                         draw_tile_batch.tiles.push(*tile);
 
                         if !draw_path.occludes || tile.alpha_tile_id != AlphaTileId(!0) {
                             continue;
                         }
 
-                        let tile_coords = vec2i(tile.tile_x as i32, tile.tile_y as i32);
                         let z_value = draw_tile_batch.z_buffer_data
                                                      .get_mut(tile_coords)
                                                      .expect("Z value out of bounds!");
@@ -1058,7 +1390,8 @@ impl TileBatchBuilder {
         let effective_view_box = scene.effective_view_box(built_options);
         let draw_path = scene.get_draw_path(draw_path_id);
 
-        let mut path_bounds = transform * draw_path.outline().bounds();
+        let mut path_bounds = transform * (draw_path.layer_transform() *
+                                           draw_path.outline().bounds());
         match path_bounds.intersection(effective_view_box) {
             Some(intersection) => path_bounds = intersection,
             None => return None,
@@ -1120,19 +1453,28 @@ fn prepare_clip_path_for_gpu_binning(scene: &Scene,
 }
 
 fn fixup_batch_for_new_path_if_possible(batch_color_texture: &mut Option<TileBatchTexture>,
+                                        batch_paint_mask_texture: &mut Option<TileBatchTexture>,
                                         draw_path: &BuiltDrawPath)
                                         -> bool {
     if draw_path.color_texture.is_some() {
         if batch_color_texture.is_none() {
             *batch_color_texture = draw_path.color_texture;
-            return true;
-        }
-        if draw_path.color_texture != *batch_color_texture {
+        } else if draw_path.color_texture != *batch_color_texture {
             debug!("batch break: path color texture {:?} batch color texture {:?}",
                    draw_path.color_texture,
                    batch_color_texture);
             return false;
         }
     }
+    if draw_path.paint_mask_texture.is_some() {
+        if batch_paint_mask_texture.is_none() {
+            *batch_paint_mask_texture = draw_path.paint_mask_texture;
+        } else if draw_path.paint_mask_texture != *batch_paint_mask_texture {
+            debug!("batch break: path mask texture {:?} batch mask texture {:?}",
+                   draw_path.paint_mask_texture,
+                   batch_paint_mask_texture);
+            return false;
+        }
+    }
     true
 }
@@ -19,6 +19,15 @@ pub trait Executor {
     /// ```
     fn build_vector<T, F>(&self, length: usize, builder: F) -> Vec<T>
                           where T: Send, F: Fn(usize) -> T + Send + Sync;
+
+    /// Identifies the thread that this is called on, so that profiles can show which thread did
+    /// the work.
+    ///
+    /// The thread that calls `build_vector()` is 0, and worker threads are numbered from 1.
+    #[inline]
+    fn current_thread_index() -> usize {
+        0
+    }
 }
 
 pub struct SequentialExecutor;
@@ -20,4 +20,9 @@ impl Executor for RayonExecutor {
                           where T: Send, F: Fn(usize) -> T + Send + Sync {
         (0..length).into_par_iter().map(builder).collect()
     }
+
+    #[inline]
+    fn current_thread_index() -> usize {
+        rayon::current_thread_index().map_or(0, |index| index + 1)
+    }
 }
@@ -27,7 +27,7 @@ use crate::options::{BuildOptions, RenderCommandListener};
 use crate::scene::{Scene, SceneSink};
 use crossbeam_channel::{self, Receiver, Sender};
 use pathfinder_geometry::rect::RectF;
-use pathfinder_gpu::Device;
+use pathfinder_gpu::{Device, DeviceError};
 use std::thread;
 
 const MAX_MESSAGES_IN_FLIGHT: usize = 1024;
@@ -74,17 +74,25 @@ impl SceneProxy {
     }
 
     /// Sends all queued commands to the given renderer.
+    ///
+    /// If a command fails, the rest of the scene's commands are drained without being rendered
+    /// and the first error is returned.
     #[inline]
-    pub fn render<D>(&mut self, renderer: &mut Renderer<D>) where D: Device {
+    pub fn render<D>(&mut self, renderer: &mut Renderer<D>) -> Result<(), DeviceError>
+                     where D: Device {
+        let mut result = Ok(());
         renderer.begin_scene();
         while let Ok(command) = self.receiver.recv() {
-            renderer.render_command(&command);
+            if result.is_ok() {
+                result = renderer.render_command(&command);
+            }
             match command {
                 RenderCommand::Finish { .. } => break,
                 _ => {}
             }
         }
-        renderer.end_scene();
+        let end_result = renderer.end_scene();
+        result.and(end_result)
     }
 
     /// A convenience method to build a scene and send the resulting commands
@@ -99,9 +107,10 @@ impl SceneProxy {
     /// ```
     #[inline]
     pub fn build_and_render<D>(&mut self, renderer: &mut Renderer<D>, build_options: BuildOptions)
+                               -> Result<(), DeviceError>
                                where D: Device {
         self.build(build_options);
-        self.render(renderer);
+        self.render(renderer)
     }
 
     #[inline]
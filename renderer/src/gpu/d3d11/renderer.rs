@@ -15,13 +15,14 @@ use crate::gpu::renderer::{FramebufferFlags, RendererCore};
 use crate::gpu_data::{AlphaTileD3D11, BackdropInfoD3D11, DiceMetadataD3D11, DrawTileBatchD3D11};
 use crate::gpu_data::{Fill, FirstTileD3D11, MicrolineD3D11, PathSource, PropagateMetadataD3D11};
 use crate::gpu_data::{SegmentIndicesD3D11, SegmentsD3D11, TileD3D11, TileBatchDataD3D11};
-use crate::gpu_data::{TileBatchTexture, TilePathInfoD3D11};
+use crate::gpu_data::{PathTransformD3D11, TileBatchTexture, TilePathInfoD3D11};
+use crate::profile::{self, ProfileCategory};
 use byte_slice_cast::AsSliceOf;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::Vector2F;
 use pathfinder_gpu::allocator::{BufferID, BufferTag, GPUMemoryAllocator};
 use pathfinder_gpu::{BufferTarget, ComputeDimensions, ComputeState, Device, ImageAccess};
-use pathfinder_gpu::{RenderTarget, UniformData};
+use pathfinder_gpu::{DeviceError, RenderTarget, UniformData};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x4, I32x2};
 use std::ops::Range;
@@ -48,28 +49,30 @@ pub(crate) struct RendererD3D11<D> where D: Device {
 
 impl<D> RendererD3D11<D> where D: Device {
     pub(crate) fn new(core: &mut RendererCore<D>, resources: &dyn ResourceLoader)
-                      -> RendererD3D11<D> {
-        let programs = ProgramsD3D11::new(&core.device, resources);
-        RendererD3D11 {
+                      -> Result<RendererD3D11<D>, DeviceError> {
+        let programs = ProgramsD3D11::new(&core.device, resources)?;
+        Ok(RendererD3D11 {
             programs,
             allocated_fill_count: INITIAL_ALLOCATED_FILL_COUNT,
             allocated_microline_count: INITIAL_ALLOCATED_MICROLINE_COUNT,
             scene_buffers: SceneBuffers::new(),
             tile_batch_info: VecMap::<TileBatchInfoD3D11>::new(),
-        }
+        })
     }
 
     fn bound(&mut self,
              core: &mut RendererCore<D>,
              tiles_d3d11_buffer_id: BufferID,
              tile_count: u32,
-             tile_path_info: &[TilePathInfoD3D11]) {
+             tile_path_info: &[TilePathInfoD3D11])
+             -> Result<(), DeviceError> {
         let bound_program = &self.programs.bound_program;
 
         let path_info_buffer_id =
-            core.allocator.allocate_buffer::<TilePathInfoD3D11>(&core.device,
-                                                                tile_path_info.len() as u64,
-                                                                BufferTag("TilePathInfoD3D11"));
+            core.allocator
+                .try_allocate_buffer::<TilePathInfoD3D11>(&core.device,
+                                                          tile_path_info.len() as u64,
+                                                          BufferTag("TilePathInfoD3D11"))?;
         let tile_path_info_buffer = core.allocator.get_buffer(path_info_buffer_id);
         core.device.upload_to_buffer(tile_path_info_buffer,
                                      0,
@@ -105,18 +108,20 @@ impl<D> RendererD3D11<D> where D: Device {
         core.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
 
         core.allocator.free_buffer(path_info_buffer_id);
+        Ok(())
     }
 
     fn upload_propagate_metadata(&mut self,
                                  core: &mut RendererCore<D>,
                                  propagate_metadata: &[PropagateMetadataD3D11],
                                  backdrops: &[BackdropInfoD3D11])
-                                 -> PropagateMetadataBufferIDsD3D11 {
+                                 -> Result<PropagateMetadataBufferIDsD3D11, DeviceError> {
         let propagate_metadata_storage_id =
             core.allocator
-                .allocate_buffer::<PropagateMetadataD3D11>(&core.device,
-                                                           propagate_metadata.len() as u64,
-                                                           BufferTag("PropagateMetadataD3D11"));
+                .try_allocate_buffer::<PropagateMetadataD3D11>(
+                    &core.device,
+                    propagate_metadata.len() as u64,
+                    BufferTag("PropagateMetadataD3D11"))?;
         let propagate_metadata_buffer = core.allocator.get_buffer(propagate_metadata_storage_id);
         core.device.upload_to_buffer(propagate_metadata_buffer,
                                      0,
@@ -124,14 +129,15 @@ impl<D> RendererD3D11<D> where D: Device {
                                      BufferTarget::Storage);
 
         let backdrops_storage_id =
-            core.allocator.allocate_buffer::<BackdropInfoD3D11>(&core.device,
-                                                                backdrops.len() as u64,
-                                                                BufferTag("BackdropInfoD3D11"));
+            core.allocator
+                .try_allocate_buffer::<BackdropInfoD3D11>(&core.device,
+                                                          backdrops.len() as u64,
+                                                          BufferTag("BackdropInfoD3D11"))?;
 
-        PropagateMetadataBufferIDsD3D11 {
+        Ok(PropagateMetadataBufferIDsD3D11 {
              propagate_metadata: propagate_metadata_storage_id,
              backdrops: backdrops_storage_id,
-        }
+        })
     }
 
     fn upload_initial_backdrops(&self,
@@ -147,17 +153,17 @@ impl<D> RendererD3D11<D> where D: Device {
                     microlines_storage: &MicrolinesBufferIDsD3D11,
                     propagate_metadata_buffer_ids: &PropagateMetadataBufferIDsD3D11,
                     tiles_d3d11_buffer_id: BufferID)
-                    -> Option<FillBufferInfoD3D11> {
+                    -> Result<Option<FillBufferInfoD3D11>, DeviceError> {
         let bin_program = &self.programs.bin_program;
 
         let fill_vertex_buffer_id =
-            core.allocator.allocate_buffer::<Fill>(&core.device,
-                                                   self.allocated_fill_count as u64,
-                                                   BufferTag("Fill"));
+            core.allocator.try_allocate_buffer::<Fill>(&core.device,
+                                                       self.allocated_fill_count as u64,
+                                                       BufferTag("Fill"))?;
         let fill_indirect_draw_params_buffer_id =
-            core.allocator.allocate_buffer::<u32>(&core.device,
-                                                  8,
-                                                  BufferTag("FillIndirectDrawParamsD3D11"));
+            core.allocator.try_allocate_buffer::<u32>(&core.device,
+                                                      8,
+                                                      BufferTag("FillIndirectDrawParamsD3D11"))?;
 
         let fill_vertex_buffer = core.allocator.get_buffer(fill_vertex_buffer_id);
         let microlines_buffer = core.allocator.get_buffer(microlines_storage.buffer_id);
@@ -211,7 +217,7 @@ impl<D> RendererD3D11<D> where D: Device {
         let indirect_draw_params_receiver =
             core.device.read_buffer(fill_indirect_draw_params_buffer,
                                     BufferTarget::Storage,
-                                    0..32);
+                                    0..32)?;
         let indirect_draw_params = core.device.recv_buffer(&indirect_draw_params_receiver);
         let indirect_draw_params: &[u32] = indirect_draw_params.as_slice_of().unwrap();
 
@@ -219,51 +225,63 @@ impl<D> RendererD3D11<D> where D: Device {
             indirect_draw_params[FILL_INDIRECT_DRAW_PARAMS_INSTANCE_COUNT_INDEX];
         if needed_fill_count > self.allocated_fill_count {
             self.allocated_fill_count = needed_fill_count.next_power_of_two();
-            return None;
+            return Ok(None);
         }
 
         core.stats.fill_count += needed_fill_count as usize;
 
-        Some(FillBufferInfoD3D11 { fill_vertex_buffer_id, fill_indirect_draw_params_buffer_id })
+        Ok(Some(FillBufferInfoD3D11 { fill_vertex_buffer_id, fill_indirect_draw_params_buffer_id }))
     }
 
     pub(crate) fn upload_scene(&mut self,
                                core: &mut RendererCore<D>,
                                draw_segments: &SegmentsD3D11,
-                               clip_segments: &SegmentsD3D11) {
-        self.scene_buffers.upload(&mut core.allocator, &core.device, draw_segments, clip_segments);
+                               clip_segments: &SegmentsD3D11)
+                               -> Result<(), DeviceError> {
+        self.scene_buffers.upload(&mut core.allocator, &core.device, draw_segments, clip_segments)
     }
 
-    fn allocate_tiles(&mut self, core: &mut RendererCore<D>, tile_count: u32) -> BufferID {
-        core.allocator.allocate_buffer::<TileD3D11>(&core.device,
-                                                    tile_count as u64,
-                                                    BufferTag("TileD3D11"))
+    fn allocate_tiles(&mut self, core: &mut RendererCore<D>, tile_count: u32)
+                      -> Result<BufferID, DeviceError> {
+        core.allocator.try_allocate_buffer::<TileD3D11>(&core.device,
+                                                        tile_count as u64,
+                                                        BufferTag("TileD3D11"))
     }
 
     fn dice_segments(&mut self,
                      core: &mut RendererCore<D>,
                      dice_metadata: &[DiceMetadataD3D11],
+                     path_transforms: &[PathTransformD3D11],
                      batch_segment_count: u32,
                      path_source: PathSource,
-                     transform: Transform2F)
-                     -> Option<MicrolinesBufferIDsD3D11> {
+                     transform: Transform2F,
+                     tolerance: f32)
+                     -> Result<Option<MicrolinesBufferIDsD3D11>, DeviceError> {
         let dice_program = &self.programs.dice_program;
 
         let microlines_buffer_id =
-            core.allocator.allocate_buffer::<MicrolineD3D11>(&core.device,
-                                                             self.allocated_microline_count as u64,
-                                                             BufferTag("MicrolineD3D11"));
+            core.allocator
+                .try_allocate_buffer::<MicrolineD3D11>(&core.device,
+                                                       self.allocated_microline_count as u64,
+                                                       BufferTag("MicrolineD3D11"))?;
         let dice_metadata_buffer_id =
-            core.allocator.allocate_buffer::<DiceMetadataD3D11>(&core.device,
-                                                                dice_metadata.len() as u64,
-                                                                BufferTag("DiceMetadataD3D11"));
+            core.allocator
+                .try_allocate_buffer::<DiceMetadataD3D11>(&core.device,
+                                                          dice_metadata.len() as u64,
+                                                          BufferTag("DiceMetadataD3D11"))?;
+        let path_transforms_buffer_id =
+            core.allocator
+                .try_allocate_buffer::<PathTransformD3D11>(&core.device,
+                                                           path_transforms.len() as u64,
+                                                           BufferTag("PathTransformD3D11"))?;
         let dice_indirect_draw_params_buffer_id =
-            core.allocator.allocate_buffer::<u32>(&core.device,
-                                                  8,
-                                                  BufferTag("DiceIndirectDrawParamsD3D11"));
+            core.allocator.try_allocate_buffer::<u32>(&core.device,
+                                                      8,
+                                                      BufferTag("DiceIndirectDrawParamsD3D11"))?;
 
         let microlines_buffer = core.allocator.get_buffer(microlines_buffer_id);
         let dice_metadata_storage_buffer = core.allocator.get_buffer(dice_metadata_buffer_id);
+        let path_transforms_storage_buffer = core.allocator.get_buffer(path_transforms_buffer_id);
         let dice_indirect_draw_params_buffer =
             core.allocator.get_buffer(dice_indirect_draw_params_buffer_id);
 
@@ -293,6 +311,10 @@ impl<D> RendererD3D11<D> where D: Device {
                                      0,
                                      dice_metadata,
                                      BufferTarget::Storage);
+        core.device.upload_to_buffer(path_transforms_storage_buffer,
+                                     0,
+                                     path_transforms,
+                                     BufferTarget::Storage);
 
         let timer_query = core.timer_query_cache.start_timing_draw_call(&core.device,
                                                                         &core.options);
@@ -313,6 +335,7 @@ impl<D> RendererD3D11<D> where D: Device {
                  UniformData::Int(batch_segment_count as i32)),
                 (&dice_program.max_microline_count_uniform,
                  UniformData::Int(self.allocated_microline_count as i32)),
+                (&dice_program.tolerance_uniform, UniformData::Float(tolerance)),
             ],
             images: &[],
             storage_buffers: &[
@@ -322,6 +345,7 @@ impl<D> RendererD3D11<D> where D: Device {
                 (&dice_program.input_indices_storage_buffer, point_indices_buffer),
                 (&dice_program.microlines_storage_buffer, microlines_buffer),
                 (&dice_program.dice_metadata_storage_buffer, &dice_metadata_storage_buffer),
+                (&dice_program.path_transforms_storage_buffer, &path_transforms_storage_buffer),
             ],
         });
 
@@ -332,21 +356,25 @@ impl<D> RendererD3D11<D> where D: Device {
         let indirect_compute_params_receiver =
             core.device.read_buffer(&dice_indirect_draw_params_buffer,
                                     BufferTarget::Storage,
-                                    0..32);
+                                    0..32)?;
         let indirect_compute_params = core.device.recv_buffer(&indirect_compute_params_receiver);
         let indirect_compute_params: &[u32] = indirect_compute_params.as_slice_of().unwrap();
 
         core.allocator.free_buffer(dice_metadata_buffer_id);
+        core.allocator.free_buffer(path_transforms_buffer_id);
         core.allocator.free_buffer(dice_indirect_draw_params_buffer_id);
 
         let microline_count =
             indirect_compute_params[BIN_INDIRECT_DRAW_PARAMS_MICROLINE_COUNT_INDEX];
         if microline_count > self.allocated_microline_count {
             self.allocated_microline_count = microline_count.next_power_of_two();
-            return None;
+            return Ok(None);
         }
 
-        Some(MicrolinesBufferIDsD3D11 { buffer_id: microlines_buffer_id, count: microline_count })
+        Ok(Some(MicrolinesBufferIDsD3D11 {
+            buffer_id: microlines_buffer_id,
+            count: microline_count,
+        }))
     }
 
     fn draw_fills(&mut self,
@@ -393,6 +421,8 @@ impl<D> RendererD3D11<D> where D: Device {
                 (&fill_program.alpha_tile_range_uniform,
                  UniformData::IVec2(I32x2::new(alpha_tile_range.start as i32,
                                                alpha_tile_range.end as i32))),
+                (&fill_program.sample_count_uniform,
+                 UniformData::Int(core.antialiasing_mode.sample_count() as i32)),
             ],
             storage_buffers: &[
                 (&fill_program.fills_storage_buffer, fill_vertex_buffer),
@@ -410,24 +440,33 @@ impl<D> RendererD3D11<D> where D: Device {
 
     pub(crate) fn prepare_and_draw_tiles(&mut self,
                                          core: &mut RendererCore<D>,
-                                         batch: &DrawTileBatchD3D11) {
+                                         batch: &DrawTileBatchD3D11)
+                                         -> Result<(), DeviceError> {
         let tile_batch_id = batch.tile_batch_data.batch_id;
-        self.prepare_tiles(core, &batch.tile_batch_data);
+        self.prepare_tiles(core, &batch.tile_batch_data)?;
         let batch_info = self.tile_batch_info[tile_batch_id.0 as usize].clone();
+        let _span = profile::span(&core.profiler,
+                                  ProfileCategory::Render,
+                                  "Composite",
+                                  0,
+                                  Some(tile_batch_id.0));
         self.draw_tiles(core,
                         batch_info.tiles_d3d11_buffer_id,
                         batch_info.first_tile_map_buffer_id,
-                        batch.color_texture);
+                        batch.color_texture,
+                        batch.paint_mask_texture);
+        Ok(())
     }
 
     // Computes backdrops, performs clipping, and populates Z buffers on GPU.
     pub(crate) fn prepare_tiles(&mut self,
                                 core: &mut RendererCore<D>,
-                                batch: &TileBatchDataD3D11) {
+                                batch: &TileBatchDataD3D11)
+                                -> Result<(), DeviceError> {
         core.stats.total_tile_count += batch.tile_count as usize;
 
         // Upload tiles to GPU or allocate them as appropriate.
-        let tiles_d3d11_buffer_id = self.allocate_tiles(core, batch.tile_count);
+        let tiles_d3d11_buffer_id = self.allocate_tiles(core, batch.tile_count)?;
 
         // Fetch and/or allocate clip storage as needed.
         let clip_buffer_ids = match batch.clipped_path_info {
@@ -442,42 +481,50 @@ impl<D> RendererD3D11<D> where D: Device {
         };
 
         // Allocate a Z-buffer.
-        let z_buffer_id = self.allocate_z_buffer(core);
+        let z_buffer_id = self.allocate_z_buffer(core)?;
 
         // Propagate backdrops, bin fills, render fills, and/or perform clipping on GPU if
         // necessary.
         // Allocate space for tile lists.
-        let first_tile_map_buffer_id = self.allocate_first_tile_map(core);
+        let first_tile_map_buffer_id = self.allocate_first_tile_map(core)?;
 
         let propagate_metadata_buffer_ids =
             self.upload_propagate_metadata(core,
                                            &batch.prepare_info.propagate_metadata,
-                                           &batch.prepare_info.backdrops);
+                                           &batch.prepare_info.backdrops)?;
 
         // Dice (flatten) segments into microlines. We might have to do this twice if our
         // first attempt runs out of space in the storage buffer.
+        let batch_index = Some(batch.batch_id.0);
+        let dice_span =
+            profile::span(&core.profiler, ProfileCategory::Render, "Dice", 0, batch_index);
         let mut microlines_storage = None;
         for _ in 0..2 {
             microlines_storage = self.dice_segments(core,
                                                     &batch.prepare_info.dice_metadata,
+                                                    &batch.prepare_info.path_transforms,
                                                     batch.segment_count,
                                                     batch.path_source,
-                                                    batch.prepare_info.transform);
+                                                    batch.prepare_info.transform,
+                                                    batch.prepare_info.flattening_tolerance)?;
             if microlines_storage.is_some() {
                 break;
             }
         }
         let microlines_storage =
             microlines_storage.expect("Ran out of space for microlines when dicing!");
+        drop(dice_span);
 
         // Initialize tiles, and bin segments. We might have to do this twice if our first
         // attempt runs out of space in the fill buffer.
+        let bin_span =
+            profile::span(&core.profiler, ProfileCategory::Render, "Bin", 0, batch_index);
         let mut fill_buffer_info = None;
         for _ in 0..2 {
             self.bound(core,
                        tiles_d3d11_buffer_id,
                        batch.tile_count,
-                       &batch.prepare_info.tile_path_info);
+                       &batch.prepare_info.tile_path_info)?;
 
             self.upload_initial_backdrops(core,
                                           propagate_metadata_buffer_ids.backdrops,
@@ -486,20 +533,21 @@ impl<D> RendererD3D11<D> where D: Device {
             fill_buffer_info = self.bin_segments(core,
                                                  &microlines_storage,
                                                  &propagate_metadata_buffer_ids,
-                                                 tiles_d3d11_buffer_id);
+                                                 tiles_d3d11_buffer_id)?;
             if fill_buffer_info.is_some() {
                 break;
             }
         }
         let fill_buffer_info =
             fill_buffer_info.expect("Ran out of space for fills when binning!");
+        drop(bin_span);
 
         core.allocator.free_buffer(microlines_storage.buffer_id);
 
         // TODO(pcwalton): If we run out of space for alpha tile indices, propagate
         // multiple times.
 
-        let alpha_tiles_buffer_id = self.allocate_alpha_tile_info(core, batch.tile_count);
+        let alpha_tiles_buffer_id = self.allocate_alpha_tile_info(core, batch.tile_count)?;
 
         let propagate_tiles_info =
             self.propagate_tiles(core,
@@ -510,17 +558,20 @@ impl<D> RendererD3D11<D> where D: Device {
                                  first_tile_map_buffer_id,
                                  alpha_tiles_buffer_id,
                                  &propagate_metadata_buffer_ids,
-                                 clip_buffer_ids.as_ref());
+                                 clip_buffer_ids.as_ref())?;
 
         core.allocator.free_buffer(propagate_metadata_buffer_ids.backdrops);
 
         // FIXME(pcwalton): Don't unconditionally pass true for copying here.
-        core.reallocate_alpha_tile_pages_if_necessary(true);
+        core.reallocate_alpha_tile_pages_if_necessary(true)?;
+        let fill_span =
+            profile::span(&core.profiler, ProfileCategory::Render, "Fill", 0, batch_index);
         self.draw_fills(core,
                         &fill_buffer_info,
                         tiles_d3d11_buffer_id,
                         alpha_tiles_buffer_id,
                         &propagate_tiles_info);
+        drop(fill_span);
 
         core.allocator.free_buffer(fill_buffer_info.fill_vertex_buffer_id);
         core.allocator.free_buffer(fill_buffer_info.fill_indirect_draw_params_buffer_id);
@@ -537,6 +588,7 @@ impl<D> RendererD3D11<D> where D: Device {
             propagate_metadata_buffer_id: propagate_metadata_buffer_ids.propagate_metadata,
             first_tile_map_buffer_id,
         });
+        Ok(())
     }
 
     fn propagate_tiles(&mut self,
@@ -549,7 +601,7 @@ impl<D> RendererD3D11<D> where D: Device {
                        alpha_tiles_buffer_id: BufferID,
                        propagate_metadata_buffer_ids: &PropagateMetadataBufferIDsD3D11,
                        clip_buffer_ids: Option<&ClipBufferIDs>)
-                       -> PropagateTilesInfoD3D11 {
+                       -> Result<PropagateTilesInfoD3D11, DeviceError> {
         let propagate_program = &self.programs.propagate_program;
 
         let tiles_d3d11_buffer = core.allocator.get_buffer(tiles_d3d11_buffer_id);
@@ -625,7 +677,7 @@ impl<D> RendererD3D11<D> where D: Device {
         let fill_indirect_draw_params_receiver =
             core.device.read_buffer(&fill_indirect_draw_params_buffer,
                                     BufferTarget::Storage,
-                                    0..32);
+                                    0..32)?;
         let fill_indirect_draw_params = core.device
                                             .recv_buffer(&fill_indirect_draw_params_receiver);
         let fill_indirect_draw_params: &[u32] = fill_indirect_draw_params.as_slice_of().unwrap();
@@ -638,7 +690,7 @@ impl<D> RendererD3D11<D> where D: Device {
         core.stats.alpha_tile_count += batch_alpha_tile_count as usize;
         let alpha_tile_end = core.alpha_tile_count;
 
-        PropagateTilesInfoD3D11 { alpha_tile_range: alpha_tile_start..alpha_tile_end }
+        Ok(PropagateTilesInfoD3D11 { alpha_tile_range: alpha_tile_start..alpha_tile_end })
     }
 
     fn sort_tiles(&mut self,
@@ -679,30 +731,32 @@ impl<D> RendererD3D11<D> where D: Device {
         core.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
     }
 
-    fn allocate_first_tile_map(&mut self, core: &mut RendererCore<D>) -> BufferID {
-        core.allocator.allocate_buffer::<FirstTileD3D11>(&core.device,
-                                                         core.tile_size().area() as u64,
-                                                         BufferTag("FirstTileD3D11"))
+    fn allocate_first_tile_map(&mut self, core: &mut RendererCore<D>)
+                               -> Result<BufferID, DeviceError> {
+        core.allocator.try_allocate_buffer::<FirstTileD3D11>(&core.device,
+                                                             core.tile_size().area() as u64,
+                                                             BufferTag("FirstTileD3D11"))
     }
 
     fn allocate_alpha_tile_info(&mut self, core: &mut RendererCore<D>, index_count: u32)
-                                -> BufferID {
-        core.allocator.allocate_buffer::<AlphaTileD3D11>(&core.device,
-                                                         index_count as u64,
-                                                         BufferTag("AlphaTileD3D11"))
+                                -> Result<BufferID, DeviceError> {
+        core.allocator.try_allocate_buffer::<AlphaTileD3D11>(&core.device,
+                                                             index_count as u64,
+                                                             BufferTag("AlphaTileD3D11"))
     }
 
-    fn allocate_z_buffer(&mut self, core: &mut RendererCore<D>) -> BufferID {
-        core.allocator.allocate_buffer::<i32>(&core.device,
-                                              core.tile_size().area() as u64,
-                                              BufferTag("ZBufferD3D11"))
+    fn allocate_z_buffer(&mut self, core: &mut RendererCore<D>) -> Result<BufferID, DeviceError> {
+        core.allocator.try_allocate_buffer::<i32>(&core.device,
+                                                  core.tile_size().area() as u64,
+                                                  BufferTag("ZBufferD3D11"))
     }
 
     pub(crate) fn draw_tiles(&mut self,
                              core: &mut RendererCore<D>,
                              tiles_d3d11_buffer_id: BufferID,
                              first_tile_map_buffer_id: BufferID,
-                             color_texture_0: Option<TileBatchTexture>) {
+                             color_texture_0: Option<TileBatchTexture>,
+                             paint_mask_texture: Option<TileBatchTexture>) {
         let timer_query = core.timer_query_cache.start_timing_draw_call(&core.device,
                                                                         &core.options);
 
@@ -713,7 +767,8 @@ impl<D> RendererD3D11<D> where D: Device {
         core.set_uniforms_for_drawing_tiles(&tile_program.common,
                                             &mut textures,
                                             &mut uniforms,
-                                            color_texture_0);
+                                            color_texture_0,
+                                            paint_mask_texture);
 
         uniforms.push((&tile_program.framebuffer_tile_size_uniform,
                        UniformData::IVec2(core.framebuffer_tile_size().0)));
@@ -846,9 +901,10 @@ impl SceneBuffers {
                  device: &D,
                  draw_segments: &SegmentsD3D11,
                  clip_segments: &SegmentsD3D11)
+                 -> Result<(), DeviceError>
                  where D: Device {
-        self.draw.upload(allocator, device, draw_segments);
-        self.clip.upload(allocator, device, clip_segments);
+        self.draw.upload(allocator, device, draw_segments)?;
+        self.clip.upload(allocator, device, clip_segments)
     }
 }
 
@@ -867,21 +923,23 @@ impl SceneSourceBuffers {
                  allocator: &mut GPUMemoryAllocator<D>,
                  device: &D,
                  segments: &SegmentsD3D11)
+                 -> Result<(), DeviceError>
                  where D: Device {
         let needed_points_capacity = (segments.points.len() as u32).next_power_of_two();
         let needed_point_indices_capacity = (segments.indices.len() as u32).next_power_of_two();
         if self.points_capacity < needed_points_capacity {
             self.points_buffer =
-                Some(allocator.allocate_buffer::<Vector2F>(device,
-                                                           needed_points_capacity as u64,
-                                                           BufferTag("PointsD3D11")));
+                Some(allocator.try_allocate_buffer::<Vector2F>(device,
+                                                               needed_points_capacity as u64,
+                                                               BufferTag("PointsD3D11"))?);
             self.points_capacity = needed_points_capacity;
         }
         if self.point_indices_capacity < needed_point_indices_capacity {
-            self.point_indices_buffer = Some(allocator.allocate_buffer::<SegmentIndicesD3D11>(
-                device,
-                needed_point_indices_capacity as u64,
-                BufferTag("PointIndicesD3D11")));
+            self.point_indices_buffer =
+                Some(allocator.try_allocate_buffer::<SegmentIndicesD3D11>(
+                    device,
+                    needed_point_indices_capacity as u64,
+                    BufferTag("PointIndicesD3D11"))?);
             self.point_indices_capacity = needed_point_indices_capacity;
         }
         device.upload_to_buffer(allocator.get_buffer(self.points_buffer.unwrap()),
@@ -893,5 +951,6 @@ impl SceneSourceBuffers {
                                 &segments.indices,
                                 BufferTarget::Storage);
         self.point_indices_count = segments.indices.len() as u32;
+        Ok(())
     }
 }
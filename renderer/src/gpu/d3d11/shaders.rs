@@ -10,7 +10,7 @@
 
 use crate::gpu::shaders::TileProgramCommon;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
-use pathfinder_gpu::{ComputeDimensions, Device};
+use pathfinder_gpu::{ComputeDimensions, Device, DeviceError};
 use pathfinder_resources::ResourceLoader;
 
 pub const BOUND_WORKGROUP_SIZE: u32 = 64;
@@ -30,16 +30,17 @@ pub struct ProgramsD3D11<D> where D: Device {
 }
 
 impl<D> ProgramsD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsD3D11<D> {
-        ProgramsD3D11 {
-            bound_program: BoundProgramD3D11::new(device, resources),
-            dice_program: DiceProgramD3D11::new(device, resources),
-            bin_program: BinProgramD3D11::new(device, resources),
-            propagate_program: PropagateProgramD3D11::new(device, resources),
-            sort_program: SortProgramD3D11::new(device, resources),
-            fill_program: FillProgramD3D11::new(device, resources),
-            tile_program: TileProgramD3D11::new(device, resources),
-        }
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ProgramsD3D11<D>, DeviceError> {
+        Ok(ProgramsD3D11 {
+            bound_program: BoundProgramD3D11::new(device, resources)?,
+            dice_program: DiceProgramD3D11::new(device, resources)?,
+            bin_program: BinProgramD3D11::new(device, resources)?,
+            propagate_program: PropagateProgramD3D11::new(device, resources)?,
+            sort_program: SortProgramD3D11::new(device, resources)?,
+            fill_program: FillProgramD3D11::new(device, resources)?,
+            tile_program: TileProgramD3D11::new(device, resources)?,
+        })
     }
 }
 
@@ -60,8 +61,9 @@ pub struct PropagateProgramD3D11<D> where D: Device {
 }
 
 impl<D> PropagateProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> PropagateProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/propagate");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<PropagateProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/propagate")?;
         let local_size = ComputeDimensions { x: PROPAGATE_WORKGROUP_SIZE, y: 1, z: 1 };
         device.set_compute_program_local_size(&mut program, local_size);
 
@@ -79,7 +81,7 @@ impl<D> PropagateProgramD3D11<D> where D: Device {
             device.get_storage_buffer(&program, "IndirectDrawParams", 7);
         let alpha_tiles_storage_buffer = device.get_storage_buffer(&program, "AlphaTiles", 8);
 
-        PropagateProgramD3D11 {
+        Ok(PropagateProgramD3D11 {
             program,
             framebuffer_tile_size_uniform,
             column_count_uniform,
@@ -93,7 +95,7 @@ impl<D> PropagateProgramD3D11<D> where D: Device {
             first_tile_map_storage_buffer,
             indirect_draw_params_storage_buffer,
             alpha_tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -102,33 +104,37 @@ pub struct FillProgramD3D11<D> where D: Device {
     pub dest_image: D::ImageParameter,
     pub area_lut_texture: D::TextureParameter,
     pub alpha_tile_range_uniform: D::Uniform,
+    pub sample_count_uniform: D::Uniform,
     pub fills_storage_buffer: D::StorageBuffer,
     pub tiles_storage_buffer: D::StorageBuffer,
     pub alpha_tiles_storage_buffer: D::StorageBuffer,
 }
 
 impl<D> FillProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> FillProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/fill");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<FillProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/fill")?;
         let local_size = ComputeDimensions { x: TILE_WIDTH, y: TILE_HEIGHT / 4, z: 1 };
         device.set_compute_program_local_size(&mut program, local_size);
 
         let dest_image = device.get_image_parameter(&program, "Dest");
         let area_lut_texture = device.get_texture_parameter(&program, "AreaLUT");
         let alpha_tile_range_uniform = device.get_uniform(&program, "AlphaTileRange");
+        let sample_count_uniform = device.get_uniform(&program, "SampleCount");
         let fills_storage_buffer = device.get_storage_buffer(&program, "Fills", 0);
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 1);
         let alpha_tiles_storage_buffer = device.get_storage_buffer(&program, "AlphaTiles", 2);
 
-        FillProgramD3D11 {
+        Ok(FillProgramD3D11 {
             program,
             dest_image,
             area_lut_texture,
             alpha_tile_range_uniform,
+            sample_count_uniform,
             fills_storage_buffer,
             tiles_storage_buffer,
             alpha_tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -143,8 +149,8 @@ pub struct TileProgramD3D11<D> where D: Device {
 }
 
 impl<D> TileProgramD3D11<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> TileProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/tile");
+    fn new(device: &D, resources: &dyn ResourceLoader) -> Result<TileProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/tile")?;
         device.set_compute_program_local_size(&mut program,
                                               ComputeDimensions { x: 16, y: 4, z: 1 });
 
@@ -156,7 +162,7 @@ impl<D> TileProgramD3D11<D> where D: Device {
         let first_tile_map_storage_buffer = device.get_storage_buffer(&program, "FirstTileMap", 1);
 
         let common = TileProgramCommon::new(device, program);
-        TileProgramD3D11 {
+        Ok(TileProgramD3D11 {
             common,
             load_action_uniform,
             clear_color_uniform,
@@ -164,7 +170,7 @@ impl<D> TileProgramD3D11<D> where D: Device {
             dest_image,
             tiles_storage_buffer,
             first_tile_map_storage_buffer,
-        }
+        })
     }
 }
 
@@ -181,8 +187,9 @@ pub struct BinProgramD3D11<D> where D: Device {
 }
 
 impl<D> BinProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> BinProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/bin");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<BinProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/bin")?;
         let dimensions = ComputeDimensions { x: BIN_WORKGROUP_SIZE, y: 1, z: 1 };
         device.set_compute_program_local_size(&mut program, dimensions);
 
@@ -197,7 +204,7 @@ impl<D> BinProgramD3D11<D> where D: Device {
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 4);
         let backdrops_storage_buffer = device.get_storage_buffer(&program, "Backdrops", 5);
 
-        BinProgramD3D11 {
+        Ok(BinProgramD3D11 {
             program,
             microline_count_uniform,
             max_fill_count_uniform,
@@ -207,7 +214,7 @@ impl<D> BinProgramD3D11<D> where D: Device {
             tiles_storage_buffer,
             microlines_storage_buffer,
             backdrops_storage_buffer,
-        }
+        })
     }
 }
 
@@ -218,16 +225,19 @@ pub struct DiceProgramD3D11<D> where D: Device {
     pub path_count_uniform: D::Uniform,
     pub last_batch_segment_index_uniform: D::Uniform,
     pub max_microline_count_uniform: D::Uniform,
+    pub tolerance_uniform: D::Uniform,
     pub compute_indirect_params_storage_buffer: D::StorageBuffer,
     pub dice_metadata_storage_buffer: D::StorageBuffer,
     pub points_storage_buffer: D::StorageBuffer,
     pub input_indices_storage_buffer: D::StorageBuffer,
     pub microlines_storage_buffer: D::StorageBuffer,
+    pub path_transforms_storage_buffer: D::StorageBuffer,
 }
 
 impl<D> DiceProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> DiceProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/dice");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<DiceProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/dice")?;
         let dimensions = ComputeDimensions { x: DICE_WORKGROUP_SIZE, y: 1, z: 1 };
         device.set_compute_program_local_size(&mut program, dimensions);
 
@@ -237,6 +247,7 @@ impl<D> DiceProgramD3D11<D> where D: Device {
         let last_batch_segment_index_uniform = device.get_uniform(&program,
                                                                   "LastBatchSegmentIndex");
         let max_microline_count_uniform = device.get_uniform(&program, "MaxMicrolineCount");
+        let tolerance_uniform = device.get_uniform(&program, "Tolerance");
 
         let compute_indirect_params_storage_buffer =
             device.get_storage_buffer(&program, "ComputeIndirectParams", 0);
@@ -244,20 +255,24 @@ impl<D> DiceProgramD3D11<D> where D: Device {
         let points_storage_buffer = device.get_storage_buffer(&program, "Points", 2);
         let input_indices_storage_buffer = device.get_storage_buffer(&program, "InputIndices", 3);
         let microlines_storage_buffer = device.get_storage_buffer(&program, "Microlines", 4);
+        let path_transforms_storage_buffer =
+            device.get_storage_buffer(&program, "PathTransforms", 5);
 
-        DiceProgramD3D11 {
+        Ok(DiceProgramD3D11 {
             program,
             transform_uniform,
             translation_uniform,
             path_count_uniform,
             last_batch_segment_index_uniform,
             max_microline_count_uniform,
+            tolerance_uniform,
             compute_indirect_params_storage_buffer,
             dice_metadata_storage_buffer,
             points_storage_buffer,
             input_indices_storage_buffer,
             microlines_storage_buffer,
-        }
+            path_transforms_storage_buffer,
+        })
     }
 }
 
@@ -270,8 +285,9 @@ pub struct BoundProgramD3D11<D> where D: Device {
 }
 
 impl<D> BoundProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> BoundProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/bound");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<BoundProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/bound")?;
         let dimensions = ComputeDimensions { x: BOUND_WORKGROUP_SIZE, y: 1, z: 1 };
         device.set_compute_program_local_size(&mut program, dimensions);
 
@@ -281,13 +297,13 @@ impl<D> BoundProgramD3D11<D> where D: Device {
         let tile_path_info_storage_buffer = device.get_storage_buffer(&program, "TilePathInfo", 0);
         let tiles_storage_buffer = device.get_storage_buffer(&program, "Tiles", 1);
 
-        BoundProgramD3D11 {
+        Ok(BoundProgramD3D11 {
             program,
             path_count_uniform,
             tile_count_uniform,
             tile_path_info_storage_buffer,
             tiles_storage_buffer,
-        }
+        })
     }
 }
 
@@ -300,8 +316,9 @@ pub struct SortProgramD3D11<D> where D: Device {
 }
 
 impl<D> SortProgramD3D11<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> SortProgramD3D11<D> {
-        let mut program = device.create_compute_program(resources, "d3d11/sort");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<SortProgramD3D11<D>, DeviceError> {
+        let mut program = device.create_compute_program(resources, "d3d11/sort")?;
         let dimensions = ComputeDimensions { x: SORT_WORKGROUP_SIZE, y: 1, z: 1 };
         device.set_compute_program_local_size(&mut program, dimensions);
 
@@ -310,12 +327,12 @@ impl<D> SortProgramD3D11<D> where D: Device {
         let first_tile_map_storage_buffer = device.get_storage_buffer(&program, "FirstTileMap", 1);
         let z_buffer_storage_buffer = device.get_storage_buffer(&program, "ZBuffer", 2);
 
-        SortProgramD3D11 {
+        Ok(SortProgramD3D11 {
             program,
             tile_count_uniform,
             tiles_storage_buffer,
             first_tile_map_storage_buffer,
             z_buffer_storage_buffer,
-        }
+        })
     }
 }
\ No newline at end of file
@@ -16,6 +16,7 @@ use crate::gpu::d3d9::shaders::{ClipTileCombineVertexArrayD3D9, ClipTileCopyVert
 use crate::gpu::d3d9::shaders::{CopyTileVertexArray, FillVertexArrayD3D9};
 use crate::gpu::d3d9::shaders::{ProgramsD3D9, TileVertexArrayD3D9};
 use crate::gpu_data::{Clip, DrawTileBatchD3D9, Fill, TileBatchTexture, TileObjectPrimitive};
+use crate::profile::{self, ProfileCategory};
 use crate::tile_map::DenseTileMap;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
 use byte_slice_cast::AsByteSlice;
@@ -26,9 +27,9 @@ use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::vector::{Vector2I, Vector4F, vec2i};
 use pathfinder_gpu::allocator::{BufferID, BufferTag, FramebufferID, FramebufferTag};
 use pathfinder_gpu::allocator::{TextureID, TextureTag};
-use pathfinder_gpu::{BlendFactor, BlendState, BufferTarget, ClearOps, Device, Primitive};
-use pathfinder_gpu::{RenderOptions, RenderState, RenderTarget, StencilFunc, StencilState};
-use pathfinder_gpu::{TextureDataRef, TextureFormat, UniformData};
+use pathfinder_gpu::{BlendFactor, BlendState, BufferTarget, ClearOps, Device, DeviceError};
+use pathfinder_gpu::{Primitive, RenderOptions, RenderState, RenderTarget, StencilFunc};
+use pathfinder_gpu::{StencilState, TextureDataRef, TextureFormat, UniformData};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x2;
 use std::u32;
@@ -47,21 +48,24 @@ pub(crate) struct RendererD3D9<D> where D: Device {
 
     // Temporary framebuffers
     dest_blend_framebuffer_id: FramebufferID,
+
+    // The number of tile batches drawn so far this frame, which labels their profile spans.
+    tile_batch_count: u32,
 }
 
 impl<D> RendererD3D9<D> where D: Device {
     pub(crate) fn new(core: &mut RendererCore<D>, resources: &dyn ResourceLoader)
-                      -> RendererD3D9<D> {
-        let programs = ProgramsD3D9::new(&core.device, resources);
+                      -> Result<RendererD3D9<D>, DeviceError> {
+        let programs = ProgramsD3D9::new(&core.device, resources)?;
 
         let window_size = core.options.dest.window_size(&core.device);
         let dest_blend_framebuffer_id =
-            core.allocator.allocate_framebuffer(&core.device,
-                                                window_size,
-                                                TextureFormat::RGBA8,
-                                                FramebufferTag("DestBlendD3D9"));
+            core.allocator.try_allocate_framebuffer(&core.device,
+                                                    window_size,
+                                                    TextureFormat::RGBA8,
+                                                    FramebufferTag("DestBlendD3D9"))?;
 
-        RendererD3D9 {
+        Ok(RendererD3D9 {
             programs,
             quads_vertex_indices_buffer_id: None,
             quads_vertex_indices_length: 0,
@@ -70,50 +74,64 @@ impl<D> RendererD3D9<D> where D: Device {
             pending_fills: vec![],
 
             dest_blend_framebuffer_id,
-        }
+
+            tile_batch_count: 0,
+        })
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        self.tile_batch_count = 0;
     }
 
     pub(crate) fn upload_and_draw_tiles(&mut self,
                                         core: &mut RendererCore<D>,
-                                        batch: &DrawTileBatchD3D9) {
+                                        batch: &DrawTileBatchD3D9)
+                                        -> Result<(), DeviceError> {
+        let batch_index = Some(self.tile_batch_count);
+        self.tile_batch_count += 1;
+        let _span =
+            profile::span(&core.profiler, ProfileCategory::Render, "Composite", 0, batch_index);
         if !batch.clips.is_empty() {
-            let clip_buffer_info = self.upload_clip_tiles(core, &batch.clips);
-            self.clip_tiles(core, &clip_buffer_info);
+            let clip_buffer_info = self.upload_clip_tiles(core, &batch.clips)?;
+            self.clip_tiles(core, &clip_buffer_info)?;
             core.allocator.free_buffer(clip_buffer_info.clip_buffer_id);
         }
 
-        let tile_buffer = self.upload_tiles(core, &batch.tiles);
-        let z_buffer_texture_id = self.upload_z_buffer(core, &batch.z_buffer_data);
+        let tile_buffer = self.upload_tiles(core, &batch.tiles)?;
+        let z_buffer_texture_id = self.upload_z_buffer(core, &batch.z_buffer_data)?;
 
         self.draw_tiles(core,
                         batch.tiles.len() as u32,
                         tile_buffer.tile_vertex_buffer_id,
                         batch.color_texture,
+                        batch.paint_mask_texture,
                         batch.blend_mode,
                         z_buffer_texture_id);
 
         core.allocator.free_texture(z_buffer_texture_id);
         core.allocator.free_buffer(tile_buffer.tile_vertex_buffer_id);
+        Ok(())
     }
 
     fn upload_tiles(&mut self, core: &mut RendererCore<D>, tiles: &[TileObjectPrimitive])
-                    -> TileBufferD3D9 {
+                    -> Result<TileBufferD3D9, DeviceError> {
         let tile_vertex_buffer_id =
-            core.allocator.allocate_buffer::<TileObjectPrimitive>(&core.device,
-                                                                  tiles.len() as u64,
-                                                                  BufferTag("TileD3D9"));
+            core.allocator.try_allocate_buffer::<TileObjectPrimitive>(&core.device,
+                                                                      tiles.len() as u64,
+                                                                      BufferTag("TileD3D9"))?;
         let tile_vertex_buffer = &core.allocator.get_buffer(tile_vertex_buffer_id);
         core.device.upload_to_buffer(tile_vertex_buffer, 0, tiles, BufferTarget::Vertex);
-        self.ensure_index_buffer(core, tiles.len());
+        self.ensure_index_buffer(core, tiles.len())?;
 
-        TileBufferD3D9 { tile_vertex_buffer_id }
+        Ok(TileBufferD3D9 { tile_vertex_buffer_id })
     }
 
 
-    fn ensure_index_buffer(&mut self, core: &mut RendererCore<D>, mut length: usize) {
+    fn ensure_index_buffer(&mut self, core: &mut RendererCore<D>, mut length: usize)
+                           -> Result<(), DeviceError> {
         length = length.next_power_of_two();
         if self.quads_vertex_indices_length >= length {
-            return;
+            return Ok(());
         }
 
         // TODO(pcwalton): Generate these with SIMD.
@@ -125,13 +143,15 @@ impl<D> RendererD3D9<D> where D: Device {
             ]);
         }
 
+        // Allocate the new buffer before freeing the old one so that a failed allocation leaves
+        // the old buffer in place.
+        let quads_vertex_indices_buffer_id =
+            core.allocator.try_allocate_buffer::<u32>(&core.device,
+                                                      indices.len() as u64,
+                                                      BufferTag("QuadsVertexIndicesD3D9"))?;
         if let Some(quads_vertex_indices_buffer_id) = self.quads_vertex_indices_buffer_id.take() {
             core.allocator.free_buffer(quads_vertex_indices_buffer_id);
         }
-        let quads_vertex_indices_buffer_id =
-            core.allocator.allocate_buffer::<u32>(&core.device,
-                                                  indices.len() as u64,
-                                                  BufferTag("QuadsVertexIndicesD3D9"));
         let quads_vertex_indices_buffer =
             core.allocator.get_buffer(quads_vertex_indices_buffer_id);
         core.device.upload_to_buffer(quads_vertex_indices_buffer,
@@ -140,11 +160,13 @@ impl<D> RendererD3D9<D> where D: Device {
                                      BufferTarget::Index);
         self.quads_vertex_indices_buffer_id = Some(quads_vertex_indices_buffer_id);
         self.quads_vertex_indices_length = length;
+        Ok(())
     }
 
-    pub(crate) fn add_fills(&mut self, core: &mut RendererCore<D>, fill_batch: &[Fill]) {
+    pub(crate) fn add_fills(&mut self, core: &mut RendererCore<D>, fill_batch: &[Fill])
+                            -> Result<(), DeviceError> {
         if fill_batch.is_empty() {
-            return;
+            return Ok(());
         }
 
         core.stats.fill_count += fill_batch.len();
@@ -159,32 +181,37 @@ impl<D> RendererD3D9<D> where D: Device {
 
         core.stats.alpha_tile_count = core.alpha_tile_count as usize;
 
-        core.reallocate_alpha_tile_pages_if_necessary(preserve_alpha_mask_contents);
+        core.reallocate_alpha_tile_pages_if_necessary(preserve_alpha_mask_contents)?;
 
         if self.buffered_fills.len() + self.pending_fills.len() > MAX_FILLS_PER_BATCH {
-            self.draw_buffered_fills(core);
+            self.draw_buffered_fills(core)?;
         }
 
         self.buffered_fills.extend(self.pending_fills.drain(..));
+        Ok(())
     }
 
-    pub(crate) fn draw_buffered_fills(&mut self, core: &mut RendererCore<D>) {
+    pub(crate) fn draw_buffered_fills(&mut self, core: &mut RendererCore<D>)
+                                      -> Result<(), DeviceError> {
         if self.buffered_fills.is_empty() {
-            return;
+            return Ok(());
         }
 
-        let fill_storage_info = self.upload_buffered_fills(core);
+        let _span = profile::span(&core.profiler, ProfileCategory::Render, "Fill", 0, None);
+        let fill_storage_info = self.upload_buffered_fills(core)?;
         self.draw_fills(core, fill_storage_info.fill_buffer_id, fill_storage_info.fill_count);
         core.allocator.free_buffer(fill_storage_info.fill_buffer_id);
+        Ok(())
     }
 
-    fn upload_buffered_fills(&mut self, core: &mut RendererCore<D>) -> FillBufferInfoD3D9 {
+    fn upload_buffered_fills(&mut self, core: &mut RendererCore<D>)
+                             -> Result<FillBufferInfoD3D9, DeviceError> {
         let buffered_fills = &mut self.buffered_fills;
         debug_assert!(!buffered_fills.is_empty());
 
-        let fill_buffer_id = core.allocator.allocate_buffer::<Fill>(&core.device,
-                                                                    MAX_FILLS_PER_BATCH as u64,
-                                                                    BufferTag("Fill"));
+        let fill_buffer_id = core.allocator.try_allocate_buffer::<Fill>(&core.device,
+                                                                        MAX_FILLS_PER_BATCH as u64,
+                                                                        BufferTag("Fill"))?;
         let fill_vertex_buffer = core.allocator.get_buffer(fill_buffer_id);
         debug_assert!(buffered_fills.len() <= u32::MAX as usize);
         core.device.upload_to_buffer(fill_vertex_buffer, 0, &buffered_fills, BufferTarget::Vertex);
@@ -192,7 +219,7 @@ impl<D> RendererD3D9<D> where D: Device {
         let fill_count = buffered_fills.len() as u32;
         buffered_fills.clear();
 
-        FillBufferInfoD3D9 { fill_buffer_id, fill_count }
+        Ok(FillBufferInfoD3D9 { fill_buffer_id, fill_count })
     }
 
     fn draw_fills(&mut self,
@@ -218,7 +245,8 @@ impl<D> RendererD3D9<D> where D: Device {
                                                          fill_raster_program,
                                                          fill_vertex_buffer,
                                                          quad_vertex_positions_buffer,
-                                                         quad_vertex_indices_buffer);
+                                                         quad_vertex_indices_buffer)
+                                                         .expect("Failed to create vertex array!");
 
         let mut clear_color = None;
         if !core.framebuffer_flags.contains(FramebufferFlags::MASK_FRAMEBUFFER_IS_DIRTY) {
@@ -239,6 +267,8 @@ impl<D> RendererD3D9<D> where D: Device {
                  UniformData::Vec2(mask_viewport.size().to_f32().0)),
                 (&fill_raster_program.tile_size_uniform,
                  UniformData::Vec2(F32x2::new(TILE_WIDTH as f32, TILE_HEIGHT as f32))),
+                (&fill_raster_program.sample_count_uniform,
+                 UniformData::Int(core.antialiasing_mode.sample_count() as i32)),
             ],
             images: &[],
             storage_buffers: &[],
@@ -263,13 +293,14 @@ impl<D> RendererD3D9<D> where D: Device {
         core.framebuffer_flags.insert(FramebufferFlags::MASK_FRAMEBUFFER_IS_DIRTY);
     }
 
-    fn clip_tiles(&mut self, core: &mut RendererCore<D>, clip_buffer_info: &ClipBufferInfo) {
+    fn clip_tiles(&mut self, core: &mut RendererCore<D>, clip_buffer_info: &ClipBufferInfo)
+                  -> Result<(), DeviceError> {
         // Allocate temp mask framebuffer.
         let mask_temp_framebuffer_id =
-            core.allocator.allocate_framebuffer(&core.device,
-                                                self.mask_viewport(core).size(),
-                                                core.mask_texture_format(),
-                                                FramebufferTag("TempClipMaskD3D9"));
+            core.allocator.try_allocate_framebuffer(&core.device,
+                                                    self.mask_viewport(core).size(),
+                                                    core.mask_texture_format(),
+                                                    FramebufferTag("TempClipMaskD3D9"))?;
         let mask_temp_framebuffer = core.allocator.get_framebuffer(mask_temp_framebuffer_id);
 
         let mask_storage = core.mask_storage.as_ref().expect("Where's the mask storage?");
@@ -289,13 +320,15 @@ impl<D> RendererD3D9<D> where D: Device {
                                              &self.programs.tile_clip_copy_program,
                                              clip_vertex_buffer,
                                              quad_vertex_positions_buffer,
-                                             quad_vertex_indices_buffer);
+                                             quad_vertex_indices_buffer)
+                                             .expect("Failed to create vertex array!");
         let tile_clip_combine_vertex_array =   
             ClipTileCombineVertexArrayD3D9::new(&core.device,
                                                 &self.programs.tile_clip_combine_program,
                                                 clip_vertex_buffer,
                                                 quad_vertex_positions_buffer,
-                                                quad_vertex_indices_buffer);
+                                                quad_vertex_indices_buffer)
+                                                .expect("Failed to create vertex array!");
 
         let timer_query = core.timer_query_cache.start_timing_draw_call(&core.device,
                                                                         &core.options);
@@ -353,31 +386,33 @@ impl<D> RendererD3D9<D> where D: Device {
         core.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
 
         core.allocator.free_framebuffer(mask_temp_framebuffer_id);
+        Ok(())
     }
 
     fn upload_z_buffer(&mut self, core: &mut RendererCore<D>, z_buffer_map: &DenseTileMap<i32>)
-                       -> TextureID {
-        let z_buffer_texture_id = core.allocator.allocate_texture(&core.device,
-                                                                  z_buffer_map.rect.size(),
-                                                                  TextureFormat::RGBA8,
-                                                                  TextureTag("ZBufferD3D9"));
+                       -> Result<TextureID, DeviceError> {
+        let z_buffer_texture_id = core.allocator.try_allocate_texture(&core.device,
+                                                                      z_buffer_map.rect.size(),
+                                                                      TextureFormat::RGBA8,
+                                                                      TextureTag("ZBufferD3D9"))?;
         let z_buffer_texture = core.allocator.get_texture(z_buffer_texture_id);
         debug_assert_eq!(z_buffer_map.rect.origin(), Vector2I::default());
         let z_data: &[u8] = z_buffer_map.data.as_byte_slice();
         core.device.upload_to_texture(z_buffer_texture,
                                       z_buffer_map.rect,
                                       TextureDataRef::U8(&z_data));
-        z_buffer_texture_id
+        Ok(z_buffer_texture_id)
     }
 
     // Uploads clip tiles from CPU to GPU.
-    fn upload_clip_tiles(&mut self, core: &mut RendererCore<D>, clips: &[Clip]) -> ClipBufferInfo {
-        let clip_buffer_id = core.allocator.allocate_buffer::<Clip>(&core.device,
-                                                                    clips.len() as u64,
-                                                                    BufferTag("ClipD3D9"));
+    fn upload_clip_tiles(&mut self, core: &mut RendererCore<D>, clips: &[Clip])
+                         -> Result<ClipBufferInfo, DeviceError> {
+        let clip_buffer_id = core.allocator.try_allocate_buffer::<Clip>(&core.device,
+                                                                        clips.len() as u64,
+                                                                        BufferTag("ClipD3D9"))?;
         let clip_buffer = core.allocator.get_buffer(clip_buffer_id);
         core.device.upload_to_buffer(clip_buffer, 0, clips, BufferTarget::Vertex);
-        ClipBufferInfo { clip_buffer_id, clip_count: clips.len() as u32 }
+        Ok(ClipBufferInfo { clip_buffer_id, clip_count: clips.len() as u32 })
     }
 
     fn draw_tiles(&mut self,
@@ -385,6 +420,7 @@ impl<D> RendererD3D9<D> where D: Device {
                   tile_count: u32,
                   tile_vertex_buffer_id: BufferID,
                   color_texture_0: Option<TileBatchTexture>,
+                  paint_mask_texture: Option<TileBatchTexture>,
                   blend_mode: BlendMode,
                   z_buffer_texture_id: TextureID) {
         // TODO(pcwalton): Disable blend for solid tiles.
@@ -421,7 +457,8 @@ impl<D> RendererD3D9<D> where D: Device {
         core.set_uniforms_for_drawing_tiles(&tile_raster_program.common,
                                             &mut textures,
                                             &mut uniforms,
-                                            color_texture_0);
+                                            color_texture_0,
+                                            paint_mask_texture);
 
         uniforms.push((&tile_raster_program.transform_uniform,
                        UniformData::Mat4(self.tile_transform(core).to_columns())));
@@ -440,27 +477,34 @@ impl<D> RendererD3D9<D> where D: Device {
                                                          &self.programs.tile_program,
                                                          tile_vertex_buffer,
                                                          quad_vertex_positions_buffer,
-                                                         quad_vertex_indices_buffer);
-
-        core.device.draw_elements_instanced(6, tile_count, &RenderState {
-            target: &core.draw_render_target(),
-            program: &tile_raster_program.common.program,
-            vertex_array: &tile_vertex_array.vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &textures,
-            images: &[],
-            storage_buffers: &[],
-            uniforms: &uniforms,
-            viewport: draw_viewport,
-            options: RenderOptions {
-                blend: blend_mode.to_blend_state(),
-                stencil: self.stencil_state(core),
-                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
-                ..RenderOptions::default()
-            },
-        });
+                                                         quad_vertex_indices_buffer)
+                                                         .expect("Failed to create vertex array!");
+
+        let scissor_rects = core.draw_scissor_rects();
+        let mut draw_count = 0;
+        for scissor_rect in scissor_rects {
+            core.device.draw_elements_instanced(6, tile_count, &RenderState {
+                target: &core.draw_render_target(),
+                program: &tile_raster_program.common.program,
+                vertex_array: &tile_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &textures,
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &uniforms,
+                viewport: draw_viewport,
+                options: RenderOptions {
+                    blend: blend_mode.to_blend_state(),
+                    stencil: self.stencil_state(core),
+                    clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                    scissor: scissor_rect,
+                    ..RenderOptions::default()
+                },
+            });
+            draw_count += 1;
+        }
 
-        core.stats.drawcall_count += 1;
+        core.stats.drawcall_count += draw_count;
         core.finish_timing_draw_call(&timer_query);
         core.current_timer.as_mut().unwrap().push_query(TimeCategory::Composite, timer_query);
 
@@ -497,10 +541,12 @@ impl<D> RendererD3D9<D> where D: Device {
                                               .get_buffer(quads_vertex_indices_buffer_id);
         let vertex_buffer = core.allocator.get_buffer(vertex_buffer_id);
 
-        let tile_copy_vertex_array = CopyTileVertexArray::new(&core.device,
-                                                              &self.programs.tile_copy_program,
-                                                              vertex_buffer,
-                                                              quads_vertex_indices_buffer);
+        let tile_copy_vertex_array =
+            CopyTileVertexArray::new(&core.device,
+                                     &self.programs.tile_copy_program,
+                                     vertex_buffer,
+                                     quads_vertex_indices_buffer)
+                                     .expect("Failed to create vertex array!");
 
         let dest_blend_framebuffer = core.allocator
                                          .get_framebuffer(self.dest_blend_framebuffer_id);
@@ -9,7 +9,8 @@
 // except according to those terms.
 
 use crate::gpu::shaders::{TILE_INSTANCE_SIZE, TileProgramCommon};
-use pathfinder_gpu::{BufferTarget, Device, VertexAttrClass, VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::{BufferTarget, Device, DeviceError, VertexAttrClass, VertexAttrDescriptor};
+use pathfinder_gpu::VertexAttrType;
 use pathfinder_resources::ResourceLoader;
 
 const FILL_INSTANCE_SIZE: usize = 12;
@@ -25,8 +26,8 @@ impl<D> FillVertexArrayD3D9<D> where D: Device {
                vertex_buffer: &D::Buffer,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> FillVertexArrayD3D9<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<FillVertexArrayD3D9<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let tess_coord_attr = device.get_vertex_attr(&fill_program.program, "TessCoord").unwrap();
         let line_segment_attr = device.get_vertex_attr(&fill_program.program, "LineSegment")
@@ -64,7 +65,7 @@ impl<D> FillVertexArrayD3D9<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        FillVertexArrayD3D9 { vertex_array }
+        Ok(FillVertexArrayD3D9 { vertex_array })
     }
 }
 
@@ -78,8 +79,8 @@ impl<D> TileVertexArrayD3D9<D> where D: Device {
                tile_vertex_buffer: &D::Buffer,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> TileVertexArrayD3D9<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<TileVertexArrayD3D9<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let tile_offset_attr =
             device.get_vertex_attr(&tile_program.common.program, "TileOffset").unwrap();
@@ -151,7 +152,7 @@ impl<D> TileVertexArrayD3D9<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        TileVertexArrayD3D9 { vertex_array }
+        Ok(TileVertexArrayD3D9 { vertex_array })
     }
 }
 
@@ -165,8 +166,8 @@ impl<D> ClipTileCopyVertexArrayD3D9<D> where D: Device {
                vertex_buffer: &D::Buffer,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> ClipTileCopyVertexArrayD3D9<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<ClipTileCopyVertexArrayD3D9<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let tile_offset_attr =
             device.get_vertex_attr(&clip_tile_copy_program.program, "TileOffset").unwrap();
@@ -195,7 +196,7 @@ impl<D> ClipTileCopyVertexArrayD3D9<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        ClipTileCopyVertexArrayD3D9 { vertex_array }
+        Ok(ClipTileCopyVertexArrayD3D9 { vertex_array })
     }
 }
 
@@ -209,8 +210,8 @@ impl<D> ClipTileCombineVertexArrayD3D9<D> where D: Device {
                vertex_buffer: &D::Buffer,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> ClipTileCombineVertexArrayD3D9<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<ClipTileCombineVertexArrayD3D9<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let tile_offset_attr =
             device.get_vertex_attr(&clip_tile_combine_program.program, "TileOffset").unwrap();
@@ -272,7 +273,7 @@ impl<D> ClipTileCombineVertexArrayD3D9<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        ClipTileCombineVertexArrayD3D9 { vertex_array }
+        Ok(ClipTileCombineVertexArrayD3D9 { vertex_array })
     }
 }
 
@@ -285,8 +286,8 @@ impl<D> CopyTileVertexArray<D> where D: Device {
                copy_tile_program: &CopyTileProgram<D>,
                copy_tile_vertex_buffer: &D::Buffer,
                quads_vertex_indices_buffer: &D::Buffer)
-               -> CopyTileVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<CopyTileVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let tile_position_attr =
             device.get_vertex_attr(&copy_tile_program.program, "TilePosition").unwrap();
@@ -303,7 +304,7 @@ impl<D> CopyTileVertexArray<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quads_vertex_indices_buffer, BufferTarget::Index);
 
-        CopyTileVertexArray { vertex_array }
+        Ok(CopyTileVertexArray { vertex_array })
     }
 }
 
@@ -311,21 +312,24 @@ pub struct FillProgramD3D9<D> where D: Device {
     pub program: D::Program,
     pub framebuffer_size_uniform: D::Uniform,
     pub tile_size_uniform: D::Uniform,
+    pub sample_count_uniform: D::Uniform,
     pub area_lut_texture: D::TextureParameter,
 }
 
 impl<D> FillProgramD3D9<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> FillProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/fill");
+    fn new(device: &D, resources: &dyn ResourceLoader) -> Result<FillProgramD3D9<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "d3d9/fill")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
+        let sample_count_uniform = device.get_uniform(&program, "SampleCount");
         let area_lut_texture = device.get_texture_parameter(&program, "AreaLUT");
-        FillProgramD3D9 {
+        Ok(FillProgramD3D9 {
             program,
             framebuffer_size_uniform,
             tile_size_uniform,
+            sample_count_uniform,
             area_lut_texture,
-        }
+        })
     }
 }
 
@@ -336,12 +340,12 @@ pub struct TileProgramD3D9<D> where D: Device {
 }
 
 impl<D> TileProgramD3D9<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> TileProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile");
+    fn new(device: &D, resources: &dyn ResourceLoader) -> Result<TileProgramD3D9<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "d3d9/tile")?;
         let dest_texture = device.get_texture_parameter(&program, "DestTexture");
         let transform_uniform = device.get_uniform(&program, "Transform");
         let common = TileProgramCommon::new(device, program);
-        TileProgramD3D9 { common, dest_texture, transform_uniform }
+        Ok(TileProgramD3D9 { common, dest_texture, transform_uniform })
     }
 }
 
@@ -352,11 +356,12 @@ pub struct ClipTileCombineProgramD3D9<D> where D: Device {
 }
 
 impl<D> ClipTileCombineProgramD3D9<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileCombineProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_clip_combine");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ClipTileCombineProgramD3D9<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "d3d9/tile_clip_combine")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
-        ClipTileCombineProgramD3D9 { program, src_texture, framebuffer_size_uniform }
+        Ok(ClipTileCombineProgramD3D9 { program, src_texture, framebuffer_size_uniform })
     }
 }
 
@@ -367,11 +372,12 @@ pub struct ClipTileCopyProgramD3D9<D> where D: Device {
 }
 
 impl<D> ClipTileCopyProgramD3D9<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ClipTileCopyProgramD3D9<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_clip_copy");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ClipTileCopyProgramD3D9<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "d3d9/tile_clip_copy")?;
         let src_texture = device.get_texture_parameter(&program, "Src");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
-        ClipTileCopyProgramD3D9 { program, src_texture, framebuffer_size_uniform }
+        Ok(ClipTileCopyProgramD3D9 { program, src_texture, framebuffer_size_uniform })
     }
 }
 
@@ -384,19 +390,20 @@ pub struct CopyTileProgram<D> where D: Device {
 }
 
 impl<D> CopyTileProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> CopyTileProgram<D> {
-        let program = device.create_raster_program(resources, "d3d9/tile_copy");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<CopyTileProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "d3d9/tile_copy")?;
         let transform_uniform = device.get_uniform(&program, "Transform");
         let tile_size_uniform = device.get_uniform(&program, "TileSize");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let src_texture = device.get_texture_parameter(&program, "Src");
-        CopyTileProgram {
+        Ok(CopyTileProgram {
             program,
             transform_uniform,
             tile_size_uniform,
             framebuffer_size_uniform,
             src_texture,
-        }
+        })
     }
 }
 
@@ -409,14 +416,14 @@ pub struct D3D9Programs<D> where D: Device {
 }
 
 impl<D> D3D9Programs<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> D3D9Programs<D> {
-        D3D9Programs {
-            fill_program: FillProgramD3D9::new(device, resources),
-            tile_program: TileProgramD3D9::new(device, resources),
-            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources),
-            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources),
-            tile_copy_program: CopyTileProgram::new(device, resources),
-        }
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> Result<D3D9Programs<D>, DeviceError> {
+        Ok(D3D9Programs {
+            fill_program: FillProgramD3D9::new(device, resources)?,
+            tile_program: TileProgramD3D9::new(device, resources)?,
+            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources)?,
+            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources)?,
+            tile_copy_program: CopyTileProgram::new(device, resources)?,
+        })
     }
 }
 
@@ -429,13 +436,13 @@ pub struct ProgramsD3D9<D> where D: Device {
 }
 
 impl<D> ProgramsD3D9<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsD3D9<D> {
-        ProgramsD3D9 {
-            fill_program: FillProgramD3D9::new(device, resources),
-            tile_program: TileProgramD3D9::new(device, resources),
-            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources),
-            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources),
-            tile_copy_program: CopyTileProgram::new(device, resources),
-        }
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> Result<ProgramsD3D9<D>, DeviceError> {
+        Ok(ProgramsD3D9 {
+            fill_program: FillProgramD3D9::new(device, resources)?,
+            tile_program: TileProgramD3D9::new(device, resources)?,
+            tile_clip_copy_program: ClipTileCopyProgramD3D9::new(device, resources)?,
+            tile_clip_combine_program: ClipTileCombineProgramD3D9::new(device, resources)?,
+            tile_copy_program: CopyTileProgram::new(device, resources)?,
+        })
     }
 }
@@ -19,7 +19,7 @@ use crate::gpu::options::RendererLevel;
 use crate::gpu::perf::{RenderStats, RenderTime};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::{Vector2I, vec2i};
-use pathfinder_gpu::Device;
+use pathfinder_gpu::{Device, DeviceError};
 use pathfinder_gpu::allocator::GPUMemoryAllocator;
 use pathfinder_resources::ResourceLoader;
 use pathfinder_ui::{FONT_ASCENT, LINE_HEIGHT, PADDING, UIPresenter, WINDOW_COLOR};
@@ -53,16 +53,16 @@ impl<D> DebugUIPresenter<D> where D: Device {
                resources: &dyn ResourceLoader,
                framebuffer_size: Vector2I,
                renderer_level: RendererLevel)
-               -> DebugUIPresenter<D> {
-        let ui_presenter = UIPresenter::new(device, resources, framebuffer_size);
-        DebugUIPresenter {
+               -> Result<DebugUIPresenter<D>, DeviceError> {
+        let ui_presenter = UIPresenter::new(device, resources, framebuffer_size)?;
+        Ok(DebugUIPresenter {
             ui_presenter,
             cpu_samples: SampleBuffer::new(),
             gpu_samples: SampleBuffer::new(),
             backend_name: device.backend_name(),
             device_name: device.device_name(),
             renderer_level,
-        }
+        })
     }
 
     pub fn add_sample(&mut self, stats: RenderStats, rendering_time: RenderTime) {
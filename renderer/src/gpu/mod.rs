@@ -16,6 +16,7 @@ pub mod debug;
 pub mod options;
 pub mod perf;
 pub mod renderer;
+pub mod tiled_export;
 
 pub(crate) mod blend;
 pub(crate) mod shaders;
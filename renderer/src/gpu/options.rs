@@ -8,9 +8,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use pathfinder_color::ColorF;
+use pathfinder_color::{ColorF, ColorSpace};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
+use pathfinder_gpu::allocator::AllocatorPolicy;
 use pathfinder_gpu::{Device, FeatureLevel};
 
 /// Renderer options that can't be changed after the renderer is created.
@@ -28,6 +29,24 @@ pub struct RendererOptions<D> where D: Device {
     pub background_color: Option<ColorF>,
     /// Whether to display the debug UI.
     pub show_debug_ui: bool,
+    /// Whether to blend in linear light, using half-float render targets, instead of in
+    /// gamma-encoded sRGB.
+    ///
+    /// This makes translucent overlaps and antialiased edges look like they would in
+    /// color-managed software, at the cost of twice the texture memory. It is only supported at
+    /// `RendererLevel::D3D9` for now, because the D3D11 compositor writes through `rgba8` images.
+    /// `Renderer::new()` fails with `DeviceError::Unsupported` if it's set at D3D11.
+    pub linear_compositing: bool,
+    /// The color space that the destination framebuffer is displayed in.
+    ///
+    /// For anything other than sRGB, the scene is rendered into an intermediate framebuffer and
+    /// converted when it is copied to the destination.
+    pub dest_color_space: ColorSpace,
+    /// How GPU memory is recycled, and how much of it the renderer may use.
+    ///
+    /// A budget that's too small for the renderer's own resources makes `Renderer::new()` fail.
+    /// Changes made later take effect at the next `begin_scene()`.
+    pub gpu_memory_policy: AllocatorPolicy,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -52,6 +71,9 @@ impl<D> Default for RendererOptions<D> where D: Device {
             dest: DestFramebuffer::default(),
             background_color: None,
             show_debug_ui: false,
+            linear_compositing: false,
+            dest_color_space: ColorSpace::SRGB,
+            gpu_memory_policy: AllocatorPolicy::default(),
         }
     }
 }
@@ -91,8 +91,9 @@ impl<D> TimerQueryCache<D> where D: Device {
         TimerQueryCache { free_queries: vec![] }
     }
 
-    pub(crate) fn alloc(&mut self, device: &D) -> D::TimerQuery {
-        self.free_queries.pop().unwrap_or_else(|| device.create_timer_query())
+    // Devices that can't create timer queries simply don't report GPU times.
+    pub(crate) fn alloc(&mut self, device: &D) -> Option<D::TimerQuery> {
+        self.free_queries.pop().or_else(|| device.create_timer_query().ok())
     }
 
     pub(crate) fn free(&mut self, old_query: D::TimerQuery) {
@@ -105,7 +106,7 @@ impl<D> TimerQueryCache<D> where D: Device {
             return None;
         }
 
-        let timer_query = self.alloc(device);
+        let timer_query = self.alloc(device)?;
         device.begin_timer_query(&timer_query);
         Some(timer_query)
     }
@@ -18,22 +18,27 @@ use crate::gpu::shaders::{BlitProgram, BlitVertexArray, ClearProgram, ClearVerte
 use crate::gpu::shaders::{ProgramsCore, ReprojectionProgram, ReprojectionVertexArray};
 use crate::gpu::shaders::{StencilProgram, StencilVertexArray, TileProgramCommon, VertexArraysCore};
 use crate::gpu_data::{ColorCombineMode, RenderCommand, TextureLocation, TextureMetadataEntry};
-use crate::gpu_data::{TexturePageDescriptor, TexturePageId, TileBatchTexture};
-use crate::options::BoundingQuad;
+use crate::gpu_data::{TextureAtlasRegion, TexturePageDescriptor, TexturePageId, TileBatchTexture};
+use crate::options::{AntialiasingMode, BoundingQuad};
+use crate::profile::Profiler;
+use crate::scene::MaskMode;
 use crate::tiles::{TILE_HEIGHT, TILE_WIDTH};
 use half::f16;
-use pathfinder_color::{self as color, ColorF, ColorU};
-use pathfinder_content::effects::{BlendMode, BlurDirection, Filter, PatternFilter};
+use pathfinder_color::{self as color, ColorF, ColorSpace, ColorU};
+use pathfinder_content::effects::{BlendMode, BlurDirection, Filter, PatternFilter, SubpixelLayout};
+use pathfinder_content::pattern::{AlphaMode, Image, ImageFormat};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform3d::Transform4F;
 use pathfinder_geometry::util;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, Vector4F, vec2f, vec2i};
 use pathfinder_gpu::allocator::{BufferID, BufferTag, FramebufferID, FramebufferTag};
-use pathfinder_gpu::allocator::{GPUMemoryAllocator, TextureID, TextureTag};
+use pathfinder_gpu::allocator::{GPUMemoryAllocator, GPUMemorySnapshot};
+use pathfinder_gpu::allocator::{TextureID, TextureTag};
 use pathfinder_gpu::{BufferData, BufferTarget, ClearOps, DepthFunc, DepthState, Device, Primitive};
 use pathfinder_gpu::{RenderOptions, RenderState, RenderTarget, StencilFunc, StencilState};
-use pathfinder_gpu::{TextureBinding, TextureDataRef, TextureFormat, UniformBinding, UniformData};
+use pathfinder_gpu::{TextureBinding, TextureData, TextureDataRef, TextureFormat, UniformBinding};
+use pathfinder_gpu::{DeviceError, TextureSamplingFlags, UniformData};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::{F32x2, F32x4, I32x2};
 use std::collections::VecDeque;
@@ -51,7 +56,7 @@ pub(crate) const MASK_TILES_DOWN: u32 = 256;
 const SQRT_2_PI_INV: f32 = 0.3989422804014327;
 
 const TEXTURE_METADATA_ENTRIES_PER_ROW: i32 = 128;
-const TEXTURE_METADATA_TEXTURE_WIDTH:   i32 = TEXTURE_METADATA_ENTRIES_PER_ROW * 8;
+const TEXTURE_METADATA_TEXTURE_WIDTH:   i32 = TEXTURE_METADATA_ENTRIES_PER_ROW * 10;
 const TEXTURE_METADATA_TEXTURE_HEIGHT:  i32 = 65536 / TEXTURE_METADATA_ENTRIES_PER_ROW;
 
 // FIXME(pcwalton): Shrink this again!
@@ -65,6 +70,18 @@ const COMBINER_CTRL_FILTER_BLUR: i32 =              0x3;
 const COMBINER_CTRL_COLOR_FILTER_SHIFT: i32 =       4;
 const COMBINER_CTRL_COLOR_COMBINE_SHIFT: i32 =      6;
 const COMBINER_CTRL_COMPOSITE_SHIFT: i32 =          8;
+const COMBINER_CTRL_PAINT_MASK_SHIFT: i32 =         12;
+
+const COMBINER_CTRL_PAINT_MASK_ALPHA: i32 =         0x1;
+const COMBINER_CTRL_PAINT_MASK_LUMINANCE: i32 =     0x2;
+// Render targets have premultiplied alpha. The ctrl word travels as a half float, which holds
+// bits 4 through 14 exactly, so this is the highest bit that can be used.
+const COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED: i32 = 0x4;
+
+const BLIT_ALPHA_MODE_PREMULTIPLIED: i32 =          0;
+const BLIT_ALPHA_MODE_STRAIGHT: i32 =               1;
+const BLIT_ALPHA_MODE_UNPREMULTIPLY: i32 =          2;
+const BLIT_ALPHA_MODE_MASK: i32 =                   3;
 
 pub struct Renderer<D> where D: Device {
     // Basic data
@@ -105,6 +122,7 @@ pub(crate) struct RendererCore<D> where D: Device {
     pub(crate) stats: RenderStats,
     pub(crate) current_timer: Option<PendingTimer<D>>,
     pub(crate) timer_query_cache: TimerQueryCache<D>,
+    pub(crate) profiler: Option<Profiler>,
 
     // Core shaders
     pub(crate) programs: ProgramsCore<D>,
@@ -128,6 +146,9 @@ pub(crate) struct RendererCore<D> where D: Device {
     pub(crate) mask_storage: Option<MaskStorage>,
     pub(crate) alpha_tile_count: u32,
     pub(crate) framebuffer_flags: FramebufferFlags,
+    pub(crate) damage_rects: Vec<RectI>,
+    pub(crate) antialiasing_mode: AntialiasingMode,
+    pub(crate) subpixel_layout: SubpixelLayout,
 }
 
 // TODO(pcwalton): Remove this.
@@ -144,70 +165,79 @@ pub(crate) struct MaskStorage {
 }
 
 impl<D> Renderer<D> where D: Device {
+    /// Creates a new renderer, compiling its shaders and allocating the GPU resources it needs up
+    /// front.
+    ///
+    /// Returns an error if any of those resources couldn't be created, for example because a
+    /// shader failed to compile or the GPU ran out of memory.
     pub fn new(device: D,
                resources: &dyn ResourceLoader,
                mode: RendererMode,
                options: RendererOptions<D>)
-               -> Renderer<D> {
-        let mut allocator = GPUMemoryAllocator::new();
+               -> Result<Renderer<D>, DeviceError> {
+        if options.linear_compositing && mode.level == RendererLevel::D3D11 {
+            return Err(DeviceError::Unsupported("linear compositing at D3D11"));
+        }
+
+        let mut allocator = GPUMemoryAllocator::with_policy(options.gpu_memory_policy);
 
         device.begin_commands();
 
         let quad_vertex_positions_buffer_id =
-            allocator.allocate_buffer::<u16>(&device,
-                                             QUAD_VERTEX_POSITIONS.len() as u64,
-                                             BufferTag("QuadVertexPositions"));
+            allocator.try_allocate_buffer::<u16>(&device,
+                                                 QUAD_VERTEX_POSITIONS.len() as u64,
+                                                 BufferTag("QuadVertexPositions"))?;
         device.upload_to_buffer(allocator.get_buffer(quad_vertex_positions_buffer_id),
                                 0,
                                 &QUAD_VERTEX_POSITIONS,
                                 BufferTarget::Vertex);
         let quad_vertex_indices_buffer_id =
-            allocator.allocate_buffer::<u32>(&device,
-                                             QUAD_VERTEX_INDICES.len() as u64,
-                                             BufferTag("QuadVertexIndices"));
+            allocator.try_allocate_buffer::<u32>(&device,
+                                                 QUAD_VERTEX_INDICES.len() as u64,
+                                                 BufferTag("QuadVertexIndices"))?;
         device.upload_to_buffer(allocator.get_buffer(quad_vertex_indices_buffer_id),
                                 0,
                                 &QUAD_VERTEX_INDICES,
                                 BufferTarget::Index);
 
-        let area_lut_texture_id = allocator.allocate_texture(&device,
-                                                             Vector2I::splat(256),
-                                                             TextureFormat::RGBA8,
-                                                             TextureTag("AreaLUT"));
-        let gamma_lut_texture_id = allocator.allocate_texture(&device,
-                                                              vec2i(256, 8),
-                                                              TextureFormat::R8,
-                                                              TextureTag("GammaLUT"));
+        let area_lut_texture_id = allocator.try_allocate_texture(&device,
+                                                                 Vector2I::splat(256),
+                                                                 TextureFormat::RGBA8,
+                                                                 TextureTag("AreaLUT"))?;
+        let gamma_lut_texture_id = allocator.try_allocate_texture(&device,
+                                                                  vec2i(256, 8),
+                                                                  TextureFormat::R8,
+                                                                  TextureTag("GammaLUT"))?;
         device.upload_png_to_texture(resources,
                                      "area-lut",
                                      allocator.get_texture(area_lut_texture_id),
-                                     TextureFormat::RGBA8);
+                                     TextureFormat::RGBA8)?;
         device.upload_png_to_texture(resources,
                                      "gamma-lut",
                                      allocator.get_texture(gamma_lut_texture_id),
-                                     TextureFormat::R8);
+                                     TextureFormat::R8)?;
 
         let window_size = options.dest.window_size(&device);
         let intermediate_dest_framebuffer_id =
-            allocator.allocate_framebuffer(&device,
-                                           window_size,
-                                           TextureFormat::RGBA8,
-                                           FramebufferTag("IntermediateDest"));
+            allocator.try_allocate_framebuffer(&device,
+                                               window_size,
+                                               TextureFormat::RGBA8,
+                                               FramebufferTag("IntermediateDest"))?;
 
         let texture_metadata_texture_size = vec2i(TEXTURE_METADATA_TEXTURE_WIDTH,
                                                   TEXTURE_METADATA_TEXTURE_HEIGHT);
         let texture_metadata_texture_id =
-            allocator.allocate_texture(&device,
-                                       texture_metadata_texture_size,
-                                       TextureFormat::RGBA16F,
-                                       TextureTag("TextureMetadata"));
+            allocator.try_allocate_texture(&device,
+                                           texture_metadata_texture_size,
+                                           TextureFormat::RGBA16F,
+                                           TextureTag("TextureMetadata"))?;
 
-        let core_programs = ProgramsCore::new(&device, resources);
+        let core_programs = ProgramsCore::new(&device, resources)?;
         let core_vertex_arrays =
              VertexArraysCore::new(&device,
                                    &core_programs,
                                    allocator.get_buffer(quad_vertex_positions_buffer_id),
-                                   allocator.get_buffer(quad_vertex_indices_buffer_id));
+                                   allocator.get_buffer(quad_vertex_indices_buffer_id))?;
 
         let mut core = RendererCore {
             device,
@@ -217,6 +247,7 @@ impl<D> Renderer<D> where D: Device {
             stats: RenderStats::default(),
             current_timer: None,
             timer_query_cache: TimerQueryCache::new(),
+            profiler: None,
             renderer_flags: RendererFlags::empty(),
 
             programs: core_programs,
@@ -237,24 +268,30 @@ impl<D> Renderer<D> where D: Device {
             mask_storage: None,
             alpha_tile_count: 0,
             framebuffer_flags: FramebufferFlags::empty(),
+            damage_rects: vec![],
+            antialiasing_mode: AntialiasingMode::default(),
+            subpixel_layout: SubpixelLayout::default(),
         };
 
         let level_impl = match core.mode.level {
             RendererLevel::D3D9 => {
-                RendererLevelImpl::D3D9(RendererD3D9::new(&mut core, resources))
+                RendererLevelImpl::D3D9(RendererD3D9::new(&mut core, resources)?)
             }
             RendererLevel::D3D11 => {
-                RendererLevelImpl::D3D11(RendererD3D11::new(&mut core, resources))
+                RendererLevelImpl::D3D11(RendererD3D11::new(&mut core, resources)?)
             }
         };
 
-        let blit_program = BlitProgram::new(&core.device, resources);
-        let clear_program = ClearProgram::new(&core.device, resources);
-        let stencil_program = StencilProgram::new(&core.device, resources);
-        let reprojection_program = ReprojectionProgram::new(&core.device, resources);
+        let blit_program = BlitProgram::new(&core.device, resources)?;
+        let clear_program = ClearProgram::new(&core.device, resources)?;
+        let stencil_program = StencilProgram::new(&core.device, resources)?;
+        let reprojection_program = ReprojectionProgram::new(&core.device, resources)?;
 
         let debug_ui_presenter = if core.options.show_debug_ui {
-            Some(DebugUIPresenter::new(&core.device, resources, window_size, core.mode.level))
+            Some(DebugUIPresenter::new(&core.device,
+                                       resources,
+                                       window_size,
+                                       core.mode.level)?)
         } else {
             None
         };
@@ -266,11 +303,11 @@ impl<D> Renderer<D> where D: Device {
                                &reprojection_program,
                                &stencil_program,
                                quad_vertex_positions_buffer_id,
-                               quad_vertex_indices_buffer_id);
+                               quad_vertex_indices_buffer_id)?;
 
         core.device.end_commands();
 
-        Renderer {
+        Ok(Renderer {
             core,
             level_impl,
 
@@ -287,76 +324,97 @@ impl<D> Renderer<D> where D: Device {
             debug_ui_presenter,
             last_stats: VecDeque::new(),
             last_rendering_time: None,
-        }
+        })
     }
 
     pub fn begin_scene(&mut self) {
         self.core.framebuffer_flags = FramebufferFlags::empty();
 
         self.core.device.begin_commands();
+        self.core.allocator.set_policy(self.core.options.gpu_memory_policy);
         self.core.current_timer = Some(PendingTimer::new());
         self.core.stats = RenderStats::default();
 
         self.core.alpha_tile_count = 0;
+        self.core.damage_rects.clear();
     }
 
-    pub fn render_command(&mut self, command: &RenderCommand) {
+    pub fn render_command(&mut self, command: &RenderCommand) -> Result<(), DeviceError> {
         debug!("render command: {:?}", command);
         match *command {
-            RenderCommand::Start { bounding_quad, path_count, needs_readable_framebuffer } => {
-                self.start_rendering(bounding_quad, path_count, needs_readable_framebuffer);
+            RenderCommand::Start {
+                bounding_quad,
+                path_count,
+                needs_readable_framebuffer,
+                ref damage_rects,
+                antialiasing_mode,
+                subpixel_layout,
+            } => {
+                self.start_rendering(bounding_quad,
+                                     path_count,
+                                     needs_readable_framebuffer,
+                                     damage_rects,
+                                     antialiasing_mode,
+                                     subpixel_layout)?;
             }
             RenderCommand::AllocateTexturePage { page_id, ref descriptor } => {
-                self.allocate_pattern_texture_page(page_id, descriptor)
+                self.allocate_pattern_texture_page(page_id, descriptor)?
             }
             RenderCommand::UploadTexelData { ref texels, location } => {
                 self.upload_texel_data(texels, location)
             }
-            RenderCommand::DeclareRenderTarget { id, location } => {
-                self.declare_render_target(id, location)
+            RenderCommand::UploadImage { ref image, location } => {
+                self.upload_image(image, location)?
+            }
+            RenderCommand::CopyTexelData { src, dest } => self.copy_texel_data(src, dest),
+            RenderCommand::DeclareRenderTarget { id, location, padding } => {
+                self.declare_render_target(id, location, padding)
             }
             RenderCommand::UploadTextureMetadata(ref metadata) => {
                 self.upload_texture_metadata(metadata)
             }
             RenderCommand::AddFillsD3D9(ref fills) => {
-                self.level_impl.require_d3d9().add_fills(&mut self.core, fills)
+                self.level_impl.require_d3d9().add_fills(&mut self.core, fills)?
             }
             RenderCommand::FlushFillsD3D9 => {
-                self.level_impl.require_d3d9().draw_buffered_fills(&mut self.core);
+                self.level_impl.require_d3d9().draw_buffered_fills(&mut self.core)?;
             }
             RenderCommand::UploadSceneD3D11 { ref draw_segments, ref clip_segments } => {
                 self.level_impl
                     .require_d3d11()
-                    .upload_scene(&mut self.core, draw_segments, clip_segments)
+                    .upload_scene(&mut self.core, draw_segments, clip_segments)?
             }
             RenderCommand::PushRenderTarget(render_target_id) => {
                 self.push_render_target(render_target_id)
             }
             RenderCommand::PopRenderTarget => self.pop_render_target(),
             RenderCommand::PrepareClipTilesD3D11(ref batch) => {
-                self.level_impl.require_d3d11().prepare_tiles(&mut self.core, batch)
+                self.level_impl.require_d3d11().prepare_tiles(&mut self.core, batch)?
             }
             RenderCommand::DrawTilesD3D9(ref batch) => {
-                self.level_impl.require_d3d9().upload_and_draw_tiles(&mut self.core, batch)
+                self.level_impl.require_d3d9().upload_and_draw_tiles(&mut self.core, batch)?
             }
             RenderCommand::DrawTilesD3D11(ref batch) => {
-                self.level_impl.require_d3d11().prepare_and_draw_tiles(&mut self.core, batch)
+                self.level_impl.require_d3d11().prepare_and_draw_tiles(&mut self.core, batch)?
             }
             RenderCommand::Finish { cpu_build_time } => {
                 self.core.stats.cpu_build_time = cpu_build_time;
             }
         }
+        Ok(())
     }
 
-    pub fn end_scene(&mut self) {
+    pub fn end_scene(&mut self) -> Result<(), DeviceError> {
         self.clear_dest_framebuffer_if_necessary();
-        self.blit_intermediate_dest_framebuffer_if_necessary();
+
+        // Finish the frame even if the blit fails, so that the device isn't left mid-frame.
+        let result = self.blit_intermediate_dest_framebuffer_if_necessary();
 
         self.core.stats.gpu_bytes_allocated = self.core.allocator.bytes_allocated();
         self.core.stats.gpu_bytes_committed = self.core.allocator.bytes_committed();
 
         match self.level_impl {
-            RendererLevelImpl::D3D9(_) => {}
+            RendererLevelImpl::D3D9(ref mut d3d9_renderer) => d3d9_renderer.end_frame(),
             RendererLevelImpl::D3D11(ref mut d3d11_renderer) => {
                 d3d11_renderer.end_frame(&mut self.core)
             }
@@ -375,13 +433,30 @@ impl<D> Renderer<D> where D: Device {
         self.core.allocator.purge_if_needed();
 
         self.core.device.end_commands();
+        result
     }
 
     fn start_rendering(&mut self,
                        bounding_quad: BoundingQuad,
                        path_count: usize,
-                       needs_readable_framebuffer: bool) {
+                       needs_readable_framebuffer: bool,
+                       damage_rects: &[RectI],
+                       antialiasing_mode: AntialiasingMode,
+                       subpixel_layout: SubpixelLayout)
+                       -> Result<(), DeviceError> {
+        self.core.damage_rects = split_into_disjoint_rects(damage_rects);
+        self.core.antialiasing_mode = antialiasing_mode;
+        self.core.subpixel_layout = subpixel_layout;
+
         match (&self.core.options.dest, self.core.mode.level) {
+            (&DestFramebuffer::Other(_), RendererLevel::D3D11)
+                    if !self.core.damage_rects.is_empty() => {
+                // The compute tiling path always touches every tile, so render the whole scene
+                // offscreen and copy only the damaged region to the destination.
+                self.core
+                    .renderer_flags
+                    .insert(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
+            }
             (&DestFramebuffer::Other(_), _) => {
                 self.core
                     .renderer_flags
@@ -400,13 +475,23 @@ impl<D> Renderer<D> where D: Device {
             }
         }
 
+        // Converting between color spaces happens when copying out of the intermediate
+        // framebuffer.
+        if self.core.compositing_color_space() != self.core.options.dest_color_space {
+            self.core.renderer_flags.insert(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED);
+        }
+        self.core.reallocate_intermediate_dest_framebuffer_if_necessary()?;
+
         if self.core.renderer_flags.contains(RendererFlags::USE_DEPTH) {
             self.draw_stencil(&bounding_quad);
         }
 
+        self.clear_damaged_dest_framebuffer_if_necessary();
+
         self.core.stats.path_count = path_count;
 
         self.core.render_targets.clear();
+        Ok(())
     }
 
     fn update_debug_ui(&mut self) {
@@ -461,6 +546,15 @@ impl<D> Renderer<D> where D: Device {
         &mut self.core.device
     }
 
+    /// Returns true if the underlying device has been lost.
+    ///
+    /// Once this happens, nothing this renderer draws will reach the screen. To recover, drop the
+    /// renderer, create a new device, and build a new `Renderer` on top of it.
+    #[inline]
+    pub fn is_device_lost(&self) -> bool {
+        self.core.device.is_device_lost()
+    }
+
     #[inline]
     pub fn mode(&self) -> &RendererMode {
         &self.core.mode
@@ -475,6 +569,18 @@ impl<D> Renderer<D> where D: Device {
         &mut self.core.options
     }
 
+    /// Records the renderer's phases in `profiler`, or stops recording them if `None`.
+    #[inline]
+    pub fn set_profiler(&mut self, profiler: Option<Profiler>) {
+        self.core.profiler = profiler;
+    }
+
+    /// Returns the GPU memory that the renderer holds, grouped by what it's used for.
+    #[inline]
+    pub fn gpu_memory_snapshot(&self) -> GPUMemorySnapshot {
+        self.core.allocator.snapshot()
+    }
+
     #[inline]
     pub fn debug_ui_presenter_mut(&mut self) -> DebugUIPresenterInfo<D> {
         DebugUIPresenterInfo {
@@ -518,57 +624,240 @@ impl<D> Renderer<D> where D: Device {
 
     fn allocate_pattern_texture_page(&mut self,
                                      page_id: TexturePageId,
-                                     descriptor: &TexturePageDescriptor) {
+                                     descriptor: &TexturePageDescriptor)
+                                     -> Result<(), DeviceError> {
         // Fill in IDs up to the requested page ID.
         let page_index = page_id.0 as usize;
         while self.core.pattern_texture_pages.len() < page_index + 1 {
             self.core.pattern_texture_pages.push(None);
         }
 
-        // Clear out any existing texture.
+        // Pages hold linear colors when compositing in linear light, since render targets are
+        // allocated from them.
+        let texture_size = descriptor.size;
+        let format = self.core.color_texture_format();
+
+        // Keep any existing texture if it already fits the bill, as is usual when scenes are
+        // rebuilt every frame. Otherwise, clear it out.
         if let Some(old_texture_page) = self.core.pattern_texture_pages[page_index].take() {
+            let old_framebuffer =
+                self.core.allocator.get_framebuffer(old_texture_page.framebuffer_id);
+            let old_texture = self.core.device.framebuffer_texture(old_framebuffer);
+            if self.core.device.texture_size(old_texture) == texture_size &&
+                    self.core.device.texture_format(old_texture) == format {
+                self.core.pattern_texture_pages[page_index] = Some(old_texture_page);
+                return Ok(());
+            }
             self.core.allocator.free_framebuffer(old_texture_page.framebuffer_id);
         }
 
         // Allocate texture.
-        let texture_size = descriptor.size;
         let framebuffer_id = self.core
                                  .allocator
-                                 .allocate_framebuffer(&self.core.device,
-                                                       texture_size,
-                                                       TextureFormat::RGBA8,
-                                                       FramebufferTag("PatternPage"));
-        self.core.pattern_texture_pages[page_index] = Some(PatternTexturePage {
-            framebuffer_id,
-            must_preserve_contents: false,
-        });
+                                 .try_allocate_framebuffer(&self.core.device,
+                                                           texture_size,
+                                                           format,
+                                                           FramebufferTag("PatternPage"))?;
+        self.core.pattern_texture_pages[page_index] = Some(PatternTexturePage { framebuffer_id });
+        Ok(())
     }
 
     fn upload_texel_data(&mut self, texels: &[ColorU], location: TextureLocation) {
-        let texture_page = self.core
-                               .pattern_texture_pages[location.page.0 as usize]
-                               .as_mut()
-                               .expect("Texture page not allocated yet!");
-        let framebuffer_id = texture_page.framebuffer_id;
+        let framebuffer_id = self.core
+                                 .pattern_texture_pages[location.page.0 as usize]
+                                 .as_ref()
+                                 .expect("Texture page not allocated yet!")
+                                 .framebuffer_id;
         let framebuffer = self.core.allocator.get_framebuffer(framebuffer_id);
         let texture = self.core.device.framebuffer_texture(framebuffer);
-        let texels = color::color_slice_to_u8_slice(texels);
-        self.core.device.upload_to_texture(texture, location.rect, TextureDataRef::U8(texels));
-        texture_page.must_preserve_contents = true;
+        match self.core.device.texture_format(texture) {
+            TextureFormat::RGBA16F => {
+                let texels = srgb_texels_to_linear_f16(texels);
+                self.core.device.upload_to_texture(texture,
+                                                   location.rect,
+                                                   TextureDataRef::F16(&texels));
+            }
+            _ => {
+                let texels = color::color_slice_to_u8_slice(texels);
+                self.core.device.upload_to_texture(texture,
+                                                   location.rect,
+                                                   TextureDataRef::U8(texels));
+            }
+        }
+    }
+
+    // Uploads an image to a staging texture in its own format and blits it into place, which
+    // converts it to the page's format and color space and to straight alpha. Images in formats
+    // that the device can't sample are converted on the CPU instead.
+    fn upload_image(&mut self, image: &Image, location: TextureLocation)
+                    -> Result<(), DeviceError> {
+        let format = match image.format() {
+            ImageFormat::RGBA8 if image.alpha_mode() == AlphaMode::Straight => {
+                let texels = color::u8_slice_to_color_slice(image.data());
+                self.upload_texel_data(texels, location);
+                return Ok(());
+            }
+            ImageFormat::RGBA8 => TextureFormat::RGBA8,
+            ImageFormat::BGRA8 => TextureFormat::BGRA8,
+            ImageFormat::R8 => TextureFormat::R8,
+            ImageFormat::RGBA16F => TextureFormat::RGBA16F,
+        };
+        if !self.core.device.supports_texture_format(format) {
+            self.upload_texel_data(&image.to_rgba8(), location);
+            return Ok(());
+        }
+
+        // Freeing the staging texture returns it to the allocator's pool, so a video that uploads
+        // a frame of the same size every build reuses it.
+        let size = image.size();
+        let staging_texture_id =
+            self.core.allocator.try_allocate_texture(&self.core.device,
+                                                     size,
+                                                     format,
+                                                     TextureTag("ImageStaging"))?;
+        let staging_texture = self.core.allocator.get_texture(staging_texture_id);
+        let staging_rect = RectI::new(Vector2I::default(), size);
+        match format {
+            TextureFormat::RGBA16F => {
+                let data: Vec<f16> = image.data().chunks_exact(2).map(|bytes| {
+                    f16::from_bits(u16::from_le_bytes([bytes[0], bytes[1]]))
+                }).collect();
+                self.core.device.upload_to_texture(staging_texture,
+                                                   staging_rect,
+                                                   TextureDataRef::F16(&data));
+            }
+            _ => {
+                self.core.device.upload_to_texture(staging_texture,
+                                                   staging_rect,
+                                                   TextureDataRef::U8(image.data()));
+            }
+        }
+        self.core.device.set_texture_sampling_mode(staging_texture,
+                                                   TextureSamplingFlags::NEAREST_MIN |
+                                                   TextureSamplingFlags::NEAREST_MAG);
+
+        let src_color_space = match image.format() {
+            ImageFormat::RGBA16F => ColorSpace::LinearSRGB,
+            ImageFormat::RGBA8 | ImageFormat::BGRA8 | ImageFormat::R8 => ColorSpace::SRGB,
+        };
+        let alpha_mode = match (image.format(), image.alpha_mode()) {
+            (ImageFormat::R8, _) => BLIT_ALPHA_MODE_MASK,
+            (_, AlphaMode::Premultiplied) => BLIT_ALPHA_MODE_UNPREMULTIPLY,
+            (_, AlphaMode::Straight) => BLIT_ALPHA_MODE_STRAIGHT,
+        };
+
+        let dest_framebuffer_id = self.core
+                                      .pattern_texture_pages[location.page.0 as usize]
+                                      .as_ref()
+                                      .expect("Texture page not allocated yet!")
+                                      .framebuffer_id;
+        let dest_framebuffer = self.core.allocator.get_framebuffer(dest_framebuffer_id);
+
+        let timer_query = self.core.timer_query_cache.start_timing_draw_call(&self.core.device,
+                                                                             &self.core.options);
+
+        let blit_program = &self.core.programs.blit_program;
+        let dest_color_space = self.core.compositing_color_space();
+        self.core.device.draw_elements(6, &RenderState {
+            target: &RenderTarget::Framebuffer(dest_framebuffer),
+            program: &blit_program.program,
+            vertex_array: &self.core.vertex_arrays.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[(&blit_program.src_texture, staging_texture)],
+            images: &[],
+            storage_buffers: &[],
+            uniforms: &[
+                (&blit_program.framebuffer_size_uniform, UniformData::Vec2(size.to_f32().0)),
+                (&blit_program.dest_rect_uniform,
+                 UniformData::Vec4(RectF::new(Vector2F::zero(), size.to_f32()).0)),
+                (&blit_program.src_color_space_uniform, UniformData::Int(src_color_space as i32)),
+                (&blit_program.dest_color_space_uniform,
+                 UniformData::Int(dest_color_space as i32)),
+                (&blit_program.alpha_mode_uniform, UniformData::Int(alpha_mode)),
+            ],
+            viewport: location.rect,
+            options: RenderOptions::default(),
+        });
+
+        self.core.stats.drawcall_count += 1;
+        self.core.finish_timing_draw_call(&timer_query);
+        self.core.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
+
+        self.core.allocator.free_texture(staging_texture_id);
+        Ok(())
+    }
+
+    // Copies texels between pattern texture pages by drawing the source page, offset so that the
+    // source rect lines up with the destination rect, with the destination rect as the viewport.
+    fn copy_texel_data(&mut self, src: TextureLocation, dest: TextureLocation) {
+        debug_assert_ne!(src.page, dest.page);
+        debug_assert_eq!(src.rect.size(), dest.rect.size());
+
+        let src_framebuffer_id = self.core
+                                     .pattern_texture_pages[src.page.0 as usize]
+                                     .as_ref()
+                                     .expect("Texture page not allocated yet!")
+                                     .framebuffer_id;
+        let dest_framebuffer_id = self.core
+                                      .pattern_texture_pages[dest.page.0 as usize]
+                                      .as_ref()
+                                      .expect("Texture page not allocated yet!")
+                                      .framebuffer_id;
+
+        let src_framebuffer = self.core.allocator.get_framebuffer(src_framebuffer_id);
+        let src_texture = self.core.device.framebuffer_texture(src_framebuffer);
+        let src_page_size = self.core.device.texture_size(src_texture);
+        let dest_framebuffer = self.core.allocator.get_framebuffer(dest_framebuffer_id);
+
+        let timer_query = self.core.timer_query_cache.start_timing_draw_call(&self.core.device,
+                                                                             &self.core.options);
+
+        // Both pages share a format and color space, so the blit doesn't convert anything.
+        let blit_program = &self.core.programs.blit_program;
+        let src_rect = RectF::new(-src.rect.origin().to_f32(), src_page_size.to_f32());
+        let color_space = self.core.compositing_color_space();
+        self.core.device.draw_elements(6, &RenderState {
+            target: &RenderTarget::Framebuffer(dest_framebuffer),
+            program: &blit_program.program,
+            vertex_array: &self.core.vertex_arrays.blit_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[(&blit_program.src_texture, src_texture)],
+            images: &[],
+            storage_buffers: &[],
+            uniforms: &[
+                (&blit_program.framebuffer_size_uniform,
+                 UniformData::Vec2(dest.rect.size().to_f32().0)),
+                (&blit_program.dest_rect_uniform, UniformData::Vec4(src_rect.0)),
+                (&blit_program.src_color_space_uniform, UniformData::Int(color_space as i32)),
+                (&blit_program.dest_color_space_uniform, UniformData::Int(color_space as i32)),
+                (&blit_program.alpha_mode_uniform, UniformData::Int(BLIT_ALPHA_MODE_STRAIGHT)),
+            ],
+            viewport: dest.rect,
+            options: RenderOptions::default(),
+        });
+
+        self.core.stats.drawcall_count += 1;
+        self.core.finish_timing_draw_call(&timer_query);
+        self.core.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
     }
 
     fn declare_render_target(&mut self,
                              render_target_id: RenderTargetId,
-                             location: TextureLocation) {
+                             location: TextureLocation,
+                             padding: i32) {
         while self.core.render_targets.len() < render_target_id.render_target as usize + 1 {
             self.core.render_targets.push(RenderTargetInfo {
                 location: TextureLocation { page: TexturePageId(!0), rect: RectI::default() },
+                padding: 0,
+                needs_clear: false,
             });
         }
         let mut render_target =
             &mut self.core.render_targets[render_target_id.render_target as usize];
         debug_assert_eq!(render_target.location.page, TexturePageId(!0));
         render_target.location = location;
+        render_target.padding = padding;
+        render_target.needs_clear = true;
     }
 
     fn upload_texture_metadata(&mut self, metadata: &[TextureMetadataEntry]) {
@@ -577,10 +866,17 @@ impl<D> Renderer<D> where D: Device {
              TEXTURE_METADATA_TEXTURE_WIDTH * 4) as usize;
         let mut texels = Vec::with_capacity(padded_texel_size);
         for entry in metadata {
-            let base_color = entry.base_color.to_f32();
-            let filter_params = self.compute_filter_params(&entry.filter,
-                                                           entry.blend_mode,
-                                                           entry.color_0_combine_mode);
+            let base_color =
+                entry.base_color.to_f32().to_color_space(self.core.compositing_color_space());
+            let mut filter_params = self.compute_filter_params(&entry.filter,
+                                                               entry.blend_mode,
+                                                               entry.color_0_combine_mode);
+            filter_params.ctrl |= paint_mask_ctrl(entry) << COMBINER_CTRL_PAINT_MASK_SHIFT;
+
+            let (atlas_origin, atlas_size) = atlas_region_to_rect(entry.color_0_atlas_region);
+            let (paint_mask_atlas_origin, paint_mask_atlas_size) =
+                atlas_region_to_rect(entry.paint_mask_atlas_region);
+
             texels.extend_from_slice(&[
                 // 0
                 f16::from_f32(entry.color_0_transform.m11()),
@@ -590,8 +886,8 @@ impl<D> Renderer<D> where D: Device {
                 // 1
                 f16::from_f32(entry.color_0_transform.m13()),
                 f16::from_f32(entry.color_0_transform.m23()),
-                f16::default(),
-                f16::default(),
+                f16::from_f32(entry.paint_mask_transform.m13()),
+                f16::from_f32(entry.paint_mask_transform.m23()),
                 // 2
                 f16::from_f32(base_color.r()),
                 f16::from_f32(base_color.g()),
@@ -618,10 +914,20 @@ impl<D> Renderer<D> where D: Device {
                 f16::default(),
                 f16::default(),
                 // 7
-                f16::default(),
-                f16::default(),
-                f16::default(),
-                f16::default(),
+                f16::from_f32(atlas_origin.x()),
+                f16::from_f32(atlas_origin.y()),
+                f16::from_f32(atlas_size.x()),
+                f16::from_f32(atlas_size.y()),
+                // 8
+                f16::from_f32(entry.paint_mask_transform.m11()),
+                f16::from_f32(entry.paint_mask_transform.m21()),
+                f16::from_f32(entry.paint_mask_transform.m12()),
+                f16::from_f32(entry.paint_mask_transform.m22()),
+                // 9
+                f16::from_f32(paint_mask_atlas_origin.x()),
+                f16::from_f32(paint_mask_atlas_origin.y()),
+                f16::from_f32(paint_mask_atlas_size.x()),
+                f16::from_f32(paint_mask_atlas_size.y()),
             ]);
         }
         while texels.len() < padded_texel_size {
@@ -651,32 +957,35 @@ impl<D> Renderer<D> where D: Device {
                                     BufferData::Memory(&indices),
                                     BufferTarget::Index);
 
-        self.core.device.draw_elements(indices.len() as u32, &RenderState {
-            target: &self.core.draw_render_target(),
-            program: &self.stencil_program.program,
-            vertex_array: &self.frame.stencil_vertex_array.vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &[],
-            images: &[],
-            storage_buffers: &[],
-            uniforms: &[],
-            viewport: self.core.draw_viewport(),
-            options: RenderOptions {
-                // FIXME(pcwalton): Should we really write to the depth buffer?
-                depth: Some(DepthState { func: DepthFunc::Less, write: true }),
-                stencil: Some(StencilState {
-                    func: StencilFunc::Always,
-                    reference: 1,
-                    mask: 1,
-                    write: true,
-                }),
-                color_mask: false,
-                clear_ops: ClearOps { stencil: Some(0), ..ClearOps::default() },
-                ..RenderOptions::default()
-            },
-        });
+        for scissor_rect in self.core.draw_scissor_rects() {
+            self.core.device.draw_elements(indices.len() as u32, &RenderState {
+                target: &self.core.draw_render_target(),
+                program: &self.stencil_program.program,
+                vertex_array: &self.frame.stencil_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &[],
+                viewport: self.core.draw_viewport(),
+                options: RenderOptions {
+                    // FIXME(pcwalton): Should we really write to the depth buffer?
+                    depth: Some(DepthState { func: DepthFunc::Less, write: true }),
+                    stencil: Some(StencilState {
+                        func: StencilFunc::Always,
+                        reference: 1,
+                        mask: 1,
+                        write: true,
+                    }),
+                    color_mask: false,
+                    clear_ops: ClearOps { stencil: Some(0), ..ClearOps::default() },
+                    scissor: scissor_rect,
+                    ..RenderOptions::default()
+                },
+            });
 
-        self.core.stats.drawcall_count += 1;
+            self.core.stats.drawcall_count += 1;
+        }
     }
 
     pub fn reproject_texture(&mut self,
@@ -685,36 +994,40 @@ impl<D> Renderer<D> where D: Device {
                              new_transform: &Transform4F) {
         let clear_color = self.core.clear_color_for_draw_operation();
 
-        self.core.device.draw_elements(6, &RenderState {
-            target: &self.core.draw_render_target(),
-            program: &self.reprojection_program.program,
-            vertex_array: &self.frame.reprojection_vertex_array.vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &[(&self.reprojection_program.texture, texture)],
-            images: &[],
-            storage_buffers: &[],
-            uniforms: &[
-                (&self.reprojection_program.old_transform_uniform,
-                 UniformData::from_transform_3d(old_transform)),
-                (&self.reprojection_program.new_transform_uniform,
-                 UniformData::from_transform_3d(new_transform)),
-            ],
-            viewport: self.core.draw_viewport(),
-            options: RenderOptions {
-                blend: BlendMode::SrcOver.to_blend_state(),
-                depth: Some(DepthState { func: DepthFunc::Less, write: false, }),
-                clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
-                ..RenderOptions::default()
-            },
-        });
+        for scissor_rect in self.core.draw_scissor_rects() {
+            self.core.device.draw_elements(6, &RenderState {
+                target: &self.core.draw_render_target(),
+                program: &self.reprojection_program.program,
+                vertex_array: &self.frame.reprojection_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[(&self.reprojection_program.texture, texture)],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &[
+                    (&self.reprojection_program.old_transform_uniform,
+                     UniformData::from_transform_3d(old_transform)),
+                    (&self.reprojection_program.new_transform_uniform,
+                     UniformData::from_transform_3d(new_transform)),
+                ],
+                viewport: self.core.draw_viewport(),
+                options: RenderOptions {
+                    blend: BlendMode::SrcOver.to_blend_state(),
+                    depth: Some(DepthState { func: DepthFunc::Less, write: false, }),
+                    clear_ops: ClearOps { color: clear_color, ..ClearOps::default() },
+                    scissor: scissor_rect,
+                    ..RenderOptions::default()
+                },
+            });
 
-        self.core.stats.drawcall_count += 1;
+            self.core.stats.drawcall_count += 1;
+        }
 
         self.core.preserve_draw_framebuffer();
     }
 
     fn push_render_target(&mut self, render_target_id: RenderTargetId) {
         self.core.render_target_stack.push(render_target_id);
+        self.clear_render_target_if_necessary(render_target_id);
     }
 
     fn pop_render_target(&mut self) {
@@ -724,7 +1037,9 @@ impl<D> Renderer<D> where D: Device {
     fn clear_dest_framebuffer_if_necessary(&mut self) {
         let background_color = match self.core.options.background_color {
             None => return,
-            Some(background_color) => background_color,
+            Some(background_color) => {
+                background_color.to_color_space(self.core.options.dest_color_space)
+            }
         };
 
         if self.core.framebuffer_flags.contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY) {
@@ -740,7 +1055,7 @@ impl<D> Renderer<D> where D: Device {
         ];
 
         self.core.device.draw_elements(6, &RenderState {
-            target: &RenderTarget::Default,
+            target: &self.core.dest_render_target(),
             program: &self.clear_program.program,
             vertex_array: &self.frame.clear_vertex_array.vertex_array,
             primitive: Primitive::Triangles,
@@ -755,24 +1070,93 @@ impl<D> Renderer<D> where D: Device {
         self.core.stats.drawcall_count += 1;
     }
 
-    fn blit_intermediate_dest_framebuffer_if_necessary(&mut self) {
+    // Clears a render target, padding included, the first time it's drawn to in a frame. Other
+    // render targets and images may share its page, so the page as a whole is never cleared.
+    fn clear_render_target_if_necessary(&mut self, render_target_id: RenderTargetId) {
+        let render_target = &mut self.core.render_targets[render_target_id.render_target as usize];
+        if !render_target.needs_clear {
+            return;
+        }
+        render_target.needs_clear = false;
+
+        let (rect, padding) = (render_target.location.rect, render_target.padding);
+        let padded_rect = RectI::new(rect.origin() - Vector2I::splat(padding),
+                                     rect.size() + padding * 2);
+        let uniforms = [
+            (&self.clear_program.rect_uniform,
+             UniformData::Vec4(RectF::new(Vector2F::zero(), padded_rect.size().to_f32()).0)),
+            (&self.clear_program.framebuffer_size_uniform,
+             UniformData::Vec2(padded_rect.size().to_f32().0)),
+            (&self.clear_program.color_uniform, UniformData::Vec4(ColorF::default().0)),
+        ];
+
+        self.core.device.draw_elements(6, &RenderState {
+            target: &self.core.draw_render_target(),
+            program: &self.clear_program.program,
+            vertex_array: &self.frame.clear_vertex_array.vertex_array,
+            primitive: Primitive::Triangles,
+            textures: &[],
+            images: &[],
+            storage_buffers: &[],
+            uniforms: &uniforms[..],
+            viewport: padded_rect,
+            options: RenderOptions::default(),
+        });
+
+        self.core.stats.drawcall_count += 1;
+    }
+
+    // Clears the damaged regions of the destination framebuffer up front when only part of it is
+    // to be redrawn, since a clear load action would wipe out the parts we need to preserve.
+    fn clear_damaged_dest_framebuffer_if_necessary(&mut self) {
+        if self.core.mode.level == RendererLevel::D3D11 || self.core.damage_rects.is_empty() {
+            return;
+        }
+        let background_color = match self.core.options.background_color {
+            None => return,
+            Some(background_color) => {
+                background_color.to_color_space(self.core.compositing_color_space())
+            }
+        };
+
+        let draw_viewport = self.core.draw_viewport();
+        let uniforms = [
+            (&self.clear_program.rect_uniform, UniformData::Vec4(draw_viewport.to_f32().0)),
+            (&self.clear_program.framebuffer_size_uniform,
+             UniformData::Vec2(draw_viewport.size().to_f32().0)),
+            (&self.clear_program.color_uniform, UniformData::Vec4(background_color.0)),
+        ];
+
+        // Clear each rect separately. Any space between them isn't redrawn.
+        for &damage_rect in &self.core.damage_rects {
+            self.core.device.draw_elements(6, &RenderState {
+                target: &self.core.draw_render_target(),
+                program: &self.clear_program.program,
+                vertex_array: &self.frame.clear_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &[],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &uniforms[..],
+                viewport: draw_viewport,
+                options: RenderOptions { scissor: Some(damage_rect), ..RenderOptions::default() },
+            });
+
+            self.core.stats.drawcall_count += 1;
+        }
+
+        self.core.preserve_draw_framebuffer();
+    }
+
+    fn blit_intermediate_dest_framebuffer_if_necessary(&mut self) -> Result<(), DeviceError> {
         if !self.core
                 .renderer_flags
                 .contains(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED) {
-            return;
+            return Ok(());
         }
 
         let main_viewport = self.core.main_viewport();
-
-        if self.core.intermediate_dest_framebuffer_size != main_viewport.size() {
-            self.core.allocator.free_framebuffer(self.core.intermediate_dest_framebuffer_id);
-            self.core.intermediate_dest_framebuffer_id =
-                self.core.allocator.allocate_framebuffer(&self.core.device,
-                                                         main_viewport.size(),
-                                                         TextureFormat::RGBA8,
-                                                         FramebufferTag("IntermediateDest"));
-            self.core.intermediate_dest_framebuffer_size = main_viewport.size();
-        }
+        self.core.reallocate_intermediate_dest_framebuffer_if_necessary()?;
 
         let intermediate_dest_framebuffer =
             self.core.allocator.get_framebuffer(self.core.intermediate_dest_framebuffer_id);
@@ -782,31 +1166,47 @@ impl<D> Renderer<D> where D: Device {
              self.core.device.framebuffer_texture(intermediate_dest_framebuffer))
         ];
 
-        self.core.device.draw_elements(6, &RenderState {
-            target: &RenderTarget::Default,
-            program: &self.blit_program.program,
-            vertex_array: &self.frame.blit_vertex_array.vertex_array,
-            primitive: Primitive::Triangles,
-            textures: &textures[..],
-            images: &[],
-            storage_buffers: &[],
-            uniforms: &[
-                (&self.blit_program.framebuffer_size_uniform,
-                 UniformData::Vec2(main_viewport.size().to_f32().0)),
-                (&self.blit_program.dest_rect_uniform,
-                 UniformData::Vec4(RectF::new(Vector2F::zero(), main_viewport.size().to_f32()).0)),
-            ],
-            viewport: main_viewport,
-            options: RenderOptions {
-                clear_ops: ClearOps {
-                    color: Some(ColorF::new(0.0, 0.0, 0.0, 1.0)),
-                    ..ClearOps::default()
+        // Only copy the damaged regions, if any, and leave the rest of the destination alone.
+        let (blit_clear_color, scissor_rects) = if self.core.damage_rects.is_empty() {
+            (Some(ColorF::new(0.0, 0.0, 0.0, 1.0)), vec![None])
+        } else {
+            (None, self.core.damage_rects.iter().cloned().map(Some).collect::<Vec<_>>())
+        };
+
+        let uniforms = [
+            (&self.blit_program.framebuffer_size_uniform,
+             UniformData::Vec2(main_viewport.size().to_f32().0)),
+            (&self.blit_program.dest_rect_uniform,
+             UniformData::Vec4(RectF::new(Vector2F::zero(), main_viewport.size().to_f32()).0)),
+            (&self.blit_program.src_color_space_uniform,
+             UniformData::Int(self.core.compositing_color_space() as i32)),
+            (&self.blit_program.dest_color_space_uniform,
+             UniformData::Int(self.core.options.dest_color_space as i32)),
+            (&self.blit_program.alpha_mode_uniform,
+             UniformData::Int(BLIT_ALPHA_MODE_PREMULTIPLIED)),
+        ];
+
+        for scissor_rect in scissor_rects {
+            self.core.device.draw_elements(6, &RenderState {
+                target: &self.core.dest_render_target(),
+                program: &self.blit_program.program,
+                vertex_array: &self.frame.blit_vertex_array.vertex_array,
+                primitive: Primitive::Triangles,
+                textures: &textures[..],
+                images: &[],
+                storage_buffers: &[],
+                uniforms: &uniforms[..],
+                viewport: main_viewport,
+                options: RenderOptions {
+                    clear_ops: ClearOps { color: blit_clear_color, ..ClearOps::default() },
+                    scissor: scissor_rect,
+                    ..RenderOptions::default()
                 },
-                ..RenderOptions::default()
-            },
-        });
+            });
 
-        self.core.stats.drawcall_count += 1;
+            self.core.stats.drawcall_count += 1;
+        }
+        Ok(())
     }
 
     #[inline]
@@ -819,6 +1219,61 @@ impl<D> Renderer<D> where D: Device {
         self.core.draw_render_target()
     }
 
+    /// Reads back a region of the destination framebuffer, converting each pixel from
+    /// `RendererOptions::dest_color_space` to `color_space`.
+    ///
+    /// The destination holds premultiplied colors; the returned colors have straight alpha. Rows
+    /// are in the order that `Device::read_pixels()` returns them.
+    pub fn read_dest_pixels(&self, viewport: RectI, color_space: ColorSpace) -> Vec<ColorF> {
+        let device = &self.core.device;
+        device.begin_commands();
+        let receiver = device.read_pixels(&self.core.dest_render_target(), viewport);
+        device.end_commands();
+
+        let dest_color_space = self.core.options.dest_color_space;
+        let to_color = |premultiplied: F32x4| {
+            let alpha = premultiplied[3];
+            let rgba = if alpha > 0.0 {
+                premultiplied * F32x4::new(1.0 / alpha, 1.0 / alpha, 1.0 / alpha, 1.0)
+            } else {
+                premultiplied
+            };
+            ColorF(rgba, dest_color_space).to_color_space(color_space)
+        };
+
+        match device.recv_texture_data(&receiver) {
+            TextureData::U8(values) => {
+                values.chunks(4).map(|texel| {
+                    let rgba = F32x4::new(texel[0] as f32,
+                                          texel[1] as f32,
+                                          texel[2] as f32,
+                                          texel[3] as f32);
+                    to_color(rgba * F32x4::splat(1.0 / 255.0))
+                }).collect()
+            }
+            TextureData::U16(values) => {
+                values.chunks(4).map(|texel| {
+                    let rgba = F32x4::new(texel[0] as f32,
+                                          texel[1] as f32,
+                                          texel[2] as f32,
+                                          texel[3] as f32);
+                    to_color(rgba * F32x4::splat(1.0 / 65535.0))
+                }).collect()
+            }
+            TextureData::F16(values) => {
+                values.chunks(4).map(|texel| {
+                    to_color(F32x4::new(texel[0].to_f32(),
+                                        texel[1].to_f32(),
+                                        texel[2].to_f32(),
+                                        texel[3].to_f32()))
+                }).collect()
+            }
+            TextureData::F32(values) => {
+                values.chunks(4).map(|texel| to_color(F32x4::from_slice(texel))).collect()
+            }
+        }
+    }
+
     #[inline]
     pub fn render_stats(&self) -> &RenderStats {
         &self.core.stats
@@ -863,13 +1318,17 @@ impl<D> Renderer<D> where D: Device {
                     ctrl: ctrl | (COMBINER_CTRL_FILTER_BLUR << COMBINER_CTRL_COLOR_FILTER_SHIFT),
                 }
             }
-            Filter::PatternFilter(PatternFilter::Text { 
+            Filter::PatternFilter(PatternFilter::Text {
                 fg_color,
                 bg_color,
                 defringing_kernel,
                 gamma_correction,
             }) => {
-                let mut p2 = fg_color.0;
+                // The shader numbers subpixel layouts in declaration order.
+                let color_space = self.core.compositing_color_space();
+                let mut p1 = bg_color.to_color_space(color_space).0;
+                p1.set_w(self.core.subpixel_layout as i32 as f32);
+                let mut p2 = fg_color.to_color_space(color_space).0;
                 p2.set_w(gamma_correction as i32 as f32);
 
                 FilterParams {
@@ -877,7 +1336,7 @@ impl<D> Renderer<D> where D: Device {
                         Some(ref kernel) => F32x4::from_slice(&kernel.0),
                         None => F32x4::default(),
                     },
-                    p1: bg_color.0,
+                    p1,
                     p2,
                     ctrl: ctrl | (COMBINER_CTRL_FILTER_TEXT << COMBINER_CTRL_COLOR_FILTER_SHIFT),
                 }
@@ -895,6 +1354,51 @@ impl<D> Renderer<D> where D: Device {
 }
 
 impl<D> RendererCore<D> where D: Device {
+    /// The color space that blending happens in.
+    pub(crate) fn compositing_color_space(&self) -> ColorSpace {
+        if self.options.linear_compositing && self.mode.level == RendererLevel::D3D9 {
+            ColorSpace::LinearSRGB
+        } else {
+            ColorSpace::SRGB
+        }
+    }
+
+    // The format of render targets and the textures that paints sample from.
+    fn color_texture_format(&self) -> TextureFormat {
+        match self.compositing_color_space() {
+            ColorSpace::LinearSRGB => TextureFormat::RGBA16F,
+            _ => TextureFormat::RGBA8,
+        }
+    }
+
+    fn reallocate_intermediate_dest_framebuffer_if_necessary(&mut self)
+                                                             -> Result<(), DeviceError> {
+        if !self.renderer_flags.contains(RendererFlags::INTERMEDIATE_DEST_FRAMEBUFFER_NEEDED) {
+            return Ok(());
+        }
+
+        let size = self.main_viewport().size();
+        let format = self.color_texture_format();
+        let old_framebuffer = self.allocator.get_framebuffer(self.intermediate_dest_framebuffer_id);
+        let old_format =
+            self.device.texture_format(self.device.framebuffer_texture(old_framebuffer));
+        if self.intermediate_dest_framebuffer_size == size && old_format == format {
+            return Ok(());
+        }
+
+        // Allocate the new framebuffer before freeing the old one so that a failed allocation
+        // doesn't leave a dangling ID behind.
+        let framebuffer_id =
+            self.allocator.try_allocate_framebuffer(&self.device,
+                                                    size,
+                                                    format,
+                                                    FramebufferTag("IntermediateDest"))?;
+        self.allocator.free_framebuffer(self.intermediate_dest_framebuffer_id);
+        self.intermediate_dest_framebuffer_id = framebuffer_id;
+        self.intermediate_dest_framebuffer_size = size;
+        Ok(())
+    }
+
     pub(crate) fn mask_texture_format(&self) -> TextureFormat {
         match self.mode.level {
             RendererLevel::D3D9 => TextureFormat::RGBA16F,
@@ -902,11 +1406,12 @@ impl<D> RendererCore<D> where D: Device {
         }
     }
 
-    pub(crate) fn reallocate_alpha_tile_pages_if_necessary(&mut self, copy_existing: bool) {
+    pub(crate) fn reallocate_alpha_tile_pages_if_necessary(&mut self, copy_existing: bool)
+                                                           -> Result<(), DeviceError> {
         let alpha_tile_pages_needed = ((self.alpha_tile_count + 0xffff) >> 16) as u32;
         if let Some(ref mask_storage) = self.mask_storage {
             if alpha_tile_pages_needed <= mask_storage.allocated_page_count {
-                return;
+                return Ok(());
             }
         }
 
@@ -914,10 +1419,10 @@ impl<D> RendererCore<D> where D: Device {
                              MASK_FRAMEBUFFER_HEIGHT * alpha_tile_pages_needed as i32);
         let format = self.mask_texture_format();
         let mask_framebuffer_id =
-            self.allocator.allocate_framebuffer(&self.device,
-                                                new_size,
-                                                format,
-                                                FramebufferTag("TileAlphaMask"));
+            self.allocator.try_allocate_framebuffer(&self.device,
+                                                    new_size,
+                                                    format,
+                                                    FramebufferTag("TileAlphaMask"))?;
         let mask_framebuffer = self.allocator.get_framebuffer(mask_framebuffer_id);
         let old_mask_storage = self.mask_storage.take();
         self.mask_storage = Some(MaskStorage {
@@ -928,7 +1433,7 @@ impl<D> RendererCore<D> where D: Device {
         // Copy over existing content if needed.
         let old_mask_framebuffer_id = match old_mask_storage {
             Some(old_storage) if copy_existing => old_storage.framebuffer_id,
-            Some(_) | None => return,
+            Some(_) | None => return Ok(()),
         };
         let old_mask_framebuffer = self.allocator.get_framebuffer(old_mask_framebuffer_id);
         let old_mask_texture = self.device.framebuffer_texture(old_mask_framebuffer);
@@ -950,6 +1455,12 @@ impl<D> RendererCore<D> where D: Device {
                  UniformData::Vec2(new_size.to_f32().0)),
                 (&self.programs.blit_program.dest_rect_uniform,
                  UniformData::Vec4(RectF::new(Vector2F::zero(), old_size.to_f32()).0)),
+                (&self.programs.blit_program.src_color_space_uniform,
+                 UniformData::Int(ColorSpace::SRGB as i32)),
+                (&self.programs.blit_program.dest_color_space_uniform,
+                 UniformData::Int(ColorSpace::SRGB as i32)),
+                (&self.programs.blit_program.alpha_mode_uniform,
+                 UniformData::Int(BLIT_ALPHA_MODE_PREMULTIPLIED)),
             ],
             viewport: RectI::new(Vector2I::default(), new_size),
             options: RenderOptions {
@@ -964,6 +1475,7 @@ impl<D> RendererCore<D> where D: Device {
         self.stats.drawcall_count += 1;
         self.finish_timing_draw_call(&timer_query);
         self.current_timer.as_mut().unwrap().push_query(TimeCategory::Other, timer_query);
+        Ok(())
     }
 
     pub(crate) fn set_uniforms_for_drawing_tiles<'a>(
@@ -971,7 +1483,8 @@ impl<D> RendererCore<D> where D: Device {
             tile_program: &'a TileProgramCommon<D>,
             textures: &mut Vec<TextureBinding<'a, D::TextureParameter, D::Texture>>,
             uniforms: &mut Vec<UniformBinding<'a, D::Uniform>>,
-            color_texture_0: Option<TileBatchTexture>) {
+            color_texture_0: Option<TileBatchTexture>,
+            paint_mask_texture: Option<TileBatchTexture>) {
         let draw_viewport = self.draw_viewport();
 
         let gamma_lut_texture = self.allocator.get_texture(self.gamma_lut_texture_id);
@@ -999,6 +1512,24 @@ impl<D> RendererCore<D> where D: Device {
             textures.push((&tile_program.mask_texture_0, mask_texture));
         }
 
+        // A mask on the same page as the color texture gets the color texture's sampling mode.
+        match paint_mask_texture {
+            Some(paint_mask_texture) => {
+                let paint_mask_texture_page = self.texture_page(paint_mask_texture.page);
+                let paint_mask_texture_size =
+                    self.device.texture_size(paint_mask_texture_page).to_f32();
+                self.device.set_texture_sampling_mode(paint_mask_texture_page,
+                                                      paint_mask_texture.sampling_flags);
+                textures.push((&tile_program.paint_mask_texture, paint_mask_texture_page));
+                uniforms.push((&tile_program.paint_mask_texture_size_uniform,
+                               UniformData::Vec2(paint_mask_texture_size.0)));
+            }
+            None => {
+                uniforms.push((&tile_program.paint_mask_texture_size_uniform,
+                               UniformData::Vec2(F32x2::default())));
+            }
+        }
+
         match color_texture_0 {
             Some(color_texture) => {
                 let color_texture_page = self.texture_page(color_texture.page);
@@ -1031,26 +1562,14 @@ impl<D> RendererCore<D> where D: Device {
     }
 
     pub(crate) fn clear_color_for_draw_operation(&self) -> Option<ColorF> {
-        let must_preserve_contents = match self.render_target_stack.last() {
-            Some(&render_target_id) => {
-                let texture_page = self.render_target_location(render_target_id).page;
-                self.pattern_texture_pages[texture_page.0 as usize]
-                    .as_ref()
-                    .expect("Draw target texture page not allocated!")
-                    .must_preserve_contents
-            }
-            None => {
-                self.framebuffer_flags.contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY)
-            }
-        };
-
-        if must_preserve_contents {
-            None
-        } else if self.render_target_stack.is_empty() {
-            self.options.background_color
-        } else {
-            Some(ColorF::default())
+        // Render targets are cleared when they're pushed.
+        if !self.render_target_stack.is_empty() ||
+                self.framebuffer_flags.contains(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY) {
+            return None;
         }
+        self.options
+            .background_color
+            .map(|color| color.to_color_space(self.compositing_color_space()))
     }
 
     // Sizing
@@ -1085,6 +1604,13 @@ impl<D> RendererCore<D> where D: Device {
         }
     }
 
+    fn dest_render_target(&self) -> RenderTarget<D> {
+        match self.options.dest {
+            DestFramebuffer::Default { .. } => RenderTarget::Default,
+            DestFramebuffer::Other(ref framebuffer) => RenderTarget::Framebuffer(framebuffer),
+        }
+    }
+
     pub fn draw_render_target(&self) -> RenderTarget<D> {
         match self.render_target_stack.last() {
             Some(&render_target_id) => {
@@ -1099,29 +1625,35 @@ impl<D> RendererCore<D> where D: Device {
                         self.allocator.get_framebuffer(self.intermediate_dest_framebuffer_id);
                     RenderTarget::Framebuffer(intermediate_dest_framebuffer)
                 } else {
-                    match self.options.dest {
-                        DestFramebuffer::Default { .. } => RenderTarget::Default,
-                        DestFramebuffer::Other(ref framebuffer) => {
-                            RenderTarget::Framebuffer(framebuffer)
-                        }
-                    }
+                    self.dest_render_target()
                 }
             }
         }
     }
 
-    pub(crate) fn preserve_draw_framebuffer(&mut self) {
+    /// Returns the scissor rects that draws to the current render target must use. Each draw is
+    /// repeated once per rect, and `None` means that it isn't scissored.
+    ///
+    /// This confines draws to render targets to their part of the page. When drawing directly to
+    /// the destination framebuffer and only parts of it are being redrawn, there's one rect per
+    /// damaged region. These don't overlap, so nothing is blended twice.
+    pub(crate) fn draw_scissor_rects(&self) -> Vec<Option<RectI>> {
+        if self.mode.level == RendererLevel::D3D11 {
+            return vec![None];
+        }
         match self.render_target_stack.last() {
             Some(&render_target_id) => {
-                let texture_page = self.render_target_location(render_target_id).page;
-                self.pattern_texture_pages[texture_page.0 as usize]
-                    .as_mut()
-                    .expect("Draw target texture page not allocated!")
-                    .must_preserve_contents = true;
-            }
-            None => {
-                self.framebuffer_flags.insert(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY);
+                let size = self.render_target_location(render_target_id).rect.size();
+                vec![Some(RectI::new(Vector2I::default(), size))]
             }
+            None if self.damage_rects.is_empty() => vec![None],
+            None => self.damage_rects.iter().cloned().map(Some).collect(),
+        }
+    }
+
+    pub(crate) fn preserve_draw_framebuffer(&mut self) {
+        if self.render_target_stack.is_empty() {
+            self.framebuffer_flags.insert(FramebufferFlags::DEST_FRAMEBUFFER_IS_DIRTY);
         }
     }
 
@@ -1146,30 +1678,30 @@ impl<D> Frame<D> where D: Device {
            stencil_program: &StencilProgram<D>,
            quad_vertex_positions_buffer_id: BufferID,
            quad_vertex_indices_buffer_id: BufferID)
-           -> Frame<D> {
+           -> Result<Frame<D>, DeviceError> {
         let quad_vertex_positions_buffer = allocator.get_buffer(quad_vertex_positions_buffer_id);
         let quad_vertex_indices_buffer = allocator.get_buffer(quad_vertex_indices_buffer_id);
 
         let blit_vertex_array = BlitVertexArray::new(device,
                                                      &blit_program,
                                                      &quad_vertex_positions_buffer,
-                                                     &quad_vertex_indices_buffer);
+                                                     &quad_vertex_indices_buffer)?;
         let clear_vertex_array = ClearVertexArray::new(device,
                                                        &clear_program,
                                                        &quad_vertex_positions_buffer,
-                                                       &quad_vertex_indices_buffer);
+                                                       &quad_vertex_indices_buffer)?;
         let reprojection_vertex_array = ReprojectionVertexArray::new(device,
                                                                      &reprojection_program,
                                                                      &quad_vertex_positions_buffer,
-                                                                     &quad_vertex_indices_buffer);
-        let stencil_vertex_array = StencilVertexArray::new(device, &stencil_program);
+                                                                     &quad_vertex_indices_buffer)?;
+        let stencil_vertex_array = StencilVertexArray::new(device, &stencil_program)?;
 
-        Frame {
+        Ok(Frame {
             blit_vertex_array,
             clear_vertex_array,
             reprojection_vertex_array,
             stencil_vertex_array,
-        }
+        })
     }
 }
 
@@ -1206,6 +1738,9 @@ bitflags! {
 
 struct RenderTargetInfo {
     location: TextureLocation,
+    padding: i32,
+    // Whether the render target hasn't been cleared yet this frame.
+    needs_clear: bool,
 }
 
 bitflags! {
@@ -1227,6 +1762,63 @@ fn pixel_size_to_tile_size(pixel_size: Vector2I) -> Vector2I {
     vec2i(size.x() / TILE_WIDTH as i32, size.y() / TILE_HEIGHT as i32)
 }
 
+// Splits overlapping rects into pieces so that no pixel is covered twice. Scissored draws are
+// repeated per rect, and blending the same tile twice would darken it.
+fn split_into_disjoint_rects(rects: &[RectI]) -> Vec<RectI> {
+    let mut disjoint_rects: Vec<RectI> = vec![];
+    for &rect in rects {
+        let mut pieces = vec![rect];
+        for &existing_rect in &disjoint_rects {
+            pieces = pieces.into_iter().flat_map(|piece| {
+                subtract_rect(piece, existing_rect)
+            }).collect();
+        }
+        disjoint_rects.extend(pieces.into_iter().filter(|piece| piece.area() > 0));
+    }
+    disjoint_rects
+}
+
+// Returns up to four rects that together cover the parts of `rect` outside `hole`.
+fn subtract_rect(rect: RectI, hole: RectI) -> Vec<RectI> {
+    let overlap = match rect.intersection(hole) {
+        None => return vec![rect],
+        Some(overlap) => overlap,
+    };
+    vec![
+        RectI::from_points(rect.origin(), vec2i(rect.max_x(), overlap.min_y())),
+        RectI::from_points(vec2i(rect.min_x(), overlap.max_y()), rect.lower_right()),
+        RectI::from_points(vec2i(rect.min_x(), overlap.min_y()), overlap.lower_left()),
+        RectI::from_points(overlap.upper_right(), vec2i(rect.max_x(), overlap.max_y())),
+    ].into_iter().filter(|piece| piece.width() > 0 && piece.height() > 0).collect()
+}
+
+// Returns the paint mask bits of the ctrl word, before shifting.
+fn paint_mask_ctrl(entry: &TextureMetadataEntry) -> i32 {
+    let mode = match entry.paint_mask_mode {
+        None => return 0,
+        Some(MaskMode::Alpha) => COMBINER_CTRL_PAINT_MASK_ALPHA,
+        Some(MaskMode::Luminance) => COMBINER_CTRL_PAINT_MASK_LUMINANCE,
+    };
+    if entry.paint_mask_premultiplied {
+        mode | COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED
+    } else {
+        mode
+    }
+}
+
+// Returns the origin and size of an atlas region as the tile shaders expect them. Negative sizes
+// tell the shader to clamp to the region instead of repeating.
+fn atlas_region_to_rect(atlas_region: Option<TextureAtlasRegion>) -> (Vector2F, Vector2F) {
+    match atlas_region {
+        None => (Vector2F::zero(), Vector2F::zero()),
+        Some(region) => {
+            let sign = vec2f(if region.repeat_x { 1.0 } else { -1.0 },
+                             if region.repeat_y { 1.0 } else { -1.0 });
+            (region.rect.origin().to_f32(), region.rect.size().to_f32() * sign)
+        }
+    }
+}
+
 struct FilterParams {
     p0: F32x4,
     p1: F32x4,
@@ -1236,7 +1828,6 @@ struct FilterParams {
 
 pub(crate) struct PatternTexturePage {
     pub(crate) framebuffer_id: FramebufferID,
-    pub(crate) must_preserve_contents: bool,
 }
 
 pub struct DebugUIPresenterInfo<'a, D> where D: Device {
@@ -1244,3 +1835,479 @@ pub struct DebugUIPresenterInfo<'a, D> where D: Device {
     pub allocator: &'a mut GPUMemoryAllocator<D>,
     pub debug_ui_presenter: &'a mut DebugUIPresenter<D>,
 }
+
+// Decodes sRGB texels to linear half floats, for pattern pages that hold linear colors.
+fn srgb_texels_to_linear_f16(texels: &[ColorU]) -> Vec<f16> {
+    let mut linear_table = [f16::default(); 256];
+    for (value, linear) in linear_table.iter_mut().enumerate() {
+        let color = ColorU::new(value as u8, 0, 0, 255).to_f32();
+        *linear = f16::from_f32(color.to_color_space(ColorSpace::LinearSRGB).r());
+    }
+
+    let mut linear_texels = Vec::with_capacity(texels.len() * 4);
+    for texel in texels {
+        linear_texels.extend_from_slice(&[
+            linear_table[texel.r as usize],
+            linear_table[texel.g as usize],
+            linear_table[texel.b as usize],
+            f16::from_f32(texel.a as f32 * (1.0 / 255.0)),
+        ]);
+    }
+    linear_texels
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BLIT_ALPHA_MODE_UNPREMULTIPLY, COMBINER_CTRL_PAINT_MASK_LUMINANCE};
+    use super::{COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED, Renderer, paint_mask_ctrl};
+    use super::{atlas_region_to_rect, split_into_disjoint_rects};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::{DestFramebuffer, RendererLevel, RendererMode, RendererOptions};
+    use crate::gpu_data::RenderCommand;
+    use crate::options::{BuildOptions, RenderCommandListener};
+    use crate::paint::Paint;
+    use crate::profile::Profiler;
+    use crate::scene::{DrawPath, Mask, MaskMode, RenderTarget, Scene, SceneSink};
+    use pathfinder_color::ColorU;
+    use pathfinder_color::ColorF;
+    use pathfinder_content::effects::{Filter, PatternFilter, SubpixelLayout};
+    use pathfinder_content::outline::Outline;
+    use pathfinder_content::pattern::{AlphaMode, Image, ImageFormat, Pattern};
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use pathfinder_gpu::recording::{RecordedCommand, RecordedDraw, RecordedTarget};
+    use pathfinder_gpu::recording::RecordingDevice;
+    use pathfinder_gpu::{DeviceError, FeatureLevel, TextureFormat, UniformData};
+    use half::f16;
+    use pathfinder_resources::embedded::EmbeddedResourceLoader;
+    use std::sync::{Arc, Mutex};
+
+    fn create_renderer(device: RecordingDevice, level: RendererLevel)
+                       -> Result<Renderer<RecordingDevice>, DeviceError> {
+        let options = RendererOptions {
+            dest: DestFramebuffer::full_window(vec2i(64, 64)),
+            ..RendererOptions::default()
+        };
+        Renderer::new(device, &EmbeddedResourceLoader::new(), RendererMode { level }, options)
+    }
+
+    // Builds a scene containing a single antialiased square and renders it.
+    fn render_square(renderer: &mut Renderer<RecordingDevice>, level: RendererLevel) {
+        render_square_with_paint(renderer, level, &Paint::from_color(ColorU::black()))
+    }
+
+    fn render_square_with_paint(renderer: &mut Renderer<RecordingDevice>,
+                                level: RendererLevel,
+                                paint: &Paint) {
+        let commands = build_square(level, paint, BuildOptions::default());
+        renderer.begin_scene();
+        for command in &commands {
+            renderer.render_command(command).unwrap();
+        }
+        renderer.end_scene().unwrap();
+    }
+
+    fn build_square(level: RendererLevel, paint: &Paint, options: BuildOptions)
+                    -> Vec<RenderCommand> {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(paint);
+        let outline = Outline::from_rect(RectF::new(vec2f(8.5, 8.5), vec2f(20.0, 20.0)));
+        scene.push_draw_path(DrawPath::new(outline, paint));
+        build_scene(&mut scene, level, options)
+    }
+
+    fn build_scene(scene: &mut Scene, level: RendererLevel, options: BuildOptions)
+                   -> Vec<RenderCommand> {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            listener_commands.lock().unwrap().push(command)
+        }));
+        let mut sink = SceneSink::new(listener, level);
+        scene.build(options, &mut sink, &SequentialExecutor);
+
+        let commands = commands.lock().unwrap().drain(..).collect();
+        commands
+    }
+
+    #[test]
+    fn test_d3d9_draw_calls() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        renderer.device().take_commands();
+        render_square(&mut renderer, RendererLevel::D3D9);
+
+        // Fills are rasterized into the mask framebuffer, and then tiles are drawn to the screen.
+        let draws = renderer.device().draw_calls();
+        let fill_index = draws.iter().position(|draw| draw.program == "d3d9/fill").unwrap();
+        let tile_index = draws.iter().rposition(|draw| draw.program == "d3d9/tile").unwrap();
+        assert!(fill_index < tile_index);
+        assert!(draws[fill_index].instance_count.unwrap() > 0);
+        assert_ne!(draws[fill_index].target, RecordedTarget::Default);
+        assert_eq!(draws[tile_index].target, RecordedTarget::Default);
+        assert!(renderer.device().compute_dispatches().is_empty());
+    }
+
+    #[test]
+    fn test_d3d9_composite_spans() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        let profiler = Profiler::new();
+        renderer.set_profiler(Some(profiler.clone()));
+
+        // Tile batches are numbered from zero in each frame.
+        for _ in 0..2 {
+            profiler.clear();
+            render_square(&mut renderer, RendererLevel::D3D9);
+            let indices: Vec<_> = profiler.spans().iter().filter(|span| {
+                span.name == "Composite"
+            }).map(|span| span.index).collect();
+            assert_eq!(indices, vec![Some(0)]);
+        }
+    }
+
+    #[test]
+    fn test_budget_exceeded() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+
+        // Leave no room for the mask framebuffer that the square's fills need.
+        let allocated = renderer.core.allocator.bytes_allocated();
+        renderer.options_mut().gpu_memory_policy.budget = Some(allocated);
+        let commands = build_square(RendererLevel::D3D9,
+                                    &Paint::from_color(ColorU::black()),
+                                    BuildOptions::default());
+
+        renderer.begin_scene();
+        let error = commands.iter().find_map(|command| renderer.render_command(command).err());
+        match error {
+            Some(DeviceError::BudgetExceeded { allocated: error_allocated, budget, .. }) => {
+                assert_eq!(error_allocated, allocated);
+                assert_eq!(budget, allocated);
+            }
+            error => panic!("Expected the budget to be exceeded, got {:?}!", error),
+        }
+        assert!(renderer.end_scene().is_ok());
+        assert!(renderer.core.allocator.bytes_allocated() <= allocated);
+    }
+
+    // Renders the square with the given damage and returns the scissor rects of the draws of
+    // `program` to the screen.
+    fn render_damaged_square(level: RendererLevel, damage_rects: Vec<RectI>, program: &str)
+                             -> Vec<Option<RectI>> {
+        let feature_level = match level {
+            RendererLevel::D3D9 => FeatureLevel::D3D10,
+            RendererLevel::D3D11 => FeatureLevel::D3D11,
+        };
+        let device = RecordingDevice::new(feature_level);
+        let mut renderer = create_renderer(device, level).unwrap();
+        let options = BuildOptions { damage_rects, ..BuildOptions::default() };
+        let commands = build_square(level, &Paint::from_color(ColorU::black()), options);
+        renderer.begin_scene();
+        for command in &commands {
+            renderer.render_command(command).unwrap();
+        }
+        renderer.end_scene().unwrap();
+
+        renderer.device().draw_calls().iter().filter(|draw| {
+            draw.program == program && draw.target == RecordedTarget::Default
+        }).map(|draw| draw.options.scissor).collect()
+    }
+
+    #[test]
+    fn test_split_into_disjoint_rects() {
+        let rects = [RectI::new(vec2i(0, 0), vec2i(32, 32)),
+                     RectI::new(vec2i(16, 16), vec2i(32, 32)),
+                     RectI::new(vec2i(4, 4), vec2i(8, 8))];
+        let disjoint_rects = split_into_disjoint_rects(&rects);
+        for (index, rect) in disjoint_rects.iter().enumerate() {
+            assert!(rects.iter().any(|other| other.intersection(*rect) == Some(*rect)));
+            assert!(disjoint_rects[(index + 1)..].iter().all(|other| !rect.intersects(*other)));
+        }
+        let area: i32 = disjoint_rects.iter().map(|rect| rect.area()).sum();
+        assert_eq!(area, 32 * 32 * 2 - 16 * 16);
+    }
+
+    #[test]
+    fn test_d3d9_damage_scissors() {
+        // Overlapping damage is drawn once per disjoint piece, never through the union.
+        let damage_rects = vec![RectI::new(vec2i(0, 0), vec2i(32, 32)),
+                                RectI::new(vec2i(16, 16), vec2i(32, 32))];
+        let scissor_rects = render_damaged_square(RendererLevel::D3D9,
+                                                  damage_rects.clone(),
+                                                  "d3d9/tile");
+        assert_eq!(scissor_rects.iter().cloned().collect::<Option<Vec<_>>>(),
+                   Some(split_into_disjoint_rects(&damage_rects)));
+        assert_eq!(scissor_rects.len(), 3);
+    }
+
+    #[test]
+    fn test_d3d11_damage() {
+        // Paths that miss the damage aren't sent to the GPU at all.
+        let damage_rects = vec![RectI::new(vec2i(40, 40), vec2i(16, 16))];
+        let commands = build_square(RendererLevel::D3D11,
+                                    &Paint::from_color(ColorU::black()),
+                                    BuildOptions { damage_rects, ..BuildOptions::default() });
+        assert!(commands.iter().all(|command| {
+            match *command {
+                RenderCommand::DrawTilesD3D11(_) => false,
+                _ => true,
+            }
+        }));
+
+        // Paths that hit it are, and only the damage is copied to the screen.
+        let damage_rects = vec![RectI::new(vec2i(0, 0), vec2i(16, 16)),
+                                RectI::new(vec2i(16, 0), vec2i(16, 16))];
+        let commands = build_square(RendererLevel::D3D11,
+                                    &Paint::from_color(ColorU::black()),
+                                    BuildOptions {
+                                        damage_rects: damage_rects.clone(),
+                                        ..BuildOptions::default()
+                                    });
+        assert!(commands.iter().any(|command| {
+            match *command {
+                RenderCommand::DrawTilesD3D11(_) => true,
+                _ => false,
+            }
+        }));
+        let scissor_rects = render_damaged_square(RendererLevel::D3D11,
+                                                  damage_rects.clone(),
+                                                  "blit");
+        assert_eq!(scissor_rects, damage_rects.into_iter().map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_d3d11_dispatches() {
+        let device = RecordingDevice::new(FeatureLevel::D3D11);
+        let mut renderer = create_renderer(device, RendererLevel::D3D11).unwrap();
+        render_square(&mut renderer, RendererLevel::D3D11);
+
+        let dispatches = renderer.device().compute_dispatches();
+        for program in &["d3d11/bin", "d3d11/fill", "d3d11/tile"] {
+            assert!(dispatches.iter().any(|dispatch| dispatch.program == *program));
+        }
+    }
+
+    #[test]
+    fn test_image_uploads() {
+        let image = Image::from_data(vec2i(4, 4),
+                                     ImageFormat::BGRA8,
+                                     AlphaMode::Premultiplied,
+                                     Arc::new(vec![128; 64]));
+        let paint = Paint::from_pattern(Pattern::from_image(image));
+        let alpha_mode = |draw: &RecordedDraw| {
+            draw.uniforms.iter().find(|(name, _)| name == "AlphaMode").map(|&(_, data)| {
+                match data {
+                    UniformData::Int(alpha_mode) => alpha_mode,
+                    _ => panic!("Alpha mode isn't an integer!"),
+                }
+            })
+        };
+
+        // BGRA images are uploaded as is and unpremultiplied by the blit into the pattern page.
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        render_square_with_paint(&mut renderer, RendererLevel::D3D9, &paint);
+        let draws = renderer.device().draw_calls();
+        let blit = draws.iter().find(|draw| draw.program == "blit").unwrap();
+        assert_eq!(alpha_mode(blit), Some(BLIT_ALPHA_MODE_UNPREMULTIPLY));
+        assert_eq!(blit.viewport.size(), vec2i(4, 4));
+        assert_ne!(blit.target, RecordedTarget::Default);
+
+        // Without BGRA support, the image is converted on the CPU and uploaded directly.
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        device.set_texture_format_supported(TextureFormat::BGRA8, false);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        render_square_with_paint(&mut renderer, RendererLevel::D3D9, &paint);
+        let draws = renderer.device().draw_calls();
+        assert!(draws.iter().all(|draw| draw.program != "blit"));
+        assert!(renderer.device().commands().iter().any(|command| {
+            match *command {
+                RecordedCommand::UploadToTexture { rect, .. } => rect.size() == vec2i(4, 4),
+                _ => false,
+            }
+        }));
+    }
+
+    #[test]
+    fn test_premultiplied_paint_masks() {
+        // Mask one square with a half-transparent render target, and another with an image.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let mask_paint = scene.push_paint(&Paint::from_color(ColorU::new(255, 255, 255, 128)));
+        let render_target = scene.push_render_target(RenderTarget::new(vec2i(16, 16),
+                                                                       String::new()));
+        let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(16.0, 16.0)));
+        scene.push_draw_path(DrawPath::new(outline, mask_paint));
+        scene.pop_render_target();
+        let image = Image::new(vec2i(4, 4), Arc::new(vec![ColorU::new(255, 255, 255, 128); 16]));
+        let patterns = [Pattern::from_render_target(render_target, vec2i(16, 16)),
+                        Pattern::from_image(image)];
+        for (index, pattern) in patterns.iter().enumerate() {
+            let mask = scene.push_mask(Mask::new(pattern.clone(), MaskMode::Luminance));
+            let origin = vec2f(8.0 + 24.0 * index as f32, 8.0);
+            let outline = Outline::from_rect(RectF::new(origin, vec2f(16.0, 16.0)));
+            let mut draw_path = DrawPath::new(outline, paint);
+            draw_path.set_mask(Some(mask));
+            scene.push_draw_path(draw_path);
+        }
+
+        // Only the render target's texels are premultiplied, so only it is unpremultiplied
+        // before its luminance is taken.
+        let commands = build_scene(&mut scene, RendererLevel::D3D9, BuildOptions::default());
+        let metadata = commands.iter().find_map(|command| {
+            match *command {
+                RenderCommand::UploadTextureMetadata(ref metadata) => Some(metadata),
+                _ => None,
+            }
+        }).unwrap();
+        let mut ctrls: Vec<_> = metadata.iter()
+                                        .filter(|entry| entry.paint_mask_mode.is_some())
+                                        .map(paint_mask_ctrl)
+                                        .collect();
+        ctrls.sort();
+        assert_eq!(ctrls, vec![
+            COMBINER_CTRL_PAINT_MASK_LUMINANCE,
+            COMBINER_CTRL_PAINT_MASK_LUMINANCE | COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED,
+        ]);
+
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        renderer.begin_scene();
+        for command in &commands {
+            renderer.render_command(command).unwrap();
+        }
+        renderer.end_scene().unwrap();
+    }
+
+    #[test]
+    fn test_text_filter_subpixel_layout() {
+        // The shader learns the layout from the build options, numbered in declaration order.
+        for &(subpixel_layout, expected) in &[(SubpixelLayout::RGB, 0.0),
+                                               (SubpixelLayout::BGR, 1.0)] {
+            // Composite subpixel-rendered text through a text filter.
+            let mut scene = Scene::new();
+            scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+            let render_target = scene.push_render_target(RenderTarget::new(vec2i(192, 64),
+                                                                           String::new()));
+            let black = scene.push_paint(&Paint::from_color(ColorU::black()));
+            let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(16.0, 16.0)));
+            scene.push_draw_path(DrawPath::new(outline, black));
+            scene.pop_render_target();
+            let mut pattern = Pattern::from_render_target(render_target, vec2i(192, 64));
+            pattern.set_filter(Some(PatternFilter::Text {
+                fg_color: ColorF::black(),
+                bg_color: ColorF::white(),
+                defringing_kernel: None,
+                gamma_correction: false,
+            }));
+            let paint = scene.push_paint(&Paint::from_pattern(pattern));
+            let outline = Outline::from_rect(RectF::new(vec2f(0.0, 0.0), vec2f(16.0, 16.0)));
+            scene.push_draw_path(DrawPath::new(outline, paint));
+
+            let options = BuildOptions {
+                subpixel_aa_enabled: true,
+                subpixel_layout,
+                ..BuildOptions::default()
+            };
+            let commands = build_scene(&mut scene, RendererLevel::D3D9, options);
+
+            let device = RecordingDevice::new(FeatureLevel::D3D10);
+            let mut renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+            renderer.begin_scene();
+            for command in &commands {
+                renderer.render_command(command).unwrap();
+            }
+            renderer.end_scene().unwrap();
+
+            let metadata = commands.iter().find_map(|command| {
+                match *command {
+                    RenderCommand::UploadTextureMetadata(ref metadata) => Some(metadata),
+                    _ => None,
+                }
+            }).unwrap();
+            let entry = metadata.iter().find(|entry| {
+                match entry.filter {
+                    Filter::PatternFilter(PatternFilter::Text { .. }) => true,
+                    _ => false,
+                }
+            }).unwrap();
+            let filter_params = renderer.compute_filter_params(&entry.filter,
+                                                               entry.blend_mode,
+                                                               entry.color_0_combine_mode);
+            assert_eq!(filter_params.p1.w(), expected);
+        }
+    }
+
+    #[test]
+    fn test_atlas_regions_in_texels() {
+        // Pack a few odd-sized images into an atlas, one of them repeating horizontally.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        for index in 0..3 {
+            let size = vec2i(13 + index, 7);
+            let pixels = vec![ColorU::white(); size.x() as usize * size.y() as usize];
+            let mut pattern = Pattern::from_image(Image::new(size, Arc::new(pixels)));
+            pattern.set_repeat_x(index == 0);
+            let paint = scene.push_paint(&Paint::from_pattern(pattern));
+            let origin = vec2f(16.0 * index as f32, 0.0);
+            let outline = Outline::from_rect(RectF::new(origin, vec2f(16.0, 16.0)));
+            scene.push_draw_path(DrawPath::new(outline, paint));
+        }
+
+        let commands = build_scene(&mut scene, RendererLevel::D3D9, BuildOptions::default());
+        let metadata = commands.iter().find_map(|command| {
+            match *command {
+                RenderCommand::UploadTextureMetadata(ref metadata) => Some(metadata),
+                _ => None,
+            }
+        }).unwrap();
+        let mut regions: Vec<_> = metadata.iter()
+                                          .filter_map(|entry| entry.color_0_atlas_region)
+                                          .collect();
+        regions.sort_by_key(|region| region.rect.width());
+        assert_eq!(regions.len(), 3);
+        for (index, region) in regions.iter().enumerate() {
+            assert_eq!(region.rect.size(), vec2i(13 + index as i32, 7));
+            assert_eq!(region.repeat_x, index == 0);
+
+            // Texel coordinates survive the trip through half precision exactly.
+            let (origin, size) = atlas_region_to_rect(Some(*region));
+            for &value in &[origin.x(), origin.y(), size.x(), size.y()] {
+                assert_eq!(f16::from_f32(value).to_f32(), value);
+            }
+            let sign = vec2f(if index == 0 { 1.0 } else { -1.0 }, -1.0);
+            assert_eq!(size, region.rect.size().to_f32() * sign);
+        }
+    }
+
+    #[test]
+    fn test_creation_failures() {
+        // D3D11 rendering needs compute shaders.
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        assert!(create_renderer(device, RendererLevel::D3D11).is_err());
+
+        // The D3D11 compositor can't blend in linear light.
+        let options = RendererOptions {
+            linear_compositing: true,
+            ..RendererOptions::default()
+        };
+        let mode = RendererMode { level: RendererLevel::D3D11 };
+        let device = RecordingDevice::new(FeatureLevel::D3D11);
+        let error = Renderer::new(device, &EmbeddedResourceLoader::new(), mode, options).err();
+        assert_eq!(error, Some(DeviceError::Unsupported("linear compositing at D3D11")));
+
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        device.set_out_of_memory(true);
+        assert_eq!(create_renderer(device, RendererLevel::D3D9).err(),
+                   Some(DeviceError::OutOfMemory));
+
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let renderer = create_renderer(device, RendererLevel::D3D9).unwrap();
+        assert!(!renderer.is_device_lost());
+        renderer.device().lose_device();
+        assert!(renderer.is_device_lost());
+    }
+}
@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use pathfinder_gpu::{BufferTarget, BufferUploadMode, Device, VertexAttrClass};
+use pathfinder_gpu::{BufferTarget, BufferUploadMode, Device, DeviceError, VertexAttrClass};
 use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 
@@ -24,8 +24,8 @@ impl<D> BlitVertexArray<D> where D: Device {
                blit_program: &BlitProgram<D>,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> BlitVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<BlitVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
         let position_attr = device.get_vertex_attr(&blit_program.program, "Position").unwrap();
 
         device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
@@ -40,7 +40,7 @@ impl<D> BlitVertexArray<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        BlitVertexArray { vertex_array }
+        Ok(BlitVertexArray { vertex_array })
     }
 }
 
@@ -53,13 +53,13 @@ impl<D> VertexArraysCore<D> where D: Device {
                programs: &ProgramsCore<D>,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> VertexArraysCore<D> {
-        VertexArraysCore {
+               -> Result<VertexArraysCore<D>, DeviceError> {
+        Ok(VertexArraysCore {
             blit_vertex_array: BlitVertexArray::new(device,
                                                     &programs.blit_program,
                                                     quad_vertex_positions_buffer,
-                                                    quad_vertex_indices_buffer),
-        }
+                                                    quad_vertex_indices_buffer)?,
+        })
     }
 }
 
@@ -72,8 +72,8 @@ impl<D> ClearVertexArray<D> where D: Device {
                clear_program: &ClearProgram<D>,
                quad_vertex_positions_buffer: &D::Buffer,
                quad_vertex_indices_buffer: &D::Buffer)
-               -> ClearVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+               -> Result<ClearVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
         let position_attr = device.get_vertex_attr(&clear_program.program, "Position").unwrap();
 
         device.bind_buffer(&vertex_array, quad_vertex_positions_buffer, BufferTarget::Vertex);
@@ -88,7 +88,7 @@ impl<D> ClearVertexArray<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        ClearVertexArray { vertex_array }
+        Ok(ClearVertexArray { vertex_array })
     }
 }
 
@@ -96,16 +96,30 @@ pub struct BlitProgram<D> where D: Device {
     pub program: D::Program,
     pub dest_rect_uniform: D::Uniform,
     pub framebuffer_size_uniform: D::Uniform,
+    pub src_color_space_uniform: D::Uniform,
+    pub dest_color_space_uniform: D::Uniform,
+    pub alpha_mode_uniform: D::Uniform,
     pub src_texture: D::TextureParameter,
 }
 
 impl<D> BlitProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> BlitProgram<D> {
-        let program = device.create_raster_program(resources, "blit");
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> Result<BlitProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "blit")?;
         let dest_rect_uniform = device.get_uniform(&program, "DestRect");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
+        let src_color_space_uniform = device.get_uniform(&program, "SrcColorSpace");
+        let dest_color_space_uniform = device.get_uniform(&program, "DestColorSpace");
+        let alpha_mode_uniform = device.get_uniform(&program, "AlphaMode");
         let src_texture = device.get_texture_parameter(&program, "Src");
-        BlitProgram { program, dest_rect_uniform, framebuffer_size_uniform, src_texture }
+        Ok(BlitProgram {
+            program,
+            dest_rect_uniform,
+            framebuffer_size_uniform,
+            src_color_space_uniform,
+            dest_color_space_uniform,
+            alpha_mode_uniform,
+            src_texture,
+        })
     }
 }
 
@@ -114,10 +128,10 @@ pub struct ProgramsCore<D> where D: Device {
 }
 
 impl<D> ProgramsCore<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ProgramsCore<D> {
-        ProgramsCore {
-            blit_program: BlitProgram::new(device, resources),
-        }
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> Result<ProgramsCore<D>, DeviceError> {
+        Ok(ProgramsCore {
+            blit_program: BlitProgram::new(device, resources)?,
+        })
     }
 }
 
@@ -129,12 +143,12 @@ pub struct ClearProgram<D> where D: Device {
 }
 
 impl<D> ClearProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ClearProgram<D> {
-        let program = device.create_raster_program(resources, "clear");
+    pub fn new(device: &D, resources: &dyn ResourceLoader) -> Result<ClearProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "clear")?;
         let rect_uniform = device.get_uniform(&program, "Rect");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let color_uniform = device.get_uniform(&program, "Color");
-        ClearProgram { program, rect_uniform, framebuffer_size_uniform, color_uniform }
+        Ok(ClearProgram { program, rect_uniform, framebuffer_size_uniform, color_uniform })
     }
 }
 
@@ -150,6 +164,8 @@ pub struct TileProgramCommon<D> where D: Device {
     pub color_texture_1: D::TextureParameter,
     pub mask_texture_0: D::TextureParameter,
     pub mask_texture_size_0_uniform: D::Uniform,
+    pub paint_mask_texture: D::TextureParameter,
+    pub paint_mask_texture_size_uniform: D::Uniform,
     pub gamma_lut_texture: D::TextureParameter,
     pub framebuffer_size_uniform: D::Uniform,
 }
@@ -166,6 +182,8 @@ impl<D> TileProgramCommon<D> where D: Device {
         let color_texture_1 = device.get_texture_parameter(&program, "ColorTexture1");
         let mask_texture_0 = device.get_texture_parameter(&program, "MaskTexture0");
         let mask_texture_size_0_uniform = device.get_uniform(&program, "MaskTextureSize0");
+        let paint_mask_texture = device.get_texture_parameter(&program, "PaintMaskTexture");
+        let paint_mask_texture_size_uniform = device.get_uniform(&program, "PaintMaskTextureSize");
         let gamma_lut_texture = device.get_texture_parameter(&program, "GammaLUT");
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
 
@@ -181,6 +199,8 @@ impl<D> TileProgramCommon<D> where D: Device {
             color_texture_1,
             mask_texture_0,
             mask_texture_size_0_uniform,
+            paint_mask_texture,
+            paint_mask_texture_size_uniform,
             gamma_lut_texture,
             framebuffer_size_uniform,
         }
@@ -192,9 +212,10 @@ pub struct StencilProgram<D> where D: Device {
 }
 
 impl<D> StencilProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> StencilProgram<D> {
-        let program = device.create_raster_program(resources, "stencil");
-        StencilProgram { program }
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<StencilProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "stencil")?;
+        Ok(StencilProgram { program })
     }
 }
 
@@ -205,10 +226,11 @@ pub struct StencilVertexArray<D> where D: Device {
 }
 
 impl<D> StencilVertexArray<D> where D: Device {
-    pub fn new(device: &D, stencil_program: &StencilProgram<D>) -> StencilVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
-        let vertex_buffer = device.create_buffer(BufferUploadMode::Static);
-        let index_buffer = device.create_buffer(BufferUploadMode::Static);
+    pub fn new(device: &D, stencil_program: &StencilProgram<D>)
+               -> Result<StencilVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
+        let vertex_buffer = device.create_buffer(BufferUploadMode::Static)?;
+        let index_buffer = device.create_buffer(BufferUploadMode::Static)?;
 
         let position_attr = device.get_vertex_attr(&stencil_program.program, "Position").unwrap();
 
@@ -224,7 +246,7 @@ impl<D> StencilVertexArray<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, &index_buffer, BufferTarget::Index);
 
-        StencilVertexArray { vertex_array, vertex_buffer, index_buffer }
+        Ok(StencilVertexArray { vertex_array, vertex_buffer, index_buffer })
     }
 }
 
@@ -236,12 +258,13 @@ pub struct ReprojectionProgram<D> where D: Device {
 }
 
 impl<D> ReprojectionProgram<D> where D: Device {
-    pub fn new(device: &D, resources: &dyn ResourceLoader) -> ReprojectionProgram<D> {
-        let program = device.create_raster_program(resources, "reproject");
+    pub fn new(device: &D, resources: &dyn ResourceLoader)
+               -> Result<ReprojectionProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "reproject")?;
         let old_transform_uniform = device.get_uniform(&program, "OldTransform");
         let new_transform_uniform = device.get_uniform(&program, "NewTransform");
         let texture = device.get_texture_parameter(&program, "Texture");
-        ReprojectionProgram { program, old_transform_uniform, new_transform_uniform, texture }
+        Ok(ReprojectionProgram { program, old_transform_uniform, new_transform_uniform, texture })
     }
 }
 
@@ -255,8 +278,8 @@ impl<D> ReprojectionVertexArray<D> where D: Device {
         reprojection_program: &ReprojectionProgram<D>,
         quad_vertex_positions_buffer: &D::Buffer,
         quad_vertex_indices_buffer: &D::Buffer,
-    ) -> ReprojectionVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+    ) -> Result<ReprojectionVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
         let position_attr = device.get_vertex_attr(&reprojection_program.program, "Position")
                                   .unwrap();
 
@@ -272,6 +295,6 @@ impl<D> ReprojectionVertexArray<D> where D: Device {
         });
         device.bind_buffer(&vertex_array, quad_vertex_indices_buffer, BufferTarget::Index);
 
-        ReprojectionVertexArray { vertex_array }
+        Ok(ReprojectionVertexArray { vertex_array })
     }
 }
@@ -0,0 +1,280 @@
+// pathfinder/renderer/src/gpu/tiled_export.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders images too large for a single framebuffer by splitting them into tiles.
+//!
+//! Each tile is built with a translated transform, rendered offscreen, and read back. Tiles are
+//! collected into bands one tile high, and each band is streamed into a PNG encoder as soon as it
+//! is complete, so peak memory use depends on the width of the image but not on its height.
+//!
+//! Every tile starts from a cleared framebuffer, and pixels are unpremultiplied on readback, since
+//! PNG stores straight alpha.
+
+use crate::concurrent::executor::Executor;
+use crate::gpu::options::DestFramebuffer;
+use crate::gpu::renderer::Renderer;
+use crate::gpu_data::RenderCommand;
+use crate::options::{BuildOptions, RenderCommandListener, RenderTransform};
+use crate::scene::{Scene, SceneSink};
+use pathfinder_geometry::rect::{RectF, RectI};
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_color::{ColorF, ColorSpace};
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
+use pathfinder_gpu::{Device, TextureFormat};
+use std::io::{self, Write};
+use std::mem;
+use std::sync::Mutex;
+
+const DEFAULT_TILE_WIDTH: i32 = 2048;
+const DEFAULT_TILE_HEIGHT: i32 = 512;
+
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Describes the image that `export_png()` produces.
+#[derive(Clone, Copy, Debug)]
+pub struct TiledExportOptions {
+    /// The size of the output image, in pixels.
+    pub size: Vector2I,
+    /// Maps scene coordinates to output image pixels.
+    pub transform: Transform2F,
+    /// The size of the framebuffer each tile is rendered into. Smaller tiles use less memory
+    /// but need more passes.
+    pub tile_size: Vector2I,
+}
+
+impl TiledExportOptions {
+    /// Exports the given scene rectangle at one pixel per unit.
+    pub fn from_view_box(view_box: RectF) -> TiledExportOptions {
+        TiledExportOptions {
+            size: view_box.size().ceil().to_i32(),
+            transform: Transform2F::from_translation(-view_box.origin()),
+            tile_size: vec2i(DEFAULT_TILE_WIDTH, DEFAULT_TILE_HEIGHT),
+        }
+    }
+}
+
+/// Renders `scene` tile by tile and writes the result to `writer` as an RGBA PNG.
+///
+/// The renderer's destination and the scene's view box are replaced while exporting and
+/// restored afterward. The renderer's background color, if any, fills uncovered pixels; otherwise
+/// they're transparent.
+pub fn export_png<D, E, W>(renderer: &mut Renderer<D>,
+                           scene: &mut Scene,
+                           executor: &E,
+                           options: &TiledExportOptions,
+                           writer: W)
+                           -> io::Result<()>
+                           where D: Device, E: Executor, W: Write {
+    if options.size.x() <= 0 || options.size.y() <= 0 ||
+            options.tile_size.x() <= 0 || options.tile_size.y() <= 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Empty image or tile size"));
+    }
+    let tile_size = options.tile_size.min(options.size);
+
+    let mut encoder = png::Encoder::new(writer, options.size.x() as u32, options.size.y() as u32);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut png_writer = encoder.write_header()?;
+    let mut png_stream = png_writer.stream_writer();
+
+    let framebuffer = {
+        let device = renderer.device();
+        device.begin_commands();
+        let framebuffer = device.create_texture(TextureFormat::RGBA8, tile_size)
+                                .and_then(|texture| device.create_framebuffer(texture));
+        device.end_commands();
+        framebuffer.map_err(|error| io::Error::new(io::ErrorKind::Other, error))?
+    };
+    let old_dest = mem::replace(&mut renderer.options_mut().dest,
+                                DestFramebuffer::Other(framebuffer));
+    // A background color makes the renderer clear the framebuffer before each tile, so that no
+    // pixels are left over from the previous one.
+    let old_background_color = renderer.options().background_color;
+    if old_background_color.is_none() {
+        renderer.options_mut().background_color = Some(ColorF::transparent_black());
+    }
+    let old_view_box = scene.view_box();
+    scene.set_view_box(RectF::new(Vector2F::zero(), tile_size.to_f32()));
+
+    let result = export_bands(renderer, scene, executor, options, tile_size, &mut png_stream);
+
+    scene.set_view_box(old_view_box);
+    renderer.options_mut().background_color = old_background_color;
+    if let DestFramebuffer::Other(framebuffer) = mem::replace(&mut renderer.options_mut().dest,
+                                                              old_dest) {
+        let device = renderer.device();
+        device.begin_commands();
+        drop(device.destroy_framebuffer(framebuffer));
+        device.end_commands();
+    }
+
+    result?;
+    png_stream.finish()?;
+    Ok(())
+}
+
+fn export_bands<D, E, W>(renderer: &mut Renderer<D>,
+                         scene: &mut Scene,
+                         executor: &E,
+                         options: &TiledExportOptions,
+                         tile_size: Vector2I,
+                         png_stream: &mut W)
+                         -> io::Result<()>
+                         where D: Device, E: Executor, W: Write {
+    let commands = Mutex::new(vec![]);
+    let listener = RenderCommandListener::new(Box::new(|command| {
+        commands.lock().unwrap().push(command)
+    }));
+    let mut sink = SceneSink::new(listener, renderer.mode().level);
+
+    let band_stride = options.size.x() as usize * BYTES_PER_PIXEL;
+    let tile_stride = tile_size.x() as usize * BYTES_PER_PIXEL;
+    let mut band = vec![0; band_stride * tile_size.y() as usize];
+
+    for band_y in (0..options.size.y()).step_by(tile_size.y() as usize) {
+        let band_height = (options.size.y() - band_y).min(tile_size.y()) as usize;
+        for tile_x in (0..options.size.x()).step_by(tile_size.x() as usize) {
+            let tile_width = (options.size.x() - tile_x).min(tile_size.x()) as usize;
+            let tile_origin = vec2i(tile_x, band_y).to_f32();
+            let transform = Transform2F::from_translation(-tile_origin) * options.transform;
+            scene.build(BuildOptions {
+                transform: RenderTransform::Transform2D(transform),
+                ..BuildOptions::default()
+            }, &mut sink, executor);
+
+            let tile_commands: Vec<RenderCommand> = mem::take(&mut *commands.lock().unwrap());
+            renderer.begin_scene();
+            for command in &tile_commands {
+                renderer.render_command(command)
+                        .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+            }
+            renderer.end_scene().map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+
+            // Edge tiles are rendered at full size, and only the part inside the image is kept.
+            let pixels = read_tile_pixels(renderer, tile_size);
+            let band_offset = tile_x as usize * BYTES_PER_PIXEL;
+            for row in 0..band_height {
+                let src = &pixels[(row * tile_stride)..(row * tile_stride +
+                                                        tile_width * BYTES_PER_PIXEL)];
+                let dest_start = row * band_stride + band_offset;
+                band[dest_start..(dest_start + src.len())].copy_from_slice(src);
+            }
+        }
+
+        png_stream.write_all(&band[0..(band_stride * band_height)])?;
+    }
+
+    Ok(())
+}
+
+// Returns the tile's pixels as straight-alpha sRGB RGBA8.
+fn read_tile_pixels<D>(renderer: &Renderer<D>, tile_size: Vector2I) -> Vec<u8> where D: Device {
+    let viewport = RectI::new(Vector2I::zero(), tile_size);
+    let colors = renderer.read_dest_pixels(viewport, ColorSpace::SRGB);
+    let mut pixels = Vec::with_capacity(colors.len() * BYTES_PER_PIXEL);
+    for color in colors {
+        let color = color.to_u8();
+        pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TiledExportOptions, export_png};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::{DestFramebuffer, RendererLevel, RendererMode, RendererOptions};
+    use crate::gpu::renderer::Renderer;
+    use crate::paint::Paint;
+    use crate::scene::{DrawPath, Scene};
+    use pathfinder_color::{ColorF, ColorU};
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use pathfinder_gpu::recording::{RecordedCommand, RecordedTarget, RecordingDevice};
+    use pathfinder_gpu::{FeatureLevel, UniformData};
+    use pathfinder_resources::embedded::EmbeddedResourceLoader;
+
+    #[test]
+    fn test_tiles() {
+        let device = RecordingDevice::new(FeatureLevel::D3D10);
+        let options = RendererOptions {
+            dest: DestFramebuffer::full_window(vec2i(64, 64)),
+            ..RendererOptions::default()
+        };
+        let mode = RendererMode { level: RendererLevel::D3D9 };
+        let mut renderer = Renderer::new(device, &EmbeddedResourceLoader::new(), mode, options)
+            .unwrap();
+        renderer.device().take_commands();
+
+        // The square only lies in the second of two tiles.
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let outline = Outline::from_rect(RectF::new(vec2f(72.0, 8.0), vec2f(20.0, 20.0)));
+        scene.push_draw_path(DrawPath::new(outline, paint));
+
+        let export_options = TiledExportOptions {
+            size: vec2i(128, 64),
+            transform: Transform2F::default(),
+            tile_size: vec2i(64, 64),
+        };
+        let mut png_data = vec![];
+        export_png(&mut renderer,
+                   &mut scene,
+                   &SequentialExecutor,
+                   &export_options,
+                   &mut png_data).unwrap();
+        assert!(renderer.options().background_color.is_none());
+
+        // Split the commands at each tile's readback.
+        let commands = renderer.device().take_commands();
+        let mut tiles = vec![vec![]];
+        let mut tile_target = None;
+        for command in commands {
+            match command {
+                RecordedCommand::ReadPixels { target, .. } => {
+                    tile_target = Some(target);
+                    tiles.push(vec![]);
+                }
+                RecordedCommand::Draw(draw) => tiles.last_mut().unwrap().push(draw),
+                _ => {}
+            }
+        }
+        let tile_target = tile_target.unwrap();
+        assert_ne!(tile_target, RecordedTarget::Default);
+        assert_eq!(tiles.len(), 3);
+
+        // Each tile clears the framebuffer to transparent, whether or not anything is drawn.
+        let transparent = ColorF::transparent_black().0;
+        for draws in &tiles[0..2] {
+            let draw = draws.iter().find(|draw| draw.target == tile_target).unwrap();
+            let cleared = draw.options.clear_ops.color.map(|color| color.0) == Some(transparent) ||
+                draw.program == "clear" && draw.uniforms.iter().any(|(name, data)| {
+                    match *data {
+                        UniformData::Vec4(color) => name == "Color" && color == transparent,
+                        _ => false,
+                    }
+                });
+            assert!(cleared);
+        }
+
+        // The translation puts the square in the second tile only.
+        let tile_draw_count = |index: usize| {
+            tiles[index].iter().filter(|draw| draw.program == "d3d9/tile").count()
+        };
+        assert_eq!(tile_draw_count(0), 0);
+        assert!(tile_draw_count(1) > 0);
+
+        let decoder = png::Decoder::new(&png_data[..]);
+        let (info, _) = decoder.read_info().unwrap();
+        assert_eq!((info.width, info.height), (128, 64));
+    }
+}
@@ -11,12 +11,13 @@
 //! Packed data ready to be sent to the GPU.
 
 use crate::builder::{ALPHA_TILES_PER_LEVEL, ALPHA_TILE_LEVEL_COUNT};
-use crate::options::BoundingQuad;
+use crate::options::{AntialiasingMode, BoundingQuad};
 use crate::paint::PaintCompositeOp;
-use crate::scene::PathId;
+use crate::scene::{MaskMode, PathId};
 use crate::tile_map::DenseTileMap;
 use pathfinder_color::ColorU;
-use pathfinder_content::effects::{BlendMode, Filter};
+use pathfinder_content::effects::{BlendMode, Filter, SubpixelLayout};
+use pathfinder_content::pattern::Image;
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::line_segment::{LineSegment2F, LineSegmentU16};
 use pathfinder_geometry::rect::RectI;
@@ -49,6 +50,17 @@ pub enum RenderCommand {
         /// This is needed if a path that renders directly to the output framebuffer (i.e. not to a
         /// render target) uses one of the more exotic blend modes.
         needs_readable_framebuffer: bool,
+
+        /// The tile-aligned regions of the destination framebuffer to redraw, in device pixels.
+        ///
+        /// If this is empty, the whole destination framebuffer is redrawn.
+        damage_rects: Vec<RectI>,
+
+        /// How the coverage of pixels along path edges is computed.
+        antialiasing_mode: AntialiasingMode,
+
+        /// The subpixel arrangement that text filters defringe for.
+        subpixel_layout: SubpixelLayout,
     },
 
     // Allocates a texture page.
@@ -57,10 +69,18 @@ pub enum RenderCommand {
     // Uploads data to a texture page.
     UploadTexelData { texels: Arc<Vec<ColorU>>, location: TextureLocation },
 
-    // Associates a render target with a texture page.
-    //
-    // TODO(pcwalton): Add a rect to this so we can render to subrects of a page.
-    DeclareRenderTarget { id: RenderTargetId, location: TextureLocation },
+    // Uploads an image to a texture page in its own pixel format. The renderer converts it to the
+    // page's format and to straight alpha, on the GPU if the device can sample the format.
+    UploadImage { image: Image, location: TextureLocation },
+
+    // Copies texels from one place to another, possibly on a different page. Emitted when texture
+    // pages are compacted. Both locations have the same size.
+    CopyTexelData { src: TextureLocation, dest: TextureLocation },
+
+    // Associates a render target with a region of a texture page, which may be shared with other
+    // render targets and images. The `padding` pixels around the region on each side belong to
+    // the render target too and are cleared along with it.
+    DeclareRenderTarget { id: RenderTargetId, location: TextureLocation, padding: i32 },
 
     // Upload texture metadata.
     UploadTextureMetadata(Vec<TextureMetadataEntry>),
@@ -113,6 +133,23 @@ pub struct TextureLocation {
     pub rect: RectI,
 }
 
+/// How much of a texture page is in use, for memory debugging.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TexturePageUsage {
+    /// The page.
+    pub page: TexturePageId,
+    /// The size of the page in texels.
+    pub size: Vector2I,
+    /// True if the page is a shared atlas, or false if it holds a single image or render target.
+    pub is_atlas: bool,
+    /// True if the page has been freed and is waiting to be reused.
+    pub is_free: bool,
+    /// The number of texels that are allocated.
+    pub used_area: u32,
+    /// The number of texels that are available.
+    pub free_area: u32,
+}
+
 /// Information about a batch of tiles to be prepared (postprocessed).
 #[derive(Clone, Debug)]
 pub struct TileBatchDataD3D11 {
@@ -155,11 +192,19 @@ pub struct PrepareTilesInfoD3D11 {
     /// Metadata about each path that will be diced (flattened).
     pub dice_metadata: Vec<DiceMetadataD3D11>,
 
+    /// A transform for each path, applied to its segments before `transform`.
+    ///
+    /// This is the identity except for symbol instances, which share their segments.
+    pub path_transforms: Vec<PathTransformD3D11>,
+
     /// Sparse information about all the allocated tiles.
     pub tile_path_info: Vec<TilePathInfoD3D11>,
 
     /// A transform to apply to the segments.
     pub transform: Transform2F,
+
+    /// The maximum distance, in device pixels, between a curve and its diced lines.
+    pub flattening_tolerance: f32,
 }
 
 #[derive(Clone, Debug)]
@@ -219,6 +264,8 @@ pub struct DrawTileBatchD3D9 {
     pub z_buffer_data: DenseTileMap<i32>,
     /// The color texture to use.
     pub color_texture: Option<TileBatchTexture>,
+    /// The texture of the mask that the paints are drawn through, if any.
+    pub paint_mask_texture: Option<TileBatchTexture>,
     /// The filter to use.
     pub filter: Filter,
     /// The blend mode to composite these tiles with.
@@ -232,6 +279,8 @@ pub struct DrawTileBatchD3D11 {
     pub tile_batch_data: TileBatchDataD3D11,
     /// The color texture to use.
     pub color_texture: Option<TileBatchTexture>,
+    /// The texture of the mask that the paints are drawn through, if any.
+    pub paint_mask_texture: Option<TileBatchTexture>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -322,16 +371,47 @@ pub struct DiceMetadataD3D11 {
     pub pad: u32,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+pub struct PathTransformD3D11 {
+    /// The 2x2 matrix, in column-major order.
+    pub matrix: [f32; 4],
+    pub translation: [f32; 2],
+    pub pad: [f32; 2],
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub struct TextureMetadataEntry {
     pub color_0_transform: Transform2F,
     pub color_0_combine_mode: ColorCombineMode,
+    pub color_0_atlas_region: Option<TextureAtlasRegion>,
+    pub paint_mask_transform: Transform2F,
+    /// `None` if the paint isn't drawn through a mask.
+    pub paint_mask_mode: Option<MaskMode>,
+    /// True if the mask's texels have premultiplied alpha.
+    pub paint_mask_premultiplied: bool,
+    pub paint_mask_atlas_region: Option<TextureAtlasRegion>,
     pub base_color: ColorU,
     pub filter: Filter,
     pub blend_mode: BlendMode,
 }
 
+/// The part of a shared atlas page that an image occupies.
+///
+/// Hardware clamping and wrapping apply to the whole page, so the tile shaders confine sampling to
+/// this region themselves.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureAtlasRegion {
+    /// The region in texels of the page.
+    ///
+    /// The metadata texture stores this in half precision, which represents texel coordinates
+    /// exactly up to 2048. Normalized coordinates would be off by a fraction of a texel.
+    pub rect: RectI,
+    pub repeat_x: bool,
+    pub repeat_y: bool,
+}
+
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub enum ColorCombineMode {
@@ -433,6 +513,18 @@ impl PathBatchIndex {
     }
 }
 
+impl PathTransformD3D11 {
+    #[inline]
+    pub fn from_transform(transform: &Transform2F) -> PathTransformD3D11 {
+        let (matrix, vector) = (transform.matrix.0, transform.vector);
+        PathTransformD3D11 {
+            matrix: [matrix[0], matrix[1], matrix[2], matrix[3]],
+            translation: [vector.x(), vector.y()],
+            pad: [0.0; 2],
+        }
+    }
+}
+
 impl AlphaTileId {
     #[inline]
     pub fn new(next_alpha_tile_index: &[AtomicUsize; ALPHA_TILE_LEVEL_COUNT], level: usize) 
@@ -473,8 +565,14 @@ impl Debug for RenderCommand {
             RenderCommand::UploadTexelData { ref texels, location } => {
                 write!(formatter, "UploadTexelData(x{:?}, {:?})", texels.len(), location)
             }
-            RenderCommand::DeclareRenderTarget { id, location } => {
-                write!(formatter, "DeclareRenderTarget({:?}, {:?})", id, location)
+            RenderCommand::UploadImage { ref image, location } => {
+                write!(formatter, "UploadImage({:?}, {:?})", image, location)
+            }
+            RenderCommand::CopyTexelData { src, dest } => {
+                write!(formatter, "CopyTexelData({:?}, {:?})", src, dest)
+            }
+            RenderCommand::DeclareRenderTarget { id, location, padding } => {
+                write!(formatter, "DeclareRenderTarget({:?}, {:?}, {})", id, location, padding)
             }
             RenderCommand::UploadTextureMetadata(ref metadata) => {
                 write!(formatter, "UploadTextureMetadata(x{})", metadata.len())
@@ -510,9 +608,10 @@ impl Debug for RenderCommand {
             }
             RenderCommand::DrawTilesD3D11(ref batch) => {
                 write!(formatter,
-                       "DrawTilesD3D11({:?}, C0 {:?})",
+                       "DrawTilesD3D11({:?}, C0 {:?}, M {:?})",
                        batch.tile_batch_data.batch_id,
-                       batch.color_texture)
+                       batch.color_texture,
+                       batch.paint_mask_texture)
             }
             RenderCommand::Finish { cpu_build_time } => {
                 write!(formatter, "Finish({} ms)", cpu_build_time.as_secs_f64() * 1000.0)
@@ -20,10 +20,14 @@ pub mod gpu;
 pub mod gpu_data;
 pub mod options;
 pub mod paint;
+pub mod profile;
 pub mod scene;
+pub mod serialization;
+pub mod trace;
 
 mod allocator;
 mod builder;
+mod path_cache;
 mod tile_map;
 mod tiler;
 mod tiles;
@@ -12,11 +12,14 @@
 
 use crate::gpu::options::RendererLevel;
 use crate::gpu_data::RenderCommand;
-use pathfinder_geometry::rect::RectF;
+use crate::profile::Profiler;
+use crate::tiles;
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::transform3d::Perspective;
-use pathfinder_geometry::vector::{Vector2F, Vector4F};
+use pathfinder_geometry::vector::{Vector2F, Vector4F, vec2i};
 use pathfinder_content::clip::PolygonClipper3D;
+use pathfinder_content::effects::SubpixelLayout;
 
 pub struct RenderCommandListener<'a> {
     send_fn: RenderCommandSendFunction<'a>,
@@ -36,20 +39,111 @@ impl<'a> RenderCommandListener<'a> {
     }
 }
 
+/// The default maximum distance, in device pixels, between a curve and its flattened lines.
+pub const DEFAULT_FLATTENING_TOLERANCE: f32 = 0.25;
+
+// Smaller tolerances would make flattening produce an unbounded number of segments.
+const MIN_FLATTENING_TOLERANCE: f32 = 0.01;
+
+const MAX_SAMPLE_COUNT: u32 = 16;
+
 /// Options that influence scene building.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct BuildOptions {
     pub transform: RenderTransform,
     pub dilation: Vector2F,
     pub subpixel_aa_enabled: bool,
+    /// The subpixel arrangement that subpixel AA renders for and that text filters defringe
+    /// for. Ignored if subpixel AA is disabled.
+    pub subpixel_layout: SubpixelLayout,
+    /// Regions of the destination framebuffer, in device pixels, that have changed since the
+    /// last frame.
+    ///
+    /// If this is empty, the whole framebuffer is redrawn. Otherwise, only the tiles that these
+    /// rectangles touch are drawn, and the rest of the destination framebuffer is left as it was,
+    /// so the destination must retain its contents from frame to frame.
+    pub damage_rects: Vec<RectI>,
+    /// The maximum distance, in device pixels, between a curve and the line segments that
+    /// approximate it. Smaller values look smoother when zoomed in but produce more segments.
+    ///
+    /// Values below 0.01, and values that aren't finite, are treated as 0.01.
+    pub flattening_tolerance: f32,
+    /// How path edges are antialiased.
+    pub antialiasing_mode: AntialiasingMode,
+    /// If present, the phases of the build are recorded here.
+    pub profiler: Option<Profiler>,
+}
+
+impl Default for BuildOptions {
+    #[inline]
+    fn default() -> BuildOptions {
+        BuildOptions {
+            transform: RenderTransform::default(),
+            dilation: Vector2F::zero(),
+            subpixel_aa_enabled: false,
+            subpixel_layout: SubpixelLayout::default(),
+            damage_rects: vec![],
+            flattening_tolerance: DEFAULT_FLATTENING_TOLERANCE,
+            antialiasing_mode: AntialiasingMode::default(),
+            profiler: None,
+        }
+    }
 }
 
 impl BuildOptions {
     pub(crate) fn prepare(self, bounds: RectF) -> PreparedBuildOptions {
+        let damage_tile_rects = self.damage_rects.iter().map(|rect| {
+            tiles::round_rect_out_to_tile_bounds(rect.to_f32())
+        }).filter(|tile_rect| tile_rect.area() > 0).collect();
+        let flattening_tolerance = if self.flattening_tolerance.is_finite() {
+            self.flattening_tolerance.max(MIN_FLATTENING_TOLERANCE)
+        } else {
+            MIN_FLATTENING_TOLERANCE
+        };
         PreparedBuildOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            subpixel_layout: self.subpixel_layout,
+            damage_tile_rects,
+            flattening_tolerance,
+            antialiasing_mode: self.antialiasing_mode,
+            profiler: self.profiler,
+        }
+    }
+}
+
+/// How the coverage of pixels along path edges is computed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AntialiasingMode {
+    /// Exact area coverage, looked up in a table. This is the default.
+    Analytic,
+    /// Each pixel is entirely inside or entirely outside a path, depending on its center.
+    ///
+    /// This is useful for pixel art, stencil masks, and object ID buffers for picking.
+    Aliased,
+    /// Coverage is the fraction of this many point samples inside a path, arranged in an N-rooks
+    /// pattern. The count is rounded up to a power of two, between 1 and 16.
+    Supersampled(u32),
+}
+
+impl Default for AntialiasingMode {
+    #[inline]
+    fn default() -> AntialiasingMode {
+        AntialiasingMode::Analytic
+    }
+}
+
+impl AntialiasingMode {
+    /// The number of point samples per pixel, or zero for analytic coverage.
+    #[inline]
+    pub fn sample_count(self) -> u32 {
+        match self {
+            AntialiasingMode::Analytic => 0,
+            AntialiasingMode::Aliased => 1,
+            AntialiasingMode::Supersampled(count) => {
+                count.max(1).min(MAX_SAMPLE_COUNT).next_power_of_two()
+            }
         }
     }
 }
@@ -123,6 +217,11 @@ pub(crate) struct PreparedBuildOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) subpixel_layout: SubpixelLayout,
+    pub(crate) damage_tile_rects: Vec<RectI>,
+    pub(crate) flattening_tolerance: f32,
+    pub(crate) antialiasing_mode: AntialiasingMode,
+    pub(crate) profiler: Option<Profiler>,
 }
 
 #[derive(Clone, Copy)]
@@ -141,6 +240,25 @@ impl PreparedBuildOptions {
         }
     }
 
+    /// Returns the factor by which paths are stretched so that each subpixel gets its own
+    /// pixel, or one if subpixel AA is disabled.
+    #[inline]
+    pub(crate) fn subpixel_scale(&self) -> Vector2F {
+        if self.subpixel_aa_enabled {
+            self.subpixel_layout.scale()
+        } else {
+            Vector2F::splat(1.0)
+        }
+    }
+
+    /// Returns the damaged regions of the destination framebuffer, rounded out to tile
+    /// boundaries and converted to device pixels.
+    pub(crate) fn damage_rects(&self) -> Vec<RectI> {
+        self.damage_tile_rects.iter().map(|tile_rect| {
+            tile_rect.scale_xy(vec2i(tiles::TILE_WIDTH as i32, tiles::TILE_HEIGHT as i32))
+        }).collect()
+    }
+
     #[inline]
     pub(crate) fn to_prepare_mode(&self, renderer_level: RendererLevel) -> PrepareMode {
         match renderer_level {
@@ -181,3 +299,25 @@ impl PreparedRenderTransform {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{BuildOptions, DEFAULT_FLATTENING_TOLERANCE, MIN_FLATTENING_TOLERANCE};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::vec2f;
+    use std::f32;
+
+    fn prepared_tolerance(flattening_tolerance: f32) -> f32 {
+        let options = BuildOptions { flattening_tolerance, ..BuildOptions::default() };
+        options.prepare(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0))).flattening_tolerance
+    }
+
+    #[test]
+    fn test_flattening_tolerance_clamping() {
+        assert_eq!(prepared_tolerance(DEFAULT_FLATTENING_TOLERANCE), DEFAULT_FLATTENING_TOLERANCE);
+        assert_eq!(prepared_tolerance(4.0), 4.0);
+        for &tolerance in &[0.001, 0.0, -1.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(prepared_tolerance(tolerance), MIN_FLATTENING_TOLERANCE);
+        }
+    }
+}
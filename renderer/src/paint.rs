@@ -8,15 +8,17 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::allocator::{AllocationMode, TextureAllocator};
-use crate::gpu_data::{ColorCombineMode, RenderCommand, TextureLocation, TextureMetadataEntry, TexturePageDescriptor};
-use crate::gpu_data::{TexturePageId, TileBatchTexture};
-use crate::scene::{RenderTarget, SceneId};
-use hashbrown::HashMap;
+use crate::allocator::{AllocationMode, TextureAllocator, TextureMove};
+use crate::gpu_data::{ColorCombineMode, RenderCommand, TextureAtlasRegion, TextureLocation};
+use crate::gpu_data::{TextureMetadataEntry, TexturePageDescriptor, TexturePageId, TexturePageUsage};
+use crate::gpu_data::TileBatchTexture;
+use crate::gpu::options::RendererLevel;
+use crate::scene::{Mask, MaskMode, RenderTarget, SceneId};
+use hashbrown::{HashMap, HashSet};
 use pathfinder_color::ColorU;
 use pathfinder_content::effects::{BlendMode, Filter, PatternFilter};
 use pathfinder_content::gradient::{Gradient, GradientGeometry};
-use pathfinder_content::pattern::{Pattern, PatternSource};
+use pathfinder_content::pattern::{Image, Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
@@ -26,6 +28,7 @@ use pathfinder_gpu::TextureSamplingFlags;
 use pathfinder_simd::default::{F32x2, F32x4};
 use std::f32;
 use std::fmt::{self, Debug, Formatter};
+use std::mem;
 use std::sync::Arc;
 
 // The size of a gradient tile.
@@ -33,18 +36,41 @@ use std::sync::Arc;
 // TODO(pcwalton): Choose this size dynamically!
 const GRADIENT_TILE_LENGTH: u32 = 256;
 
+// Images no larger than this along either axis share atlas pages instead of getting their own.
+const MAX_ATLAS_IMAGE_LENGTH: i32 = 256;
+
+// The default amount of image data, in bytes, that a palette keeps resident between builds.
+const DEFAULT_IMAGE_CACHE_BUDGET: usize = 64 * 1024 * 1024;
+
+// The space left around render targets packed into atlas pages, so that bilinear filtering doesn't
+// pick up texels of neighboring allocations. Blurred render targets get more.
+const MIN_RENDER_TARGET_PADDING: i32 = 1;
+
 #[derive(Clone)]
 pub struct Palette {
     pub paints: Vec<Paint>,
     render_targets: Vec<RenderTargetData>,
     cache: HashMap<Paint, PaintId>,
     allocator: TextureAllocator,
+    image_cache: ImageCache,
+    // Gradient tiles are regenerated every build, so the last build's tiles are freed first.
+    gradient_tile_locations: Vec<TextureLocation>,
+    // Whether to compact the texture atlases at the next build.
+    compact_textures: bool,
     scene_id: SceneId,
 }
 
 #[derive(Clone)]
 struct RenderTargetData {
     render_target: RenderTarget,
+    // Where the last build put the render target. Render targets are placed anew every build.
+    placement: Option<RenderTargetPlacement>,
+}
+
+#[derive(Clone, Copy)]
+struct RenderTargetPlacement {
+    // The space handed out by the texture allocator, padding included.
+    allocation: TextureLocation,
     metadata: RenderTargetMetadata,
 }
 
@@ -52,6 +78,7 @@ struct RenderTargetData {
 pub struct Paint {
     base_color: ColorU,
     overlay: Option<PaintOverlay>,
+    mask: Option<Mask>,
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -96,6 +123,9 @@ impl Palette {
             render_targets: vec![],
             cache: HashMap::new(),
             allocator: TextureAllocator::new(),
+            image_cache: ImageCache::new(),
+            gradient_tile_locations: vec![],
+            compact_textures: false,
             scene_id,
         }
     }
@@ -104,7 +134,7 @@ impl Palette {
 impl Paint {
     #[inline]
     pub fn from_color(color: ColorU) -> Paint {
-        Paint { base_color: color, overlay: None }
+        Paint { base_color: color, overlay: None, mask: None }
     }
 
     #[inline]
@@ -115,6 +145,7 @@ impl Paint {
                 composite_op: PaintCompositeOp::SrcIn,
                 contents: PaintContents::Gradient(gradient),
             }),
+            mask: None,
         }
     }
 
@@ -126,6 +157,7 @@ impl Paint {
                 composite_op: PaintCompositeOp::SrcIn,
                 contents: PaintContents::Pattern(pattern),
             }),
+            mask: None,
         }
     }
 
@@ -140,7 +172,7 @@ impl Paint {
     }
 
     pub fn is_opaque(&self) -> bool {
-        if !self.base_color.is_opaque() {
+        if !self.base_color.is_opaque() || self.mask.is_some() {
             return false;
         }
 
@@ -187,6 +219,9 @@ impl Paint {
                 PaintContents::Pattern(ref mut pattern) => pattern.apply_transform(*transform),
             }
         }
+        if let Some(ref mut mask) = self.mask {
+            mask.apply_transform(transform);
+        }
     }
 
     #[inline]
@@ -235,6 +270,19 @@ impl Paint {
         }
     }
 
+    /// Returns the mask that scales this paint's coverage, if any.
+    ///
+    /// Masks are attached to paints when a scene with masked draw paths is built.
+    #[inline]
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn set_mask(&mut self, new_mask: Option<Mask>) {
+        self.mask = new_mask
+    }
+
     #[inline]
     pub fn gradient(&self) -> Option<&Gradient> {
         match self.overlay {
@@ -283,6 +331,8 @@ pub struct PaintInfo {
 pub struct PaintMetadata {
     /// Metadata associated with the color texture, if applicable.
     pub color_texture_metadata: Option<PaintColorTextureMetadata>,
+    /// Metadata associated with the mask texture, if applicable.
+    pub mask_texture_metadata: Option<PaintMaskTextureMetadata>,
     /// The base color that the color texture gets mixed into.
     pub base_color: ColorU,
     pub blend_mode: BlendMode,
@@ -304,6 +354,24 @@ pub struct PaintColorTextureMetadata {
     pub filter: PaintFilter,
     /// How the color texture is to be composited over the base color.
     pub composite_op: PaintCompositeOp,
+    /// The part of a shared atlas page that the texture occupies, if applicable.
+    pub atlas_region: Option<TextureAtlasRegion>,
+}
+
+#[derive(Debug)]
+pub struct PaintMaskTextureMetadata {
+    /// The location of the mask.
+    pub location: TextureLocation,
+    /// The transform to apply to screen coordinates to translate them into UVs.
+    pub transform: Transform2F,
+    /// The sampling mode for the texture.
+    pub sampling_flags: TextureSamplingFlags,
+    /// Which part of the mask's texels scales coverage.
+    pub mode: MaskMode,
+    /// True if the mask's texels have premultiplied alpha, as render targets do.
+    pub premultiplied: bool,
+    /// The part of a shared atlas page that the mask occupies, if applicable.
+    pub atlas_region: Option<TextureAtlasRegion>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -318,6 +386,9 @@ pub struct RadialGradientMetadata {
 pub struct RenderTargetMetadata {
     /// The location of the render target.
     pub location: TextureLocation,
+    /// The number of pixels around `location` on each side that belong to the render target and
+    /// are cleared along with it, so that filters sampling past its edges read transparent black.
+    pub padding: i32,
 }
 
 #[derive(Debug)]
@@ -345,32 +416,120 @@ impl Palette {
         paint_id
     }
 
+    // Removes the paints pushed since the palette had `len` of them. Builds push paints that are
+    // only meaningful to that build and remove them afterward.
+    pub(crate) fn truncate_paints(&mut self, len: usize) {
+        for paint in self.paints.drain(len..) {
+            self.cache.remove(&paint);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn render_targets(&self) -> impl Iterator<Item = &RenderTarget> {
+        self.render_targets.iter().map(|render_target_data| &render_target_data.render_target)
+    }
+
     pub fn push_render_target(&mut self, render_target: RenderTarget) -> RenderTargetId {
         let id = self.render_targets.len() as u32;
+        self.render_targets.push(RenderTargetData { render_target, placement: None });
+        RenderTargetId { scene: self.scene_id.0, render_target: id }
+    }
 
-        let metadata = RenderTargetMetadata {
-            location: self.allocator.allocate_image(render_target.size()),
-        };
+    // Layers are sized to their contents in device space, so their render targets are resized
+    // whenever the scene is built. Texture space is only allocated after that.
+    pub(crate) fn resize_render_target(&mut self, id: RenderTargetId, new_size: Vector2I) {
+        debug_assert_eq!(id.scene, self.scene_id.0);
+        let render_target_data = &mut self.render_targets[id.render_target as usize];
+        let name = render_target_data.render_target.name().to_owned();
+        render_target_data.render_target = RenderTarget::new(new_size, name);
+    }
 
-        self.render_targets.push(RenderTargetData { render_target, metadata });
-        RenderTargetId { scene: self.scene_id.0, render_target: id }
+    /// Sets the number of bytes of image data to keep uploaded between builds.
+    ///
+    /// Images that weren't used by the most recent build are evicted, least recently used first,
+    /// when the total exceeds this.
+    #[inline]
+    pub fn set_image_cache_budget(&mut self, new_budget: usize) {
+        self.image_cache.budget = new_budget;
+    }
+
+    /// Requests that the next build move images out of sparsely-used atlas pages so that those
+    /// pages can be reused.
+    ///
+    /// The build emits `CopyTexelData` commands so that the renderer moves the texels along.
+    #[inline]
+    pub fn compact_textures(&mut self) {
+        self.compact_textures = true;
     }
 
-    pub fn build_paint_info(&mut self, render_transform: Transform2F) -> PaintInfo {
+    /// Returns how much of each texture page is in use, for memory debugging.
+    #[inline]
+    pub fn texture_page_usage(&self) -> Vec<TexturePageUsage> {
+        self.allocator.page_usage()
+    }
+
+    /// Assigns texture locations to paints and creates the commands that upload their textures.
+    ///
+    /// `paints_in_use` is indexed by paint ID and is true for paints that something draws with.
+    /// Images are only uploaded, and kept uploaded, for paints in use.
+    ///
+    /// Render targets share atlas pages with each other, except under `RendererLevel::D3D11`,
+    /// where each one gets a page of its own. Each pair in `render_target_samples` names a render
+    /// target and another render target that is sampled while drawing to it; those two never
+    /// share a page, so that no draw samples the texture it renders to.
+    pub fn build_paint_info(&mut self,
+                            render_transform: Transform2F,
+                            paints_in_use: &[bool],
+                            render_target_samples: &[(RenderTargetId, RenderTargetId)],
+                            renderer_level: RendererLevel)
+                            -> PaintInfo {
         let mut paint_metadata = vec![];
 
+        // Free the last build's gradient tiles and render targets and make room for any images
+        // that aren't uploaded yet. Freed space goes back into the pool for this build to reuse,
+        // so rebuilding a scene doesn't allocate any new pages.
+        for location in self.gradient_tile_locations.drain(..) {
+            self.allocator.free(location);
+        }
+        for render_target_data in &mut self.render_targets {
+            if let Some(placement) = render_target_data.placement.take() {
+                self.allocator.free(placement.allocation);
+            }
+        }
+        self.image_cache.prepare(&self.paints, paints_in_use, &mut self.allocator);
+
+        // Compact before allocating anything new, so that every page involved already exists.
+        let mut texture_moves = vec![];
+        if mem::take(&mut self.compact_textures) {
+            texture_moves = self.allocator.compact();
+            self.image_cache.relocate(&texture_moves);
+        }
+
+        // Place render targets.
+        let render_target_paddings = self.render_target_paddings(renderer_level);
+        let render_target_groups = self.render_target_groups(render_target_samples);
+        for ((render_target_data, padding), group) in self.render_targets
+                                                          .iter_mut()
+                                                          .zip(render_target_paddings)
+                                                          .zip(render_target_groups) {
+            let size = render_target_data.render_target.size();
+            render_target_data.placement =
+                Some(RenderTargetPlacement::new(&mut self.allocator, size, padding, group));
+        }
+
         // Assign paint locations.
         let mut gradient_tile_builder = GradientTileBuilder::new();
         let mut image_texel_info = vec![];
-        for paint in &self.paints {
+        for (paint, &paint_in_use) in self.paints.iter().zip(paints_in_use.iter()) {
             let allocator = &mut self.allocator;
+            let image_cache = &mut self.image_cache;
             let render_targets = &self.render_targets;
-            let color_texture_metadata = paint.overlay.as_ref().map(|overlay| {
+            let color_texture_metadata = paint.overlay.as_ref().and_then(|overlay| {
                 match overlay.contents {
                     PaintContents::Gradient(ref gradient) => {
                         // FIXME(pcwalton): The gradient size might not be big enough. Detect this.
                         let location = gradient_tile_builder.allocate(allocator, gradient);
-                        PaintColorTextureMetadata {
+                        Some(PaintColorTextureMetadata {
                             location,
                             page_scale: allocator.page_scale(location.page),
                             sampling_flags: TextureSamplingFlags::empty(),
@@ -382,58 +541,68 @@ impl Palette {
                             },
                             transform: Transform2F::default(),
                             composite_op: overlay.composite_op(),
-                        }
+                            atlas_region: None,
+                        })
                     }
                     PaintContents::Pattern(ref pattern) => {
-                        let location;
-                        match *pattern.source() {
-                            PatternSource::RenderTarget { id: render_target_id, .. } => {
-                                let index = render_target_id.render_target as usize;
-                                location = render_targets[index].metadata.location;
-                            }
-                            PatternSource::Image(ref image) => {
-                                // TODO(pcwalton): We should be able to use tile cleverness to
-                                // repeat inside the atlas in some cases.
-                                let allocation_mode = AllocationMode::OwnPage;
-                                location = allocator.allocate(image.size(), allocation_mode);
-                                image_texel_info.push(ImageTexelInfo {
-                                    location,
-                                    texels: (*image.pixels()).clone(),
-                                });
+                        if let PatternSource::Image(_) = *pattern.source() {
+                            if !paint_in_use {
+                                return None;
                             }
                         }
 
-                        let mut sampling_flags = TextureSamplingFlags::empty();
-                        if pattern.repeat_x() {
-                            sampling_flags.insert(TextureSamplingFlags::REPEAT_U);
-                        }
-                        if pattern.repeat_y() {
-                            sampling_flags.insert(TextureSamplingFlags::REPEAT_V);
-                        }
-                        if !pattern.smoothing_enabled() {
-                            sampling_flags.insert(TextureSamplingFlags::NEAREST_MIN |
-                                                  TextureSamplingFlags::NEAREST_MAG);
-                        }
+                        let (location, atlas_region, sampling_flags) =
+                            place_pattern(pattern,
+                                          render_targets,
+                                          allocator,
+                                          image_cache,
+                                          &mut image_texel_info);
 
                         let filter = match pattern.filter() {
                             None => PaintFilter::None,
                             Some(pattern_filter) => PaintFilter::PatternFilter(pattern_filter),
                         };
 
-                        PaintColorTextureMetadata {
+                        Some(PaintColorTextureMetadata {
                             location,
                             page_scale: allocator.page_scale(location.page),
                             sampling_flags,
                             filter,
                             transform: Transform2F::default(),
                             composite_op: overlay.composite_op(),
-                        }
+                            atlas_region,
+                        })
                     }
                 }
             });
 
+            let mask_texture_metadata = match paint.mask {
+                Some(ref mask) if paint_in_use => {
+                    let (location, atlas_region, sampling_flags) =
+                        place_pattern(&mask.pattern,
+                                      &self.render_targets,
+                                      &mut self.allocator,
+                                      &mut self.image_cache,
+                                      &mut image_texel_info);
+                    let premultiplied = match *mask.pattern.source() {
+                        PatternSource::RenderTarget { .. } => true,
+                        PatternSource::Image(_) => false,
+                    };
+                    Some(PaintMaskTextureMetadata {
+                        location,
+                        transform: Transform2F::default(),
+                        sampling_flags,
+                        mode: mask.mode,
+                        premultiplied,
+                        atlas_region,
+                    })
+                }
+                _ => None,
+            };
+
             paint_metadata.push(PaintMetadata {
                 color_texture_metadata,
+                mask_texture_metadata,
                 is_opaque: paint.is_opaque(),
                 base_color: paint.base_color(),
                 // FIXME(pcwalton)
@@ -443,6 +612,16 @@ impl Palette {
 
         // Calculate texture transforms.
         for (paint, metadata) in self.paints.iter().zip(paint_metadata.iter_mut()) {
+            if let Some(ref mut mask_texture_metadata) = metadata.mask_texture_metadata {
+                let mask = paint.mask.as_ref().expect("Why do we have mask texture metadata but \
+                                                       no mask?");
+                let texture_scale = self.allocator.page_scale(mask_texture_metadata.location.page);
+                mask_texture_metadata.transform =
+                    pattern_texture_transform(&mask.pattern,
+                                              mask_texture_metadata.location.rect,
+                                              texture_scale) * render_transform;
+            }
+
             let mut color_texture_metadata = match metadata.color_texture_metadata {
                 None => continue,
                 Some(ref mut color_texture_metadata) => color_texture_metadata,
@@ -471,22 +650,7 @@ impl Palette {
                     ..
                 }) => transform.inverse(),
                 PaintContents::Pattern(ref pattern) => {
-                    match pattern.source() {
-                        PatternSource::Image(_) => {
-                            let texture_origin_uv =
-                                rect_to_uv(texture_rect, texture_scale).origin();
-                            Transform2F::from_scale(texture_scale).translate(texture_origin_uv) *
-                                pattern.transform().inverse()
-                        }
-                        PatternSource::RenderTarget { .. } => {
-                            // FIXME(pcwalton): Only do this in GL, not Metal!
-                            let texture_origin_uv =
-                                rect_to_uv(texture_rect, texture_scale).lower_left();
-                            Transform2F::from_translation(texture_origin_uv) *
-                                Transform2F::from_scale(texture_scale * vec2f(1.0, -1.0)) *
-                                pattern.transform().inverse()
-                        }
-                    }
+                    pattern_texture_transform(pattern, texture_rect, texture_scale)
                 }
             };
             color_texture_metadata.transform *= render_transform;
@@ -504,6 +668,24 @@ impl Palette {
                 } else {
                     ColorCombineMode::None
                 },
+                color_0_atlas_region: paint_metadata.color_texture_metadata
+                                                    .as_ref()
+                                                    .and_then(|metadata| metadata.atlas_region),
+                paint_mask_transform: match paint_metadata.mask_texture_metadata {
+                    None => Transform2F::default(),
+                    Some(ref mask_texture_metadata) => mask_texture_metadata.transform,
+                },
+                paint_mask_mode: paint_metadata.mask_texture_metadata
+                                               .as_ref()
+                                               .map(|metadata| metadata.mode),
+                paint_mask_premultiplied: paint_metadata.mask_texture_metadata
+                                                        .as_ref()
+                                                        .map_or(false, |metadata| {
+                                                            metadata.premultiplied
+                                                        }),
+                paint_mask_atlas_region: paint_metadata.mask_texture_metadata
+                                                       .as_ref()
+                                                       .and_then(|metadata| metadata.atlas_region),
                 base_color: paint_metadata.base_color,
                 filter: paint_metadata.filter(),
                 blend_mode: paint_metadata.blend_mode,
@@ -511,6 +693,14 @@ impl Palette {
         }).collect();
         let mut render_commands = vec![RenderCommand::UploadTextureMetadata(texture_metadata)];
 
+        // Move texels before any freed pages are reallocated.
+        for texture_move in texture_moves {
+            render_commands.push(RenderCommand::CopyTexelData {
+                src: texture_move.old,
+                dest: texture_move.new,
+            });
+        }
+
         // Allocate textures.
         let mut texture_page_descriptors = vec![];
         for page_index in 0..self.allocator.page_count() {
@@ -527,7 +717,7 @@ impl Palette {
 
         // Gather up render target metadata.
         let render_target_metadata: Vec<_> = self.render_targets.iter().map(|render_target_data| {
-            render_target_data.metadata
+            render_target_data.metadata()
         }).collect();
 
         // Create render commands.
@@ -536,12 +726,14 @@ impl Palette {
             render_commands.push(RenderCommand::DeclareRenderTarget {
                 id,
                 location: metadata.location,
+                padding: metadata.padding,
             });
         }
+        self.gradient_tile_locations = gradient_tile_builder.tile_locations();
         gradient_tile_builder.create_render_commands(&mut render_commands);
         for image_texel_info in image_texel_info {
-            render_commands.push(RenderCommand::UploadTexelData {
-                texels: image_texel_info.texels,
+            render_commands.push(RenderCommand::UploadImage {
+                image: image_texel_info.image,
                 location: image_texel_info.location,
             });
         }
@@ -549,6 +741,62 @@ impl Palette {
         PaintInfo { render_commands, paint_metadata, render_target_metadata }
     }
 
+    // Returns how much space to leave around each render target so that filters sampling it read
+    // only transparent texels past its edges.
+    fn render_target_paddings(&self, renderer_level: RendererLevel) -> Vec<i32> {
+        // The D3D11 tile shader writes to absolute tile coordinates, so each render target has to
+        // start at the origin of a page of its own.
+        if renderer_level == RendererLevel::D3D11 {
+            return vec![0; self.render_targets.len()];
+        }
+
+        let mut paddings = vec![MIN_RENDER_TARGET_PADDING; self.render_targets.len()];
+        for paint in &self.paints {
+            let pattern = match paint.overlay {
+                Some(PaintOverlay { contents: PaintContents::Pattern(ref pattern), .. }) => pattern,
+                _ => continue,
+            };
+            let (render_target_id, sigma) = match (pattern.source(), pattern.filter()) {
+                (&PatternSource::RenderTarget { id, .. },
+                 Some(PatternFilter::Blur { sigma, .. })) => (id, sigma),
+                _ => continue,
+            };
+
+            // The renderer computes the blur support the same way. The farthest tap lands one
+            // texel beyond it.
+            let support = f32::ceil(1.5 * sigma) as i32 * 2;
+            let padding = &mut paddings[render_target_id.render_target as usize];
+            *padding = (*padding).max(support + 1 + MIN_RENDER_TARGET_PADDING);
+        }
+        paddings
+    }
+
+    // Assigns each render target the atlas page group it's allocated in. Images use group 0, so
+    // render targets start at group 1, and a render target never gets the group of a render target
+    // that it samples or that samples it.
+    fn render_target_groups(&self, render_target_samples: &[(RenderTargetId, RenderTargetId)])
+                            -> Vec<u32> {
+        let mut groups: Vec<u32> = vec![0; self.render_targets.len()];
+        for render_target_index in 0..self.render_targets.len() {
+            let neighbor_groups: Vec<u32> =
+                render_target_samples.iter().filter_map(|&(dest, src)| {
+                    if dest.render_target as usize == render_target_index {
+                        Some(groups[src.render_target as usize])
+                    } else if src.render_target as usize == render_target_index {
+                        Some(groups[dest.render_target as usize])
+                    } else {
+                        None
+                    }
+                }).collect();
+            let mut group = 1;
+            while neighbor_groups.contains(&group) {
+                group += 1;
+            }
+            groups[render_target_index] = group;
+        }
+        groups
+    }
+
     pub(crate) fn append_palette(&mut self, palette: Palette) -> MergedPaletteInfo {
         // Merge render targets.
         let mut render_target_mapping = HashMap::new();
@@ -626,12 +874,97 @@ impl PaintMetadata {
     pub(crate) fn tile_batch_texture(&self) -> Option<TileBatchTexture> {
         self.color_texture_metadata.as_ref().map(PaintColorTextureMetadata::as_tile_batch_texture)
     }
+
+    pub(crate) fn mask_tile_batch_texture(&self) -> Option<TileBatchTexture> {
+        self.mask_texture_metadata.as_ref().map(PaintMaskTextureMetadata::as_tile_batch_texture)
+    }
 }
 
 fn rect_to_uv(rect: RectI, texture_scale: Vector2F) -> RectF {
     rect.to_f32() * texture_scale
 }
 
+// Finds the texels of a pattern, allocating space for an image and queuing its upload if it isn't
+// resident yet. Returns where the texels are, the part of an atlas page that the shader has to
+// confine sampling to, if any, and the sampling flags for the page.
+fn place_pattern(pattern: &Pattern,
+                 render_targets: &[RenderTargetData],
+                 allocator: &mut TextureAllocator,
+                 image_cache: &mut ImageCache,
+                 image_texel_info: &mut Vec<ImageTexelInfo>)
+                 -> (TextureLocation, Option<TextureAtlasRegion>, TextureSamplingFlags) {
+    let location;
+    let mut atlas_region = None;
+    match *pattern.source() {
+        PatternSource::RenderTarget { id: render_target_id, .. } => {
+            let metadata = render_targets[render_target_id.render_target as usize].metadata();
+            location = metadata.location;
+
+            // Padded render targets don't fill their pages, so the shader clamps or repeats them
+            // just like images in an atlas.
+            if metadata.padding > 0 {
+                atlas_region = Some(TextureAtlasRegion {
+                    rect: location.rect,
+                    repeat_x: pattern.repeat_x(),
+                    repeat_y: pattern.repeat_y(),
+                });
+            }
+        }
+        PatternSource::Image(ref image) => {
+            let (allocation, needs_upload) = image_cache.get_or_allocate(image, allocator);
+            location = TextureLocation {
+                page: allocation.page,
+                rect: RectI::new(allocation.rect.origin(), image.size()),
+            };
+            if needs_upload {
+                image_texel_info.push(ImageTexelInfo { location, image: (*image).clone() });
+            }
+
+            // Images in an atlas are clamped or repeated by the shader.
+            if image_allocation_mode(image) == AllocationMode::Atlas {
+                atlas_region = Some(TextureAtlasRegion {
+                    rect: location.rect,
+                    repeat_x: pattern.repeat_x(),
+                    repeat_y: pattern.repeat_y(),
+                });
+            }
+        }
+    }
+
+    let mut sampling_flags = TextureSamplingFlags::empty();
+    if pattern.repeat_x() && atlas_region.is_none() {
+        sampling_flags.insert(TextureSamplingFlags::REPEAT_U);
+    }
+    if pattern.repeat_y() && atlas_region.is_none() {
+        sampling_flags.insert(TextureSamplingFlags::REPEAT_V);
+    }
+    if !pattern.smoothing_enabled() {
+        sampling_flags.insert(TextureSamplingFlags::NEAREST_MIN |
+                              TextureSamplingFlags::NEAREST_MAG);
+    }
+
+    (location, atlas_region, sampling_flags)
+}
+
+// Returns the transform from pattern space to UVs of the pattern's texels at `texture_rect`.
+fn pattern_texture_transform(pattern: &Pattern, texture_rect: RectI, texture_scale: Vector2F)
+                             -> Transform2F {
+    match pattern.source() {
+        PatternSource::Image(_) => {
+            let texture_origin_uv = rect_to_uv(texture_rect, texture_scale).origin();
+            Transform2F::from_scale(texture_scale).translate(texture_origin_uv) *
+                pattern.transform().inverse()
+        }
+        PatternSource::RenderTarget { .. } => {
+            // FIXME(pcwalton): Only do this in GL, not Metal!
+            let texture_origin_uv = rect_to_uv(texture_rect, texture_scale).lower_left();
+            Transform2F::from_translation(texture_origin_uv) *
+                Transform2F::from_scale(texture_scale * vec2f(1.0, -1.0)) *
+                pattern.transform().inverse()
+        }
+    }
+}
+
 // Gradient allocation
 
 struct GradientTileBuilder {
@@ -683,22 +1016,186 @@ impl GradientTileBuilder {
         location
     }
 
+    fn tile_locations(&self) -> Vec<TextureLocation> {
+        self.tiles.iter().map(GradientTile::location).collect()
+    }
+
     fn create_render_commands(self, render_commands: &mut Vec<RenderCommand>) {
         for tile in self.tiles {
+            let location = tile.location();
             render_commands.push(RenderCommand::UploadTexelData {
                 texels: Arc::new(tile.texels),
-                location: TextureLocation {
-                    rect: RectI::new(vec2i(0, 0), Vector2I::splat(GRADIENT_TILE_LENGTH as i32)),
-                    page: tile.page,
-                },
+                location,
             });
         }
     }
 }
 
+impl GradientTile {
+    fn location(&self) -> TextureLocation {
+        TextureLocation {
+            rect: RectI::new(vec2i(0, 0), Vector2I::splat(GRADIENT_TILE_LENGTH as i32)),
+            page: self.page,
+        }
+    }
+}
+
 struct ImageTexelInfo {
     location: TextureLocation,
-    texels: Arc<Vec<ColorU>>,
+    image: Image,
+}
+
+// Render target placement
+
+impl RenderTargetData {
+    fn metadata(&self) -> RenderTargetMetadata {
+        self.placement.expect("Render target hasn't been placed yet!").metadata
+    }
+}
+
+impl RenderTargetPlacement {
+    // Render targets with padding go into atlas pages of their group, or pages of their own if
+    // they're too big. Either way, the padding is part of the allocation.
+    fn new(allocator: &mut TextureAllocator, size: Vector2I, padding: i32, group: u32)
+           -> RenderTargetPlacement {
+        if padding == 0 {
+            let allocation = allocator.allocate_image(size);
+            let metadata = RenderTargetMetadata { location: allocation, padding };
+            return RenderTargetPlacement { allocation, metadata };
+        }
+
+        let allocation = allocator.allocate_in_group(size + padding * 2, group);
+        let location = TextureLocation {
+            page: allocation.page,
+            rect: RectI::new(allocation.rect.origin() + padding, size),
+        };
+        RenderTargetPlacement { allocation, metadata: RenderTargetMetadata { location, padding } }
+    }
+}
+
+// Image caching
+
+// Keeps images uploaded across builds so that unchanged images aren't uploaded again.
+#[derive(Clone)]
+struct ImageCache {
+    entries: HashMap<Image, CachedImage>,
+    // The number of bytes of image data in `entries`.
+    byte_size: usize,
+    budget: usize,
+    build_index: u64,
+}
+
+#[derive(Clone, Copy)]
+struct CachedImage {
+    // The space allocated for the image. Atlas allocations can be larger than the image itself.
+    allocation: TextureLocation,
+    // The last build that used this image.
+    last_used: u64,
+}
+
+impl ImageCache {
+    fn new() -> ImageCache {
+        ImageCache {
+            entries: HashMap::new(),
+            byte_size: 0,
+            budget: DEFAULT_IMAGE_CACHE_BUDGET,
+            build_index: 0,
+        }
+    }
+
+    // Marks the images of the paints in use as used by this build, then evicts the least recently
+    // used of the other images until the new images fit in the budget.
+    fn prepare(&mut self,
+               paints: &[Paint],
+               paints_in_use: &[bool],
+               allocator: &mut TextureAllocator) {
+        self.build_index += 1;
+
+        let mut new_images = HashSet::new();
+        for (paint, &paint_in_use) in paints.iter().zip(paints_in_use.iter()) {
+            if !paint_in_use {
+                continue;
+            }
+            let mask_pattern = paint.mask.as_ref().map(|mask| &mask.pattern);
+            for pattern in paint.pattern().into_iter().chain(mask_pattern) {
+                let image = match *pattern.source() {
+                    PatternSource::Image(ref image) => image,
+                    PatternSource::RenderTarget { .. } => continue,
+                };
+                match self.entries.get_mut(image) {
+                    Some(entry) => entry.last_used = self.build_index,
+                    None => {
+                        new_images.insert(image);
+                    }
+                }
+            }
+        }
+
+        let new_byte_size: usize = new_images.into_iter().map(image_byte_size).sum();
+        if self.byte_size + new_byte_size <= self.budget {
+            return;
+        }
+
+        let build_index = self.build_index;
+        let mut candidates: Vec<_> = self.entries.iter().filter_map(|(image, entry)| {
+            if entry.last_used < build_index {
+                Some((entry.last_used, image.clone()))
+            } else {
+                None
+            }
+        }).collect();
+        candidates.sort_by_key(|&(last_used, _)| last_used);
+
+        for (_, image) in candidates {
+            if self.byte_size + new_byte_size <= self.budget {
+                break;
+            }
+            let entry = self.entries.remove(&image).unwrap();
+            allocator.free(entry.allocation);
+            self.byte_size -= image_byte_size(&image);
+        }
+    }
+
+    // Follows the allocations that `TextureAllocator::compact()` moved. Moves can chain, so they're
+    // applied in order.
+    fn relocate(&mut self, moves: &[TextureMove]) {
+        for texture_move in moves {
+            for entry in self.entries.values_mut() {
+                if entry.allocation == texture_move.old {
+                    entry.allocation = texture_move.new;
+                }
+            }
+        }
+    }
+
+    // Returns the space allocated for the image and whether its texels need to be uploaded.
+    fn get_or_allocate(&mut self, image: &Image, allocator: &mut TextureAllocator)
+                       -> (TextureLocation, bool) {
+        if let Some(entry) = self.entries.get(image) {
+            return (entry.allocation, false);
+        }
+
+        let allocation = allocator.allocate(image.size(), image_allocation_mode(image));
+        self.entries.insert(image.clone(), CachedImage {
+            allocation,
+            last_used: self.build_index,
+        });
+        self.byte_size += image_byte_size(image);
+        (allocation, true)
+    }
+}
+
+fn image_allocation_mode(image: &Image) -> AllocationMode {
+    let size = image.size();
+    if size.x() <= MAX_ATLAS_IMAGE_LENGTH && size.y() <= MAX_ATLAS_IMAGE_LENGTH {
+        AllocationMode::Atlas
+    } else {
+        AllocationMode::OwnPage
+    }
+}
+
+fn image_byte_size(image: &Image) -> usize {
+    image.size().x() as usize * image.size().y() as usize * 4
 }
 
 impl PaintColorTextureMetadata {
@@ -710,3 +1207,205 @@ impl PaintColorTextureMetadata {
         }
     }
 }
+
+impl PaintMaskTextureMetadata {
+    // Masks scale the alpha of the color beneath them, hence `DestIn`.
+    pub(crate) fn as_tile_batch_texture(&self) -> TileBatchTexture {
+        TileBatchTexture {
+            page: self.location.page,
+            sampling_flags: self.sampling_flags,
+            composite_op: PaintCompositeOp::DestIn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::RenderCommand;
+    use crate::scene::{RenderTarget, SceneId};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::{BlurDirection, PatternFilter};
+    use pathfinder_content::pattern::{Image, Pattern};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{Vector2I, vec2i};
+    use std::sync::Arc;
+
+    use super::{Paint, PaintId, Palette};
+
+    fn push_image_paint(palette: &mut Palette, size: Vector2I, color: ColorU) -> PaintId {
+        let pixels = Arc::new(vec![color; size.x() as usize * size.y() as usize]);
+        let mut pattern = Pattern::from_image(Image::new(size, pixels));
+        pattern.set_repeat_x(true);
+        palette.push_paint(&Paint::from_pattern(pattern))
+    }
+
+    fn count_uploads(palette: &mut Palette, paints_in_use: &[bool]) -> usize {
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  paints_in_use,
+                                                  &[],
+                                                  RendererLevel::D3D9);
+        paint_info.render_commands.iter().filter(|command| {
+            match **command {
+                RenderCommand::UploadImage { .. } => true,
+                _ => false,
+            }
+        }).count()
+    }
+
+    #[test]
+    fn test_images_stay_uploaded() {
+        let mut palette = Palette::new(SceneId(0));
+        let small = push_image_paint(&mut palette, vec2i(16, 16), ColorU::black());
+        let large = push_image_paint(&mut palette, vec2i(512, 512), ColorU::white());
+        assert_eq!(count_uploads(&mut palette, &[true, true]), 2);
+        assert_eq!(count_uploads(&mut palette, &[true, true]), 0);
+
+        // Small images share an atlas page and repeat in the shader.
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  &[true, true],
+                                                  &[],
+                                                  RendererLevel::D3D9);
+        let small_metadata = paint_info.paint_metadata[small.0 as usize]
+                                       .color_texture_metadata
+                                       .as_ref()
+                                       .unwrap();
+        let atlas_region = small_metadata.atlas_region.unwrap();
+        assert!(atlas_region.repeat_x && !atlas_region.repeat_y);
+        assert!(small_metadata.sampling_flags.is_empty());
+        let large_metadata = paint_info.paint_metadata[large.0 as usize]
+                                       .color_texture_metadata
+                                       .as_ref()
+                                       .unwrap();
+        assert!(large_metadata.atlas_region.is_none());
+    }
+
+    #[test]
+    fn test_image_cache_eviction() {
+        let mut palette = Palette::new(SceneId(0));
+        palette.set_image_cache_budget(32 * 32 * 4);
+        push_image_paint(&mut palette, vec2i(32, 32), ColorU::black());
+        push_image_paint(&mut palette, vec2i(32, 32), ColorU::white());
+
+        // Unused paints don't get uploaded.
+        assert_eq!(count_uploads(&mut palette, &[true, false]), 1);
+        assert_eq!(count_uploads(&mut palette, &[true, false]), 0);
+
+        // Only one image fits, so switching between them evicts the other.
+        assert_eq!(count_uploads(&mut palette, &[false, true]), 1);
+        assert_eq!(count_uploads(&mut palette, &[true, false]), 1);
+
+        // Images in use are never evicted, even when over budget.
+        assert_eq!(count_uploads(&mut palette, &[true, true]), 1);
+        assert_eq!(count_uploads(&mut palette, &[true, true]), 0);
+    }
+
+    #[test]
+    fn test_texture_compaction() {
+        let mut palette = Palette::new(SceneId(0));
+        let mut paints_in_use = vec![];
+        for index in 0..17 {
+            push_image_paint(&mut palette, vec2i(256, 256), ColorU::new(index, 0, 0, 255));
+            paints_in_use.push(true);
+        }
+        assert_eq!(count_uploads(&mut palette, &paints_in_use), 17);
+        assert_eq!(palette.texture_page_usage().len(), 2);
+
+        // Stop using most of the first page, so that the second page's image fits into it.
+        palette.set_image_cache_budget(0);
+        for paint_in_use in &mut paints_in_use[2..16] {
+            *paint_in_use = false;
+        }
+        palette.build_paint_info(Transform2F::default(),
+                                 &paints_in_use,
+                                 &[],
+                                 RendererLevel::D3D9);
+
+        palette.compact_textures();
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  &paints_in_use,
+                                                  &[],
+                                                  RendererLevel::D3D9);
+        let copies: Vec<_> = paint_info.render_commands.iter().filter_map(|command| {
+            match *command {
+                RenderCommand::CopyTexelData { src, dest } => Some((src, dest)),
+                _ => None,
+            }
+        }).collect();
+        assert_eq!(copies.len(), 1);
+        let new_location = paint_info.paint_metadata[16].color_texture_metadata
+                                                        .as_ref()
+                                                        .unwrap()
+                                                        .location;
+        assert_eq!(new_location, copies[0].1);
+
+        // Nothing is uploaded again after compaction.
+        assert_eq!(count_uploads(&mut palette, &paints_in_use), 0);
+        let usage = palette.texture_page_usage();
+        assert_eq!(usage.iter().filter(|page_usage| !page_usage.is_free).count(), 1);
+    }
+
+    #[test]
+    fn test_render_targets_share_pages() {
+        let mut palette = Palette::new(SceneId(0));
+        let mut render_target_ids = vec![];
+        for _ in 0..100 {
+            let render_target = RenderTarget::new(vec2i(60, 40), String::new());
+            render_target_ids.push(palette.push_render_target(render_target));
+        }
+        let mut pattern = Pattern::from_render_target(render_target_ids[0], vec2i(60, 40));
+        pattern.set_filter(Some(PatternFilter::Blur { direction: BlurDirection::X, sigma: 2.0 }));
+        palette.push_paint(&Paint::from_pattern(pattern));
+
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  &[true],
+                                                  &[],
+                                                  RendererLevel::D3D9);
+        let metadata = &paint_info.render_target_metadata;
+        let page = metadata[0].location.page;
+        assert!(metadata.iter().all(|metadata| metadata.location.page == page));
+        for (index, metadata) in metadata.iter().enumerate() {
+            assert_eq!(metadata.location.rect.size(), vec2i(60, 40));
+            let padded_rect = metadata.location.rect.contract(-Vector2I::splat(metadata.padding));
+            for other_metadata in &paint_info.render_target_metadata[(index + 1)..] {
+                assert!(!padded_rect.intersects(other_metadata.location.rect));
+            }
+        }
+
+        // The blurred render target is padded enough for the blur's taps.
+        assert_eq!(metadata[0].padding, 8);
+        assert_eq!(metadata[1].padding, 1);
+        let color_texture_metadata = paint_info.paint_metadata[0].color_texture_metadata
+                                                                 .as_ref()
+                                                                 .unwrap();
+        assert!(color_texture_metadata.atlas_region.is_some());
+
+        // Rebuilding reuses the same space without allocating any new pages.
+        let locations: Vec<_> = metadata.iter().map(|metadata| metadata.location).collect();
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  &[true],
+                                                  &[],
+                                                  RendererLevel::D3D9);
+        assert!(paint_info.render_commands.iter().all(|command| {
+            match *command {
+                RenderCommand::AllocateTexturePage { .. } => false,
+                _ => true,
+            }
+        }));
+        let new_locations: Vec<_> = paint_info.render_target_metadata
+                                              .iter()
+                                              .map(|metadata| metadata.location)
+                                              .collect();
+        assert_eq!(locations, new_locations);
+
+        // Under D3D11, every render target gets a page of its own.
+        let paint_info = palette.build_paint_info(Transform2F::default(),
+                                                  &[true],
+                                                  &[],
+                                                  RendererLevel::D3D11);
+        let metadata = &paint_info.render_target_metadata;
+        assert_eq!(metadata[0].padding, 0);
+        assert_eq!(metadata[0].location.rect.origin(), Vector2I::default());
+        assert_ne!(metadata[0].location.page, metadata[1].location.page);
+    }
+}
@@ -0,0 +1,392 @@
+// pathfinder/renderer/src/path_cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Caches the results of CPU tiling between builds so that unchanged paths skip the tiler.
+//!
+//! Alpha tile IDs are allocated afresh on every build, so cached paths store them in a
+//! path-local numbering: the path's own alpha tiles are numbered from zero in allocation order,
+//! and alpha tiles borrowed from its clip path are tagged with `CLIP_ALPHA_TILE_BIT` and numbered
+//! in the clip path's local order. Reusing a path allocates new IDs and renumbers accordingly.
+
+use crate::builder::{BuiltPath, BuiltPathBinCPUData, BuiltPathData, SceneBuilder};
+use crate::gpu_data::{AlphaTileId, Fill};
+use crate::options::{PrepareMode, PreparedBuildOptions, PreparedRenderTransform};
+use crate::scene::{DrawPathHandle, PathId, Scene, SceneId};
+use fxhash::FxHashMap;
+use pathfinder_content::effects::SubpixelLayout;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+use std::mem;
+
+const CLIP_ALPHA_TILE_BIT: u32 = 0x8000_0000;
+
+pub(crate) struct PathCache {
+    key: Option<PathCacheKey>,
+    clip_paths: Vec<Option<CachedPath>>,
+    draw_paths: FxHashMap<DrawPathHandle, CachedDrawPath>,
+}
+
+// Everything outside the paths themselves that tiling depends on. If any of this changes, the
+// whole cache is discarded.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct PathCacheKey {
+    scene_id: SceneId,
+    transform: Transform2F,
+    dilation: Vector2F,
+    subpixel_aa_enabled: bool,
+    subpixel_layout: SubpixelLayout,
+    flattening_tolerance: f32,
+    view_box: RectF,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CachedPath {
+    built_path: BuiltPath,
+    fills: Vec<Fill>,
+    alpha_tile_count: u32,
+}
+
+struct CachedDrawPath {
+    revision: u32,
+    path: CachedPath,
+}
+
+/// A path built this frame, either by the tiler or from the cache.
+pub(crate) struct CachedBuildResult {
+    pub(crate) built_path: BuiltPath,
+    /// The actual IDs of the alpha tiles this path owns, in path-local order.
+    pub(crate) alpha_tile_ids: Vec<AlphaTileId>,
+    /// A new cache entry, if the path was tiled this frame.
+    pub(crate) new_entry: Option<CachedPath>,
+}
+
+impl PathCache {
+    #[inline]
+    pub(crate) fn new() -> PathCache {
+        PathCache { key: None, clip_paths: vec![], draw_paths: FxHashMap::default() }
+    }
+
+    /// Discards the cache if anything global that tiling depends on has changed since the last
+    /// build. Returns true if caching is possible for this build at all.
+    pub(crate) fn prepare(&mut self,
+                          scene: &Scene,
+                          built_options: &PreparedBuildOptions,
+                          prepare_mode: &PrepareMode,
+                          view_box: RectF)
+                          -> bool {
+        let transform = match (prepare_mode, &built_options.transform) {
+            (&PrepareMode::CPU, &PreparedRenderTransform::None) => Transform2F::default(),
+            (&PrepareMode::CPU, &PreparedRenderTransform::Transform2D(transform)) => transform,
+            _ => {
+                self.clear();
+                return false;
+            }
+        };
+
+        let key = PathCacheKey {
+            scene_id: scene.id(),
+            transform,
+            dilation: built_options.dilation,
+            subpixel_aa_enabled: built_options.subpixel_aa_enabled,
+            subpixel_layout: built_options.subpixel_layout,
+            flattening_tolerance: built_options.flattening_tolerance,
+            view_box,
+        };
+        if self.key != Some(key) {
+            self.clear();
+            self.key = Some(key);
+        }
+        true
+    }
+
+    fn clear(&mut self) {
+        self.key = None;
+        self.clip_paths.clear();
+        self.draw_paths.clear();
+    }
+
+    #[inline]
+    pub(crate) fn get_clip_path(&self, path_id: PathId) -> Option<&CachedPath> {
+        self.clip_paths.get(path_id.0 as usize).and_then(|entry| entry.as_ref())
+    }
+
+    pub(crate) fn get_draw_path(&self, scene: &Scene, path_id: PathId) -> Option<&CachedPath> {
+        let handle = scene.draw_path_handle(path_id.to_draw_path_id());
+        match self.draw_paths.get(&handle) {
+            Some(entry) if entry.revision == scene.draw_path_revision(handle) => Some(&entry.path),
+            _ => None,
+        }
+    }
+
+    /// Replaces the entries of paths that were rebuilt this frame and drops entries for paths
+    /// that no longer exist.
+    pub(crate) fn update(&mut self,
+                         scene: &Scene,
+                         new_clip_entries: Vec<Option<CachedPath>>,
+                         new_draw_entries: Vec<Option<CachedPath>>) {
+        self.clip_paths.resize(new_clip_entries.len(), None);
+        for (clip_path_index, new_entry) in new_clip_entries.into_iter().enumerate() {
+            if new_entry.is_some() {
+                self.clip_paths[clip_path_index] = new_entry;
+            }
+        }
+
+        let mut old_draw_paths = mem::replace(&mut self.draw_paths, FxHashMap::default());
+        for (draw_path_index, new_entry) in new_draw_entries.into_iter().enumerate() {
+            let handle = scene.draw_path_handle(PathId(draw_path_index as u32).to_draw_path_id());
+            let entry = match new_entry {
+                Some(path) => CachedDrawPath { revision: scene.draw_path_revision(handle), path },
+                None => {
+                    match old_draw_paths.remove(&handle) {
+                        Some(entry) => entry,
+                        None => continue,
+                    }
+                }
+            };
+            self.draw_paths.insert(handle, entry);
+        }
+    }
+}
+
+impl CachedPath {
+    /// Creates a cache entry from a freshly tiled path. `clip_alpha_tile_ids` are the alpha
+    /// tiles owned by the path's clip path this frame, if it has one.
+    ///
+    /// Returns the entry and the IDs of the alpha tiles the path owns, in path-local order. No
+    /// entry is made if the path borrows alpha tiles that aren't among `clip_alpha_tile_ids`, as
+    /// happens when the clip path was built without the cache.
+    pub(crate) fn new(built_path: &BuiltPath,
+                      fills: &[Fill],
+                      clip_alpha_tile_ids: Option<&[AlphaTileId]>)
+                      -> (Option<CachedPath>, Vec<AlphaTileId>) {
+        // Every alpha tile has at least one fill, so the fills tell us which tiles we own.
+        let mut local_ids = FxHashMap::default();
+        let mut alpha_tile_ids = vec![];
+        for fill in fills {
+            local_ids.entry(fill.link).or_insert_with(|| {
+                alpha_tile_ids.push(AlphaTileId(fill.link));
+                alpha_tile_ids.len() as u32 - 1
+            });
+        }
+        if let Some(clip_alpha_tile_ids) = clip_alpha_tile_ids {
+            for (clip_local_id, clip_alpha_tile_id) in clip_alpha_tile_ids.iter().enumerate() {
+                local_ids.insert(clip_alpha_tile_id.0, CLIP_ALPHA_TILE_BIT | clip_local_id as u32);
+            }
+        }
+
+        let cached_path = remap_built_path(built_path, None, |id| {
+            local_ids.get(&id).cloned()
+        }).map(|built_path| {
+            CachedPath {
+                built_path,
+                fills: fills.iter().map(|fill| {
+                    Fill { link: local_ids[&fill.link], ..*fill }
+                }).collect(),
+                alpha_tile_count: alpha_tile_ids.len() as u32,
+            }
+        });
+        (cached_path, alpha_tile_ids)
+    }
+
+    /// Reconstructs the path for this frame, allocating new alpha tiles for it.
+    pub(crate) fn instantiate(&self,
+                              scene_builder: &SceneBuilder,
+                              path_id: PathId,
+                              clip_alpha_tile_ids: Option<&[AlphaTileId]>)
+                              -> (BuiltPath, Vec<Fill>, Vec<AlphaTileId>) {
+        let alpha_tile_ids: Vec<_> = (0..self.alpha_tile_count).map(|_| {
+            scene_builder.allocate_alpha_tile_id()
+        }).collect();
+        let actual_id = |local_id: u32| {
+            if local_id & CLIP_ALPHA_TILE_BIT == 0 {
+                alpha_tile_ids[local_id as usize].0
+            } else {
+                clip_alpha_tile_ids.expect("Cached path refers to a missing clip path!")
+                                   [(local_id & !CLIP_ALPHA_TILE_BIT) as usize].0
+            }
+        };
+
+        let built_path = remap_built_path(&self.built_path, Some(path_id), |local_id| {
+            Some(actual_id(local_id))
+        }).unwrap();
+        let fills = self.fills.iter().map(|fill| {
+            Fill { link: actual_id(fill.link), ..*fill }
+        }).collect();
+        (built_path, fills, alpha_tile_ids)
+    }
+}
+
+// Renumbers all valid alpha tile IDs in a path and optionally changes its path ID. Returns `None`
+// if `remap` has no new ID for one of them.
+fn remap_built_path<F>(built_path: &BuiltPath, new_path_id: Option<PathId>, remap: F)
+                       -> Option<BuiltPath> where F: Fn(u32) -> Option<u32> {
+    let remap_id = |alpha_tile_id: AlphaTileId| {
+        if alpha_tile_id.is_valid() {
+            remap(alpha_tile_id.0).map(AlphaTileId)
+        } else {
+            Some(alpha_tile_id)
+        }
+    };
+
+    let mut built_path = built_path.clone();
+    if let BuiltPathData::CPU(BuiltPathBinCPUData { ref mut tiles, ref mut clip_tiles, .. }) =
+            built_path.data {
+        for tile in &mut tiles.data {
+            tile.alpha_tile_id = remap_id(tile.alpha_tile_id)?;
+            if let Some(new_path_id) = new_path_id {
+                tile.path_id = new_path_id;
+            }
+        }
+        if let Some(ref mut clip_tiles) = *clip_tiles {
+            for clip_tile in &mut clip_tiles.data {
+                clip_tile.dest_tile_id = remap_id(clip_tile.dest_tile_id)?;
+                clip_tile.src_tile_id = remap_id(clip_tile.src_tile_id)?;
+            }
+        }
+    }
+    Some(built_path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::CachedPath;
+    use crate::builder::{BuiltPath, BuiltPathBinCPUData, BuiltPathData};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::{AlphaTileId, Clip, Fill, RenderCommand, TileObjectPrimitive};
+    use crate::options::{BuildOptions, RenderCommandListener};
+    use crate::paint::{Paint, PaintId};
+    use crate::scene::{ClipPath, ClipPathId, DrawPath, DrawPathId, PathId, Scene, SceneSink};
+    use crate::tile_map::DenseTileMap;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_geometry::rect::{RectF, RectI};
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use std::sync::{Arc, Mutex};
+
+    // Returns the sorted fill segments and the number of alpha tiles drawn.
+    fn build(scene: &mut Scene, sink: &mut SceneSink, commands: &Mutex<Vec<RenderCommand>>)
+             -> (Vec<(u16, u16, u16, u16)>, usize) {
+        scene.build(BuildOptions::default(), sink, &SequentialExecutor);
+        let mut fills = vec![];
+        let mut alpha_tile_count = 0;
+        for command in commands.lock().unwrap().drain(..) {
+            match command {
+                RenderCommand::AddFillsD3D9(new_fills) => {
+                    fills.extend(new_fills.iter().map(|fill| {
+                        let segment = fill.line_segment;
+                        (segment.from_x, segment.from_y, segment.to_x, segment.to_y)
+                    }));
+                }
+                RenderCommand::DrawTilesD3D9(batch) => {
+                    alpha_tile_count += batch.tiles.iter().filter(|tile| {
+                        tile.alpha_tile_id.is_valid()
+                    }).count();
+                }
+                _ => {}
+            }
+        }
+        fills.sort();
+        (fills, alpha_tile_count)
+    }
+
+    #[test]
+    fn test_reuse_and_invalidate() {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            listener_commands.lock().unwrap().push(command)
+        }));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let outline = Outline::from_rect(RectF::new(vec2f(10.5, 10.5), vec2f(100.0, 60.0)));
+        let clip_path = scene.push_clip_path(ClipPath::new(Outline::from_rect(RectF::new(
+            vec2f(20.25, 0.0), vec2f(200.0, 200.0)))));
+        let mut draw_path = DrawPath::new(outline, paint);
+        draw_path.set_clip_path(Some(clip_path));
+        scene.push_draw_path(draw_path);
+        scene.push_draw_path(DrawPath::new(
+            Outline::from_rect(RectF::new(vec2f(50.5, 50.5), vec2f(80.0, 80.0))), paint));
+
+        let first = build(&mut scene, &mut sink, &commands);
+        assert!(!first.0.is_empty());
+        assert_eq!(build(&mut scene, &mut sink, &commands), first);
+
+        // Changing the outline without telling the scene shows that the cached result is used...
+        scene.draw_paths[1].outline =
+            Outline::from_rect(RectF::new(vec2f(150.5, 150.5), vec2f(40.0, 40.0)));
+        assert_eq!(build(&mut scene, &mut sink, &commands), first);
+
+        // ...until the path is marked dirty.
+        scene.mark_draw_path_dirty(DrawPathId(1));
+        let edited = build(&mut scene, &mut sink, &commands);
+        assert_ne!(edited, first);
+
+        // A fresh sink has no cache, and must agree with the incremental result.
+        let fresh_commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = fresh_commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            listener_commands.lock().unwrap().push(command)
+        }));
+        let mut fresh_sink = SceneSink::new(listener, RendererLevel::D3D9);
+        assert_eq!(build(&mut scene, &mut fresh_sink, &fresh_commands), edited);
+    }
+
+    #[test]
+    fn test_missing_clip_alpha_tiles() {
+        // A one-tile path with alpha tile 1, clipped by alpha tile 7 of its clip path.
+        let tile_rect = RectI::new(vec2i(0, 0), vec2i(1, 1));
+        let built_path = BuiltPath {
+            data: BuiltPathData::CPU(BuiltPathBinCPUData {
+                backdrops: vec![0],
+                tiles: DenseTileMap::from_builder(|_| {
+                    TileObjectPrimitive {
+                        tile_x: 0,
+                        tile_y: 0,
+                        alpha_tile_id: AlphaTileId(1),
+                        path_id: PathId(0),
+                        color: 0,
+                        ctrl: 0,
+                        backdrop: 0,
+                    }
+                }, tile_rect),
+                clip_tiles: Some(DenseTileMap::from_builder(|_| {
+                    Clip {
+                        dest_tile_id: AlphaTileId(1),
+                        dest_backdrop: 0,
+                        src_tile_id: AlphaTileId(7),
+                        src_backdrop: 0,
+                    }
+                }, tile_rect)),
+            }),
+            tile_bounds: tile_rect,
+            fill_rule: FillRule::Winding,
+            clip_path_id: Some(ClipPathId(0)),
+            ctrl_byte: 0,
+            paint_id: PaintId(0),
+        };
+        let fills = vec![Fill { link: 1, ..Fill::default() }];
+
+        let (entry, alpha_tile_ids) =
+            CachedPath::new(&built_path, &fills, Some(&[AlphaTileId(7)]));
+        assert!(entry.is_some());
+        assert_eq!(alpha_tile_ids, vec![AlphaTileId(1)]);
+
+        // A clip path built without the cache reports no alpha tiles, so the path can't be cached.
+        let (entry, alpha_tile_ids) = CachedPath::new(&built_path, &fills, Some(&[]));
+        assert!(entry.is_none());
+        assert_eq!(alpha_tile_ids, vec![AlphaTileId(1)]);
+    }
+}
@@ -0,0 +1,236 @@
+// pathfinder/renderer/src/profile.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Optional instrumentation that records how long the phases of scene building and rendering
+//! take.
+//!
+//! Attach a `Profiler` to `BuildOptions` and to the `Renderer`, render some frames, and then call
+//! `write_chrome_trace()` to get a file that `chrome://tracing` or Perfetto can open.
+
+use instant::Instant;
+use serde_json::{Value, json};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Collects timed spans. Clones share the same spans, so one profiler can be handed to both the
+/// scene builder and the renderer, even on different threads.
+#[derive(Clone)]
+pub struct Profiler {
+    state: Arc<Mutex<ProfilerState>>,
+}
+
+struct ProfilerState {
+    epoch: Instant,
+    spans: Vec<ProfileSpan>,
+}
+
+/// A phase of work that was timed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileSpan {
+    /// What was done, for example `"TileDrawPaths"` or `"Fill"`.
+    pub name: &'static str,
+    /// Whether this was scene building or rendering.
+    pub category: ProfileCategory,
+    /// The thread that did the work, as reported by `Executor::current_thread_index()`.
+    ///
+    /// Render spans are always on thread 0.
+    pub thread_index: usize,
+    /// When the work started, relative to when the profiler was created.
+    pub start: Duration,
+    /// How long the work took.
+    pub duration: Duration,
+    /// The path index or tile batch ID that the work was for, if any. At D3D9, tile batches have
+    /// no IDs, so they're numbered in the order they're drawn in each frame instead.
+    pub index: Option<u32>,
+}
+
+/// Which part of Pathfinder a span was recorded by.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileCategory {
+    /// The scene builder, on the CPU.
+    Build,
+    /// The renderer. Render spans measure the time spent issuing GPU commands on the CPU, not the
+    /// time the GPU takes to run them; see `RenderTime` for the latter.
+    Render,
+}
+
+/// Records a span when dropped. Returned by `Profiler::span()`.
+pub struct ProfileScope {
+    profiler: Profiler,
+    name: &'static str,
+    category: ProfileCategory,
+    thread_index: usize,
+    index: Option<u32>,
+    start: Instant,
+}
+
+impl Profiler {
+    #[inline]
+    pub fn new() -> Profiler {
+        Profiler {
+            state: Arc::new(Mutex::new(ProfilerState { epoch: Instant::now(), spans: vec![] })),
+        }
+    }
+
+    /// Starts timing a span, which ends when the returned scope is dropped.
+    #[inline]
+    pub fn span(&self,
+                category: ProfileCategory,
+                name: &'static str,
+                thread_index: usize,
+                index: Option<u32>)
+                -> ProfileScope {
+        ProfileScope {
+            profiler: self.clone(),
+            name,
+            category,
+            thread_index,
+            index,
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the spans recorded so far, in the order in which they ended.
+    pub fn spans(&self) -> Vec<ProfileSpan> {
+        self.state.lock().unwrap().spans.clone()
+    }
+
+    /// Discards the spans recorded so far.
+    pub fn clear(&self) {
+        self.state.lock().unwrap().spans.clear();
+    }
+
+    /// Writes the spans recorded so far as Chrome trace-event JSON.
+    ///
+    /// Build and render spans appear as separate processes, with one track per thread.
+    pub fn write_chrome_trace<W>(&self, writer: W) -> io::Result<()> where W: Write {
+        let mut events = vec![
+            process_name_event(ProfileCategory::Build, "Scene builder"),
+            process_name_event(ProfileCategory::Render, "Renderer"),
+        ];
+        for span in self.state.lock().unwrap().spans.iter() {
+            let mut event = json!({
+                "name": span.name,
+                "cat": span.category.name(),
+                "ph": "X",
+                "ts": duration_to_micros(span.start),
+                "dur": duration_to_micros(span.duration),
+                "pid": span.category.process_id(),
+                "tid": span.thread_index,
+            });
+            if let Some(index) = span.index {
+                event["args"] = json!({ "index": index });
+            }
+            events.push(event);
+        }
+
+        let trace = json!({ "traceEvents": events, "displayTimeUnit": "ms" });
+        serde_json::to_writer(writer, &trace).map_err(io::Error::from)
+    }
+}
+
+impl Default for Profiler {
+    #[inline]
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let end = Instant::now();
+        let mut state = self.profiler.state.lock().unwrap();
+        let span = ProfileSpan {
+            name: self.name,
+            category: self.category,
+            thread_index: self.thread_index,
+            start: self.start - state.epoch,
+            duration: end - self.start,
+            index: self.index,
+        };
+        state.spans.push(span);
+    }
+}
+
+impl ProfileCategory {
+    fn name(self) -> &'static str {
+        match self {
+            ProfileCategory::Build => "build",
+            ProfileCategory::Render => "render",
+        }
+    }
+
+    fn process_id(self) -> u32 {
+        match self {
+            ProfileCategory::Build => 1,
+            ProfileCategory::Render => 2,
+        }
+    }
+}
+
+// Starts a span if there's a profiler attached.
+pub(crate) fn span(profiler: &Option<Profiler>,
+                   category: ProfileCategory,
+                   name: &'static str,
+                   thread_index: usize,
+                   index: Option<u32>)
+                   -> Option<ProfileScope> {
+    profiler.as_ref().map(|profiler| profiler.span(category, name, thread_index, index))
+}
+
+fn process_name_event(category: ProfileCategory, name: &str) -> Value {
+    json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": category.process_id(),
+        "args": { "name": name },
+    })
+}
+
+fn duration_to_micros(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1_000_000.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ProfileCategory, Profiler};
+    use serde_json::Value;
+
+    #[test]
+    fn test_chrome_trace() {
+        let profiler = Profiler::new();
+        {
+            let _outer = profiler.span(ProfileCategory::Build, "Build", 0, None);
+            let _inner = profiler.span(ProfileCategory::Build, "TileDrawPath", 2, Some(7));
+        }
+        drop(profiler.span(ProfileCategory::Render, "Fill", 0, None));
+
+        let spans = profiler.spans();
+        let names: Vec<_> = spans.iter().map(|span| span.name).collect();
+        assert_eq!(names, vec!["TileDrawPath", "Build", "Fill"]);
+        assert!(spans[1].start <= spans[0].start && spans[0].duration <= spans[1].duration);
+
+        let mut bytes = vec![];
+        profiler.write_chrome_trace(&mut bytes).unwrap();
+        let trace: Value = serde_json::from_slice(&bytes).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 5);
+        let tile_event = events.iter().find(|event| event["name"] == "TileDrawPath").unwrap();
+        assert_eq!(tile_event["ph"], "X");
+        assert_eq!(tile_event["tid"], 2);
+        assert_eq!(tile_event["args"]["index"], 7);
+        let fill_event = events.iter().find(|event| event["name"] == "Fill").unwrap();
+        assert_ne!(fill_event["pid"], tile_event["pid"]);
+
+        profiler.clear();
+        assert!(profiler.spans().is_empty());
+    }
+}
@@ -13,16 +13,23 @@
 use crate::builder::SceneBuilder;
 use crate::concurrent::executor::Executor;
 use crate::gpu::options::RendererLevel;
+use crate::gpu_data::TexturePageUsage;
 use crate::options::{BuildOptions, PreparedBuildOptions};
 use crate::options::{PreparedRenderTransform, RenderCommandListener};
 use crate::paint::{MergedPaletteInfo, Paint, PaintId, PaintInfo, Palette};
+use crate::path_cache::PathCache;
+use hashbrown::HashMap;
+use pathfinder_color::ColorF;
 use pathfinder_content::effects::BlendMode;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::outline::Outline;
+use pathfinder_content::pattern::{Pattern, PatternSource};
 use pathfinder_content::render_target::RenderTargetId;
-use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::{Vector2I, vec2f};
+use pathfinder_geometry::util;
+use pathfinder_geometry::vector::{Vector2I, vec2i};
+use std::mem;
 use std::ops::Range;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::u64;
@@ -31,12 +38,25 @@ static NEXT_SCENE_ID: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Clone)]
 pub struct Scene {
-    display_list: Vec<DisplayItem>,
-    draw_paths: Vec<DrawPath>,
-    clip_paths: Vec<ClipPath>,
-    palette: Palette,
-    bounds: RectF,
-    view_box: RectF,
+    pub(crate) display_list: Vec<DisplayItem>,
+    pub(crate) draw_paths: Vec<DrawPath>,
+    pub(crate) clip_paths: Vec<ClipPath>,
+    pub(crate) masks: Vec<Mask>,
+    pub(crate) symbols: Vec<Outline>,
+    pub(crate) palette: Palette,
+    pub(crate) bounds: RectF,
+    pub(crate) view_box: RectF,
+    // Maps each draw path, by index, to its handle.
+    draw_path_handles: Vec<DrawPathHandle>,
+    // Maps each handle to the current index of its draw path, or `None` if it was removed.
+    draw_path_indices: Vec<Option<u32>>,
+    // Maps each handle to a counter that is bumped whenever its draw path changes, so that
+    // cached tiling can be invalidated.
+    draw_path_revisions: Vec<u32>,
+    // Layers that have been pushed but not yet popped, innermost last.
+    layers: Vec<Layer>,
+    // The clip paths that the build in progress made for paths in layers.
+    layer_clip_paths: Range<u32>,
     id: SceneId,
     epoch: SceneEpoch,
 }
@@ -52,18 +72,39 @@ impl Scene {
             display_list: vec![],
             draw_paths: vec![],
             clip_paths: vec![],
+            masks: vec![],
+            symbols: vec![],
             palette: Palette::new(scene_id),
             bounds: RectF::default(),
             view_box: RectF::default(),
+            draw_path_handles: vec![],
+            draw_path_indices: vec![],
+            draw_path_revisions: vec![],
+            layers: vec![],
+            layer_clip_paths: 0..0,
             id: scene_id,
             epoch: SceneEpoch::new(0, 1),
         }
     }
 
-    pub fn push_draw_path(&mut self, draw_path: DrawPath) {
+    /// Draws a path on top of everything drawn so far into the current render target.
+    ///
+    /// The returned handle stays valid across edits that reorder or remove other paths.
+    pub fn push_draw_path(&mut self, draw_path: DrawPath) -> DrawPathHandle {
         let draw_path_index = DrawPathId(self.draw_paths.len() as u32);
-        self.draw_paths.push(draw_path);
+        let handle = self.store_draw_path(draw_path);
         self.push_draw_path_with_index(draw_path_index);
+        handle
+    }
+
+    // Adds a draw path to storage without referencing it from the display list.
+    pub(crate) fn store_draw_path(&mut self, draw_path: DrawPath) -> DrawPathHandle {
+        let handle = DrawPathHandle(self.draw_path_indices.len() as u32);
+        self.draw_path_indices.push(Some(self.draw_paths.len() as u32));
+        self.draw_path_revisions.push(0);
+        self.draw_path_handles.push(handle);
+        self.draw_paths.push(draw_path);
+        handle
     }
 
     fn push_draw_path_with_index(&mut self, draw_path_id: DrawPathId) {
@@ -87,6 +128,52 @@ impl Scene {
         clip_path_id
     }
 
+    /// Adds a mask that draw paths and layers can be drawn through with `DrawPath::set_mask()`
+    /// and `LayerOptions::mask`.
+    pub fn push_mask(&mut self, mask: Mask) -> MaskId {
+        let mask_id = MaskId(self.masks.len() as u32);
+        self.masks.push(mask);
+        self.epoch.next();
+        mask_id
+    }
+
+    /// Defines an outline that can be drawn many times with `push_instance()`.
+    ///
+    /// Instances of a symbol share work. With GPU tiling they share segment data. With CPU tiling,
+    /// instances share tiling and masks only if their device transforms differ by a translation
+    /// that is a multiple of the 16-pixel tile size in both directions, and only if they are
+    /// unclipped and fully inside the view box. Instances offset by any other amount, such as a
+    /// single pixel, are tiled separately.
+    pub fn define_symbol(&mut self, outline: Outline) -> SymbolId {
+        let symbol_id = SymbolId(self.symbols.len() as u32);
+        self.symbols.push(outline);
+        symbol_id
+    }
+
+    #[inline]
+    pub fn symbol_outline(&self, symbol: SymbolId) -> &Outline {
+        &self.symbols[symbol.0 as usize]
+    }
+
+    /// Creates a draw path that draws `symbol` with the given transform and paint.
+    ///
+    /// The path can be customized (e.g. with a fill rule or blend mode) before it's pushed with
+    /// `push_draw_path()`.
+    pub fn instantiate_symbol(&self, symbol: SymbolId, transform: Transform2F, paint: PaintId)
+                              -> DrawPath {
+        let mut draw_path = DrawPath::new(self.symbol_outline(symbol).clone(), paint);
+        draw_path.outline.transform(&transform);
+        draw_path.instance = Some(SymbolInstance { symbol, transform });
+        draw_path
+    }
+
+    /// Draws `symbol` with the given transform and paint on top of everything drawn so far.
+    pub fn push_instance(&mut self, symbol: SymbolId, transform: Transform2F, paint: PaintId)
+                         -> DrawPathHandle {
+        let draw_path = self.instantiate_symbol(symbol, transform, paint);
+        self.push_draw_path(draw_path)
+    }
+
     pub fn push_render_target(&mut self, render_target: RenderTarget) -> RenderTargetId {
         let render_target_id = self.palette.push_render_target(render_target);
         self.display_list.push(DisplayItem::PushRenderTarget(render_target_id));
@@ -94,11 +181,242 @@ impl Scene {
         render_target_id
     }
 
+    /// Pushes a render target that was created earlier with `push_render_target` back onto the
+    /// stack, so that more content can be drawn into it. Its existing contents are preserved.
+    pub fn resume_render_target(&mut self, render_target_id: RenderTargetId) {
+        debug_assert_eq!(render_target_id.scene, self.id.0);
+        self.display_list.push(DisplayItem::PushRenderTarget(render_target_id));
+        self.epoch.next();
+    }
+
     pub fn pop_render_target(&mut self) {
         self.display_list.push(DisplayItem::PopRenderTarget);
     }
 
+    /// Starts drawing into a new layer, which is composited onto the current render target as a
+    /// whole when `pop_layer()` is called.
+    ///
+    /// The layer gets a render target sized to the device-space bounds of the content drawn
+    /// directly into it whenever the scene is built, so unlike `push_render_target()` there's no
+    /// need to size it up front or to draw a pattern afterward. Layers must be popped before the
+    /// scene is built or serialized.
+    pub fn push_layer(&mut self, options: LayerOptions) {
+        let first_display_item_index = self.display_list.len();
+        if !options.isolated && options.is_passthrough() {
+            self.layers.push(Layer { options, render_target_id: None, first_display_item_index });
+            return;
+        }
+
+        // The size is filled in when the layer is popped.
+        let render_target = RenderTarget::new(Vector2I::default(), String::new());
+        let render_target_id = self.palette.push_render_target(render_target);
+        self.display_list.push(DisplayItem::PushRenderTarget(render_target_id));
+        self.layers.push(Layer {
+            options,
+            render_target_id: Some(render_target_id),
+            first_display_item_index,
+        });
+        self.epoch.next();
+    }
+
+    /// Finishes the innermost layer and composites it onto the enclosing render target.
+    ///
+    /// Panics if there's no layer to pop.
+    pub fn pop_layer(&mut self) {
+        let layer = self.layers.pop().expect("pop_layer() called without a matching push_layer()!");
+        let render_target_id = match layer.render_target_id {
+            None => return,
+            Some(render_target_id) => render_target_id,
+        };
+
+        // Find the paths drawn directly into the layer, skipping any render targets nested in it.
+        let mut draw_path_indices = vec![];
+        let mut render_target_depth = 0;
+        for display_item in &self.display_list[(layer.first_display_item_index + 1)..] {
+            match *display_item {
+                DisplayItem::PushRenderTarget(_) => render_target_depth += 1,
+                DisplayItem::PopRenderTarget => render_target_depth -= 1,
+                DisplayItem::DrawPaths(ref range) if render_target_depth == 0 => {
+                    draw_path_indices.extend((range.start.0 as usize)..(range.end.0 as usize));
+                }
+                DisplayItem::DrawPaths(_) => {}
+            }
+        }
+        debug_assert_eq!(render_target_depth, 0);
+        self.display_list.push(DisplayItem::PopRenderTarget);
+        self.epoch.next();
+
+        // The contents stay where they are. Until the scene is built, the render target is sized
+        // as though the build transform were the identity.
+        let rect = match self.layer_bounds(&draw_path_indices, layer.options.clip) {
+            Some(rect) => rect.round_out().to_i32(),
+            None => {
+                // Nothing visible was drawn, so there's nothing to composite.
+                self.palette.resize_render_target(render_target_id, vec2i(1, 1));
+                return;
+            }
+        };
+        self.palette.resize_render_target(render_target_id, rect.size());
+        self.composite_layer(render_target_id, rect, &layer.options);
+    }
+
+    fn composite_layer(&mut self,
+                       render_target_id: RenderTargetId,
+                       rect: RectI,
+                       options: &LayerOptions) {
+        let mut pattern = Pattern::from_render_target(render_target_id, rect.size());
+        pattern.apply_transform(Transform2F::from_translation(rect.origin().to_f32()));
+        pattern.set_smoothing_enabled(false);
+        let mut paint = Paint::from_pattern(pattern);
+        let opacity = util::clamp(options.opacity, 0.0, 1.0);
+        paint.set_base_color(ColorF::new(1.0, 1.0, 1.0, opacity).to_u8());
+        let paint_id = self.push_paint(&paint);
+
+        let mut draw_path = DrawPath::new(Outline::from_rect(rect.to_f32()), paint_id);
+        draw_path.set_clip_path(options.clip);
+        draw_path.set_mask(options.mask);
+        draw_path.set_blend_mode(options.blend_mode);
+        draw_path.set_name("Layer".to_owned());
+        draw_path.layer = Some(render_target_id);
+        self.push_draw_path(draw_path);
+    }
+
+    // Returns the scene-space bounds of the given draw paths, clipped to the bounds of the clip
+    // path, or `None` if they're empty.
+    fn layer_bounds(&self, draw_path_indices: &[usize], clip_path: Option<ClipPathId>)
+                    -> Option<RectF> {
+        let mut bounds = draw_path_indices.iter().fold(None, |bounds: Option<RectF>, &index| {
+            let path_bounds = self.draw_paths[index].outline.bounds();
+            Some(match bounds {
+                None => path_bounds,
+                Some(bounds) => bounds.union_rect(path_bounds),
+            })
+        });
+        if let Some(clip_path_id) = clip_path {
+            let clip_bounds = self.clip_paths[clip_path_id.0 as usize].outline.bounds();
+            bounds = bounds.and_then(|bounds| bounds.intersection(clip_bounds));
+        }
+        bounds.filter(|bounds| bounds.width() > 0.0 && bounds.height() > 0.0)
+    }
+
+    // Sizes the render target of each layer to the device-space bounds of its contents, and
+    // works out how the paths drawn into it are moved to its origin. Nothing in the scene is
+    // rewritten except the outlines of the paths that composite the layers.
+    //
+    // Clip paths made for paths in layers are appended to `clip_paths`, and paints made for masks
+    // and layers are pushed to the palette when the paint info is built. `build()` removes both
+    // afterward.
+    fn place_layers(&mut self, options: &PreparedBuildOptions) {
+        let clip_path_count = self.clip_paths.len() as u32;
+        self.layer_clip_paths = clip_path_count..clip_path_count;
+
+        // Under perspective, layers are drawn in scene space and projected when composited.
+        let (device_transform, view_box) = match options.transform {
+            PreparedRenderTransform::Transform2D(transform) => (transform, Some(self.view_box)),
+            PreparedRenderTransform::None => (Transform2F::default(), Some(self.view_box)),
+            PreparedRenderTransform::Perspective { .. } => (Transform2F::default(), None),
+        };
+        let inverse_device_transform = device_transform.inverse();
+
+        // Find the paths drawn directly into each render target.
+        let mut contents: HashMap<RenderTargetId, Vec<usize>> = HashMap::new();
+        let mut render_target_stack = vec![];
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push(render_target_id)
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths(ref range) => {
+                    if let Some(&render_target_id) = render_target_stack.last() {
+                        contents.entry(render_target_id)
+                                .or_insert_with(Vec::new)
+                                .extend((range.start.0 as usize)..(range.end.0 as usize));
+                    }
+                }
+            }
+        }
+
+        // Layers nested in another layer are composited by a path inside it, which comes first,
+        // so inner layers are placed before the layers that contain them.
+        let mut layer_transforms = vec![None; self.draw_paths.len()];
+        for composite_index in 0..self.draw_paths.len() {
+            let render_target_id = match self.draw_paths[composite_index].layer {
+                None => continue,
+                Some(render_target_id) => render_target_id,
+            };
+            let content = contents.get(&render_target_id).map_or(&[][..], |content| &content[..]);
+            let bounds = self.layer_bounds(content, self.draw_paths[composite_index].clip_path);
+            let device_rect = bounds.and_then(|bounds| {
+                let device_bounds = (device_transform * bounds).dilate(options.dilation);
+                match view_box {
+                    None => Some(device_bounds),
+                    Some(view_box) => device_bounds.intersection(view_box),
+                }
+            }).map(|device_bounds| device_bounds.round_out().to_i32());
+
+            let (new_outline, layer_pattern) = match (bounds, device_rect) {
+                (Some(bounds), Some(rect)) if rect.width() > 0 && rect.height() > 0 => {
+                    // The contents are drawn relative to the origin of the device-space rect,
+                    // and the render target is drawn back there.
+                    let origin = rect.origin().to_f32();
+                    let layer_transform = inverse_device_transform *
+                        Transform2F::from_translation(-origin) * device_transform;
+                    for &index in content {
+                        layer_transforms[index] = Some((render_target_id, layer_transform));
+                    }
+
+                    let mut pattern = Pattern::from_render_target(render_target_id, rect.size());
+                    pattern.apply_transform(inverse_device_transform *
+                                            Transform2F::from_translation(origin));
+                    pattern.set_smoothing_enabled(false);
+                    self.palette.resize_render_target(render_target_id, rect.size());
+                    (Outline::from_rect(bounds), Some(pattern))
+                }
+                _ => {
+                    self.palette.resize_render_target(render_target_id, vec2i(1, 1));
+                    (Outline::new(), None)
+                }
+            };
+
+            let composite_path = &mut self.draw_paths[composite_index];
+            composite_path.build_state.layer_pattern = layer_pattern;
+            if composite_path.outline.bounds() != new_outline.bounds() {
+                composite_path.outline = new_outline;
+                self.mark_draw_path_dirty(DrawPathId(composite_index as u32));
+            }
+        }
+
+        // Paths in layers that are clipped get copies of their clip paths moved along with them.
+        let mut layer_clip_path_mapping = HashMap::new();
+        for (index, layer) in layer_transforms.into_iter().enumerate() {
+            let draw_path = &mut self.draw_paths[index];
+            draw_path.build_state.clip_path = match (layer, draw_path.clip_path) {
+                (Some((render_target_id, layer_transform)), Some(clip_path_id)) => {
+                    let clip_paths = &mut self.clip_paths;
+                    let key = (clip_path_id, render_target_id);
+                    Some(*layer_clip_path_mapping.entry(key).or_insert_with(|| {
+                        let mut clip_path = clip_paths[clip_path_id.0 as usize].clone();
+                        clip_path.outline.transform(&layer_transform);
+                        clip_paths.push(clip_path);
+                        ClipPathId(clip_paths.len() as u32 - 1)
+                    }))
+                }
+                _ => None,
+            };
+            let layer_transform = layer.map(|(_, layer_transform)| layer_transform);
+            if draw_path.build_state.layer_transform != layer_transform {
+                draw_path.build_state.layer_transform = layer_transform;
+                self.mark_draw_path_dirty(DrawPathId(index as u32));
+            }
+        }
+        self.layer_clip_paths.end = self.clip_paths.len() as u32;
+    }
+
     pub fn append_scene(&mut self, scene: Scene) {
+        debug_assert!(scene.layers.is_empty(), "The appended scene has layers that weren't popped!");
         let MergedPaletteInfo {
             render_target_mapping,
             paint_mapping,
@@ -111,19 +429,48 @@ impl Scene {
             self.clip_paths.push(clip_path);
         }
 
+        // Merge masks, pointing render target masks at the merged render targets.
+        let first_mask_index = self.masks.len() as u32;
+        for mut mask in scene.masks {
+            if let PatternSource::RenderTarget { id, size } = *mask.pattern.source() {
+                let mut pattern = Pattern::from_render_target(render_target_mapping[&id], size);
+                pattern.apply_transform(mask.pattern.transform());
+                pattern.set_repeat_x(mask.pattern.repeat_x());
+                pattern.set_repeat_y(mask.pattern.repeat_y());
+                pattern.set_smoothing_enabled(mask.pattern.smoothing_enabled());
+                mask.pattern = pattern;
+            }
+            self.masks.push(mask);
+        }
+
+        // Merge symbols.
+        let first_symbol_index = self.symbols.len() as u32;
+        self.symbols.extend(scene.symbols);
+
         // Merge draw paths.
         let mut draw_path_mapping = Vec::with_capacity(scene.draw_paths.len());
         for draw_path in scene.draw_paths {
             draw_path_mapping.push(self.draw_paths.len() as u32);
-            self.draw_paths.push(DrawPath {
+            self.store_draw_path(DrawPath {
                 outline: draw_path.outline,
                 paint: paint_mapping[&draw_path.paint],
                 clip_path: draw_path.clip_path.map(|clip_path_id| {
                     ClipPathId(clip_path_mapping[clip_path_id.0 as usize] as u32)
                 }),
+                mask: draw_path.mask.map(|mask_id| MaskId(mask_id.0 + first_mask_index)),
                 fill_rule: draw_path.fill_rule,
                 blend_mode: draw_path.blend_mode,
                 name: draw_path.name,
+                instance: draw_path.instance.map(|instance| {
+                    SymbolInstance {
+                        symbol: SymbolId(instance.symbol.0 + first_symbol_index),
+                        ..instance
+                    }
+                }),
+                layer: draw_path.layer.map(|render_target_id| {
+                    render_target_mapping[&render_target_id]
+                }),
+                build_state: DrawPathBuildState::default(),
             });
         }
 
@@ -150,9 +497,277 @@ impl Scene {
         self.epoch.next();
     }
 
+    /// Returns the current index of the draw path with the given handle, or `None` if the path
+    /// has been removed.
+    #[inline]
+    pub fn draw_path_id(&self, handle: DrawPathHandle) -> Option<DrawPathId> {
+        self.draw_path_indices[handle.0 as usize].map(DrawPathId)
+    }
+
+    #[inline]
+    pub fn draw_path(&self, handle: DrawPathHandle) -> Option<&DrawPath> {
+        self.draw_path_id(handle).map(|draw_path_id| self.get_draw_path(draw_path_id))
+    }
+
+    /// Replaces the draw path with the given handle, keeping its position in the display list,
+    /// and returns the old path.
+    ///
+    /// Panics if the path has been removed.
+    pub fn replace_draw_path(&mut self, handle: DrawPathHandle, new_draw_path: DrawPath)
+                             -> DrawPath {
+        let index = self.expect_draw_path_index(handle);
+        let old_draw_path = mem::replace(&mut self.draw_paths[index], new_draw_path);
+        self.mark_draw_path_dirty(DrawPathId(index as u32));
+        self.recompute_bounds();
+        self.epoch.next();
+        old_draw_path
+    }
+
+    /// Panics if the path has been removed.
+    pub fn set_draw_path_outline(&mut self, handle: DrawPathHandle, new_outline: Outline) {
+        let index = self.expect_draw_path_index(handle);
+        self.draw_paths[index].outline = new_outline;
+        self.draw_paths[index].instance = None;
+        self.mark_draw_path_dirty(DrawPathId(index as u32));
+        self.recompute_bounds();
+        self.epoch.next();
+    }
+
+    /// Panics if the path has been removed.
+    pub fn set_draw_path_paint(&mut self, handle: DrawPathHandle, new_paint: PaintId) {
+        let index = self.expect_draw_path_index(handle);
+        self.draw_paths[index].paint = new_paint;
+        self.mark_draw_path_dirty(DrawPathId(index as u32));
+    }
+
+    /// Transforms the outline of the draw path with the given handle in place.
+    ///
+    /// Panics if the path has been removed.
+    pub fn transform_draw_path(&mut self, handle: DrawPathHandle, transform: &Transform2F) {
+        let index = self.expect_draw_path_index(handle);
+        let draw_path = &mut self.draw_paths[index];
+        draw_path.outline.transform(transform);
+        if let Some(ref mut instance) = draw_path.instance {
+            instance.transform = *transform * instance.transform;
+        }
+        self.mark_draw_path_dirty(DrawPathId(index as u32));
+        self.recompute_bounds();
+        self.epoch.next();
+    }
+
+    /// Forces the draw path with the given ID to be re-tiled on the next build.
+    ///
+    /// The editing methods on `Scene` do this automatically; this is only needed if something
+    /// the path depends on changed behind the scene's back.
+    pub fn mark_draw_path_dirty(&mut self, draw_path_id: DrawPathId) {
+        let handle = self.draw_path_handles[draw_path_id.0 as usize];
+        self.draw_path_revisions[handle.0 as usize] += 1;
+        self.epoch.next();
+    }
+
+    #[inline]
+    pub(crate) fn draw_path_handle(&self, draw_path_id: DrawPathId) -> DrawPathHandle {
+        self.draw_path_handles[draw_path_id.0 as usize]
+    }
+
     #[inline]
-    pub fn build_paint_info(&mut self, render_transform: Transform2F) -> PaintInfo {
-        self.palette.build_paint_info(render_transform)
+    pub(crate) fn draw_path_revision(&self, handle: DrawPathHandle) -> u32 {
+        self.draw_path_revisions[handle.0 as usize]
+    }
+
+    /// Removes the draw path with the given handle from the scene and returns it, or returns
+    /// `None` if it was already removed.
+    ///
+    /// The IDs of paths drawn after it shift down by one; handles are unaffected.
+    pub fn remove_draw_path(&mut self, handle: DrawPathHandle) -> Option<DrawPath> {
+        let index = self.draw_path_indices[handle.0 as usize]? as usize;
+        let draw_path = self.take_draw_path(index);
+        self.draw_path_indices[handle.0 as usize] = None;
+        self.remove_empty_draw_path_ranges();
+        self.recompute_bounds();
+        self.epoch.next();
+        Some(draw_path)
+    }
+
+    /// Moves the draw path with the given handle so that it's drawn immediately after `target`,
+    /// into the same render target.
+    ///
+    /// Panics if either path has been removed.
+    pub fn move_draw_path_above(&mut self, handle: DrawPathHandle, target: DrawPathHandle) {
+        self.move_draw_path(handle, target, 1)
+    }
+
+    /// Moves the draw path with the given handle so that it's drawn immediately before `target`,
+    /// into the same render target.
+    ///
+    /// Panics if either path has been removed.
+    pub fn move_draw_path_below(&mut self, handle: DrawPathHandle, target: DrawPathHandle) {
+        self.move_draw_path(handle, target, 0)
+    }
+
+    fn move_draw_path(&mut self, handle: DrawPathHandle, target: DrawPathHandle, offset: u32) {
+        if handle == target {
+            return;
+        }
+
+        let index = self.expect_draw_path_index(handle);
+        let draw_path = self.take_draw_path(index);
+
+        let target_index = self.expect_draw_path_index(target) as u32;
+        let new_index = target_index + offset;
+        for display_item in &mut self.display_list {
+            if let DisplayItem::DrawPaths(ref mut range) = *display_item {
+                if range.start.0 <= target_index && target_index < range.end.0 {
+                    range.end.0 += 1;
+                } else if range.start.0 >= new_index {
+                    range.start.0 += 1;
+                    range.end.0 += 1;
+                }
+            }
+        }
+        self.draw_paths.insert(new_index as usize, draw_path);
+        self.draw_path_handles.insert(new_index as usize, handle);
+        self.reindex_draw_paths(new_index as usize);
+
+        // The old range may have been left empty.
+        self.remove_empty_draw_path_ranges();
+        self.epoch.next();
+    }
+
+    // Removes the draw path at `index` from storage and from the display list, and shifts the
+    // indices of later paths down. The handle of the removed path is left dangling.
+    fn take_draw_path(&mut self, index: usize) -> DrawPath {
+        let draw_path = self.draw_paths.remove(index);
+        self.draw_path_handles.remove(index);
+        self.reindex_draw_paths(index);
+
+        for display_item in &mut self.display_list {
+            if let DisplayItem::DrawPaths(ref mut range) = *display_item {
+                if range.start.0 as usize > index {
+                    range.start.0 -= 1;
+                }
+                if range.end.0 as usize > index {
+                    range.end.0 -= 1;
+                }
+            }
+        }
+
+        draw_path
+    }
+
+    fn reindex_draw_paths(&mut self, first_index: usize) {
+        for index in first_index..self.draw_path_handles.len() {
+            let handle = self.draw_path_handles[index];
+            self.draw_path_indices[handle.0 as usize] = Some(index as u32);
+        }
+    }
+
+    fn remove_empty_draw_path_ranges(&mut self) {
+        self.display_list.retain(|display_item| {
+            match *display_item {
+                DisplayItem::DrawPaths(ref range) => range.start != range.end,
+                _ => true,
+            }
+        });
+    }
+
+    fn expect_draw_path_index(&self, handle: DrawPathHandle) -> usize {
+        match self.draw_path_indices[handle.0 as usize] {
+            Some(index) => index as usize,
+            None => panic!("Draw path {:?} was removed!", handle),
+        }
+    }
+
+    // Edits can shrink the scene, so bounds are recomputed from scratch rather than unioned.
+    fn recompute_bounds(&mut self) {
+        let draw_path_bounds = self.draw_paths.iter().map(|draw_path| draw_path.outline.bounds());
+        let clip_path_bounds = self.clip_paths.iter().map(|clip_path| clip_path.outline.bounds());
+        self.bounds = draw_path_bounds.chain(clip_path_bounds)
+                                      .fold(None, |bounds: Option<RectF>, path_bounds| {
+                                          Some(match bounds {
+                                              None => path_bounds,
+                                              Some(bounds) => bounds.union_rect(path_bounds),
+                                          })
+                                      })
+                                      .unwrap_or_default();
+    }
+
+    pub fn build_paint_info(&mut self,
+                            render_transform: Transform2F,
+                            renderer_level: RendererLevel)
+                            -> PaintInfo {
+        // The tile shaders look everything about a paint up by its ID, so each combination of
+        // paint, mask and layer in use gets a paint of its own.
+        for draw_path in &mut self.draw_paths {
+            let build_state = &mut draw_path.build_state;
+            let base_paint = &self.palette.paints[draw_path.paint.0 as usize];
+            let layer_transform = build_state.layer_transform.unwrap_or_default();
+            if draw_path.mask.is_none() && build_state.layer_pattern.is_none() &&
+                    (base_paint.is_color() || layer_transform.is_identity()) {
+                build_state.paint = None;
+                continue;
+            }
+
+            let mut paint = match build_state.layer_pattern {
+                None => base_paint.clone(),
+                Some(ref layer_pattern) => {
+                    let mut paint = Paint::from_pattern(layer_pattern.clone());
+                    paint.set_base_color(base_paint.base_color());
+                    paint
+                }
+            };
+            if let Some(mask_id) = draw_path.mask {
+                paint.set_mask(Some(self.masks[mask_id.0 as usize].clone()));
+            }
+            paint.apply_transform(&layer_transform);
+            build_state.paint = Some(self.palette.push_paint(&paint));
+        }
+
+        let mut paints_in_use = vec![false; self.palette.paints.len()];
+        for draw_path in &self.draw_paths {
+            paints_in_use[draw_path.paint().0 as usize] = true;
+        }
+        let render_target_samples = self.render_target_samples();
+        self.palette.build_paint_info(render_transform,
+                                      &paints_in_use,
+                                      &render_target_samples,
+                                      renderer_level)
+    }
+
+    // Returns each render target paired with the render targets that paths drawn to it sample,
+    // either through their paint or their mask.
+    fn render_target_samples(&self) -> Vec<(RenderTargetId, RenderTargetId)> {
+        let mut render_target_samples = vec![];
+        let mut render_target_stack = vec![];
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    render_target_stack.push(render_target_id)
+                }
+                DisplayItem::PopRenderTarget => {
+                    render_target_stack.pop();
+                }
+                DisplayItem::DrawPaths(ref draw_path_id_range) => {
+                    let dest_render_target_id = match render_target_stack.last() {
+                        None => continue,
+                        Some(&render_target_id) => render_target_id,
+                    };
+                    for draw_path_index in draw_path_id_range.start.0..draw_path_id_range.end.0 {
+                        let paint_id = self.draw_paths[draw_path_index as usize].paint();
+                        let paint = &self.palette.paints[paint_id.0 as usize];
+                        let mask_pattern = paint.mask().map(|mask| &mask.pattern);
+                        for pattern in paint.pattern().into_iter().chain(mask_pattern) {
+                            if let PatternSource::RenderTarget { id, .. } = *pattern.source() {
+                                render_target_samples.push((dest_render_target_id, id));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        render_target_samples.sort_by_key(|&(dest, src)| (dest.render_target, src.render_target));
+        render_target_samples.dedup();
+        render_target_samples
     }
 
     #[allow(clippy::trivially_copy_pass_by_ref)]
@@ -162,6 +777,29 @@ impl Scene {
         paint_id
     }
 
+    /// Sets how many bytes of image data stay uploaded between builds of this scene.
+    ///
+    /// Images are only uploaded again after being evicted. The default is 64 MB.
+    #[inline]
+    pub fn set_image_cache_budget(&mut self, new_budget: usize) {
+        self.palette.set_image_cache_budget(new_budget);
+    }
+
+    /// Moves images out of sparsely-used texture atlas pages at the next build, so that the pages
+    /// can be reused.
+    ///
+    /// The renderer copies the moved texels, so nothing is uploaded again.
+    #[inline]
+    pub fn compact_textures(&mut self) {
+        self.palette.compact_textures();
+    }
+
+    /// Returns how much of each texture page is in use, for memory debugging.
+    #[inline]
+    pub fn texture_page_usage(&self) -> Vec<TexturePageUsage> {
+        self.palette.texture_page_usage()
+    }
+
     #[inline]
     pub fn bounds(&self) -> RectF {
         self.bounds
@@ -184,10 +822,22 @@ impl Scene {
         self.epoch.next();
     }
 
+    // `layer_transform` moves the outline into the layer it's drawn into, if any, before the
+    // build transform is applied.
     pub(crate) fn apply_render_options(&self,
                                        original_outline: &Outline,
+                                       layer_transform: Transform2F,
                                        options: &PreparedBuildOptions)
                                        -> Outline {
+        let mut layer_outline;
+        let original_outline = if layer_transform.is_identity() {
+            original_outline
+        } else {
+            layer_outline = original_outline.clone();
+            layer_outline.transform(&layer_transform);
+            &layer_outline
+        };
+
         let mut outline;
         match options.transform {
             PreparedRenderTransform::Perspective {
@@ -217,7 +867,7 @@ impl Scene {
                         PreparedRenderTransform::Perspective { .. } => unreachable!(),
                     };
                     if options.subpixel_aa_enabled {
-                        transform *= Transform2F::from_scale(vec2f(3.0, 1.0))
+                        transform *= Transform2F::from_scale(options.subpixel_scale())
                     }
                     outline.transform(&transform);
                 }
@@ -234,7 +884,7 @@ impl Scene {
     #[inline]
     pub(crate) fn effective_view_box(&self, render_options: &PreparedBuildOptions) -> RectF {
         if render_options.subpixel_aa_enabled {
-            self.view_box * vec2f(3.0, 1.0)
+            self.view_box * render_options.subpixel_scale()
         } else {
             self.view_box
         }
@@ -246,8 +896,29 @@ impl Scene {
                             sink: &'b mut SceneSink<'a>,
                             executor: &E)
                             where E: Executor {
+        debug_assert!(self.layers.is_empty(), "Layers must be popped before building!");
         let prepared_options = options.prepare(self.bounds);
-        SceneBuilder::new(self, &prepared_options, sink).build(executor)
+        let (paint_count, clip_path_count) = (self.palette.paints.len(), self.clip_paths.len());
+        self.place_layers(&prepared_options);
+        SceneBuilder::new(self, &prepared_options, sink).build(executor);
+
+        // Paints and clip paths made for this build refer to its layer placement.
+        self.palette.truncate_paints(paint_count);
+        self.clip_paths.truncate(clip_path_count);
+        self.layer_clip_paths = 0..0;
+    }
+
+    // Clip paths made for paths in layers are placed anew on every build, so their tiling isn't
+    // cached.
+    #[inline]
+    pub(crate) fn is_layer_clip_path(&self, clip_path_id: ClipPathId) -> bool {
+        self.layer_clip_paths.contains(&clip_path_id.0)
+    }
+
+    // Returns true if `push_layer()` has been called more times than `pop_layer()`.
+    #[inline]
+    pub(crate) fn has_open_layers(&self) -> bool {
+        !self.layers.is_empty()
     }
 
     #[inline]
@@ -275,6 +946,16 @@ impl Scene {
         &self.clip_paths[clip_path_id.0 as usize]
     }
 
+    #[inline]
+    pub fn masks(&self) -> &[Mask] {
+        &self.masks
+    }
+
+    #[inline]
+    pub fn get_mask(&self, mask_id: MaskId) -> &Mask {
+        &self.masks[mask_id.0 as usize]
+    }
+
     #[inline]
     pub fn palette(&self) -> &Palette {
         &self.palette
@@ -295,6 +976,7 @@ pub struct SceneSink<'a> {
     pub(crate) listener: RenderCommandListener<'a>,
     pub(crate) renderer_level: RendererLevel,
     pub(crate) last_scene: Option<LastSceneInfo>,
+    pub(crate) path_cache: PathCache,
 }
 
 pub(crate) struct LastSceneInfo {
@@ -302,6 +984,9 @@ pub(crate) struct LastSceneInfo {
     pub(crate) scene_epoch: SceneEpoch,
     pub(crate) draw_segment_ranges: Vec<Range<u32>>,
     pub(crate) clip_segment_ranges: Vec<Range<u32>>,
+    // The transform to apply to each draw path's segments. This is only something other than
+    // the identity for symbol instances.
+    pub(crate) draw_segment_transforms: Vec<Transform2F>,
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -335,7 +1020,7 @@ impl<'a> SceneSink<'a> {
     #[inline]
     pub fn new(listener: RenderCommandListener<'a>, renderer_level: RendererLevel)
                -> SceneSink<'a> {
-        SceneSink { listener, renderer_level, last_scene: None }
+        SceneSink { listener, renderer_level, last_scene: None, path_cache: PathCache::new() }
     }
 }
 
@@ -344,9 +1029,29 @@ pub struct DrawPath {
     pub outline: Outline,
     pub paint: PaintId,
     pub clip_path: Option<ClipPathId>,
+    pub mask: Option<MaskId>,
     pub fill_rule: FillRule,
     pub blend_mode: BlendMode,
     pub name: String,
+    pub(crate) instance: Option<SymbolInstance>,
+    // If this path composites a layer, the layer's render target.
+    pub(crate) layer: Option<RenderTargetId>,
+    pub(crate) build_state: DrawPathBuildState,
+}
+
+// How a draw path is drawn, which depends on the layer it's in and the build options. Worked out
+// anew whenever the scene is built.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct DrawPathBuildState {
+    // `paint` with the mask, layer pattern and layer transform applied.
+    paint: Option<PaintId>,
+    // A copy of `clip_path` with the layer transform applied.
+    clip_path: Option<ClipPathId>,
+    // Moves the path from scene space to the origin of the render target of the layer it's in.
+    layer_transform: Option<Transform2F>,
+    // For a path that composites a layer, a pattern mapping the layer's render target to where
+    // its contents were drawn.
+    layer_pattern: Option<Pattern>,
 }
 
 #[derive(Clone, Debug)]
@@ -362,6 +1067,48 @@ pub struct DrawPathId(pub u32);
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct ClipPathId(pub u32);
 
+/// An image or render target whose texels scale the coverage of a draw path or layer, like a soft
+/// clip.
+///
+/// The pattern's transform places the mask in the coordinate space of the path or layer it's
+/// applied to, and the pattern's repeat and smoothing settings apply. Pattern filters are ignored.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Mask {
+    pub pattern: Pattern,
+    pub mode: MaskMode,
+}
+
+/// Which part of a mask's texels scales coverage.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MaskMode {
+    /// The alpha channel.
+    Alpha,
+    /// The luminance of the color, multiplied by alpha, as in SVG luminance masks.
+    Luminance,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct MaskId(pub u32);
+
+/// A stable reference to a draw path in a scene.
+///
+/// Unlike a `DrawPathId`, which is the path's current position in the scene, a handle continues
+/// to refer to the same path when other paths are removed or reordered.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DrawPathHandle(pub u32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SymbolId(pub u32);
+
+/// Where a draw path created with `Scene::instantiate_symbol()` came from.
+///
+/// The path's outline is always the symbol's outline transformed by `transform`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SymbolInstance {
+    pub symbol: SymbolId,
+    pub transform: Transform2F,
+}
+
 /// Either a draw path ID or a clip path ID, depending on context.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct PathId(pub u32);
@@ -372,6 +1119,37 @@ pub struct RenderTarget {
     name: String,
 }
 
+/// How a layer pushed with `Scene::push_layer()` is composited.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayerOptions {
+    /// The opacity that the layer as a whole is composited with, from 0.0 to 1.0.
+    pub opacity: f32,
+    /// The blend mode that the layer is composited with. `BlendMode::DestOut` makes a knockout
+    /// layer, which erases the content beneath it.
+    pub blend_mode: BlendMode,
+    /// A clip path, in the coordinate space of the enclosing render target, applied to the
+    /// layer as a whole.
+    pub clip: Option<ClipPathId>,
+    /// A mask, in the coordinate space of the enclosing render target, applied to the layer as
+    /// a whole.
+    pub mask: Option<MaskId>,
+    /// Whether the layer's contents are always drawn into their own render target.
+    ///
+    /// If false, a layer whose options make compositing it a no-op (full opacity, source-over
+    /// blending, no clip and no mask) is drawn directly into the enclosing render target
+    /// instead. Either way, layers are drawn onto a transparent backdrop.
+    pub isolated: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Layer {
+    options: LayerOptions,
+    // `None` if the layer was flattened into the enclosing render target.
+    render_target_id: Option<RenderTargetId>,
+    // The length of the display list when the layer was pushed.
+    first_display_item_index: usize,
+}
+
 /// Drawing commands.
 #[derive(Clone, Debug)]
 pub enum DisplayItem {
@@ -392,9 +1170,13 @@ impl DrawPath {
             outline,
             paint,
             clip_path: None,
+            mask: None,
             fill_rule: FillRule::Winding,
             blend_mode: BlendMode::SrcOver,
             name: String::new(),
+            instance: None,
+            layer: None,
+            build_state: DrawPathBuildState::default(),
         }
     }
 
@@ -403,9 +1185,16 @@ impl DrawPath {
         &self.outline
     }
 
+    /// Returns the symbol this path draws, if it was created with `Scene::instantiate_symbol()`.
+    #[inline]
+    pub fn instance(&self) -> Option<SymbolInstance> {
+        self.instance
+    }
+
+    // Only valid while the scene is being built, since that's when layers are placed.
     #[inline]
     pub(crate) fn clip_path(&self) -> Option<ClipPathId> {
-        self.clip_path
+        self.build_state.clip_path.or(self.clip_path)
     }
 
     #[inline]
@@ -413,9 +1202,21 @@ impl DrawPath {
         self.clip_path = new_clip_path
     }
 
+    #[inline]
+    pub fn set_mask(&mut self, new_mask: Option<MaskId>) {
+        self.mask = new_mask
+    }
+
+    // Only valid while the scene is being built, since that's when masks and layers are applied
+    // to paints.
     #[inline]
     pub(crate) fn paint(&self) -> PaintId {
-        self.paint
+        self.build_state.paint.unwrap_or(self.paint)
+    }
+
+    #[inline]
+    pub(crate) fn layer_transform(&self) -> Transform2F {
+        self.build_state.layer_transform.unwrap_or_default()
     }
 
     #[inline]
@@ -471,6 +1272,18 @@ impl ClipPath {
     }
 }
 
+impl Mask {
+    #[inline]
+    pub fn new(pattern: Pattern, mode: MaskMode) -> Mask {
+        Mask { pattern, mode }
+    }
+
+    #[inline]
+    pub fn apply_transform(&mut self, transform: &Transform2F) {
+        self.pattern.apply_transform(*transform);
+    }
+}
+
 impl RenderTarget {
     #[inline]
     pub fn new(size: Vector2I, name: String) -> RenderTarget {
@@ -481,6 +1294,32 @@ impl RenderTarget {
     pub fn size(&self) -> Vector2I {
         self.size
     }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Default for LayerOptions {
+    #[inline]
+    fn default() -> LayerOptions {
+        LayerOptions {
+            opacity: 1.0,
+            blend_mode: BlendMode::SrcOver,
+            clip: None,
+            mask: None,
+            isolated: true,
+        }
+    }
+}
+
+impl LayerOptions {
+    #[inline]
+    fn is_passthrough(&self) -> bool {
+        self.opacity >= 1.0 && self.blend_mode == BlendMode::SrcOver && self.clip.is_none() &&
+            self.mask.is_none()
+    }
 }
 
 impl DrawPathId {
@@ -508,3 +1347,319 @@ impl PathId {
         DrawPathId(self.0)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ClipPath, DisplayItem, DrawPath, DrawPathHandle, DrawPathId, LayerOptions};
+    use super::{Mask, MaskMode, RenderTarget, Scene, SceneSink};
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu::options::RendererLevel;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::{BuildOptions, RenderCommandListener, RenderTransform};
+    use crate::paint::{Paint, PaintId};
+    use crate::profile::{ProfileCategory, Profiler};
+    use pathfinder_color::ColorU;
+    use pathfinder_content::outline::Outline;
+    use pathfinder_content::effects::{BlurDirection, PatternFilter};
+    use pathfinder_content::pattern::Pattern;
+    use pathfinder_content::render_target::RenderTargetId;
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use std::sync::{Arc, Mutex};
+
+    fn push_square(scene: &mut Scene, paint: PaintId, origin: f32) -> DrawPathHandle {
+        let outline = Outline::from_rect(RectF::new(vec2f(origin, origin), vec2f(1.0, 1.0)));
+        scene.push_draw_path(DrawPath::new(outline, paint))
+    }
+
+    fn draw_path_ranges(scene: &Scene) -> Vec<(u32, u32)> {
+        scene.display_list().iter().filter_map(|display_item| {
+            match *display_item {
+                DisplayItem::DrawPaths(ref range) => Some((range.start.0, range.end.0)),
+                _ => None,
+            }
+        }).collect()
+    }
+
+    #[test]
+    fn test_remove_and_reorder() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let a = push_square(&mut scene, paint, 0.0);
+        scene.push_render_target(RenderTarget::new(vec2i(4, 4), String::new()));
+        let b = push_square(&mut scene, paint, 1.0);
+        scene.pop_render_target();
+        let c = push_square(&mut scene, paint, 2.0);
+        let d = push_square(&mut scene, paint, 3.0);
+        assert_eq!(draw_path_ranges(&scene), vec![(0, 1), (1, 2), (2, 4)]);
+
+        // Move `d` into the render target, below `b`.
+        let epoch = scene.epoch();
+        scene.move_draw_path_below(d, b);
+        assert_ne!(scene.epoch(), epoch);
+        assert_eq!(draw_path_ranges(&scene), vec![(0, 1), (1, 3), (3, 4)]);
+        assert_eq!(scene.draw_path_id(d), Some(DrawPathId(1)));
+        assert_eq!(scene.draw_path_id(b), Some(DrawPathId(2)));
+        assert_eq!(scene.draw_path_id(c), Some(DrawPathId(3)));
+
+        // Removing `a` leaves an empty range, which is dropped.
+        assert!(scene.remove_draw_path(a).is_some());
+        assert!(scene.remove_draw_path(a).is_none());
+        assert_eq!(draw_path_ranges(&scene), vec![(0, 2), (2, 3)]);
+        assert_eq!(scene.bounds(), RectF::new(vec2f(1.0, 1.0), vec2f(3.0, 3.0)));
+
+        // Move `c` back above `b`, emptying the last range.
+        scene.move_draw_path_above(c, b);
+        assert_eq!(draw_path_ranges(&scene), vec![(0, 3)]);
+        assert_eq!(scene.draw_path_id(c), Some(DrawPathId(2)));
+
+        scene.transform_draw_path(c, &Transform2F::from_translation(vec2f(4.0, 0.0)));
+        assert_eq!(scene.bounds(), RectF::new(vec2f(1.0, 1.0), vec2f(6.0, 3.0)));
+    }
+
+    #[test]
+    fn test_layers() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let a = push_square(&mut scene, paint, 0.0);
+
+        // A layer that composites as a no-op is flattened unless it's isolated.
+        scene.push_layer(LayerOptions { isolated: false, ..LayerOptions::default() });
+        push_square(&mut scene, paint, 1.0);
+        scene.pop_layer();
+        assert_eq!(scene.display_list().len(), 1);
+
+        scene.push_layer(LayerOptions { opacity: 0.5, ..LayerOptions::default() });
+        let b = push_square(&mut scene, paint, 10.5);
+        push_square(&mut scene, paint, 12.0);
+        scene.pop_layer();
+
+        // The layer's render target is just big enough for its contents, which stay where they
+        // are, and it's drawn back where the contents were.
+        let b_outline = RectF::new(vec2f(10.5, 10.5), vec2f(1.0, 1.0));
+        let render_targets: Vec<_> = scene.palette.render_targets().collect();
+        assert_eq!(render_targets.len(), 1);
+        assert_eq!(render_targets[0].size(), vec2i(3, 3));
+        assert_eq!(scene.draw_path(b).unwrap().outline().bounds(), b_outline);
+        assert_eq!(scene.draw_path(a).unwrap().outline().bounds(),
+                   RectF::new(vec2f(0.0, 0.0), vec2f(1.0, 1.0)));
+        assert_eq!(draw_path_ranges(&scene), vec![(0, 2), (2, 4), (4, 5)]);
+        let composite_path = scene.get_draw_path(DrawPathId(4));
+        assert_eq!(composite_path.outline().bounds(),
+                   RectF::new(vec2f(10.0, 10.0), vec2f(3.0, 3.0)));
+        assert_eq!(scene.palette.paints[composite_path.paint.0 as usize].base_color().a, 128);
+
+        // Building at twice the size places the layer in device space, without touching the
+        // contents or keeping the paints made for the build.
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint_count = scene.palette.paints.len();
+        let options = BuildOptions {
+            transform: RenderTransform::Transform2D(Transform2F::from_scale(2.0)),
+            ..BuildOptions::default()
+        };
+        build(&mut scene, options);
+        assert_eq!(scene.palette.render_targets().next().unwrap().size(), vec2i(5, 5));
+        assert_eq!(scene.draw_path(b).unwrap().outline().bounds(), b_outline);
+        assert_eq!(scene.draw_path(b).unwrap().layer_transform(),
+                   Transform2F::from_translation(vec2f(-10.5, -10.5)));
+        assert!(scene.draw_path(a).unwrap().layer_transform().is_identity());
+        assert_eq!(scene.get_draw_path(DrawPathId(4)).outline().bounds(),
+                   RectF::new(vec2f(10.5, 10.5), vec2f(2.5, 2.5)));
+        assert_eq!(scene.palette.paints.len(), paint_count);
+    }
+
+    #[test]
+    fn test_clipped_paths_in_layers() {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            listener_commands.lock().unwrap().push(command)
+        }));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+
+        // A clipped path inside a translucent layer, as in an SVG group with opacity.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 64.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let clip_outline = Outline::from_rect(RectF::new(vec2f(4.25, 4.25), vec2f(20.0, 20.0)));
+        let clip_path = scene.push_clip_path(ClipPath::new(clip_outline));
+        scene.push_layer(LayerOptions { opacity: 0.5, ..LayerOptions::default() });
+        let outline = Outline::from_rect(RectF::new(vec2f(8.5, 8.5), vec2f(30.0, 30.0)));
+        let mut draw_path = DrawPath::new(outline, paint);
+        draw_path.set_clip_path(Some(clip_path));
+        scene.push_draw_path(draw_path);
+        scene.pop_layer();
+
+        // The layer's clip path is tiled anew on every build, so the path it clips must be too.
+        let fill_count = |commands: &Mutex<Vec<RenderCommand>>| {
+            commands.lock().unwrap().drain(..).map(|command| {
+                match command {
+                    RenderCommand::AddFillsD3D9(fills) => fills.len(),
+                    _ => 0,
+                }
+            }).sum::<usize>()
+        };
+        scene.build(BuildOptions::default(), &mut sink, &SequentialExecutor);
+        let first_fill_count = fill_count(&commands);
+        assert!(first_fill_count > 0);
+        scene.build(BuildOptions::default(), &mut sink, &SequentialExecutor);
+        assert_eq!(fill_count(&commands), first_fill_count);
+    }
+
+    #[test]
+    fn test_masks() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let render_target = scene.push_render_target(RenderTarget::new(vec2i(4, 4),
+                                                                       String::new()));
+        push_square(&mut scene, paint, 0.0);
+        scene.pop_render_target();
+        let pattern = Pattern::from_render_target(render_target, vec2i(4, 4));
+        let mask = scene.push_mask(Mask::new(pattern, MaskMode::Luminance));
+
+        let mut draw_path = DrawPath::new(Outline::from_rect(RectF::new(vec2f(0.0, 0.0),
+                                                                        vec2f(4.0, 4.0))),
+                                          paint);
+        draw_path.set_mask(Some(mask));
+        let masked = scene.push_draw_path(draw_path);
+
+        // The masked path draws with a paint of its own that samples the render target, and
+        // rebuilding reuses that paint.
+        let paint_count = scene.palette.paints.len();
+        let paint_info = scene.build_paint_info(Transform2F::default(), RendererLevel::D3D9);
+        let masked_paint = scene.draw_path(masked).unwrap().paint();
+        assert_ne!(masked_paint, paint);
+        assert!(paint_info.paint_metadata[paint.0 as usize].mask_texture_metadata.is_none());
+        let mask_metadata = paint_info.paint_metadata[masked_paint.0 as usize]
+                                      .mask_texture_metadata
+                                      .as_ref()
+                                      .unwrap();
+        assert_eq!(mask_metadata.mode, MaskMode::Luminance);
+        assert_eq!(mask_metadata.location,
+                   paint_info.render_target_metadata[render_target.render_target as usize]
+                             .location);
+        scene.build_paint_info(Transform2F::default(), RendererLevel::D3D9);
+        assert_eq!(scene.palette.paints.len(), paint_count + 1);
+
+        // A layer with a mask can't be flattened, even if it's otherwise a no-op.
+        let display_item_count = scene.display_list().len();
+        scene.push_layer(LayerOptions {
+            isolated: false,
+            mask: Some(mask),
+            ..LayerOptions::default()
+        });
+        push_square(&mut scene, paint, 8.0);
+        scene.pop_layer();
+        assert_eq!(scene.palette.render_targets().count(), 2);
+        assert!(scene.display_list().len() > display_item_count + 1);
+    }
+
+    #[test]
+    fn test_sampled_render_targets_get_separate_pages() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+
+        // Draw a shape, then blur it into a second render target, as canvas shadows do.
+        let shape = scene.push_render_target(RenderTarget::new(vec2i(16, 16), String::new()));
+        push_square(&mut scene, paint, 0.0);
+        scene.pop_render_target();
+        let blurred = scene.push_render_target(RenderTarget::new(vec2i(16, 16), String::new()));
+        let mut pattern = Pattern::from_render_target(shape, vec2i(16, 16));
+        pattern.set_filter(Some(PatternFilter::Blur { direction: BlurDirection::X, sigma: 2.0 }));
+        let blur_paint = scene.push_paint(&Paint::from_pattern(pattern));
+        push_square(&mut scene, blur_paint, 0.0);
+        scene.pop_render_target();
+        let unrelated = scene.push_render_target(RenderTarget::new(vec2i(16, 16), String::new()));
+        push_square(&mut scene, paint, 0.0);
+        scene.pop_render_target();
+
+        let paint_info = scene.build_paint_info(Transform2F::default(), RendererLevel::D3D9);
+        let page = |render_target: RenderTargetId| {
+            paint_info.render_target_metadata[render_target.render_target as usize].location.page
+        };
+        assert_ne!(page(shape), page(blurred));
+        assert_eq!(page(shape), page(unrelated));
+    }
+
+    // Builds the scene for D3D9 with a fresh sink and returns the render commands.
+    fn build(scene: &mut Scene, options: BuildOptions) -> Vec<RenderCommand> {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let listener_commands = commands.clone();
+        let listener = RenderCommandListener::new(Box::new(move |command| {
+            listener_commands.lock().unwrap().push(command)
+        }));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+        scene.build(options, &mut sink, &SequentialExecutor);
+        let commands = commands.lock().unwrap().drain(..).collect();
+        commands
+    }
+
+    // Returns the number of fills and the number of drawn alpha tiles in a D3D9 build.
+    fn count_fills_and_alpha_tiles(scene: &mut Scene) -> (usize, usize) {
+        let (mut fill_count, mut alpha_tile_count) = (0, 0);
+        for command in build(scene, BuildOptions::default()) {
+            match command {
+                RenderCommand::AddFillsD3D9(fills) => fill_count += fills.len(),
+                RenderCommand::DrawTilesD3D9(batch) => {
+                    alpha_tile_count += batch.tiles.iter().filter(|tile| {
+                        tile.alpha_tile_id.is_valid()
+                    }).count();
+                }
+                _ => {}
+            }
+        }
+        (fill_count, alpha_tile_count)
+    }
+
+    #[test]
+    fn test_symbol_instances_share_masks() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(256.0, 256.0)));
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        let symbol = scene.define_symbol(Outline::from_rect(RectF::new(vec2f(0.5, 0.5),
+                                                                       vec2f(20.0, 10.0))));
+        scene.push_instance(symbol, Transform2F::from_translation(vec2f(20.0, 20.0)), paint);
+        let (fill_count, alpha_tile_count) = count_fills_and_alpha_tiles(&mut scene);
+        assert!(fill_count > 0);
+
+        // An instance two tiles over reuses the masks of the first.
+        scene.push_instance(symbol, Transform2F::from_translation(vec2f(52.0, 36.0)), paint);
+        assert_eq!(count_fills_and_alpha_tiles(&mut scene),
+                   (fill_count, alpha_tile_count * 2));
+
+        // An instance at a different subtile position needs its own.
+        scene.push_instance(symbol, Transform2F::from_translation(vec2f(100.25, 20.0)), paint);
+        assert!(count_fills_and_alpha_tiles(&mut scene).0 > fill_count);
+    }
+
+    #[test]
+    fn test_build_profile() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint::from_color(ColorU::black()));
+        push_square(&mut scene, paint, 0.0);
+        push_square(&mut scene, paint, 2.0);
+
+        let profiler = Profiler::new();
+        let listener = RenderCommandListener::new(Box::new(|_| {}));
+        let mut sink = SceneSink::new(listener, RendererLevel::D3D9);
+        let options = BuildOptions { profiler: Some(profiler.clone()), ..BuildOptions::default() };
+        scene.build(options, &mut sink, &SequentialExecutor);
+
+        let spans = profiler.spans();
+        assert!(spans.iter().all(|span| {
+            span.category == ProfileCategory::Build && span.thread_index == 0
+        }));
+        let names: Vec<_> = spans.iter().map(|span| span.name).collect();
+        assert_eq!(names, vec![
+            "PaintInfo",
+            "TileClipPaths",
+            "TileDrawPath",
+            "TileDrawPath",
+            "TileDrawPaths",
+            "BuildBatches",
+            "Build",
+        ]);
+        assert_eq!(spans[3].index, Some(1));
+    }
+}
@@ -0,0 +1,1013 @@
+// pathfinder/renderer/src/serialization.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A compact binary encoding of scenes, for caching and for transfer between processes.
+//!
+//! All values are little-endian. The encoding starts with a magic number and a version, and
+//! decoding rejects versions other than `SCENE_FORMAT_VERSION`.
+
+use crate::paint::{Paint, PaintCompositeOp, PaintContents};
+use crate::scene::{ClipPath, ClipPathId, DisplayItem, DrawPath, DrawPathId, RenderTarget, Scene};
+use crate::scene::{Mask, MaskId, MaskMode, SymbolId, SymbolInstance};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_color::{ColorF, ColorSpace, ColorU};
+use pathfinder_content::effects::{BlendMode, BlurDirection, DefringingKernel, PatternFilter};
+use pathfinder_content::fill::FillRule;
+use pathfinder_content::gradient::{ColorStop, Gradient, GradientGeometry};
+use pathfinder_content::outline::{Contour, Outline, PointFlags};
+use pathfinder_content::pattern::{AlphaMode, Image, ImageFormat, Pattern, PatternSource};
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectF;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
+use pathfinder_simd::default::{F32x2, F32x4};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+/// The version of the encoding produced by `Scene::serialize()`.
+///
+/// Bump this whenever the encoding changes.
+pub const SCENE_FORMAT_VERSION: u32 = 8;
+
+const SCENE_MAGIC: [u8; 4] = *b"PFSC";
+
+// Counts are read from untrusted input, which may end long before they say it does. Reserve room
+// for at most this many elements up front, and let the rest be allocated as they're actually read.
+const MAX_RESERVED_ELEMENTS: usize = 4096;
+
+// The index of each blend mode in this table is its encoding.
+static BLEND_MODES: [BlendMode; 27] = [
+    BlendMode::Clear,
+    BlendMode::Copy,
+    BlendMode::SrcIn,
+    BlendMode::SrcOut,
+    BlendMode::SrcOver,
+    BlendMode::SrcAtop,
+    BlendMode::DestIn,
+    BlendMode::DestOut,
+    BlendMode::DestOver,
+    BlendMode::DestAtop,
+    BlendMode::Xor,
+    BlendMode::Lighter,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::HardLight,
+    BlendMode::Overlay,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+    BlendMode::Hue,
+    BlendMode::Saturation,
+    BlendMode::Color,
+    BlendMode::Luminosity,
+];
+
+/// An error that occurred while decoding a scene.
+#[derive(Debug)]
+pub enum SceneDeserializeError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The data doesn't start with the scene magic number.
+    BadMagic,
+    /// The data was written by an incompatible version of Pathfinder.
+    UnsupportedVersion(u32),
+    /// The data is corrupt.
+    InvalidData(&'static str),
+}
+
+impl Scene {
+    /// Writes this scene to `writer` in the binary scene format.
+    ///
+    /// Fails with `io::ErrorKind::InvalidInput` if any layers haven't been popped, since their
+    /// render targets aren't sized until then.
+    pub fn serialize<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
+        if self.has_open_layers() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Scene has unpopped layers"));
+        }
+
+        writer.write_all(&SCENE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(SCENE_FORMAT_VERSION)?;
+
+        write_rect(writer, self.view_box)?;
+        write_rect(writer, self.bounds)?;
+
+        let render_targets: Vec<&RenderTarget> = self.palette.render_targets().collect();
+        write_len(writer, render_targets.len())?;
+        for render_target in render_targets {
+            write_vector_2i(writer, render_target.size())?;
+            write_string(writer, render_target.name())?;
+        }
+
+        write_len(writer, self.palette.paints.len())?;
+        for paint in &self.palette.paints {
+            write_paint(writer, paint)?;
+        }
+
+        write_len(writer, self.clip_paths.len())?;
+        for clip_path in &self.clip_paths {
+            write_outline(writer, &clip_path.outline)?;
+            write_fill_rule(writer, clip_path.fill_rule)?;
+            write_string(writer, &clip_path.name)?;
+        }
+
+        write_len(writer, self.masks.len())?;
+        for mask in &self.masks {
+            write_mask(writer, mask)?;
+        }
+
+        write_len(writer, self.symbols.len())?;
+        for symbol in &self.symbols {
+            write_outline(writer, symbol)?;
+        }
+
+        write_len(writer, self.draw_paths.len())?;
+        for draw_path in &self.draw_paths {
+            write_outline(writer, &draw_path.outline)?;
+            writer.write_u16::<LittleEndian>(draw_path.paint.0)?;
+            match draw_path.clip_path {
+                None => writer.write_u8(0)?,
+                Some(clip_path_id) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(clip_path_id.0)?;
+                }
+            }
+            match draw_path.mask {
+                None => writer.write_u8(0)?,
+                Some(mask_id) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(mask_id.0)?;
+                }
+            }
+            write_fill_rule(writer, draw_path.fill_rule)?;
+            write_blend_mode(writer, draw_path.blend_mode)?;
+            write_string(writer, &draw_path.name)?;
+            match draw_path.instance {
+                None => writer.write_u8(0)?,
+                Some(ref instance) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(instance.symbol.0)?;
+                    write_transform(writer, &instance.transform)?;
+                }
+            }
+            match draw_path.layer {
+                None => writer.write_u8(0)?,
+                Some(render_target_id) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(render_target_id.render_target)?;
+                }
+            }
+        }
+
+        write_len(writer, self.display_list.len())?;
+        for display_item in &self.display_list {
+            match *display_item {
+                DisplayItem::DrawPaths(ref range) => {
+                    writer.write_u8(0)?;
+                    writer.write_u32::<LittleEndian>(range.start.0)?;
+                    writer.write_u32::<LittleEndian>(range.end.0)?;
+                }
+                DisplayItem::PushRenderTarget(render_target_id) => {
+                    writer.write_u8(1)?;
+                    writer.write_u32::<LittleEndian>(render_target_id.render_target)?;
+                }
+                DisplayItem::PopRenderTarget => writer.write_u8(2)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a scene written by `serialize()`.
+    ///
+    /// The result is a new scene with a fresh ID; render target references are remapped to it.
+    pub fn deserialize<R>(reader: &mut R) -> Result<Scene, SceneDeserializeError> where R: Read {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != SCENE_MAGIC {
+            return Err(SceneDeserializeError::BadMagic);
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != SCENE_FORMAT_VERSION {
+            return Err(SceneDeserializeError::UnsupportedVersion(version));
+        }
+
+        let mut scene = Scene::new();
+        let scene_id = scene.id().0;
+        scene.view_box = read_rect(reader)?;
+        let bounds = read_rect(reader)?;
+
+        let render_target_count = read_len(reader)?;
+        for _ in 0..render_target_count {
+            let size = read_vector_2i(reader)?;
+            let name = read_string(reader)?;
+            scene.palette.push_render_target(RenderTarget::new(size, name));
+        }
+
+        let paint_count = read_len(reader)?;
+        let mut paint_mapping = Vec::with_capacity(paint_count.min(MAX_RESERVED_ELEMENTS));
+        for _ in 0..paint_count {
+            let paint = read_paint(reader, scene_id, render_target_count)?;
+            paint_mapping.push(scene.palette.push_paint(&paint));
+        }
+
+        let clip_path_count = read_len(reader)?;
+        for _ in 0..clip_path_count {
+            let mut clip_path = ClipPath::new(read_outline(reader)?);
+            clip_path.fill_rule = read_fill_rule(reader)?;
+            clip_path.name = read_string(reader)?;
+            scene.clip_paths.push(clip_path);
+        }
+
+        let mask_count = read_len(reader)?;
+        for _ in 0..mask_count {
+            scene.masks.push(read_mask(reader, scene_id, render_target_count)?);
+        }
+
+        let symbol_count = read_len(reader)?;
+        for _ in 0..symbol_count {
+            scene.symbols.push(read_outline(reader)?);
+        }
+
+        let draw_path_count = read_len(reader)?;
+        for _ in 0..draw_path_count {
+            let outline = read_outline(reader)?;
+            let paint_index = reader.read_u16::<LittleEndian>()? as usize;
+            let paint = *paint_mapping.get(paint_index)
+                                      .ok_or(SceneDeserializeError::InvalidData("paint ID"))?;
+            let mut draw_path = DrawPath::new(outline, paint);
+            if reader.read_u8()? != 0 {
+                let clip_path_id = reader.read_u32::<LittleEndian>()?;
+                if clip_path_id as usize >= clip_path_count {
+                    return Err(SceneDeserializeError::InvalidData("clip path ID"));
+                }
+                draw_path.clip_path = Some(ClipPathId(clip_path_id));
+            }
+            if reader.read_u8()? != 0 {
+                let mask_id = reader.read_u32::<LittleEndian>()?;
+                if mask_id as usize >= mask_count {
+                    return Err(SceneDeserializeError::InvalidData("mask ID"));
+                }
+                draw_path.mask = Some(MaskId(mask_id));
+            }
+            draw_path.fill_rule = read_fill_rule(reader)?;
+            draw_path.blend_mode = read_blend_mode(reader)?;
+            draw_path.name = read_string(reader)?;
+            if reader.read_u8()? != 0 {
+                let symbol = reader.read_u32::<LittleEndian>()?;
+                if symbol as usize >= symbol_count {
+                    return Err(SceneDeserializeError::InvalidData("symbol ID"));
+                }
+                let transform = read_transform(reader)?;
+                draw_path.instance = Some(SymbolInstance { symbol: SymbolId(symbol), transform });
+            }
+            if reader.read_u8()? != 0 {
+                let render_target = reader.read_u32::<LittleEndian>()?;
+                if render_target as usize >= render_target_count {
+                    return Err(SceneDeserializeError::InvalidData("layer render target ID"));
+                }
+                draw_path.layer = Some(RenderTargetId { scene: scene_id, render_target });
+            }
+            scene.store_draw_path(draw_path);
+        }
+
+        let display_item_count = read_len(reader)?;
+        for _ in 0..display_item_count {
+            let display_item = match reader.read_u8()? {
+                0 => {
+                    let start = reader.read_u32::<LittleEndian>()?;
+                    let end = reader.read_u32::<LittleEndian>()?;
+                    if start > end || end as usize > draw_path_count {
+                        return Err(SceneDeserializeError::InvalidData("draw path range"));
+                    }
+                    DisplayItem::DrawPaths(DrawPathId(start)..DrawPathId(end))
+                }
+                1 => {
+                    let render_target = reader.read_u32::<LittleEndian>()?;
+                    if render_target as usize >= render_target_count {
+                        return Err(SceneDeserializeError::InvalidData("render target ID"));
+                    }
+                    DisplayItem::PushRenderTarget(RenderTargetId { scene: scene_id, render_target })
+                }
+                2 => DisplayItem::PopRenderTarget,
+                _ => return Err(SceneDeserializeError::InvalidData("display item")),
+            };
+            scene.display_list.push(display_item);
+        }
+
+        scene.set_bounds(bounds);
+        Ok(scene)
+    }
+}
+
+fn write_paint<W>(writer: &mut W, paint: &Paint) -> io::Result<()> where W: Write {
+    write_color_u(writer, paint.base_color())?;
+    match paint.mask() {
+        None => writer.write_u8(0)?,
+        Some(mask) => {
+            writer.write_u8(1)?;
+            write_mask(writer, mask)?;
+        }
+    }
+
+    let overlay = match *paint.overlay() {
+        None => return writer.write_u8(0),
+        Some(ref overlay) => overlay,
+    };
+
+    writer.write_u8(1)?;
+    write_composite_op(writer, overlay.composite_op())?;
+
+    match *overlay.contents() {
+        PaintContents::Gradient(ref gradient) => {
+            writer.write_u8(0)?;
+            write_gradient(writer, gradient)
+        }
+        PaintContents::Pattern(ref pattern) => {
+            writer.write_u8(1)?;
+            write_pattern(writer, pattern)
+        }
+    }
+}
+
+fn read_paint<R>(reader: &mut R, scene_id: u32, render_target_count: usize)
+                 -> Result<Paint, SceneDeserializeError> where R: Read {
+    let base_color = read_color_u(reader)?;
+    let mask = match reader.read_u8()? {
+        0 => None,
+        _ => Some(read_mask(reader, scene_id, render_target_count)?),
+    };
+    if reader.read_u8()? == 0 {
+        let mut paint = Paint::from_color(base_color);
+        paint.set_mask(mask);
+        return Ok(paint);
+    }
+
+    let composite_op = read_composite_op(reader)?;
+
+    let mut paint = match reader.read_u8()? {
+        0 => Paint::from_gradient(read_gradient(reader)?),
+        1 => Paint::from_pattern(read_pattern(reader, scene_id, render_target_count)?),
+        _ => return Err(SceneDeserializeError::InvalidData("paint contents")),
+    };
+    paint.set_base_color(base_color);
+    paint.set_mask(mask);
+    if let Some(ref mut overlay) = *paint.overlay_mut() {
+        overlay.set_composite_op(composite_op);
+    }
+    Ok(paint)
+}
+
+fn write_mask<W>(writer: &mut W, mask: &Mask) -> io::Result<()> where W: Write {
+    write_pattern(writer, &mask.pattern)?;
+    writer.write_u8(match mask.mode {
+        MaskMode::Alpha => 0,
+        MaskMode::Luminance => 1,
+    })
+}
+
+fn read_mask<R>(reader: &mut R, scene_id: u32, render_target_count: usize)
+                -> Result<Mask, SceneDeserializeError> where R: Read {
+    let pattern = read_pattern(reader, scene_id, render_target_count)?;
+    let mode = match reader.read_u8()? {
+        0 => MaskMode::Alpha,
+        1 => MaskMode::Luminance,
+        _ => return Err(SceneDeserializeError::InvalidData("mask mode")),
+    };
+    Ok(Mask::new(pattern, mode))
+}
+
+pub(crate) fn write_composite_op<W>(writer: &mut W, composite_op: PaintCompositeOp)
+                                     -> io::Result<()> where W: Write {
+    writer.write_u8(match composite_op {
+        PaintCompositeOp::SrcIn => 0,
+        PaintCompositeOp::DestIn => 1,
+    })
+}
+
+pub(crate) fn read_composite_op<R>(reader: &mut R)
+                                   -> Result<PaintCompositeOp, SceneDeserializeError>
+                                   where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(PaintCompositeOp::SrcIn),
+        1 => Ok(PaintCompositeOp::DestIn),
+        _ => Err(SceneDeserializeError::InvalidData("paint composite op")),
+    }
+}
+
+fn write_gradient<W>(writer: &mut W, gradient: &Gradient) -> io::Result<()> where W: Write {
+    match gradient.geometry {
+        GradientGeometry::Linear(line) => {
+            writer.write_u8(0)?;
+            write_line_segment(writer, line)?;
+        }
+        GradientGeometry::Radial { line, radii, transform } => {
+            writer.write_u8(1)?;
+            write_line_segment(writer, line)?;
+            writer.write_f32::<LittleEndian>(radii.x())?;
+            writer.write_f32::<LittleEndian>(radii.y())?;
+            write_transform(writer, &transform)?;
+        }
+    }
+
+    write_len(writer, gradient.stops().len())?;
+    for stop in gradient.stops() {
+        writer.write_f32::<LittleEndian>(stop.offset)?;
+        write_color_u(writer, stop.color)?;
+    }
+    Ok(())
+}
+
+fn read_gradient<R>(reader: &mut R) -> Result<Gradient, SceneDeserializeError> where R: Read {
+    let mut gradient = match reader.read_u8()? {
+        0 => Gradient::linear(read_line_segment(reader)?),
+        1 => {
+            let line = read_line_segment(reader)?;
+            let radii = F32x2::new(reader.read_f32::<LittleEndian>()?,
+                                   reader.read_f32::<LittleEndian>()?);
+            let transform = read_transform(reader)?;
+            let mut gradient = Gradient::radial(line, radii);
+            gradient.geometry = GradientGeometry::Radial { line, radii, transform };
+            gradient
+        }
+        _ => return Err(SceneDeserializeError::InvalidData("gradient geometry")),
+    };
+
+    let stop_count = read_len(reader)?;
+    for _ in 0..stop_count {
+        let offset = reader.read_f32::<LittleEndian>()?;
+        let color = read_color_u(reader)?;
+        gradient.add(ColorStop::new(color, offset));
+    }
+    Ok(gradient)
+}
+
+fn write_pattern<W>(writer: &mut W, pattern: &Pattern) -> io::Result<()> where W: Write {
+    match *pattern.source() {
+        PatternSource::Image(ref image) => {
+            writer.write_u8(0)?;
+            write_image(writer, image)?;
+        }
+        PatternSource::RenderTarget { id, size } => {
+            writer.write_u8(1)?;
+            writer.write_u32::<LittleEndian>(id.render_target)?;
+            write_vector_2i(writer, size)?;
+        }
+    }
+
+    write_transform(writer, &pattern.transform())?;
+
+    write_pattern_filter(writer, pattern.filter())?;
+
+    let flags = (pattern.repeat_x() as u8) |
+        ((pattern.repeat_y() as u8) << 1) |
+        ((!pattern.smoothing_enabled() as u8) << 2);
+    writer.write_u8(flags)
+}
+
+fn read_pattern<R>(reader: &mut R, scene_id: u32, render_target_count: usize)
+                   -> Result<Pattern, SceneDeserializeError> where R: Read {
+    let mut pattern = match reader.read_u8()? {
+        0 => Pattern::from_image(read_image(reader)?),
+        1 => {
+            let render_target = reader.read_u32::<LittleEndian>()?;
+            if render_target as usize >= render_target_count {
+                return Err(SceneDeserializeError::InvalidData("render target ID"));
+            }
+            let size = read_vector_2i(reader)?;
+            if size.x() <= 0 || size.y() <= 0 {
+                return Err(SceneDeserializeError::InvalidData("render target pattern size"));
+            }
+            Pattern::from_render_target(RenderTargetId { scene: scene_id, render_target }, size)
+        }
+        _ => return Err(SceneDeserializeError::InvalidData("pattern source")),
+    };
+
+    pattern.apply_transform(read_transform(reader)?);
+
+    let filter = read_pattern_filter(reader)?;
+    pattern.set_filter(filter);
+
+    let flags = reader.read_u8()?;
+    pattern.set_repeat_x((flags & 0x1) != 0);
+    pattern.set_repeat_y((flags & 0x2) != 0);
+    pattern.set_smoothing_enabled((flags & 0x4) == 0);
+    Ok(pattern)
+}
+
+pub(crate) fn write_image<W>(writer: &mut W, image: &Image) -> io::Result<()> where W: Write {
+    write_vector_2i(writer, image.size())?;
+    writer.write_u8(match image.format() {
+        ImageFormat::RGBA8 => 0,
+        ImageFormat::BGRA8 => 1,
+        ImageFormat::R8 => 2,
+        ImageFormat::RGBA16F => 3,
+    })?;
+    writer.write_u8(match image.alpha_mode() {
+        AlphaMode::Straight => 0,
+        AlphaMode::Premultiplied => 1,
+    })?;
+    writer.write_all(image.data())
+}
+
+pub(crate) fn read_image<R>(reader: &mut R) -> Result<Image, SceneDeserializeError> where R: Read {
+    let size = read_vector_2i(reader)?;
+    if size.x() < 0 || size.y() < 0 {
+        return Err(SceneDeserializeError::InvalidData("image size"));
+    }
+    let format = match reader.read_u8()? {
+        0 => ImageFormat::RGBA8,
+        1 => ImageFormat::BGRA8,
+        2 => ImageFormat::R8,
+        3 => ImageFormat::RGBA16F,
+        _ => return Err(SceneDeserializeError::InvalidData("image format")),
+    };
+    let alpha_mode = match reader.read_u8()? {
+        0 => AlphaMode::Straight,
+        1 => AlphaMode::Premultiplied,
+        _ => return Err(SceneDeserializeError::InvalidData("alpha mode")),
+    };
+
+    // Don't trust the size enough to allocate all of it up front.
+    let byte_len = size.x() as u64 * size.y() as u64 * format.bytes_per_pixel() as u64;
+    let mut data = vec![];
+    reader.take(byte_len).read_to_end(&mut data)?;
+    if data.len() as u64 != byte_len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(Image::from_data(size, format, alpha_mode, Arc::new(data)))
+}
+
+pub(crate) fn write_pattern_filter<W>(writer: &mut W, filter: Option<PatternFilter>)
+                                       -> io::Result<()> where W: Write {
+    match filter {
+        None => writer.write_u8(0)?,
+        Some(PatternFilter::Text { fg_color, bg_color, defringing_kernel, gamma_correction }) => {
+            writer.write_u8(1)?;
+            write_color_f(writer, fg_color)?;
+            write_color_f(writer, bg_color)?;
+            match defringing_kernel {
+                None => writer.write_u8(0)?,
+                Some(DefringingKernel(ref kernel)) => {
+                    writer.write_u8(1)?;
+                    for &value in kernel {
+                        writer.write_f32::<LittleEndian>(value)?;
+                    }
+                }
+            }
+            writer.write_u8(gamma_correction as u8)?;
+        }
+        Some(PatternFilter::Blur { direction, sigma }) => {
+            writer.write_u8(2)?;
+            writer.write_u8(match direction {
+                BlurDirection::X => 0,
+                BlurDirection::Y => 1,
+            })?;
+            writer.write_f32::<LittleEndian>(sigma)?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn read_pattern_filter<R>(reader: &mut R)
+                                      -> Result<Option<PatternFilter>, SceneDeserializeError>
+                                      where R: Read {
+    let filter = match reader.read_u8()? {
+        0 => None,
+        1 => {
+            let fg_color = read_color_f(reader)?;
+            let bg_color = read_color_f(reader)?;
+            let defringing_kernel = match reader.read_u8()? {
+                0 => None,
+                _ => {
+                    let mut kernel = [0.0; 4];
+                    for value in &mut kernel {
+                        *value = reader.read_f32::<LittleEndian>()?;
+                    }
+                    Some(DefringingKernel(kernel))
+                }
+            };
+            let gamma_correction = reader.read_u8()? != 0;
+            Some(PatternFilter::Text { fg_color, bg_color, defringing_kernel, gamma_correction })
+        }
+        2 => {
+            let direction = match reader.read_u8()? {
+                0 => BlurDirection::X,
+                1 => BlurDirection::Y,
+                _ => return Err(SceneDeserializeError::InvalidData("blur direction")),
+            };
+            let sigma = reader.read_f32::<LittleEndian>()?;
+            Some(PatternFilter::Blur { direction, sigma })
+        }
+        _ => return Err(SceneDeserializeError::InvalidData("pattern filter")),
+    };
+    Ok(filter)
+}
+
+fn write_outline<W>(writer: &mut W, outline: &Outline) -> io::Result<()> where W: Write {
+    write_len(writer, outline.contours().len())?;
+    for contour in outline.contours() {
+        write_len(writer, contour.len() as usize)?;
+        writer.write_u8(contour.is_closed() as u8)?;
+        for point_index in 0..contour.len() {
+            write_vector_2f(writer, contour.position_of(point_index))?;
+            writer.write_u8(contour.flags_of(point_index).bits())?;
+        }
+    }
+    Ok(())
+}
+
+fn read_outline<R>(reader: &mut R) -> Result<Outline, SceneDeserializeError> where R: Read {
+    let contour_count = read_len(reader)?;
+    let mut outline = Outline::with_capacity(contour_count.min(MAX_RESERVED_ELEMENTS));
+    for _ in 0..contour_count {
+        let point_count = read_len(reader)?;
+        let closed = reader.read_u8()? != 0;
+        let reserved_count = point_count.min(MAX_RESERVED_ELEMENTS);
+        let (mut points, mut flags) = (Vec::with_capacity(reserved_count),
+                                       Vec::with_capacity(reserved_count));
+        for _ in 0..point_count {
+            points.push(read_vector_2f(reader)?);
+            let point_flags = PointFlags::from_bits(reader.read_u8()?);
+            flags.push(point_flags.ok_or(SceneDeserializeError::InvalidData("point flags"))?);
+        }
+        outline.push_contour(Contour::from_points_and_flags(points, flags, closed));
+    }
+    Ok(outline)
+}
+
+fn write_fill_rule<W>(writer: &mut W, fill_rule: FillRule) -> io::Result<()> where W: Write {
+    writer.write_u8(match fill_rule {
+        FillRule::Winding => 0,
+        FillRule::EvenOdd => 1,
+    })
+}
+
+fn read_fill_rule<R>(reader: &mut R) -> Result<FillRule, SceneDeserializeError> where R: Read {
+    match reader.read_u8()? {
+        0 => Ok(FillRule::Winding),
+        1 => Ok(FillRule::EvenOdd),
+        _ => Err(SceneDeserializeError::InvalidData("fill rule")),
+    }
+}
+
+pub(crate) fn write_blend_mode<W>(writer: &mut W, blend_mode: BlendMode)
+                                   -> io::Result<()> where W: Write {
+    let index = BLEND_MODES.iter().position(|&mode| mode == blend_mode).unwrap();
+    writer.write_u8(index as u8)
+}
+
+pub(crate) fn read_blend_mode<R>(reader: &mut R)
+                                  -> Result<BlendMode, SceneDeserializeError> where R: Read {
+    BLEND_MODES.get(reader.read_u8()? as usize)
+               .cloned()
+               .ok_or(SceneDeserializeError::InvalidData("blend mode"))
+}
+
+pub(crate) fn write_transform<W>(writer: &mut W, transform: &Transform2F)
+                                  -> io::Result<()> where W: Write {
+    for &value in &[transform.m11(), transform.m12(), transform.m13(),
+                    transform.m21(), transform.m22(), transform.m23()] {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_transform<R>(reader: &mut R) -> io::Result<Transform2F> where R: Read {
+    let mut values = [0.0; 6];
+    for value in &mut values {
+        *value = reader.read_f32::<LittleEndian>()?;
+    }
+    Ok(Transform2F::row_major(values[0], values[1], values[2], values[3], values[4], values[5]))
+}
+
+pub(crate) fn write_line_segment<W>(writer: &mut W, line: LineSegment2F)
+                                     -> io::Result<()> where W: Write {
+    write_vector_2f(writer, line.from())?;
+    write_vector_2f(writer, line.to())
+}
+
+pub(crate) fn read_line_segment<R>(reader: &mut R) -> io::Result<LineSegment2F> where R: Read {
+    let from = read_vector_2f(reader)?;
+    let to = read_vector_2f(reader)?;
+    Ok(LineSegment2F::new(from, to))
+}
+
+pub(crate) fn write_rect<W>(writer: &mut W, rect: RectF) -> io::Result<()> where W: Write {
+    write_vector_2f(writer, rect.origin())?;
+    write_vector_2f(writer, rect.size())
+}
+
+pub(crate) fn read_rect<R>(reader: &mut R) -> io::Result<RectF> where R: Read {
+    let origin = read_vector_2f(reader)?;
+    let size = read_vector_2f(reader)?;
+    Ok(RectF::new(origin, size))
+}
+
+pub(crate) fn write_vector_2f<W>(writer: &mut W, vector: Vector2F)
+                                  -> io::Result<()> where W: Write {
+    writer.write_f32::<LittleEndian>(vector.x())?;
+    writer.write_f32::<LittleEndian>(vector.y())
+}
+
+pub(crate) fn read_vector_2f<R>(reader: &mut R) -> io::Result<Vector2F> where R: Read {
+    let x = reader.read_f32::<LittleEndian>()?;
+    let y = reader.read_f32::<LittleEndian>()?;
+    Ok(vec2f(x, y))
+}
+
+pub(crate) fn write_vector_2i<W>(writer: &mut W, vector: Vector2I)
+                                  -> io::Result<()> where W: Write {
+    writer.write_i32::<LittleEndian>(vector.x())?;
+    writer.write_i32::<LittleEndian>(vector.y())
+}
+
+pub(crate) fn read_vector_2i<R>(reader: &mut R) -> io::Result<Vector2I> where R: Read {
+    let x = reader.read_i32::<LittleEndian>()?;
+    let y = reader.read_i32::<LittleEndian>()?;
+    Ok(vec2i(x, y))
+}
+
+pub(crate) fn write_color_u<W>(writer: &mut W, color: ColorU) -> io::Result<()> where W: Write {
+    writer.write_all(&[color.r, color.g, color.b, color.a])
+}
+
+pub(crate) fn read_color_u<R>(reader: &mut R) -> io::Result<ColorU> where R: Read {
+    let mut rgba = [0; 4];
+    reader.read_exact(&mut rgba)?;
+    Ok(ColorU::new(rgba[0], rgba[1], rgba[2], rgba[3]))
+}
+
+fn write_color_f<W>(writer: &mut W, color: ColorF) -> io::Result<()> where W: Write {
+    for &value in &[color.r(), color.g(), color.b(), color.a()] {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    writer.write_u8(match color.color_space() {
+        ColorSpace::SRGB => 0,
+        ColorSpace::DisplayP3 => 1,
+        ColorSpace::LinearSRGB => 2,
+    })
+}
+
+fn read_color_f<R>(reader: &mut R) -> Result<ColorF, SceneDeserializeError> where R: Read {
+    let r = reader.read_f32::<LittleEndian>()?;
+    let g = reader.read_f32::<LittleEndian>()?;
+    let b = reader.read_f32::<LittleEndian>()?;
+    let a = reader.read_f32::<LittleEndian>()?;
+    let color_space = match reader.read_u8()? {
+        0 => ColorSpace::SRGB,
+        1 => ColorSpace::DisplayP3,
+        2 => ColorSpace::LinearSRGB,
+        _ => return Err(SceneDeserializeError::InvalidData("color space")),
+    };
+    Ok(ColorF(F32x4::new(r, g, b, a), color_space))
+}
+
+fn write_string<W>(writer: &mut W, string: &str) -> io::Result<()> where W: Write {
+    write_len(writer, string.len())?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_string<R>(reader: &mut R) -> Result<String, SceneDeserializeError> where R: Read {
+    let len = read_len(reader)?;
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    String::from_utf8(bytes).map_err(|_| SceneDeserializeError::InvalidData("string"))
+}
+
+pub(crate) fn write_len<W>(writer: &mut W, len: usize) -> io::Result<()> where W: Write {
+    writer.write_u32::<LittleEndian>(len as u32)
+}
+
+pub(crate) fn read_len<R>(reader: &mut R) -> io::Result<usize> where R: Read {
+    Ok(reader.read_u32::<LittleEndian>()? as usize)
+}
+
+impl From<io::Error> for SceneDeserializeError {
+    #[inline]
+    fn from(error: io::Error) -> SceneDeserializeError {
+        SceneDeserializeError::Io(error)
+    }
+}
+
+impl Display for SceneDeserializeError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            SceneDeserializeError::Io(ref error) => write!(formatter, "I/O error: {}", error),
+            SceneDeserializeError::BadMagic => formatter.write_str("not a Pathfinder scene"),
+            SceneDeserializeError::UnsupportedVersion(version) => {
+                write!(formatter,
+                       "unsupported scene format version {} (expected {})",
+                       version,
+                       SCENE_FORMAT_VERSION)
+            }
+            SceneDeserializeError::InvalidData(what) => write!(formatter, "invalid {}", what),
+        }
+    }
+}
+
+impl Error for SceneDeserializeError {}
+
+#[cfg(test)]
+mod test {
+    use super::{SCENE_FORMAT_VERSION, SceneDeserializeError, read_outline, read_pattern};
+    use super::read_string;
+    use crate::paint::Paint;
+    use crate::scene::{ClipPath, DrawPath, LayerOptions, Mask, MaskMode, RenderTarget, Scene};
+    use pathfinder_color::{ColorF, ColorSpace, ColorU};
+    use pathfinder_content::effects::{BlendMode, BlurDirection, PatternFilter};
+    use pathfinder_content::fill::FillRule;
+    use pathfinder_content::gradient::Gradient;
+    use pathfinder_content::outline::{Contour, Outline};
+    use pathfinder_content::pattern::{AlphaMode, Image, ImageFormat, Pattern};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::{vec2f, vec2i};
+    use pathfinder_simd::default::F32x2;
+    use std::io;
+    use std::sync::Arc;
+
+    fn make_scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(vec2f(0.0, 0.0), vec2f(64.0, 48.0)));
+
+        let mut outline = Outline::new();
+        let mut contour = Contour::new();
+        contour.push_endpoint(vec2f(1.0, 2.0));
+        contour.push_quadratic(vec2f(10.0, 0.0), vec2f(20.0, 2.0));
+        contour.push_cubic(vec2f(25.0, 10.0), vec2f(15.0, 20.0), vec2f(2.0, 30.0));
+        contour.close();
+        outline.push_contour(contour);
+
+        let clip_path_id = scene.push_clip_path(ClipPath::new(Outline::from_rect(RectF::new(
+            vec2f(0.0, 0.0), vec2f(32.0, 32.0)))));
+
+        let render_target = RenderTarget::new(vec2i(16, 16), "Offscreen".to_owned());
+        let render_target_id = scene.push_render_target(render_target);
+        let paint = scene.push_paint(&Paint::from_color(ColorU::new(1, 2, 3, 4)));
+        scene.push_draw_path(DrawPath::new(outline.clone(), paint));
+        scene.pop_render_target();
+
+        let mut pattern = Pattern::from_render_target(render_target_id, vec2i(16, 16));
+        pattern.set_filter(Some(PatternFilter::Blur { direction: BlurDirection::Y, sigma: 2.5 }));
+        pattern.set_repeat_x(true);
+        let paint = scene.push_paint(&Paint::from_pattern(pattern));
+        let mut draw_path = DrawPath::new(outline.clone(), paint);
+        draw_path.set_clip_path(Some(clip_path_id));
+        let mask = Mask::new(Pattern::from_render_target(render_target_id, vec2i(16, 16)),
+                             MaskMode::Luminance);
+        draw_path.set_mask(Some(scene.push_mask(mask)));
+        draw_path.set_fill_rule(FillRule::EvenOdd);
+        draw_path.set_blend_mode(BlendMode::Luminosity);
+        draw_path.set_name("Blurred".to_owned());
+        scene.push_draw_path(draw_path);
+
+        let mut gradient = Gradient::radial(vec2f(3.0, 4.0), F32x2::new(0.5, 9.0));
+        gradient.add_color_stop(ColorU::new(255, 0, 0, 255), 0.0);
+        gradient.add_color_stop(ColorU::new(0, 0, 255, 128), 1.0);
+        let mut paint = Paint::from_gradient(gradient);
+        paint.apply_transform(&Transform2F::from_rotation(0.25));
+        let paint = scene.push_paint(&paint);
+        scene.push_draw_path(DrawPath::new(outline.clone(), paint));
+
+        let pixels = (0..6).map(|index| ColorU::new(index, 2 * index, 3 * index, 255)).collect();
+        let mut pattern = Pattern::from_image(Image::new(vec2i(3, 2), Arc::new(pixels)));
+        pattern.set_smoothing_enabled(false);
+        pattern.set_filter(Some(PatternFilter::Text {
+            fg_color: ColorF::new(1.0, 0.5, 0.25, 1.0).to_color_space(ColorSpace::DisplayP3),
+            bg_color: ColorF::transparent_black(),
+            defringing_kernel: None,
+            gamma_correction: true,
+        }));
+        let paint = scene.push_paint(&Paint::from_pattern(pattern));
+        scene.push_draw_path(DrawPath::new(outline.clone(), paint));
+
+        let data = (0..16).collect();
+        let image = Image::from_data(vec2i(2, 1),
+                                     ImageFormat::RGBA16F,
+                                     AlphaMode::Premultiplied,
+                                     Arc::new(data));
+        let paint = scene.push_paint(&Paint::from_pattern(Pattern::from_image(image)));
+        scene.push_draw_path(DrawPath::new(outline.clone(), paint));
+
+        scene.push_layer(LayerOptions { opacity: 0.5, ..LayerOptions::default() });
+        scene.push_draw_path(DrawPath::new(outline.clone(), paint));
+        scene.pop_layer();
+
+        let symbol = scene.define_symbol(outline);
+        scene.push_instance(symbol, Transform2F::from_translation(vec2f(8.0, 4.0)), paint);
+
+        scene
+    }
+
+    fn serialize(scene: &Scene) -> Vec<u8> {
+        let mut bytes = vec![];
+        scene.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let scene = make_scene();
+        let bytes = serialize(&scene);
+        let decoded = Scene::deserialize(&mut &bytes[..]).unwrap();
+
+        assert_ne!(decoded.id(), scene.id());
+        assert_eq!(decoded.view_box(), scene.view_box());
+        assert_eq!(decoded.bounds(), scene.bounds());
+        assert_eq!(decoded.palette().paints.len(), scene.palette().paints.len());
+        assert_eq!(decoded.draw_paths().len(), scene.draw_paths().len());
+        assert_eq!(decoded.masks().len(), scene.masks().len());
+        assert_eq!(decoded.display_list().len(), scene.display_list().len());
+        assert_eq!(decoded.draw_paths().last().unwrap().instance(),
+                   scene.draw_paths().last().unwrap().instance());
+        let layer = |scene: &Scene| scene.draw_paths().iter().filter_map(|draw_path| {
+            draw_path.layer.map(|render_target_id| render_target_id.render_target)
+        }).collect::<Vec<_>>();
+        assert_eq!(layer(&decoded), vec![1]);
+        assert_eq!(layer(&decoded), layer(&scene));
+        let text_colors = |scene: &Scene| scene.palette().paints.iter().filter_map(|paint| {
+            match paint.pattern().and_then(|pattern| pattern.filter()) {
+                Some(PatternFilter::Text { fg_color, bg_color, .. }) => Some((fg_color, bg_color)),
+                _ => None,
+            }
+        }).collect::<Vec<_>>();
+        let (fg_color, bg_color) = text_colors(&decoded)[0];
+        assert_eq!(fg_color.color_space(), ColorSpace::DisplayP3);
+        assert_eq!(bg_color.color_space(), ColorSpace::SRGB);
+        assert_eq!(text_colors(&decoded), text_colors(&scene));
+
+        // Re-encoding must reproduce the original bytes exactly.
+        assert_eq!(serialize(&decoded), bytes);
+    }
+
+    #[test]
+    fn test_truncated_counts() {
+        // Huge counts followed by nothing must fail without allocating room for all of them.
+        let huge_count = [0xff; 4];
+        match read_outline(&mut &huge_count[..]) {
+            Err(SceneDeserializeError::Io(_)) => {}
+            _ => panic!("expected the outline to be truncated"),
+        }
+        match read_string(&mut &huge_count[..]) {
+            Err(SceneDeserializeError::Io(_)) => {}
+            _ => panic!("expected the string to be truncated"),
+        }
+
+        let mut bytes = serialize(&make_scene());
+        bytes.truncate(bytes.len() / 2);
+        assert!(Scene::deserialize(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let mut bytes = serialize(&make_scene());
+        bytes[4..8].copy_from_slice(&(SCENE_FORMAT_VERSION + 1).to_le_bytes());
+        match Scene::deserialize(&mut &bytes[..]) {
+            Err(SceneDeserializeError::UnsupportedVersion(version)) => {
+                assert_eq!(version, SCENE_FORMAT_VERSION + 1)
+            }
+            _ => panic!("expected a version mismatch error"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_pattern_size() {
+        for &size in &[(0, 16), (16, -1)] {
+            let mut bytes = vec![1, 0, 0, 0, 0];
+            bytes.extend_from_slice(&i32::to_le_bytes(size.0));
+            bytes.extend_from_slice(&i32::to_le_bytes(size.1));
+            match read_pattern(&mut &bytes[..], 0, 1) {
+                Err(SceneDeserializeError::InvalidData(_)) => {}
+                _ => panic!("expected an invalid pattern size"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_unpopped_layers() {
+        let mut scene = make_scene();
+        scene.push_layer(LayerOptions { opacity: 0.5, ..LayerOptions::default() });
+        let error = scene.serialize(&mut vec![]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        scene.pop_layer();
+        assert!(scene.serialize(&mut vec![]).is_ok());
+    }
+}
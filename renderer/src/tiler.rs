@@ -24,8 +24,10 @@ use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2f, vec2i};
 use pathfinder_simd::default::{F32x2, U32x2};
+use smallvec::SmallVec;
 
-const FLATTENING_TOLERANCE: f32 = 0.25;
+// The fraction of the flattening tolerance spent approximating cubics with quadratics.
+const CUBIC_TO_QUADRATIC_TOLERANCE_FRACTION: f32 = 0.1;
 
 pub(crate) struct Tiler<'a, 'b, 'c, 'd> {
     scene_builder: &'a SceneBuilder<'b, 'a, 'c, 'd>,
@@ -199,21 +201,177 @@ impl<'a, 'b, 'c, 'd> Tiler<'a, 'b, 'c, 'd> {
 fn process_segment(segment: &Segment,
                    scene_builder: &SceneBuilder,
                    object_builder: &mut ObjectBuilder) {
-    // TODO(pcwalton): Stop degree elevating.
+    if segment.is_line() {
+        return process_line_segment(segment.baseline, scene_builder, object_builder);
+    }
+
+    let tolerance = scene_builder.built_options.flattening_tolerance;
+    let mut from = segment.baseline.from();
+    flatten_curve(segment, tolerance, |to| {
+        process_line_segment(LineSegment2F::new(from, to), scene_builder, object_builder);
+        from = to;
+    });
+}
+
+// Flattens a quadratic or cubic curve, calling `emit` with the endpoint of each line in order.
+//
+// This uses the parabola-based method from Levien, "Flattening quadratic Béziers" 2019:
+// https://raphlinus.github.io/graphics/curves/2019/12/23/flatten-quadbez.html
+//
+// Every quadratic is a segment of a parabola, and the number of lines needed to approximate a
+// parabola to a given tolerance is close to the integral of the square root of its curvature.
+// That integral has a good closed-form approximation, so we can pick a near-optimal line count up
+// front and spread the lines out evenly in terms of it. Cubics are approximated by quadratics
+// first, using a small fraction of the tolerance.
+fn flatten_curve<F>(segment: &Segment, tolerance: f32, mut emit: F) where F: FnMut(Vector2F) {
+    let mut quadratics: SmallVec<[Quadratic; 8]> = SmallVec::new();
+    let mut sqrt_tolerance = tolerance.sqrt();
     if segment.is_quadratic() {
-        let cubic = segment.to_cubic();
-        return process_segment(&cubic, scene_builder, object_builder);
+        quadratics.push(Quadratic {
+            p0: segment.baseline.from(),
+            p1: segment.ctrl.from(),
+            p2: segment.baseline.to(),
+        });
+    } else {
+        let cubic_tolerance = tolerance * CUBIC_TO_QUADRATIC_TOLERANCE_FRACTION;
+        push_quadratics_for_cubic(segment, cubic_tolerance, &mut quadratics);
+        sqrt_tolerance *= (1.0 - CUBIC_TO_QUADRATIC_TOLERANCE_FRACTION).sqrt();
     }
 
-    if segment.is_line() ||
-            (segment.is_cubic() && segment.as_cubic_segment().is_flat(FLATTENING_TOLERANCE)) {
-        return process_line_segment(segment.baseline, scene_builder, object_builder);
+    let params: SmallVec<[QuadraticFlatteningParams; 8]> = quadratics.iter().map(|quadratic| {
+        quadratic.flattening_params(sqrt_tolerance)
+    }).collect();
+    let total_value: f32 = params.iter().map(|params| params.value).sum();
+
+    // Degenerate curves produce a NaN total, which casts to zero lines.
+    let line_count = ((0.5 * total_value / sqrt_tolerance).ceil() as u32).max(1);
+    let step = total_value / line_count as f32;
+
+    let (mut line_index, mut value_start) = (1, 0.0);
+    for (quadratic, params) in quadratics.iter().zip(params.iter()) {
+        let value_end = value_start + params.value;
+        while line_index < line_count {
+            let target = line_index as f32 * step;
+            if target >= value_end {
+                break;
+            }
+            let t = params.subdivision_t((target - value_start) / params.value);
+            emit(quadratic.sample(t));
+            line_index += 1;
+        }
+        value_start = value_end;
+    }
+
+    emit(segment.baseline.to());
+}
+
+// Approximates a cubic by quadratics, each within `tolerance` of the part of the cubic it
+// replaces. The error bound is from the "Cubic to quadratic" section of Levien's post.
+fn push_quadratics_for_cubic(segment: &Segment,
+                             tolerance: f32,
+                             quadratics: &mut SmallVec<[Quadratic; 8]>) {
+    let (p0, p1) = (segment.baseline.from(), segment.ctrl.from());
+    let (p2, p3) = (segment.ctrl.to(), segment.baseline.to());
+    let error = ((p2 * 3.0 - p3) - (p1 * 3.0 - p0)).square_length();
+    let quadratic_count =
+        ((error / (432.0 * tolerance * tolerance)).powf(1.0 / 6.0).ceil() as u32).max(1);
+
+    let mut rest = *segment;
+    for quadratic_index in 0..quadratic_count {
+        let remaining_count = quadratic_count - quadratic_index;
+        let cubic = if remaining_count == 1 {
+            rest
+        } else {
+            let (prev, next) = rest.as_cubic_segment().split(1.0 / remaining_count as f32);
+            rest = next;
+            prev
+        };
+
+        let (c0, c1) = (cubic.baseline.from(), cubic.ctrl.from());
+        let (c2, c3) = (cubic.ctrl.to(), cubic.baseline.to());
+        let ctrl = ((c1 * 3.0 - c0) + (c2 * 3.0 - c3)) * 0.25;
+        quadratics.push(Quadratic { p0: c0, p1: ctrl, p2: c3 });
     }
+}
 
-    // TODO(pcwalton): Use a smarter flattening algorithm.
-    let (prev, next) = segment.split(0.5);
-    process_segment(&prev, scene_builder, object_builder);
-    process_segment(&next, scene_builder, object_builder);
+#[derive(Clone, Copy, Debug)]
+struct Quadratic {
+    p0: Vector2F,
+    p1: Vector2F,
+    p2: Vector2F,
+}
+
+// Describes the parabola segment that a quadratic maps onto, in terms of the approximate
+// integral of the square root of curvature.
+#[derive(Clone, Copy, Debug)]
+struct QuadraticFlatteningParams {
+    a0: f32,
+    a2: f32,
+    u0: f32,
+    u_scale: f32,
+    // Proportional to the number of lines this quadratic needs.
+    value: f32,
+}
+
+impl Quadratic {
+    #[inline]
+    fn sample(&self, t: f32) -> Vector2F {
+        let mt = 1.0 - t;
+        self.p0 * (mt * mt) + self.p1 * (2.0 * mt * t) + self.p2 * (t * t)
+    }
+
+    fn flattening_params(&self, sqrt_tolerance: f32) -> QuadraticFlatteningParams {
+        let d01 = self.p1 - self.p0;
+        let d12 = self.p2 - self.p1;
+        let dd = d01 - d12;
+        let cross = (self.p2 - self.p0).det(dd);
+        let x0 = d01.dot(dd) / cross;
+        let x2 = d12.dot(dd) / cross;
+        let scale = (cross / (dd.length() * (x2 - x0))).abs();
+
+        let a0 = approx_parabola_integral(x0);
+        let a2 = approx_parabola_integral(x2);
+        let mut value = 0.0;
+        if scale.is_finite() {
+            let da = (a2 - a0).abs();
+            let sqrt_scale = scale.sqrt();
+            value = if x0.signum() == x2.signum() {
+                da * sqrt_scale
+            } else {
+                // The segment includes the cusp of the parabola, so clamp the curvature there.
+                let x_min = sqrt_tolerance / sqrt_scale;
+                sqrt_tolerance * da / approx_parabola_integral(x_min)
+            };
+        }
+
+        let u0 = approx_parabola_inv_integral(a0);
+        let u2 = approx_parabola_inv_integral(a2);
+        QuadraticFlatteningParams { a0, a2, u0, u_scale: 1.0 / (u2 - u0), value }
+    }
+}
+
+impl QuadraticFlatteningParams {
+    // Maps a fraction of `value` to the parameter of the quadratic at that point.
+    #[inline]
+    fn subdivision_t(&self, x: f32) -> f32 {
+        let a = self.a0 + (self.a2 - self.a0) * x;
+        let u = approx_parabola_inv_integral(a);
+        (u - self.u0) * self.u_scale
+    }
+}
+
+// An approximation to the integral of `(1 + 4x²)^-0.25`.
+#[inline]
+fn approx_parabola_integral(x: f32) -> f32 {
+    const D: f32 = 0.67;
+    x / (1.0 - D + (D * D * D * D + 0.25 * x * x).sqrt().sqrt())
+}
+
+// An approximation to the inverse of `approx_parabola_integral()`.
+#[inline]
+fn approx_parabola_inv_integral(x: f32) -> f32 {
+    const B: f32 = 0.39;
+    x * (1.0 - B + (B * B + 0.25 * x * x).sqrt())
 }
 
 // This is the meat of the technique. It implements the fast lattice-clipping algorithm from
@@ -339,3 +497,62 @@ enum StepDirection {
     X,
     Y,
 }
+
+#[cfg(test)]
+mod test {
+    use pathfinder_content::segment::Segment;
+    use pathfinder_geometry::line_segment::LineSegment2F;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use super::flatten_curve;
+
+    fn flatten(segment: &Segment, tolerance: f32) -> Vec<Vector2F> {
+        let mut points = vec![segment.baseline.from()];
+        flatten_curve(segment, tolerance, |point| points.push(point));
+        points
+    }
+
+    // Returns the largest distance from a point on the curve to the flattened polyline.
+    fn max_error(segment: &Segment, points: &[Vector2F]) -> f32 {
+        (0..=1000).map(|sample_index| {
+            let point = segment.sample(sample_index as f32 / 1000.0);
+            points.windows(2).map(|line| {
+                let line = LineSegment2F::new(line[0], line[1]);
+                let t = ((point - line.from()).dot(line.vector()) / line.square_length())
+                    .max(0.0)
+                    .min(1.0);
+                (line.sample(t) - point).length()
+            }).fold(f32::INFINITY, f32::min)
+        }).fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_flattening_respects_tolerance() {
+        let quadratic = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(400.0, 0.0)),
+                                           vec2f(100.0, 300.0));
+        let cubic = Segment::cubic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(300.0, 0.0)),
+                                   LineSegment2F::new(vec2f(400.0, 300.0),
+                                                      vec2f(-100.0, 300.0)));
+        for segment in &[quadratic, cubic] {
+            let mut last_point_count = 0;
+            for &tolerance in &[1.0, 0.25, 0.05] {
+                let points = flatten(segment, tolerance);
+                assert_eq!(*points.last().unwrap(), segment.baseline.to());
+                assert!(max_error(segment, &points) <= tolerance * 1.1);
+                assert!(points.len() > last_point_count);
+                last_point_count = points.len();
+            }
+        }
+    }
+
+    #[test]
+    fn test_flattening_degenerate_curves() {
+        let point = vec2f(10.0, 10.0);
+        let collapsed = Segment::cubic(LineSegment2F::new(point, point),
+                                       LineSegment2F::new(point, point));
+        assert_eq!(flatten(&collapsed, 0.25), vec![point, point]);
+
+        let straight = Segment::quadratic(LineSegment2F::new(vec2f(0.0, 0.0), vec2f(8.0, 0.0)),
+                                          vec2f(4.0, 0.0));
+        assert_eq!(flatten(&straight, 0.25), vec![vec2f(0.0, 0.0), vec2f(8.0, 0.0)]);
+    }
+}
@@ -0,0 +1,1149 @@
+// pathfinder/renderer/src/trace.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recording and replaying of render command streams.
+//!
+//! A trace captures everything the renderer receives from the scene builder, so a rendering bug
+//! can be reproduced without the original scene or `SceneProxy`. Each command is stored as a
+//! one-byte tag, a 32-bit payload length, and the payload, all little-endian.
+
+use crate::gpu::renderer::Renderer;
+use crate::gpu_data::{AlphaTileId, BackdropInfoD3D11, Clip, ClippedPathInfo, ColorCombineMode};
+use crate::gpu_data::{DiceMetadataD3D11, DrawTileBatchD3D11, DrawTileBatchD3D9, Fill};
+use crate::gpu_data::{PathBatchIndex, PathSource, PrepareTilesInfoD3D11, PropagateMetadataD3D11};
+use crate::gpu_data::{RenderCommand, SegmentIndicesD3D11, SegmentsD3D11, TextureLocation};
+use crate::gpu_data::{TextureAtlasRegion, TextureMetadataEntry, TexturePageDescriptor};
+use crate::gpu_data::{TexturePageId, TileBatchDataD3D11, TileBatchId, TileBatchTexture};
+use crate::gpu_data::{PathTransformD3D11, TileObjectPrimitive, TilePathInfoD3D11};
+use crate::options::{AntialiasingMode, RenderCommandListener};
+use crate::scene::{MaskMode, PathId};
+use crate::serialization::{self, SceneDeserializeError};
+use crate::tile_map::DenseTileMap;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use pathfinder_content::effects::{Filter, SubpixelLayout};
+use pathfinder_content::render_target::RenderTargetId;
+use pathfinder_geometry::line_segment::LineSegmentU16;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::vector::Vector4F;
+use pathfinder_gpu::{Device, DeviceError, TextureSamplingFlags};
+use pathfinder_simd::default::F32x2;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The version of the trace format written by `TraceRecorder`.
+///
+/// Bump this whenever the encoding of any render command changes.
+pub const TRACE_FORMAT_VERSION: u32 = 15;
+
+const TRACE_MAGIC: [u8; 4] = *b"PFTR";
+
+/// The number of distinct render command tags.
+pub const RENDER_COMMAND_KIND_COUNT: usize = 16;
+
+/// Writes every render command that passes through it to a trace.
+///
+/// I/O errors can't be reported from inside a listener, so the first one is remembered and
+/// returned from `finish()`; nothing more is written after it.
+pub struct TraceRecorder<W> where W: Write + Send {
+    state: Arc<Mutex<RecorderState<W>>>,
+}
+
+struct RecorderState<W> {
+    writer: W,
+    buffer: Vec<u8>,
+    error: Option<io::Error>,
+}
+
+/// Reads render commands back from a trace.
+pub struct TraceReader<R> where R: Read {
+    reader: R,
+    buffer: Vec<u8>,
+    bytes_read: u64,
+}
+
+/// An error that occurred while reading or replaying a trace.
+#[derive(Debug)]
+pub enum TraceError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The renderer failed to replay a command.
+    Device(DeviceError),
+    /// The data doesn't start with the trace magic number.
+    BadMagic,
+    /// The trace was written by an incompatible version of Pathfinder.
+    UnsupportedVersion(u32),
+    /// The trace is corrupt.
+    InvalidData(&'static str),
+}
+
+impl<W> TraceRecorder<W> where W: Write + Send {
+    /// Writes the trace header to `writer` and returns a recorder that appends to it.
+    pub fn new(mut writer: W) -> io::Result<TraceRecorder<W>> {
+        writer.write_all(&TRACE_MAGIC)?;
+        writer.write_u32::<LittleEndian>(TRACE_FORMAT_VERSION)?;
+        Ok(TraceRecorder {
+            state: Arc::new(Mutex::new(RecorderState { writer, buffer: vec![], error: None })),
+        })
+    }
+
+    /// Returns a listener that records each command and then forwards it to `next`.
+    pub fn listener<'a>(&self, next: RenderCommandListener<'a>) -> RenderCommandListener<'a>
+                        where W: 'a {
+        let state = self.state.clone();
+        RenderCommandListener::new(Box::new(move |command| {
+            state.lock().unwrap().record(&command);
+            next.send(command);
+        }))
+    }
+
+    /// Flushes the trace and returns the first error encountered while recording, if any.
+    pub fn finish(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(error) = state.error.take() {
+            return Err(error);
+        }
+        state.writer.flush()
+    }
+}
+
+impl<W> RecorderState<W> where W: Write {
+    fn record(&mut self, command: &RenderCommand) {
+        if self.error.is_some() {
+            return;
+        }
+
+        self.buffer.clear();
+        write_command_payload(&mut self.buffer, command).unwrap();
+
+        let result = self.writer.write_u8(command_kind(command) as u8).and_then(|_| {
+            self.writer.write_u32::<LittleEndian>(self.buffer.len() as u32)
+        }).and_then(|_| self.writer.write_all(&self.buffer));
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+}
+
+impl<R> TraceReader<R> where R: Read {
+    /// Reads and checks the trace header.
+    pub fn new(mut reader: R) -> Result<TraceReader<R>, TraceError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != TRACE_MAGIC {
+            return Err(TraceError::BadMagic);
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != TRACE_FORMAT_VERSION {
+            return Err(TraceError::UnsupportedVersion(version));
+        }
+        Ok(TraceReader { reader, buffer: vec![], bytes_read: 8 })
+    }
+
+    /// Returns the next command, or `None` at the end of the trace.
+    pub fn next_command(&mut self) -> Result<Option<RenderCommand>, TraceError> {
+        let mut tag = [0];
+        if self.reader.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        let payload_len = self.reader.read_u32::<LittleEndian>()? as usize;
+
+        // Don't trust the length enough to allocate all of it up front.
+        self.buffer.clear();
+        (&mut self.reader).take(payload_len as u64).read_to_end(&mut self.buffer)?;
+        if self.buffer.len() != payload_len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        self.bytes_read += 5 + payload_len as u64;
+
+        let mut payload = &self.buffer[..];
+        let command = read_command_payload(&mut payload, tag[0])?;
+        if !payload.is_empty() {
+            return Err(TraceError::InvalidData("trailing command data"));
+        }
+        Ok(Some(command))
+    }
+
+    /// The number of bytes of the trace consumed so far, including the header.
+    #[inline]
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+}
+
+/// Feeds every command in a trace to `renderer`, beginning and ending a scene around each frame
+/// the same way `SceneProxy` does.
+pub fn replay_trace<R, D>(reader: &mut TraceReader<R>, renderer: &mut Renderer<D>)
+                          -> Result<(), TraceError>
+                          where R: Read, D: Device {
+    while let Some(command) = reader.next_command()? {
+        if let RenderCommand::Start { .. } = command {
+            renderer.begin_scene();
+        }
+        renderer.render_command(&command)?;
+        if let RenderCommand::Finish { .. } = command {
+            renderer.end_scene()?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the tag that identifies the kind of `command` in a trace, in the range
+/// `0..RENDER_COMMAND_KIND_COUNT`.
+pub fn command_kind(command: &RenderCommand) -> usize {
+    match *command {
+        RenderCommand::Start { .. } => 0,
+        RenderCommand::AllocateTexturePage { .. } => 1,
+        RenderCommand::UploadTexelData { .. } => 2,
+        RenderCommand::DeclareRenderTarget { .. } => 3,
+        RenderCommand::UploadTextureMetadata(..) => 4,
+        RenderCommand::AddFillsD3D9(..) => 5,
+        RenderCommand::FlushFillsD3D9 => 6,
+        RenderCommand::UploadSceneD3D11 { .. } => 7,
+        RenderCommand::PushRenderTarget(..) => 8,
+        RenderCommand::PopRenderTarget => 9,
+        RenderCommand::PrepareClipTilesD3D11(..) => 10,
+        RenderCommand::DrawTilesD3D9(..) => 11,
+        RenderCommand::DrawTilesD3D11(..) => 12,
+        RenderCommand::Finish { .. } => 13,
+        RenderCommand::CopyTexelData { .. } => 14,
+        RenderCommand::UploadImage { .. } => 15,
+    }
+}
+
+/// Returns a human-readable name for a command kind returned by `command_kind()`.
+pub fn command_kind_name(kind: usize) -> &'static str {
+    static NAMES: [&str; RENDER_COMMAND_KIND_COUNT] = [
+        "Start",
+        "AllocateTexturePage",
+        "UploadTexelData",
+        "DeclareRenderTarget",
+        "UploadTextureMetadata",
+        "AddFillsD3D9",
+        "FlushFillsD3D9",
+        "UploadSceneD3D11",
+        "PushRenderTarget",
+        "PopRenderTarget",
+        "PrepareClipTilesD3D11",
+        "DrawTilesD3D9",
+        "DrawTilesD3D11",
+        "Finish",
+        "CopyTexelData",
+        "UploadImage",
+    ];
+    NAMES[kind]
+}
+
+fn write_command_payload<W>(writer: &mut W, command: &RenderCommand) -> io::Result<()>
+                            where W: Write {
+    match *command {
+        RenderCommand::Start {
+            path_count,
+            ref bounding_quad,
+            needs_readable_framebuffer,
+            ref damage_rects,
+            antialiasing_mode,
+            subpixel_layout,
+        } => {
+            writer.write_u64::<LittleEndian>(path_count as u64)?;
+            for vertex in bounding_quad {
+                write_vector_4f(writer, *vertex)?;
+            }
+            writer.write_u8(needs_readable_framebuffer as u8)?;
+            serialization::write_len(writer, damage_rects.len())?;
+            for damage_rect in damage_rects {
+                write_rect_i(writer, *damage_rect)?;
+            }
+            match antialiasing_mode {
+                AntialiasingMode::Analytic => writer.write_u8(0)?,
+                AntialiasingMode::Aliased => writer.write_u8(1)?,
+                AntialiasingMode::Supersampled(sample_count) => {
+                    writer.write_u8(2)?;
+                    writer.write_u32::<LittleEndian>(sample_count)?;
+                }
+            }
+            writer.write_u8(match subpixel_layout {
+                SubpixelLayout::RGB => 0,
+                SubpixelLayout::BGR => 1,
+                SubpixelLayout::VRGB => 2,
+                SubpixelLayout::VBGR => 3,
+            })
+        }
+        RenderCommand::AllocateTexturePage { page_id, descriptor } => {
+            writer.write_u32::<LittleEndian>(page_id.0)?;
+            serialization::write_vector_2i(writer, descriptor.size)
+        }
+        RenderCommand::UploadTexelData { ref texels, location } => {
+            serialization::write_len(writer, texels.len())?;
+            for &texel in texels.iter() {
+                serialization::write_color_u(writer, texel)?;
+            }
+            write_texture_location(writer, location)
+        }
+        RenderCommand::DeclareRenderTarget { id, location, padding } => {
+            write_render_target_id(writer, id)?;
+            write_texture_location(writer, location)?;
+            writer.write_i32::<LittleEndian>(padding)
+        }
+        RenderCommand::UploadTextureMetadata(ref metadata) => {
+            serialization::write_len(writer, metadata.len())?;
+            for entry in metadata {
+                serialization::write_transform(writer, &entry.color_0_transform)?;
+                writer.write_u8(match entry.color_0_combine_mode {
+                    ColorCombineMode::None => 0,
+                    ColorCombineMode::SrcIn => 1,
+                    ColorCombineMode::DestIn => 2,
+                })?;
+                write_texture_atlas_region(writer, entry.color_0_atlas_region)?;
+                serialization::write_transform(writer, &entry.paint_mask_transform)?;
+                writer.write_u8(match entry.paint_mask_mode {
+                    None => 0,
+                    Some(MaskMode::Alpha) => 1,
+                    Some(MaskMode::Luminance) => 2,
+                })?;
+                writer.write_u8(entry.paint_mask_premultiplied as u8)?;
+                write_texture_atlas_region(writer, entry.paint_mask_atlas_region)?;
+                serialization::write_color_u(writer, entry.base_color)?;
+                write_filter(writer, &entry.filter)?;
+                serialization::write_blend_mode(writer, entry.blend_mode)?;
+            }
+            Ok(())
+        }
+        RenderCommand::AddFillsD3D9(ref fills) => {
+            serialization::write_len(writer, fills.len())?;
+            for fill in fills {
+                let line_segment = &fill.line_segment;
+                for &value in &[line_segment.from_x, line_segment.from_y,
+                                line_segment.to_x, line_segment.to_y] {
+                    writer.write_u16::<LittleEndian>(value)?;
+                }
+                writer.write_u32::<LittleEndian>(fill.link)?;
+            }
+            Ok(())
+        }
+        RenderCommand::UploadSceneD3D11 { ref draw_segments, ref clip_segments } => {
+            write_segments(writer, draw_segments)?;
+            write_segments(writer, clip_segments)
+        }
+        RenderCommand::PushRenderTarget(render_target_id) => {
+            write_render_target_id(writer, render_target_id)
+        }
+        RenderCommand::FlushFillsD3D9 | RenderCommand::PopRenderTarget => Ok(()),
+        RenderCommand::PrepareClipTilesD3D11(ref batch) => write_tile_batch_data(writer, batch),
+        RenderCommand::DrawTilesD3D9(ref batch) => {
+            serialization::write_len(writer, batch.tiles.len())?;
+            for tile in &batch.tiles {
+                writer.write_i16::<LittleEndian>(tile.tile_x)?;
+                writer.write_i16::<LittleEndian>(tile.tile_y)?;
+                writer.write_u32::<LittleEndian>(tile.alpha_tile_id.0)?;
+                writer.write_u32::<LittleEndian>(tile.path_id.0)?;
+                writer.write_u16::<LittleEndian>(tile.color)?;
+                writer.write_u8(tile.ctrl)?;
+                writer.write_i8(tile.backdrop)?;
+            }
+            write_clips(writer, &batch.clips)?;
+            write_rect_i(writer, batch.z_buffer_data.rect)?;
+            serialization::write_len(writer, batch.z_buffer_data.data.len())?;
+            for &value in &batch.z_buffer_data.data {
+                writer.write_i32::<LittleEndian>(value)?;
+            }
+            write_tile_batch_texture(writer, batch.color_texture)?;
+            write_tile_batch_texture(writer, batch.paint_mask_texture)?;
+            write_filter(writer, &batch.filter)?;
+            serialization::write_blend_mode(writer, batch.blend_mode)
+        }
+        RenderCommand::DrawTilesD3D11(ref batch) => {
+            write_tile_batch_data(writer, &batch.tile_batch_data)?;
+            write_tile_batch_texture(writer, batch.color_texture)?;
+            write_tile_batch_texture(writer, batch.paint_mask_texture)
+        }
+        RenderCommand::Finish { cpu_build_time } => {
+            writer.write_u64::<LittleEndian>(cpu_build_time.as_secs())?;
+            writer.write_u32::<LittleEndian>(cpu_build_time.subsec_nanos())
+        }
+        RenderCommand::CopyTexelData { src, dest } => {
+            write_texture_location(writer, src)?;
+            write_texture_location(writer, dest)
+        }
+        RenderCommand::UploadImage { ref image, location } => {
+            serialization::write_image(writer, image)?;
+            write_texture_location(writer, location)
+        }
+    }
+}
+
+fn read_command_payload(reader: &mut &[u8], tag: u8) -> Result<RenderCommand, TraceError> {
+    Ok(match tag {
+        0 => {
+            let path_count = reader.read_u64::<LittleEndian>()? as usize;
+            let mut bounding_quad = [Vector4F::default(); 4];
+            for vertex in &mut bounding_quad {
+                *vertex = read_vector_4f(reader)?;
+            }
+            let needs_readable_framebuffer = reader.read_u8()? != 0;
+            let damage_rect_count = read_count(reader, 16)?;
+            let mut damage_rects = Vec::with_capacity(damage_rect_count);
+            for _ in 0..damage_rect_count {
+                damage_rects.push(read_rect_i(reader)?);
+            }
+            let antialiasing_mode = match reader.read_u8()? {
+                0 => AntialiasingMode::Analytic,
+                1 => AntialiasingMode::Aliased,
+                2 => AntialiasingMode::Supersampled(reader.read_u32::<LittleEndian>()?),
+                _ => return Err(TraceError::InvalidData("antialiasing mode")),
+            };
+            let subpixel_layout = match reader.read_u8()? {
+                0 => SubpixelLayout::RGB,
+                1 => SubpixelLayout::BGR,
+                2 => SubpixelLayout::VRGB,
+                3 => SubpixelLayout::VBGR,
+                _ => return Err(TraceError::InvalidData("subpixel layout")),
+            };
+            RenderCommand::Start {
+                path_count,
+                bounding_quad,
+                needs_readable_framebuffer,
+                damage_rects,
+                antialiasing_mode,
+                subpixel_layout,
+            }
+        }
+        1 => {
+            let page_id = TexturePageId(reader.read_u32::<LittleEndian>()?);
+            let size = serialization::read_vector_2i(reader)?;
+            RenderCommand::AllocateTexturePage {
+                page_id,
+                descriptor: TexturePageDescriptor { size },
+            }
+        }
+        2 => {
+            let texel_count = read_count(reader, 4)?;
+            let mut texels = Vec::with_capacity(texel_count);
+            for _ in 0..texel_count {
+                texels.push(serialization::read_color_u(reader)?);
+            }
+            let location = read_texture_location(reader)?;
+            RenderCommand::UploadTexelData { texels: Arc::new(texels), location }
+        }
+        3 => {
+            let id = read_render_target_id(reader)?;
+            let location = read_texture_location(reader)?;
+            let padding = reader.read_i32::<LittleEndian>()?;
+            RenderCommand::DeclareRenderTarget { id, location, padding }
+        }
+        4 => {
+            let entry_count = read_count(reader, 1)?;
+            let mut metadata = Vec::with_capacity(entry_count);
+            for _ in 0..entry_count {
+                let color_0_transform = serialization::read_transform(reader)?;
+                let color_0_combine_mode = match reader.read_u8()? {
+                    0 => ColorCombineMode::None,
+                    1 => ColorCombineMode::SrcIn,
+                    2 => ColorCombineMode::DestIn,
+                    _ => return Err(TraceError::InvalidData("color combine mode")),
+                };
+                let color_0_atlas_region = read_texture_atlas_region(reader)?;
+                let paint_mask_transform = serialization::read_transform(reader)?;
+                let paint_mask_mode = match reader.read_u8()? {
+                    0 => None,
+                    1 => Some(MaskMode::Alpha),
+                    2 => Some(MaskMode::Luminance),
+                    _ => return Err(TraceError::InvalidData("mask mode")),
+                };
+                let paint_mask_premultiplied = reader.read_u8()? != 0;
+                let paint_mask_atlas_region = read_texture_atlas_region(reader)?;
+                let base_color = serialization::read_color_u(reader)?;
+                let filter = read_filter(reader)?;
+                let blend_mode = serialization::read_blend_mode(reader)?;
+                metadata.push(TextureMetadataEntry {
+                    color_0_transform,
+                    color_0_combine_mode,
+                    color_0_atlas_region,
+                    paint_mask_transform,
+                    paint_mask_mode,
+                    paint_mask_premultiplied,
+                    paint_mask_atlas_region,
+                    base_color,
+                    filter,
+                    blend_mode,
+                });
+            }
+            RenderCommand::UploadTextureMetadata(metadata)
+        }
+        5 => {
+            let fill_count = read_count(reader, 12)?;
+            let mut fills = Vec::with_capacity(fill_count);
+            for _ in 0..fill_count {
+                let line_segment = LineSegmentU16 {
+                    from_x: reader.read_u16::<LittleEndian>()?,
+                    from_y: reader.read_u16::<LittleEndian>()?,
+                    to_x: reader.read_u16::<LittleEndian>()?,
+                    to_y: reader.read_u16::<LittleEndian>()?,
+                };
+                let link = reader.read_u32::<LittleEndian>()?;
+                fills.push(Fill { line_segment, link });
+            }
+            RenderCommand::AddFillsD3D9(fills)
+        }
+        6 => RenderCommand::FlushFillsD3D9,
+        7 => {
+            let draw_segments = read_segments(reader)?;
+            let clip_segments = read_segments(reader)?;
+            RenderCommand::UploadSceneD3D11 { draw_segments, clip_segments }
+        }
+        8 => RenderCommand::PushRenderTarget(read_render_target_id(reader)?),
+        9 => RenderCommand::PopRenderTarget,
+        10 => RenderCommand::PrepareClipTilesD3D11(read_tile_batch_data(reader)?),
+        11 => {
+            let tile_count = read_count(reader, 16)?;
+            let mut tiles = Vec::with_capacity(tile_count);
+            for _ in 0..tile_count {
+                tiles.push(TileObjectPrimitive {
+                    tile_x: reader.read_i16::<LittleEndian>()?,
+                    tile_y: reader.read_i16::<LittleEndian>()?,
+                    alpha_tile_id: AlphaTileId(reader.read_u32::<LittleEndian>()?),
+                    path_id: PathId(reader.read_u32::<LittleEndian>()?),
+                    color: reader.read_u16::<LittleEndian>()?,
+                    ctrl: reader.read_u8()?,
+                    backdrop: reader.read_i8()?,
+                });
+            }
+            let clips = read_clips(reader)?;
+            let rect = read_rect_i(reader)?;
+            let data_len = read_count(reader, 4)?;
+            let mut data = Vec::with_capacity(data_len);
+            for _ in 0..data_len {
+                data.push(reader.read_i32::<LittleEndian>()?);
+            }
+            let color_texture = read_tile_batch_texture(reader)?;
+            let paint_mask_texture = read_tile_batch_texture(reader)?;
+            let filter = read_filter(reader)?;
+            let blend_mode = serialization::read_blend_mode(reader)?;
+            RenderCommand::DrawTilesD3D9(DrawTileBatchD3D9 {
+                tiles,
+                clips,
+                z_buffer_data: DenseTileMap { data, rect },
+                color_texture,
+                paint_mask_texture,
+                filter,
+                blend_mode,
+            })
+        }
+        12 => {
+            let tile_batch_data = read_tile_batch_data(reader)?;
+            let color_texture = read_tile_batch_texture(reader)?;
+            let paint_mask_texture = read_tile_batch_texture(reader)?;
+            RenderCommand::DrawTilesD3D11(DrawTileBatchD3D11 {
+                tile_batch_data,
+                color_texture,
+                paint_mask_texture,
+            })
+        }
+        13 => {
+            let secs = reader.read_u64::<LittleEndian>()?;
+            let nanos = reader.read_u32::<LittleEndian>()?;
+            RenderCommand::Finish { cpu_build_time: Duration::new(secs, nanos) }
+        }
+        14 => {
+            let src = read_texture_location(reader)?;
+            let dest = read_texture_location(reader)?;
+            RenderCommand::CopyTexelData { src, dest }
+        }
+        15 => {
+            let image = serialization::read_image(reader)?;
+            let location = read_texture_location(reader)?;
+            RenderCommand::UploadImage { image, location }
+        }
+        _ => return Err(TraceError::InvalidData("render command tag")),
+    })
+}
+
+fn write_tile_batch_data<W>(writer: &mut W, batch: &TileBatchDataD3D11) -> io::Result<()>
+                            where W: Write {
+    writer.write_u32::<LittleEndian>(batch.batch_id.0)?;
+    writer.write_u32::<LittleEndian>(batch.path_count)?;
+    writer.write_u32::<LittleEndian>(batch.tile_count)?;
+    writer.write_u32::<LittleEndian>(batch.segment_count)?;
+
+    let prepare_info = &batch.prepare_info;
+    serialization::write_len(writer, prepare_info.backdrops.len())?;
+    for backdrop in &prepare_info.backdrops {
+        writer.write_i32::<LittleEndian>(backdrop.initial_backdrop)?;
+        writer.write_i32::<LittleEndian>(backdrop.tile_x_offset)?;
+        writer.write_u32::<LittleEndian>(backdrop.path_index.0)?;
+    }
+    serialization::write_len(writer, prepare_info.propagate_metadata.len())?;
+    for metadata in &prepare_info.propagate_metadata {
+        write_rect_i(writer, metadata.tile_rect)?;
+        for &value in &[metadata.tile_offset, metadata.path_index.0, metadata.z_write,
+                        metadata.clip_path_index.0, metadata.backdrop_offset] {
+            writer.write_u32::<LittleEndian>(value)?;
+        }
+    }
+    serialization::write_len(writer, prepare_info.dice_metadata.len())?;
+    for metadata in &prepare_info.dice_metadata {
+        writer.write_u32::<LittleEndian>(metadata.global_path_id.0)?;
+        writer.write_u32::<LittleEndian>(metadata.first_global_segment_index)?;
+        writer.write_u32::<LittleEndian>(metadata.first_batch_segment_index)?;
+    }
+    serialization::write_len(writer, prepare_info.path_transforms.len())?;
+    for path_transform in &prepare_info.path_transforms {
+        for &value in path_transform.matrix.iter().chain(path_transform.translation.iter()) {
+            writer.write_f32::<LittleEndian>(value)?;
+        }
+    }
+    serialization::write_len(writer, prepare_info.tile_path_info.len())?;
+    for info in &prepare_info.tile_path_info {
+        for &value in &[info.tile_min_x, info.tile_min_y, info.tile_max_x, info.tile_max_y] {
+            writer.write_i16::<LittleEndian>(value)?;
+        }
+        writer.write_u32::<LittleEndian>(info.first_tile_index)?;
+        writer.write_u16::<LittleEndian>(info.color)?;
+        writer.write_u8(info.ctrl)?;
+        writer.write_i8(info.backdrop)?;
+    }
+    serialization::write_transform(writer, &prepare_info.transform)?;
+    writer.write_f32::<LittleEndian>(prepare_info.flattening_tolerance)?;
+
+    writer.write_u8(match batch.path_source {
+        PathSource::Draw => 0,
+        PathSource::Clip => 1,
+    })?;
+
+    match batch.clipped_path_info {
+        None => writer.write_u8(0),
+        Some(ref clipped_path_info) => {
+            writer.write_u8(1)?;
+            writer.write_u32::<LittleEndian>(clipped_path_info.clip_batch_id.0)?;
+            writer.write_u32::<LittleEndian>(clipped_path_info.clipped_path_count)?;
+            writer.write_u32::<LittleEndian>(clipped_path_info.max_clipped_tile_count)?;
+            match clipped_path_info.clips {
+                None => writer.write_u8(0),
+                Some(ref clips) => {
+                    writer.write_u8(1)?;
+                    write_clips(writer, clips)
+                }
+            }
+        }
+    }
+}
+
+fn read_tile_batch_data(reader: &mut &[u8]) -> Result<TileBatchDataD3D11, TraceError> {
+    let batch_id = TileBatchId(reader.read_u32::<LittleEndian>()?);
+    let path_count = reader.read_u32::<LittleEndian>()?;
+    let tile_count = reader.read_u32::<LittleEndian>()?;
+    let segment_count = reader.read_u32::<LittleEndian>()?;
+
+    let backdrop_count = read_count(reader, 12)?;
+    let mut backdrops = Vec::with_capacity(backdrop_count);
+    for _ in 0..backdrop_count {
+        backdrops.push(BackdropInfoD3D11 {
+            initial_backdrop: reader.read_i32::<LittleEndian>()?,
+            tile_x_offset: reader.read_i32::<LittleEndian>()?,
+            path_index: PathBatchIndex(reader.read_u32::<LittleEndian>()?),
+        });
+    }
+    let propagate_metadata_count = read_count(reader, 36)?;
+    let mut propagate_metadata = Vec::with_capacity(propagate_metadata_count);
+    for _ in 0..propagate_metadata_count {
+        propagate_metadata.push(PropagateMetadataD3D11 {
+            tile_rect: read_rect_i(reader)?,
+            tile_offset: reader.read_u32::<LittleEndian>()?,
+            path_index: PathBatchIndex(reader.read_u32::<LittleEndian>()?),
+            z_write: reader.read_u32::<LittleEndian>()?,
+            clip_path_index: PathBatchIndex(reader.read_u32::<LittleEndian>()?),
+            backdrop_offset: reader.read_u32::<LittleEndian>()?,
+            pad0: 0,
+            pad1: 0,
+            pad2: 0,
+        });
+    }
+    let dice_metadata_count = read_count(reader, 12)?;
+    let mut dice_metadata = Vec::with_capacity(dice_metadata_count);
+    for _ in 0..dice_metadata_count {
+        dice_metadata.push(DiceMetadataD3D11 {
+            global_path_id: PathId(reader.read_u32::<LittleEndian>()?),
+            first_global_segment_index: reader.read_u32::<LittleEndian>()?,
+            first_batch_segment_index: reader.read_u32::<LittleEndian>()?,
+            pad: 0,
+        });
+    }
+    let path_transform_count = read_count(reader, 24)?;
+    let mut path_transforms = Vec::with_capacity(path_transform_count);
+    for _ in 0..path_transform_count {
+        let mut values = [0.0; 6];
+        reader.read_f32_into::<LittleEndian>(&mut values)?;
+        path_transforms.push(PathTransformD3D11 {
+            matrix: [values[0], values[1], values[2], values[3]],
+            translation: [values[4], values[5]],
+            pad: [0.0; 2],
+        });
+    }
+    let tile_path_info_count = read_count(reader, 16)?;
+    let mut tile_path_info = Vec::with_capacity(tile_path_info_count);
+    for _ in 0..tile_path_info_count {
+        tile_path_info.push(TilePathInfoD3D11 {
+            tile_min_x: reader.read_i16::<LittleEndian>()?,
+            tile_min_y: reader.read_i16::<LittleEndian>()?,
+            tile_max_x: reader.read_i16::<LittleEndian>()?,
+            tile_max_y: reader.read_i16::<LittleEndian>()?,
+            first_tile_index: reader.read_u32::<LittleEndian>()?,
+            color: reader.read_u16::<LittleEndian>()?,
+            ctrl: reader.read_u8()?,
+            backdrop: reader.read_i8()?,
+        });
+    }
+    let transform = serialization::read_transform(reader)?;
+    let flattening_tolerance = reader.read_f32::<LittleEndian>()?;
+
+    let path_source = match reader.read_u8()? {
+        0 => PathSource::Draw,
+        1 => PathSource::Clip,
+        _ => return Err(TraceError::InvalidData("path source")),
+    };
+
+    let clipped_path_info = match reader.read_u8()? {
+        0 => None,
+        _ => {
+            let clip_batch_id = TileBatchId(reader.read_u32::<LittleEndian>()?);
+            let clipped_path_count = reader.read_u32::<LittleEndian>()?;
+            let max_clipped_tile_count = reader.read_u32::<LittleEndian>()?;
+            let clips = match reader.read_u8()? {
+                0 => None,
+                _ => Some(read_clips(reader)?),
+            };
+            Some(ClippedPathInfo {
+                clip_batch_id,
+                clipped_path_count,
+                max_clipped_tile_count,
+                clips,
+            })
+        }
+    };
+
+    Ok(TileBatchDataD3D11 {
+        batch_id,
+        path_count,
+        tile_count,
+        segment_count,
+        prepare_info: PrepareTilesInfoD3D11 {
+            backdrops,
+            propagate_metadata,
+            dice_metadata,
+            path_transforms,
+            tile_path_info,
+            transform,
+            flattening_tolerance,
+        },
+        path_source,
+        clipped_path_info,
+    })
+}
+
+fn write_segments<W>(writer: &mut W, segments: &SegmentsD3D11) -> io::Result<()> where W: Write {
+    serialization::write_len(writer, segments.points.len())?;
+    for &point in &segments.points {
+        serialization::write_vector_2f(writer, point)?;
+    }
+    serialization::write_len(writer, segments.indices.len())?;
+    for indices in &segments.indices {
+        writer.write_u32::<LittleEndian>(indices.first_point_index)?;
+        writer.write_u32::<LittleEndian>(indices.flags)?;
+    }
+    Ok(())
+}
+
+fn read_segments(reader: &mut &[u8]) -> Result<SegmentsD3D11, TraceError> {
+    let point_count = read_count(reader, 8)?;
+    let mut points = Vec::with_capacity(point_count);
+    for _ in 0..point_count {
+        points.push(serialization::read_vector_2f(reader)?);
+    }
+    let index_count = read_count(reader, 8)?;
+    let mut indices = Vec::with_capacity(index_count);
+    for _ in 0..index_count {
+        indices.push(SegmentIndicesD3D11 {
+            first_point_index: reader.read_u32::<LittleEndian>()?,
+            flags: reader.read_u32::<LittleEndian>()?,
+        });
+    }
+    Ok(SegmentsD3D11 { points, indices })
+}
+
+fn write_clips<W>(writer: &mut W, clips: &[Clip]) -> io::Result<()> where W: Write {
+    serialization::write_len(writer, clips.len())?;
+    for clip in clips {
+        writer.write_u32::<LittleEndian>(clip.dest_tile_id.0)?;
+        writer.write_i32::<LittleEndian>(clip.dest_backdrop)?;
+        writer.write_u32::<LittleEndian>(clip.src_tile_id.0)?;
+        writer.write_i32::<LittleEndian>(clip.src_backdrop)?;
+    }
+    Ok(())
+}
+
+fn read_clips(reader: &mut &[u8]) -> Result<Vec<Clip>, TraceError> {
+    let clip_count = read_count(reader, 16)?;
+    let mut clips = Vec::with_capacity(clip_count);
+    for _ in 0..clip_count {
+        clips.push(Clip {
+            dest_tile_id: AlphaTileId(reader.read_u32::<LittleEndian>()?),
+            dest_backdrop: reader.read_i32::<LittleEndian>()?,
+            src_tile_id: AlphaTileId(reader.read_u32::<LittleEndian>()?),
+            src_backdrop: reader.read_i32::<LittleEndian>()?,
+        });
+    }
+    Ok(clips)
+}
+
+fn write_tile_batch_texture<W>(writer: &mut W, texture: Option<TileBatchTexture>)
+                               -> io::Result<()> where W: Write {
+    match texture {
+        None => writer.write_u8(0),
+        Some(texture) => {
+            writer.write_u8(1)?;
+            writer.write_u32::<LittleEndian>(texture.page.0)?;
+            writer.write_u8(texture.sampling_flags.bits())?;
+            serialization::write_composite_op(writer, texture.composite_op)
+        }
+    }
+}
+
+fn read_tile_batch_texture(reader: &mut &[u8]) -> Result<Option<TileBatchTexture>, TraceError> {
+    if reader.read_u8()? == 0 {
+        return Ok(None);
+    }
+    let page = TexturePageId(reader.read_u32::<LittleEndian>()?);
+    let sampling_flags = TextureSamplingFlags::from_bits(reader.read_u8()?)
+        .ok_or(TraceError::InvalidData("texture sampling flags"))?;
+    let composite_op = serialization::read_composite_op(reader)?;
+    Ok(Some(TileBatchTexture { page, sampling_flags, composite_op }))
+}
+
+fn write_texture_atlas_region<W>(writer: &mut W, region: Option<TextureAtlasRegion>)
+                                 -> io::Result<()> where W: Write {
+    match region {
+        None => writer.write_u8(0),
+        Some(region) => {
+            writer.write_u8(1 | ((region.repeat_x as u8) << 1) | ((region.repeat_y as u8) << 2))?;
+            write_rect_i(writer, region.rect)
+        }
+    }
+}
+
+fn read_texture_atlas_region(reader: &mut &[u8])
+                             -> Result<Option<TextureAtlasRegion>, TraceError> {
+    let flags = reader.read_u8()?;
+    if flags == 0 {
+        return Ok(None);
+    }
+    if flags & !0x7 != 0 {
+        return Err(TraceError::InvalidData("texture atlas region"));
+    }
+    let rect = read_rect_i(reader)?;
+    Ok(Some(TextureAtlasRegion { rect, repeat_x: flags & 0x2 != 0, repeat_y: flags & 0x4 != 0 }))
+}
+
+fn write_filter<W>(writer: &mut W, filter: &Filter) -> io::Result<()> where W: Write {
+    match *filter {
+        Filter::None => writer.write_u8(0),
+        Filter::RadialGradient { line, radii, uv_origin } => {
+            writer.write_u8(1)?;
+            serialization::write_line_segment(writer, line)?;
+            writer.write_f32::<LittleEndian>(radii.x())?;
+            writer.write_f32::<LittleEndian>(radii.y())?;
+            serialization::write_vector_2f(writer, uv_origin)
+        }
+        Filter::PatternFilter(pattern_filter) => {
+            writer.write_u8(2)?;
+            serialization::write_pattern_filter(writer, Some(pattern_filter))
+        }
+    }
+}
+
+fn read_filter(reader: &mut &[u8]) -> Result<Filter, TraceError> {
+    match reader.read_u8()? {
+        0 => Ok(Filter::None),
+        1 => {
+            let line = serialization::read_line_segment(reader)?;
+            let radii = F32x2::new(reader.read_f32::<LittleEndian>()?,
+                                   reader.read_f32::<LittleEndian>()?);
+            let uv_origin = serialization::read_vector_2f(reader)?;
+            Ok(Filter::RadialGradient { line, radii, uv_origin })
+        }
+        2 => {
+            match serialization::read_pattern_filter(reader)? {
+                Some(pattern_filter) => Ok(Filter::PatternFilter(pattern_filter)),
+                None => Err(TraceError::InvalidData("filter")),
+            }
+        }
+        _ => Err(TraceError::InvalidData("filter")),
+    }
+}
+
+fn write_texture_location<W>(writer: &mut W, location: TextureLocation) -> io::Result<()>
+                             where W: Write {
+    writer.write_u32::<LittleEndian>(location.page.0)?;
+    write_rect_i(writer, location.rect)
+}
+
+fn read_texture_location(reader: &mut &[u8]) -> io::Result<TextureLocation> {
+    let page = TexturePageId(reader.read_u32::<LittleEndian>()?);
+    let rect = read_rect_i(reader)?;
+    Ok(TextureLocation { page, rect })
+}
+
+fn write_render_target_id<W>(writer: &mut W, id: RenderTargetId) -> io::Result<()>
+                             where W: Write {
+    writer.write_u32::<LittleEndian>(id.scene)?;
+    writer.write_u32::<LittleEndian>(id.render_target)
+}
+
+fn read_render_target_id(reader: &mut &[u8]) -> io::Result<RenderTargetId> {
+    let scene = reader.read_u32::<LittleEndian>()?;
+    let render_target = reader.read_u32::<LittleEndian>()?;
+    Ok(RenderTargetId { scene, render_target })
+}
+
+fn write_rect_i<W>(writer: &mut W, rect: RectI) -> io::Result<()> where W: Write {
+    serialization::write_vector_2i(writer, rect.origin())?;
+    serialization::write_vector_2i(writer, rect.size())
+}
+
+fn read_rect_i(reader: &mut &[u8]) -> io::Result<RectI> {
+    let origin = serialization::read_vector_2i(reader)?;
+    let size = serialization::read_vector_2i(reader)?;
+    Ok(RectI::new(origin, size))
+}
+
+fn write_vector_4f<W>(writer: &mut W, vector: Vector4F) -> io::Result<()> where W: Write {
+    for &value in &[vector.x(), vector.y(), vector.z(), vector.w()] {
+        writer.write_f32::<LittleEndian>(value)?;
+    }
+    Ok(())
+}
+
+fn read_vector_4f(reader: &mut &[u8]) -> io::Result<Vector4F> {
+    let x = reader.read_f32::<LittleEndian>()?;
+    let y = reader.read_f32::<LittleEndian>()?;
+    let z = reader.read_f32::<LittleEndian>()?;
+    let w = reader.read_f32::<LittleEndian>()?;
+    Ok(Vector4F::new(x, y, z, w))
+}
+
+// Reads an element count, rejecting counts that couldn't possibly fit in the rest of the payload
+// so that a corrupt trace can't trigger a huge allocation.
+fn read_count(reader: &mut &[u8], min_element_size: usize) -> Result<usize, TraceError> {
+    let count = serialization::read_len(reader)?;
+    if count.saturating_mul(min_element_size) > reader.len() {
+        return Err(TraceError::InvalidData("element count"));
+    }
+    Ok(count)
+}
+
+impl From<io::Error> for TraceError {
+    #[inline]
+    fn from(error: io::Error) -> TraceError {
+        TraceError::Io(error)
+    }
+}
+
+impl From<DeviceError> for TraceError {
+    #[inline]
+    fn from(error: DeviceError) -> TraceError {
+        TraceError::Device(error)
+    }
+}
+
+impl From<SceneDeserializeError> for TraceError {
+    fn from(error: SceneDeserializeError) -> TraceError {
+        match error {
+            SceneDeserializeError::Io(error) => TraceError::Io(error),
+            SceneDeserializeError::BadMagic => TraceError::BadMagic,
+            SceneDeserializeError::UnsupportedVersion(version) => {
+                TraceError::UnsupportedVersion(version)
+            }
+            SceneDeserializeError::InvalidData(what) => TraceError::InvalidData(what),
+        }
+    }
+}
+
+impl Display for TraceError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            TraceError::Io(ref error) => write!(formatter, "I/O error: {}", error),
+            TraceError::Device(ref error) => write!(formatter, "replay failed: {}", error),
+            TraceError::BadMagic => formatter.write_str("not a Pathfinder render command trace"),
+            TraceError::UnsupportedVersion(version) => {
+                write!(formatter,
+                       "unsupported trace format version {} (expected {})",
+                       version,
+                       TRACE_FORMAT_VERSION)
+            }
+            TraceError::InvalidData(what) => write!(formatter, "invalid {}", what),
+        }
+    }
+}
+
+impl Error for TraceError {}
+
+#[cfg(test)]
+mod test {
+    use super::{TraceError, TraceReader, TraceRecorder};
+    use crate::gpu_data::{Clip, DrawTileBatchD3D9, Fill, RenderCommand, TextureLocation};
+    use crate::gpu_data::{TexturePageId, TileBatchTexture};
+    use crate::options::{AntialiasingMode, RenderCommandListener};
+    use crate::paint::PaintCompositeOp;
+    use crate::tile_map::DenseTileMap;
+    use pathfinder_color::ColorU;
+    use pathfinder_content::effects::{BlendMode, BlurDirection, Filter, PatternFilter};
+    use pathfinder_content::effects::SubpixelLayout;
+    use pathfinder_content::pattern::{AlphaMode, Image, ImageFormat};
+    use pathfinder_content::render_target::RenderTargetId;
+    use pathfinder_geometry::rect::RectI;
+    use pathfinder_geometry::vector::{Vector4F, vec2i};
+    use pathfinder_gpu::TextureSamplingFlags;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn commands() -> Vec<RenderCommand> {
+        let render_target_id = RenderTargetId { scene: 3, render_target: 1 };
+        vec![
+            RenderCommand::Start {
+                path_count: 2,
+                bounding_quad: [Vector4F::new(1.0, 2.0, 0.0, 1.0); 4],
+                needs_readable_framebuffer: true,
+                damage_rects: vec![RectI::new(vec2i(16, 32), vec2i(48, 16))],
+                antialiasing_mode: AntialiasingMode::Supersampled(4),
+                subpixel_layout: SubpixelLayout::VBGR,
+            },
+            RenderCommand::UploadTexelData {
+                texels: Arc::new(vec![ColorU::new(1, 2, 3, 4); 6]),
+                location: TextureLocation {
+                    page: TexturePageId(2),
+                    rect: RectI::new(vec2i(1, 1), vec2i(3, 2)),
+                },
+            },
+            RenderCommand::UploadImage {
+                image: Image::from_data(vec2i(3, 1),
+                                        ImageFormat::BGRA8,
+                                        AlphaMode::Premultiplied,
+                                        Arc::new((0..12).collect())),
+                location: TextureLocation {
+                    page: TexturePageId(1),
+                    rect: RectI::new(vec2i(4, 0), vec2i(3, 1)),
+                },
+            },
+            RenderCommand::CopyTexelData {
+                src: TextureLocation {
+                    page: TexturePageId(1),
+                    rect: RectI::new(vec2i(0, 0), vec2i(64, 64)),
+                },
+                dest: TextureLocation {
+                    page: TexturePageId(0),
+                    rect: RectI::new(vec2i(128, 0), vec2i(64, 64)),
+                },
+            },
+            RenderCommand::DeclareRenderTarget {
+                id: render_target_id,
+                location: TextureLocation {
+                    page: TexturePageId(0),
+                    rect: RectI::new(vec2i(8, 8), vec2i(100, 50)),
+                },
+                padding: 8,
+            },
+            RenderCommand::PushRenderTarget(render_target_id),
+            RenderCommand::AddFillsD3D9(vec![Fill::default(); 3]),
+            RenderCommand::FlushFillsD3D9,
+            RenderCommand::DrawTilesD3D9(DrawTileBatchD3D9 {
+                tiles: vec![],
+                clips: vec![Clip::default()],
+                z_buffer_data: DenseTileMap { data: vec![0, 1, 2, 3], rect: RectI::default() },
+                color_texture: Some(TileBatchTexture {
+                    page: TexturePageId(2),
+                    sampling_flags: TextureSamplingFlags::REPEAT_U,
+                    composite_op: PaintCompositeOp::DestIn,
+                }),
+                paint_mask_texture: Some(TileBatchTexture {
+                    page: TexturePageId(0),
+                    sampling_flags: TextureSamplingFlags::NEAREST_MAG,
+                    composite_op: PaintCompositeOp::DestIn,
+                }),
+                filter: Filter::PatternFilter(PatternFilter::Blur {
+                    direction: BlurDirection::X,
+                    sigma: 1.5,
+                }),
+                blend_mode: BlendMode::Multiply,
+            }),
+            RenderCommand::PopRenderTarget,
+            RenderCommand::Finish { cpu_build_time: Duration::from_micros(1234) },
+        ]
+    }
+
+    fn record(commands: Vec<RenderCommand>) -> Vec<u8> {
+        let forwarded = Arc::new(Mutex::new(0));
+        let recorder = TraceRecorder::new(vec![]).unwrap();
+        {
+            let forwarded_in_listener = forwarded.clone();
+            let listener = recorder.listener(RenderCommandListener::new(Box::new(move |_| {
+                *forwarded_in_listener.lock().unwrap() += 1;
+            })));
+            let command_count = commands.len();
+            for command in commands {
+                listener.send(command);
+            }
+            assert_eq!(*forwarded.lock().unwrap(), command_count);
+        }
+        recorder.finish().unwrap();
+        let state = recorder.state.lock().unwrap();
+        state.writer.clone()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bytes = record(commands());
+
+        let mut reader = TraceReader::new(&bytes[..]).unwrap();
+        let mut decoded = vec![];
+        while let Some(command) = reader.next_command().unwrap() {
+            decoded.push(command);
+        }
+        assert_eq!(decoded.len(), commands().len());
+        assert_eq!(reader.bytes_read(), bytes.len() as u64);
+
+        // Re-recording the decoded commands must reproduce the original trace exactly.
+        assert_eq!(record(decoded), bytes);
+    }
+
+    #[test]
+    fn test_truncated_trace() {
+        let bytes = record(commands());
+        let mut reader = TraceReader::new(&bytes[0..(bytes.len() - 1)]).unwrap();
+        loop {
+            match reader.next_command() {
+                Ok(Some(_)) => {}
+                Ok(None) => panic!("truncated trace read to completion"),
+                Err(TraceError::Io(_)) => break,
+                Err(error) => panic!("unexpected error: {}", error),
+            }
+        }
+
+        // A command that claims a huge payload must fail without allocating room for it.
+        let mut bytes = record(vec![]);
+        bytes.push(0);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut reader = TraceReader::new(&bytes[..]).unwrap();
+        match reader.next_command() {
+            Err(TraceError::Io(_)) => {}
+            _ => panic!("expected the payload to be truncated"),
+        }
+    }
+}
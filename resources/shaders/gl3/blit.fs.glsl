@@ -20,12 +20,77 @@ precision highp float;
 
 uniform sampler2D uSrc;
 
+
+uniform int uSrcColorSpace;
+uniform int uDestColorSpace;
+
+uniform int uAlphaMode;
+
 in vec2 vTexCoord;
 
 out vec4 oFragColor;
 
+
+
+
+
+
+
+
+
+
+
+
+
+
+vec3 decodeSRGB(vec3 color){
+    vec3 magnitude = abs(color);
+    vec3 decoded = mix(magnitude / 12.92,
+                      pow((magnitude + 0.055)/ 1.055, vec3(2.4)),
+                      greaterThan(magnitude, vec3(0.04045)));
+    return sign(color)* decoded;
+}
+
+vec3 encodeSRGB(vec3 color){
+    vec3 magnitude = abs(color);
+    vec3 encoded = mix(magnitude * 12.92,
+                       1.055 * pow(magnitude, vec3(1.0 / 2.4))- 0.055,
+                       greaterThan(magnitude, vec3(0.0031308)));
+    return sign(color)* encoded;
+}
+
+vec3 convertColorSpace(vec3 color){
+    if(uSrcColorSpace != 2)
+        color = decodeSRGB(color);
+
+    if(uSrcColorSpace == 1){
+        color = color * mat3(1.2249401, - 0.2249404, 0.0000000,
+                             - 0.0420569, 1.0420571, 0.0000000,
+                             - 0.0196376, - 0.0786361, 1.0982735);
+    }
+    if(uDestColorSpace == 1){
+        color = color * mat3(0.8224621, 0.1775380, 0.0000000,
+                             0.0331941, 0.9668058, 0.0000000,
+                             0.0170827, 0.0723974, 0.9105199);
+    }
+    if(uDestColorSpace != 2)
+        color = encodeSRGB(color);
+    return color;
+}
+
 void main(){
     vec4 color = texture(uSrc, vTexCoord);
+    if(uAlphaMode == 3){
+        color = vec4(vec3(1.0), color . r);
+    } else if(uAlphaMode == 0){
+        if(uSrcColorSpace != uDestColorSpace && color . a > 0.0)
+            color . rgb = convertColorSpace(color . rgb / color . a)* color . a;
+    } else {
+        if(uAlphaMode == 2 && color . a > 0.0)
+            color . rgb /= color . a;
+        if(uSrcColorSpace != uDestColorSpace)
+            color . rgb = convertColorSpace(color . rgb);
+    }
     oFragColor = color;
 }
 
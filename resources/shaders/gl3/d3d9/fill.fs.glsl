@@ -50,14 +50,48 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT){
 }
 
 
+vec4 computeSampleCoverage(vec2 from, vec2 to, vec2 sampleOffset){
+    from -= sampleOffset;
+    to -= sampleOffset;
+
+
+    if((from . x <= 0.0)==(to . x <= 0.0))
+        return vec4(0.0);
+
+    float y = mix(from . y, to . y, from . x /(from . x - to . x));
+    return step(vec4(y), vec4(0.0, 1.0, 2.0, 3.0))* sign(from . x - to . x);
+}
+
+
+
+
+
+vec4 computeSupersampledCoverage(vec2 from, vec2 to, int sampleCount){
+    vec4 coverage = vec4(0.0);
+    for(int sampleIndex = 0;sampleIndex < sampleCount;sampleIndex ++){
+        int sampleRow = 0;
+        for(int bit = 1;bit < sampleCount;bit <<= 1)
+            sampleRow =(sampleRow << 1)|((sampleIndex & bit)!= 0 ? 1 : 0);
+        vec2 sampleOffset =(vec2(sampleIndex, sampleRow)+ 0.5)/ float(sampleCount)- 0.5;
+        coverage += computeSampleCoverage(from, to, sampleOffset);
+    }
+    return coverage / float(sampleCount);
+}
+
+
 uniform sampler2D uAreaLUT;
 
+uniform int uSampleCount;
+
 in vec2 vFrom;
 in vec2 vTo;
 
 out vec4 oFragColor;
 
 void main(){
-    oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    if(uSampleCount == 0)
+        oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    else
+        oFragColor = computeSupersampledCoverage(vFrom, vTo, uSampleCount);
 }
 
@@ -90,6 +90,11 @@ precision highp float;
 
 
 
+
+
+
+
+
 
 
 
@@ -102,6 +107,26 @@ vec4 sampleColor(sampler2D colorTexture, vec2 colorTexCoord){
 
 
 
+
+vec2 wrapColorTexCoord(vec2 colorTexCoord, vec4 colorTexRect, vec2 colorTextureSize){
+    vec2 size = abs(colorTexRect . zw);
+    if(size . x == 0.0 || size . y == 0.0)
+        return colorTexCoord;
+
+
+    vec4 texRect = colorTexRect / colorTextureSize . xyxy;
+    size /= colorTextureSize;
+
+    vec2 offset = colorTexCoord - texRect . xy;
+    offset = mix(offset, mod(offset, size), greaterThan(colorTexRect . zw, vec2(0.0)));
+
+
+    vec2 halfTexel = vec2(0.5)/ colorTextureSize;
+    return texRect . xy + clamp(offset, halfTexel, size - halfTexel);
+}
+
+
+
 vec4 combineColor0(vec4 destColor, vec4 srcColor, int op){
     switch(op){
     case 0x1 :
@@ -114,8 +139,8 @@ vec4 combineColor0(vec4 destColor, vec4 srcColor, int op){
 
 
 
-float filterTextSample1Tap(float offset, sampler2D colorTexture, vec2 colorTexCoord){
-    return texture(colorTexture, colorTexCoord + vec2(offset, 0.0)). r;
+float filterTextSample1Tap(vec2 offset, sampler2D colorTexture, vec2 colorTexCoord){
+    return texture(colorTexture, colorTexCoord + offset). r;
 }
 
 
@@ -125,14 +150,14 @@ void filterTextSample9Tap(out vec4 outAlphaLeft,
                           sampler2D colorTexture,
                           vec2 colorTexCoord,
                           vec4 kernel,
-                          float onePixel){
+                          vec2 onePixel){
     bool wide = kernel . x > 0.0;
     outAlphaLeft =
         vec4(wide ? filterTextSample1Tap(- 4.0 * onePixel, colorTexture, colorTexCoord): 0.0,
              filterTextSample1Tap(- 3.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(- 2.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(- 1.0 * onePixel, colorTexture, colorTexCoord));
-    outAlphaCenter = filterTextSample1Tap(0.0, colorTexture, colorTexCoord);
+    outAlphaCenter = filterTextSample1Tap(vec2(0.0), colorTexture, colorTexCoord);
     outAlphaRight =
         vec4(filterTextSample1Tap(1.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(2.0 * onePixel, colorTexture, colorTexCoord),
@@ -174,6 +199,10 @@ vec4 filterText(vec2 colorTexCoord,
     bool gammaCorrectionEnabled = filterParams2 . a != 0.0;
 
 
+    bool bgrLayout = filterParams1 . a == 1.0 || filterParams1 . a == 3.0;
+    bool verticalLayout = filterParams1 . a >= 2.0;
+
+
     vec3 alpha;
     if(kernel . w == 0.0){
         alpha = texture(colorTexture, colorTexCoord). rrr;
@@ -186,7 +215,8 @@ vec4 filterText(vec2 colorTexCoord,
                              colorTexture,
                              colorTexCoord,
                              kernel,
-                             1.0 / colorTextureSize . x);
+                             verticalLayout ? vec2(0.0, 1.0 / colorTextureSize . y):
+                                              vec2(1.0 / colorTextureSize . x, 0.0));
 
         float r = filterTextConvolve7Tap(alphaLeft, vec3(alphaCenter, alphaRight . xy), kernel);
         float g = filterTextConvolve7Tap(vec4(alphaLeft . yzw, alphaCenter), alphaRight . xyz, kernel);
@@ -194,7 +224,7 @@ vec4 filterText(vec2 colorTexCoord,
                                          alphaRight . yzw,
                                          kernel);
 
-        alpha = vec3(r, g, b);
+        alpha = bgrLayout ? vec3(b, g, r): vec3(r, g, b);
     }
 
 
@@ -559,13 +589,35 @@ float sampleMask(float maskAlpha,
 
 
 
+
+
+
+
+float samplePaintMask(sampler2D paintMaskTexture,
+                      vec2 paintMaskTextureSize,
+                      vec2 paintMaskTexCoord,
+                      vec4 paintMaskTexRect,
+                      int paintMaskCtrl){
+    vec2 texCoord = wrapColorTexCoord(paintMaskTexCoord, paintMaskTexRect, paintMaskTextureSize);
+    vec4 texel = texture(paintMaskTexture, texCoord);
+    if((paintMaskCtrl & 0x3)!= 0x2)
+        return texel . a;
+
+
+    if((paintMaskCtrl & 0x4)!= 0)
+        texel . rgb = texel . a > 0.0 ? texel . rgb / texel . a : vec3(0.0);
+    return dot(texel . rgb, vec3(0.2125, 0.7154, 0.0721))* texel . a;
+}
+
 vec4 calculateColor(vec2 fragCoord,
                     sampler2D colorTexture0,
                     sampler2D maskTexture0,
+                    sampler2D paintMaskTexture,
                     sampler2D destTexture,
                     sampler2D gammaLUT,
                     vec2 colorTextureSize0,
                     vec2 maskTextureSize0,
+                    vec2 paintMaskTextureSize,
                     vec4 filterParams0,
                     vec4 filterParams1,
                     vec4 filterParams2,
@@ -573,6 +625,9 @@ vec4 calculateColor(vec2 fragCoord,
                     int ctrl,
                     vec3 maskTexCoord0,
                     vec2 colorTexCoord0,
+                    vec4 colorTexRect0,
+                    vec2 paintMaskTexCoord,
+                    vec4 paintMaskTexRect,
                     vec4 baseColor,
                     int tileCtrl){
 
@@ -581,12 +636,23 @@ vec4 calculateColor(vec2 fragCoord,
     maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
 
 
+    int paintMaskCtrl = ctrl >> 12;
+    if((paintMaskCtrl & 0x3)!= 0){
+        maskAlpha *= samplePaintMask(paintMaskTexture,
+                                     paintMaskTextureSize,
+                                     paintMaskTexCoord,
+                                     paintMaskTexRect,
+                                     paintMaskCtrl);
+    }
+
+
     vec4 color = baseColor;
     int color0Combine =(ctrl >> 6)&
                                        0x3;
     if(color0Combine != 0){
         int color0Filter =(ctrl >> 4)& 0x3;
-        vec4 color0 = filterColor(colorTexCoord0,
+        vec2 colorTexCoord = wrapColorTexCoord(colorTexCoord0, colorTexRect0, colorTextureSize0);
+        vec4 color0 = filterColor(colorTexCoord,
                                   colorTexture0,
                                   gammaLUT,
                                   colorTextureSize0,
@@ -614,19 +680,24 @@ vec4 calculateColor(vec2 fragCoord,
 
 uniform sampler2D uColorTexture0;
 uniform sampler2D uMaskTexture0;
+uniform sampler2D uPaintMaskTexture;
 uniform sampler2D uDestTexture;
 uniform sampler2D uGammaLUT;
 uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
+uniform vec2 uPaintMaskTextureSize;
 uniform vec2 uFramebufferSize;
 
 in vec3 vMaskTexCoord0;
 in vec2 vColorTexCoord0;
+in vec4 vColorTexRect0;
 in vec4 vBaseColor;
 in float vTileCtrl;
 in vec4 vFilterParams0;
 in vec4 vFilterParams1;
 in vec4 vFilterParams2;
+in vec2 vPaintMaskTexCoord;
+in vec4 vPaintMaskTexRect;
 in float vCtrl;
 
 out vec4 oFragColor;
@@ -639,10 +710,12 @@ void main(){
     oFragColor = calculateColor(gl_FragCoord . xy,
                                 uColorTexture0,
                                 uMaskTexture0,
+                                uPaintMaskTexture,
                                 uDestTexture,
                                 uGammaLUT,
                                 uColorTextureSize0,
                                 uMaskTextureSize0,
+                                uPaintMaskTextureSize,
                                 vFilterParams0,
                                 vFilterParams1,
                                 vFilterParams2,
@@ -650,6 +723,9 @@ void main(){
                                 int(vCtrl),
                                 vMaskTexCoord0,
                                 vColorTexCoord0,
+                                vColorTexRect0,
+                                vPaintMaskTexCoord,
+                                vPaintMaskTexRect,
                                 vBaseColor,
                                 int(vTileCtrl));
 }
@@ -41,6 +41,7 @@ uniform vec2 uTranslation;
 uniform int uPathCount;
 uniform int uLastBatchSegmentIndex;
 uniform int uMaxMicrolineCount;
+uniform float uTolerance;
 
 layout(std430, binding = 0)buffer bComputeIndirectParams {
 
@@ -67,6 +68,14 @@ layout(std430, binding = 3)buffer bInputIndices {
     restrict readonly uvec2 iInputIndices[];
 };
 
+
+
+
+
+layout(std430, binding = 5)buffer bPathTransforms {
+    restrict readonly vec4 iPathTransforms[];
+};
+
 layout(std430, binding = 4)buffer bMicrolines {
 
 
@@ -96,7 +105,7 @@ bool curveIsFlat(vec4 baseline, vec4 ctrl){
     vec4 uv = vec4(3.0)* ctrl - vec4(2.0)* baseline - baseline . zwxy;
     uv *= uv;
     uv = max(uv, uv . zwxy);
-    return uv . x + uv . y <= 16.0 * 0.25 * 0.25;
+    return uv . x + uv . y <= 16.0 * uTolerance * uTolerance;
 }
 
 void subdivideCurve(vec4 baseline,
@@ -127,8 +136,8 @@ vec2 sampleLine(vec4 line, float t){
     return mix(line . xy, line . zw, t);
 }
 
-vec2 getPoint(uint pointIndex){
-    return uTransform * iPoints[pointIndex]+ uTranslation;
+vec2 getPoint(uint pointIndex, mat2 pathTransform, vec2 pathTranslation){
+    return uTransform *(pathTransform * iPoints[pointIndex]+ pathTranslation)+ uTranslation;
 }
 
 void main(){
@@ -159,6 +168,10 @@ void main(){
     uint globalSegmentIndex = batchSegmentIndex - firstBatchSegmentIndexInPath +
         firstGlobalSegmentIndexInPath;
 
+    vec4 pathTransformColumns = iPathTransforms[batchPathIndex * 2 + 0];
+    mat2 pathTransform = mat2(pathTransformColumns . xy, pathTransformColumns . zw);
+    vec2 pathTranslation = iPathTransforms[batchPathIndex * 2 + 1]. xy;
+
     uvec2 inputIndices = iInputIndices[globalSegmentIndex];
     uint fromPointIndex = inputIndices . x, flagsPathIndex = inputIndices . y;
 
@@ -170,7 +183,8 @@ void main(){
     else
         toPointIndex += 1;
 
-    vec4 baseline = vec4(getPoint(fromPointIndex), getPoint(toPointIndex));
+    vec4 baseline = vec4(getPoint(fromPointIndex, pathTransform, pathTranslation),
+                         getPoint(toPointIndex, pathTransform, pathTranslation));
 
 
 
@@ -181,16 +195,16 @@ void main(){
     bool isCurve =(flagsPathIndex &(0x40000000u |
                                                                         0x80000000u))!= 0;
     if(isCurve){
-        vec2 ctrl0 = getPoint(fromPointIndex + 1);
+        vec2 ctrl0 = getPoint(fromPointIndex + 1, pathTransform, pathTranslation);
         if((flagsPathIndex & 0x80000000u)!= 0){
             vec2 ctrl0_2 = ctrl0 * vec2(2.0);
             ctrl =(baseline +(ctrl0 * vec2(2.0)). xyxy)* vec4(1.0 / 3.0);
         } else {
-            ctrl = vec4(ctrl0, getPoint(fromPointIndex + 2));
+            ctrl = vec4(ctrl0, getPoint(fromPointIndex + 2, pathTransform, pathTranslation));
         }
         vec2 bound = vec2(6.0)* max(abs(ctrl . zw - 2.0 * ctrl . xy + baseline . xy),
                                      abs(baseline . zw - 2.0 * ctrl . zw + ctrl . xy));
-        segmentCountF = sqrt(length(bound)/(8.0 * 0.25));
+        segmentCountF = sqrt(length(bound)/(8.0 * uTolerance));
     } else {
         segmentCountF = length(baseline . zw - baseline . xy)/ 16.0;
     }
@@ -50,6 +50,35 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT){
 }
 
 
+vec4 computeSampleCoverage(vec2 from, vec2 to, vec2 sampleOffset){
+    from -= sampleOffset;
+    to -= sampleOffset;
+
+
+    if((from . x <= 0.0)==(to . x <= 0.0))
+        return vec4(0.0);
+
+    float y = mix(from . y, to . y, from . x /(from . x - to . x));
+    return step(vec4(y), vec4(0.0, 1.0, 2.0, 3.0))* sign(from . x - to . x);
+}
+
+
+
+
+
+vec4 computeSupersampledCoverage(vec2 from, vec2 to, int sampleCount){
+    vec4 coverage = vec4(0.0);
+    for(int sampleIndex = 0;sampleIndex < sampleCount;sampleIndex ++){
+        int sampleRow = 0;
+        for(int bit = 1;bit < sampleCount;bit <<= 1)
+            sampleRow =(sampleRow << 1)|((sampleIndex & bit)!= 0 ? 1 : 0);
+        vec2 sampleOffset =(vec2(sampleIndex, sampleRow)+ 0.5)/ float(sampleCount)- 0.5;
+        coverage += computeSampleCoverage(from, to, sampleOffset);
+    }
+    return coverage / float(sampleCount);
+}
+
+
 layout(local_size_x = 16, local_size_y = 4)in;
 
 
@@ -59,6 +88,8 @@ layout(local_size_x = 16, local_size_y = 4)in;
 
 layout(rgba8)uniform image2D uDest;
 uniform sampler2D uAreaLUT;
+
+uniform int uSampleCount;
 uniform ivec2 uAlphaTileRange;
 
 layout(std430, binding = 0)buffer bFills {
@@ -100,7 +131,13 @@ vec4 accumulateCoverageForFillList(int fillIndex, ivec2 tileSubCoord){
         vec4 lineSegment = vec4(fillFrom & 0xffff, fillFrom >> 16,
                                 fillTo & 0xffff, fillTo >> 16)/ 256.0;
         lineSegment -= tileFragCoord . xyxy;
-        coverages += computeCoverage(lineSegment . xy, lineSegment . zw, uAreaLUT);
+        if(uSampleCount == 0){
+            coverages += computeCoverage(lineSegment . xy, lineSegment . zw, uAreaLUT);
+        } else {
+            coverages += computeSupersampledCoverage(lineSegment . xy,
+                                                     lineSegment . zw,
+                                                     uSampleCount);
+        }
         fillIndex = int(iFills[fillIndex * 3 + 2]);
         iteration ++;
     } while(fillIndex >= 0 && iteration < 1024);
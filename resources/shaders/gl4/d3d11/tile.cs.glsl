@@ -92,6 +92,11 @@ layout(local_size_x = 16, local_size_y = 4)in;
 
 
 
+
+
+
+
+
 
 
 
@@ -104,6 +109,26 @@ vec4 sampleColor(sampler2D colorTexture, vec2 colorTexCoord){
 
 
 
+
+vec2 wrapColorTexCoord(vec2 colorTexCoord, vec4 colorTexRect, vec2 colorTextureSize){
+    vec2 size = abs(colorTexRect . zw);
+    if(size . x == 0.0 || size . y == 0.0)
+        return colorTexCoord;
+
+
+    vec4 texRect = colorTexRect / colorTextureSize . xyxy;
+    size /= colorTextureSize;
+
+    vec2 offset = colorTexCoord - texRect . xy;
+    offset = mix(offset, mod(offset, size), greaterThan(colorTexRect . zw, vec2(0.0)));
+
+
+    vec2 halfTexel = vec2(0.5)/ colorTextureSize;
+    return texRect . xy + clamp(offset, halfTexel, size - halfTexel);
+}
+
+
+
 vec4 combineColor0(vec4 destColor, vec4 srcColor, int op){
     switch(op){
     case 0x1 :
@@ -116,8 +141,8 @@ vec4 combineColor0(vec4 destColor, vec4 srcColor, int op){
 
 
 
-float filterTextSample1Tap(float offset, sampler2D colorTexture, vec2 colorTexCoord){
-    return texture(colorTexture, colorTexCoord + vec2(offset, 0.0)). r;
+float filterTextSample1Tap(vec2 offset, sampler2D colorTexture, vec2 colorTexCoord){
+    return texture(colorTexture, colorTexCoord + offset). r;
 }
 
 
@@ -127,14 +152,14 @@ void filterTextSample9Tap(out vec4 outAlphaLeft,
                           sampler2D colorTexture,
                           vec2 colorTexCoord,
                           vec4 kernel,
-                          float onePixel){
+                          vec2 onePixel){
     bool wide = kernel . x > 0.0;
     outAlphaLeft =
         vec4(wide ? filterTextSample1Tap(- 4.0 * onePixel, colorTexture, colorTexCoord): 0.0,
              filterTextSample1Tap(- 3.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(- 2.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(- 1.0 * onePixel, colorTexture, colorTexCoord));
-    outAlphaCenter = filterTextSample1Tap(0.0, colorTexture, colorTexCoord);
+    outAlphaCenter = filterTextSample1Tap(vec2(0.0), colorTexture, colorTexCoord);
     outAlphaRight =
         vec4(filterTextSample1Tap(1.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(2.0 * onePixel, colorTexture, colorTexCoord),
@@ -176,6 +201,10 @@ vec4 filterText(vec2 colorTexCoord,
     bool gammaCorrectionEnabled = filterParams2 . a != 0.0;
 
 
+    bool bgrLayout = filterParams1 . a == 1.0 || filterParams1 . a == 3.0;
+    bool verticalLayout = filterParams1 . a >= 2.0;
+
+
     vec3 alpha;
     if(kernel . w == 0.0){
         alpha = texture(colorTexture, colorTexCoord). rrr;
@@ -188,7 +217,8 @@ vec4 filterText(vec2 colorTexCoord,
                              colorTexture,
                              colorTexCoord,
                              kernel,
-                             1.0 / colorTextureSize . x);
+                             verticalLayout ? vec2(0.0, 1.0 / colorTextureSize . y):
+                                              vec2(1.0 / colorTextureSize . x, 0.0));
 
         float r = filterTextConvolve7Tap(alphaLeft, vec3(alphaCenter, alphaRight . xy), kernel);
         float g = filterTextConvolve7Tap(vec4(alphaLeft . yzw, alphaCenter), alphaRight . xyz, kernel);
@@ -196,7 +226,7 @@ vec4 filterText(vec2 colorTexCoord,
                                          alphaRight . yzw,
                                          kernel);
 
-        alpha = vec3(r, g, b);
+        alpha = bgrLayout ? vec3(b, g, r): vec3(r, g, b);
     }
 
 
@@ -561,13 +591,35 @@ float sampleMask(float maskAlpha,
 
 
 
+
+
+
+
+float samplePaintMask(sampler2D paintMaskTexture,
+                      vec2 paintMaskTextureSize,
+                      vec2 paintMaskTexCoord,
+                      vec4 paintMaskTexRect,
+                      int paintMaskCtrl){
+    vec2 texCoord = wrapColorTexCoord(paintMaskTexCoord, paintMaskTexRect, paintMaskTextureSize);
+    vec4 texel = texture(paintMaskTexture, texCoord);
+    if((paintMaskCtrl & 0x3)!= 0x2)
+        return texel . a;
+
+
+    if((paintMaskCtrl & 0x4)!= 0)
+        texel . rgb = texel . a > 0.0 ? texel . rgb / texel . a : vec3(0.0);
+    return dot(texel . rgb, vec3(0.2125, 0.7154, 0.0721))* texel . a;
+}
+
 vec4 calculateColor(vec2 fragCoord,
                     sampler2D colorTexture0,
                     sampler2D maskTexture0,
+                    sampler2D paintMaskTexture,
                     sampler2D destTexture,
                     sampler2D gammaLUT,
                     vec2 colorTextureSize0,
                     vec2 maskTextureSize0,
+                    vec2 paintMaskTextureSize,
                     vec4 filterParams0,
                     vec4 filterParams1,
                     vec4 filterParams2,
@@ -575,6 +627,9 @@ vec4 calculateColor(vec2 fragCoord,
                     int ctrl,
                     vec3 maskTexCoord0,
                     vec2 colorTexCoord0,
+                    vec4 colorTexRect0,
+                    vec2 paintMaskTexCoord,
+                    vec4 paintMaskTexRect,
                     vec4 baseColor,
                     int tileCtrl){
 
@@ -583,12 +638,23 @@ vec4 calculateColor(vec2 fragCoord,
     maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
 
 
+    int paintMaskCtrl = ctrl >> 12;
+    if((paintMaskCtrl & 0x3)!= 0){
+        maskAlpha *= samplePaintMask(paintMaskTexture,
+                                     paintMaskTextureSize,
+                                     paintMaskTexCoord,
+                                     paintMaskTexRect,
+                                     paintMaskCtrl);
+    }
+
+
     vec4 color = baseColor;
     int color0Combine =(ctrl >> 6)&
                                        0x3;
     if(color0Combine != 0){
         int color0Filter =(ctrl >> 4)& 0x3;
-        vec4 color0 = filterColor(colorTexCoord0,
+        vec2 colorTexCoord = wrapColorTexCoord(colorTexCoord0, colorTexRect0, colorTextureSize0);
+        vec4 color0 = filterColor(colorTexCoord,
                                   colorTexture0,
                                   gammaLUT,
                                   colorTextureSize0,
@@ -633,13 +699,16 @@ void computeTileVaryings(vec2 position,
                          sampler2D textureMetadata,
                          ivec2 textureMetadataSize,
                          out vec2 outColorTexCoord0,
+                         out vec4 outColorTexRect0,
                          out vec4 outBaseColor,
                          out vec4 outFilterParams0,
                          out vec4 outFilterParams1,
                          out vec4 outFilterParams2,
+                         out vec2 outPaintMaskTexCoord,
+                         out vec4 outPaintMaskTexRect,
                          out int outCtrl){
     vec2 metadataScale = vec2(1.0)/ vec2(textureMetadataSize);
-    vec2 metadataEntryCoord = vec2(colorEntry % 128 * 8, colorEntry / 128);
+    vec2 metadataEntryCoord = vec2(colorEntry % 128 * 10, colorEntry / 128);
     vec4 colorTexMatrix0 = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 0);
     vec4 colorTexOffsets = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 1);
     vec4 baseColor = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 2);
@@ -647,7 +716,13 @@ void computeTileVaryings(vec2 position,
     vec4 filterParams1 = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 4);
     vec4 filterParams2 = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 5);
     vec4 extra = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 6);
+    vec4 colorTexRect0 = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 7);
+    vec4 paintMaskMatrix = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 8);
+    vec4 paintMaskRect = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 9);
     outColorTexCoord0 = mat2(colorTexMatrix0)* position + colorTexOffsets . xy;
+    outColorTexRect0 = colorTexRect0;
+    outPaintMaskTexCoord = mat2(paintMaskMatrix)* position + colorTexOffsets . zw;
+    outPaintMaskTexRect = paintMaskRect;
     outBaseColor = baseColor;
     outFilterParams0 = filterParams0;
     outFilterParams1 = filterParams1;
@@ -673,10 +748,12 @@ uniform sampler2D uZBuffer;
 uniform ivec2 uZBufferSize;
 uniform sampler2D uColorTexture0;
 uniform sampler2D uMaskTexture0;
+uniform sampler2D uPaintMaskTexture;
 uniform sampler2D uDestTexture;
 uniform sampler2D uGammaLUT;
 uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
+uniform vec2 uPaintMaskTextureSize;
 uniform vec2 uFramebufferSize;
 uniform ivec2 uFramebufferTileSize;
 layout(rgba8)uniform image2D uDestImage;
@@ -749,26 +826,34 @@ void main(){
             vec3 maskTexCoord0 = vec3(vec2(ivec2(maskTileCoord)+ tileSubCoord), backdrop);
 
             vec2 colorTexCoord0;
+            vec4 colorTexRect0;
             vec4 baseColor, filterParams0, filterParams1, filterParams2;
+            vec2 paintMaskTexCoord;
+            vec4 paintMaskTexRect;
             int ctrl;
             computeTileVaryings(fragCoord,
                                 int(colorEntry),
                                 uTextureMetadata,
                                 uTextureMetadataSize,
                                 colorTexCoord0,
+                                colorTexRect0,
                                 baseColor,
                                 filterParams0,
                                 filterParams1,
                                 filterParams2,
+                                paintMaskTexCoord,
+                                paintMaskTexRect,
                                 ctrl);
 
             vec4 srcColor = calculateColor(fragCoord,
                                            uColorTexture0,
                                            uMaskTexture0,
+                                           uPaintMaskTexture,
                                            uDestTexture,
                                            uGammaLUT,
                                            uColorTextureSize0,
                                            uMaskTextureSize0,
+                                           uPaintMaskTextureSize,
                                            filterParams0,
                                            filterParams1,
                                            filterParams2,
@@ -776,6 +861,9 @@ void main(){
                                            ctrl,
                                            maskTexCoord0,
                                            colorTexCoord0,
+                                           colorTexRect0,
+                                           paintMaskTexCoord,
+                                           paintMaskTexRect,
                                            baseColor,
                                            tileCtrl);
 
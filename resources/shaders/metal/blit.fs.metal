@@ -14,10 +14,80 @@ struct main0_in
     float2 vTexCoord [[user(locn0)]];
 };
 
-fragment main0_out main0(main0_in in [[stage_in]], texture2d<float> uSrc [[texture(0)]], sampler uSrcSmplr [[sampler(0)]])
+static inline __attribute__((always_inline))
+float3 decodeSRGB(thread const float3& color)
+{
+    float3 magnitude = abs(color);
+    float3 decoded = select(magnitude / float3(12.9200000762939453125), pow((magnitude + float3(0.054999999701976776123046875)) / float3(1.05499994754791259765625), float3(2.400000095367431640625)), magnitude > float3(0.040449999272823333740234375));
+    return sign(color) * decoded;
+}
+
+static inline __attribute__((always_inline))
+float3 encodeSRGB(thread const float3& color)
+{
+    float3 magnitude = abs(color);
+    float3 encoded = select(magnitude * 12.9200000762939453125, (pow(magnitude, float3(0.4166666567325592041015625)) * 1.05499994754791259765625) - float3(0.054999999701976776123046875), magnitude > float3(0.003130800090730190277099609375));
+    return sign(color) * encoded;
+}
+
+static inline __attribute__((always_inline))
+float3 convertColorSpace(thread float3& color, constant int& uSrcColorSpace, constant int& uDestColorSpace)
+{
+    if (uSrcColorSpace != 2)
+    {
+        float3 param = color;
+        color = decodeSRGB(param);
+    }
+    if (uSrcColorSpace == 1)
+    {
+        color = color * float3x3(float3(1.22494018077850341796875, -0.2249404042959213256835937, 0.0), float3(-0.0420569024980068206787109375, 1.04205715656280517578125, 0.0), float3(-0.019637599587440490722656250, -0.078636102378368377685546875, 1.09827351570129394531250));
+    }
+    if (uDestColorSpace == 1)
+    {
+        color = color * float3x3(float3(0.82246208190917968750, 0.17753799259662628173828125, 0.0), float3(0.0331941023468971252441406250, 0.96680581569671630859375, 0.0), float3(0.01708270050585269927978515625, 0.072397403419017791748046875, 0.91051989793777465820312500));
+    }
+    if (uDestColorSpace != 2)
+    {
+        float3 param_1 = color;
+        color = encodeSRGB(param_1);
+    }
+    return color;
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant int& uSrcColorSpace [[buffer(0)]], constant int& uDestColorSpace [[buffer(1)]], constant int& uAlphaMode [[buffer(2)]], texture2d<float> uSrc [[texture(0)]], sampler uSrcSmplr [[sampler(0)]])
 {
     main0_out out = {};
     float4 color = uSrc.sample(uSrcSmplr, in.vTexCoord);
+    if (uAlphaMode == 3)
+    {
+        color = float4(float3(1.0), color.x);
+    }
+    else
+    {
+        if (uAlphaMode == 0)
+        {
+            if ((uSrcColorSpace != uDestColorSpace) && (color.w > 0.0))
+            {
+                float3 param = color.xyz / float3(color.w);
+                float3 _converted = convertColorSpace(param, uSrcColorSpace, uDestColorSpace);
+                color = float4(_converted * color.w, color.w);
+            }
+        }
+        else
+        {
+            if ((uAlphaMode == 2) && (color.w > 0.0))
+            {
+                float3 _unpremultiplied = color.xyz / float3(color.w);
+                color = float4(_unpremultiplied.x, _unpremultiplied.y, _unpremultiplied.z, color.w);
+            }
+            if (uSrcColorSpace != uDestColorSpace)
+            {
+                float3 param_1 = color.xyz;
+                float3 _converted_1 = convertColorSpace(param_1, uSrcColorSpace, uDestColorSpace);
+                color = float4(_converted_1.x, _converted_1.y, _converted_1.z, color.w);
+            }
+        }
+    }
     out.oFragColor = color;
     return out;
 }
@@ -28,6 +28,11 @@ struct bInputIndices
     uint2 iInputIndices[1];
 };
 
+struct bPathTransforms
+{
+    float4 iPathTransforms[1];
+};
+
 struct bComputeIndirectParams
 {
     uint iComputeIndirectParams[1];
@@ -36,9 +41,9 @@ struct bComputeIndirectParams
 constant uint3 gl_WorkGroupSize [[maybe_unused]] = uint3(64u, 1u, 1u);
 
 static inline __attribute__((always_inline))
-float2 getPoint(thread const uint& pointIndex, thread float2x2 uTransform, const device bPoints& v_194, thread float2 uTranslation)
+float2 getPoint(thread const uint& pointIndex, thread const float2x2& pathTransform, thread const float2& pathTranslation, thread float2x2 uTransform, const device bPoints& v_194, thread float2 uTranslation)
 {
-    return (uTransform * v_194.iPoints[pointIndex]) + uTranslation;
+    return (uTransform * ((pathTransform * v_194.iPoints[pointIndex]) + pathTranslation)) + uTranslation;
 }
 
 static inline __attribute__((always_inline))
@@ -75,7 +80,7 @@ void emitMicroline(thread const float4& microlineSegment, thread const uint& pat
     v_76.iMicrolines[outputMicrolineIndex] = uint4((uint(microlinePixels.x) & 65535u) | (uint(microlinePixels.y) << uint(16)), (uint(microlinePixels.z) & 65535u) | (uint(microlinePixels.w) << uint(16)), ((uint(microlineFractPixels.x) | (uint(microlineFractPixels.y) << uint(8))) | (uint(microlineFractPixels.z) << uint(16))) | (uint(microlineFractPixels.w) << uint(24)), pathIndex);
 }
 
-kernel void main0(constant int& uMaxMicrolineCount [[buffer(0)]], constant int& uLastBatchSegmentIndex [[buffer(5)]], constant int& uPathCount [[buffer(6)]], constant float2x2& uTransform [[buffer(2)]], constant float2& uTranslation [[buffer(4)]], device bMicrolines& v_76 [[buffer(1)]], const device bPoints& v_194 [[buffer(3)]], const device bDiceMetadata& _253 [[buffer(7)]], const device bInputIndices& _300 [[buffer(8)]], device bComputeIndirectParams& _439 [[buffer(9)]], uint3 gl_GlobalInvocationID [[thread_position_in_grid]])
+kernel void main0(constant int& uMaxMicrolineCount [[buffer(0)]], constant int& uLastBatchSegmentIndex [[buffer(5)]], constant int& uPathCount [[buffer(6)]], constant float2x2& uTransform [[buffer(2)]], constant float2& uTranslation [[buffer(4)]], device bMicrolines& v_76 [[buffer(1)]], const device bPoints& v_194 [[buffer(3)]], const device bDiceMetadata& _253 [[buffer(7)]], const device bInputIndices& _300 [[buffer(8)]], device bComputeIndirectParams& _439 [[buffer(9)]], const device bPathTransforms& _265 [[buffer(10)]], constant float& uTolerance [[buffer(11)]], uint3 gl_GlobalInvocationID [[thread_position_in_grid]])
 {
     uint batchSegmentIndex = gl_GlobalInvocationID.x;
     if (batchSegmentIndex >= uint(uLastBatchSegmentIndex))
@@ -126,6 +131,9 @@ kernel void main0(constant int& uMaxMicrolineCount [[buffer(0)]], constant int&
     uint firstGlobalSegmentIndexInPath = diceMetadata.y;
     uint firstBatchSegmentIndexInPath = diceMetadata.z;
     uint globalSegmentIndex = (batchSegmentIndex - firstBatchSegmentIndexInPath) + firstGlobalSegmentIndexInPath;
+    float4 pathTransformColumns = _265.iPathTransforms[(batchPathIndex * 2u) + 0u];
+    float2x2 pathTransform = float2x2(float2(pathTransformColumns.xy), float2(pathTransformColumns.zw));
+    float2 pathTranslation = _265.iPathTransforms[(batchPathIndex * 2u) + 1u].xy;
     uint2 inputIndices = _300.iInputIndices[globalSegmentIndex];
     uint fromPointIndex = inputIndices.x;
     uint flagsPathIndex = inputIndices.y;
@@ -147,14 +155,14 @@ kernel void main0(constant int& uMaxMicrolineCount [[buffer(0)]], constant int&
     }
     uint param = fromPointIndex;
     uint param_1 = toPointIndex;
-    float4 baseline = float4(getPoint(param, uTransform, v_194, uTranslation), getPoint(param_1, uTransform, v_194, uTranslation));
+    float4 baseline = float4(getPoint(param, pathTransform, pathTranslation, uTransform, v_194, uTranslation), getPoint(param_1, pathTransform, pathTranslation, uTransform, v_194, uTranslation));
     float4 ctrl = float4(0.0);
     bool isCurve = (flagsPathIndex & 3221225472u) != 0u;
     float segmentCountF;
     if (isCurve)
     {
         uint param_2 = fromPointIndex + 1u;
-        float2 ctrl0 = getPoint(param_2, uTransform, v_194, uTranslation);
+        float2 ctrl0 = getPoint(param_2, pathTransform, pathTranslation, uTransform, v_194, uTranslation);
         if ((flagsPathIndex & 2147483648u) != 0u)
         {
             float2 ctrl0_2 = ctrl0 * float2(2.0);
@@ -163,10 +171,10 @@ kernel void main0(constant int& uMaxMicrolineCount [[buffer(0)]], constant int&
         else
         {
             uint param_3 = fromPointIndex + 2u;
-            ctrl = float4(ctrl0, getPoint(param_3, uTransform, v_194, uTranslation));
+            ctrl = float4(ctrl0, getPoint(param_3, pathTransform, pathTranslation, uTransform, v_194, uTranslation));
         }
         float2 bound = float2(6.0) * fast::max(abs((ctrl.zw - (ctrl.xy * 2.0)) + baseline.xy), abs((baseline.zw - (ctrl.zw * 2.0)) + ctrl.xy));
-        segmentCountF = sqrt(length(bound) / 2.0);
+        segmentCountF = sqrt(length(bound) / (8.0 * uTolerance));
     }
     else
     {
@@ -38,7 +38,40 @@ float4 computeCoverage(thread const float2& from, thread const float2& to, threa
 }
 
 static inline __attribute__((always_inline))
-float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& tileSubCoord, const device bFills& v_148, thread texture2d<float> uAreaLUT, thread const sampler uAreaLUTSmplr)
+float4 computeSampleCoverage(thread float2& from, thread float2& to, thread const float2& sampleOffset)
+{
+    from -= sampleOffset;
+    to -= sampleOffset;
+    if ((from.x <= 0.0) == (to.x <= 0.0))
+    {
+        return float4(0.0);
+    }
+    float y = mix(from.y, to.y, from.x / (from.x - to.x));
+    return step(float4(y), float4(0.0, 1.0, 2.0, 3.0)) * sign(from.x - to.x);
+}
+
+static inline __attribute__((always_inline))
+float4 computeSupersampledCoverage(thread const float2& from, thread const float2& to, thread const int& sampleCount)
+{
+    float4 coverage = float4(0.0);
+    for (int sampleIndex = 0; sampleIndex < sampleCount; sampleIndex++)
+    {
+        int sampleRow = 0;
+        for (int bit = 1; bit < sampleCount; bit = bit << 1)
+        {
+            sampleRow = (sampleRow << 1) | (((sampleIndex & bit) != 0) ? 1 : 0);
+        }
+        float2 sampleOffset = ((float2(float(sampleIndex), float(sampleRow)) + float2(0.5)) / float2(float(sampleCount))) - float2(0.5);
+        float2 param = from;
+        float2 param_1 = to;
+        float2 param_2 = sampleOffset;
+        coverage += computeSampleCoverage(param, param_1, param_2);
+    }
+    return coverage / float4(float(sampleCount));
+}
+
+static inline __attribute__((always_inline))
+float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& tileSubCoord, const device bFills& v_148, constant int& uSampleCount, thread texture2d<float> uAreaLUT, thread const sampler uAreaLUTSmplr)
 {
     float2 tileFragCoord = float2(tileSubCoord) + float2(0.5);
     float4 coverages = float4(0.0);
@@ -49,9 +82,19 @@ float4 accumulateCoverageForFillList(thread int& fillIndex, thread const int2& t
         uint fillTo = v_148.iFills[(fillIndex * 3) + 1];
         float4 lineSegment = float4(float(fillFrom & 65535u), float(fillFrom >> uint(16)), float(fillTo & 65535u), float(fillTo >> uint(16))) / float4(256.0);
         lineSegment -= tileFragCoord.xyxy;
-        float2 param = lineSegment.xy;
-        float2 param_1 = lineSegment.zw;
-        coverages += computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+        if (uSampleCount == 0)
+        {
+            float2 param = lineSegment.xy;
+            float2 param_1 = lineSegment.zw;
+            coverages += computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+        }
+        else
+        {
+            float2 param_2 = lineSegment.xy;
+            float2 param_3 = lineSegment.zw;
+            int param_4 = uSampleCount;
+            coverages += computeSupersampledCoverage(param_2, param_3, param_4);
+        }
         fillIndex = int(v_148.iFills[(fillIndex * 3) + 2]);
         iteration++;
     } while ((fillIndex >= 0) && (iteration < 1024));
@@ -66,7 +109,7 @@ int2 computeTileCoord(thread const uint& alphaTileIndex, thread uint3& gl_LocalI
     return (int2(16, 4) * int2(int(x), int(y))) + int2(gl_LocalInvocationID.xy);
 }
 
-kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], const device bFills& v_148 [[buffer(0)]], const device bAlphaTiles& _284 [[buffer(2)]], device bTiles& _294 [[buffer(3)]], texture2d<float> uAreaLUT [[texture(0)]], texture2d<float, access::read_write> uDest [[texture(1)]], sampler uAreaLUTSmplr [[sampler(0)]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]])
+kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], constant int& uSampleCount [[buffer(4)]], const device bFills& v_148 [[buffer(0)]], const device bAlphaTiles& _284 [[buffer(2)]], device bTiles& _294 [[buffer(3)]], texture2d<float> uAreaLUT [[texture(0)]], texture2d<float, access::read_write> uDest [[texture(1)]], sampler uAreaLUTSmplr [[sampler(0)]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]])
 {
     int2 tileSubCoord = int2(gl_LocalInvocationID.xy) * int2(1, 4);
     uint batchAlphaTileIndex = gl_WorkGroupID.x | (gl_WorkGroupID.y << uint(15));
@@ -85,7 +128,7 @@ kernel void main0(constant int2& uAlphaTileRange [[buffer(1)]], const device bFi
     float4 coverages = float4(float(backdrop));
     int param = fillIndex;
     int2 param_1 = tileSubCoord;
-    float4 _334 = accumulateCoverageForFillList(param, param_1, v_148, uAreaLUT, uAreaLUTSmplr);
+    float4 _334 = accumulateCoverageForFillList(param, param_1, v_148, uSampleCount, uAreaLUT, uAreaLUTSmplr);
     coverages += _334;
     coverages = fast::clamp(abs(coverages), float4(0.0), float4(1.0));
     int clipTileIndex = int(_284.iAlphaTiles[(batchAlphaTileIndex * 2u) + 1u]);
@@ -40,10 +40,10 @@ float4 fetchUnscaled(thread const texture2d<float> srcTexture, thread const samp
 }
 
 static inline __attribute__((always_inline))
-void computeTileVaryings(thread const float2& position, thread const int& colorEntry, thread const texture2d<float> textureMetadata, thread const sampler textureMetadataSmplr, thread const int2& textureMetadataSize, thread float2& outColorTexCoord0, thread float4& outBaseColor, thread float4& outFilterParams0, thread float4& outFilterParams1, thread float4& outFilterParams2, thread int& outCtrl)
+void computeTileVaryings(thread const float2& position, thread const int& colorEntry, thread const texture2d<float> textureMetadata, thread const sampler textureMetadataSmplr, thread const int2& textureMetadataSize, thread float2& outColorTexCoord0, thread float4& outColorTexRect0, thread float4& outBaseColor, thread float4& outFilterParams0, thread float4& outFilterParams1, thread float4& outFilterParams2, thread float2& outPaintMaskTexCoord, thread float4& outPaintMaskTexRect, thread int& outCtrl)
 {
     float2 metadataScale = float2(1.0) / float2(textureMetadataSize);
-    float2 metadataEntryCoord = float2(float((colorEntry % 128) * 8), float(colorEntry / 128));
+    float2 metadataEntryCoord = float2(float((colorEntry % 128) * 10), float(colorEntry / 128));
     float2 param = metadataScale;
     float2 param_1 = metadataEntryCoord;
     int param_2 = 0;
@@ -72,7 +72,22 @@ void computeTileVaryings(thread const float2& position, thread const int& colorE
     float2 param_19 = metadataEntryCoord;
     int param_20 = 6;
     float4 extra = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_18, param_19, param_20);
+    float2 param_21 = metadataScale;
+    float2 param_22 = metadataEntryCoord;
+    int param_23 = 7;
+    float4 colorTexRect0 = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_21, param_22, param_23);
+    float2 param_24 = metadataScale;
+    float2 param_25 = metadataEntryCoord;
+    int param_26 = 8;
+    float4 paintMaskMatrix = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_24, param_25, param_26);
+    float2 param_27 = metadataScale;
+    float2 param_28 = metadataEntryCoord;
+    int param_29 = 9;
+    float4 paintMaskRect = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_27, param_28, param_29);
     outColorTexCoord0 = (float2x2(float2(colorTexMatrix0.xy), float2(colorTexMatrix0.zw)) * position) + colorTexOffsets.xy;
+    outColorTexRect0 = colorTexRect0;
+    outPaintMaskTexCoord = (float2x2(float2(paintMaskMatrix.xy), float2(paintMaskMatrix.zw)) * position) + colorTexOffsets.zw;
+    outPaintMaskTexRect = paintMaskRect;
     outBaseColor = baseColor;
     outFilterParams0 = filterParams0;
     outFilterParams1 = filterParams1;
@@ -164,19 +179,19 @@ float4 filterBlur(thread const float2& colorTexCoord, thread const texture2d<flo
 }
 
 static inline __attribute__((always_inline))
-float filterTextSample1Tap(thread const float& offset, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
+float filterTextSample1Tap(thread const float2& offset, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
 {
-    return colorTexture.sample(colorTextureSmplr, (colorTexCoord + float2(offset, 0.0)), level(0.0)).x;
+    return colorTexture.sample(colorTextureSmplr, (colorTexCoord + offset), level(0.0)).x;
 }
 
 static inline __attribute__((always_inline))
-void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCenter, thread float4& outAlphaRight, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord, thread const float4& kernel0, thread const float& onePixel)
+void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCenter, thread float4& outAlphaRight, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord, thread const float4& kernel0, thread const float2& onePixel)
 {
     bool wide = kernel0.x > 0.0;
     float _276;
     if (wide)
     {
-        float param = (-4.0) * onePixel;
+        float2 param = (-4.0) * onePixel;
         float2 param_1 = colorTexCoord;
         _276 = filterTextSample1Tap(param, colorTexture, colorTextureSmplr, param_1);
     }
@@ -184,26 +199,26 @@ void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCen
     {
         _276 = 0.0;
     }
-    float param_2 = (-3.0) * onePixel;
+    float2 param_2 = (-3.0) * onePixel;
     float2 param_3 = colorTexCoord;
-    float param_4 = (-2.0) * onePixel;
+    float2 param_4 = (-2.0) * onePixel;
     float2 param_5 = colorTexCoord;
-    float param_6 = (-1.0) * onePixel;
+    float2 param_6 = (-1.0) * onePixel;
     float2 param_7 = colorTexCoord;
     outAlphaLeft = float4(_276, filterTextSample1Tap(param_2, colorTexture, colorTextureSmplr, param_3), filterTextSample1Tap(param_4, colorTexture, colorTextureSmplr, param_5), filterTextSample1Tap(param_6, colorTexture, colorTextureSmplr, param_7));
-    float param_8 = 0.0;
+    float2 param_8 = float2(0.0);
     float2 param_9 = colorTexCoord;
     outAlphaCenter = filterTextSample1Tap(param_8, colorTexture, colorTextureSmplr, param_9);
-    float param_10 = 1.0 * onePixel;
+    float2 param_10 = 1.0 * onePixel;
     float2 param_11 = colorTexCoord;
-    float param_12 = 2.0 * onePixel;
+    float2 param_12 = 2.0 * onePixel;
     float2 param_13 = colorTexCoord;
-    float param_14 = 3.0 * onePixel;
+    float2 param_14 = 3.0 * onePixel;
     float2 param_15 = colorTexCoord;
     float _336;
     if (wide)
     {
-        float param_16 = 4.0 * onePixel;
+        float2 param_16 = 4.0 * onePixel;
         float2 param_17 = colorTexCoord;
         _336 = filterTextSample1Tap(param_16, colorTexture, colorTextureSmplr, param_17);
     }
@@ -245,6 +260,8 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
     float3 bgColor = filterParams1.xyz;
     float3 fgColor = filterParams2.xyz;
     bool gammaCorrectionEnabled = filterParams2.w != 0.0;
+    bool bgrLayout = (filterParams1.w == 1.0) || (filterParams1.w == 3.0);
+    bool verticalLayout = filterParams1.w >= 2.0;
     float3 alpha;
     if (kernel0.w == 0.0)
     {
@@ -254,7 +271,7 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
     {
         float2 param_3 = colorTexCoord;
         float4 param_4 = kernel0;
-        float param_5 = 1.0 / colorTextureSize.x;
+        float2 param_5 = verticalLayout ? float2(0.0, 1.0 / colorTextureSize.y) : float2(1.0 / colorTextureSize.x, 0.0);
         float4 param;
         float param_1;
         float4 param_2;
@@ -274,7 +291,7 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
         float3 param_13 = alphaRight.yzw;
         float4 param_14 = kernel0;
         float b = filterTextConvolve7Tap(param_12, param_13, param_14);
-        alpha = float3(r, g, b);
+        alpha = bgrLayout ? float3(b, g, r) : float3(r, g, b);
     }
     if (gammaCorrectionEnabled)
     {
@@ -607,7 +624,61 @@ float4 composite(thread const float4& srcColor, thread const texture2d<float> de
 }
 
 static inline __attribute__((always_inline))
-float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& baseColor, thread const int& tileCtrl)
+float2 wrapColorTexCoord(thread const float2& colorTexCoord, thread const float4& colorTexRect, thread const float2& colorTextureSize)
+{
+    float2 size = abs(colorTexRect.zw);
+    bool _1380 = size.x == 0.0;
+    bool _1386;
+    if (!_1380)
+    {
+        _1386 = size.y == 0.0;
+    }
+    else
+    {
+        _1386 = _1380;
+    }
+    if (_1386)
+    {
+        return colorTexCoord;
+    }
+    float4 texRect = colorTexRect / colorTextureSize.xyxy;
+    size /= colorTextureSize;
+    float2 offset = colorTexCoord - texRect.xy;
+    offset = select(offset, mod(offset, size), colorTexRect.zw > float2(0.0));
+    float2 halfTexel = float2(0.5) / colorTextureSize;
+    return texRect.xy + fast::clamp(offset, halfTexel, size - halfTexel);
+}
+
+static inline __attribute__((always_inline))
+float samplePaintMask(thread const texture2d<float> paintMaskTexture, thread const sampler paintMaskTextureSmplr, thread const float2& paintMaskTextureSize, thread const float2& paintMaskTexCoord, thread const float4& paintMaskTexRect, thread const int& paintMaskCtrl)
+{
+    float2 param = paintMaskTexCoord;
+    float4 param_1 = paintMaskTexRect;
+    float2 param_2 = paintMaskTextureSize;
+    float2 texCoord = wrapColorTexCoord(param, param_1, param_2);
+    float4 texel = paintMaskTexture.sample(paintMaskTextureSmplr, texCoord);
+    if ((paintMaskCtrl & 3) != 2)
+    {
+        return texel.w;
+    }
+    if ((paintMaskCtrl & 4) != 0)
+    {
+        float3 _unpremultiplied;
+        if (texel.w > 0.0)
+        {
+            _unpremultiplied = texel.xyz / float3(texel.w);
+        }
+        else
+        {
+            _unpremultiplied = float3(0.0);
+        }
+        texel = float4(_unpremultiplied.x, _unpremultiplied.y, _unpremultiplied.z, texel.w);
+    }
+    return dot(texel.xyz, float3(0.2125, 0.7154, 0.0721)) * texel.w;
+}
+
+static inline __attribute__((always_inline))
+float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> paintMaskTexture, thread const sampler paintMaskTextureSmplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float2& paintMaskTextureSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& colorTexRect0, thread const float2& paintMaskTexCoord, thread const float4& paintMaskTexRect, thread const float4& baseColor, thread const int& tileCtrl)
 {
     int maskCtrl0 = (tileCtrl >> 0) & 3;
     float maskAlpha = 1.0;
@@ -616,38 +687,51 @@ float4 calculateColor(thread const float2& fragCoord, thread const texture2d<flo
     float3 param_2 = maskTexCoord0;
     int param_3 = maskCtrl0;
     maskAlpha = sampleMask(param, maskTexture0, maskTexture0Smplr, param_1, param_2, param_3);
+    int paintMaskCtrl = ctrl >> 12;
+    if ((paintMaskCtrl & 3) != 0)
+    {
+        float2 param_4 = paintMaskTextureSize;
+        float2 param_5 = paintMaskTexCoord;
+        float4 param_6 = paintMaskTexRect;
+        int param_7 = paintMaskCtrl;
+        maskAlpha *= samplePaintMask(paintMaskTexture, paintMaskTextureSmplr, param_4, param_5, param_6, param_7);
+    }
     float4 color = baseColor;
     int color0Combine = (ctrl >> 6) & 3;
     if (color0Combine != 0)
     {
         int color0Filter = (ctrl >> 4) & 3;
-        float2 param_4 = colorTexCoord0;
-        float2 param_5 = colorTextureSize0;
-        float2 param_6 = fragCoord;
-        float2 param_7 = framebufferSize;
-        float4 param_8 = filterParams0;
-        float4 param_9 = filterParams1;
-        float4 param_10 = filterParams2;
-        int param_11 = color0Filter;
-        float4 color0 = filterColor(param_4, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_5, param_6, param_7, param_8, param_9, param_10, param_11);
-        float4 param_12 = color;
-        float4 param_13 = color0;
-        int param_14 = color0Combine;
-        color = combineColor0(param_12, param_13, param_14);
+        float2 param_8 = colorTexCoord0;
+        float4 param_9 = colorTexRect0;
+        float2 param_10 = colorTextureSize0;
+        float2 colorTexCoord = wrapColorTexCoord(param_8, param_9, param_10);
+        float2 param_11 = colorTexCoord;
+        float2 param_12 = colorTextureSize0;
+        float2 param_13 = fragCoord;
+        float2 param_14 = framebufferSize;
+        float4 param_15 = filterParams0;
+        float4 param_16 = filterParams1;
+        float4 param_17 = filterParams2;
+        int param_18 = color0Filter;
+        float4 color0 = filterColor(param_11, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_12, param_13, param_14, param_15, param_16, param_17, param_18);
+        float4 param_19 = color;
+        float4 param_20 = color0;
+        int param_21 = color0Combine;
+        color = combineColor0(param_19, param_20, param_21);
     }
     color.w *= maskAlpha;
     int compositeOp = (ctrl >> 8) & 15;
-    float4 param_15 = color;
-    float2 param_16 = framebufferSize;
-    float2 param_17 = fragCoord;
-    int param_18 = compositeOp;
-    color = composite(param_15, destTexture, destTextureSmplr, param_16, param_17, param_18);
+    float4 param_22 = color;
+    float2 param_23 = framebufferSize;
+    float2 param_24 = fragCoord;
+    int param_25 = compositeOp;
+    color = composite(param_22, destTexture, destTextureSmplr, param_23, param_24, param_25);
     float3 _1364 = color.xyz * color.w;
     color = float4(_1364.x, _1364.y, _1364.z, color.w);
     return color;
 }
 
-kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant int& uLoadAction [[buffer(4)]], constant int2& uTextureMetadataSize [[buffer(7)]], constant float2& uFramebufferSize [[buffer(0)]], constant float2& uTileSize [[buffer(1)]], constant float4& uClearColor [[buffer(5)]], constant float2& uColorTextureSize0 [[buffer(8)]], constant float2& uMaskTextureSize0 [[buffer(9)]], const device bFirstTileMap& _1510 [[buffer(2)]], const device bTiles& _1603 [[buffer(6)]], texture2d<float, access::read_write> uDestImage [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], texture2d<float> uColorTexture0 [[texture(2)]], texture2d<float> uMaskTexture0 [[texture(3)]], texture2d<float> uDestTexture [[texture(4)]], texture2d<float> uGammaLUT [[texture(5)]], sampler uTextureMetadataSmplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uMaskTexture0Smplr [[sampler(2)]], sampler uDestTextureSmplr [[sampler(3)]], sampler uGammaLUTSmplr [[sampler(4)]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]])
+kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant int& uLoadAction [[buffer(4)]], constant int2& uTextureMetadataSize [[buffer(7)]], constant float2& uFramebufferSize [[buffer(0)]], constant float2& uTileSize [[buffer(1)]], constant float4& uClearColor [[buffer(5)]], constant float2& uColorTextureSize0 [[buffer(8)]], constant float2& uMaskTextureSize0 [[buffer(9)]], constant float2& uPaintMaskTextureSize [[buffer(10)]], const device bFirstTileMap& _1510 [[buffer(2)]], const device bTiles& _1603 [[buffer(6)]], texture2d<float, access::read_write> uDestImage [[texture(0)]], texture2d<float> uTextureMetadata [[texture(1)]], texture2d<float> uColorTexture0 [[texture(2)]], texture2d<float> uMaskTexture0 [[texture(3)]], texture2d<float> uDestTexture [[texture(4)]], texture2d<float> uGammaLUT [[texture(5)]], texture2d<float> uPaintMaskTexture [[texture(6)]], sampler uTextureMetadataSmplr [[sampler(0)]], sampler uColorTexture0Smplr [[sampler(1)]], sampler uMaskTexture0Smplr [[sampler(2)]], sampler uDestTextureSmplr [[sampler(3)]], sampler uGammaLUTSmplr [[sampler(4)]], sampler uPaintMaskTextureSmplr [[sampler(5)]], uint3 gl_WorkGroupID [[threadgroup_position_in_grid]], uint3 gl_LocalInvocationID [[thread_position_in_threadgroup]])
 {
     int2 tileCoord = int2(gl_WorkGroupID.xy);
     int2 firstTileSubCoord = int2(gl_LocalInvocationID.xy) * int2(1, 4);
@@ -678,7 +762,10 @@ kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant in
     float4 param_6;
     float4 param_7;
     float4 param_8;
-    int param_9;
+    float4 param_9;
+    float2 param_10;
+    float4 param_11;
+    int param_12;
     while (tileIndex >= 0)
     {
         for (int subY_1 = 0; subY_1 < 4; subY_1++)
@@ -704,34 +791,41 @@ kernel void main0(constant int2& uFramebufferTileSize [[buffer(3)]], constant in
             float2 param_1 = fragCoord;
             int param_2 = int(colorEntry);
             int2 param_3 = uTextureMetadataSize;
-            computeTileVaryings(param_1, param_2, uTextureMetadata, uTextureMetadataSmplr, param_3, param_4, param_5, param_6, param_7, param_8, param_9);
+            computeTileVaryings(param_1, param_2, uTextureMetadata, uTextureMetadataSmplr, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11, param_12);
             float2 colorTexCoord0 = param_4;
-            float4 baseColor = param_5;
-            float4 filterParams0 = param_6;
-            float4 filterParams1 = param_7;
-            float4 filterParams2 = param_8;
-            int ctrl = param_9;
-            float2 param_10 = fragCoord;
-            float2 param_11 = uColorTextureSize0;
-            float2 param_12 = uMaskTextureSize0;
-            float4 param_13 = filterParams0;
-            float4 param_14 = filterParams1;
-            float4 param_15 = filterParams2;
-            float2 param_16 = uFramebufferSize;
-            int param_17 = ctrl;
-            float3 param_18 = maskTexCoord0;
-            float2 param_19 = colorTexCoord0;
-            float4 param_20 = baseColor;
-            int param_21 = tileCtrl;
-            float4 srcColor = calculateColor(param_10, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uDestTexture, uDestTextureSmplr, uGammaLUT, uGammaLUTSmplr, param_11, param_12, param_13, param_14, param_15, param_16, param_17, param_18, param_19, param_20, param_21);
+            float4 colorTexRect0 = param_5;
+            float4 baseColor = param_6;
+            float4 filterParams0 = param_7;
+            float4 filterParams1 = param_8;
+            float4 filterParams2 = param_9;
+            float2 paintMaskTexCoord = param_10;
+            float4 paintMaskTexRect = param_11;
+            int ctrl = param_12;
+            float2 param_13 = fragCoord;
+            float2 param_14 = uColorTextureSize0;
+            float2 param_15 = uMaskTextureSize0;
+            float2 param_16 = uPaintMaskTextureSize;
+            float4 param_17 = filterParams0;
+            float4 param_18 = filterParams1;
+            float4 param_19 = filterParams2;
+            float2 param_20 = uFramebufferSize;
+            int param_21 = ctrl;
+            float3 param_22 = maskTexCoord0;
+            float2 param_23 = colorTexCoord0;
+            float4 param_24 = colorTexRect0;
+            float2 param_25 = paintMaskTexCoord;
+            float4 param_26 = paintMaskTexRect;
+            float4 param_27 = baseColor;
+            int param_28 = tileCtrl;
+            float4 srcColor = calculateColor(param_13, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uPaintMaskTexture, uPaintMaskTextureSmplr, uDestTexture, uDestTextureSmplr, uGammaLUT, uGammaLUTSmplr, param_14, param_15, param_16, param_17, param_18, param_19, param_20, param_21, param_22, param_23, param_24, param_25, param_26, param_27, param_28);
             destColors[subY_1] = (destColors[subY_1] * (1.0 - srcColor.w)) + srcColor;
         }
         tileIndex = int(_1603.iTiles[(tileIndex * 4) + 0]);
     }
     for (int subY_2 = 0; subY_2 < 4; subY_2++)
     {
-        int2 param_22 = firstFragCoord + int2(0, subY_2);
-        uDestImage.write(destColors[subY_2], uint2(toImageCoords(param_22, uFramebufferSize)));
+        int2 param_29 = firstFragCoord + int2(0, subY_2);
+        uDestImage.write(destColors[subY_2], uint2(toImageCoords(param_29, uFramebufferSize)));
     }
 }
 
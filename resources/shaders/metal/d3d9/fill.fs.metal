@@ -31,12 +31,55 @@ float4 computeCoverage(thread const float2& from, thread const float2& to, threa
     return areaLUT.sample(areaLUTSmplr, (float2(y + 8.0, abs(d * dX)) / float2(16.0))) * dX;
 }
 
-fragment main0_out main0(main0_in in [[stage_in]], texture2d<float> uAreaLUT [[texture(0)]], sampler uAreaLUTSmplr [[sampler(0)]])
+static inline __attribute__((always_inline))
+float4 computeSampleCoverage(thread float2& from, thread float2& to, thread const float2& sampleOffset)
+{
+    from -= sampleOffset;
+    to -= sampleOffset;
+    if ((from.x <= 0.0) == (to.x <= 0.0))
+    {
+        return float4(0.0);
+    }
+    float y = mix(from.y, to.y, from.x / (from.x - to.x));
+    return step(float4(y), float4(0.0, 1.0, 2.0, 3.0)) * sign(from.x - to.x);
+}
+
+static inline __attribute__((always_inline))
+float4 computeSupersampledCoverage(thread const float2& from, thread const float2& to, thread const int& sampleCount)
+{
+    float4 coverage = float4(0.0);
+    for (int sampleIndex = 0; sampleIndex < sampleCount; sampleIndex++)
+    {
+        int sampleRow = 0;
+        for (int bit = 1; bit < sampleCount; bit = bit << 1)
+        {
+            sampleRow = (sampleRow << 1) | (((sampleIndex & bit) != 0) ? 1 : 0);
+        }
+        float2 sampleOffset = ((float2(float(sampleIndex), float(sampleRow)) + float2(0.5)) / float2(float(sampleCount))) - float2(0.5);
+        float2 param = from;
+        float2 param_1 = to;
+        float2 param_2 = sampleOffset;
+        coverage += computeSampleCoverage(param, param_1, param_2);
+    }
+    return coverage / float4(float(sampleCount));
+}
+
+fragment main0_out main0(main0_in in [[stage_in]], constant int& uSampleCount [[buffer(0)]], texture2d<float> uAreaLUT [[texture(0)]], sampler uAreaLUTSmplr [[sampler(0)]])
 {
     main0_out out = {};
-    float2 param = in.vFrom;
-    float2 param_1 = in.vTo;
-    out.oFragColor = computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+    if (uSampleCount == 0)
+    {
+        float2 param = in.vFrom;
+        float2 param_1 = in.vTo;
+        out.oFragColor = computeCoverage(param, param_1, uAreaLUT, uAreaLUTSmplr);
+    }
+    else
+    {
+        float2 param_2 = in.vFrom;
+        float2 param_3 = in.vTo;
+        int param_4 = uSampleCount;
+        out.oFragColor = computeSupersampledCoverage(param_2, param_3, param_4);
+    }
     return out;
 }
 
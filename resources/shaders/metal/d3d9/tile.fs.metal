@@ -17,12 +17,15 @@ struct main0_in
 {
     float3 vMaskTexCoord0 [[user(locn0)]];
     float2 vColorTexCoord0 [[user(locn1)]];
-    float4 vBaseColor [[user(locn2)]];
-    float vTileCtrl [[user(locn3)]];
-    float4 vFilterParams0 [[user(locn4)]];
-    float4 vFilterParams1 [[user(locn5)]];
-    float4 vFilterParams2 [[user(locn6)]];
-    float vCtrl [[user(locn7)]];
+    float4 vColorTexRect0 [[user(locn2)]];
+    float4 vBaseColor [[user(locn3)]];
+    float vTileCtrl [[user(locn4)]];
+    float4 vFilterParams0 [[user(locn5)]];
+    float4 vFilterParams1 [[user(locn6)]];
+    float4 vFilterParams2 [[user(locn7)]];
+    float2 vPaintMaskTexCoord [[user(locn8)]];
+    float4 vPaintMaskTexRect [[user(locn9)]];
+    float vCtrl [[user(locn10)]];
 };
 
 // Implementation of the GLSL mod() function, which is slightly different than Metal fmod()
@@ -116,19 +119,19 @@ float4 filterBlur(thread const float2& colorTexCoord, thread const texture2d<flo
 }
 
 static inline __attribute__((always_inline))
-float filterTextSample1Tap(thread const float& offset, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
+float filterTextSample1Tap(thread const float2& offset, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord)
 {
-    return colorTexture.sample(colorTextureSmplr, (colorTexCoord + float2(offset, 0.0))).x;
+    return colorTexture.sample(colorTextureSmplr, (colorTexCoord + offset)).x;
 }
 
 static inline __attribute__((always_inline))
-void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCenter, thread float4& outAlphaRight, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord, thread const float4& kernel0, thread const float& onePixel)
+void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCenter, thread float4& outAlphaRight, thread const texture2d<float> colorTexture, thread const sampler colorTextureSmplr, thread const float2& colorTexCoord, thread const float4& kernel0, thread const float2& onePixel)
 {
     bool wide = kernel0.x > 0.0;
     float _250;
     if (wide)
     {
-        float param = (-4.0) * onePixel;
+        float2 param = (-4.0) * onePixel;
         float2 param_1 = colorTexCoord;
         _250 = filterTextSample1Tap(param, colorTexture, colorTextureSmplr, param_1);
     }
@@ -136,26 +139,26 @@ void filterTextSample9Tap(thread float4& outAlphaLeft, thread float& outAlphaCen
     {
         _250 = 0.0;
     }
-    float param_2 = (-3.0) * onePixel;
+    float2 param_2 = (-3.0) * onePixel;
     float2 param_3 = colorTexCoord;
-    float param_4 = (-2.0) * onePixel;
+    float2 param_4 = (-2.0) * onePixel;
     float2 param_5 = colorTexCoord;
-    float param_6 = (-1.0) * onePixel;
+    float2 param_6 = (-1.0) * onePixel;
     float2 param_7 = colorTexCoord;
     outAlphaLeft = float4(_250, filterTextSample1Tap(param_2, colorTexture, colorTextureSmplr, param_3), filterTextSample1Tap(param_4, colorTexture, colorTextureSmplr, param_5), filterTextSample1Tap(param_6, colorTexture, colorTextureSmplr, param_7));
-    float param_8 = 0.0;
+    float2 param_8 = float2(0.0);
     float2 param_9 = colorTexCoord;
     outAlphaCenter = filterTextSample1Tap(param_8, colorTexture, colorTextureSmplr, param_9);
-    float param_10 = 1.0 * onePixel;
+    float2 param_10 = 1.0 * onePixel;
     float2 param_11 = colorTexCoord;
-    float param_12 = 2.0 * onePixel;
+    float2 param_12 = 2.0 * onePixel;
     float2 param_13 = colorTexCoord;
-    float param_14 = 3.0 * onePixel;
+    float2 param_14 = 3.0 * onePixel;
     float2 param_15 = colorTexCoord;
     float _310;
     if (wide)
     {
-        float param_16 = 4.0 * onePixel;
+        float2 param_16 = 4.0 * onePixel;
         float2 param_17 = colorTexCoord;
         _310 = filterTextSample1Tap(param_16, colorTexture, colorTextureSmplr, param_17);
     }
@@ -197,6 +200,8 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
     float3 bgColor = filterParams1.xyz;
     float3 fgColor = filterParams2.xyz;
     bool gammaCorrectionEnabled = filterParams2.w != 0.0;
+    bool bgrLayout = (filterParams1.w == 1.0) || (filterParams1.w == 3.0);
+    bool verticalLayout = filterParams1.w >= 2.0;
     float3 alpha;
     if (kernel0.w == 0.0)
     {
@@ -206,7 +211,7 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
     {
         float2 param_3 = colorTexCoord;
         float4 param_4 = kernel0;
-        float param_5 = 1.0 / colorTextureSize.x;
+        float2 param_5 = verticalLayout ? float2(0.0, 1.0 / colorTextureSize.y) : float2(1.0 / colorTextureSize.x, 0.0);
         float4 param;
         float param_1;
         float4 param_2;
@@ -226,7 +231,7 @@ float4 filterText(thread const float2& colorTexCoord, thread const texture2d<flo
         float3 param_13 = alphaRight.yzw;
         float4 param_14 = kernel0;
         float b = filterTextConvolve7Tap(param_12, param_13, param_14);
-        alpha = float3(r, g, b);
+        alpha = bgrLayout ? float3(b, g, r) : float3(r, g, b);
     }
     if (gammaCorrectionEnabled)
     {
@@ -559,7 +564,61 @@ float4 composite(thread const float4& srcColor, thread const texture2d<float> de
 }
 
 static inline __attribute__((always_inline))
-float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& baseColor, thread const int& tileCtrl)
+float2 wrapColorTexCoord(thread const float2& colorTexCoord, thread const float4& colorTexRect, thread const float2& colorTextureSize)
+{
+    float2 size = abs(colorTexRect.zw);
+    bool _1356 = size.x == 0.0;
+    bool _1362;
+    if (!_1356)
+    {
+        _1362 = size.y == 0.0;
+    }
+    else
+    {
+        _1362 = _1356;
+    }
+    if (_1362)
+    {
+        return colorTexCoord;
+    }
+    float4 texRect = colorTexRect / colorTextureSize.xyxy;
+    size /= colorTextureSize;
+    float2 offset = colorTexCoord - texRect.xy;
+    offset = select(offset, mod(offset, size), colorTexRect.zw > float2(0.0));
+    float2 halfTexel = float2(0.5) / colorTextureSize;
+    return texRect.xy + fast::clamp(offset, halfTexel, size - halfTexel);
+}
+
+static inline __attribute__((always_inline))
+float samplePaintMask(thread const texture2d<float> paintMaskTexture, thread const sampler paintMaskTextureSmplr, thread const float2& paintMaskTextureSize, thread const float2& paintMaskTexCoord, thread const float4& paintMaskTexRect, thread const int& paintMaskCtrl)
+{
+    float2 param = paintMaskTexCoord;
+    float4 param_1 = paintMaskTexRect;
+    float2 param_2 = paintMaskTextureSize;
+    float2 texCoord = wrapColorTexCoord(param, param_1, param_2);
+    float4 texel = paintMaskTexture.sample(paintMaskTextureSmplr, texCoord);
+    if ((paintMaskCtrl & 3) != 2)
+    {
+        return texel.w;
+    }
+    if ((paintMaskCtrl & 4) != 0)
+    {
+        float3 _unpremultiplied;
+        if (texel.w > 0.0)
+        {
+            _unpremultiplied = texel.xyz / float3(texel.w);
+        }
+        else
+        {
+            _unpremultiplied = float3(0.0);
+        }
+        texel = float4(_unpremultiplied.x, _unpremultiplied.y, _unpremultiplied.z, texel.w);
+    }
+    return dot(texel.xyz, float3(0.2125, 0.7154, 0.0721)) * texel.w;
+}
+
+static inline __attribute__((always_inline))
+float4 calculateColor(thread const float2& fragCoord, thread const texture2d<float> colorTexture0, thread const sampler colorTexture0Smplr, thread const texture2d<float> maskTexture0, thread const sampler maskTexture0Smplr, thread const texture2d<float> paintMaskTexture, thread const sampler paintMaskTextureSmplr, thread const texture2d<float> destTexture, thread const sampler destTextureSmplr, thread const texture2d<float> gammaLUT, thread const sampler gammaLUTSmplr, thread const float2& colorTextureSize0, thread const float2& maskTextureSize0, thread const float2& paintMaskTextureSize, thread const float4& filterParams0, thread const float4& filterParams1, thread const float4& filterParams2, thread const float2& framebufferSize, thread const int& ctrl, thread const float3& maskTexCoord0, thread const float2& colorTexCoord0, thread const float4& colorTexRect0, thread const float2& paintMaskTexCoord, thread const float4& paintMaskTexRect, thread const float4& baseColor, thread const int& tileCtrl)
 {
     int maskCtrl0 = (tileCtrl >> 0) & 3;
     float maskAlpha = 1.0;
@@ -568,53 +627,70 @@ float4 calculateColor(thread const float2& fragCoord, thread const texture2d<flo
     float3 param_2 = maskTexCoord0;
     int param_3 = maskCtrl0;
     maskAlpha = sampleMask(param, maskTexture0, maskTexture0Smplr, param_1, param_2, param_3);
+    int paintMaskCtrl = ctrl >> 12;
+    if ((paintMaskCtrl & 3) != 0)
+    {
+        float2 param_4 = paintMaskTextureSize;
+        float2 param_5 = paintMaskTexCoord;
+        float4 param_6 = paintMaskTexRect;
+        int param_7 = paintMaskCtrl;
+        maskAlpha *= samplePaintMask(paintMaskTexture, paintMaskTextureSmplr, param_4, param_5, param_6, param_7);
+    }
     float4 color = baseColor;
     int color0Combine = (ctrl >> 6) & 3;
     if (color0Combine != 0)
     {
         int color0Filter = (ctrl >> 4) & 3;
-        float2 param_4 = colorTexCoord0;
-        float2 param_5 = colorTextureSize0;
-        float2 param_6 = fragCoord;
-        float2 param_7 = framebufferSize;
-        float4 param_8 = filterParams0;
-        float4 param_9 = filterParams1;
-        float4 param_10 = filterParams2;
-        int param_11 = color0Filter;
-        float4 color0 = filterColor(param_4, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_5, param_6, param_7, param_8, param_9, param_10, param_11);
-        float4 param_12 = color;
-        float4 param_13 = color0;
-        int param_14 = color0Combine;
-        color = combineColor0(param_12, param_13, param_14);
+        float2 param_8 = colorTexCoord0;
+        float4 param_9 = colorTexRect0;
+        float2 param_10 = colorTextureSize0;
+        float2 colorTexCoord = wrapColorTexCoord(param_8, param_9, param_10);
+        float2 param_11 = colorTexCoord;
+        float2 param_12 = colorTextureSize0;
+        float2 param_13 = fragCoord;
+        float2 param_14 = framebufferSize;
+        float4 param_15 = filterParams0;
+        float4 param_16 = filterParams1;
+        float4 param_17 = filterParams2;
+        int param_18 = color0Filter;
+        float4 color0 = filterColor(param_11, colorTexture0, colorTexture0Smplr, gammaLUT, gammaLUTSmplr, param_12, param_13, param_14, param_15, param_16, param_17, param_18);
+        float4 param_19 = color;
+        float4 param_20 = color0;
+        int param_21 = color0Combine;
+        color = combineColor0(param_19, param_20, param_21);
     }
     color.w *= maskAlpha;
     int compositeOp = (ctrl >> 8) & 15;
-    float4 param_15 = color;
-    float2 param_16 = framebufferSize;
-    float2 param_17 = fragCoord;
-    int param_18 = compositeOp;
-    color = composite(param_15, destTexture, destTextureSmplr, param_16, param_17, param_18);
+    float4 param_22 = color;
+    float2 param_23 = framebufferSize;
+    float2 param_24 = fragCoord;
+    int param_25 = compositeOp;
+    color = composite(param_22, destTexture, destTextureSmplr, param_23, param_24, param_25);
     float3 _1340 = color.xyz * color.w;
     color = float4(_1340.x, _1340.y, _1340.z, color.w);
     return color;
 }
 
-fragment main0_out main0(main0_in in [[stage_in]], constant float2& uColorTextureSize0 [[buffer(0)]], constant float2& uMaskTextureSize0 [[buffer(1)]], constant float2& uFramebufferSize [[buffer(2)]], texture2d<float> uColorTexture0 [[texture(0)]], texture2d<float> uMaskTexture0 [[texture(1)]], texture2d<float> uDestTexture [[texture(2)]], texture2d<float> uGammaLUT [[texture(3)]], sampler uColorTexture0Smplr [[sampler(0)]], sampler uMaskTexture0Smplr [[sampler(1)]], sampler uDestTextureSmplr [[sampler(2)]], sampler uGammaLUTSmplr [[sampler(3)]], float4 gl_FragCoord [[position]])
+fragment main0_out main0(main0_in in [[stage_in]], constant float2& uColorTextureSize0 [[buffer(0)]], constant float2& uMaskTextureSize0 [[buffer(1)]], constant float2& uFramebufferSize [[buffer(2)]], constant float2& uPaintMaskTextureSize [[buffer(3)]], texture2d<float> uColorTexture0 [[texture(0)]], texture2d<float> uMaskTexture0 [[texture(1)]], texture2d<float> uDestTexture [[texture(2)]], texture2d<float> uGammaLUT [[texture(3)]], texture2d<float> uPaintMaskTexture [[texture(4)]], sampler uColorTexture0Smplr [[sampler(0)]], sampler uMaskTexture0Smplr [[sampler(1)]], sampler uDestTextureSmplr [[sampler(2)]], sampler uGammaLUTSmplr [[sampler(3)]], sampler uPaintMaskTextureSmplr [[sampler(4)]], float4 gl_FragCoord [[position]])
 {
     main0_out out = {};
     float2 param = gl_FragCoord.xy;
     float2 param_1 = uColorTextureSize0;
     float2 param_2 = uMaskTextureSize0;
-    float4 param_3 = in.vFilterParams0;
-    float4 param_4 = in.vFilterParams1;
-    float4 param_5 = in.vFilterParams2;
-    float2 param_6 = uFramebufferSize;
-    int param_7 = int(in.vCtrl);
-    float3 param_8 = in.vMaskTexCoord0;
-    float2 param_9 = in.vColorTexCoord0;
-    float4 param_10 = in.vBaseColor;
-    int param_11 = int(in.vTileCtrl);
-    out.oFragColor = calculateColor(param, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uDestTexture, uDestTextureSmplr, uGammaLUT, uGammaLUTSmplr, param_1, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11);
+    float2 param_3 = uPaintMaskTextureSize;
+    float4 param_4 = in.vFilterParams0;
+    float4 param_5 = in.vFilterParams1;
+    float4 param_6 = in.vFilterParams2;
+    float2 param_7 = uFramebufferSize;
+    int param_8 = int(in.vCtrl);
+    float3 param_9 = in.vMaskTexCoord0;
+    float2 param_10 = in.vColorTexCoord0;
+    float4 param_11 = in.vColorTexRect0;
+    float2 param_12 = in.vPaintMaskTexCoord;
+    float4 param_13 = in.vPaintMaskTexRect;
+    float4 param_14 = in.vBaseColor;
+    int param_15 = int(in.vTileCtrl);
+    out.oFragColor = calculateColor(param, uColorTexture0, uColorTexture0Smplr, uMaskTexture0, uMaskTexture0Smplr, uPaintMaskTexture, uPaintMaskTextureSmplr, uDestTexture, uDestTextureSmplr, uGammaLUT, uGammaLUTSmplr, param_1, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11, param_12, param_13, param_14, param_15);
     return out;
 }
 
@@ -10,12 +10,15 @@ struct main0_out
 {
     float3 vMaskTexCoord0 [[user(locn0)]];
     float2 vColorTexCoord0 [[user(locn1)]];
-    float4 vBaseColor [[user(locn2)]];
-    float vTileCtrl [[user(locn3)]];
-    float4 vFilterParams0 [[user(locn4)]];
-    float4 vFilterParams1 [[user(locn5)]];
-    float4 vFilterParams2 [[user(locn6)]];
-    float vCtrl [[user(locn7)]];
+    float4 vColorTexRect0 [[user(locn2)]];
+    float4 vBaseColor [[user(locn3)]];
+    float vTileCtrl [[user(locn4)]];
+    float4 vFilterParams0 [[user(locn5)]];
+    float4 vFilterParams1 [[user(locn6)]];
+    float4 vFilterParams2 [[user(locn7)]];
+    float2 vPaintMaskTexCoord [[user(locn8)]];
+    float4 vPaintMaskTexRect [[user(locn9)]];
+    float vCtrl [[user(locn10)]];
     float4 gl_Position [[position]];
 };
 
@@ -36,10 +39,10 @@ float4 fetchUnscaled(thread const texture2d<float> srcTexture, thread const samp
 }
 
 static inline __attribute__((always_inline))
-void computeTileVaryings(thread const float2& position, thread const int& colorEntry, thread const texture2d<float> textureMetadata, thread const sampler textureMetadataSmplr, thread const int2& textureMetadataSize, thread float2& outColorTexCoord0, thread float4& outBaseColor, thread float4& outFilterParams0, thread float4& outFilterParams1, thread float4& outFilterParams2, thread int& outCtrl)
+void computeTileVaryings(thread const float2& position, thread const int& colorEntry, thread const texture2d<float> textureMetadata, thread const sampler textureMetadataSmplr, thread const int2& textureMetadataSize, thread float2& outColorTexCoord0, thread float4& outColorTexRect0, thread float4& outBaseColor, thread float4& outFilterParams0, thread float4& outFilterParams1, thread float4& outFilterParams2, thread float2& outPaintMaskTexCoord, thread float4& outPaintMaskTexRect, thread int& outCtrl)
 {
     float2 metadataScale = float2(1.0) / float2(textureMetadataSize);
-    float2 metadataEntryCoord = float2(float((colorEntry % 128) * 8), float(colorEntry / 128));
+    float2 metadataEntryCoord = float2(float((colorEntry % 128) * 10), float(colorEntry / 128));
     float2 param = metadataScale;
     float2 param_1 = metadataEntryCoord;
     int param_2 = 0;
@@ -68,7 +71,22 @@ void computeTileVaryings(thread const float2& position, thread const int& colorE
     float2 param_19 = metadataEntryCoord;
     int param_20 = 6;
     float4 extra = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_18, param_19, param_20);
+    float2 param_21 = metadataScale;
+    float2 param_22 = metadataEntryCoord;
+    int param_23 = 7;
+    float4 colorTexRect0 = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_21, param_22, param_23);
+    float2 param_24 = metadataScale;
+    float2 param_25 = metadataEntryCoord;
+    int param_26 = 8;
+    float4 paintMaskMatrix = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_24, param_25, param_26);
+    float2 param_27 = metadataScale;
+    float2 param_28 = metadataEntryCoord;
+    int param_29 = 9;
+    float4 paintMaskRect = fetchUnscaled(textureMetadata, textureMetadataSmplr, param_27, param_28, param_29);
     outColorTexCoord0 = (float2x2(float2(colorTexMatrix0.xy), float2(colorTexMatrix0.zw)) * position) + colorTexOffsets.xy;
+    outColorTexRect0 = colorTexRect0;
+    outPaintMaskTexCoord = (float2x2(float2(paintMaskMatrix.xy), float2(paintMaskMatrix.zw)) * position) + colorTexOffsets.zw;
+    outPaintMaskTexRect = paintMaskRect;
     outBaseColor = baseColor;
     outFilterParams0 = filterParams0;
     outFilterParams1 = filterParams1;
@@ -113,14 +131,20 @@ vertex main0_out main0(main0_in in [[stage_in]], constant int2& uZBufferSize [[b
     float4 param_5;
     float4 param_6;
     float4 param_7;
-    int param_8;
-    computeTileVaryings(param, param_1, uTextureMetadata, uTextureMetadataSmplr, param_2, param_3, param_4, param_5, param_6, param_7, param_8);
+    float4 param_8;
+    float2 param_9;
+    float4 param_10;
+    int param_11;
+    computeTileVaryings(param, param_1, uTextureMetadata, uTextureMetadataSmplr, param_2, param_3, param_4, param_5, param_6, param_7, param_8, param_9, param_10, param_11);
     out.vColorTexCoord0 = param_3;
-    out.vBaseColor = param_4;
-    out.vFilterParams0 = param_5;
-    out.vFilterParams1 = param_6;
-    out.vFilterParams2 = param_7;
-    int ctrl = param_8;
+    out.vColorTexRect0 = param_4;
+    out.vBaseColor = param_5;
+    out.vFilterParams0 = param_6;
+    out.vFilterParams1 = param_7;
+    out.vFilterParams2 = param_8;
+    out.vPaintMaskTexCoord = param_9;
+    out.vPaintMaskTexRect = param_10;
+    int ctrl = param_11;
     out.vTileCtrl = float(in.aCtrlBackdrop.x);
     out.vCtrl = float(ctrl);
     out.vMaskTexCoord0 = float3(maskTexCoord0, float(in.aCtrlBackdrop.y));
@@ -0,0 +1,154 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uSrcColorSpace: i32,
+    uDestColorSpace: i32,
+    uAlphaMode: i32,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uSrc: texture_2d<f32>;
+@group(1) @binding(1) 
+var uSrcSampler: sampler;
+@group(1) @binding(2) 
+var<uniform> global: bUniforms;
+var<private> vTexCoord_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn decodeSRGB(color: vec3<f32>) -> vec3<f32> {
+    var color_1: vec3<f32>;
+    var magnitude: vec3<f32>;
+    var decoded: vec3<f32>;
+
+    color_1 = color;
+    let _e2 = color_1;
+    magnitude = abs(_e2);
+    let _e5 = magnitude;
+    let _e9 = magnitude;
+    let _e19 = magnitude;
+    decoded = select((_e5 / vec3(12.92f)), pow(((_e9 + vec3(0.055f)) / vec3(1.055f)), vec3(2.4f)), (_e19 > vec3(0.04045f)));
+    let _e25 = color_1;
+    let _e27 = decoded;
+    return (sign(_e25) * _e27);
+}
+
+fn encodeSRGB(color_2: vec3<f32>) -> vec3<f32> {
+    var color_3: vec3<f32>;
+    var magnitude_1: vec3<f32>;
+    var encoded: vec3<f32>;
+
+    color_3 = color_2;
+    let _e2 = color_3;
+    magnitude_1 = abs(_e2);
+    let _e5 = magnitude_1;
+    let _e9 = magnitude_1;
+    let _e17 = magnitude_1;
+    encoded = select((_e5 * 12.92f), ((1.055f * pow(_e9, vec3(0.41666666f))) - vec3(0.055f)), (_e17 > vec3(0.0031308f)));
+    let _e23 = color_3;
+    let _e25 = encoded;
+    return (sign(_e23) * _e25);
+}
+
+fn convertColorSpace(color_4: vec3<f32>) -> vec3<f32> {
+    var color_5: vec3<f32>;
+
+    color_5 = color_4;
+    let _e6 = global.uSrcColorSpace;
+    if (_e6 != 2i) {
+        let _e9 = color_5;
+        let _e10 = decodeSRGB(_e9);
+        color_5 = _e10;
+    }
+    let _e11 = global.uSrcColorSpace;
+    if (_e11 == 1i) {
+        {
+            let _e14 = color_5;
+            color_5 = (_e14 * mat3x3<f32>(vec3<f32>(1.2249401f, -0.2249404f, 0f), vec3<f32>(-0.0420569f, 1.0420572f, 0f), vec3<f32>(-0.0196376f, -0.0786361f, 1.0982735f)));
+        }
+    }
+    let _e29 = global.uDestColorSpace;
+    if (_e29 == 1i) {
+        {
+            let _e32 = color_5;
+            color_5 = (_e32 * mat3x3<f32>(vec3<f32>(0.8224621f, 0.177538f, 0f), vec3<f32>(0.0331941f, 0.9668058f, 0f), vec3<f32>(0.0170827f, 0.0723974f, 0.9105199f)));
+        }
+    }
+    let _e47 = global.uDestColorSpace;
+    if (_e47 != 2i) {
+        let _e50 = color_5;
+        let _e51 = encodeSRGB(_e50);
+        color_5 = _e51;
+    }
+    let _e52 = color_5;
+    return _e52;
+}
+
+fn main_1() {
+    var color_6: vec4<f32>;
+
+    let _e10 = vTexCoord_1;
+    let _e11 = textureSample(uSrc, uSrcSampler, _e10);
+    color_6 = _e11;
+    let _e13 = global.uAlphaMode;
+    if (_e13 == 3i) {
+        {
+            let _e17 = color_6;
+            color_6 = vec4<f32>(1f, 1f, 1f, _e17.x);
+        }
+    } else {
+        let _e20 = global.uAlphaMode;
+        if (_e20 == 0i) {
+            {
+                let _e23 = global.uSrcColorSpace;
+                let _e24 = global.uDestColorSpace;
+                let _e26 = color_6;
+                if ((_e23 != _e24) && (_e26.w > 0f)) {
+                    let _e31 = color_6;
+                    let _e33 = color_6;
+                    let _e37 = convertColorSpace((_e31.xyz / vec3(_e33.w)));
+                    let _e38 = color_6;
+                    let _e40 = (_e37 * _e38.w);
+                    color_6.x = _e40.x;
+                    color_6.y = _e40.y;
+                    color_6.z = _e40.z;
+                }
+            }
+        } else {
+            {
+                let _e47 = global.uAlphaMode;
+                let _e50 = color_6;
+                if ((_e47 == 2i) && (_e50.w > 0f)) {
+                    let _e55 = color_6;
+                    let _e57 = color_6;
+                    let _e60 = (_e55.xyz / vec3(_e57.w));
+                    color_6.x = _e60.x;
+                    color_6.y = _e60.y;
+                    color_6.z = _e60.z;
+                }
+                let _e67 = global.uSrcColorSpace;
+                let _e68 = global.uDestColorSpace;
+                if (_e67 != _e68) {
+                    let _e70 = color_6;
+                    let _e72 = convertColorSpace(_e70.xyz);
+                    color_6.x = _e72.x;
+                    color_6.y = _e72.y;
+                    color_6.z = _e72.z;
+                }
+            }
+        }
+    }
+    let _e79 = color_6;
+    oFragColor = _e79;
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord: vec2<f32>) -> FragmentOutput {
+    vTexCoord_1 = vTexCoord;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
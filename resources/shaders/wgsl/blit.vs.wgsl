@@ -0,0 +1,44 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uDestRect: vec4<f32>,
+    uFramebufferSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> vTexCoord: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+    var texCoord: vec2<f32>;
+
+    let _e6 = global.uDestRect;
+    let _e8 = global.uDestRect;
+    let _e10 = aPosition_1;
+    let _e13 = global.uFramebufferSize;
+    position = (mix(_e6.xy, _e8.zw, vec2<f32>(_e10)) / _e13);
+    let _e16 = aPosition_1;
+    texCoord = vec2<f32>(_e16);
+    let _e19 = texCoord;
+    vTexCoord = _e19;
+    let _e25 = position;
+    let _e26 = mix(vec2(-1f), vec2(1f), _e25);
+    gl_Position = vec4<f32>(_e26.x, _e26.y, 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = vTexCoord;
+    let _e5 = gl_Position;
+    return VertexOutput(_e3, _e5);
+}
@@ -0,0 +1,27 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uColor: vec4<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    let _e3 = global.uColor;
+    let _e4 = _e3.xyz;
+    let _e10 = global.uColor;
+    oFragColor = (vec4<f32>(_e4.x, _e4.y, _e4.z, 1f) * _e10.w);
+    return;
+}
+
+@fragment 
+fn main() -> FragmentOutput {
+    main_1();
+    let _e1 = oFragColor;
+    return FragmentOutput(_e1);
+}
@@ -0,0 +1,36 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uRect: vec4<f32>,
+    uFramebufferSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e5 = global.uRect;
+    let _e7 = global.uRect;
+    let _e9 = aPosition_1;
+    let _e12 = global.uFramebufferSize;
+    position = (((mix(_e5.xy, _e7.zw, vec2<f32>(_e9)) / _e12) * 2f) - vec2(1f));
+    let _e21 = position;
+    let _e23 = position;
+    gl_Position = vec4<f32>(_e21.x, -(_e23.y), 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = gl_Position;
+    return VertexOutput(_e3);
+}
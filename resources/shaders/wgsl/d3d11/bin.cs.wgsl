@@ -0,0 +1,526 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uMicrolineCount: i32,
+    uMaxFillCount: i32,
+}
+
+struct bMicrolines {
+    iMicrolines: array<vec4<u32>>,
+}
+
+struct bMetadata {
+    iMetadata: array<vec4<i32>>,
+}
+
+struct bFills {
+    iFills: array<u32>,
+}
+
+struct bIndirectDrawParams {
+    iIndirectDrawParams: array<atomic<u32>>,
+}
+
+struct bTiles {
+    iTiles: array<atomic<u32>>,
+}
+
+struct bBackdrops {
+    iBackdrops: array<atomic<u32>>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage> global_1: bMicrolines;
+@group(0) @binding(1) 
+var<storage> global_2: bMetadata;
+@group(0) @binding(2) 
+var<storage, read_write> global_3: bIndirectDrawParams;
+@group(0) @binding(3) 
+var<storage, read_write> global_4: bFills;
+@group(0) @binding(4) 
+var<storage, read_write> global_5: bTiles;
+@group(0) @binding(5) 
+var<storage, read_write> global_6: bBackdrops;
+var<private> gl_GlobalInvocationID: vec3<u32>;
+
+fn computeTileIndexNoCheck(tileCoords: vec2<i32>, pathTileRect: vec4<i32>, pathTileOffset: u32) -> u32 {
+    var tileCoords_1: vec2<i32>;
+    var pathTileRect_1: vec4<i32>;
+    var pathTileOffset_1: u32;
+    var offsetCoords: vec2<i32>;
+
+    tileCoords_1 = tileCoords;
+    pathTileRect_1 = pathTileRect;
+    pathTileOffset_1 = pathTileOffset;
+    let _e6 = tileCoords_1;
+    let _e7 = pathTileRect_1;
+    offsetCoords = (_e6 - _e7.xy);
+    let _e11 = pathTileOffset_1;
+    let _e12 = offsetCoords;
+    let _e16 = offsetCoords;
+    let _e18 = pathTileRect_1;
+    let _e20 = pathTileRect_1;
+    return ((_e11 + u32(_e12.x)) + u32((_e16.y * (_e18.z - _e20.x))));
+}
+
+fn computeTileOutcodes(tileCoords_2: vec2<i32>, pathTileRect_2: vec4<i32>) -> vec4<bool> {
+    var tileCoords_3: vec2<i32>;
+    var pathTileRect_3: vec4<i32>;
+
+    tileCoords_3 = tileCoords_2;
+    pathTileRect_3 = pathTileRect_2;
+    let _e4 = tileCoords_3;
+    let _e5 = pathTileRect_3;
+    let _e7 = (_e4 < _e5.xy);
+    let _e8 = tileCoords_3;
+    let _e9 = pathTileRect_3;
+    let _e11 = (_e8 >= _e9.zw);
+    return vec4<bool>(_e7.x, _e7.y, _e11.x, _e11.y);
+}
+
+fn computeTileIndex(tileCoords_4: vec2<i32>, pathTileRect_4: vec4<i32>, pathTileOffset_2: u32, outTileIndex: ptr<function, u32>) -> bool {
+    var tileCoords_5: vec2<i32>;
+    var pathTileRect_5: vec4<i32>;
+    var pathTileOffset_3: u32;
+
+    tileCoords_5 = tileCoords_4;
+    pathTileRect_5 = pathTileRect_4;
+    pathTileOffset_3 = pathTileOffset_2;
+    let _e7 = tileCoords_5;
+    let _e8 = pathTileRect_5;
+    let _e9 = pathTileOffset_3;
+    let _e10 = computeTileIndexNoCheck(_e7, _e8, _e9);
+    (*outTileIndex) = _e10;
+    let _e11 = tileCoords_5;
+    let _e12 = pathTileRect_5;
+    let _e13 = computeTileOutcodes(_e11, _e12);
+    let _e14 = tileCoords_5;
+    let _e15 = pathTileRect_5;
+    let _e16 = computeTileOutcodes(_e14, _e15);
+    return !(any(_e16));
+}
+
+fn addFill(lineSegment: vec4<f32>, tileCoords_6: vec2<i32>, pathTileRect_6: vec4<i32>, pathTileOffset_4: u32) {
+    var lineSegment_1: vec4<f32>;
+    var tileCoords_7: vec2<i32>;
+    var pathTileRect_7: vec4<i32>;
+    var pathTileOffset_5: u32;
+    var tileIndex: u32;
+    var scaledLocalLine: vec4<u32>;
+    var fillIndex: u32;
+    var fillLink: u32;
+
+    lineSegment_1 = lineSegment;
+    tileCoords_7 = tileCoords_6;
+    pathTileRect_7 = pathTileRect_6;
+    pathTileOffset_5 = pathTileOffset_4;
+    let _e17 = tileCoords_7;
+    let _e18 = pathTileRect_7;
+    let _e19 = pathTileOffset_5;
+    let _e20 = computeTileIndex(_e17, _e18, _e19, (&tileIndex));
+    if !(_e20) {
+        {
+            return;
+        }
+    }
+    let _e22 = lineSegment_1;
+    let _e23 = tileCoords_7;
+    scaledLocalLine = vec4<u32>(((_e22 - vec4<f32>((_e23.xyxy * vec4(16i)))) * vec4(256f)));
+    let _e35 = scaledLocalLine;
+    let _e37 = scaledLocalLine;
+    if (_e35.x == _e37.z) {
+        return;
+    }
+    let _e42 = atomicAdd((&global_3.iIndirectDrawParams[1]), 1u);
+    fillIndex = _e42;
+    let _e44 = tileIndex;
+    let _e50 = fillIndex;
+    let _e53 = atomicExchange((&global_5.iTiles[((_e44 * 4u) + 1u)]), u32(i32(_e50)));
+    fillLink = _e53;
+    let _e55 = fillIndex;
+    let _e56 = global.uMaxFillCount;
+    if (_e55 < u32(_e56)) {
+        {
+            let _e59 = fillIndex;
+            let _e65 = scaledLocalLine;
+            let _e67 = scaledLocalLine;
+            global_4.iFills[((_e59 * 3u) + 0u)] = (_e65.x | (_e67.y << 16u));
+            let _e72 = fillIndex;
+            let _e78 = scaledLocalLine;
+            let _e80 = scaledLocalLine;
+            global_4.iFills[((_e72 * 3u) + 1u)] = (_e78.z | (_e80.w << 16u));
+            let _e85 = fillIndex;
+            let _e91 = fillLink;
+            global_4.iFills[((_e85 * 3u) + 2u)] = _e91;
+            return;
+        }
+    } else {
+        return;
+    }
+}
+
+fn adjustBackdrop(backdropDelta: i32, tileCoords_8: vec2<i32>, pathTileRect_8: vec4<i32>, pathTileOffset_6: u32, pathBackdropOffset: u32) {
+    var backdropDelta_1: i32;
+    var tileCoords_9: vec2<i32>;
+    var pathTileRect_9: vec4<i32>;
+    var pathTileOffset_7: u32;
+    var pathBackdropOffset_1: u32;
+    var outcodes: vec4<bool>;
+    var backdropIndex: u32;
+    var tileIndex_1: u32;
+
+    backdropDelta_1 = backdropDelta;
+    tileCoords_9 = tileCoords_8;
+    pathTileRect_9 = pathTileRect_8;
+    pathTileOffset_7 = pathTileOffset_6;
+    pathBackdropOffset_1 = pathBackdropOffset;
+    let _e14 = tileCoords_9;
+    let _e15 = pathTileRect_9;
+    let _e16 = computeTileOutcodes(_e14, _e15);
+    outcodes = _e16;
+    let _e18 = outcodes;
+    if any(_e18) {
+        {
+            let _e20 = outcodes;
+            let _e23 = outcodes;
+            let _e26 = outcodes;
+            if ((!(_e20.x) && _e23.y) && !(_e26.z)) {
+                {
+                    let _e30 = pathBackdropOffset_1;
+                    let _e31 = tileCoords_9;
+                    let _e33 = pathTileRect_9;
+                    backdropIndex = (_e30 + u32((_e31.x - _e33.x)));
+                    let _e39 = backdropIndex;
+                    let _e43 = backdropDelta_1;
+                    let _e45 = atomicAdd((&global_6.iBackdrops[(_e39 * 3u)]), u32(_e43));
+                    return;
+                }
+            } else {
+                return;
+            }
+        }
+    } else {
+        {
+            let _e46 = tileCoords_9;
+            let _e47 = pathTileRect_9;
+            let _e48 = pathTileOffset_7;
+            let _e49 = computeTileIndexNoCheck(_e46, _e47, _e48);
+            tileIndex_1 = _e49;
+            let _e51 = tileIndex_1;
+            let _e57 = backdropDelta_1;
+            let _e61 = atomicAdd((&global_5.iTiles[((_e51 * 4u) + 2u)]), (u32(_e57) << 24u));
+            return;
+        }
+    }
+}
+
+fn unpackMicroline(packedMicroline: vec4<u32>, outPathIndex: ptr<function, u32>) -> vec4<f32> {
+    var packedMicroline_1: vec4<u32>;
+    var signedMicroline: vec4<i32>;
+
+    packedMicroline_1 = packedMicroline;
+    let _e3 = packedMicroline_1;
+    (*outPathIndex) = _e3.w;
+    let _e5 = packedMicroline_1;
+    signedMicroline = vec4<i32>(_e5);
+    let _e8 = signedMicroline;
+    let _e14 = signedMicroline;
+    let _e18 = signedMicroline;
+    let _e24 = signedMicroline;
+    let _e33 = signedMicroline;
+    let _e37 = signedMicroline;
+    let _e43 = signedMicroline;
+    let _e49 = signedMicroline;
+    return (vec4<f32>(f32(((_e8.x << 16u) >> 16u)), f32((_e14.x >> 16u)), f32(((_e18.y << 16u) >> 16u)), f32((_e24.y >> 16u))) + (vec4<f32>(f32((_e33.z & 255i)), f32(((_e37.z >> 8u) & 255i)), f32(((_e43.z >> 16u) & 255i)), f32(((_e49.z >> 24u) & 255i))) / vec4(256f)));
+}
+
+fn main_1() {
+    var segmentIndex: u32;
+    var pathIndex: u32;
+    var lineSegment_2: vec4<f32>;
+    var pathTileRect_10: vec4<i32>;
+    var pathTileOffset_8: u32;
+    var pathBackdropOffset_2: u32;
+    var tileSize: vec2<i32> = vec2(16i);
+    var tileLineSegment: vec4<i32>;
+    var fromTileCoords: vec2<i32>;
+    var toTileCoords: vec2<i32>;
+    var vector: vec2<f32>;
+    var local: f32;
+    var local_1: f32;
+    var vectorIsNegative: vec2<f32>;
+    var local_2: i32;
+    var local_3: i32;
+    var tileStep: vec2<i32>;
+    var local_4: i32;
+    var local_5: i32;
+    var firstTileCrossing: vec2<f32>;
+    var tMax: vec2<f32>;
+    var tDelta: vec2<f32>;
+    var currentPosition: vec2<f32>;
+    var tileCoords_10: vec2<i32>;
+    var lastStepDirection: i32 = 0i;
+    var iteration: u32 = 0u;
+    var nextStepDirection: i32;
+    var local_6: f32;
+    var local_7: f32;
+    var nextT: f32;
+    var nextPosition: vec2<f32>;
+    var clippedLineSegment: vec4<f32>;
+    var auxiliarySegment: vec4<f32>;
+    var haveAuxiliarySegment: bool;
+
+    let _e7 = gl_GlobalInvocationID;
+    segmentIndex = _e7.x;
+    let _e10 = segmentIndex;
+    let _e11 = global.uMicrolineCount;
+    if (_e10 >= u32(_e11)) {
+        return;
+    }
+    let _e15 = segmentIndex;
+    let _e17 = global_1.iMicrolines[_e15];
+    let _e18 = unpackMicroline(_e17, (&pathIndex));
+    lineSegment_2 = _e18;
+    let _e20 = pathIndex;
+    let _e26 = global_2.iMetadata[((_e20 * 3u) + 0u)];
+    pathTileRect_10 = _e26;
+    let _e28 = pathIndex;
+    let _e34 = global_2.iMetadata[((_e28 * 3u) + 1u)];
+    pathTileOffset_8 = u32(_e34.x);
+    let _e38 = pathIndex;
+    let _e44 = global_2.iMetadata[((_e38 * 3u) + 2u)];
+    pathBackdropOffset_2 = u32(_e44.x);
+    let _e51 = lineSegment_2;
+    let _e52 = tileSize;
+    tileLineSegment = vec4<i32>(floor((_e51 / vec4<f32>(_e52.xyxy))));
+    let _e59 = tileLineSegment;
+    fromTileCoords = _e59.xy;
+    let _e62 = tileLineSegment;
+    toTileCoords = _e62.zw;
+    let _e65 = lineSegment_2;
+    let _e67 = lineSegment_2;
+    vector = (_e65.zw - _e67.xy);
+    let _e71 = vector;
+    if (_e71.x < 0f) {
+        local = -1f;
+    } else {
+        local = 0f;
+    }
+    let _e78 = local;
+    let _e79 = vector;
+    if (_e79.y < 0f) {
+        local_1 = -1f;
+    } else {
+        local_1 = 0f;
+    }
+    let _e86 = local_1;
+    vectorIsNegative = vec2<f32>(_e78, _e86);
+    let _e89 = vector;
+    if (_e89.x < 0f) {
+        local_2 = -1i;
+    } else {
+        local_2 = 1i;
+    }
+    let _e96 = local_2;
+    let _e97 = vector;
+    if (_e97.y < 0f) {
+        local_3 = -1i;
+    } else {
+        local_3 = 1i;
+    }
+    let _e104 = local_3;
+    tileStep = vec2<i32>(_e96, _e104);
+    let _e107 = fromTileCoords;
+    let _e108 = vector;
+    if (_e108.x >= 0f) {
+        local_4 = 1i;
+    } else {
+        local_4 = 0i;
+    }
+    let _e115 = local_4;
+    let _e116 = vector;
+    if (_e116.y >= 0f) {
+        local_5 = 1i;
+    } else {
+        local_5 = 0i;
+    }
+    let _e123 = local_5;
+    let _e126 = tileSize;
+    firstTileCrossing = vec2<f32>(((_e107 + vec2<i32>(_e115, _e123)) * _e126));
+    let _e130 = firstTileCrossing;
+    let _e131 = lineSegment_2;
+    let _e134 = vector;
+    tMax = ((_e130 - _e131.xy) / _e134);
+    let _e137 = tileSize;
+    let _e138 = vector;
+    tDelta = abs((vec2<f32>(_e137) / _e138));
+    let _e143 = lineSegment_2;
+    currentPosition = _e143.xy;
+    let _e146 = fromTileCoords;
+    tileCoords_10 = _e146;
+    loop {
+        let _e152 = iteration;
+        if !((_e152 < 1024u)) {
+            break;
+        }
+        {
+            let _e157 = tMax;
+            let _e159 = tMax;
+            if (_e157.x < _e159.y) {
+                nextStepDirection = 1i;
+            } else {
+                let _e163 = tMax;
+                let _e165 = tMax;
+                if (_e163.x > _e165.y) {
+                    nextStepDirection = 2i;
+                } else {
+                    let _e169 = tileStep;
+                    if (f32(_e169.x) > 0f) {
+                        nextStepDirection = 1i;
+                    } else {
+                        nextStepDirection = 2i;
+                    }
+                }
+            }
+            let _e176 = nextStepDirection;
+            if (_e176 == 1i) {
+                let _e179 = tMax;
+                local_6 = _e179.x;
+            } else {
+                let _e181 = tMax;
+                local_6 = _e181.y;
+            }
+            let _e184 = nextStepDirection;
+            if (_e184 == 1i) {
+                let _e187 = tMax;
+                local_7 = _e187.x;
+            } else {
+                let _e189 = tMax;
+                local_7 = _e189.y;
+            }
+            let _e192 = local_7;
+            nextT = min(_e192, 1f);
+            let _e196 = tileCoords_10;
+            let _e197 = toTileCoords;
+            if all((_e196 == _e197)) {
+                nextStepDirection = 0i;
+            }
+            let _e201 = lineSegment_2;
+            let _e203 = lineSegment_2;
+            let _e205 = nextT;
+            nextPosition = mix(_e201.xy, _e203.zw, vec2(_e205));
+            let _e209 = currentPosition;
+            let _e210 = nextPosition;
+            clippedLineSegment = vec4<f32>(_e209.x, _e209.y, _e210.x, _e210.y);
+            let _e217 = clippedLineSegment;
+            let _e218 = tileCoords_10;
+            let _e219 = pathTileRect_10;
+            let _e220 = pathTileOffset_8;
+            addFill(_e217, _e218, _e219, _e220);
+            haveAuxiliarySegment = false;
+            let _e224 = tileStep;
+            let _e228 = nextStepDirection;
+            if ((_e224.y < 0i) && (_e228 == 2i)) {
+                {
+                    let _e232 = clippedLineSegment;
+                    let _e233 = _e232.zw;
+                    let _e234 = tileCoords_10;
+                    let _e235 = tileSize;
+                    let _e237 = vec2<f32>((_e234 * _e235));
+                    auxiliarySegment = vec4<f32>(_e233.x, _e233.y, _e237.x, _e237.y);
+                    haveAuxiliarySegment = true;
+                }
+            } else {
+                let _e244 = tileStep;
+                let _e248 = lastStepDirection;
+                if ((_e244.y > 0i) && (_e248 == 2i)) {
+                    {
+                        let _e252 = tileCoords_10;
+                        let _e253 = tileSize;
+                        let _e255 = vec2<f32>((_e252 * _e253));
+                        let _e256 = clippedLineSegment;
+                        let _e257 = _e256.xy;
+                        auxiliarySegment = vec4<f32>(_e255.x, _e255.y, _e257.x, _e257.y);
+                        haveAuxiliarySegment = true;
+                    }
+                }
+            }
+            let _e264 = haveAuxiliarySegment;
+            if _e264 {
+                let _e265 = auxiliarySegment;
+                let _e266 = tileCoords_10;
+                let _e267 = pathTileRect_10;
+                let _e268 = pathTileOffset_8;
+                addFill(_e265, _e266, _e267, _e268);
+            }
+            let _e269 = tileStep;
+            let _e273 = lastStepDirection;
+            if ((_e269.x < 0i) && (_e273 == 1i)) {
+                {
+                    let _e278 = tileCoords_10;
+                    let _e279 = pathTileRect_10;
+                    let _e280 = pathTileOffset_8;
+                    let _e281 = pathBackdropOffset_2;
+                    adjustBackdrop(1i, _e278, _e279, _e280, _e281);
+                }
+            } else {
+                let _e282 = tileStep;
+                let _e286 = nextStepDirection;
+                if ((_e282.x > 0i) && (_e286 == 1i)) {
+                    {
+                        let _e291 = tileCoords_10;
+                        let _e292 = pathTileRect_10;
+                        let _e293 = pathTileOffset_8;
+                        let _e294 = pathBackdropOffset_2;
+                        adjustBackdrop(-1i, _e291, _e292, _e293, _e294);
+                    }
+                }
+            }
+            let _e295 = nextStepDirection;
+            if (_e295 == 1i) {
+                {
+                    let _e299 = tMax;
+                    let _e301 = tDelta;
+                    tMax.x = (_e299.x + _e301.x);
+                    let _e305 = tileCoords_10;
+                    let _e307 = tileStep;
+                    tileCoords_10.x = (_e305.x + _e307.x);
+                }
+            } else {
+                let _e310 = nextStepDirection;
+                if (_e310 == 2i) {
+                    {
+                        let _e314 = tMax;
+                        let _e316 = tDelta;
+                        tMax.y = (_e314.y + _e316.y);
+                        let _e320 = tileCoords_10;
+                        let _e322 = tileStep;
+                        tileCoords_10.y = (_e320.y + _e322.y);
+                    }
+                } else {
+                    let _e325 = nextStepDirection;
+                    if (_e325 == 0i) {
+                        {
+                            break;
+                        }
+                    }
+                }
+            }
+            let _e328 = nextPosition;
+            currentPosition = _e328;
+            let _e329 = nextStepDirection;
+            lastStepDirection = _e329;
+            let _e330 = iteration;
+            iteration = (_e330 + 1u);
+        }
+    }
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) param: vec3<u32>) {
+    gl_GlobalInvocationID = param;
+    main_1();
+    return;
+}
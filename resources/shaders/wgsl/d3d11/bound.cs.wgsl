@@ -0,0 +1,125 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uPathCount: i32,
+    uTileCount: i32,
+}
+
+struct bTilePathInfo {
+    iTilePathInfo: array<vec4<u32>>,
+}
+
+struct bTiles {
+    iTiles: array<u32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage> global_1: bTilePathInfo;
+@group(0) @binding(1) 
+var<storage, read_write> global_2: bTiles;
+var<private> gl_GlobalInvocationID: vec3<u32>;
+
+fn main_1() {
+    var tileIndex: u32;
+    var lowPathIndex: u32 = 0u;
+    var highPathIndex: u32;
+    var iteration: i32 = 0i;
+    var midPathIndex: u32;
+    var midTileIndex: u32;
+    var pathIndex: u32;
+    var pathInfo: vec4<u32>;
+    var packedTileRect: vec2<i32>;
+    var tileRect: vec4<i32>;
+    var tileOffset: u32;
+    var tileWidth: u32;
+    var tileCoords: vec2<i32>;
+
+    let _e9 = gl_GlobalInvocationID;
+    tileIndex = _e9.x;
+    let _e12 = tileIndex;
+    let _e13 = global.uTileCount;
+    if (_e12 >= u32(_e13)) {
+        return;
+    }
+    let _e18 = global.uPathCount;
+    highPathIndex = u32(_e18);
+    loop {
+        let _e23 = iteration;
+        let _e26 = lowPathIndex;
+        let _e29 = highPathIndex;
+        if !(((_e23 < 1024i) && ((_e26 + 1u) < _e29))) {
+            break;
+        }
+        {
+            let _e33 = lowPathIndex;
+            let _e34 = highPathIndex;
+            let _e35 = lowPathIndex;
+            midPathIndex = (_e33 + ((_e34 - _e35) / 2u));
+            let _e41 = midPathIndex;
+            let _e43 = global_1.iTilePathInfo[_e41];
+            midTileIndex = _e43.z;
+            let _e46 = tileIndex;
+            let _e47 = midTileIndex;
+            if (_e46 < _e47) {
+                {
+                    let _e49 = midPathIndex;
+                    highPathIndex = _e49;
+                }
+            } else {
+                {
+                    let _e50 = midPathIndex;
+                    lowPathIndex = _e50;
+                    let _e51 = tileIndex;
+                    let _e52 = midTileIndex;
+                    if (_e51 == _e52) {
+                        break;
+                    }
+                }
+            }
+            let _e54 = iteration;
+            iteration = (_e54 + 1i);
+        }
+    }
+    let _e57 = lowPathIndex;
+    pathIndex = _e57;
+    let _e59 = pathIndex;
+    let _e61 = global_1.iTilePathInfo[_e59];
+    pathInfo = _e61;
+    let _e63 = pathInfo;
+    packedTileRect = vec2<i32>(_e63.xy);
+    let _e67 = packedTileRect;
+    let _e73 = packedTileRect;
+    let _e77 = packedTileRect;
+    let _e83 = packedTileRect;
+    tileRect = vec4<i32>(((_e67.x << 16u) >> 16u), (_e73.x >> 16u), ((_e77.y << 16u) >> 16u), (_e83.y >> 16u));
+    let _e89 = tileIndex;
+    let _e90 = pathInfo;
+    tileOffset = (_e89 - _e90.z);
+    let _e94 = tileRect;
+    let _e96 = tileRect;
+    tileWidth = u32((_e94.z - _e96.x));
+    let _e101 = tileRect;
+    let _e103 = tileOffset;
+    let _e104 = tileWidth;
+    let _e106 = tileOffset;
+    let _e107 = tileWidth;
+    tileCoords = (_e101.xy + vec2<i32>(i32((_e103 % _e104)), i32((_e106 / _e107))));
+    let _e114 = tileIndex;
+    global_2.iTiles[((_e114 * 4u) + 0u)] = 4294967295u;
+    let _e121 = tileIndex;
+    global_2.iTiles[((_e121 * 4u) + 1u)] = 4294967295u;
+    let _e128 = tileIndex;
+    global_2.iTiles[((_e128 * 4u) + 2u)] = 16777215u;
+    let _e135 = tileIndex;
+    let _e141 = pathInfo;
+    global_2.iTiles[((_e135 * 4u) + 3u)] = _e141.w;
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) param: vec3<u32>) {
+    gl_GlobalInvocationID = param;
+    main_1();
+    return;
+}
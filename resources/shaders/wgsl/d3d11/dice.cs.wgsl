@@ -0,0 +1,494 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uTransform: mat2x2<f32>,
+    uTranslation: vec2<f32>,
+    uPathCount: i32,
+    uLastBatchSegmentIndex: i32,
+    uMaxMicrolineCount: i32,
+    uTolerance: f32,
+}
+
+struct bDiceMetadata {
+    iDiceMetadata: array<vec4<u32>>,
+}
+
+struct bPoints {
+    iPoints: array<vec2<f32>>,
+}
+
+struct bInputIndices {
+    iInputIndices: array<vec2<u32>>,
+}
+
+struct bPathTransforms {
+    iPathTransforms: array<vec4<f32>>,
+}
+
+struct bMicrolines {
+    iMicrolines: array<vec4<u32>>,
+}
+
+struct bComputeIndirectParams {
+    iComputeIndirectParams: array<atomic<u32>>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage, read_write> global_1: bComputeIndirectParams;
+@group(0) @binding(1) 
+var<storage> global_2: bDiceMetadata;
+@group(0) @binding(2) 
+var<storage> global_3: bPoints;
+@group(0) @binding(3) 
+var<storage> global_4: bInputIndices;
+@group(0) @binding(5) 
+var<storage> global_5: bPathTransforms;
+@group(0) @binding(4) 
+var<storage, read_write> global_6: bMicrolines;
+var<private> gl_GlobalInvocationID: vec3<u32>;
+
+fn emitMicroline(microlineSegment: vec4<f32>, pathIndex: u32, outputMicrolineIndex: u32) {
+    var microlineSegment_1: vec4<f32>;
+    var pathIndex_1: u32;
+    var outputMicrolineIndex_1: u32;
+    var microlineSubpixels: vec4<i32>;
+    var microlinePixels: vec4<i32>;
+    var microlineFractPixels: vec4<i32>;
+
+    microlineSegment_1 = microlineSegment;
+    pathIndex_1 = pathIndex;
+    outputMicrolineIndex_1 = outputMicrolineIndex;
+    let _e10 = outputMicrolineIndex_1;
+    let _e11 = global.uMaxMicrolineCount;
+    if (_e10 >= u32(_e11)) {
+        return;
+    }
+    let _e14 = microlineSegment_1;
+    microlineSubpixels = vec4<i32>(round((clamp(_e14, vec4(-32768f), vec4(32767f)) * 256f)));
+    let _e25 = microlineSubpixels;
+    microlinePixels = vec4<i32>(floor((vec4<f32>(_e25) / vec4(256f))));
+    let _e33 = microlineSubpixels;
+    let _e34 = microlinePixels;
+    microlineFractPixels = (_e33 - (_e34 * 256i));
+    let _e39 = outputMicrolineIndex_1;
+    let _e41 = microlinePixels;
+    let _e46 = microlinePixels;
+    let _e52 = microlinePixels;
+    let _e57 = microlinePixels;
+    let _e63 = microlineFractPixels;
+    let _e66 = microlineFractPixels;
+    let _e72 = microlineFractPixels;
+    let _e78 = microlineFractPixels;
+    let _e84 = pathIndex_1;
+    global_6.iMicrolines[_e39] = vec4<u32>(((u32(_e41.x) & 65535u) | (u32(_e46.y) << 16u)), ((u32(_e52.z) & 65535u) | (u32(_e57.w) << 16u)), (((u32(_e63.x) | (u32(_e66.y) << 8u)) | (u32(_e72.z) << 16u)) | (u32(_e78.w) << 24u)), _e84);
+    return;
+}
+
+fn curveIsFlat(baseline: vec4<f32>, ctrl: vec4<f32>) -> bool {
+    var baseline_1: vec4<f32>;
+    var ctrl_1: vec4<f32>;
+    var uv: vec4<f32>;
+
+    baseline_1 = baseline;
+    ctrl_1 = ctrl;
+    let _e8 = ctrl_1;
+    let _e12 = baseline_1;
+    let _e15 = baseline_1;
+    uv = (((vec4(3f) * _e8) - (vec4(2f) * _e12)) - _e15.zwxy);
+    let _e19 = uv;
+    let _e20 = uv;
+    uv = (_e19 * _e20);
+    let _e22 = uv;
+    let _e23 = uv;
+    uv = max(_e22, _e23.zwxy);
+    let _e26 = uv;
+    let _e28 = uv;
+    let _e32 = global.uTolerance;
+    let _e34 = global.uTolerance;
+    return ((_e26.x + _e28.y) <= ((16f * _e32) * _e34));
+}
+
+fn subdivideCurve(baseline_2: vec4<f32>, ctrl_2: vec4<f32>, t: f32, prevBaseline: ptr<function, vec4<f32>>, prevCtrl: ptr<function, vec4<f32>>, nextBaseline: ptr<function, vec4<f32>>, nextCtrl: ptr<function, vec4<f32>>) {
+    var baseline_3: vec4<f32>;
+    var ctrl_3: vec4<f32>;
+    var t_1: f32;
+    var p0_: vec2<f32>;
+    var p1_: vec2<f32>;
+    var p2_: vec2<f32>;
+    var p3_: vec2<f32>;
+    var p0p1_: vec2<f32>;
+    var p1p2_: vec2<f32>;
+    var p2p3_: vec2<f32>;
+    var p0p1p2_: vec2<f32>;
+    var p1p2p3_: vec2<f32>;
+    var p0p1p2p3_: vec2<f32>;
+
+    baseline_3 = baseline_2;
+    ctrl_3 = ctrl_2;
+    t_1 = t;
+    let _e10 = baseline_3;
+    p0_ = _e10.xy;
+    let _e13 = ctrl_3;
+    p1_ = _e13.xy;
+    let _e16 = ctrl_3;
+    p2_ = _e16.zw;
+    let _e19 = baseline_3;
+    p3_ = _e19.zw;
+    let _e22 = p0_;
+    let _e23 = p1_;
+    let _e24 = t_1;
+    p0p1_ = mix(_e22, _e23, vec2(_e24));
+    let _e28 = p1_;
+    let _e29 = p2_;
+    let _e30 = t_1;
+    p1p2_ = mix(_e28, _e29, vec2(_e30));
+    let _e34 = p2_;
+    let _e35 = p3_;
+    let _e36 = t_1;
+    p2p3_ = mix(_e34, _e35, vec2(_e36));
+    let _e40 = p0p1_;
+    let _e41 = p1p2_;
+    let _e42 = t_1;
+    p0p1p2_ = mix(_e40, _e41, vec2(_e42));
+    let _e46 = p1p2_;
+    let _e47 = p2p3_;
+    let _e48 = t_1;
+    p1p2p3_ = mix(_e46, _e47, vec2(_e48));
+    let _e52 = p0p1p2_;
+    let _e53 = p1p2p3_;
+    let _e54 = t_1;
+    p0p1p2p3_ = mix(_e52, _e53, vec2(_e54));
+    let _e58 = p0_;
+    let _e59 = p0p1p2p3_;
+    (*prevBaseline) = vec4<f32>(_e58.x, _e58.y, _e59.x, _e59.y);
+    let _e65 = p0p1_;
+    let _e66 = p0p1p2_;
+    (*prevCtrl) = vec4<f32>(_e65.x, _e65.y, _e66.x, _e66.y);
+    let _e72 = p0p1p2p3_;
+    let _e73 = p3_;
+    (*nextBaseline) = vec4<f32>(_e72.x, _e72.y, _e73.x, _e73.y);
+    let _e79 = p1p2p3_;
+    let _e80 = p2p3_;
+    (*nextCtrl) = vec4<f32>(_e79.x, _e79.y, _e80.x, _e80.y);
+    return;
+}
+
+fn sampleCurve(baseline_4: vec4<f32>, ctrl_4: vec4<f32>, t_2: f32) -> vec2<f32> {
+    var baseline_5: vec4<f32>;
+    var ctrl_5: vec4<f32>;
+    var t_3: f32;
+    var p0_1: vec2<f32>;
+    var p1_1: vec2<f32>;
+    var p2_1: vec2<f32>;
+    var p3_1: vec2<f32>;
+    var p0p1_1: vec2<f32>;
+    var p1p2_1: vec2<f32>;
+    var p2p3_1: vec2<f32>;
+    var p0p1p2_1: vec2<f32>;
+    var p1p2p3_1: vec2<f32>;
+
+    baseline_5 = baseline_4;
+    ctrl_5 = ctrl_4;
+    t_3 = t_2;
+    let _e6 = baseline_5;
+    p0_1 = _e6.xy;
+    let _e9 = ctrl_5;
+    p1_1 = _e9.xy;
+    let _e12 = ctrl_5;
+    p2_1 = _e12.zw;
+    let _e15 = baseline_5;
+    p3_1 = _e15.zw;
+    let _e18 = p0_1;
+    let _e19 = p1_1;
+    let _e20 = t_3;
+    p0p1_1 = mix(_e18, _e19, vec2(_e20));
+    let _e24 = p1_1;
+    let _e25 = p2_1;
+    let _e26 = t_3;
+    p1p2_1 = mix(_e24, _e25, vec2(_e26));
+    let _e30 = p2_1;
+    let _e31 = p3_1;
+    let _e32 = t_3;
+    p2p3_1 = mix(_e30, _e31, vec2(_e32));
+    let _e36 = p0p1_1;
+    let _e37 = p1p2_1;
+    let _e38 = t_3;
+    p0p1p2_1 = mix(_e36, _e37, vec2(_e38));
+    let _e42 = p1p2_1;
+    let _e43 = p2p3_1;
+    let _e44 = t_3;
+    p1p2p3_1 = mix(_e42, _e43, vec2(_e44));
+    let _e48 = p0p1p2_1;
+    let _e49 = p1p2p3_1;
+    let _e50 = t_3;
+    return mix(_e48, _e49, vec2(_e50));
+}
+
+fn sampleLine(line: vec4<f32>, t_4: f32) -> vec2<f32> {
+    var line_1: vec4<f32>;
+    var t_5: f32;
+
+    line_1 = line;
+    t_5 = t_4;
+    let _e4 = line_1;
+    let _e6 = line_1;
+    let _e8 = t_5;
+    return mix(_e4.xy, _e6.zw, vec2(_e8));
+}
+
+fn getPoint(pointIndex: u32, pathTransform: mat2x2<f32>, pathTranslation: vec2<f32>) -> vec2<f32> {
+    var pointIndex_1: u32;
+    var pathTransform_1: mat2x2<f32>;
+    var pathTranslation_1: vec2<f32>;
+
+    pointIndex_1 = pointIndex;
+    pathTransform_1 = pathTransform;
+    pathTranslation_1 = pathTranslation;
+    let _e12 = global.uTransform;
+    let _e13 = pathTransform_1;
+    let _e14 = pointIndex_1;
+    let _e16 = global_3.iPoints[_e14];
+    let _e18 = pathTranslation_1;
+    let _e21 = global.uTranslation;
+    return ((_e12 * ((_e13 * _e16) + _e18)) + _e21);
+}
+
+fn main_1() {
+    var batchSegmentIndex: u32;
+    var lowPathIndex: u32 = 0u;
+    var highPathIndex: u32;
+    var iteration: i32 = 0i;
+    var midPathIndex: u32;
+    var midBatchSegmentIndex: u32;
+    var batchPathIndex: u32;
+    var diceMetadata: vec4<u32>;
+    var firstGlobalSegmentIndexInPath: u32;
+    var firstBatchSegmentIndexInPath: u32;
+    var globalSegmentIndex: u32;
+    var pathTransformColumns: vec4<f32>;
+    var pathTransform_2: mat2x2<f32>;
+    var pathTranslation_2: vec2<f32>;
+    var inputIndices: vec2<u32>;
+    var fromPointIndex: u32;
+    var flagsPathIndex: u32;
+    var toPointIndex: u32;
+    var baseline_6: vec4<f32>;
+    var ctrl_6: vec4<f32> = vec4(0f);
+    var segmentCountF: f32;
+    var isCurve: bool;
+    var ctrl0_: vec2<f32>;
+    var ctrl0_2_: vec2<f32>;
+    var bound: vec2<f32>;
+    var segmentCount: i32;
+    var firstOutputMicrolineIndex: u32;
+    var prevT: f32 = 0f;
+    var prevPoint: vec2<f32>;
+    var segmentIndex: i32 = 0i;
+    var nextT: f32;
+    var nextPoint: vec2<f32>;
+
+    let _e15 = gl_GlobalInvocationID;
+    batchSegmentIndex = _e15.x;
+    let _e18 = batchSegmentIndex;
+    let _e19 = global.uLastBatchSegmentIndex;
+    if (_e18 >= u32(_e19)) {
+        return;
+    }
+    let _e24 = global.uPathCount;
+    highPathIndex = u32(_e24);
+    loop {
+        let _e29 = iteration;
+        let _e32 = lowPathIndex;
+        let _e35 = highPathIndex;
+        if !(((_e29 < 1024i) && ((_e32 + 1u) < _e35))) {
+            break;
+        }
+        {
+            let _e39 = lowPathIndex;
+            let _e40 = highPathIndex;
+            let _e41 = lowPathIndex;
+            midPathIndex = (_e39 + ((_e40 - _e41) / 2u));
+            let _e47 = midPathIndex;
+            let _e49 = global_2.iDiceMetadata[_e47];
+            midBatchSegmentIndex = _e49.z;
+            let _e52 = batchSegmentIndex;
+            let _e53 = midBatchSegmentIndex;
+            if (_e52 < _e53) {
+                {
+                    let _e55 = midPathIndex;
+                    highPathIndex = _e55;
+                }
+            } else {
+                {
+                    let _e56 = midPathIndex;
+                    lowPathIndex = _e56;
+                    let _e57 = batchSegmentIndex;
+                    let _e58 = midBatchSegmentIndex;
+                    if (_e57 == _e58) {
+                        break;
+                    }
+                }
+            }
+            let _e60 = iteration;
+            iteration = (_e60 + 1i);
+        }
+    }
+    let _e63 = lowPathIndex;
+    batchPathIndex = _e63;
+    let _e65 = batchPathIndex;
+    let _e67 = global_2.iDiceMetadata[_e65];
+    diceMetadata = _e67;
+    let _e69 = diceMetadata;
+    firstGlobalSegmentIndexInPath = _e69.y;
+    let _e72 = diceMetadata;
+    firstBatchSegmentIndexInPath = _e72.z;
+    let _e75 = batchSegmentIndex;
+    let _e76 = firstBatchSegmentIndexInPath;
+    let _e78 = firstGlobalSegmentIndexInPath;
+    globalSegmentIndex = ((_e75 - _e76) + _e78);
+    let _e81 = batchPathIndex;
+    let _e87 = global_5.iPathTransforms[((_e81 * 2u) + 0u)];
+    pathTransformColumns = _e87;
+    let _e89 = pathTransformColumns;
+    let _e90 = _e89.xy;
+    let _e91 = pathTransformColumns;
+    let _e92 = _e91.zw;
+    pathTransform_2 = mat2x2<f32>(vec2<f32>(_e90.x, _e90.y), vec2<f32>(_e92.x, _e92.y));
+    let _e101 = batchPathIndex;
+    let _e107 = global_5.iPathTransforms[((_e101 * 2u) + 1u)];
+    pathTranslation_2 = _e107.xy;
+    let _e110 = globalSegmentIndex;
+    let _e112 = global_4.iInputIndices[_e110];
+    inputIndices = _e112;
+    let _e114 = inputIndices;
+    fromPointIndex = _e114.x;
+    let _e117 = inputIndices;
+    flagsPathIndex = _e117.y;
+    let _e120 = fromPointIndex;
+    toPointIndex = _e120;
+    let _e122 = flagsPathIndex;
+    if ((_e122 & 1073741824u) != 0u) {
+        let _e127 = toPointIndex;
+        toPointIndex = (_e127 + 3u);
+    } else {
+        let _e130 = flagsPathIndex;
+        if ((_e130 & 2147483648u) != 0u) {
+            let _e135 = toPointIndex;
+            toPointIndex = (_e135 + 2u);
+        } else {
+            let _e138 = toPointIndex;
+            toPointIndex = (_e138 + 1u);
+        }
+    }
+    let _e141 = fromPointIndex;
+    let _e142 = pathTransform_2;
+    let _e143 = pathTranslation_2;
+    let _e144 = getPoint(_e141, _e142, _e143);
+    let _e145 = toPointIndex;
+    let _e146 = pathTransform_2;
+    let _e147 = pathTranslation_2;
+    let _e148 = getPoint(_e145, _e146, _e147);
+    baseline_6 = vec4<f32>(_e144.x, _e144.y, _e148.x, _e148.y);
+    let _e159 = flagsPathIndex;
+    isCurve = ((_e159 & 3221225472u) != 0u);
+    let _e165 = isCurve;
+    if _e165 {
+        {
+            let _e166 = fromPointIndex;
+            let _e169 = pathTransform_2;
+            let _e170 = pathTranslation_2;
+            let _e171 = getPoint((_e166 + 1u), _e169, _e170);
+            ctrl0_ = _e171;
+            let _e173 = flagsPathIndex;
+            if ((_e173 & 2147483648u) != 0u) {
+                {
+                    let _e178 = ctrl0_;
+                    ctrl0_2_ = (_e178 * vec2(2f));
+                    let _e183 = baseline_6;
+                    let _e184 = ctrl0_;
+                    ctrl_6 = ((_e183 + (_e184 * vec2(2f)).xyxy) * vec4(0.33333334f));
+                }
+            } else {
+                {
+                    let _e193 = ctrl0_;
+                    let _e194 = fromPointIndex;
+                    let _e197 = pathTransform_2;
+                    let _e198 = pathTranslation_2;
+                    let _e199 = getPoint((_e194 + 2u), _e197, _e198);
+                    ctrl_6 = vec4<f32>(_e193.x, _e193.y, _e199.x, _e199.y);
+                }
+            }
+            let _e207 = ctrl_6;
+            let _e210 = ctrl_6;
+            let _e214 = baseline_6;
+            let _e218 = baseline_6;
+            let _e221 = ctrl_6;
+            let _e225 = ctrl_6;
+            bound = (vec2(6f) * max(abs(((_e207.zw - (2f * _e210.xy)) + _e214.xy)), abs(((_e218.zw - (2f * _e221.zw)) + _e225.xy))));
+            let _e232 = bound;
+            let _e235 = global.uTolerance;
+            segmentCountF = sqrt((length(_e232) / (8f * _e235)));
+        }
+    } else {
+        {
+            let _e239 = baseline_6;
+            let _e241 = baseline_6;
+            segmentCountF = (length((_e239.zw - _e241.xy)) / 16f);
+        }
+    }
+    let _e247 = segmentCountF;
+    segmentCount = max(i32(ceil(_e247)), 1i);
+    let _e254 = segmentCount;
+    let _e256 = atomicAdd((&global_1.iComputeIndirectParams[3]), u32(_e254));
+    firstOutputMicrolineIndex = _e256;
+    let _e260 = baseline_6;
+    prevPoint = _e260.xy;
+    loop {
+        let _e265 = segmentIndex;
+        let _e266 = segmentCount;
+        if !((_e265 < _e266)) {
+            break;
+        }
+        {
+            let _e272 = segmentIndex;
+            let _e276 = segmentCount;
+            nextT = (f32((_e272 + 1i)) / f32(_e276));
+            let _e281 = isCurve;
+            if _e281 {
+                let _e282 = baseline_6;
+                let _e283 = ctrl_6;
+                let _e284 = nextT;
+                let _e285 = sampleCurve(_e282, _e283, _e284);
+                nextPoint = _e285;
+            } else {
+                let _e286 = baseline_6;
+                let _e287 = nextT;
+                let _e288 = sampleLine(_e286, _e287);
+                nextPoint = _e288;
+            }
+            let _e289 = prevPoint;
+            let _e290 = nextPoint;
+            let _e296 = batchPathIndex;
+            let _e297 = firstOutputMicrolineIndex;
+            let _e298 = segmentIndex;
+            emitMicroline(vec4<f32>(_e289.x, _e289.y, _e290.x, _e290.y), _e296, (_e297 + u32(_e298)));
+            let _e301 = nextT;
+            prevT = _e301;
+            let _e302 = nextPoint;
+            prevPoint = _e302;
+        }
+        continuing {
+            let _e269 = segmentIndex;
+            segmentIndex = (_e269 + 1i);
+        }
+    }
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) param: vec3<u32>) {
+    gl_GlobalInvocationID = param;
+    main_1();
+    return;
+}
@@ -0,0 +1,362 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uSampleCount: i32,
+    uAlphaTileRange: vec2<i32>,
+}
+
+struct bFills {
+    iFills: array<u32>,
+}
+
+struct bTiles {
+    iTiles: array<u32>,
+}
+
+struct bAlphaTiles {
+    iAlphaTiles: array<u32>,
+}
+
+@group(1) @binding(0) 
+var uDest: texture_storage_2d<rgba8unorm,read_write>;
+@group(1) @binding(1) 
+var uAreaLUT: texture_2d<f32>;
+@group(1) @binding(2) 
+var uAreaLUTSampler: sampler;
+@group(1) @binding(3) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage> global_1: bFills;
+@group(0) @binding(1) 
+var<storage, read_write> global_2: bTiles;
+@group(0) @binding(2) 
+var<storage> global_3: bAlphaTiles;
+var<private> gl_LocalInvocationID: vec3<u32>;
+var<private> gl_WorkGroupID: vec3<u32>;
+
+fn computeCoverage(from_: vec2<f32>, to: vec2<f32>, areaLUT: texture_2d<f32>, areaLUTSampler: sampler) -> vec4<f32> {
+    var from_1: vec2<f32>;
+    var to_1: vec2<f32>;
+    var local: vec2<f32>;
+    var left: vec2<f32>;
+    var local_1: vec2<f32>;
+    var right: vec2<f32>;
+    var window: vec2<f32>;
+    var offset: f32;
+    var t: f32;
+    var y: f32;
+    var d: f32;
+    var dX: f32;
+
+    from_1 = from_;
+    to_1 = to;
+    let _e6 = from_1;
+    let _e8 = to_1;
+    if (_e6.x < _e8.x) {
+        let _e11 = from_1;
+        local = _e11;
+    } else {
+        let _e12 = to_1;
+        local = _e12;
+    }
+    let _e14 = local;
+    left = _e14;
+    let _e16 = from_1;
+    let _e18 = to_1;
+    if (_e16.x < _e18.x) {
+        let _e21 = to_1;
+        local_1 = _e21;
+    } else {
+        let _e22 = from_1;
+        local_1 = _e22;
+    }
+    let _e24 = local_1;
+    right = _e24;
+    let _e26 = from_1;
+    let _e28 = to_1;
+    window = clamp(vec2<f32>(_e26.x, _e28.x), vec2(-0.5f), vec2(0.5f));
+    let _e37 = window;
+    let _e39 = window;
+    let _e43 = left;
+    offset = (mix(_e37.x, _e39.y, 0.5f) - _e43.x);
+    let _e47 = offset;
+    let _e48 = right;
+    let _e50 = left;
+    t = (_e47 / (_e48.x - _e50.x));
+    let _e55 = left;
+    let _e57 = right;
+    let _e59 = t;
+    y = mix(_e55.y, _e57.y, _e59);
+    let _e62 = right;
+    let _e64 = left;
+    let _e67 = right;
+    let _e69 = left;
+    d = ((_e62.y - _e64.y) / (_e67.x - _e69.x));
+    let _e74 = window;
+    let _e76 = window;
+    dX = (_e74.x - _e76.y);
+    let _e80 = y;
+    let _e83 = d;
+    let _e84 = dX;
+    let _e92 = textureSampleLevel(areaLUT, areaLUTSampler, (vec2<f32>((_e80 + 8f), abs((_e83 * _e84))) / vec2(16f)), 0f);
+    let _e93 = dX;
+    return (_e92 * _e93);
+}
+
+fn computeSampleCoverage(from_2: vec2<f32>, to_2: vec2<f32>, sampleOffset: vec2<f32>) -> vec4<f32> {
+    var from_3: vec2<f32>;
+    var to_3: vec2<f32>;
+    var sampleOffset_1: vec2<f32>;
+    var y_1: f32;
+
+    from_3 = from_2;
+    to_3 = to_2;
+    sampleOffset_1 = sampleOffset;
+    let _e6 = from_3;
+    let _e7 = sampleOffset_1;
+    from_3 = (_e6 - _e7);
+    let _e9 = to_3;
+    let _e10 = sampleOffset_1;
+    to_3 = (_e9 - _e10);
+    let _e12 = from_3;
+    let _e16 = to_3;
+    if ((_e12.x <= 0f) == (_e16.x <= 0f)) {
+        return vec4(0f);
+    }
+    let _e23 = from_3;
+    let _e25 = to_3;
+    let _e27 = from_3;
+    let _e29 = from_3;
+    let _e31 = to_3;
+    y_1 = mix(_e23.y, _e25.y, (_e27.x / (_e29.x - _e31.x)));
+    let _e37 = y_1;
+    let _e45 = from_3;
+    let _e47 = to_3;
+    return (step(vec4(_e37), vec4<f32>(0f, 1f, 2f, 3f)) * sign((_e45.x - _e47.x)));
+}
+
+fn computeSupersampledCoverage(from_4: vec2<f32>, to_4: vec2<f32>, sampleCount: i32) -> vec4<f32> {
+    var from_5: vec2<f32>;
+    var to_5: vec2<f32>;
+    var sampleCount_1: i32;
+    var coverage: vec4<f32> = vec4(0f);
+    var sampleIndex: i32 = 0i;
+    var sampleRow: i32;
+    var bit: i32;
+    var local_2: i32;
+    var sampleOffset_2: vec2<f32>;
+
+    from_5 = from_4;
+    to_5 = to_4;
+    sampleCount_1 = sampleCount;
+    loop {
+        let _e11 = sampleIndex;
+        let _e12 = sampleCount_1;
+        if !((_e11 < _e12)) {
+            break;
+        }
+        {
+            sampleRow = 0i;
+            bit = 1i;
+            loop {
+                let _e22 = bit;
+                let _e23 = sampleCount_1;
+                if !((_e22 < _e23)) {
+                    break;
+                }
+                let _e29 = sampleRow;
+                let _e32 = sampleIndex;
+                let _e33 = bit;
+                if ((_e32 & _e33) != 0i) {
+                    local_2 = 1i;
+                } else {
+                    local_2 = 0i;
+                }
+                let _e40 = local_2;
+                sampleRow = ((_e29 << 1u) | _e40);
+                continuing {
+                    let _e26 = bit;
+                    bit = (_e26 << 1u);
+                }
+            }
+            let _e42 = sampleIndex;
+            let _e43 = sampleRow;
+            let _e50 = sampleCount_1;
+            sampleOffset_2 = (((vec2<f32>(f32(_e42), f32(_e43)) + vec2(0.5f)) / vec2(f32(_e50))) - vec2(0.5f));
+            let _e58 = coverage;
+            let _e59 = from_5;
+            let _e60 = to_5;
+            let _e61 = sampleOffset_2;
+            let _e62 = computeSampleCoverage(_e59, _e60, _e61);
+            coverage = (_e58 + _e62);
+        }
+        continuing {
+            let _e15 = sampleIndex;
+            sampleIndex = (_e15 + 1i);
+        }
+    }
+    let _e64 = coverage;
+    let _e65 = sampleCount_1;
+    return (_e64 / vec4(f32(_e65)));
+}
+
+fn accumulateCoverageForFillList(fillIndex: i32, tileSubCoord: vec2<i32>) -> vec4<f32> {
+    var fillIndex_1: i32;
+    var tileSubCoord_1: vec2<i32>;
+    var tileFragCoord: vec2<f32>;
+    var coverages: vec4<f32> = vec4(0f);
+    var iteration: i32 = 0i;
+    var fillFrom: u32;
+    var fillTo: u32;
+    var lineSegment: vec4<f32>;
+
+    fillIndex_1 = fillIndex;
+    tileSubCoord_1 = tileSubCoord;
+    let _e10 = tileSubCoord_1;
+    tileFragCoord = (vec2<f32>(_e10) + vec2(0.5f));
+    loop {
+        {
+            let _e21 = fillIndex_1;
+            let _e27 = global_1.iFills[((_e21 * 3i) + 0i)];
+            fillFrom = _e27;
+            let _e29 = fillIndex_1;
+            let _e35 = global_1.iFills[((_e29 * 3i) + 1i)];
+            fillTo = _e35;
+            let _e37 = fillFrom;
+            let _e40 = fillFrom;
+            let _e43 = fillTo;
+            let _e46 = fillTo;
+            lineSegment = (vec4<f32>(f32((_e37 & 65535u)), f32((_e40 >> 16u)), f32((_e43 & 65535u)), f32((_e46 >> 16u))) / vec4(256f));
+            let _e58 = lineSegment;
+            let _e59 = tileFragCoord;
+            lineSegment = (_e58 - _e59.xyxy);
+            let _e62 = global.uSampleCount;
+            if (_e62 == 0i) {
+                {
+                    let _e65 = coverages;
+                    let _e66 = lineSegment;
+                    let _e68 = lineSegment;
+                    let _e70 = computeCoverage(_e66.xy, _e68.zw, uAreaLUT, uAreaLUTSampler);
+                    coverages = (_e65 + _e70);
+                }
+            } else {
+                {
+                    let _e72 = coverages;
+                    let _e73 = lineSegment;
+                    let _e75 = lineSegment;
+                    let _e77 = global.uSampleCount;
+                    let _e78 = computeSupersampledCoverage(_e73.xy, _e75.zw, _e77);
+                    coverages = (_e72 + _e78);
+                }
+            }
+            let _e80 = fillIndex_1;
+            let _e86 = global_1.iFills[((_e80 * 3i) + 2i)];
+            fillIndex_1 = i32(_e86);
+            let _e88 = iteration;
+            iteration = (_e88 + 1i);
+        }
+        let _e91 = fillIndex_1;
+        let _e94 = iteration;
+        if !(((_e91 >= 0i) && (_e94 < 1024i))) {
+            break;
+        }
+    }
+    let _e99 = coverages;
+    return _e99;
+}
+
+fn computeTileCoord(alphaTileIndex: u32) -> vec2<i32> {
+    var alphaTileIndex_1: u32;
+    var x: u32;
+    var y_2: u32;
+
+    alphaTileIndex_1 = alphaTileIndex;
+    let _e2 = alphaTileIndex_1;
+    x = (_e2 & 255u);
+    let _e6 = alphaTileIndex_1;
+    let _e9 = alphaTileIndex_1;
+    y_2 = ((_e6 >> 8u) & (255u + (((_e9 >> 16u) & 255u) << 8u)));
+    let _e24 = x;
+    let _e25 = y_2;
+    let _e30 = gl_LocalInvocationID;
+    return ((vec2<i32>(16i, 4i) * vec2<i32>(i32(_e24), i32(_e25))) + vec2<i32>(_e30.xy));
+}
+
+fn main_1() {
+    var tileSubCoord_2: vec2<i32>;
+    var batchAlphaTileIndex: u32;
+    var alphaTileIndex_2: u32;
+    var tileIndex: u32;
+    var fillIndex_2: i32;
+    var backdrop: i32;
+    var coverages_1: vec4<f32>;
+    var clipTileIndex: i32;
+
+    let _e8 = gl_LocalInvocationID;
+    tileSubCoord_2 = (vec2<i32>(_e8.xy) * vec2<i32>(1i, 4i));
+    let _e17 = gl_WorkGroupID;
+    let _e19 = gl_WorkGroupID;
+    batchAlphaTileIndex = (_e17.x | (_e19.y << 15u));
+    let _e25 = batchAlphaTileIndex;
+    let _e26 = global.uAlphaTileRange;
+    alphaTileIndex_2 = (_e25 + u32(_e26.x));
+    let _e31 = alphaTileIndex_2;
+    let _e32 = global.uAlphaTileRange;
+    if (_e31 >= u32(_e32.y)) {
+        return;
+    }
+    let _e36 = batchAlphaTileIndex;
+    let _e42 = global_3.iAlphaTiles[((_e36 * 2u) + 0u)];
+    tileIndex = _e42;
+    let _e44 = tileIndex;
+    let _e50 = global_2.iTiles[((_e44 * 4u) + 2u)];
+    if ((i32((_e50 << 8u)) >> 8u) < 0i) {
+        return;
+    }
+    let _e58 = tileIndex;
+    let _e64 = global_2.iTiles[((_e58 * 4u) + 1u)];
+    fillIndex_2 = i32(_e64);
+    let _e67 = tileIndex;
+    let _e73 = global_2.iTiles[((_e67 * 4u) + 3u)];
+    backdrop = (i32(_e73) >> 24u);
+    let _e78 = backdrop;
+    coverages_1 = vec4(f32(_e78));
+    let _e82 = coverages_1;
+    let _e83 = fillIndex_2;
+    let _e84 = tileSubCoord_2;
+    let _e85 = accumulateCoverageForFillList(_e83, _e84);
+    coverages_1 = (_e82 + _e85);
+    let _e87 = coverages_1;
+    coverages_1 = clamp(abs(_e87), vec4(0f), vec4(1f));
+    let _e94 = batchAlphaTileIndex;
+    let _e100 = global_3.iAlphaTiles[((_e94 * 2u) + 1u)];
+    clipTileIndex = i32(_e100);
+    let _e103 = clipTileIndex;
+    if (_e103 >= 0i) {
+        let _e106 = clipTileIndex;
+        let _e108 = computeTileCoord(u32(_e106));
+        let _e109 = clipTileIndex;
+        let _e111 = computeTileCoord(u32(_e109));
+        let _e112 = coverages_1;
+        let _e113 = clipTileIndex;
+        let _e115 = computeTileCoord(u32(_e113));
+        let _e116 = clipTileIndex;
+        let _e118 = computeTileCoord(u32(_e116));
+        let _e119 = textureLoad(uDest, _e118);
+        coverages_1 = min(_e112, _e119);
+    }
+    let _e121 = alphaTileIndex_2;
+    let _e122 = computeTileCoord(_e121);
+    let _e123 = alphaTileIndex_2;
+    let _e124 = computeTileCoord(_e123);
+    let _e125 = coverages_1;
+    textureStore(uDest, _e124, _e125);
+    return;
+}
+
+@compute @workgroup_size(16, 4, 1) 
+fn main(@builtin(local_invocation_id) param: vec3<u32>, @builtin(workgroup_id) param_1: vec3<u32>) {
+    gl_LocalInvocationID = param;
+    gl_WorkGroupID = param_1;
+    main_1();
+    return;
+}
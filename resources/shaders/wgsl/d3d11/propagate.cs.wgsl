@@ -0,0 +1,351 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferTileSize: vec2<i32>,
+    uColumnCount: i32,
+    uFirstAlphaTileIndex: i32,
+}
+
+struct bDrawMetadata {
+    iDrawMetadata: array<vec4<u32>>,
+}
+
+struct bClipMetadata {
+    iClipMetadata: array<vec4<u32>>,
+}
+
+struct bBackdrops {
+    iBackdrops: array<i32>,
+}
+
+struct bDrawTiles {
+    iDrawTiles: array<u32>,
+}
+
+struct bClipTiles {
+    iClipTiles: array<u32>,
+}
+
+struct bAlphaTiles {
+    iAlphaTiles: array<u32>,
+}
+
+struct bZBuffer {
+    iZBuffer: array<atomic<i32>>,
+}
+
+struct bFirstTileMap {
+    iFirstTileMap: array<atomic<i32>>,
+}
+
+struct bIndirectDrawParams {
+    iIndirectDrawParams: array<atomic<u32>>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage> global_1: bDrawMetadata;
+@group(0) @binding(1) 
+var<storage> global_2: bClipMetadata;
+@group(0) @binding(2) 
+var<storage> global_3: bBackdrops;
+@group(0) @binding(3) 
+var<storage, read_write> global_4: bDrawTiles;
+@group(0) @binding(4) 
+var<storage, read_write> global_5: bClipTiles;
+@group(0) @binding(5) 
+var<storage, read_write> global_6: bZBuffer;
+@group(0) @binding(6) 
+var<storage, read_write> global_7: bFirstTileMap;
+@group(0) @binding(7) 
+var<storage, read_write> global_8: bIndirectDrawParams;
+@group(0) @binding(8) 
+var<storage, read_write> global_9: bAlphaTiles;
+var<private> gl_GlobalInvocationID: vec3<u32>;
+
+fn calculateTileIndex(bufferOffset: u32, tileRect: vec4<u32>, tileCoord: vec2<u32>) -> u32 {
+    var bufferOffset_1: u32;
+    var tileRect_1: vec4<u32>;
+    var tileCoord_1: vec2<u32>;
+
+    bufferOffset_1 = bufferOffset;
+    tileRect_1 = tileRect;
+    tileCoord_1 = tileCoord;
+    let _e6 = bufferOffset_1;
+    let _e7 = tileCoord_1;
+    let _e9 = tileRect_1;
+    let _e11 = tileRect_1;
+    let _e16 = tileCoord_1;
+    return ((_e6 + (_e7.y * (_e9.z - _e11.x))) + _e16.x);
+}
+
+fn main_1() {
+    var columnIndex: u32;
+    var currentBackdrop: i32;
+    var tileX: i32;
+    var drawPathIndex: u32;
+    var drawTileRect: vec4<u32>;
+    var drawOffsets: vec4<u32>;
+    var drawTileSize: vec2<u32>;
+    var drawTileBufferOffset: u32;
+    var zWrite: bool;
+    var clipPathIndex: i32;
+    var clipTileRect: vec4<u32> = vec4(0u);
+    var clipOffsets: vec4<u32> = vec4(0u);
+    var clipTileBufferOffset: u32;
+    var clipBackdropOffset: u32;
+    var tileY: u32 = 0u;
+    var drawTileCoord: vec2<u32>;
+    var drawTileIndex: u32;
+    var drawAlphaTileIndex: i32;
+    var clipAlphaTileIndex: i32;
+    var drawFirstFillIndex: i32;
+    var drawBackdropDelta: i32;
+    var drawTileWord: u32;
+    var drawTileBackdrop: i32;
+    var haveDrawAlphaMask: bool;
+    var needNewAlphaTile: bool;
+    var tileCoord_2: vec2<u32>;
+    var clipTileCoord: vec2<u32>;
+    var clipTileIndex: u32;
+    var thisClipAlphaTileIndex: i32;
+    var clipTileWord: u32;
+    var clipTileBackdrop: i32;
+    var drawBatchAlphaTileIndex: u32;
+    var tileCoord_3: vec2<i32>;
+    var tileMapIndex: i32;
+    var nextTileIndex: i32;
+
+    let _e25 = gl_GlobalInvocationID;
+    columnIndex = _e25.x;
+    let _e28 = columnIndex;
+    let _e30 = global.uColumnCount;
+    if (i32(_e28) >= _e30) {
+        return;
+    }
+    let _e32 = columnIndex;
+    let _e38 = global_3.iBackdrops[((_e32 * 3u) + 0u)];
+    currentBackdrop = _e38;
+    let _e40 = columnIndex;
+    let _e46 = global_3.iBackdrops[((_e40 * 3u) + 1u)];
+    tileX = _e46;
+    let _e48 = columnIndex;
+    let _e54 = global_3.iBackdrops[((_e48 * 3u) + 2u)];
+    drawPathIndex = u32(_e54);
+    let _e57 = drawPathIndex;
+    let _e63 = global_1.iDrawMetadata[((_e57 * 3u) + 0u)];
+    drawTileRect = _e63;
+    let _e65 = drawPathIndex;
+    let _e71 = global_1.iDrawMetadata[((_e65 * 3u) + 1u)];
+    drawOffsets = _e71;
+    let _e73 = drawTileRect;
+    let _e75 = drawTileRect;
+    drawTileSize = (_e73.zw - _e75.xy);
+    let _e79 = drawOffsets;
+    drawTileBufferOffset = _e79.x;
+    let _e82 = drawOffsets;
+    zWrite = (_e82.z != 0u);
+    let _e87 = drawOffsets;
+    clipPathIndex = i32(_e87.w);
+    let _e97 = clipPathIndex;
+    if (_e97 >= 0i) {
+        {
+            let _e100 = clipPathIndex;
+            let _e106 = global_2.iClipMetadata[((_e100 * 2i) + 0i)];
+            clipTileRect = _e106;
+            let _e107 = clipPathIndex;
+            let _e113 = global_2.iClipMetadata[((_e107 * 2i) + 1i)];
+            clipOffsets = _e113;
+        }
+    }
+    let _e114 = clipOffsets;
+    clipTileBufferOffset = _e114.x;
+    let _e117 = clipOffsets;
+    clipBackdropOffset = _e117.y;
+    loop {
+        let _e122 = tileY;
+        let _e123 = drawTileSize;
+        if !((_e122 < _e123.y)) {
+            break;
+        }
+        {
+            let _e130 = tileX;
+            let _e131 = tileY;
+            drawTileCoord = vec2<u32>(u32(_e130), _e131);
+            let _e135 = drawTileBufferOffset;
+            let _e136 = drawTileRect;
+            let _e137 = drawTileCoord;
+            let _e138 = calculateTileIndex(_e135, _e136, _e137);
+            drawTileIndex = _e138;
+            drawAlphaTileIndex = -1i;
+            clipAlphaTileIndex = -1i;
+            let _e144 = drawTileIndex;
+            let _e150 = global_4.iDrawTiles[((_e144 * 4u) + 1u)];
+            drawFirstFillIndex = i32(_e150);
+            let _e153 = drawTileIndex;
+            let _e159 = global_4.iDrawTiles[((_e153 * 4u) + 2u)];
+            drawBackdropDelta = (i32(_e159) >> 24u);
+            let _e164 = drawTileIndex;
+            let _e170 = global_4.iDrawTiles[((_e164 * 4u) + 3u)];
+            drawTileWord = (_e170 & 16777215u);
+            let _e174 = currentBackdrop;
+            drawTileBackdrop = _e174;
+            let _e176 = drawFirstFillIndex;
+            haveDrawAlphaMask = (_e176 >= 0i);
+            let _e180 = haveDrawAlphaMask;
+            needNewAlphaTile = _e180;
+            let _e182 = clipPathIndex;
+            if (_e182 >= 0i) {
+                {
+                    let _e185 = drawTileCoord;
+                    let _e186 = drawTileRect;
+                    tileCoord_2 = (_e185 + _e186.xy);
+                    let _e190 = tileCoord_2;
+                    let _e191 = clipTileRect;
+                    let _e193 = (_e190 >= _e191.xy);
+                    let _e194 = tileCoord_2;
+                    let _e195 = clipTileRect;
+                    let _e197 = (_e194 < _e195.zw);
+                    if all(vec4<bool>(_e193.x, _e193.y, _e197.x, _e197.y)) {
+                        {
+                            let _e204 = tileCoord_2;
+                            let _e205 = clipTileRect;
+                            clipTileCoord = (_e204 - _e205.xy);
+                            let _e209 = clipTileBufferOffset;
+                            let _e210 = clipTileRect;
+                            let _e211 = clipTileCoord;
+                            let _e212 = calculateTileIndex(_e209, _e210, _e211);
+                            clipTileIndex = _e212;
+                            let _e214 = clipTileIndex;
+                            let _e220 = global_5.iClipTiles[((_e214 * 4u) + 2u)];
+                            thisClipAlphaTileIndex = (i32((_e220 << 8u)) >> 8u);
+                            let _e227 = clipTileIndex;
+                            let _e233 = global_5.iClipTiles[((_e227 * 4u) + 3u)];
+                            clipTileWord = _e233;
+                            let _e235 = clipTileWord;
+                            clipTileBackdrop = (i32(_e235) >> 24u);
+                            let _e240 = thisClipAlphaTileIndex;
+                            if (_e240 >= 0i) {
+                                {
+                                    let _e243 = haveDrawAlphaMask;
+                                    if _e243 {
+                                        {
+                                            let _e244 = thisClipAlphaTileIndex;
+                                            clipAlphaTileIndex = _e244;
+                                            needNewAlphaTile = true;
+                                        }
+                                    } else {
+                                        {
+                                            let _e246 = drawTileBackdrop;
+                                            if (_e246 != 0i) {
+                                                {
+                                                    let _e249 = thisClipAlphaTileIndex;
+                                                    drawAlphaTileIndex = _e249;
+                                                    clipAlphaTileIndex = -1i;
+                                                    needNewAlphaTile = false;
+                                                }
+                                            } else {
+                                                {
+                                                    drawAlphaTileIndex = -1i;
+                                                    clipAlphaTileIndex = -1i;
+                                                    needNewAlphaTile = false;
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            } else {
+                                {
+                                    let _e255 = clipTileBackdrop;
+                                    if (_e255 == 0i) {
+                                        {
+                                            drawTileBackdrop = 0i;
+                                            needNewAlphaTile = false;
+                                        }
+                                    } else {
+                                        {
+                                            needNewAlphaTile = true;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    } else {
+                        {
+                            drawTileBackdrop = 0i;
+                            needNewAlphaTile = false;
+                        }
+                    }
+                }
+            }
+            let _e263 = needNewAlphaTile;
+            if _e263 {
+                {
+                    let _e266 = atomicAdd((&global_8.iIndirectDrawParams[4]), 1u);
+                    drawBatchAlphaTileIndex = _e266;
+                    let _e268 = drawBatchAlphaTileIndex;
+                    let _e274 = drawTileIndex;
+                    global_9.iAlphaTiles[((_e268 * 2u) + 0u)] = _e274;
+                    let _e275 = drawBatchAlphaTileIndex;
+                    let _e281 = clipAlphaTileIndex;
+                    global_9.iAlphaTiles[((_e275 * 2u) + 1u)] = u32(_e281);
+                    let _e283 = drawBatchAlphaTileIndex;
+                    let _e285 = global.uFirstAlphaTileIndex;
+                    drawAlphaTileIndex = (i32(_e283) + _e285);
+                }
+            }
+            let _e287 = drawTileIndex;
+            let _e293 = drawAlphaTileIndex;
+            let _e297 = drawBackdropDelta;
+            global_4.iDrawTiles[((_e287 * 4u) + 2u)] = ((u32(_e293) & 16777215u) | (u32(_e297) << 24u));
+            let _e302 = drawTileIndex;
+            let _e308 = drawTileWord;
+            let _e309 = drawTileBackdrop;
+            global_4.iDrawTiles[((_e302 * 4u) + 3u)] = (_e308 | (u32(_e309) << 24u));
+            let _e314 = tileX;
+            let _e315 = tileY;
+            let _e318 = drawTileRect;
+            tileCoord_3 = (vec2<i32>(_e314, i32(_e315)) + vec2<i32>(_e318.xy));
+            let _e323 = tileCoord_3;
+            let _e325 = global.uFramebufferTileSize;
+            let _e328 = tileCoord_3;
+            tileMapIndex = ((_e323.y * _e325.x) + _e328.x);
+            let _e332 = zWrite;
+            let _e333 = drawTileBackdrop;
+            let _e337 = drawAlphaTileIndex;
+            if ((_e332 && (_e333 != 0i)) && (_e337 < 0i)) {
+                let _e341 = tileMapIndex;
+                let _e343 = drawTileIndex;
+                let _e345 = atomicMax((&global_6.iZBuffer[_e341]), i32(_e343));
+            }
+            let _e346 = drawTileBackdrop;
+            let _e349 = drawAlphaTileIndex;
+            if ((_e346 != 0i) || (_e349 >= 0i)) {
+                {
+                    let _e353 = tileMapIndex;
+                    let _e355 = drawTileIndex;
+                    let _e357 = atomicExchange((&global_7.iFirstTileMap[_e353]), i32(_e355));
+                    nextTileIndex = _e357;
+                    let _e359 = drawTileIndex;
+                    let _e365 = nextTileIndex;
+                    global_4.iDrawTiles[((_e359 * 4u) + 0u)] = u32(_e365);
+                }
+            }
+            let _e367 = currentBackdrop;
+            let _e368 = drawBackdropDelta;
+            currentBackdrop = (_e367 + _e368);
+        }
+        continuing {
+            let _e127 = tileY;
+            tileY = (_e127 + 1u);
+        }
+    }
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) param: vec3<u32>) {
+    gl_GlobalInvocationID = param;
+    main_1();
+    return;
+}
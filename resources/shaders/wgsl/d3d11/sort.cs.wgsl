@@ -0,0 +1,152 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uTileCount: i32,
+}
+
+struct bTiles {
+    iTiles: array<u32>,
+}
+
+struct bFirstTileMap {
+    iFirstTileMap: array<i32>,
+}
+
+struct bZBuffer {
+    iZBuffer: array<i32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(0) @binding(0) 
+var<storage, read_write> global_1: bTiles;
+@group(0) @binding(1) 
+var<storage, read_write> global_2: bFirstTileMap;
+@group(0) @binding(2) 
+var<storage> global_3: bZBuffer;
+var<private> gl_GlobalInvocationID: vec3<u32>;
+
+fn getFirst(globalTileIndex: u32) -> i32 {
+    var globalTileIndex_1: u32;
+
+    globalTileIndex_1 = globalTileIndex;
+    let _e4 = globalTileIndex_1;
+    let _e6 = global_2.iFirstTileMap[_e4];
+    return _e6;
+}
+
+fn getNextTile(tileIndex: i32) -> i32 {
+    var tileIndex_1: i32;
+
+    tileIndex_1 = tileIndex;
+    let _e4 = tileIndex_1;
+    let _e10 = global_1.iTiles[((_e4 * 4i) + 0i)];
+    return i32(_e10);
+}
+
+fn setNextTile(tileIndex_2: i32, newNextTileIndex: i32) {
+    var tileIndex_3: i32;
+    var newNextTileIndex_1: i32;
+
+    tileIndex_3 = tileIndex_2;
+    newNextTileIndex_1 = newNextTileIndex;
+    let _e6 = tileIndex_3;
+    let _e12 = newNextTileIndex_1;
+    global_1.iTiles[((_e6 * 4i) + 0i)] = u32(_e12);
+    return;
+}
+
+fn main_1() {
+    var globalTileIndex_2: u32;
+    var zValue: i32;
+    var unsortedFirstTileIndex: i32;
+    var sortedFirstTileIndex: i32 = -1i;
+    var currentTileIndex: i32;
+    var prevTrialTileIndex: i32;
+    var trialTileIndex: i32;
+
+    let _e7 = gl_GlobalInvocationID;
+    globalTileIndex_2 = _e7.x;
+    let _e10 = globalTileIndex_2;
+    let _e11 = global.uTileCount;
+    if (_e10 >= u32(_e11)) {
+        return;
+    }
+    let _e14 = globalTileIndex_2;
+    let _e16 = global_3.iZBuffer[_e14];
+    zValue = _e16;
+    let _e18 = globalTileIndex_2;
+    let _e19 = getFirst(_e18);
+    unsortedFirstTileIndex = _e19;
+    loop {
+        let _e23 = unsortedFirstTileIndex;
+        if !((_e23 >= 0i)) {
+            break;
+        }
+        {
+            let _e27 = unsortedFirstTileIndex;
+            currentTileIndex = _e27;
+            let _e29 = currentTileIndex;
+            let _e30 = getNextTile(_e29);
+            unsortedFirstTileIndex = _e30;
+            let _e31 = currentTileIndex;
+            let _e32 = zValue;
+            if (_e31 >= _e32) {
+                {
+                    prevTrialTileIndex = -1i;
+                    let _e36 = sortedFirstTileIndex;
+                    trialTileIndex = _e36;
+                    loop {
+                        if false {
+                            break;
+                        }
+                        {
+                            let _e39 = trialTileIndex;
+                            let _e42 = currentTileIndex;
+                            let _e43 = trialTileIndex;
+                            if ((_e39 < 0i) || (_e42 < _e43)) {
+                                {
+                                    let _e46 = prevTrialTileIndex;
+                                    if (_e46 < 0i) {
+                                        {
+                                            let _e49 = currentTileIndex;
+                                            let _e50 = sortedFirstTileIndex;
+                                            setNextTile(_e49, _e50);
+                                            let _e51 = currentTileIndex;
+                                            sortedFirstTileIndex = _e51;
+                                        }
+                                    } else {
+                                        {
+                                            let _e52 = currentTileIndex;
+                                            let _e53 = trialTileIndex;
+                                            setNextTile(_e52, _e53);
+                                            let _e54 = prevTrialTileIndex;
+                                            let _e55 = currentTileIndex;
+                                            setNextTile(_e54, _e55);
+                                        }
+                                    }
+                                    break;
+                                }
+                            }
+                            let _e56 = trialTileIndex;
+                            prevTrialTileIndex = _e56;
+                            let _e57 = trialTileIndex;
+                            let _e58 = getNextTile(_e57);
+                            trialTileIndex = _e58;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let _e59 = globalTileIndex_2;
+    let _e61 = sortedFirstTileIndex;
+    global_2.iFirstTileMap[_e59] = _e61;
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) param: vec3<u32>) {
+    gl_GlobalInvocationID = param;
+    main_1();
+    return;
+}
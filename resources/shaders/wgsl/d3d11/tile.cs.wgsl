@@ -0,0 +1,1586 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uLoadAction: i32,
+    uClearColor: vec4<f32>,
+    uTileSize: vec2<f32>,
+    uTextureMetadataSize: vec2<i32>,
+    uZBufferSize: vec2<i32>,
+    uColorTextureSize0_: vec2<f32>,
+    uMaskTextureSize0_: vec2<f32>,
+    uPaintMaskTextureSize: vec2<f32>,
+    uFramebufferSize: vec2<f32>,
+    uFramebufferTileSize: vec2<i32>,
+}
+
+struct bTiles {
+    iTiles: array<u32>,
+}
+
+struct bFirstTileMap {
+    iFirstTileMap: array<i32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(1) @binding(1) 
+var uTextureMetadata: texture_2d<f32>;
+@group(1) @binding(2) 
+var uTextureMetadataSampler: sampler;
+@group(1) @binding(3) 
+var uZBuffer: texture_2d<f32>;
+@group(1) @binding(4) 
+var uZBufferSampler: sampler;
+@group(1) @binding(5) 
+var uColorTexture0_: texture_2d<f32>;
+@group(1) @binding(6) 
+var uColorTexture0Sampler: sampler;
+@group(1) @binding(7) 
+var uMaskTexture0_: texture_2d<f32>;
+@group(1) @binding(8) 
+var uMaskTexture0Sampler: sampler;
+@group(1) @binding(9) 
+var uPaintMaskTexture: texture_2d<f32>;
+@group(1) @binding(10) 
+var uPaintMaskTextureSampler: sampler;
+@group(1) @binding(11) 
+var uDestTexture: texture_2d<f32>;
+@group(1) @binding(12) 
+var uDestTextureSampler: sampler;
+@group(1) @binding(13) 
+var uGammaLUT: texture_2d<f32>;
+@group(1) @binding(14) 
+var uGammaLUTSampler: sampler;
+@group(1) @binding(15) 
+var uDestImage: texture_storage_2d<rgba8unorm,read_write>;
+@group(0) @binding(0) 
+var<storage> global_1: bTiles;
+@group(0) @binding(1) 
+var<storage> global_2: bFirstTileMap;
+var<private> gl_WorkGroupID: vec3<u32>;
+var<private> gl_LocalInvocationID: vec3<u32>;
+
+fn sampleColor(colorTexture: texture_2d<f32>, colorTextureSampler: sampler, colorTexCoord: vec2<f32>) -> vec4<f32> {
+    var colorTexCoord_1: vec2<f32>;
+
+    colorTexCoord_1 = colorTexCoord;
+    let _e4 = colorTexCoord_1;
+    let _e6 = textureSampleLevel(colorTexture, colorTextureSampler, _e4, 0f);
+    return _e6;
+}
+
+fn wrapColorTexCoord(colorTexCoord_2: vec2<f32>, colorTexRect: vec4<f32>, colorTextureSize: vec2<f32>) -> vec2<f32> {
+    var colorTexCoord_3: vec2<f32>;
+    var colorTexRect_1: vec4<f32>;
+    var colorTextureSize_1: vec2<f32>;
+    var size: vec2<f32>;
+    var texRect: vec4<f32>;
+    var offset: vec2<f32>;
+    var halfTexel: vec2<f32>;
+
+    colorTexCoord_3 = colorTexCoord_2;
+    colorTexRect_1 = colorTexRect;
+    colorTextureSize_1 = colorTextureSize;
+    let _e6 = colorTexRect_1;
+    size = abs(_e6.zw);
+    let _e10 = size;
+    let _e14 = size;
+    if ((_e10.x == 0f) || (_e14.y == 0f)) {
+        let _e19 = colorTexCoord_3;
+        return _e19;
+    }
+    let _e20 = colorTexRect_1;
+    let _e21 = colorTextureSize_1;
+    texRect = (_e20 / _e21.xyxy);
+    let _e25 = size;
+    let _e26 = colorTextureSize_1;
+    size = (_e25 / _e26);
+    let _e28 = colorTexCoord_3;
+    let _e29 = texRect;
+    offset = (_e28 - _e29.xy);
+    let _e33 = offset;
+    let _e34 = offset;
+    let _e35 = size;
+    let _e40 = colorTexRect_1;
+    offset = select(_e33, (_e34 - (floor((_e34 / _e35)) * _e35)), (_e40.zw > vec2(0f)));
+    let _e48 = colorTextureSize_1;
+    halfTexel = (vec2(0.5f) / _e48);
+    let _e51 = texRect;
+    let _e53 = offset;
+    let _e54 = halfTexel;
+    let _e55 = size;
+    let _e56 = halfTexel;
+    return (_e51.xy + clamp(_e53, _e54, (_e55 - _e56)));
+}
+
+fn combineColor0_(destColor: vec4<f32>, srcColor: vec4<f32>, op: i32) -> vec4<f32> {
+    var destColor_1: vec4<f32>;
+    var srcColor_1: vec4<f32>;
+    var op_1: i32;
+
+    destColor_1 = destColor;
+    srcColor_1 = srcColor;
+    op_1 = op;
+    let _e6 = op_1;
+    switch _e6 {
+        case 1: {
+            let _e7 = srcColor_1;
+            let _e8 = _e7.xyz;
+            let _e9 = srcColor_1;
+            let _e11 = destColor_1;
+            return vec4<f32>(_e8.x, _e8.y, _e8.z, (_e9.w * _e11.w));
+        }
+        case 2: {
+            let _e18 = destColor_1;
+            let _e19 = _e18.xyz;
+            let _e20 = srcColor_1;
+            let _e22 = destColor_1;
+            return vec4<f32>(_e19.x, _e19.y, _e19.z, (_e20.w * _e22.w));
+        }
+        default: {
+        }
+    }
+    let _e29 = destColor_1;
+    return _e29;
+}
+
+fn filterTextSample1Tap(offset_1: vec2<f32>, colorTexture_1: texture_2d<f32>, colorTextureSampler_1: sampler, colorTexCoord_4: vec2<f32>) -> f32 {
+    var offset_2: vec2<f32>;
+    var colorTexCoord_5: vec2<f32>;
+
+    offset_2 = offset_1;
+    colorTexCoord_5 = colorTexCoord_4;
+    let _e6 = colorTexCoord_5;
+    let _e7 = offset_2;
+    let _e10 = textureSampleLevel(colorTexture_1, colorTextureSampler_1, (_e6 + _e7), 0f);
+    return _e10.x;
+}
+
+fn filterTextSample9Tap(outAlphaLeft: ptr<function, vec4<f32>>, outAlphaCenter: ptr<function, f32>, outAlphaRight: ptr<function, vec4<f32>>, colorTexture_2: texture_2d<f32>, colorTextureSampler_2: sampler, colorTexCoord_6: vec2<f32>, kernel: vec4<f32>, onePixel: vec2<f32>) {
+    var colorTexCoord_7: vec2<f32>;
+    var kernel_1: vec4<f32>;
+    var onePixel_1: vec2<f32>;
+    var wide: bool;
+    var local: f32;
+    var local_1: f32;
+
+    colorTexCoord_7 = colorTexCoord_6;
+    kernel_1 = kernel;
+    onePixel_1 = onePixel;
+    let _e11 = kernel_1;
+    wide = (_e11.x > 0f);
+    let _e16 = wide;
+    if _e16 {
+        let _e18 = onePixel_1;
+        let _e20 = colorTexCoord_7;
+        let _e21 = filterTextSample1Tap((-4f * _e18), colorTexture_2, colorTextureSampler_2, _e20);
+        local = _e21;
+    } else {
+        local = 0f;
+    }
+    let _e24 = local;
+    let _e26 = onePixel_1;
+    let _e28 = colorTexCoord_7;
+    let _e29 = filterTextSample1Tap((-3f * _e26), colorTexture_2, colorTextureSampler_2, _e28);
+    let _e31 = onePixel_1;
+    let _e33 = colorTexCoord_7;
+    let _e34 = filterTextSample1Tap((-2f * _e31), colorTexture_2, colorTextureSampler_2, _e33);
+    let _e36 = onePixel_1;
+    let _e38 = colorTexCoord_7;
+    let _e39 = filterTextSample1Tap((-1f * _e36), colorTexture_2, colorTextureSampler_2, _e38);
+    (*outAlphaLeft) = vec4<f32>(_e24, _e29, _e34, _e39);
+    let _e43 = colorTexCoord_7;
+    let _e44 = filterTextSample1Tap(vec2(0f), colorTexture_2, colorTextureSampler_2, _e43);
+    (*outAlphaCenter) = _e44;
+    let _e46 = onePixel_1;
+    let _e48 = colorTexCoord_7;
+    let _e49 = filterTextSample1Tap((1f * _e46), colorTexture_2, colorTextureSampler_2, _e48);
+    let _e51 = onePixel_1;
+    let _e53 = colorTexCoord_7;
+    let _e54 = filterTextSample1Tap((2f * _e51), colorTexture_2, colorTextureSampler_2, _e53);
+    let _e56 = onePixel_1;
+    let _e58 = colorTexCoord_7;
+    let _e59 = filterTextSample1Tap((3f * _e56), colorTexture_2, colorTextureSampler_2, _e58);
+    let _e60 = wide;
+    if _e60 {
+        let _e62 = onePixel_1;
+        let _e64 = colorTexCoord_7;
+        let _e65 = filterTextSample1Tap((4f * _e62), colorTexture_2, colorTextureSampler_2, _e64);
+        local_1 = _e65;
+    } else {
+        local_1 = 0f;
+    }
+    let _e68 = local_1;
+    (*outAlphaRight) = vec4<f32>(_e49, _e54, _e59, _e68);
+    return;
+}
+
+fn filterTextConvolve7Tap(alpha0_: vec4<f32>, alpha1_: vec3<f32>, kernel_2: vec4<f32>) -> f32 {
+    var alpha0_1: vec4<f32>;
+    var alpha1_1: vec3<f32>;
+    var kernel_3: vec4<f32>;
+
+    alpha0_1 = alpha0_;
+    alpha1_1 = alpha1_;
+    kernel_3 = kernel_2;
+    let _e6 = alpha0_1;
+    let _e7 = kernel_3;
+    let _e9 = alpha1_1;
+    let _e10 = kernel_3;
+    return (dot(_e6, _e7) + dot(_e9, _e10.zyx));
+}
+
+fn filterTextGammaCorrectChannel(bgColor: f32, fgColor: f32, gammaLUT: texture_2d<f32>, gammaLUTSampler: sampler) -> f32 {
+    var bgColor_1: f32;
+    var fgColor_1: f32;
+
+    bgColor_1 = bgColor;
+    fgColor_1 = fgColor;
+    let _e6 = fgColor_1;
+    let _e8 = bgColor_1;
+    let _e12 = textureSampleLevel(gammaLUT, gammaLUTSampler, vec2<f32>(_e6, (1f - _e8)), 0f);
+    return _e12.x;
+}
+
+fn filterTextGammaCorrect(bgColor_2: vec3<f32>, fgColor_2: vec3<f32>, gammaLUT_1: texture_2d<f32>, gammaLUTSampler_1: sampler) -> vec3<f32> {
+    var bgColor_3: vec3<f32>;
+    var fgColor_3: vec3<f32>;
+
+    bgColor_3 = bgColor_2;
+    fgColor_3 = fgColor_2;
+    let _e6 = bgColor_3;
+    let _e8 = fgColor_3;
+    let _e10 = filterTextGammaCorrectChannel(_e6.x, _e8.x, gammaLUT_1, gammaLUTSampler_1);
+    let _e11 = bgColor_3;
+    let _e13 = fgColor_3;
+    let _e15 = filterTextGammaCorrectChannel(_e11.y, _e13.y, gammaLUT_1, gammaLUTSampler_1);
+    let _e16 = bgColor_3;
+    let _e18 = fgColor_3;
+    let _e20 = filterTextGammaCorrectChannel(_e16.z, _e18.z, gammaLUT_1, gammaLUTSampler_1);
+    return vec3<f32>(_e10, _e15, _e20);
+}
+
+fn filterText(colorTexCoord_8: vec2<f32>, colorTexture_3: texture_2d<f32>, colorTextureSampler_3: sampler, gammaLUT_2: texture_2d<f32>, gammaLUTSampler_2: sampler, colorTextureSize_2: vec2<f32>, filterParams0_: vec4<f32>, filterParams1_: vec4<f32>, filterParams2_: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_9: vec2<f32>;
+    var colorTextureSize_3: vec2<f32>;
+    var filterParams0_1: vec4<f32>;
+    var filterParams1_1: vec4<f32>;
+    var filterParams2_1: vec4<f32>;
+    var kernel_4: vec4<f32>;
+    var bgColor_4: vec3<f32>;
+    var fgColor_4: vec3<f32>;
+    var gammaCorrectionEnabled: bool;
+    var bgrLayout: bool;
+    var verticalLayout: bool;
+    var alpha: vec3<f32>;
+    var alphaLeft: vec4<f32>;
+    var alphaRight: vec4<f32>;
+    var alphaCenter: f32;
+    var local_2: vec2<f32>;
+    var local_3: vec2<f32>;
+    var r: f32;
+    var g: f32;
+    var b: f32;
+    var local_4: vec3<f32>;
+
+    colorTexCoord_9 = colorTexCoord_8;
+    colorTextureSize_3 = colorTextureSize_2;
+    filterParams0_1 = filterParams0_;
+    filterParams1_1 = filterParams1_;
+    filterParams2_1 = filterParams2_;
+    let _e14 = filterParams0_1;
+    kernel_4 = _e14;
+    let _e16 = filterParams1_1;
+    bgColor_4 = _e16.xyz;
+    let _e19 = filterParams2_1;
+    fgColor_4 = _e19.xyz;
+    let _e22 = filterParams2_1;
+    gammaCorrectionEnabled = (_e22.w != 0f);
+    let _e27 = filterParams1_1;
+    let _e31 = filterParams1_1;
+    bgrLayout = ((_e27.w == 1f) || (_e31.w == 3f));
+    let _e37 = filterParams1_1;
+    verticalLayout = (_e37.w >= 2f);
+    let _e43 = kernel_4;
+    if (_e43.w == 0f) {
+        {
+            let _e47 = colorTexCoord_9;
+            let _e49 = textureSampleLevel(colorTexture_3, colorTextureSampler_3, _e47, 0f);
+            alpha = _e49.xxx;
+        }
+    } else {
+        {
+            let _e54 = verticalLayout;
+            if _e54 {
+                let _e57 = colorTextureSize_3;
+                local_2 = vec2<f32>(0f, (1f / _e57.y));
+            } else {
+                let _e62 = colorTextureSize_3;
+                local_2 = vec2<f32>((1f / _e62.x), 0f);
+            }
+            let _e68 = colorTexCoord_9;
+            let _e69 = kernel_4;
+            let _e70 = verticalLayout;
+            if _e70 {
+                let _e73 = colorTextureSize_3;
+                local_3 = vec2<f32>(0f, (1f / _e73.y));
+            } else {
+                let _e78 = colorTextureSize_3;
+                local_3 = vec2<f32>((1f / _e78.x), 0f);
+            }
+            let _e84 = local_3;
+            filterTextSample9Tap((&alphaLeft), (&alphaCenter), (&alphaRight), colorTexture_3, colorTextureSampler_3, _e68, _e69, _e84);
+            let _e85 = alphaLeft;
+            let _e86 = alphaCenter;
+            let _e87 = alphaRight;
+            let _e88 = _e87.xy;
+            let _e92 = kernel_4;
+            let _e93 = filterTextConvolve7Tap(_e85, vec3<f32>(_e86, _e88.x, _e88.y), _e92);
+            r = _e93;
+            let _e95 = alphaLeft;
+            let _e96 = _e95.yzw;
+            let _e97 = alphaCenter;
+            let _e102 = alphaRight;
+            let _e104 = kernel_4;
+            let _e105 = filterTextConvolve7Tap(vec4<f32>(_e96.x, _e96.y, _e96.z, _e97), _e102.xyz, _e104);
+            g = _e105;
+            let _e107 = alphaLeft;
+            let _e108 = _e107.zw;
+            let _e109 = alphaCenter;
+            let _e110 = alphaRight;
+            let _e115 = alphaRight;
+            let _e117 = kernel_4;
+            let _e118 = filterTextConvolve7Tap(vec4<f32>(_e108.x, _e108.y, _e109, _e110.x), _e115.yzw, _e117);
+            b = _e118;
+            let _e120 = bgrLayout;
+            if _e120 {
+                let _e121 = b;
+                let _e122 = g;
+                let _e123 = r;
+                local_4 = vec3<f32>(_e121, _e122, _e123);
+            } else {
+                let _e125 = r;
+                let _e126 = g;
+                let _e127 = b;
+                local_4 = vec3<f32>(_e125, _e126, _e127);
+            }
+            let _e130 = local_4;
+            alpha = _e130;
+        }
+    }
+    let _e131 = gammaCorrectionEnabled;
+    if _e131 {
+        let _e132 = bgColor_4;
+        let _e133 = alpha;
+        let _e134 = filterTextGammaCorrect(_e132, _e133, gammaLUT_2, gammaLUTSampler_2);
+        alpha = _e134;
+    }
+    let _e135 = bgColor_4;
+    let _e136 = fgColor_4;
+    let _e137 = alpha;
+    let _e138 = mix(_e135, _e136, _e137);
+    return vec4<f32>(_e138.x, _e138.y, _e138.z, 1f);
+}
+
+fn filterRadialGradient(colorTexCoord_10: vec2<f32>, colorTexture_4: texture_2d<f32>, colorTextureSampler_4: sampler, colorTextureSize_4: vec2<f32>, fragCoord: vec2<f32>, framebufferSize: vec2<f32>, filterParams0_2: vec4<f32>, filterParams1_2: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_11: vec2<f32>;
+    var colorTextureSize_5: vec2<f32>;
+    var fragCoord_1: vec2<f32>;
+    var framebufferSize_1: vec2<f32>;
+    var filterParams0_3: vec4<f32>;
+    var filterParams1_3: vec4<f32>;
+    var lineFrom: vec2<f32>;
+    var lineVector: vec2<f32>;
+    var radii: vec2<f32>;
+    var uvOrigin: vec2<f32>;
+    var dP: vec2<f32>;
+    var dC: vec2<f32>;
+    var dR: f32;
+    var a: f32;
+    var b_1: f32;
+    var c: f32;
+    var discrim: f32;
+    var color: vec4<f32> = vec4(0f);
+    var ts: vec2<f32>;
+    var local_5: f32;
+    var t: f32;
+
+    colorTexCoord_11 = colorTexCoord_10;
+    colorTextureSize_5 = colorTextureSize_4;
+    fragCoord_1 = fragCoord;
+    framebufferSize_1 = framebufferSize;
+    filterParams0_3 = filterParams0_2;
+    filterParams1_3 = filterParams1_2;
+    let _e14 = filterParams0_3;
+    lineFrom = _e14.xy;
+    let _e17 = filterParams0_3;
+    lineVector = _e17.zw;
+    let _e20 = filterParams1_3;
+    radii = _e20.xy;
+    let _e23 = filterParams1_3;
+    uvOrigin = _e23.zw;
+    let _e26 = colorTexCoord_11;
+    let _e27 = lineFrom;
+    dP = (_e26 - _e27);
+    let _e30 = lineVector;
+    dC = _e30;
+    let _e32 = radii;
+    let _e34 = radii;
+    dR = (_e32.y - _e34.x);
+    let _e38 = dC;
+    let _e39 = dC;
+    let _e41 = dR;
+    let _e42 = dR;
+    a = (dot(_e38, _e39) - (_e41 * _e42));
+    let _e46 = dP;
+    let _e47 = dC;
+    let _e49 = radii;
+    let _e51 = dR;
+    b_1 = (dot(_e46, _e47) + (_e49.x * _e51));
+    let _e55 = dP;
+    let _e56 = dP;
+    let _e58 = radii;
+    let _e60 = radii;
+    c = (dot(_e55, _e56) - (_e58.x * _e60.x));
+    let _e65 = b_1;
+    let _e66 = b_1;
+    let _e68 = a;
+    let _e69 = c;
+    discrim = ((_e65 * _e66) - (_e68 * _e69));
+    let _e76 = discrim;
+    if (abs(_e76) >= 0.00001f) {
+        {
+            let _e80 = discrim;
+            let _e86 = b_1;
+            let _e90 = a;
+            ts = (vec2<f32>(((sqrt(_e80) * vec2<f32>(1f, -1f)) + vec2(_e86))) / vec2(_e90));
+            let _e94 = ts;
+            let _e96 = ts;
+            if (_e94.x > _e96.y) {
+                let _e99 = ts;
+                ts = _e99.yx;
+            }
+            let _e101 = ts;
+            if (_e101.x >= 0f) {
+                let _e105 = ts;
+                local_5 = _e105.x;
+            } else {
+                let _e107 = ts;
+                local_5 = _e107.y;
+            }
+            let _e110 = local_5;
+            t = _e110;
+            let _e112 = uvOrigin;
+            let _e113 = t;
+            let _e121 = textureSampleLevel(colorTexture_4, colorTextureSampler_4, (_e112 + vec2<f32>(clamp(_e113, 0f, 1f), 0f)), 0f);
+            color = _e121;
+        }
+    }
+    let _e122 = color;
+    return _e122;
+}
+
+fn filterBlur(colorTexCoord_12: vec2<f32>, colorTexture_5: texture_2d<f32>, colorTextureSampler_5: sampler, colorTextureSize_6: vec2<f32>, filterParams0_4: vec4<f32>, filterParams1_4: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_13: vec2<f32>;
+    var colorTextureSize_7: vec2<f32>;
+    var filterParams0_5: vec4<f32>;
+    var filterParams1_5: vec4<f32>;
+    var srcOffsetScale: vec2<f32>;
+    var support: i32;
+    var gaussCoeff: vec3<f32>;
+    var gaussSum: f32;
+    var color_1: vec4<f32>;
+    var i: i32 = 1i;
+    var gaussPartialSum: f32;
+    var srcOffset: vec2<f32>;
+
+    colorTexCoord_13 = colorTexCoord_12;
+    colorTextureSize_7 = colorTextureSize_6;
+    filterParams0_5 = filterParams0_4;
+    filterParams1_5 = filterParams1_4;
+    let _e10 = filterParams0_5;
+    let _e12 = colorTextureSize_7;
+    srcOffsetScale = (_e10.xy / _e12);
+    let _e15 = filterParams0_5;
+    support = i32(_e15.z);
+    let _e19 = filterParams1_5;
+    gaussCoeff = _e19.xyz;
+    let _e22 = gaussCoeff;
+    gaussSum = _e22.x;
+    let _e25 = colorTexCoord_13;
+    let _e27 = textureSampleLevel(colorTexture_5, colorTextureSampler_5, _e25, 0f);
+    let _e28 = gaussCoeff;
+    color_1 = (_e27 * _e28.x);
+    let _e32 = gaussCoeff;
+    let _e34 = gaussCoeff;
+    let _e36 = (_e32.xy * _e34.yz);
+    gaussCoeff.x = _e36.x;
+    gaussCoeff.y = _e36.y;
+    loop {
+        let _e43 = i;
+        let _e44 = support;
+        if !((_e43 <= _e44)) {
+            break;
+        }
+        {
+            let _e50 = gaussCoeff;
+            gaussPartialSum = _e50.x;
+            let _e53 = gaussCoeff;
+            let _e55 = gaussCoeff;
+            let _e57 = (_e53.xy * _e55.yz);
+            gaussCoeff.x = _e57.x;
+            gaussCoeff.y = _e57.y;
+            let _e62 = gaussPartialSum;
+            let _e63 = gaussCoeff;
+            gaussPartialSum = (_e62 + _e63.x);
+            let _e66 = srcOffsetScale;
+            let _e67 = i;
+            let _e69 = gaussCoeff;
+            let _e71 = gaussPartialSum;
+            srcOffset = (_e66 * (f32(_e67) + (_e69.x / _e71)));
+            let _e76 = color_1;
+            let _e77 = colorTexCoord_13;
+            let _e78 = srcOffset;
+            let _e81 = textureSampleLevel(colorTexture_5, colorTextureSampler_5, (_e77 - _e78), 0f);
+            let _e82 = colorTexCoord_13;
+            let _e83 = srcOffset;
+            let _e86 = textureSampleLevel(colorTexture_5, colorTextureSampler_5, (_e82 + _e83), 0f);
+            let _e88 = gaussPartialSum;
+            color_1 = (_e76 + ((_e81 + _e86) * _e88));
+            let _e91 = gaussSum;
+            let _e93 = gaussPartialSum;
+            gaussSum = (_e91 + (2f * _e93));
+            let _e96 = gaussCoeff;
+            let _e98 = gaussCoeff;
+            let _e100 = (_e96.xy * _e98.yz);
+            gaussCoeff.x = _e100.x;
+            gaussCoeff.y = _e100.y;
+        }
+        continuing {
+            let _e47 = i;
+            i = (_e47 + 2i);
+        }
+    }
+    let _e105 = color_1;
+    let _e106 = gaussSum;
+    return (_e105 / vec4(_e106));
+}
+
+fn filterNone(colorTexCoord_14: vec2<f32>, colorTexture_6: texture_2d<f32>, colorTextureSampler_6: sampler) -> vec4<f32> {
+    var colorTexCoord_15: vec2<f32>;
+
+    colorTexCoord_15 = colorTexCoord_14;
+    let _e4 = colorTexCoord_15;
+    let _e5 = sampleColor(colorTexture_6, colorTextureSampler_6, _e4);
+    return _e5;
+}
+
+fn filterColor(colorTexCoord_16: vec2<f32>, colorTexture_7: texture_2d<f32>, colorTextureSampler_7: sampler, gammaLUT_3: texture_2d<f32>, gammaLUTSampler_3: sampler, colorTextureSize_8: vec2<f32>, fragCoord_2: vec2<f32>, framebufferSize_2: vec2<f32>, filterParams0_6: vec4<f32>, filterParams1_6: vec4<f32>, filterParams2_2: vec4<f32>, colorFilter: i32) -> vec4<f32> {
+    var colorTexCoord_17: vec2<f32>;
+    var colorTextureSize_9: vec2<f32>;
+    var fragCoord_3: vec2<f32>;
+    var framebufferSize_3: vec2<f32>;
+    var filterParams0_7: vec4<f32>;
+    var filterParams1_7: vec4<f32>;
+    var filterParams2_3: vec4<f32>;
+    var colorFilter_1: i32;
+
+    colorTexCoord_17 = colorTexCoord_16;
+    colorTextureSize_9 = colorTextureSize_8;
+    fragCoord_3 = fragCoord_2;
+    framebufferSize_3 = framebufferSize_2;
+    filterParams0_7 = filterParams0_6;
+    filterParams1_7 = filterParams1_6;
+    filterParams2_3 = filterParams2_2;
+    colorFilter_1 = colorFilter;
+    let _e20 = colorFilter_1;
+    switch _e20 {
+        case 1: {
+            let _e21 = colorTexCoord_17;
+            let _e22 = colorTextureSize_9;
+            let _e23 = fragCoord_3;
+            let _e24 = framebufferSize_3;
+            let _e25 = filterParams0_7;
+            let _e26 = filterParams1_7;
+            let _e27 = filterRadialGradient(_e21, colorTexture_7, colorTextureSampler_7, _e22, _e23, _e24, _e25, _e26);
+            return _e27;
+        }
+        case 3: {
+            let _e28 = colorTexCoord_17;
+            let _e29 = colorTextureSize_9;
+            let _e30 = filterParams0_7;
+            let _e31 = filterParams1_7;
+            let _e32 = filterBlur(_e28, colorTexture_7, colorTextureSampler_7, _e29, _e30, _e31);
+            return _e32;
+        }
+        case 2: {
+            let _e33 = colorTexCoord_17;
+            let _e34 = colorTextureSize_9;
+            let _e35 = filterParams0_7;
+            let _e36 = filterParams1_7;
+            let _e37 = filterParams2_3;
+            let _e38 = filterText(_e33, colorTexture_7, colorTextureSampler_7, gammaLUT_3, gammaLUTSampler_3, _e34, _e35, _e36, _e37);
+            return _e38;
+        }
+        default: {
+        }
+    }
+    let _e39 = colorTexCoord_17;
+    let _e40 = filterNone(_e39, colorTexture_7, colorTextureSampler_7);
+    return _e40;
+}
+
+fn compositeSelect(cond: vec3<bool>, ifTrue: vec3<f32>, ifFalse: vec3<f32>) -> vec3<f32> {
+    var cond_1: vec3<bool>;
+    var ifTrue_1: vec3<f32>;
+    var ifFalse_1: vec3<f32>;
+    var local_6: f32;
+    var local_7: f32;
+    var local_8: f32;
+
+    cond_1 = cond;
+    ifTrue_1 = ifTrue;
+    ifFalse_1 = ifFalse;
+    let _e6 = cond_1;
+    if _e6.x {
+        let _e8 = ifTrue_1;
+        local_6 = _e8.x;
+    } else {
+        let _e10 = ifFalse_1;
+        local_6 = _e10.x;
+    }
+    let _e13 = local_6;
+    let _e14 = cond_1;
+    if _e14.y {
+        let _e16 = ifTrue_1;
+        local_7 = _e16.y;
+    } else {
+        let _e18 = ifFalse_1;
+        local_7 = _e18.y;
+    }
+    let _e21 = local_7;
+    let _e22 = cond_1;
+    if _e22.z {
+        let _e24 = ifTrue_1;
+        local_8 = _e24.z;
+    } else {
+        let _e26 = ifFalse_1;
+        local_8 = _e26.z;
+    }
+    let _e29 = local_8;
+    return vec3<f32>(_e13, _e21, _e29);
+}
+
+fn compositeDivide(num: f32, denom: f32) -> f32 {
+    var num_1: f32;
+    var denom_1: f32;
+    var local_9: f32;
+
+    num_1 = num;
+    denom_1 = denom;
+    let _e4 = denom_1;
+    if (_e4 != 0f) {
+        let _e7 = num_1;
+        let _e8 = denom_1;
+        local_9 = (_e7 / _e8);
+    } else {
+        local_9 = 0f;
+    }
+    let _e12 = local_9;
+    return _e12;
+}
+
+fn compositeColorDodge(destColor_2: vec3<f32>, srcColor_2: vec3<f32>) -> vec3<f32> {
+    var destColor_3: vec3<f32>;
+    var srcColor_3: vec3<f32>;
+    var destZero: vec3<bool>;
+    var srcOne: vec3<bool>;
+
+    destColor_3 = destColor_2;
+    srcColor_3 = srcColor_2;
+    let _e4 = destColor_3;
+    destZero = (_e4 == vec3(0f));
+    let _e9 = srcColor_3;
+    srcOne = (_e9 == vec3(1f));
+    let _e14 = srcOne;
+    let _e17 = destColor_3;
+    let _e20 = srcColor_3;
+    let _e23 = compositeSelect(_e14, vec3(1f), (_e17 / (vec3(1f) - _e20)));
+    let _e24 = destZero;
+    let _e27 = srcOne;
+    let _e30 = destColor_3;
+    let _e33 = srcColor_3;
+    let _e36 = compositeSelect(_e27, vec3(1f), (_e30 / (vec3(1f) - _e33)));
+    let _e37 = compositeSelect(_e24, vec3(0f), _e36);
+    return _e37;
+}
+
+fn compositeHSLToRGB(hsl: vec3<f32>) -> vec3<f32> {
+    var hsl_1: vec3<f32>;
+    var a_1: f32;
+    var ks: vec3<f32>;
+
+    hsl_1 = hsl;
+    let _e2 = hsl_1;
+    let _e4 = hsl_1;
+    let _e7 = hsl_1;
+    a_1 = (_e2.y * min(_e4.z, (1f - _e7.z)));
+    let _e17 = hsl_1;
+    let _e22 = (vec3<f32>(0f, 8f, 4f) + vec3((_e17.x * 1.9098593f)));
+    let _e24 = vec3(12f);
+    ks = (_e22 - (floor((_e22 / _e24)) * _e24));
+    let _e30 = hsl_1;
+    let _e32 = ks;
+    let _e38 = ks;
+    let _e46 = a_1;
+    return (_e30.zzz - (clamp(min((_e32 - vec3(3f)), (vec3(9f) - _e38)), vec3(-1f), vec3(1f)) * _e46));
+}
+
+fn compositeRGBToHSL(rgb: vec3<f32>) -> vec3<f32> {
+    var rgb_1: vec3<f32>;
+    var v: f32;
+    var xMin: f32;
+    var c_1: f32;
+    var l: f32;
+    var local_10: vec3<f32>;
+    var local_11: vec3<f32>;
+    var terms: vec3<f32>;
+    var h: f32;
+    var s: f32;
+
+    rgb_1 = rgb;
+    let _e2 = rgb_1;
+    let _e4 = rgb_1;
+    let _e7 = rgb_1;
+    v = max(max(_e2.x, _e4.y), _e7.z);
+    let _e11 = rgb_1;
+    let _e13 = rgb_1;
+    let _e16 = rgb_1;
+    xMin = min(min(_e11.x, _e13.y), _e16.z);
+    let _e20 = v;
+    let _e21 = xMin;
+    c_1 = (_e20 - _e21);
+    let _e24 = xMin;
+    let _e25 = v;
+    l = mix(_e24, _e25, 0.5f);
+    let _e29 = rgb_1;
+    let _e31 = v;
+    if (_e29.x == _e31) {
+        let _e34 = rgb_1;
+        let _e35 = _e34.yz;
+        local_11 = vec3<f32>(0f, _e35.x, _e35.y);
+    } else {
+        let _e39 = rgb_1;
+        let _e41 = v;
+        if (_e39.y == _e41) {
+            let _e44 = rgb_1;
+            let _e45 = _e44.zx;
+            local_10 = vec3<f32>(2f, _e45.x, _e45.y);
+        } else {
+            let _e50 = rgb_1;
+            let _e51 = _e50.xy;
+            local_10 = vec3<f32>(4f, _e51.x, _e51.y);
+        }
+        let _e56 = local_10;
+        local_11 = _e56;
+    }
+    let _e58 = local_11;
+    terms = _e58;
+    let _e61 = terms;
+    let _e63 = c_1;
+    let _e65 = terms;
+    let _e68 = terms;
+    let _e71 = c_1;
+    let _e72 = compositeDivide((((_e61.x * _e63) + _e65.y) - _e68.z), _e71);
+    h = (1.0471976f * _e72);
+    let _e75 = c_1;
+    let _e76 = v;
+    let _e77 = compositeDivide(_e75, _e76);
+    s = _e77;
+    let _e79 = h;
+    let _e80 = s;
+    let _e81 = l;
+    return vec3<f32>(_e79, _e80, _e81);
+}
+
+fn compositeScreen(destColor_4: vec3<f32>, srcColor_4: vec3<f32>) -> vec3<f32> {
+    var destColor_5: vec3<f32>;
+    var srcColor_5: vec3<f32>;
+
+    destColor_5 = destColor_4;
+    srcColor_5 = srcColor_4;
+    let _e4 = destColor_5;
+    let _e5 = srcColor_5;
+    let _e7 = destColor_5;
+    let _e8 = srcColor_5;
+    return ((_e4 + _e5) - (_e7 * _e8));
+}
+
+fn compositeHardLight(destColor_6: vec3<f32>, srcColor_6: vec3<f32>) -> vec3<f32> {
+    var destColor_7: vec3<f32>;
+    var srcColor_7: vec3<f32>;
+
+    destColor_7 = destColor_6;
+    srcColor_7 = srcColor_6;
+    let _e4 = destColor_7;
+    let _e7 = srcColor_7;
+    let _e12 = compositeScreen(_e4, ((vec3(2f) * _e7) - vec3(1f)));
+    let _e13 = srcColor_7;
+    let _e17 = destColor_7;
+    let _e21 = srcColor_7;
+    let _e23 = destColor_7;
+    let _e26 = srcColor_7;
+    let _e31 = compositeScreen(_e23, ((vec3(2f) * _e26) - vec3(1f)));
+    let _e32 = compositeSelect((_e13 <= vec3(0.5f)), ((_e17 * vec3(2f)) * _e21), _e31);
+    return _e32;
+}
+
+fn compositeSoftLight(destColor_8: vec3<f32>, srcColor_8: vec3<f32>) -> vec3<f32> {
+    var destColor_9: vec3<f32>;
+    var srcColor_9: vec3<f32>;
+    var darkenedDestColor: vec3<f32>;
+    var factor: vec3<f32>;
+
+    destColor_9 = destColor_8;
+    srcColor_9 = srcColor_8;
+    let _e4 = destColor_9;
+    let _e10 = destColor_9;
+    let _e15 = destColor_9;
+    let _e20 = destColor_9;
+    let _e22 = destColor_9;
+    let _e24 = compositeSelect((_e4 <= vec3(0.25f)), (((((vec3(16f) * _e10) - vec3(12f)) * _e15) + vec3(4f)) * _e20), sqrt(_e22));
+    darkenedDestColor = _e24;
+    let _e26 = srcColor_9;
+    let _e30 = destColor_9;
+    let _e33 = destColor_9;
+    let _e36 = darkenedDestColor;
+    let _e37 = destColor_9;
+    let _e39 = compositeSelect((_e26 <= vec3(0.5f)), (_e30 * (vec3(1f) - _e33)), (_e36 - _e37));
+    factor = _e39;
+    let _e41 = destColor_9;
+    let _e42 = srcColor_9;
+    let _e48 = factor;
+    return (_e41 + (((_e42 * 2f) - vec3(1f)) * _e48));
+}
+
+fn compositeHSL(destColor_10: vec3<f32>, srcColor_10: vec3<f32>, op_2: i32) -> vec3<f32> {
+    var destColor_11: vec3<f32>;
+    var srcColor_11: vec3<f32>;
+    var op_3: i32;
+
+    destColor_11 = destColor_10;
+    srcColor_11 = srcColor_10;
+    op_3 = op_2;
+    let _e6 = op_3;
+    switch _e6 {
+        case 12: {
+            let _e7 = srcColor_11;
+            let _e9 = destColor_11;
+            let _e11 = destColor_11;
+            return vec3<f32>(_e7.x, _e9.y, _e11.z);
+        }
+        case 13: {
+            let _e14 = destColor_11;
+            let _e16 = srcColor_11;
+            let _e18 = destColor_11;
+            return vec3<f32>(_e14.x, _e16.y, _e18.z);
+        }
+        case 14: {
+            let _e21 = srcColor_11;
+            let _e23 = srcColor_11;
+            let _e25 = destColor_11;
+            return vec3<f32>(_e21.x, _e23.y, _e25.z);
+        }
+        default: {
+            let _e28 = destColor_11;
+            let _e30 = destColor_11;
+            let _e32 = srcColor_11;
+            return vec3<f32>(_e28.x, _e30.y, _e32.z);
+        }
+    }
+}
+
+fn compositeRGB(destColor_12: vec3<f32>, srcColor_12: vec3<f32>, op_4: i32) -> vec3<f32> {
+    var destColor_13: vec3<f32>;
+    var srcColor_13: vec3<f32>;
+    var op_5: i32;
+
+    destColor_13 = destColor_12;
+    srcColor_13 = srcColor_12;
+    op_5 = op_4;
+    let _e6 = op_5;
+    switch _e6 {
+        case 1: {
+            let _e7 = destColor_13;
+            let _e8 = srcColor_13;
+            return (_e7 * _e8);
+        }
+        case 2: {
+            let _e10 = destColor_13;
+            let _e11 = srcColor_13;
+            let _e12 = compositeScreen(_e10, _e11);
+            return _e12;
+        }
+        case 3: {
+            let _e13 = srcColor_13;
+            let _e14 = destColor_13;
+            let _e15 = compositeHardLight(_e13, _e14);
+            return _e15;
+        }
+        case 4: {
+            let _e16 = destColor_13;
+            let _e17 = srcColor_13;
+            return min(_e16, _e17);
+        }
+        case 5: {
+            let _e19 = destColor_13;
+            let _e20 = srcColor_13;
+            return max(_e19, _e20);
+        }
+        case 6: {
+            let _e22 = destColor_13;
+            let _e23 = srcColor_13;
+            let _e24 = compositeColorDodge(_e22, _e23);
+            return _e24;
+        }
+        case 7: {
+            let _e29 = destColor_13;
+            let _e33 = srcColor_13;
+            let _e35 = compositeColorDodge((vec3(1f) - _e29), (vec3(1f) - _e33));
+            return (vec3(1f) - _e35);
+        }
+        case 8: {
+            let _e37 = destColor_13;
+            let _e38 = srcColor_13;
+            let _e39 = compositeHardLight(_e37, _e38);
+            return _e39;
+        }
+        case 9: {
+            let _e40 = destColor_13;
+            let _e41 = srcColor_13;
+            let _e42 = compositeSoftLight(_e40, _e41);
+            return _e42;
+        }
+        case 10: {
+            let _e43 = destColor_13;
+            let _e44 = srcColor_13;
+            return abs((_e43 - _e44));
+        }
+        case 11: {
+            let _e47 = destColor_13;
+            let _e48 = srcColor_13;
+            let _e52 = destColor_13;
+            let _e54 = srcColor_13;
+            return ((_e47 + _e48) - ((vec3(2f) * _e52) * _e54));
+        }
+        case 12, 13, 14, 15: {
+            let _e57 = destColor_13;
+            let _e58 = compositeRGBToHSL(_e57);
+            let _e59 = srcColor_13;
+            let _e60 = compositeRGBToHSL(_e59);
+            let _e61 = destColor_13;
+            let _e62 = compositeRGBToHSL(_e61);
+            let _e63 = srcColor_13;
+            let _e64 = compositeRGBToHSL(_e63);
+            let _e65 = op_5;
+            let _e66 = compositeHSL(_e62, _e64, _e65);
+            let _e67 = destColor_13;
+            let _e68 = compositeRGBToHSL(_e67);
+            let _e69 = srcColor_13;
+            let _e70 = compositeRGBToHSL(_e69);
+            let _e71 = destColor_13;
+            let _e72 = compositeRGBToHSL(_e71);
+            let _e73 = srcColor_13;
+            let _e74 = compositeRGBToHSL(_e73);
+            let _e75 = op_5;
+            let _e76 = compositeHSL(_e72, _e74, _e75);
+            let _e77 = compositeHSLToRGB(_e76);
+            return _e77;
+        }
+        default: {
+        }
+    }
+    let _e78 = srcColor_13;
+    return _e78;
+}
+
+fn composite(srcColor_14: vec4<f32>, destTexture: texture_2d<f32>, destTextureSampler: sampler, destTextureSize: vec2<f32>, fragCoord_4: vec2<f32>, op_6: i32) -> vec4<f32> {
+    var srcColor_15: vec4<f32>;
+    var destTextureSize_1: vec2<f32>;
+    var fragCoord_5: vec2<f32>;
+    var op_7: i32;
+    var destTexCoord: vec2<f32>;
+    var destColor_14: vec4<f32>;
+    var blendedRGB: vec3<f32>;
+
+    srcColor_15 = srcColor_14;
+    destTextureSize_1 = destTextureSize;
+    fragCoord_5 = fragCoord_4;
+    op_7 = op_6;
+    let _e10 = op_7;
+    if (_e10 == 0i) {
+        let _e13 = srcColor_15;
+        return _e13;
+    }
+    let _e14 = fragCoord_5;
+    let _e15 = destTextureSize_1;
+    destTexCoord = (_e14 / _e15);
+    let _e18 = destTexCoord;
+    let _e20 = textureSampleLevel(destTexture, destTextureSampler, _e18, 0f);
+    destColor_14 = _e20;
+    let _e22 = destColor_14;
+    let _e24 = srcColor_15;
+    let _e26 = op_7;
+    let _e27 = compositeRGB(_e22.xyz, _e24.xyz, _e26);
+    blendedRGB = _e27;
+    let _e29 = srcColor_15;
+    let _e32 = destColor_14;
+    let _e36 = srcColor_15;
+    let _e39 = srcColor_15;
+    let _e41 = destColor_14;
+    let _e44 = blendedRGB;
+    let _e48 = srcColor_15;
+    let _e51 = destColor_14;
+    let _e54 = ((((_e29.w * (1f - _e32.w)) * _e36.xyz) + ((_e39.w * _e41.w) * _e44)) + ((1f - _e48.w) * _e51.xyz));
+    return vec4<f32>(_e54.x, _e54.y, _e54.z, 1f);
+}
+
+fn sampleMask(maskAlpha: f32, maskTexture: texture_2d<f32>, maskTextureSampler: sampler, maskTextureSize: vec2<f32>, maskTexCoord: vec3<f32>, maskCtrl: i32) -> f32 {
+    var maskAlpha_1: f32;
+    var maskTextureSize_1: vec2<f32>;
+    var maskTexCoord_1: vec3<f32>;
+    var maskCtrl_1: i32;
+    var maskTexCoordI: vec2<i32>;
+    var texel: vec4<f32>;
+    var coverage: f32;
+
+    maskAlpha_1 = maskAlpha;
+    maskTextureSize_1 = maskTextureSize;
+    maskTexCoord_1 = maskTexCoord;
+    maskCtrl_1 = maskCtrl;
+    let _e10 = maskCtrl_1;
+    if (_e10 == 0i) {
+        let _e13 = maskAlpha_1;
+        return _e13;
+    }
+    let _e14 = maskTexCoord_1;
+    maskTexCoordI = vec2<i32>(floor(_e14.xy));
+    let _e19 = maskTexCoordI;
+    let _e28 = maskTextureSize_1;
+    let _e31 = textureSampleLevel(maskTexture, maskTextureSampler, ((vec2<f32>((_e19 / vec2<i32>(1i, 4i))) + vec2(0.5f)) / _e28), 0f);
+    texel = _e31;
+    let _e33 = maskTexCoordI;
+    let _e38 = texel[(_e33.y % 4i)];
+    let _e39 = maskTexCoord_1;
+    coverage = (_e38 + _e39.z);
+    let _e43 = maskCtrl_1;
+    if ((_e43 & 1i) != 0i) {
+        let _e48 = coverage;
+        coverage = abs(_e48);
+    } else {
+        let _e52 = coverage;
+        coverage = (1f - abs((1f - (_e52 - (floor((_e52 / 2f)) * 2f)))));
+    }
+    let _e61 = maskAlpha_1;
+    let _e62 = coverage;
+    return min(_e61, _e62);
+}
+
+fn samplePaintMask(paintMaskTexture: texture_2d<f32>, paintMaskTextureSampler: sampler, paintMaskTextureSize: vec2<f32>, paintMaskTexCoord: vec2<f32>, paintMaskTexRect: vec4<f32>, paintMaskCtrl: i32) -> f32 {
+    var paintMaskTextureSize_1: vec2<f32>;
+    var paintMaskTexCoord_1: vec2<f32>;
+    var paintMaskTexRect_1: vec4<f32>;
+    var paintMaskCtrl_1: i32;
+    var texCoord: vec2<f32>;
+    var texel_1: vec4<f32>;
+    var local_12: vec3<f32>;
+
+    paintMaskTextureSize_1 = paintMaskTextureSize;
+    paintMaskTexCoord_1 = paintMaskTexCoord;
+    paintMaskTexRect_1 = paintMaskTexRect;
+    paintMaskCtrl_1 = paintMaskCtrl;
+    let _e10 = paintMaskTexCoord_1;
+    let _e11 = paintMaskTexRect_1;
+    let _e12 = paintMaskTextureSize_1;
+    let _e13 = wrapColorTexCoord(_e10, _e11, _e12);
+    texCoord = _e13;
+    let _e15 = texCoord;
+    let _e17 = textureSampleLevel(paintMaskTexture, paintMaskTextureSampler, _e15, 0f);
+    texel_1 = _e17;
+    let _e19 = paintMaskCtrl_1;
+    if ((_e19 & 3i) != 2i) {
+        let _e24 = texel_1;
+        return _e24.w;
+    }
+    let _e26 = paintMaskCtrl_1;
+    if ((_e26 & 4i) != 0i) {
+        let _e31 = texel_1;
+        if (_e31.w > 0f) {
+            let _e35 = texel_1;
+            let _e37 = texel_1;
+            local_12 = (_e35.xyz / vec3(_e37.w));
+        } else {
+            local_12 = vec3(0f);
+        }
+        let _e44 = local_12;
+        texel_1.x = _e44.x;
+        texel_1.y = _e44.y;
+        texel_1.z = _e44.z;
+    }
+    let _e51 = texel_1;
+    let _e58 = texel_1;
+    return (dot(_e51.xyz, vec3<f32>(0.2125f, 0.7154f, 0.0721f)) * _e58.w);
+}
+
+fn calculateColor(fragCoord_6: vec2<f32>, colorTexture0_: texture_2d<f32>, colorTexture0Sampler: sampler, maskTexture0_: texture_2d<f32>, maskTexture0Sampler: sampler, paintMaskTexture_1: texture_2d<f32>, paintMaskTextureSampler_1: sampler, destTexture_1: texture_2d<f32>, destTextureSampler_1: sampler, gammaLUT_4: texture_2d<f32>, gammaLUTSampler_4: sampler, colorTextureSize0_: vec2<f32>, maskTextureSize0_: vec2<f32>, paintMaskTextureSize_2: vec2<f32>, filterParams0_8: vec4<f32>, filterParams1_8: vec4<f32>, filterParams2_4: vec4<f32>, framebufferSize_4: vec2<f32>, ctrl: i32, maskTexCoord0_: vec3<f32>, colorTexCoord0_: vec2<f32>, colorTexRect0_: vec4<f32>, paintMaskTexCoord_2: vec2<f32>, paintMaskTexRect_2: vec4<f32>, baseColor: vec4<f32>, tileCtrl: i32) -> vec4<f32> {
+    var fragCoord_7: vec2<f32>;
+    var colorTextureSize0_1: vec2<f32>;
+    var maskTextureSize0_1: vec2<f32>;
+    var paintMaskTextureSize_3: vec2<f32>;
+    var filterParams0_9: vec4<f32>;
+    var filterParams1_9: vec4<f32>;
+    var filterParams2_5: vec4<f32>;
+    var framebufferSize_5: vec2<f32>;
+    var ctrl_1: i32;
+    var maskTexCoord0_1: vec3<f32>;
+    var colorTexCoord0_1: vec2<f32>;
+    var colorTexRect0_1: vec4<f32>;
+    var paintMaskTexCoord_3: vec2<f32>;
+    var paintMaskTexRect_3: vec4<f32>;
+    var baseColor_1: vec4<f32>;
+    var tileCtrl_1: i32;
+    var maskCtrl0_: i32;
+    var maskAlpha_2: f32 = 1f;
+    var paintMaskCtrl_2: i32;
+    var color_2: vec4<f32>;
+    var color0Combine: i32;
+    var color0Filter: i32;
+    var colorTexCoord_18: vec2<f32>;
+    var color0_: vec4<f32>;
+    var compositeOp: i32;
+
+    fragCoord_7 = fragCoord_6;
+    colorTextureSize0_1 = colorTextureSize0_;
+    maskTextureSize0_1 = maskTextureSize0_;
+    paintMaskTextureSize_3 = paintMaskTextureSize_2;
+    filterParams0_9 = filterParams0_8;
+    filterParams1_9 = filterParams1_8;
+    filterParams2_5 = filterParams2_4;
+    framebufferSize_5 = framebufferSize_4;
+    ctrl_1 = ctrl;
+    maskTexCoord0_1 = maskTexCoord0_;
+    colorTexCoord0_1 = colorTexCoord0_;
+    colorTexRect0_1 = colorTexRect0_;
+    paintMaskTexCoord_3 = paintMaskTexCoord_2;
+    paintMaskTexRect_3 = paintMaskTexRect_2;
+    baseColor_1 = baseColor;
+    tileCtrl_1 = tileCtrl;
+    let _e42 = tileCtrl_1;
+    maskCtrl0_ = ((_e42 >> 0u) & 3i);
+    let _e50 = maskAlpha_2;
+    let _e51 = maskTextureSize0_1;
+    let _e52 = maskTexCoord0_1;
+    let _e53 = maskCtrl0_;
+    let _e54 = sampleMask(_e50, maskTexture0_, maskTexture0Sampler, _e51, _e52, _e53);
+    maskAlpha_2 = _e54;
+    let _e55 = ctrl_1;
+    paintMaskCtrl_2 = (_e55 >> 12u);
+    let _e59 = paintMaskCtrl_2;
+    if ((_e59 & 3i) != 0i) {
+        {
+            let _e64 = maskAlpha_2;
+            let _e65 = paintMaskTextureSize_3;
+            let _e66 = paintMaskTexCoord_3;
+            let _e67 = paintMaskTexRect_3;
+            let _e68 = paintMaskCtrl_2;
+            let _e69 = samplePaintMask(paintMaskTexture_1, paintMaskTextureSampler_1, _e65, _e66, _e67, _e68);
+            maskAlpha_2 = (_e64 * _e69);
+        }
+    }
+    let _e71 = baseColor_1;
+    color_2 = _e71;
+    let _e73 = ctrl_1;
+    color0Combine = ((_e73 >> 6u) & 3i);
+    let _e79 = color0Combine;
+    if (_e79 != 0i) {
+        {
+            let _e82 = ctrl_1;
+            color0Filter = ((_e82 >> 4u) & 3i);
+            let _e88 = colorTexCoord0_1;
+            let _e89 = colorTexRect0_1;
+            let _e90 = colorTextureSize0_1;
+            let _e91 = wrapColorTexCoord(_e88, _e89, _e90);
+            colorTexCoord_18 = _e91;
+            let _e93 = colorTexCoord_18;
+            let _e94 = colorTextureSize0_1;
+            let _e95 = fragCoord_7;
+            let _e96 = framebufferSize_5;
+            let _e97 = filterParams0_9;
+            let _e98 = filterParams1_9;
+            let _e99 = filterParams2_5;
+            let _e100 = color0Filter;
+            let _e101 = filterColor(_e93, colorTexture0_, colorTexture0Sampler, gammaLUT_4, gammaLUTSampler_4, _e94, _e95, _e96, _e97, _e98, _e99, _e100);
+            color0_ = _e101;
+            let _e103 = color_2;
+            let _e104 = color0_;
+            let _e105 = color0Combine;
+            let _e106 = combineColor0_(_e103, _e104, _e105);
+            color_2 = _e106;
+        }
+    }
+    let _e108 = color_2;
+    let _e110 = maskAlpha_2;
+    color_2.w = (_e108.w * _e110);
+    let _e112 = ctrl_1;
+    compositeOp = ((_e112 >> 8u) & 15i);
+    let _e118 = color_2;
+    let _e119 = framebufferSize_5;
+    let _e120 = fragCoord_7;
+    let _e121 = compositeOp;
+    let _e122 = composite(_e118, destTexture_1, destTextureSampler_1, _e119, _e120, _e121);
+    color_2 = _e122;
+    let _e123 = color_2;
+    let _e125 = color_2;
+    let _e127 = (_e123.xyz * _e125.w);
+    color_2.x = _e127.x;
+    color_2.y = _e127.y;
+    color_2.z = _e127.z;
+    let _e134 = color_2;
+    return _e134;
+}
+
+fn fetchUnscaled(srcTexture: texture_2d<f32>, srcTextureSampler: sampler, scale: vec2<f32>, originCoord: vec2<f32>, entry: i32) -> vec4<f32> {
+    var scale_1: vec2<f32>;
+    var originCoord_1: vec2<f32>;
+    var entry_1: i32;
+
+    scale_1 = scale;
+    originCoord_1 = originCoord;
+    entry_1 = entry;
+    let _e8 = originCoord_1;
+    let _e12 = entry_1;
+    let _e17 = scale_1;
+    let _e20 = textureSampleLevel(srcTexture, srcTextureSampler, (((_e8 + vec2(0.5f)) + vec2<f32>(f32(_e12), 0f)) * _e17), 0f);
+    return _e20;
+}
+
+fn computeTileVaryings(position: vec2<f32>, colorEntry: i32, textureMetadata: texture_2d<f32>, textureMetadataSampler: sampler, textureMetadataSize: vec2<i32>, outColorTexCoord0_: ptr<function, vec2<f32>>, outColorTexRect0_: ptr<function, vec4<f32>>, outBaseColor: ptr<function, vec4<f32>>, outFilterParams0_: ptr<function, vec4<f32>>, outFilterParams1_: ptr<function, vec4<f32>>, outFilterParams2_: ptr<function, vec4<f32>>, outPaintMaskTexCoord: ptr<function, vec2<f32>>, outPaintMaskTexRect: ptr<function, vec4<f32>>, outCtrl: ptr<function, i32>) {
+    var position_1: vec2<f32>;
+    var colorEntry_1: i32;
+    var textureMetadataSize_1: vec2<i32>;
+    var metadataScale: vec2<f32>;
+    var metadataEntryCoord: vec2<f32>;
+    var colorTexMatrix0_: vec4<f32>;
+    var colorTexOffsets: vec4<f32>;
+    var baseColor_2: vec4<f32>;
+    var filterParams0_10: vec4<f32>;
+    var filterParams1_10: vec4<f32>;
+    var filterParams2_6: vec4<f32>;
+    var extra: vec4<f32>;
+    var colorTexRect0_2: vec4<f32>;
+    var paintMaskMatrix: vec4<f32>;
+    var paintMaskRect: vec4<f32>;
+
+    position_1 = position;
+    colorEntry_1 = colorEntry;
+    textureMetadataSize_1 = textureMetadataSize;
+    let _e19 = textureMetadataSize_1;
+    metadataScale = (vec2(1f) / vec2<f32>(_e19));
+    let _e23 = colorEntry_1;
+    let _e28 = colorEntry_1;
+    metadataEntryCoord = vec2<f32>(f32(((_e23 % 128i) * 10i)), f32((_e28 / 128i)));
+    let _e35 = metadataScale;
+    let _e36 = metadataEntryCoord;
+    let _e38 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e35, _e36, 0i);
+    colorTexMatrix0_ = _e38;
+    let _e40 = metadataScale;
+    let _e41 = metadataEntryCoord;
+    let _e43 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e40, _e41, 1i);
+    colorTexOffsets = _e43;
+    let _e45 = metadataScale;
+    let _e46 = metadataEntryCoord;
+    let _e48 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e45, _e46, 2i);
+    baseColor_2 = _e48;
+    let _e50 = metadataScale;
+    let _e51 = metadataEntryCoord;
+    let _e53 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e50, _e51, 3i);
+    filterParams0_10 = _e53;
+    let _e55 = metadataScale;
+    let _e56 = metadataEntryCoord;
+    let _e58 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e55, _e56, 4i);
+    filterParams1_10 = _e58;
+    let _e60 = metadataScale;
+    let _e61 = metadataEntryCoord;
+    let _e63 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e60, _e61, 5i);
+    filterParams2_6 = _e63;
+    let _e65 = metadataScale;
+    let _e66 = metadataEntryCoord;
+    let _e68 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e65, _e66, 6i);
+    extra = _e68;
+    let _e70 = metadataScale;
+    let _e71 = metadataEntryCoord;
+    let _e73 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e70, _e71, 7i);
+    colorTexRect0_2 = _e73;
+    let _e75 = metadataScale;
+    let _e76 = metadataEntryCoord;
+    let _e78 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e75, _e76, 8i);
+    paintMaskMatrix = _e78;
+    let _e80 = metadataScale;
+    let _e81 = metadataEntryCoord;
+    let _e83 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e80, _e81, 9i);
+    paintMaskRect = _e83;
+    let _e85 = colorTexMatrix0_;
+    let _e86 = _e85.xy;
+    let _e87 = colorTexMatrix0_;
+    let _e88 = _e87.zw;
+    let _e96 = position_1;
+    let _e98 = colorTexOffsets;
+    (*outColorTexCoord0_) = ((mat2x2<f32>(vec2<f32>(_e86.x, _e86.y), vec2<f32>(_e88.x, _e88.y)) * _e96) + _e98.xy);
+    let _e101 = colorTexRect0_2;
+    (*outColorTexRect0_) = _e101;
+    let _e102 = paintMaskMatrix;
+    let _e103 = _e102.xy;
+    let _e104 = paintMaskMatrix;
+    let _e105 = _e104.zw;
+    let _e113 = position_1;
+    let _e115 = colorTexOffsets;
+    (*outPaintMaskTexCoord) = ((mat2x2<f32>(vec2<f32>(_e103.x, _e103.y), vec2<f32>(_e105.x, _e105.y)) * _e113) + _e115.zw);
+    let _e118 = paintMaskRect;
+    (*outPaintMaskTexRect) = _e118;
+    let _e119 = baseColor_2;
+    (*outBaseColor) = _e119;
+    let _e120 = filterParams0_10;
+    (*outFilterParams0_) = _e120;
+    let _e121 = filterParams1_10;
+    (*outFilterParams1_) = _e121;
+    let _e122 = filterParams2_6;
+    (*outFilterParams2_) = _e122;
+    let _e123 = extra;
+    (*outCtrl) = i32(_e123.x);
+    return;
+}
+
+fn calculateTileIndex(bufferOffset: u32, tileRect: vec4<u32>, tileCoord: vec2<u32>) -> u32 {
+    var bufferOffset_1: u32;
+    var tileRect_1: vec4<u32>;
+    var tileCoord_1: vec2<u32>;
+
+    bufferOffset_1 = bufferOffset;
+    tileRect_1 = tileRect;
+    tileCoord_1 = tileCoord;
+    let _e6 = bufferOffset_1;
+    let _e7 = tileCoord_1;
+    let _e9 = tileRect_1;
+    let _e11 = tileRect_1;
+    let _e16 = tileCoord_1;
+    return ((_e6 + (_e7.y * (_e9.z - _e11.x))) + _e16.x);
+}
+
+fn toImageCoords(coords: vec2<i32>) -> vec2<i32> {
+    var coords_1: vec2<i32>;
+
+    coords_1 = coords;
+    let _e4 = coords_1;
+    let _e6 = global.uFramebufferSize;
+    let _e8 = coords_1;
+    return vec2<i32>(_e4.x, i32((_e6.y - f32(_e8.y))));
+}
+
+fn main_1() {
+    var tileCoord_2: vec2<i32>;
+    var firstTileSubCoord: vec2<i32>;
+    var firstFragCoord: vec2<i32>;
+    var tileIndex: i32;
+    var destColors: mat4x4<f32>;
+    var subY: i32 = 0i;
+    var imageCoords: vec2<i32>;
+    var subY_1: i32;
+    var tileSubCoord: vec2<i32>;
+    var fragCoord_8: vec2<f32>;
+    var alphaTileIndex: i32;
+    var tileControlWord: u32;
+    var colorEntry_2: u32;
+    var tileCtrl_2: i32;
+    var backdrop: i32;
+    var maskTileCoord: vec2<u32>;
+    var maskTexCoord0_2: vec3<f32>;
+    var colorTexCoord0_2: vec2<f32>;
+    var colorTexRect0_3: vec4<f32>;
+    var baseColor_3: vec4<f32>;
+    var filterParams0_11: vec4<f32>;
+    var filterParams1_11: vec4<f32>;
+    var filterParams2_7: vec4<f32>;
+    var paintMaskTexCoord_4: vec2<f32>;
+    var paintMaskTexRect_4: vec4<f32>;
+    var ctrl_2: i32;
+    var srcColor_16: vec4<f32>;
+    var subY_2: i32 = 0i;
+
+    let _e36 = gl_WorkGroupID;
+    tileCoord_2 = vec2<i32>(_e36.xy);
+    let _e41 = gl_LocalInvocationID;
+    firstTileSubCoord = (vec2<i32>(_e41.xy) * vec2<i32>(1i, 4i));
+    let _e49 = tileCoord_2;
+    let _e50 = global.uTileSize;
+    let _e53 = firstTileSubCoord;
+    firstFragCoord = ((_e49 * vec2<i32>(_e50)) + _e53);
+    let _e56 = tileCoord_2;
+    let _e58 = global.uFramebufferTileSize;
+    let _e60 = tileCoord_2;
+    let _e65 = global_2.iFirstTileMap[(_e56.x + (_e58.x * _e60.y))];
+    tileIndex = _e65;
+    let _e67 = tileIndex;
+    let _e70 = global.uLoadAction;
+    if ((_e67 < 0i) && (_e70 != 0i)) {
+        return;
+    }
+    loop {
+        let _e77 = subY;
+        if !((_e77 < 4i)) {
+            break;
+        }
+        {
+            let _e84 = global.uLoadAction;
+            if (_e84 == 0i) {
+                {
+                    let _e87 = subY;
+                    let _e89 = global.uClearColor;
+                    destColors[_e87] = _e89;
+                }
+            } else {
+                {
+                    let _e90 = firstFragCoord;
+                    let _e92 = subY;
+                    let _e95 = toImageCoords((_e90 + vec2<i32>(0i, _e92)));
+                    imageCoords = _e95;
+                    let _e97 = subY;
+                    let _e99 = imageCoords;
+                    let _e100 = textureLoad(uDestImage, _e99);
+                    destColors[_e97] = _e100;
+                }
+            }
+        }
+        continuing {
+            let _e81 = subY;
+            subY = (_e81 + 1i);
+        }
+    }
+    loop {
+        let _e101 = tileIndex;
+        if !((_e101 >= 0i)) {
+            break;
+        }
+        {
+            subY_1 = 0i;
+            loop {
+                let _e107 = subY_1;
+                if !((_e107 < 4i)) {
+                    break;
+                }
+                {
+                    let _e114 = firstTileSubCoord;
+                    let _e116 = subY_1;
+                    tileSubCoord = (_e114 + vec2<i32>(0i, _e116));
+                    let _e120 = firstFragCoord;
+                    let _e122 = subY_1;
+                    fragCoord_8 = (vec2<f32>((_e120 + vec2<i32>(0i, _e122))) + vec2(0.5f));
+                    let _e130 = tileIndex;
+                    let _e136 = global_1.iTiles[((_e130 * 4i) + 2i)];
+                    alphaTileIndex = (i32((_e136 << 8u)) >> 8u);
+                    let _e143 = tileIndex;
+                    let _e149 = global_1.iTiles[((_e143 * 4i) + 3i)];
+                    tileControlWord = _e149;
+                    let _e151 = tileControlWord;
+                    colorEntry_2 = (_e151 & 65535u);
+                    let _e155 = tileControlWord;
+                    tileCtrl_2 = i32(((_e155 >> 16u) & 255u));
+                    let _e164 = alphaTileIndex;
+                    if (_e164 >= 0i) {
+                        {
+                            backdrop = 0i;
+                            let _e168 = alphaTileIndex;
+                            let _e171 = alphaTileIndex;
+                            let _e177 = global.uTileSize;
+                            maskTileCoord = (vec2<u32>(u32((_e168 & 255i)), u32((_e171 >> 8u))) * vec2<u32>(_e177));
+                        }
+                    } else {
+                        {
+                            let _e180 = tileControlWord;
+                            backdrop = (i32(_e180) >> 24u);
+                            maskTileCoord = vec2(0u);
+                            let _e186 = tileCtrl_2;
+                            tileCtrl_2 = (_e186 & -4i);
+                        }
+                    }
+                    let _e189 = maskTileCoord;
+                    let _e191 = tileSubCoord;
+                    let _e193 = vec2<f32>((vec2<i32>(_e189) + _e191));
+                    let _e194 = backdrop;
+                    maskTexCoord0_2 = vec3<f32>(_e193.x, _e193.y, f32(_e194));
+                    let _e209 = fragCoord_8;
+                    let _e210 = colorEntry_2;
+                    let _e212 = global.uTextureMetadataSize;
+                    computeTileVaryings(_e209, i32(_e210), uTextureMetadata, uTextureMetadataSampler, _e212, (&colorTexCoord0_2), (&colorTexRect0_3), (&baseColor_3), (&filterParams0_11), (&filterParams1_11), (&filterParams2_7), (&paintMaskTexCoord_4), (&paintMaskTexRect_4), (&ctrl_2));
+                    let _e213 = fragCoord_8;
+                    let _e214 = global.uColorTextureSize0_;
+                    let _e215 = global.uMaskTextureSize0_;
+                    let _e216 = global.uPaintMaskTextureSize;
+                    let _e217 = filterParams0_11;
+                    let _e218 = filterParams1_11;
+                    let _e219 = filterParams2_7;
+                    let _e220 = global.uFramebufferSize;
+                    let _e221 = ctrl_2;
+                    let _e222 = maskTexCoord0_2;
+                    let _e223 = colorTexCoord0_2;
+                    let _e224 = colorTexRect0_3;
+                    let _e225 = paintMaskTexCoord_4;
+                    let _e226 = paintMaskTexRect_4;
+                    let _e227 = baseColor_3;
+                    let _e228 = tileCtrl_2;
+                    let _e229 = calculateColor(_e213, uColorTexture0_, uColorTexture0Sampler, uMaskTexture0_, uMaskTexture0Sampler, uPaintMaskTexture, uPaintMaskTextureSampler, uDestTexture, uDestTextureSampler, uGammaLUT, uGammaLUTSampler, _e214, _e215, _e216, _e217, _e218, _e219, _e220, _e221, _e222, _e223, _e224, _e225, _e226, _e227, _e228);
+                    srcColor_16 = _e229;
+                    let _e231 = subY_1;
+                    let _e233 = subY_1;
+                    let _e235 = destColors[_e233];
+                    let _e237 = srcColor_16;
+                    let _e241 = srcColor_16;
+                    destColors[_e231] = ((_e235 * (1f - _e237.w)) + _e241);
+                }
+                continuing {
+                    let _e111 = subY_1;
+                    subY_1 = (_e111 + 1i);
+                }
+            }
+            let _e243 = tileIndex;
+            let _e249 = global_1.iTiles[((_e243 * 4i) + 0i)];
+            tileIndex = i32(_e249);
+        }
+    }
+    loop {
+        let _e253 = subY_2;
+        if !((_e253 < 4i)) {
+            break;
+        }
+        let _e260 = firstFragCoord;
+        let _e262 = subY_2;
+        let _e265 = toImageCoords((_e260 + vec2<i32>(0i, _e262)));
+        let _e266 = firstFragCoord;
+        let _e268 = subY_2;
+        let _e271 = toImageCoords((_e266 + vec2<i32>(0i, _e268)));
+        let _e272 = subY_2;
+        let _e274 = destColors[_e272];
+        textureStore(uDestImage, _e271, _e274);
+        continuing {
+            let _e257 = subY_2;
+            subY_2 = (_e257 + 1i);
+        }
+    }
+    return;
+}
+
+@compute @workgroup_size(16, 4, 1) 
+fn main(@builtin(workgroup_id) param: vec3<u32>, @builtin(local_invocation_id) param_1: vec3<u32>) {
+    gl_WorkGroupID = param;
+    gl_LocalInvocationID = param_1;
+    main_1();
+    return;
+}
@@ -0,0 +1,211 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uSampleCount: i32,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uAreaLUT: texture_2d<f32>;
+@group(1) @binding(1) 
+var uAreaLUTSampler: sampler;
+@group(1) @binding(2) 
+var<uniform> global: bUniforms;
+var<private> vFrom_1: vec2<f32>;
+var<private> vTo_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn computeCoverage(from_: vec2<f32>, to: vec2<f32>, areaLUT: texture_2d<f32>, areaLUTSampler: sampler) -> vec4<f32> {
+    var from_1: vec2<f32>;
+    var to_1: vec2<f32>;
+    var local: vec2<f32>;
+    var left: vec2<f32>;
+    var local_1: vec2<f32>;
+    var right: vec2<f32>;
+    var window: vec2<f32>;
+    var offset: f32;
+    var t: f32;
+    var y: f32;
+    var d: f32;
+    var dX: f32;
+
+    from_1 = from_;
+    to_1 = to;
+    let _e6 = from_1;
+    let _e8 = to_1;
+    if (_e6.x < _e8.x) {
+        let _e11 = from_1;
+        local = _e11;
+    } else {
+        let _e12 = to_1;
+        local = _e12;
+    }
+    let _e14 = local;
+    left = _e14;
+    let _e16 = from_1;
+    let _e18 = to_1;
+    if (_e16.x < _e18.x) {
+        let _e21 = to_1;
+        local_1 = _e21;
+    } else {
+        let _e22 = from_1;
+        local_1 = _e22;
+    }
+    let _e24 = local_1;
+    right = _e24;
+    let _e26 = from_1;
+    let _e28 = to_1;
+    window = clamp(vec2<f32>(_e26.x, _e28.x), vec2(-0.5f), vec2(0.5f));
+    let _e37 = window;
+    let _e39 = window;
+    let _e43 = left;
+    offset = (mix(_e37.x, _e39.y, 0.5f) - _e43.x);
+    let _e47 = offset;
+    let _e48 = right;
+    let _e50 = left;
+    t = (_e47 / (_e48.x - _e50.x));
+    let _e55 = left;
+    let _e57 = right;
+    let _e59 = t;
+    y = mix(_e55.y, _e57.y, _e59);
+    let _e62 = right;
+    let _e64 = left;
+    let _e67 = right;
+    let _e69 = left;
+    d = ((_e62.y - _e64.y) / (_e67.x - _e69.x));
+    let _e74 = window;
+    let _e76 = window;
+    dX = (_e74.x - _e76.y);
+    let _e80 = y;
+    let _e83 = d;
+    let _e84 = dX;
+    let _e91 = textureSample(areaLUT, areaLUTSampler, (vec2<f32>((_e80 + 8f), abs((_e83 * _e84))) / vec2(16f)));
+    let _e92 = dX;
+    return (_e91 * _e92);
+}
+
+fn computeSampleCoverage(from_2: vec2<f32>, to_2: vec2<f32>, sampleOffset: vec2<f32>) -> vec4<f32> {
+    var from_3: vec2<f32>;
+    var to_3: vec2<f32>;
+    var sampleOffset_1: vec2<f32>;
+    var y_1: f32;
+
+    from_3 = from_2;
+    to_3 = to_2;
+    sampleOffset_1 = sampleOffset;
+    let _e6 = from_3;
+    let _e7 = sampleOffset_1;
+    from_3 = (_e6 - _e7);
+    let _e9 = to_3;
+    let _e10 = sampleOffset_1;
+    to_3 = (_e9 - _e10);
+    let _e12 = from_3;
+    let _e16 = to_3;
+    if ((_e12.x <= 0f) == (_e16.x <= 0f)) {
+        return vec4(0f);
+    }
+    let _e23 = from_3;
+    let _e25 = to_3;
+    let _e27 = from_3;
+    let _e29 = from_3;
+    let _e31 = to_3;
+    y_1 = mix(_e23.y, _e25.y, (_e27.x / (_e29.x - _e31.x)));
+    let _e37 = y_1;
+    let _e45 = from_3;
+    let _e47 = to_3;
+    return (step(vec4(_e37), vec4<f32>(0f, 1f, 2f, 3f)) * sign((_e45.x - _e47.x)));
+}
+
+fn computeSupersampledCoverage(from_4: vec2<f32>, to_4: vec2<f32>, sampleCount: i32) -> vec4<f32> {
+    var from_5: vec2<f32>;
+    var to_5: vec2<f32>;
+    var sampleCount_1: i32;
+    var coverage: vec4<f32> = vec4(0f);
+    var sampleIndex: i32 = 0i;
+    var sampleRow: i32;
+    var bit: i32;
+    var local_2: i32;
+    var sampleOffset_2: vec2<f32>;
+
+    from_5 = from_4;
+    to_5 = to_4;
+    sampleCount_1 = sampleCount;
+    loop {
+        let _e11 = sampleIndex;
+        let _e12 = sampleCount_1;
+        if !((_e11 < _e12)) {
+            break;
+        }
+        {
+            sampleRow = 0i;
+            bit = 1i;
+            loop {
+                let _e22 = bit;
+                let _e23 = sampleCount_1;
+                if !((_e22 < _e23)) {
+                    break;
+                }
+                let _e29 = sampleRow;
+                let _e32 = sampleIndex;
+                let _e33 = bit;
+                if ((_e32 & _e33) != 0i) {
+                    local_2 = 1i;
+                } else {
+                    local_2 = 0i;
+                }
+                let _e40 = local_2;
+                sampleRow = ((_e29 << 1u) | _e40);
+                continuing {
+                    let _e26 = bit;
+                    bit = (_e26 << 1u);
+                }
+            }
+            let _e42 = sampleIndex;
+            let _e43 = sampleRow;
+            let _e50 = sampleCount_1;
+            sampleOffset_2 = (((vec2<f32>(f32(_e42), f32(_e43)) + vec2(0.5f)) / vec2(f32(_e50))) - vec2(0.5f));
+            let _e58 = coverage;
+            let _e59 = from_5;
+            let _e60 = to_5;
+            let _e61 = sampleOffset_2;
+            let _e62 = computeSampleCoverage(_e59, _e60, _e61);
+            coverage = (_e58 + _e62);
+        }
+        continuing {
+            let _e15 = sampleIndex;
+            sampleIndex = (_e15 + 1i);
+        }
+    }
+    let _e64 = coverage;
+    let _e65 = sampleCount_1;
+    return (_e64 / vec4(f32(_e65)));
+}
+
+fn main_1() {
+    let _e7 = global.uSampleCount;
+    if (_e7 == 0i) {
+        let _e10 = vFrom_1;
+        let _e11 = vTo_1;
+        let _e12 = computeCoverage(_e10, _e11, uAreaLUT, uAreaLUTSampler);
+        oFragColor = _e12;
+        return;
+    } else {
+        let _e13 = vFrom_1;
+        let _e14 = vTo_1;
+        let _e15 = global.uSampleCount;
+        let _e16 = computeSupersampledCoverage(_e13, _e14, _e15);
+        oFragColor = _e16;
+        return;
+    }
+}
+
+@fragment 
+fn main(@location(0) vFrom: vec2<f32>, @location(1) vTo: vec2<f32>) -> FragmentOutput {
+    vFrom_1 = vFrom;
+    vTo_1 = vTo;
+    main_1();
+    let _e5 = oFragColor;
+    return FragmentOutput(_e5);
+}
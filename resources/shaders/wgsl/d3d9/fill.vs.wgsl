@@ -0,0 +1,142 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+    uTileSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vFrom: vec2<f32>,
+    @location(1) vTo: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aTessCoord_1: vec2<u32>;
+var<private> aLineSegment_1: vec4<u32>;
+var<private> aTileIndex_1: i32;
+var<private> vFrom: vec2<f32>;
+var<private> vTo: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn computeTileOffset(tileIndex: u32, stencilTextureWidth: f32, tileSize: vec2<f32>) -> vec2<f32> {
+    var tileIndex_1: u32;
+    var stencilTextureWidth_1: f32;
+    var tileSize_1: vec2<f32>;
+    var tilesPerRow: u32;
+    var tileOffset: vec2<u32>;
+
+    tileIndex_1 = tileIndex;
+    stencilTextureWidth_1 = stencilTextureWidth;
+    tileSize_1 = tileSize;
+    let _e6 = stencilTextureWidth_1;
+    let _e7 = tileSize_1;
+    tilesPerRow = u32((_e6 / _e7.x));
+    let _e12 = tileIndex_1;
+    let _e13 = tilesPerRow;
+    let _e15 = tileIndex_1;
+    let _e16 = tilesPerRow;
+    tileOffset = vec2<u32>((_e12 % _e13), (_e15 / _e16));
+    let _e20 = tileOffset;
+    let _e22 = tileSize_1;
+    return ((vec2<f32>(_e20) * _e22) * vec2<f32>(1f, 0.25f));
+}
+
+fn computeVertexPosition(tileIndex_2: u32, tessCoord: vec2<u32>, packedLineSegment: vec4<u32>, tileSize_2: vec2<f32>, framebufferSize: vec2<f32>, outFrom: ptr<function, vec2<f32>>, outTo: ptr<function, vec2<f32>>) -> vec4<f32> {
+    var tileIndex_3: u32;
+    var tessCoord_1: vec2<u32>;
+    var packedLineSegment_1: vec4<u32>;
+    var tileSize_3: vec2<f32>;
+    var framebufferSize_1: vec2<f32>;
+    var tileOrigin: vec2<f32>;
+    var lineSegment: vec4<f32>;
+    var from_: vec2<f32>;
+    var to: vec2<f32>;
+    var position: vec2<f32>;
+    var offset: vec2<f32>;
+    var globalPosition: vec2<f32>;
+
+    tileIndex_3 = tileIndex_2;
+    tessCoord_1 = tessCoord;
+    packedLineSegment_1 = packedLineSegment;
+    tileSize_3 = tileSize_2;
+    framebufferSize_1 = framebufferSize;
+    let _e12 = tileIndex_3;
+    let _e14 = framebufferSize_1;
+    let _e16 = tileSize_3;
+    let _e17 = computeTileOffset(u32(_e12), _e14.x, _e16);
+    tileOrigin = _e17;
+    let _e19 = packedLineSegment_1;
+    lineSegment = (vec4<f32>(_e19) / vec4(256f));
+    let _e25 = lineSegment;
+    from_ = _e25.xy;
+    let _e28 = lineSegment;
+    to = _e28.zw;
+    let _e32 = tessCoord_1;
+    if (_e32.x == 0u) {
+        let _e37 = from_;
+        let _e39 = to;
+        position.x = floor(min(_e37.x, _e39.x));
+    } else {
+        let _e44 = from_;
+        let _e46 = to;
+        position.x = ceil(max(_e44.x, _e46.x));
+    }
+    let _e50 = tessCoord_1;
+    if (_e50.y == 0u) {
+        let _e55 = from_;
+        let _e57 = to;
+        position.y = floor(min(_e55.y, _e57.y));
+    } else {
+        let _e62 = tileSize_3;
+        position.y = _e62.y;
+    }
+    let _e65 = position;
+    position.y = floor((_e65.y * 0.25f));
+    let _e73 = position;
+    offset = (vec2<f32>(0f, 1.5f) - (_e73 * vec2<f32>(1f, 4f)));
+    let _e80 = from_;
+    let _e81 = offset;
+    (*outFrom) = (_e80 + _e81);
+    let _e83 = to;
+    let _e84 = offset;
+    (*outTo) = (_e83 + _e84);
+    let _e86 = tileOrigin;
+    let _e87 = position;
+    let _e89 = framebufferSize_1;
+    globalPosition = ((((_e86 + _e87) / _e89) * 2f) - vec2(1f));
+    let _e98 = globalPosition;
+    globalPosition.y = -(_e98.y);
+    let _e101 = globalPosition;
+    return vec4<f32>(_e101.x, _e101.y, 0f, 1f);
+}
+
+fn main_1() {
+    var local: vec2<f32>;
+    var local_1: vec2<f32>;
+
+    let _e10 = aTileIndex_1;
+    let _e12 = aTessCoord_1;
+    let _e13 = aLineSegment_1;
+    let _e14 = global.uTileSize;
+    let _e15 = global.uFramebufferSize;
+    let _e18 = computeVertexPosition(u32(_e10), _e12, _e13, _e14, _e15, (&local), (&local_1));
+    let _e19 = local;
+    vFrom = _e19;
+    let _e20 = local_1;
+    vTo = _e20;
+    gl_Position = _e18;
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aTessCoord: vec2<u32>, @location(1) @interpolate(flat) aLineSegment: vec4<u32>, @location(2) @interpolate(flat) aTileIndex: i32) -> VertexOutput {
+    aTessCoord_1 = aTessCoord;
+    aLineSegment_1 = aLineSegment;
+    aTileIndex_1 = aTileIndex;
+    main_1();
+    let _e7 = vFrom;
+    let _e9 = vTo;
+    let _e11 = gl_Position;
+    return VertexOutput(_e7, _e9, _e11);
+}
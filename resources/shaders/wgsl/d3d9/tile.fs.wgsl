@@ -0,0 +1,1277 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uColorTextureSize0_: vec2<f32>,
+    uMaskTextureSize0_: vec2<f32>,
+    uPaintMaskTextureSize: vec2<f32>,
+    uFramebufferSize: vec2<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uColorTexture0_: texture_2d<f32>;
+@group(1) @binding(1) 
+var uColorTexture0Sampler: sampler;
+@group(1) @binding(2) 
+var uMaskTexture0_: texture_2d<f32>;
+@group(1) @binding(3) 
+var uMaskTexture0Sampler: sampler;
+@group(1) @binding(4) 
+var uPaintMaskTexture: texture_2d<f32>;
+@group(1) @binding(5) 
+var uPaintMaskTextureSampler: sampler;
+@group(1) @binding(6) 
+var uDestTexture: texture_2d<f32>;
+@group(1) @binding(7) 
+var uDestTextureSampler: sampler;
+@group(1) @binding(8) 
+var uGammaLUT: texture_2d<f32>;
+@group(1) @binding(9) 
+var uGammaLUTSampler: sampler;
+@group(1) @binding(10) 
+var<uniform> global: bUniforms;
+var<private> vMaskTexCoord0_1: vec3<f32>;
+var<private> vColorTexCoord0_1: vec2<f32>;
+var<private> vColorTexRect0_1: vec4<f32>;
+var<private> vBaseColor_1: vec4<f32>;
+var<private> vTileCtrl_1: f32;
+var<private> vFilterParams0_1: vec4<f32>;
+var<private> vFilterParams1_1: vec4<f32>;
+var<private> vFilterParams2_1: vec4<f32>;
+var<private> vPaintMaskTexCoord_1: vec2<f32>;
+var<private> vPaintMaskTexRect_1: vec4<f32>;
+var<private> vCtrl_1: f32;
+var<private> oFragColor: vec4<f32>;
+var<private> gl_FragCoord: vec4<f32>;
+
+fn sampleColor(colorTexture: texture_2d<f32>, colorTextureSampler: sampler, colorTexCoord: vec2<f32>) -> vec4<f32> {
+    var colorTexCoord_1: vec2<f32>;
+
+    colorTexCoord_1 = colorTexCoord;
+    let _e4 = colorTexCoord_1;
+    let _e5 = textureSample(colorTexture, colorTextureSampler, _e4);
+    return _e5;
+}
+
+fn wrapColorTexCoord(colorTexCoord_2: vec2<f32>, colorTexRect: vec4<f32>, colorTextureSize: vec2<f32>) -> vec2<f32> {
+    var colorTexCoord_3: vec2<f32>;
+    var colorTexRect_1: vec4<f32>;
+    var colorTextureSize_1: vec2<f32>;
+    var size: vec2<f32>;
+    var texRect: vec4<f32>;
+    var offset: vec2<f32>;
+    var halfTexel: vec2<f32>;
+
+    colorTexCoord_3 = colorTexCoord_2;
+    colorTexRect_1 = colorTexRect;
+    colorTextureSize_1 = colorTextureSize;
+    let _e6 = colorTexRect_1;
+    size = abs(_e6.zw);
+    let _e10 = size;
+    let _e14 = size;
+    if ((_e10.x == 0f) || (_e14.y == 0f)) {
+        let _e19 = colorTexCoord_3;
+        return _e19;
+    }
+    let _e20 = colorTexRect_1;
+    let _e21 = colorTextureSize_1;
+    texRect = (_e20 / _e21.xyxy);
+    let _e25 = size;
+    let _e26 = colorTextureSize_1;
+    size = (_e25 / _e26);
+    let _e28 = colorTexCoord_3;
+    let _e29 = texRect;
+    offset = (_e28 - _e29.xy);
+    let _e33 = offset;
+    let _e34 = offset;
+    let _e35 = size;
+    let _e40 = colorTexRect_1;
+    offset = select(_e33, (_e34 - (floor((_e34 / _e35)) * _e35)), (_e40.zw > vec2(0f)));
+    let _e48 = colorTextureSize_1;
+    halfTexel = (vec2(0.5f) / _e48);
+    let _e51 = texRect;
+    let _e53 = offset;
+    let _e54 = halfTexel;
+    let _e55 = size;
+    let _e56 = halfTexel;
+    return (_e51.xy + clamp(_e53, _e54, (_e55 - _e56)));
+}
+
+fn combineColor0_(destColor: vec4<f32>, srcColor: vec4<f32>, op: i32) -> vec4<f32> {
+    var destColor_1: vec4<f32>;
+    var srcColor_1: vec4<f32>;
+    var op_1: i32;
+
+    destColor_1 = destColor;
+    srcColor_1 = srcColor;
+    op_1 = op;
+    let _e6 = op_1;
+    switch _e6 {
+        case 1: {
+            let _e7 = srcColor_1;
+            let _e8 = _e7.xyz;
+            let _e9 = srcColor_1;
+            let _e11 = destColor_1;
+            return vec4<f32>(_e8.x, _e8.y, _e8.z, (_e9.w * _e11.w));
+        }
+        case 2: {
+            let _e18 = destColor_1;
+            let _e19 = _e18.xyz;
+            let _e20 = srcColor_1;
+            let _e22 = destColor_1;
+            return vec4<f32>(_e19.x, _e19.y, _e19.z, (_e20.w * _e22.w));
+        }
+        default: {
+        }
+    }
+    let _e29 = destColor_1;
+    return _e29;
+}
+
+fn filterTextSample1Tap(offset_1: vec2<f32>, colorTexture_1: texture_2d<f32>, colorTextureSampler_1: sampler, colorTexCoord_4: vec2<f32>) -> f32 {
+    var offset_2: vec2<f32>;
+    var colorTexCoord_5: vec2<f32>;
+
+    offset_2 = offset_1;
+    colorTexCoord_5 = colorTexCoord_4;
+    let _e6 = colorTexCoord_5;
+    let _e7 = offset_2;
+    let _e9 = textureSample(colorTexture_1, colorTextureSampler_1, (_e6 + _e7));
+    return _e9.x;
+}
+
+fn filterTextSample9Tap(outAlphaLeft: ptr<function, vec4<f32>>, outAlphaCenter: ptr<function, f32>, outAlphaRight: ptr<function, vec4<f32>>, colorTexture_2: texture_2d<f32>, colorTextureSampler_2: sampler, colorTexCoord_6: vec2<f32>, kernel: vec4<f32>, onePixel: vec2<f32>) {
+    var colorTexCoord_7: vec2<f32>;
+    var kernel_1: vec4<f32>;
+    var onePixel_1: vec2<f32>;
+    var wide: bool;
+    var local: f32;
+    var local_1: f32;
+
+    colorTexCoord_7 = colorTexCoord_6;
+    kernel_1 = kernel;
+    onePixel_1 = onePixel;
+    let _e11 = kernel_1;
+    wide = (_e11.x > 0f);
+    let _e16 = wide;
+    if _e16 {
+        let _e18 = onePixel_1;
+        let _e20 = colorTexCoord_7;
+        let _e21 = filterTextSample1Tap((-4f * _e18), colorTexture_2, colorTextureSampler_2, _e20);
+        local = _e21;
+    } else {
+        local = 0f;
+    }
+    let _e24 = local;
+    let _e26 = onePixel_1;
+    let _e28 = colorTexCoord_7;
+    let _e29 = filterTextSample1Tap((-3f * _e26), colorTexture_2, colorTextureSampler_2, _e28);
+    let _e31 = onePixel_1;
+    let _e33 = colorTexCoord_7;
+    let _e34 = filterTextSample1Tap((-2f * _e31), colorTexture_2, colorTextureSampler_2, _e33);
+    let _e36 = onePixel_1;
+    let _e38 = colorTexCoord_7;
+    let _e39 = filterTextSample1Tap((-1f * _e36), colorTexture_2, colorTextureSampler_2, _e38);
+    (*outAlphaLeft) = vec4<f32>(_e24, _e29, _e34, _e39);
+    let _e43 = colorTexCoord_7;
+    let _e44 = filterTextSample1Tap(vec2(0f), colorTexture_2, colorTextureSampler_2, _e43);
+    (*outAlphaCenter) = _e44;
+    let _e46 = onePixel_1;
+    let _e48 = colorTexCoord_7;
+    let _e49 = filterTextSample1Tap((1f * _e46), colorTexture_2, colorTextureSampler_2, _e48);
+    let _e51 = onePixel_1;
+    let _e53 = colorTexCoord_7;
+    let _e54 = filterTextSample1Tap((2f * _e51), colorTexture_2, colorTextureSampler_2, _e53);
+    let _e56 = onePixel_1;
+    let _e58 = colorTexCoord_7;
+    let _e59 = filterTextSample1Tap((3f * _e56), colorTexture_2, colorTextureSampler_2, _e58);
+    let _e60 = wide;
+    if _e60 {
+        let _e62 = onePixel_1;
+        let _e64 = colorTexCoord_7;
+        let _e65 = filterTextSample1Tap((4f * _e62), colorTexture_2, colorTextureSampler_2, _e64);
+        local_1 = _e65;
+    } else {
+        local_1 = 0f;
+    }
+    let _e68 = local_1;
+    (*outAlphaRight) = vec4<f32>(_e49, _e54, _e59, _e68);
+    return;
+}
+
+fn filterTextConvolve7Tap(alpha0_: vec4<f32>, alpha1_: vec3<f32>, kernel_2: vec4<f32>) -> f32 {
+    var alpha0_1: vec4<f32>;
+    var alpha1_1: vec3<f32>;
+    var kernel_3: vec4<f32>;
+
+    alpha0_1 = alpha0_;
+    alpha1_1 = alpha1_;
+    kernel_3 = kernel_2;
+    let _e6 = alpha0_1;
+    let _e7 = kernel_3;
+    let _e9 = alpha1_1;
+    let _e10 = kernel_3;
+    return (dot(_e6, _e7) + dot(_e9, _e10.zyx));
+}
+
+fn filterTextGammaCorrectChannel(bgColor: f32, fgColor: f32, gammaLUT: texture_2d<f32>, gammaLUTSampler: sampler) -> f32 {
+    var bgColor_1: f32;
+    var fgColor_1: f32;
+
+    bgColor_1 = bgColor;
+    fgColor_1 = fgColor;
+    let _e6 = fgColor_1;
+    let _e8 = bgColor_1;
+    let _e11 = textureSample(gammaLUT, gammaLUTSampler, vec2<f32>(_e6, (1f - _e8)));
+    return _e11.x;
+}
+
+fn filterTextGammaCorrect(bgColor_2: vec3<f32>, fgColor_2: vec3<f32>, gammaLUT_1: texture_2d<f32>, gammaLUTSampler_1: sampler) -> vec3<f32> {
+    var bgColor_3: vec3<f32>;
+    var fgColor_3: vec3<f32>;
+
+    bgColor_3 = bgColor_2;
+    fgColor_3 = fgColor_2;
+    let _e6 = bgColor_3;
+    let _e8 = fgColor_3;
+    let _e10 = filterTextGammaCorrectChannel(_e6.x, _e8.x, gammaLUT_1, gammaLUTSampler_1);
+    let _e11 = bgColor_3;
+    let _e13 = fgColor_3;
+    let _e15 = filterTextGammaCorrectChannel(_e11.y, _e13.y, gammaLUT_1, gammaLUTSampler_1);
+    let _e16 = bgColor_3;
+    let _e18 = fgColor_3;
+    let _e20 = filterTextGammaCorrectChannel(_e16.z, _e18.z, gammaLUT_1, gammaLUTSampler_1);
+    return vec3<f32>(_e10, _e15, _e20);
+}
+
+fn filterText(colorTexCoord_8: vec2<f32>, colorTexture_3: texture_2d<f32>, colorTextureSampler_3: sampler, gammaLUT_2: texture_2d<f32>, gammaLUTSampler_2: sampler, colorTextureSize_2: vec2<f32>, filterParams0_: vec4<f32>, filterParams1_: vec4<f32>, filterParams2_: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_9: vec2<f32>;
+    var colorTextureSize_3: vec2<f32>;
+    var filterParams0_1: vec4<f32>;
+    var filterParams1_1: vec4<f32>;
+    var filterParams2_1: vec4<f32>;
+    var kernel_4: vec4<f32>;
+    var bgColor_4: vec3<f32>;
+    var fgColor_4: vec3<f32>;
+    var gammaCorrectionEnabled: bool;
+    var bgrLayout: bool;
+    var verticalLayout: bool;
+    var alpha: vec3<f32>;
+    var alphaLeft: vec4<f32>;
+    var alphaRight: vec4<f32>;
+    var alphaCenter: f32;
+    var local_2: vec2<f32>;
+    var local_3: vec2<f32>;
+    var r: f32;
+    var g: f32;
+    var b: f32;
+    var local_4: vec3<f32>;
+
+    colorTexCoord_9 = colorTexCoord_8;
+    colorTextureSize_3 = colorTextureSize_2;
+    filterParams0_1 = filterParams0_;
+    filterParams1_1 = filterParams1_;
+    filterParams2_1 = filterParams2_;
+    let _e14 = filterParams0_1;
+    kernel_4 = _e14;
+    let _e16 = filterParams1_1;
+    bgColor_4 = _e16.xyz;
+    let _e19 = filterParams2_1;
+    fgColor_4 = _e19.xyz;
+    let _e22 = filterParams2_1;
+    gammaCorrectionEnabled = (_e22.w != 0f);
+    let _e27 = filterParams1_1;
+    let _e31 = filterParams1_1;
+    bgrLayout = ((_e27.w == 1f) || (_e31.w == 3f));
+    let _e37 = filterParams1_1;
+    verticalLayout = (_e37.w >= 2f);
+    let _e43 = kernel_4;
+    if (_e43.w == 0f) {
+        {
+            let _e47 = colorTexCoord_9;
+            let _e48 = textureSample(colorTexture_3, colorTextureSampler_3, _e47);
+            alpha = _e48.xxx;
+        }
+    } else {
+        {
+            let _e53 = verticalLayout;
+            if _e53 {
+                let _e56 = colorTextureSize_3;
+                local_2 = vec2<f32>(0f, (1f / _e56.y));
+            } else {
+                let _e61 = colorTextureSize_3;
+                local_2 = vec2<f32>((1f / _e61.x), 0f);
+            }
+            let _e67 = colorTexCoord_9;
+            let _e68 = kernel_4;
+            let _e69 = verticalLayout;
+            if _e69 {
+                let _e72 = colorTextureSize_3;
+                local_3 = vec2<f32>(0f, (1f / _e72.y));
+            } else {
+                let _e77 = colorTextureSize_3;
+                local_3 = vec2<f32>((1f / _e77.x), 0f);
+            }
+            let _e83 = local_3;
+            filterTextSample9Tap((&alphaLeft), (&alphaCenter), (&alphaRight), colorTexture_3, colorTextureSampler_3, _e67, _e68, _e83);
+            let _e84 = alphaLeft;
+            let _e85 = alphaCenter;
+            let _e86 = alphaRight;
+            let _e87 = _e86.xy;
+            let _e91 = kernel_4;
+            let _e92 = filterTextConvolve7Tap(_e84, vec3<f32>(_e85, _e87.x, _e87.y), _e91);
+            r = _e92;
+            let _e94 = alphaLeft;
+            let _e95 = _e94.yzw;
+            let _e96 = alphaCenter;
+            let _e101 = alphaRight;
+            let _e103 = kernel_4;
+            let _e104 = filterTextConvolve7Tap(vec4<f32>(_e95.x, _e95.y, _e95.z, _e96), _e101.xyz, _e103);
+            g = _e104;
+            let _e106 = alphaLeft;
+            let _e107 = _e106.zw;
+            let _e108 = alphaCenter;
+            let _e109 = alphaRight;
+            let _e114 = alphaRight;
+            let _e116 = kernel_4;
+            let _e117 = filterTextConvolve7Tap(vec4<f32>(_e107.x, _e107.y, _e108, _e109.x), _e114.yzw, _e116);
+            b = _e117;
+            let _e119 = bgrLayout;
+            if _e119 {
+                let _e120 = b;
+                let _e121 = g;
+                let _e122 = r;
+                local_4 = vec3<f32>(_e120, _e121, _e122);
+            } else {
+                let _e124 = r;
+                let _e125 = g;
+                let _e126 = b;
+                local_4 = vec3<f32>(_e124, _e125, _e126);
+            }
+            let _e129 = local_4;
+            alpha = _e129;
+        }
+    }
+    let _e130 = gammaCorrectionEnabled;
+    if _e130 {
+        let _e131 = bgColor_4;
+        let _e132 = alpha;
+        let _e133 = filterTextGammaCorrect(_e131, _e132, gammaLUT_2, gammaLUTSampler_2);
+        alpha = _e133;
+    }
+    let _e134 = bgColor_4;
+    let _e135 = fgColor_4;
+    let _e136 = alpha;
+    let _e137 = mix(_e134, _e135, _e136);
+    return vec4<f32>(_e137.x, _e137.y, _e137.z, 1f);
+}
+
+fn filterRadialGradient(colorTexCoord_10: vec2<f32>, colorTexture_4: texture_2d<f32>, colorTextureSampler_4: sampler, colorTextureSize_4: vec2<f32>, fragCoord: vec2<f32>, framebufferSize: vec2<f32>, filterParams0_2: vec4<f32>, filterParams1_2: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_11: vec2<f32>;
+    var colorTextureSize_5: vec2<f32>;
+    var fragCoord_1: vec2<f32>;
+    var framebufferSize_1: vec2<f32>;
+    var filterParams0_3: vec4<f32>;
+    var filterParams1_3: vec4<f32>;
+    var lineFrom: vec2<f32>;
+    var lineVector: vec2<f32>;
+    var radii: vec2<f32>;
+    var uvOrigin: vec2<f32>;
+    var dP: vec2<f32>;
+    var dC: vec2<f32>;
+    var dR: f32;
+    var a: f32;
+    var b_1: f32;
+    var c: f32;
+    var discrim: f32;
+    var color: vec4<f32> = vec4(0f);
+    var ts: vec2<f32>;
+    var local_5: f32;
+    var t: f32;
+
+    colorTexCoord_11 = colorTexCoord_10;
+    colorTextureSize_5 = colorTextureSize_4;
+    fragCoord_1 = fragCoord;
+    framebufferSize_1 = framebufferSize;
+    filterParams0_3 = filterParams0_2;
+    filterParams1_3 = filterParams1_2;
+    let _e14 = filterParams0_3;
+    lineFrom = _e14.xy;
+    let _e17 = filterParams0_3;
+    lineVector = _e17.zw;
+    let _e20 = filterParams1_3;
+    radii = _e20.xy;
+    let _e23 = filterParams1_3;
+    uvOrigin = _e23.zw;
+    let _e26 = colorTexCoord_11;
+    let _e27 = lineFrom;
+    dP = (_e26 - _e27);
+    let _e30 = lineVector;
+    dC = _e30;
+    let _e32 = radii;
+    let _e34 = radii;
+    dR = (_e32.y - _e34.x);
+    let _e38 = dC;
+    let _e39 = dC;
+    let _e41 = dR;
+    let _e42 = dR;
+    a = (dot(_e38, _e39) - (_e41 * _e42));
+    let _e46 = dP;
+    let _e47 = dC;
+    let _e49 = radii;
+    let _e51 = dR;
+    b_1 = (dot(_e46, _e47) + (_e49.x * _e51));
+    let _e55 = dP;
+    let _e56 = dP;
+    let _e58 = radii;
+    let _e60 = radii;
+    c = (dot(_e55, _e56) - (_e58.x * _e60.x));
+    let _e65 = b_1;
+    let _e66 = b_1;
+    let _e68 = a;
+    let _e69 = c;
+    discrim = ((_e65 * _e66) - (_e68 * _e69));
+    let _e76 = discrim;
+    if (abs(_e76) >= 0.00001f) {
+        {
+            let _e80 = discrim;
+            let _e86 = b_1;
+            let _e90 = a;
+            ts = (vec2<f32>(((sqrt(_e80) * vec2<f32>(1f, -1f)) + vec2(_e86))) / vec2(_e90));
+            let _e94 = ts;
+            let _e96 = ts;
+            if (_e94.x > _e96.y) {
+                let _e99 = ts;
+                ts = _e99.yx;
+            }
+            let _e101 = ts;
+            if (_e101.x >= 0f) {
+                let _e105 = ts;
+                local_5 = _e105.x;
+            } else {
+                let _e107 = ts;
+                local_5 = _e107.y;
+            }
+            let _e110 = local_5;
+            t = _e110;
+            let _e112 = uvOrigin;
+            let _e113 = t;
+            let _e120 = textureSample(colorTexture_4, colorTextureSampler_4, (_e112 + vec2<f32>(clamp(_e113, 0f, 1f), 0f)));
+            color = _e120;
+        }
+    }
+    let _e121 = color;
+    return _e121;
+}
+
+fn filterBlur(colorTexCoord_12: vec2<f32>, colorTexture_5: texture_2d<f32>, colorTextureSampler_5: sampler, colorTextureSize_6: vec2<f32>, filterParams0_4: vec4<f32>, filterParams1_4: vec4<f32>) -> vec4<f32> {
+    var colorTexCoord_13: vec2<f32>;
+    var colorTextureSize_7: vec2<f32>;
+    var filterParams0_5: vec4<f32>;
+    var filterParams1_5: vec4<f32>;
+    var srcOffsetScale: vec2<f32>;
+    var support: i32;
+    var gaussCoeff: vec3<f32>;
+    var gaussSum: f32;
+    var color_1: vec4<f32>;
+    var i: i32 = 1i;
+    var gaussPartialSum: f32;
+    var srcOffset: vec2<f32>;
+
+    colorTexCoord_13 = colorTexCoord_12;
+    colorTextureSize_7 = colorTextureSize_6;
+    filterParams0_5 = filterParams0_4;
+    filterParams1_5 = filterParams1_4;
+    let _e10 = filterParams0_5;
+    let _e12 = colorTextureSize_7;
+    srcOffsetScale = (_e10.xy / _e12);
+    let _e15 = filterParams0_5;
+    support = i32(_e15.z);
+    let _e19 = filterParams1_5;
+    gaussCoeff = _e19.xyz;
+    let _e22 = gaussCoeff;
+    gaussSum = _e22.x;
+    let _e25 = colorTexCoord_13;
+    let _e26 = textureSample(colorTexture_5, colorTextureSampler_5, _e25);
+    let _e27 = gaussCoeff;
+    color_1 = (_e26 * _e27.x);
+    let _e31 = gaussCoeff;
+    let _e33 = gaussCoeff;
+    let _e35 = (_e31.xy * _e33.yz);
+    gaussCoeff.x = _e35.x;
+    gaussCoeff.y = _e35.y;
+    loop {
+        let _e42 = i;
+        let _e43 = support;
+        if !((_e42 <= _e43)) {
+            break;
+        }
+        {
+            let _e49 = gaussCoeff;
+            gaussPartialSum = _e49.x;
+            let _e52 = gaussCoeff;
+            let _e54 = gaussCoeff;
+            let _e56 = (_e52.xy * _e54.yz);
+            gaussCoeff.x = _e56.x;
+            gaussCoeff.y = _e56.y;
+            let _e61 = gaussPartialSum;
+            let _e62 = gaussCoeff;
+            gaussPartialSum = (_e61 + _e62.x);
+            let _e65 = srcOffsetScale;
+            let _e66 = i;
+            let _e68 = gaussCoeff;
+            let _e70 = gaussPartialSum;
+            srcOffset = (_e65 * (f32(_e66) + (_e68.x / _e70)));
+            let _e75 = color_1;
+            let _e76 = colorTexCoord_13;
+            let _e77 = srcOffset;
+            let _e79 = textureSample(colorTexture_5, colorTextureSampler_5, (_e76 - _e77));
+            let _e80 = colorTexCoord_13;
+            let _e81 = srcOffset;
+            let _e83 = textureSample(colorTexture_5, colorTextureSampler_5, (_e80 + _e81));
+            let _e85 = gaussPartialSum;
+            color_1 = (_e75 + ((_e79 + _e83) * _e85));
+            let _e88 = gaussSum;
+            let _e90 = gaussPartialSum;
+            gaussSum = (_e88 + (2f * _e90));
+            let _e93 = gaussCoeff;
+            let _e95 = gaussCoeff;
+            let _e97 = (_e93.xy * _e95.yz);
+            gaussCoeff.x = _e97.x;
+            gaussCoeff.y = _e97.y;
+        }
+        continuing {
+            let _e46 = i;
+            i = (_e46 + 2i);
+        }
+    }
+    let _e102 = color_1;
+    let _e103 = gaussSum;
+    return (_e102 / vec4(_e103));
+}
+
+fn filterNone(colorTexCoord_14: vec2<f32>, colorTexture_6: texture_2d<f32>, colorTextureSampler_6: sampler) -> vec4<f32> {
+    var colorTexCoord_15: vec2<f32>;
+
+    colorTexCoord_15 = colorTexCoord_14;
+    let _e4 = colorTexCoord_15;
+    let _e5 = sampleColor(colorTexture_6, colorTextureSampler_6, _e4);
+    return _e5;
+}
+
+fn filterColor(colorTexCoord_16: vec2<f32>, colorTexture_7: texture_2d<f32>, colorTextureSampler_7: sampler, gammaLUT_3: texture_2d<f32>, gammaLUTSampler_3: sampler, colorTextureSize_8: vec2<f32>, fragCoord_2: vec2<f32>, framebufferSize_2: vec2<f32>, filterParams0_6: vec4<f32>, filterParams1_6: vec4<f32>, filterParams2_2: vec4<f32>, colorFilter: i32) -> vec4<f32> {
+    var colorTexCoord_17: vec2<f32>;
+    var colorTextureSize_9: vec2<f32>;
+    var fragCoord_3: vec2<f32>;
+    var framebufferSize_3: vec2<f32>;
+    var filterParams0_7: vec4<f32>;
+    var filterParams1_7: vec4<f32>;
+    var filterParams2_3: vec4<f32>;
+    var colorFilter_1: i32;
+
+    colorTexCoord_17 = colorTexCoord_16;
+    colorTextureSize_9 = colorTextureSize_8;
+    fragCoord_3 = fragCoord_2;
+    framebufferSize_3 = framebufferSize_2;
+    filterParams0_7 = filterParams0_6;
+    filterParams1_7 = filterParams1_6;
+    filterParams2_3 = filterParams2_2;
+    colorFilter_1 = colorFilter;
+    let _e20 = colorFilter_1;
+    switch _e20 {
+        case 1: {
+            let _e21 = colorTexCoord_17;
+            let _e22 = colorTextureSize_9;
+            let _e23 = fragCoord_3;
+            let _e24 = framebufferSize_3;
+            let _e25 = filterParams0_7;
+            let _e26 = filterParams1_7;
+            let _e27 = filterRadialGradient(_e21, colorTexture_7, colorTextureSampler_7, _e22, _e23, _e24, _e25, _e26);
+            return _e27;
+        }
+        case 3: {
+            let _e28 = colorTexCoord_17;
+            let _e29 = colorTextureSize_9;
+            let _e30 = filterParams0_7;
+            let _e31 = filterParams1_7;
+            let _e32 = filterBlur(_e28, colorTexture_7, colorTextureSampler_7, _e29, _e30, _e31);
+            return _e32;
+        }
+        case 2: {
+            let _e33 = colorTexCoord_17;
+            let _e34 = colorTextureSize_9;
+            let _e35 = filterParams0_7;
+            let _e36 = filterParams1_7;
+            let _e37 = filterParams2_3;
+            let _e38 = filterText(_e33, colorTexture_7, colorTextureSampler_7, gammaLUT_3, gammaLUTSampler_3, _e34, _e35, _e36, _e37);
+            return _e38;
+        }
+        default: {
+        }
+    }
+    let _e39 = colorTexCoord_17;
+    let _e40 = filterNone(_e39, colorTexture_7, colorTextureSampler_7);
+    return _e40;
+}
+
+fn compositeSelect(cond: vec3<bool>, ifTrue: vec3<f32>, ifFalse: vec3<f32>) -> vec3<f32> {
+    var cond_1: vec3<bool>;
+    var ifTrue_1: vec3<f32>;
+    var ifFalse_1: vec3<f32>;
+    var local_6: f32;
+    var local_7: f32;
+    var local_8: f32;
+
+    cond_1 = cond;
+    ifTrue_1 = ifTrue;
+    ifFalse_1 = ifFalse;
+    let _e6 = cond_1;
+    if _e6.x {
+        let _e8 = ifTrue_1;
+        local_6 = _e8.x;
+    } else {
+        let _e10 = ifFalse_1;
+        local_6 = _e10.x;
+    }
+    let _e13 = local_6;
+    let _e14 = cond_1;
+    if _e14.y {
+        let _e16 = ifTrue_1;
+        local_7 = _e16.y;
+    } else {
+        let _e18 = ifFalse_1;
+        local_7 = _e18.y;
+    }
+    let _e21 = local_7;
+    let _e22 = cond_1;
+    if _e22.z {
+        let _e24 = ifTrue_1;
+        local_8 = _e24.z;
+    } else {
+        let _e26 = ifFalse_1;
+        local_8 = _e26.z;
+    }
+    let _e29 = local_8;
+    return vec3<f32>(_e13, _e21, _e29);
+}
+
+fn compositeDivide(num: f32, denom: f32) -> f32 {
+    var num_1: f32;
+    var denom_1: f32;
+    var local_9: f32;
+
+    num_1 = num;
+    denom_1 = denom;
+    let _e4 = denom_1;
+    if (_e4 != 0f) {
+        let _e7 = num_1;
+        let _e8 = denom_1;
+        local_9 = (_e7 / _e8);
+    } else {
+        local_9 = 0f;
+    }
+    let _e12 = local_9;
+    return _e12;
+}
+
+fn compositeColorDodge(destColor_2: vec3<f32>, srcColor_2: vec3<f32>) -> vec3<f32> {
+    var destColor_3: vec3<f32>;
+    var srcColor_3: vec3<f32>;
+    var destZero: vec3<bool>;
+    var srcOne: vec3<bool>;
+
+    destColor_3 = destColor_2;
+    srcColor_3 = srcColor_2;
+    let _e4 = destColor_3;
+    destZero = (_e4 == vec3(0f));
+    let _e9 = srcColor_3;
+    srcOne = (_e9 == vec3(1f));
+    let _e14 = srcOne;
+    let _e17 = destColor_3;
+    let _e20 = srcColor_3;
+    let _e23 = compositeSelect(_e14, vec3(1f), (_e17 / (vec3(1f) - _e20)));
+    let _e24 = destZero;
+    let _e27 = srcOne;
+    let _e30 = destColor_3;
+    let _e33 = srcColor_3;
+    let _e36 = compositeSelect(_e27, vec3(1f), (_e30 / (vec3(1f) - _e33)));
+    let _e37 = compositeSelect(_e24, vec3(0f), _e36);
+    return _e37;
+}
+
+fn compositeHSLToRGB(hsl: vec3<f32>) -> vec3<f32> {
+    var hsl_1: vec3<f32>;
+    var a_1: f32;
+    var ks: vec3<f32>;
+
+    hsl_1 = hsl;
+    let _e2 = hsl_1;
+    let _e4 = hsl_1;
+    let _e7 = hsl_1;
+    a_1 = (_e2.y * min(_e4.z, (1f - _e7.z)));
+    let _e17 = hsl_1;
+    let _e22 = (vec3<f32>(0f, 8f, 4f) + vec3((_e17.x * 1.9098593f)));
+    let _e24 = vec3(12f);
+    ks = (_e22 - (floor((_e22 / _e24)) * _e24));
+    let _e30 = hsl_1;
+    let _e32 = ks;
+    let _e38 = ks;
+    let _e46 = a_1;
+    return (_e30.zzz - (clamp(min((_e32 - vec3(3f)), (vec3(9f) - _e38)), vec3(-1f), vec3(1f)) * _e46));
+}
+
+fn compositeRGBToHSL(rgb: vec3<f32>) -> vec3<f32> {
+    var rgb_1: vec3<f32>;
+    var v: f32;
+    var xMin: f32;
+    var c_1: f32;
+    var l: f32;
+    var local_10: vec3<f32>;
+    var local_11: vec3<f32>;
+    var terms: vec3<f32>;
+    var h: f32;
+    var s: f32;
+
+    rgb_1 = rgb;
+    let _e2 = rgb_1;
+    let _e4 = rgb_1;
+    let _e7 = rgb_1;
+    v = max(max(_e2.x, _e4.y), _e7.z);
+    let _e11 = rgb_1;
+    let _e13 = rgb_1;
+    let _e16 = rgb_1;
+    xMin = min(min(_e11.x, _e13.y), _e16.z);
+    let _e20 = v;
+    let _e21 = xMin;
+    c_1 = (_e20 - _e21);
+    let _e24 = xMin;
+    let _e25 = v;
+    l = mix(_e24, _e25, 0.5f);
+    let _e29 = rgb_1;
+    let _e31 = v;
+    if (_e29.x == _e31) {
+        let _e34 = rgb_1;
+        let _e35 = _e34.yz;
+        local_11 = vec3<f32>(0f, _e35.x, _e35.y);
+    } else {
+        let _e39 = rgb_1;
+        let _e41 = v;
+        if (_e39.y == _e41) {
+            let _e44 = rgb_1;
+            let _e45 = _e44.zx;
+            local_10 = vec3<f32>(2f, _e45.x, _e45.y);
+        } else {
+            let _e50 = rgb_1;
+            let _e51 = _e50.xy;
+            local_10 = vec3<f32>(4f, _e51.x, _e51.y);
+        }
+        let _e56 = local_10;
+        local_11 = _e56;
+    }
+    let _e58 = local_11;
+    terms = _e58;
+    let _e61 = terms;
+    let _e63 = c_1;
+    let _e65 = terms;
+    let _e68 = terms;
+    let _e71 = c_1;
+    let _e72 = compositeDivide((((_e61.x * _e63) + _e65.y) - _e68.z), _e71);
+    h = (1.0471976f * _e72);
+    let _e75 = c_1;
+    let _e76 = v;
+    let _e77 = compositeDivide(_e75, _e76);
+    s = _e77;
+    let _e79 = h;
+    let _e80 = s;
+    let _e81 = l;
+    return vec3<f32>(_e79, _e80, _e81);
+}
+
+fn compositeScreen(destColor_4: vec3<f32>, srcColor_4: vec3<f32>) -> vec3<f32> {
+    var destColor_5: vec3<f32>;
+    var srcColor_5: vec3<f32>;
+
+    destColor_5 = destColor_4;
+    srcColor_5 = srcColor_4;
+    let _e4 = destColor_5;
+    let _e5 = srcColor_5;
+    let _e7 = destColor_5;
+    let _e8 = srcColor_5;
+    return ((_e4 + _e5) - (_e7 * _e8));
+}
+
+fn compositeHardLight(destColor_6: vec3<f32>, srcColor_6: vec3<f32>) -> vec3<f32> {
+    var destColor_7: vec3<f32>;
+    var srcColor_7: vec3<f32>;
+
+    destColor_7 = destColor_6;
+    srcColor_7 = srcColor_6;
+    let _e4 = destColor_7;
+    let _e7 = srcColor_7;
+    let _e12 = compositeScreen(_e4, ((vec3(2f) * _e7) - vec3(1f)));
+    let _e13 = srcColor_7;
+    let _e17 = destColor_7;
+    let _e21 = srcColor_7;
+    let _e23 = destColor_7;
+    let _e26 = srcColor_7;
+    let _e31 = compositeScreen(_e23, ((vec3(2f) * _e26) - vec3(1f)));
+    let _e32 = compositeSelect((_e13 <= vec3(0.5f)), ((_e17 * vec3(2f)) * _e21), _e31);
+    return _e32;
+}
+
+fn compositeSoftLight(destColor_8: vec3<f32>, srcColor_8: vec3<f32>) -> vec3<f32> {
+    var destColor_9: vec3<f32>;
+    var srcColor_9: vec3<f32>;
+    var darkenedDestColor: vec3<f32>;
+    var factor: vec3<f32>;
+
+    destColor_9 = destColor_8;
+    srcColor_9 = srcColor_8;
+    let _e4 = destColor_9;
+    let _e10 = destColor_9;
+    let _e15 = destColor_9;
+    let _e20 = destColor_9;
+    let _e22 = destColor_9;
+    let _e24 = compositeSelect((_e4 <= vec3(0.25f)), (((((vec3(16f) * _e10) - vec3(12f)) * _e15) + vec3(4f)) * _e20), sqrt(_e22));
+    darkenedDestColor = _e24;
+    let _e26 = srcColor_9;
+    let _e30 = destColor_9;
+    let _e33 = destColor_9;
+    let _e36 = darkenedDestColor;
+    let _e37 = destColor_9;
+    let _e39 = compositeSelect((_e26 <= vec3(0.5f)), (_e30 * (vec3(1f) - _e33)), (_e36 - _e37));
+    factor = _e39;
+    let _e41 = destColor_9;
+    let _e42 = srcColor_9;
+    let _e48 = factor;
+    return (_e41 + (((_e42 * 2f) - vec3(1f)) * _e48));
+}
+
+fn compositeHSL(destColor_10: vec3<f32>, srcColor_10: vec3<f32>, op_2: i32) -> vec3<f32> {
+    var destColor_11: vec3<f32>;
+    var srcColor_11: vec3<f32>;
+    var op_3: i32;
+
+    destColor_11 = destColor_10;
+    srcColor_11 = srcColor_10;
+    op_3 = op_2;
+    let _e6 = op_3;
+    switch _e6 {
+        case 12: {
+            let _e7 = srcColor_11;
+            let _e9 = destColor_11;
+            let _e11 = destColor_11;
+            return vec3<f32>(_e7.x, _e9.y, _e11.z);
+        }
+        case 13: {
+            let _e14 = destColor_11;
+            let _e16 = srcColor_11;
+            let _e18 = destColor_11;
+            return vec3<f32>(_e14.x, _e16.y, _e18.z);
+        }
+        case 14: {
+            let _e21 = srcColor_11;
+            let _e23 = srcColor_11;
+            let _e25 = destColor_11;
+            return vec3<f32>(_e21.x, _e23.y, _e25.z);
+        }
+        default: {
+            let _e28 = destColor_11;
+            let _e30 = destColor_11;
+            let _e32 = srcColor_11;
+            return vec3<f32>(_e28.x, _e30.y, _e32.z);
+        }
+    }
+}
+
+fn compositeRGB(destColor_12: vec3<f32>, srcColor_12: vec3<f32>, op_4: i32) -> vec3<f32> {
+    var destColor_13: vec3<f32>;
+    var srcColor_13: vec3<f32>;
+    var op_5: i32;
+
+    destColor_13 = destColor_12;
+    srcColor_13 = srcColor_12;
+    op_5 = op_4;
+    let _e6 = op_5;
+    switch _e6 {
+        case 1: {
+            let _e7 = destColor_13;
+            let _e8 = srcColor_13;
+            return (_e7 * _e8);
+        }
+        case 2: {
+            let _e10 = destColor_13;
+            let _e11 = srcColor_13;
+            let _e12 = compositeScreen(_e10, _e11);
+            return _e12;
+        }
+        case 3: {
+            let _e13 = srcColor_13;
+            let _e14 = destColor_13;
+            let _e15 = compositeHardLight(_e13, _e14);
+            return _e15;
+        }
+        case 4: {
+            let _e16 = destColor_13;
+            let _e17 = srcColor_13;
+            return min(_e16, _e17);
+        }
+        case 5: {
+            let _e19 = destColor_13;
+            let _e20 = srcColor_13;
+            return max(_e19, _e20);
+        }
+        case 6: {
+            let _e22 = destColor_13;
+            let _e23 = srcColor_13;
+            let _e24 = compositeColorDodge(_e22, _e23);
+            return _e24;
+        }
+        case 7: {
+            let _e29 = destColor_13;
+            let _e33 = srcColor_13;
+            let _e35 = compositeColorDodge((vec3(1f) - _e29), (vec3(1f) - _e33));
+            return (vec3(1f) - _e35);
+        }
+        case 8: {
+            let _e37 = destColor_13;
+            let _e38 = srcColor_13;
+            let _e39 = compositeHardLight(_e37, _e38);
+            return _e39;
+        }
+        case 9: {
+            let _e40 = destColor_13;
+            let _e41 = srcColor_13;
+            let _e42 = compositeSoftLight(_e40, _e41);
+            return _e42;
+        }
+        case 10: {
+            let _e43 = destColor_13;
+            let _e44 = srcColor_13;
+            return abs((_e43 - _e44));
+        }
+        case 11: {
+            let _e47 = destColor_13;
+            let _e48 = srcColor_13;
+            let _e52 = destColor_13;
+            let _e54 = srcColor_13;
+            return ((_e47 + _e48) - ((vec3(2f) * _e52) * _e54));
+        }
+        case 12, 13, 14, 15: {
+            let _e57 = destColor_13;
+            let _e58 = compositeRGBToHSL(_e57);
+            let _e59 = srcColor_13;
+            let _e60 = compositeRGBToHSL(_e59);
+            let _e61 = destColor_13;
+            let _e62 = compositeRGBToHSL(_e61);
+            let _e63 = srcColor_13;
+            let _e64 = compositeRGBToHSL(_e63);
+            let _e65 = op_5;
+            let _e66 = compositeHSL(_e62, _e64, _e65);
+            let _e67 = destColor_13;
+            let _e68 = compositeRGBToHSL(_e67);
+            let _e69 = srcColor_13;
+            let _e70 = compositeRGBToHSL(_e69);
+            let _e71 = destColor_13;
+            let _e72 = compositeRGBToHSL(_e71);
+            let _e73 = srcColor_13;
+            let _e74 = compositeRGBToHSL(_e73);
+            let _e75 = op_5;
+            let _e76 = compositeHSL(_e72, _e74, _e75);
+            let _e77 = compositeHSLToRGB(_e76);
+            return _e77;
+        }
+        default: {
+        }
+    }
+    let _e78 = srcColor_13;
+    return _e78;
+}
+
+fn composite(srcColor_14: vec4<f32>, destTexture: texture_2d<f32>, destTextureSampler: sampler, destTextureSize: vec2<f32>, fragCoord_4: vec2<f32>, op_6: i32) -> vec4<f32> {
+    var srcColor_15: vec4<f32>;
+    var destTextureSize_1: vec2<f32>;
+    var fragCoord_5: vec2<f32>;
+    var op_7: i32;
+    var destTexCoord: vec2<f32>;
+    var destColor_14: vec4<f32>;
+    var blendedRGB: vec3<f32>;
+
+    srcColor_15 = srcColor_14;
+    destTextureSize_1 = destTextureSize;
+    fragCoord_5 = fragCoord_4;
+    op_7 = op_6;
+    let _e10 = op_7;
+    if (_e10 == 0i) {
+        let _e13 = srcColor_15;
+        return _e13;
+    }
+    let _e14 = fragCoord_5;
+    let _e15 = destTextureSize_1;
+    destTexCoord = (_e14 / _e15);
+    let _e18 = destTexCoord;
+    let _e19 = textureSample(destTexture, destTextureSampler, _e18);
+    destColor_14 = _e19;
+    let _e21 = destColor_14;
+    let _e23 = srcColor_15;
+    let _e25 = op_7;
+    let _e26 = compositeRGB(_e21.xyz, _e23.xyz, _e25);
+    blendedRGB = _e26;
+    let _e28 = srcColor_15;
+    let _e31 = destColor_14;
+    let _e35 = srcColor_15;
+    let _e38 = srcColor_15;
+    let _e40 = destColor_14;
+    let _e43 = blendedRGB;
+    let _e47 = srcColor_15;
+    let _e50 = destColor_14;
+    let _e53 = ((((_e28.w * (1f - _e31.w)) * _e35.xyz) + ((_e38.w * _e40.w) * _e43)) + ((1f - _e47.w) * _e50.xyz));
+    return vec4<f32>(_e53.x, _e53.y, _e53.z, 1f);
+}
+
+fn sampleMask(maskAlpha: f32, maskTexture: texture_2d<f32>, maskTextureSampler: sampler, maskTextureSize: vec2<f32>, maskTexCoord: vec3<f32>, maskCtrl: i32) -> f32 {
+    var maskAlpha_1: f32;
+    var maskTextureSize_1: vec2<f32>;
+    var maskTexCoord_1: vec3<f32>;
+    var maskCtrl_1: i32;
+    var maskTexCoordI: vec2<i32>;
+    var texel: vec4<f32>;
+    var coverage: f32;
+
+    maskAlpha_1 = maskAlpha;
+    maskTextureSize_1 = maskTextureSize;
+    maskTexCoord_1 = maskTexCoord;
+    maskCtrl_1 = maskCtrl;
+    let _e10 = maskCtrl_1;
+    if (_e10 == 0i) {
+        let _e13 = maskAlpha_1;
+        return _e13;
+    }
+    let _e14 = maskTexCoord_1;
+    maskTexCoordI = vec2<i32>(floor(_e14.xy));
+    let _e19 = maskTexCoordI;
+    let _e28 = maskTextureSize_1;
+    let _e30 = textureSample(maskTexture, maskTextureSampler, ((vec2<f32>((_e19 / vec2<i32>(1i, 4i))) + vec2(0.5f)) / _e28));
+    texel = _e30;
+    let _e32 = maskTexCoordI;
+    let _e37 = texel[(_e32.y % 4i)];
+    let _e38 = maskTexCoord_1;
+    coverage = (_e37 + _e38.z);
+    let _e42 = maskCtrl_1;
+    if ((_e42 & 1i) != 0i) {
+        let _e47 = coverage;
+        coverage = abs(_e47);
+    } else {
+        let _e51 = coverage;
+        coverage = (1f - abs((1f - (_e51 - (floor((_e51 / 2f)) * 2f)))));
+    }
+    let _e60 = maskAlpha_1;
+    let _e61 = coverage;
+    return min(_e60, _e61);
+}
+
+fn samplePaintMask(paintMaskTexture: texture_2d<f32>, paintMaskTextureSampler: sampler, paintMaskTextureSize: vec2<f32>, paintMaskTexCoord: vec2<f32>, paintMaskTexRect: vec4<f32>, paintMaskCtrl: i32) -> f32 {
+    var paintMaskTextureSize_1: vec2<f32>;
+    var paintMaskTexCoord_1: vec2<f32>;
+    var paintMaskTexRect_1: vec4<f32>;
+    var paintMaskCtrl_1: i32;
+    var texCoord: vec2<f32>;
+    var texel_1: vec4<f32>;
+    var local_12: vec3<f32>;
+
+    paintMaskTextureSize_1 = paintMaskTextureSize;
+    paintMaskTexCoord_1 = paintMaskTexCoord;
+    paintMaskTexRect_1 = paintMaskTexRect;
+    paintMaskCtrl_1 = paintMaskCtrl;
+    let _e10 = paintMaskTexCoord_1;
+    let _e11 = paintMaskTexRect_1;
+    let _e12 = paintMaskTextureSize_1;
+    let _e13 = wrapColorTexCoord(_e10, _e11, _e12);
+    texCoord = _e13;
+    let _e15 = texCoord;
+    let _e16 = textureSample(paintMaskTexture, paintMaskTextureSampler, _e15);
+    texel_1 = _e16;
+    let _e18 = paintMaskCtrl_1;
+    if ((_e18 & 3i) != 2i) {
+        let _e23 = texel_1;
+        return _e23.w;
+    }
+    let _e25 = paintMaskCtrl_1;
+    if ((_e25 & 4i) != 0i) {
+        let _e30 = texel_1;
+        if (_e30.w > 0f) {
+            let _e34 = texel_1;
+            let _e36 = texel_1;
+            local_12 = (_e34.xyz / vec3(_e36.w));
+        } else {
+            local_12 = vec3(0f);
+        }
+        let _e43 = local_12;
+        texel_1.x = _e43.x;
+        texel_1.y = _e43.y;
+        texel_1.z = _e43.z;
+    }
+    let _e50 = texel_1;
+    let _e57 = texel_1;
+    return (dot(_e50.xyz, vec3<f32>(0.2125f, 0.7154f, 0.0721f)) * _e57.w);
+}
+
+fn calculateColor(fragCoord_6: vec2<f32>, colorTexture0_: texture_2d<f32>, colorTexture0Sampler: sampler, maskTexture0_: texture_2d<f32>, maskTexture0Sampler: sampler, paintMaskTexture_1: texture_2d<f32>, paintMaskTextureSampler_1: sampler, destTexture_1: texture_2d<f32>, destTextureSampler_1: sampler, gammaLUT_4: texture_2d<f32>, gammaLUTSampler_4: sampler, colorTextureSize0_: vec2<f32>, maskTextureSize0_: vec2<f32>, paintMaskTextureSize_2: vec2<f32>, filterParams0_8: vec4<f32>, filterParams1_8: vec4<f32>, filterParams2_4: vec4<f32>, framebufferSize_4: vec2<f32>, ctrl: i32, maskTexCoord0_: vec3<f32>, colorTexCoord0_: vec2<f32>, colorTexRect0_: vec4<f32>, paintMaskTexCoord_2: vec2<f32>, paintMaskTexRect_2: vec4<f32>, baseColor: vec4<f32>, tileCtrl: i32) -> vec4<f32> {
+    var fragCoord_7: vec2<f32>;
+    var colorTextureSize0_1: vec2<f32>;
+    var maskTextureSize0_1: vec2<f32>;
+    var paintMaskTextureSize_3: vec2<f32>;
+    var filterParams0_9: vec4<f32>;
+    var filterParams1_9: vec4<f32>;
+    var filterParams2_5: vec4<f32>;
+    var framebufferSize_5: vec2<f32>;
+    var ctrl_1: i32;
+    var maskTexCoord0_1: vec3<f32>;
+    var colorTexCoord0_1: vec2<f32>;
+    var colorTexRect0_1: vec4<f32>;
+    var paintMaskTexCoord_3: vec2<f32>;
+    var paintMaskTexRect_3: vec4<f32>;
+    var baseColor_1: vec4<f32>;
+    var tileCtrl_1: i32;
+    var maskCtrl0_: i32;
+    var maskAlpha_2: f32 = 1f;
+    var paintMaskCtrl_2: i32;
+    var color_2: vec4<f32>;
+    var color0Combine: i32;
+    var color0Filter: i32;
+    var colorTexCoord_18: vec2<f32>;
+    var color0_: vec4<f32>;
+    var compositeOp: i32;
+
+    fragCoord_7 = fragCoord_6;
+    colorTextureSize0_1 = colorTextureSize0_;
+    maskTextureSize0_1 = maskTextureSize0_;
+    paintMaskTextureSize_3 = paintMaskTextureSize_2;
+    filterParams0_9 = filterParams0_8;
+    filterParams1_9 = filterParams1_8;
+    filterParams2_5 = filterParams2_4;
+    framebufferSize_5 = framebufferSize_4;
+    ctrl_1 = ctrl;
+    maskTexCoord0_1 = maskTexCoord0_;
+    colorTexCoord0_1 = colorTexCoord0_;
+    colorTexRect0_1 = colorTexRect0_;
+    paintMaskTexCoord_3 = paintMaskTexCoord_2;
+    paintMaskTexRect_3 = paintMaskTexRect_2;
+    baseColor_1 = baseColor;
+    tileCtrl_1 = tileCtrl;
+    let _e42 = tileCtrl_1;
+    maskCtrl0_ = ((_e42 >> 0u) & 3i);
+    let _e50 = maskAlpha_2;
+    let _e51 = maskTextureSize0_1;
+    let _e52 = maskTexCoord0_1;
+    let _e53 = maskCtrl0_;
+    let _e54 = sampleMask(_e50, maskTexture0_, maskTexture0Sampler, _e51, _e52, _e53);
+    maskAlpha_2 = _e54;
+    let _e55 = ctrl_1;
+    paintMaskCtrl_2 = (_e55 >> 12u);
+    let _e59 = paintMaskCtrl_2;
+    if ((_e59 & 3i) != 0i) {
+        {
+            let _e64 = maskAlpha_2;
+            let _e65 = paintMaskTextureSize_3;
+            let _e66 = paintMaskTexCoord_3;
+            let _e67 = paintMaskTexRect_3;
+            let _e68 = paintMaskCtrl_2;
+            let _e69 = samplePaintMask(paintMaskTexture_1, paintMaskTextureSampler_1, _e65, _e66, _e67, _e68);
+            maskAlpha_2 = (_e64 * _e69);
+        }
+    }
+    let _e71 = baseColor_1;
+    color_2 = _e71;
+    let _e73 = ctrl_1;
+    color0Combine = ((_e73 >> 6u) & 3i);
+    let _e79 = color0Combine;
+    if (_e79 != 0i) {
+        {
+            let _e82 = ctrl_1;
+            color0Filter = ((_e82 >> 4u) & 3i);
+            let _e88 = colorTexCoord0_1;
+            let _e89 = colorTexRect0_1;
+            let _e90 = colorTextureSize0_1;
+            let _e91 = wrapColorTexCoord(_e88, _e89, _e90);
+            colorTexCoord_18 = _e91;
+            let _e93 = colorTexCoord_18;
+            let _e94 = colorTextureSize0_1;
+            let _e95 = fragCoord_7;
+            let _e96 = framebufferSize_5;
+            let _e97 = filterParams0_9;
+            let _e98 = filterParams1_9;
+            let _e99 = filterParams2_5;
+            let _e100 = color0Filter;
+            let _e101 = filterColor(_e93, colorTexture0_, colorTexture0Sampler, gammaLUT_4, gammaLUTSampler_4, _e94, _e95, _e96, _e97, _e98, _e99, _e100);
+            color0_ = _e101;
+            let _e103 = color_2;
+            let _e104 = color0_;
+            let _e105 = color0Combine;
+            let _e106 = combineColor0_(_e103, _e104, _e105);
+            color_2 = _e106;
+        }
+    }
+    let _e108 = color_2;
+    let _e110 = maskAlpha_2;
+    color_2.w = (_e108.w * _e110);
+    let _e112 = ctrl_1;
+    compositeOp = ((_e112 >> 8u) & 15i);
+    let _e118 = color_2;
+    let _e119 = framebufferSize_5;
+    let _e120 = fragCoord_7;
+    let _e121 = compositeOp;
+    let _e122 = composite(_e118, destTexture_1, destTextureSampler_1, _e119, _e120, _e121);
+    color_2 = _e122;
+    let _e123 = color_2;
+    let _e125 = color_2;
+    let _e127 = (_e123.xyz * _e125.w);
+    color_2.x = _e127.x;
+    color_2.y = _e127.y;
+    color_2.z = _e127.z;
+    let _e134 = color_2;
+    return _e134;
+}
+
+fn main_1() {
+    let _e31 = gl_FragCoord;
+    let _e33 = global.uColorTextureSize0_;
+    let _e34 = global.uMaskTextureSize0_;
+    let _e35 = global.uPaintMaskTextureSize;
+    let _e36 = vFilterParams0_1;
+    let _e37 = vFilterParams1_1;
+    let _e38 = vFilterParams2_1;
+    let _e39 = global.uFramebufferSize;
+    let _e40 = vCtrl_1;
+    let _e42 = vMaskTexCoord0_1;
+    let _e43 = vColorTexCoord0_1;
+    let _e44 = vColorTexRect0_1;
+    let _e45 = vPaintMaskTexCoord_1;
+    let _e46 = vPaintMaskTexRect_1;
+    let _e47 = vBaseColor_1;
+    let _e48 = vTileCtrl_1;
+    let _e50 = calculateColor(_e31.xy, uColorTexture0_, uColorTexture0Sampler, uMaskTexture0_, uMaskTexture0Sampler, uPaintMaskTexture, uPaintMaskTextureSampler, uDestTexture, uDestTextureSampler, uGammaLUT, uGammaLUTSampler, _e33, _e34, _e35, _e36, _e37, _e38, _e39, i32(_e40), _e42, _e43, _e44, _e45, _e46, _e47, i32(_e48));
+    oFragColor = _e50;
+    return;
+}
+
+@fragment 
+fn main(@location(0) vMaskTexCoord0_: vec3<f32>, @location(1) vColorTexCoord0_: vec2<f32>, @location(2) vColorTexRect0_: vec4<f32>, @location(3) vBaseColor: vec4<f32>, @location(4) vTileCtrl: f32, @location(5) vFilterParams0_: vec4<f32>, @location(6) vFilterParams1_: vec4<f32>, @location(7) vFilterParams2_: vec4<f32>, @location(8) vPaintMaskTexCoord: vec2<f32>, @location(9) vPaintMaskTexRect: vec4<f32>, @location(10) vCtrl: f32, @builtin(position) param: vec4<f32>) -> FragmentOutput {
+    vMaskTexCoord0_1 = vMaskTexCoord0_;
+    vColorTexCoord0_1 = vColorTexCoord0_;
+    vColorTexRect0_1 = vColorTexRect0_;
+    vBaseColor_1 = vBaseColor;
+    vTileCtrl_1 = vTileCtrl;
+    vFilterParams0_1 = vFilterParams0_;
+    vFilterParams1_1 = vFilterParams1_;
+    vFilterParams2_1 = vFilterParams2_;
+    vPaintMaskTexCoord_1 = vPaintMaskTexCoord;
+    vPaintMaskTexRect_1 = vPaintMaskTexRect;
+    vCtrl_1 = vCtrl;
+    gl_FragCoord = param;
+    main_1();
+    let _e25 = oFragColor;
+    return FragmentOutput(_e25);
+}
@@ -0,0 +1,275 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uTransform: mat4x4<f32>,
+    uTileSize: vec2<f32>,
+    uTextureMetadataSize: vec2<i32>,
+    uZBufferSize: vec2<i32>,
+}
+
+struct VertexOutput {
+    @location(0) vMaskTexCoord0_: vec3<f32>,
+    @location(1) vColorTexCoord0_: vec2<f32>,
+    @location(2) vColorTexRect0_: vec4<f32>,
+    @location(3) vBaseColor: vec4<f32>,
+    @location(4) vTileCtrl: f32,
+    @location(5) vFilterParams0_: vec4<f32>,
+    @location(6) vFilterParams1_: vec4<f32>,
+    @location(7) vFilterParams2_: vec4<f32>,
+    @location(8) vPaintMaskTexCoord: vec2<f32>,
+    @location(9) vPaintMaskTexRect: vec4<f32>,
+    @location(10) vCtrl: f32,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(1) @binding(1) 
+var uTextureMetadata: texture_2d<f32>;
+@group(1) @binding(2) 
+var uTextureMetadataSampler: sampler;
+@group(1) @binding(3) 
+var uZBuffer: texture_2d<f32>;
+@group(1) @binding(4) 
+var uZBufferSampler: sampler;
+var<private> aTileOffset_1: vec2<i32>;
+var<private> aTileOrigin_1: vec2<i32>;
+var<private> aMaskTexCoord0_1: vec4<u32>;
+var<private> aCtrlBackdrop_1: vec2<i32>;
+var<private> aPathIndex_1: i32;
+var<private> aColor_1: i32;
+var<private> vMaskTexCoord0_: vec3<f32>;
+var<private> vColorTexCoord0_: vec2<f32>;
+var<private> vColorTexRect0_: vec4<f32>;
+var<private> vBaseColor: vec4<f32>;
+var<private> vTileCtrl: f32;
+var<private> vFilterParams0_: vec4<f32>;
+var<private> vFilterParams1_: vec4<f32>;
+var<private> vFilterParams2_: vec4<f32>;
+var<private> vPaintMaskTexCoord: vec2<f32>;
+var<private> vPaintMaskTexRect: vec4<f32>;
+var<private> vCtrl: f32;
+var<private> gl_Position: vec4<f32>;
+
+fn fetchUnscaled(srcTexture: texture_2d<f32>, srcTextureSampler: sampler, scale: vec2<f32>, originCoord: vec2<f32>, entry: i32) -> vec4<f32> {
+    var scale_1: vec2<f32>;
+    var originCoord_1: vec2<f32>;
+    var entry_1: i32;
+
+    scale_1 = scale;
+    originCoord_1 = originCoord;
+    entry_1 = entry;
+    let _e8 = originCoord_1;
+    let _e12 = entry_1;
+    let _e17 = scale_1;
+    let _e20 = textureSampleLevel(srcTexture, srcTextureSampler, (((_e8 + vec2(0.5f)) + vec2<f32>(f32(_e12), 0f)) * _e17), 0f);
+    return _e20;
+}
+
+fn computeTileVaryings(position: vec2<f32>, colorEntry: i32, textureMetadata: texture_2d<f32>, textureMetadataSampler: sampler, textureMetadataSize: vec2<i32>, outColorTexCoord0_: ptr<function, vec2<f32>>, outColorTexRect0_: ptr<function, vec4<f32>>, outBaseColor: ptr<function, vec4<f32>>, outFilterParams0_: ptr<function, vec4<f32>>, outFilterParams1_: ptr<function, vec4<f32>>, outFilterParams2_: ptr<function, vec4<f32>>, outPaintMaskTexCoord: ptr<function, vec2<f32>>, outPaintMaskTexRect: ptr<function, vec4<f32>>, outCtrl: ptr<function, i32>) {
+    var position_1: vec2<f32>;
+    var colorEntry_1: i32;
+    var textureMetadataSize_1: vec2<i32>;
+    var metadataScale: vec2<f32>;
+    var metadataEntryCoord: vec2<f32>;
+    var colorTexMatrix0_: vec4<f32>;
+    var colorTexOffsets: vec4<f32>;
+    var baseColor: vec4<f32>;
+    var filterParams0_: vec4<f32>;
+    var filterParams1_: vec4<f32>;
+    var filterParams2_: vec4<f32>;
+    var extra: vec4<f32>;
+    var colorTexRect0_: vec4<f32>;
+    var paintMaskMatrix: vec4<f32>;
+    var paintMaskRect: vec4<f32>;
+
+    position_1 = position;
+    colorEntry_1 = colorEntry;
+    textureMetadataSize_1 = textureMetadataSize;
+    let _e19 = textureMetadataSize_1;
+    metadataScale = (vec2(1f) / vec2<f32>(_e19));
+    let _e23 = colorEntry_1;
+    let _e28 = colorEntry_1;
+    metadataEntryCoord = vec2<f32>(f32(((_e23 % 128i) * 10i)), f32((_e28 / 128i)));
+    let _e35 = metadataScale;
+    let _e36 = metadataEntryCoord;
+    let _e38 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e35, _e36, 0i);
+    colorTexMatrix0_ = _e38;
+    let _e40 = metadataScale;
+    let _e41 = metadataEntryCoord;
+    let _e43 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e40, _e41, 1i);
+    colorTexOffsets = _e43;
+    let _e45 = metadataScale;
+    let _e46 = metadataEntryCoord;
+    let _e48 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e45, _e46, 2i);
+    baseColor = _e48;
+    let _e50 = metadataScale;
+    let _e51 = metadataEntryCoord;
+    let _e53 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e50, _e51, 3i);
+    filterParams0_ = _e53;
+    let _e55 = metadataScale;
+    let _e56 = metadataEntryCoord;
+    let _e58 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e55, _e56, 4i);
+    filterParams1_ = _e58;
+    let _e60 = metadataScale;
+    let _e61 = metadataEntryCoord;
+    let _e63 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e60, _e61, 5i);
+    filterParams2_ = _e63;
+    let _e65 = metadataScale;
+    let _e66 = metadataEntryCoord;
+    let _e68 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e65, _e66, 6i);
+    extra = _e68;
+    let _e70 = metadataScale;
+    let _e71 = metadataEntryCoord;
+    let _e73 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e70, _e71, 7i);
+    colorTexRect0_ = _e73;
+    let _e75 = metadataScale;
+    let _e76 = metadataEntryCoord;
+    let _e78 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e75, _e76, 8i);
+    paintMaskMatrix = _e78;
+    let _e80 = metadataScale;
+    let _e81 = metadataEntryCoord;
+    let _e83 = fetchUnscaled(textureMetadata, textureMetadataSampler, _e80, _e81, 9i);
+    paintMaskRect = _e83;
+    let _e85 = colorTexMatrix0_;
+    let _e86 = _e85.xy;
+    let _e87 = colorTexMatrix0_;
+    let _e88 = _e87.zw;
+    let _e96 = position_1;
+    let _e98 = colorTexOffsets;
+    (*outColorTexCoord0_) = ((mat2x2<f32>(vec2<f32>(_e86.x, _e86.y), vec2<f32>(_e88.x, _e88.y)) * _e96) + _e98.xy);
+    let _e101 = colorTexRect0_;
+    (*outColorTexRect0_) = _e101;
+    let _e102 = paintMaskMatrix;
+    let _e103 = _e102.xy;
+    let _e104 = paintMaskMatrix;
+    let _e105 = _e104.zw;
+    let _e113 = position_1;
+    let _e115 = colorTexOffsets;
+    (*outPaintMaskTexCoord) = ((mat2x2<f32>(vec2<f32>(_e103.x, _e103.y), vec2<f32>(_e105.x, _e105.y)) * _e113) + _e115.zw);
+    let _e118 = paintMaskRect;
+    (*outPaintMaskTexRect) = _e118;
+    let _e119 = baseColor;
+    (*outBaseColor) = _e119;
+    let _e120 = filterParams0_;
+    (*outFilterParams0_) = _e120;
+    let _e121 = filterParams1_;
+    (*outFilterParams1_) = _e121;
+    let _e122 = filterParams2_;
+    (*outFilterParams2_) = _e122;
+    let _e123 = extra;
+    (*outCtrl) = i32(_e123.x);
+    return;
+}
+
+fn main_1() {
+    var tileOrigin: vec2<f32>;
+    var tileOffset: vec2<f32>;
+    var position_2: vec2<f32>;
+    var zValue: vec4<i32>;
+    var maskTileCoord: vec2<u32>;
+    var maskTexCoord0_: vec2<f32>;
+    var ctrl: i32;
+    var local: vec2<f32>;
+    var local_1: vec4<f32>;
+    var local_2: vec4<f32>;
+    var local_3: vec4<f32>;
+    var local_4: vec4<f32>;
+    var local_5: vec4<f32>;
+    var local_6: vec2<f32>;
+    var local_7: vec4<f32>;
+
+    let _e29 = aTileOrigin_1;
+    tileOrigin = vec2<f32>(_e29);
+    let _e32 = aTileOffset_1;
+    tileOffset = vec2<f32>(_e32);
+    let _e35 = tileOrigin;
+    let _e36 = tileOffset;
+    let _e38 = global.uTileSize;
+    position_2 = ((_e35 + _e36) * _e38);
+    let _e41 = tileOrigin;
+    let _e45 = global.uZBufferSize;
+    let _e49 = textureSampleLevel(uZBuffer, uZBufferSampler, ((_e41 + vec2(0.5f)) / vec2<f32>(_e45)), 0f);
+    zValue = vec4<i32>((_e49 * 255f));
+    let _e54 = aPathIndex_1;
+    let _e55 = zValue;
+    let _e57 = zValue;
+    let _e62 = zValue;
+    let _e67 = zValue;
+    if (_e54 < (((_e55.x | (_e57.y << 8u)) | (_e62.z << 16u)) | (_e67.w << 24u))) {
+        {
+            gl_Position = vec4(0f);
+            return;
+        }
+    }
+    let _e76 = aMaskTexCoord0_1;
+    let _e78 = aMaskTexCoord0_1;
+    let _e81 = aMaskTexCoord0_1;
+    maskTileCoord = vec2<u32>(_e76.x, (_e78.y + (256u * _e81.z)));
+    let _e87 = maskTileCoord;
+    let _e89 = tileOffset;
+    let _e91 = global.uTileSize;
+    maskTexCoord0_ = ((vec2<f32>(_e87) + _e89) * _e91);
+    let _e94 = aCtrlBackdrop_1;
+    let _e98 = aMaskTexCoord0_1;
+    if ((_e94.y == 0i) && (_e98.w != 0u)) {
+        {
+            gl_Position = vec4(0f);
+            return;
+        }
+    }
+    let _e106 = position_2;
+    let _e107 = aColor_1;
+    let _e108 = global.uTextureMetadataSize;
+    computeTileVaryings(_e106, _e107, uTextureMetadata, uTextureMetadataSampler, _e108, (&local), (&local_1), (&local_2), (&local_3), (&local_4), (&local_5), (&local_6), (&local_7), (&ctrl));
+    let _e117 = local;
+    vColorTexCoord0_ = _e117;
+    let _e118 = local_1;
+    vColorTexRect0_ = _e118;
+    let _e119 = local_2;
+    vBaseColor = _e119;
+    let _e120 = local_3;
+    vFilterParams0_ = _e120;
+    let _e121 = local_4;
+    vFilterParams1_ = _e121;
+    let _e122 = local_5;
+    vFilterParams2_ = _e122;
+    let _e123 = local_6;
+    vPaintMaskTexCoord = _e123;
+    let _e124 = local_7;
+    vPaintMaskTexRect = _e124;
+    let _e125 = aCtrlBackdrop_1;
+    vTileCtrl = f32(_e125.x);
+    let _e128 = ctrl;
+    vCtrl = f32(_e128);
+    let _e130 = maskTexCoord0_;
+    let _e131 = aCtrlBackdrop_1;
+    vMaskTexCoord0_ = vec3<f32>(_e130.x, _e130.y, f32(_e131.y));
+    let _e137 = global.uTransform;
+    let _e138 = position_2;
+    gl_Position = (_e137 * vec4<f32>(_e138.x, _e138.y, 0f, 1f));
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aTileOffset: vec2<i32>, @location(1) @interpolate(flat) aTileOrigin: vec2<i32>, @location(2) @interpolate(flat) aMaskTexCoord0_: vec4<u32>, @location(3) @interpolate(flat) aCtrlBackdrop: vec2<i32>, @location(4) @interpolate(flat) aPathIndex: i32, @location(5) @interpolate(flat) aColor: i32) -> VertexOutput {
+    aTileOffset_1 = aTileOffset;
+    aTileOrigin_1 = aTileOrigin;
+    aMaskTexCoord0_1 = aMaskTexCoord0_;
+    aCtrlBackdrop_1 = aCtrlBackdrop;
+    aPathIndex_1 = aPathIndex;
+    aColor_1 = aColor;
+    main_1();
+    let _e13 = vMaskTexCoord0_;
+    let _e15 = vColorTexCoord0_;
+    let _e17 = vColorTexRect0_;
+    let _e19 = vBaseColor;
+    let _e21 = vTileCtrl;
+    let _e23 = vFilterParams0_;
+    let _e25 = vFilterParams1_;
+    let _e27 = vFilterParams2_;
+    let _e29 = vPaintMaskTexCoord;
+    let _e31 = vPaintMaskTexRect;
+    let _e33 = vCtrl;
+    let _e35 = gl_Position;
+    return VertexOutput(_e13, _e15, _e17, _e19, _e21, _e23, _e25, _e27, _e29, _e31, _e33, _e35);
+}
@@ -0,0 +1,36 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uSrc: texture_2d<f32>;
+@group(1) @binding(1) 
+var uSrcSampler: sampler;
+var<private> vTexCoord0_1: vec2<f32>;
+var<private> vBackdrop0_1: f32;
+var<private> vTexCoord1_1: vec2<f32>;
+var<private> vBackdrop1_1: f32;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    let _e7 = vTexCoord0_1;
+    let _e8 = textureSample(uSrc, uSrcSampler, _e7);
+    let _e9 = vBackdrop0_1;
+    let _e13 = vTexCoord1_1;
+    let _e14 = textureSample(uSrc, uSrcSampler, _e13);
+    let _e15 = vBackdrop1_1;
+    oFragColor = min(abs((_e8 + vec4(_e9))), abs((_e14 + vec4(_e15))));
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord0_: vec2<f32>, @location(1) vBackdrop0_: f32, @location(2) vTexCoord1_: vec2<f32>, @location(3) vBackdrop1_: f32) -> FragmentOutput {
+    vTexCoord0_1 = vTexCoord0_;
+    vBackdrop0_1 = vBackdrop0_;
+    vTexCoord1_1 = vTexCoord1_;
+    vBackdrop1_1 = vBackdrop1_;
+    main_1();
+    let _e9 = oFragColor;
+    return FragmentOutput(_e9);
+}
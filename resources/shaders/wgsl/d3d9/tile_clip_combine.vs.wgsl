@@ -0,0 +1,79 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord0_: vec2<f32>,
+    @location(1) vBackdrop0_: f32,
+    @location(2) vTexCoord1_: vec2<f32>,
+    @location(3) vBackdrop1_: f32,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aTileOffset_1: vec2<i32>;
+var<private> aDestTileIndex_1: i32;
+var<private> aDestBackdrop_1: i32;
+var<private> aSrcTileIndex_1: i32;
+var<private> aSrcBackdrop_1: i32;
+var<private> vTexCoord0_: vec2<f32>;
+var<private> vBackdrop0_: f32;
+var<private> vTexCoord1_: vec2<f32>;
+var<private> vBackdrop1_: f32;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var destPosition: vec2<f32>;
+    var srcPosition: vec2<f32>;
+
+    let _e11 = aDestTileIndex_1;
+    let _e14 = aDestTileIndex_1;
+    let _e18 = aTileOffset_1;
+    destPosition = vec2<f32>((vec2<i32>((_e11 % 256i), (_e14 / 256i)) + _e18));
+    let _e22 = aSrcTileIndex_1;
+    let _e25 = aSrcTileIndex_1;
+    let _e29 = aTileOffset_1;
+    srcPosition = vec2<f32>((vec2<i32>((_e22 % 256i), (_e25 / 256i)) + _e29));
+    let _e33 = destPosition;
+    let _e37 = global.uFramebufferSize;
+    destPosition = (_e33 * (vec2<f32>(16f, 4f) / _e37));
+    let _e40 = srcPosition;
+    let _e44 = global.uFramebufferSize;
+    srcPosition = (_e40 * (vec2<f32>(16f, 4f) / _e44));
+    let _e47 = destPosition;
+    vTexCoord0_ = _e47;
+    let _e48 = srcPosition;
+    vTexCoord1_ = _e48;
+    let _e49 = aDestBackdrop_1;
+    vBackdrop0_ = f32(_e49);
+    let _e51 = aSrcBackdrop_1;
+    vBackdrop1_ = f32(_e51);
+    let _e53 = aDestTileIndex_1;
+    if (_e53 < 0i) {
+        destPosition = vec2(0f);
+    }
+    let _e60 = destPosition;
+    destPosition.y = (1f - _e60.y);
+    let _e68 = destPosition;
+    let _e69 = mix(vec2(-1f), vec2(1f), _e68);
+    gl_Position = vec4<f32>(_e69.x, _e69.y, 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aTileOffset: vec2<i32>, @location(1) @interpolate(flat) aDestTileIndex: i32, @location(2) @interpolate(flat) aDestBackdrop: i32, @location(3) @interpolate(flat) aSrcTileIndex: i32, @location(4) @interpolate(flat) aSrcBackdrop: i32) -> VertexOutput {
+    aTileOffset_1 = aTileOffset;
+    aDestTileIndex_1 = aDestTileIndex;
+    aDestBackdrop_1 = aDestBackdrop;
+    aSrcTileIndex_1 = aSrcTileIndex;
+    aSrcBackdrop_1 = aSrcBackdrop;
+    main_1();
+    let _e11 = vTexCoord0_;
+    let _e13 = vBackdrop0_;
+    let _e15 = vTexCoord1_;
+    let _e17 = vBackdrop1_;
+    let _e19 = gl_Position;
+    return VertexOutput(_e11, _e13, _e15, _e17, _e19);
+}
@@ -0,0 +1,26 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uSrc: texture_2d<f32>;
+@group(1) @binding(1) 
+var uSrcSampler: sampler;
+var<private> vTexCoord_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    let _e4 = vTexCoord_1;
+    let _e5 = textureSample(uSrc, uSrcSampler, _e4);
+    oFragColor = _e5;
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord: vec2<f32>) -> FragmentOutput {
+    vTexCoord_1 = vTexCoord;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
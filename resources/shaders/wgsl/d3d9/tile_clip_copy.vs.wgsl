@@ -0,0 +1,50 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aTileOffset_1: vec2<i32>;
+var<private> aTileIndex_1: i32;
+var<private> vTexCoord: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e5 = aTileIndex_1;
+    let _e8 = aTileIndex_1;
+    let _e12 = aTileOffset_1;
+    position = vec2<f32>((vec2<i32>((_e5 % 256i), (_e8 / 256i)) + _e12));
+    let _e16 = position;
+    let _e20 = global.uFramebufferSize;
+    position = (_e16 * (vec2<f32>(16f, 4f) / _e20));
+    let _e23 = position;
+    vTexCoord = _e23;
+    let _e24 = aTileIndex_1;
+    if (_e24 < 0i) {
+        position = vec2(0f);
+    }
+    let _e31 = position;
+    position.y = (1f - _e31.y);
+    let _e39 = position;
+    let _e40 = mix(vec2(-1f), vec2(1f), _e39);
+    gl_Position = vec4<f32>(_e40.x, _e40.y, 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aTileOffset: vec2<i32>, @location(1) @interpolate(flat) aTileIndex: i32) -> VertexOutput {
+    aTileOffset_1 = aTileOffset;
+    aTileIndex_1 = aTileIndex;
+    main_1();
+    let _e5 = vTexCoord;
+    let _e7 = gl_Position;
+    return VertexOutput(_e5, _e7);
+}
@@ -0,0 +1,37 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(1) @binding(1) 
+var uSrc: texture_2d<f32>;
+@group(1) @binding(2) 
+var uSrcSampler: sampler;
+var<private> oFragColor: vec4<f32>;
+var<private> gl_FragCoord: vec4<f32>;
+
+fn main_1() {
+    var texCoord: vec2<f32>;
+
+    let _e6 = gl_FragCoord;
+    let _e8 = global.uFramebufferSize;
+    texCoord = (_e6.xy / _e8);
+    let _e11 = texCoord;
+    let _e12 = textureSample(uSrc, uSrcSampler, _e11);
+    oFragColor = _e12;
+    return;
+}
+
+@fragment 
+fn main(@builtin(position) param: vec4<f32>) -> FragmentOutput {
+    gl_FragCoord = param;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
@@ -0,0 +1,34 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uTransform: mat4x4<f32>,
+    uTileSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aTilePosition_1: vec2<i32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e5 = aTilePosition_1;
+    let _e7 = global.uTileSize;
+    position = (vec2<f32>(_e5) * _e7);
+    let _e11 = global.uTransform;
+    let _e12 = position;
+    gl_Position = (_e11 * vec4<f32>(_e12.x, _e12.y, 0f, 1f));
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aTilePosition: vec2<i32>) -> VertexOutput {
+    aTilePosition_1 = aTilePosition;
+    main_1();
+    let _e3 = gl_Position;
+    return VertexOutput(_e3);
+}
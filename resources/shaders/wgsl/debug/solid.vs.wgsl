@@ -0,0 +1,33 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e3 = aPosition_1;
+    let _e5 = global.uFramebufferSize;
+    position = (((vec2<f32>(_e3) / _e5) * 2f) - vec2(1f));
+    let _e14 = position;
+    let _e16 = position;
+    gl_Position = vec4<f32>(_e14.x, -(_e16.y), 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = gl_Position;
+    return VertexOutput(_e3);
+}
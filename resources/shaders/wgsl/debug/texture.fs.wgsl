@@ -0,0 +1,39 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uColor: vec4<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var uTexture: texture_2d<f32>;
+@group(1) @binding(1) 
+var uTextureSampler: sampler;
+@group(1) @binding(2) 
+var<uniform> global: bUniforms;
+var<private> vTexCoord_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    var alpha: f32;
+
+    let _e6 = vTexCoord_1;
+    let _e7 = textureSample(uTexture, uTextureSampler, _e6);
+    let _e9 = global.uColor;
+    alpha = (_e7.x * _e9.w);
+    let _e13 = alpha;
+    let _e14 = global.uColor;
+    let _e15 = _e14.xyz;
+    oFragColor = (_e13 * vec4<f32>(_e15.x, _e15.y, _e15.z, 1f));
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord: vec2<f32>) -> FragmentOutput {
+    vTexCoord_1 = vTexCoord;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
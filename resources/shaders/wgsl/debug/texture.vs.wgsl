@@ -0,0 +1,42 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uFramebufferSize: vec2<f32>,
+    uTextureSize: vec2<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> aTexCoord_1: vec2<i32>;
+var<private> vTexCoord: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e7 = aTexCoord_1;
+    let _e9 = global.uTextureSize;
+    vTexCoord = (vec2<f32>(_e7) / _e9);
+    let _e11 = aPosition_1;
+    let _e13 = global.uFramebufferSize;
+    position = (((vec2<f32>(_e11) / _e13) * 2f) - vec2(1f));
+    let _e22 = position;
+    let _e24 = position;
+    gl_Position = vec4<f32>(_e22.x, -(_e24.y), 0f, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>, @location(1) @interpolate(flat) aTexCoord: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    aTexCoord_1 = aTexCoord;
+    main_1();
+    let _e5 = vTexCoord;
+    let _e7 = gl_Position;
+    return VertexOutput(_e5, _e7);
+}
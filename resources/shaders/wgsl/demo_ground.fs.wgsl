@@ -0,0 +1,43 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uGroundColor: vec4<f32>,
+    uGridlineColor: vec4<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> vTexCoord_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    var texCoordPx: vec2<f32>;
+    var local: vec4<f32>;
+
+    let _e6 = vTexCoord_1;
+    let _e8 = vTexCoord_1;
+    let _e9 = fwidth(_e8);
+    texCoordPx = (fract(_e6) / _e9);
+    let _e12 = texCoordPx;
+    if any((_e12 <= vec2(1f))) {
+        let _e17 = global.uGridlineColor;
+        local = _e17;
+    } else {
+        let _e18 = global.uGroundColor;
+        local = _e18;
+    }
+    let _e20 = local;
+    oFragColor = _e20;
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord: vec2<f32>) -> FragmentOutput {
+    vTexCoord_1 = vTexCoord;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
@@ -0,0 +1,36 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uTransform: mat4x4<f32>,
+    uGridlineCount: i32,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> vTexCoord: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    let _e6 = aPosition_1;
+    let _e7 = global.uGridlineCount;
+    vTexCoord = vec2<f32>((_e6 * _e7));
+    let _e11 = global.uTransform;
+    let _e12 = aPosition_1;
+    let _e15 = aPosition_1;
+    gl_Position = (_e11 * vec4<f32>(vec4<i32>(_e12.x, 0i, _e15.y, 1i)));
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = vTexCoord;
+    let _e5 = gl_Position;
+    return VertexOutput(_e3, _e5);
+}
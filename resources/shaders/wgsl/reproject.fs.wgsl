@@ -0,0 +1,41 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uOldTransform: mat4x4<f32>,
+}
+
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+@group(1) @binding(1) 
+var uTexture: texture_2d<f32>;
+@group(1) @binding(2) 
+var uTextureSampler: sampler;
+var<private> vTexCoord_1: vec2<f32>;
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    var normTexCoord: vec4<f32>;
+    var texCoord: vec2<f32>;
+
+    let _e6 = global.uOldTransform;
+    let _e7 = vTexCoord_1;
+    normTexCoord = (_e6 * vec4<f32>(_e7.x, _e7.y, 0f, 1f));
+    let _e15 = normTexCoord;
+    let _e17 = normTexCoord;
+    texCoord = (((_e15.xy / vec2(_e17.w)) + vec2(1f)) * 0.5f);
+    let _e27 = texCoord;
+    let _e28 = textureSample(uTexture, uTextureSampler, _e27);
+    oFragColor = _e28;
+    return;
+}
+
+@fragment 
+fn main(@location(0) vTexCoord: vec2<f32>) -> FragmentOutput {
+    vTexCoord_1 = vTexCoord;
+    main_1();
+    let _e3 = oFragColor;
+    return FragmentOutput(_e3);
+}
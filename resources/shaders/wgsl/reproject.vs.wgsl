@@ -0,0 +1,39 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct bUniforms {
+    uNewTransform: mat4x4<f32>,
+}
+
+struct VertexOutput {
+    @location(0) vTexCoord: vec2<f32>,
+    @builtin(position) member: vec4<f32>,
+}
+
+@group(1) @binding(0) 
+var<uniform> global: bUniforms;
+var<private> aPosition_1: vec2<i32>;
+var<private> vTexCoord: vec2<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    var position: vec2<f32>;
+
+    let _e4 = aPosition_1;
+    position = vec2<f32>(_e4);
+    let _e7 = position;
+    vTexCoord = _e7;
+    let _e10 = position;
+    position.y = (1f - _e10.y);
+    let _e14 = global.uNewTransform;
+    let _e15 = position;
+    gl_Position = (_e14 * vec4<f32>(_e15.x, _e15.y, 0f, 1f));
+    return;
+}
+
+@vertex 
+fn main(@location(0) @interpolate(flat) aPosition: vec2<i32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = vTexCoord;
+    let _e5 = gl_Position;
+    return VertexOutput(_e3, _e5);
+}
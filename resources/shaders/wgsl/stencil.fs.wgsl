@@ -0,0 +1,18 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct FragmentOutput {
+    @location(0) oFragColor: vec4<f32>,
+}
+
+var<private> oFragColor: vec4<f32>;
+
+fn main_1() {
+    oFragColor = vec4<f32>(1f, 0f, 0f, 1f);
+    return;
+}
+
+@fragment 
+fn main() -> FragmentOutput {
+    main_1();
+    let _e1 = oFragColor;
+    return FragmentOutput(_e1);
+}
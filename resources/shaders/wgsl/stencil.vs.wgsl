@@ -0,0 +1,21 @@
+// Automatically generated from files in pathfinder/shaders/. Do not edit!
+struct VertexOutput {
+    @builtin(position) member: vec4<f32>,
+}
+
+var<private> aPosition_1: vec3<f32>;
+var<private> gl_Position: vec4<f32>;
+
+fn main_1() {
+    let _e2 = aPosition_1;
+    gl_Position = vec4<f32>(_e2.x, _e2.y, _e2.z, 1f);
+    return;
+}
+
+@vertex 
+fn main(@location(0) aPosition: vec3<f32>) -> VertexOutput {
+    aPosition_1 = aPosition;
+    main_1();
+    let _e3 = gl_Position;
+    return VertexOutput(_e3);
+}
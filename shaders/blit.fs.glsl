@@ -17,12 +17,77 @@ precision highp sampler2D;
 #endif
 
 uniform sampler2D uSrc;
+// The color space of `uSrc` and of the destination: 0 is sRGB, 1 is Display P3 and 2 is linear
+// sRGB, matching `ColorSpace` on the CPU side.
+uniform int uSrcColorSpace;
+uniform int uDestColorSpace;
+// How alpha is stored in `uSrc` and wanted in the destination. See the `ALPHA_MODE_` constants.
+uniform int uAlphaMode;
 
 in vec2 vTexCoord;
 
 out vec4 oFragColor;
 
+#define COLOR_SPACE_SRGB            0
+#define COLOR_SPACE_DISPLAY_P3      1
+#define COLOR_SPACE_LINEAR_SRGB     2
+
+// Both premultiplied.
+#define ALPHA_MODE_PREMULTIPLIED    0
+// Both straight.
+#define ALPHA_MODE_STRAIGHT         1
+// Premultiplied source, straight destination.
+#define ALPHA_MODE_UNPREMULTIPLY    2
+// The source's red channel is the alpha of a white destination.
+#define ALPHA_MODE_MASK             3
+
+vec3 decodeSRGB(vec3 color) {
+    vec3 magnitude = abs(color);
+    vec3 decoded = mix(magnitude / 12.92,
+                      pow((magnitude + 0.055) / 1.055, vec3(2.4)),
+                      greaterThan(magnitude, vec3(0.04045)));
+    return sign(color) * decoded;
+}
+
+vec3 encodeSRGB(vec3 color) {
+    vec3 magnitude = abs(color);
+    vec3 encoded = mix(magnitude * 12.92,
+                       1.055 * pow(magnitude, vec3(1.0 / 2.4)) - 0.055,
+                       greaterThan(magnitude, vec3(0.0031308)));
+    return sign(color) * encoded;
+}
+
+vec3 convertColorSpace(vec3 color) {
+    if (uSrcColorSpace != COLOR_SPACE_LINEAR_SRGB)
+        color = decodeSRGB(color);
+    // Each matrix is written row by row, so it's applied with the vector on the left.
+    if (uSrcColorSpace == COLOR_SPACE_DISPLAY_P3) {
+        color = color * mat3( 1.2249401, -0.2249404,  0.0000000,
+                             -0.0420569,  1.0420571,  0.0000000,
+                             -0.0196376, -0.0786361,  1.0982735);
+    }
+    if (uDestColorSpace == COLOR_SPACE_DISPLAY_P3) {
+        color = color * mat3(0.8224621, 0.1775380, 0.0000000,
+                             0.0331941, 0.9668058, 0.0000000,
+                             0.0170827, 0.0723974, 0.9105199);
+    }
+    if (uDestColorSpace != COLOR_SPACE_LINEAR_SRGB)
+        color = encodeSRGB(color);
+    return color;
+}
+
 void main() {
     vec4 color = texture(uSrc, vTexCoord);
+    if (uAlphaMode == ALPHA_MODE_MASK) {
+        color = vec4(vec3(1.0), color.r);
+    } else if (uAlphaMode == ALPHA_MODE_PREMULTIPLIED) {
+        if (uSrcColorSpace != uDestColorSpace && color.a > 0.0)
+            color.rgb = convertColorSpace(color.rgb / color.a) * color.a;
+    } else {
+        if (uAlphaMode == ALPHA_MODE_UNPREMULTIPLY && color.a > 0.0)
+            color.rgb /= color.a;
+        if (uSrcColorSpace != uDestColorSpace)
+            color.rgb = convertColorSpace(color.rgb);
+    }
     oFragColor = color;
 }
@@ -23,7 +23,6 @@
 
 #define BIN_INDIRECT_DRAW_PARAMS_MICROLINE_COUNT_INDEX  3
 
-#define TOLERANCE           0.25
 #define MICROLINE_LENGTH    16.0
 
 precision highp float;
@@ -39,6 +38,8 @@ uniform vec2 uTranslation;
 uniform int uPathCount;
 uniform int uLastBatchSegmentIndex;
 uniform int uMaxMicrolineCount;
+// The maximum distance, in device pixels, between a curve and its flattened lines.
+uniform float uTolerance;
 
 layout(std430, binding = 0) buffer bComputeIndirectParams {
     // [0]: number of x workgroups
@@ -65,6 +66,13 @@ layout(std430, binding = 3) buffer bInputIndices {
     restrict readonly uvec2 iInputIndices[];
 };
 
+// Indexed by batch path index. Each path has two entries: the first holds its 2x2 matrix in
+// column-major order and the second holds its translation in `xy`. This transform is applied
+// before `uTransform`, so that instances of the same outline can share segments.
+layout(std430, binding = 5) buffer bPathTransforms {
+    restrict readonly vec4 iPathTransforms[];
+};
+
 layout(std430, binding = 4) buffer bMicrolines {
     // x: from (X, Y) whole pixels, packed signed 16-bit
     // y: to (X, Y) whole pixels, packed signed 16-bit
@@ -94,7 +102,7 @@ bool curveIsFlat(vec4 baseline, vec4 ctrl) {
     vec4 uv = vec4(3.0) * ctrl - vec4(2.0) * baseline - baseline.zwxy;
     uv *= uv;
     uv = max(uv, uv.zwxy);
-    return uv.x + uv.y <= 16.0 * TOLERANCE * TOLERANCE;
+    return uv.x + uv.y <= 16.0 * uTolerance * uTolerance;
 }
 
 void subdivideCurve(vec4 baseline,
@@ -125,8 +133,8 @@ vec2 sampleLine(vec4 line, float t) {
     return mix(line.xy, line.zw, t);
 }
 
-vec2 getPoint(uint pointIndex) {
-    return uTransform * iPoints[pointIndex] + uTranslation;
+vec2 getPoint(uint pointIndex, mat2 pathTransform, vec2 pathTranslation) {
+    return uTransform * (pathTransform * iPoints[pointIndex] + pathTranslation) + uTranslation;
 }
 
 void main() {
@@ -157,6 +165,10 @@ void main() {
     uint globalSegmentIndex = batchSegmentIndex - firstBatchSegmentIndexInPath +
         firstGlobalSegmentIndexInPath;
 
+    vec4 pathTransformColumns = iPathTransforms[batchPathIndex * 2 + 0];
+    mat2 pathTransform = mat2(pathTransformColumns.xy, pathTransformColumns.zw);
+    vec2 pathTranslation = iPathTransforms[batchPathIndex * 2 + 1].xy;
+
     uvec2 inputIndices = iInputIndices[globalSegmentIndex];
     uint fromPointIndex = inputIndices.x, flagsPathIndex = inputIndices.y;
 
@@ -168,7 +180,8 @@ void main() {
     else
         toPointIndex += 1;
 
-    vec4 baseline = vec4(getPoint(fromPointIndex), getPoint(toPointIndex));
+    vec4 baseline = vec4(getPoint(fromPointIndex, pathTransform, pathTranslation),
+                         getPoint(toPointIndex, pathTransform, pathTranslation));
 
     // Read control points if applicable, and calculate number of segments.
     //
@@ -179,16 +192,16 @@ void main() {
     bool isCurve = (flagsPathIndex & (FLAGS_PATH_INDEX_CURVE_IS_CUBIC |
                                       FLAGS_PATH_INDEX_CURVE_IS_QUADRATIC)) != 0;
     if (isCurve) {
-        vec2 ctrl0 = getPoint(fromPointIndex + 1);
+        vec2 ctrl0 = getPoint(fromPointIndex + 1, pathTransform, pathTranslation);
         if ((flagsPathIndex & FLAGS_PATH_INDEX_CURVE_IS_QUADRATIC) != 0) {
             vec2 ctrl0_2 = ctrl0 * vec2(2.0);
             ctrl = (baseline + (ctrl0 * vec2(2.0)).xyxy) * vec4(1.0 / 3.0);
         } else {
-            ctrl = vec4(ctrl0, getPoint(fromPointIndex + 2));
+            ctrl = vec4(ctrl0, getPoint(fromPointIndex + 2, pathTransform, pathTranslation));
         }
         vec2 bound = vec2(6.0) * max(abs(ctrl.zw - 2.0 * ctrl.xy + baseline.xy),
                                      abs(baseline.zw - 2.0 * ctrl.zw + ctrl.xy));
-        segmentCountF = sqrt(length(bound) / (8.0 * TOLERANCE));
+        segmentCountF = sqrt(length(bound) / (8.0 * uTolerance));
     } else {
         segmentCountF = length(baseline.zw - baseline.xy) / MICROLINE_LENGTH;
     }
@@ -17,7 +17,13 @@ vec4 accumulateCoverageForFillList(int fillIndex, ivec2 tileSubCoord) {
         vec4 lineSegment = vec4(fillFrom & 0xffff, fillFrom >> 16,
                                 fillTo   & 0xffff, fillTo   >> 16) / 256.0;
         lineSegment -= tileFragCoord.xyxy;
-        coverages += computeCoverage(lineSegment.xy, lineSegment.zw, uAreaLUT);
+        if (uSampleCount == 0) {
+            coverages += computeCoverage(lineSegment.xy, lineSegment.zw, uAreaLUT);
+        } else {
+            coverages += computeSupersampledCoverage(lineSegment.xy,
+                                                     lineSegment.zw,
+                                                     uSampleCount);
+        }
         fillIndex = int(iFills[fillIndex * 3 + 2]);
         iteration++;
     } while (fillIndex >= 0 && iteration < 1024);
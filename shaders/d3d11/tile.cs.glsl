@@ -40,10 +40,12 @@ uniform sampler2D uZBuffer;
 uniform ivec2 uZBufferSize;
 uniform sampler2D uColorTexture0;
 uniform sampler2D uMaskTexture0;
+uniform sampler2D uPaintMaskTexture;
 uniform sampler2D uDestTexture;
 uniform sampler2D uGammaLUT;
 uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
+uniform vec2 uPaintMaskTextureSize;
 uniform vec2 uFramebufferSize;
 uniform ivec2 uFramebufferTileSize;
 layout(rgba8) uniform image2D uDestImage;
@@ -116,26 +118,34 @@ void main() {
             vec3 maskTexCoord0 = vec3(vec2(ivec2(maskTileCoord) + tileSubCoord), backdrop);
 
             vec2 colorTexCoord0;
+            vec4 colorTexRect0;
             vec4 baseColor, filterParams0, filterParams1, filterParams2;
+            vec2 paintMaskTexCoord;
+            vec4 paintMaskTexRect;
             int ctrl;
             computeTileVaryings(fragCoord,
                                 int(colorEntry),
                                 uTextureMetadata,
                                 uTextureMetadataSize,
                                 colorTexCoord0,
+                                colorTexRect0,
                                 baseColor,
                                 filterParams0,
                                 filterParams1,
                                 filterParams2,
+                                paintMaskTexCoord,
+                                paintMaskTexRect,
                                 ctrl);
 
             vec4 srcColor = calculateColor(fragCoord,
                                            uColorTexture0,
                                            uMaskTexture0,
+                                           uPaintMaskTexture,
                                            uDestTexture,
                                            uGammaLUT,
                                            uColorTextureSize0,
                                            uMaskTextureSize0,
+                                           uPaintMaskTextureSize,
                                            filterParams0,
                                            filterParams1,
                                            filterParams2,
@@ -143,6 +153,9 @@ void main() {
                                            ctrl,
                                            maskTexCoord0,
                                            colorTexCoord0,
+                                           colorTexRect0,
+                                           paintMaskTexCoord,
+                                           paintMaskTexRect,
                                            baseColor,
                                            tileCtrl);
 
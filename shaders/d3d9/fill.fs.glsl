@@ -21,6 +21,8 @@ precision highp sampler2D;
 #include "fill_area.inc.glsl"
 
 uniform sampler2D uAreaLUT;
+// Zero for analytic coverage; otherwise, the number of point samples per pixel.
+uniform int uSampleCount;
 
 in vec2 vFrom;
 in vec2 vTo;
@@ -28,5 +30,8 @@ in vec2 vTo;
 out vec4 oFragColor;
 
 void main() {
-    oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    if (uSampleCount == 0)
+        oFragColor = computeCoverage(vFrom, vTo, uAreaLUT);
+    else
+        oFragColor = computeSupersampledCoverage(vFrom, vTo, uSampleCount);
 }
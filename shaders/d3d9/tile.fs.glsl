@@ -22,19 +22,24 @@ precision highp sampler2D;
 
 uniform sampler2D uColorTexture0;
 uniform sampler2D uMaskTexture0;
+uniform sampler2D uPaintMaskTexture;
 uniform sampler2D uDestTexture;
 uniform sampler2D uGammaLUT;
 uniform vec2 uColorTextureSize0;
 uniform vec2 uMaskTextureSize0;
+uniform vec2 uPaintMaskTextureSize;
 uniform vec2 uFramebufferSize;
 
 in vec3 vMaskTexCoord0;
 in vec2 vColorTexCoord0;
+in vec4 vColorTexRect0;
 in vec4 vBaseColor;
 in float vTileCtrl;
 in vec4 vFilterParams0;
 in vec4 vFilterParams1;
 in vec4 vFilterParams2;
+in vec2 vPaintMaskTexCoord;
+in vec4 vPaintMaskTexRect;
 in float vCtrl;
 
 out vec4 oFragColor;
@@ -47,10 +52,12 @@ void main() {
     oFragColor = calculateColor(gl_FragCoord.xy,
                                 uColorTexture0,
                                 uMaskTexture0,
+                                uPaintMaskTexture,
                                 uDestTexture,
                                 uGammaLUT,
                                 uColorTextureSize0,
                                 uMaskTextureSize0,
+                                uPaintMaskTextureSize,
                                 vFilterParams0,
                                 vFilterParams1,
                                 vFilterParams2,
@@ -58,6 +65,9 @@ void main() {
                                 int(vCtrl),
                                 vMaskTexCoord0,
                                 vColorTexCoord0,
+                                vColorTexRect0,
+                                vPaintMaskTexCoord,
+                                vPaintMaskTexRect,
                                 vBaseColor,
                                 int(vTileCtrl));
 }
@@ -36,11 +36,14 @@ in int aColor;
 
 out vec3 vMaskTexCoord0;
 out vec2 vColorTexCoord0;
+out vec4 vColorTexRect0;
 out vec4 vBaseColor;
 out float vTileCtrl;
 out vec4 vFilterParams0;
 out vec4 vFilterParams1;
 out vec4 vFilterParams2;
+out vec2 vPaintMaskTexCoord;
+out vec4 vPaintMaskTexRect;
 out float vCtrl;
 
 void main() {
@@ -66,10 +69,13 @@ void main() {
                         uTextureMetadata,
                         uTextureMetadataSize,
                         vColorTexCoord0,
+                        vColorTexRect0,
                         vBaseColor,
                         vFilterParams0,
                         vFilterParams1,
                         vFilterParams2,
+                        vPaintMaskTexCoord,
+                        vPaintMaskTexRect,
                         ctrl);
 
     vTileCtrl = float(aCtrlBackdrop.x);
@@ -25,3 +25,33 @@ vec4 computeCoverage(vec2 from, vec2 to, sampler2D areaLUT) {
     float dX = window.x - window.y;
     return texture(areaLUT, vec2(y + 8.0, abs(d * dX)) / 16.0) * dX;
 }
+
+// Returns the signed coverage of the four pixels in a column by point samples at `sampleOffset`
+// from their centers. Each sample is either entirely inside or entirely outside.
+vec4 computeSampleCoverage(vec2 from, vec2 to, vec2 sampleOffset) {
+    from -= sampleOffset;
+    to -= sampleOffset;
+
+    // Treat the sample column as half-open so that a crossing shared by two lines counts once.
+    if ((from.x <= 0.0) == (to.x <= 0.0))
+        return vec4(0.0);
+
+    float y = mix(from.y, to.y, from.x / (from.x - to.x));
+    return step(vec4(y), vec4(0.0, 1.0, 2.0, 3.0)) * sign(from.x - to.x);
+}
+
+// Averages point-sampled coverage over `sampleCount` samples, which must be a power of two.
+//
+// Sample rows are the base-2 radical inverse of sample columns, which gives an N-rooks pattern.
+// With one sample this is plain aliased rendering, sampling pixel centers.
+vec4 computeSupersampledCoverage(vec2 from, vec2 to, int sampleCount) {
+    vec4 coverage = vec4(0.0);
+    for (int sampleIndex = 0; sampleIndex < sampleCount; sampleIndex++) {
+        int sampleRow = 0;
+        for (int bit = 1; bit < sampleCount; bit <<= 1)
+            sampleRow = (sampleRow << 1) | ((sampleIndex & bit) != 0 ? 1 : 0);
+        vec2 sampleOffset = (vec2(sampleIndex, sampleRow) + 0.5) / float(sampleCount) - 0.5;
+        coverage += computeSampleCoverage(from, to, sampleOffset);
+    }
+    return coverage / float(sampleCount);
+}
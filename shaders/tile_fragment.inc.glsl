@@ -67,9 +67,15 @@
 #define COMBINER_CTRL_COMPOSITE_COLOR           0xe
 #define COMBINER_CTRL_COMPOSITE_LUMINOSITY      0xf
 
+#define COMBINER_CTRL_PAINT_MASK_MASK           0x3
+#define COMBINER_CTRL_PAINT_MASK_ALPHA          0x1
+#define COMBINER_CTRL_PAINT_MASK_LUMINANCE      0x2
+#define COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED  0x4
+
 #define COMBINER_CTRL_COLOR_FILTER_SHIFT        4
 #define COMBINER_CTRL_COLOR_COMBINE_SHIFT       6
 #define COMBINER_CTRL_COMPOSITE_SHIFT           8
+#define COMBINER_CTRL_PAINT_MASK_SHIFT          12
 
 // Color sampling
 
@@ -77,6 +83,27 @@ vec4 sampleColor(sampler2D colorTexture, vec2 colorTexCoord) {
     return texture(colorTexture, colorTexCoord);
 }
 
+// Confines texture coordinates to an image packed into an atlas, since the hardware can only clamp
+// or repeat whole textures. `colorTexRect` holds the origin and size of the image in texels; a
+// negative size clamps along that axis instead of repeating, and a zero size leaves the
+// coordinates alone.
+vec2 wrapColorTexCoord(vec2 colorTexCoord, vec4 colorTexRect, vec2 colorTextureSize) {
+    vec2 size = abs(colorTexRect.zw);
+    if (size.x == 0.0 || size.y == 0.0)
+        return colorTexCoord;
+
+    // The rect is stored in texels so that half precision keeps it exact.
+    vec4 texRect = colorTexRect / colorTextureSize.xyxy;
+    size /= colorTextureSize;
+
+    vec2 offset = colorTexCoord - texRect.xy;
+    offset = mix(offset, mod(offset, size), greaterThan(colorTexRect.zw, vec2(0.0)));
+
+    // Keep bilinear filtering from picking up texels of neighboring images.
+    vec2 halfTexel = vec2(0.5) / colorTextureSize;
+    return texRect.xy + clamp(offset, halfTexel, size - halfTexel);
+}
+
 // Color combining
 
 vec4 combineColor0(vec4 destColor, vec4 srcColor, int op) {
@@ -91,25 +118,25 @@ vec4 combineColor0(vec4 destColor, vec4 srcColor, int op) {
 
 // Text filter
 
-float filterTextSample1Tap(float offset, sampler2D colorTexture, vec2 colorTexCoord) {
-    return texture(colorTexture, colorTexCoord + vec2(offset, 0.0)).r;
+float filterTextSample1Tap(vec2 offset, sampler2D colorTexture, vec2 colorTexCoord) {
+    return texture(colorTexture, colorTexCoord + offset).r;
 }
 
-// Samples 9 taps around the current pixel.
+// Samples 9 taps around the current pixel, along the direction of the subpixel stripes.
 void filterTextSample9Tap(out vec4 outAlphaLeft,
                           out float outAlphaCenter,
                           out vec4 outAlphaRight,
                           sampler2D colorTexture,
                           vec2 colorTexCoord,
                           vec4 kernel,
-                          float onePixel) {
+                          vec2 onePixel) {
     bool wide = kernel.x > 0.0;
     outAlphaLeft =
         vec4(wide ? filterTextSample1Tap(-4.0 * onePixel, colorTexture, colorTexCoord) : 0.0,
              filterTextSample1Tap(-3.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(-2.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(-1.0 * onePixel, colorTexture, colorTexCoord));
-    outAlphaCenter = filterTextSample1Tap(0.0, colorTexture, colorTexCoord);
+    outAlphaCenter = filterTextSample1Tap(vec2(0.0), colorTexture, colorTexCoord);
     outAlphaRight =
         vec4(filterTextSample1Tap(1.0 * onePixel, colorTexture, colorTexCoord),
              filterTextSample1Tap(2.0 * onePixel, colorTexture, colorTexCoord),
@@ -135,7 +162,7 @@ vec3 filterTextGammaCorrect(vec3 bgColor, vec3 fgColor, sampler2D gammaLUT) {
 //                | x          y          z          w
 //  --------------+--------------------------------------------------------
 //  filterParams0 | kernel[0]  kernel[1]  kernel[2]  kernel[3]
-//  filterParams1 | bgColor.r  bgColor.g  bgColor.b  -
+//  filterParams1 | bgColor.r  bgColor.g  bgColor.b  subpixelLayout
 //  filterParams2 | fgColor.r  fgColor.g  fgColor.b  gammaCorrectionEnabled
 vec4 filterText(vec2 colorTexCoord,
                 sampler2D colorTexture,
@@ -150,6 +177,10 @@ vec4 filterText(vec2 colorTexCoord,
     vec3 fgColor = filterParams2.rgb;
     bool gammaCorrectionEnabled = filterParams2.a != 0.0;
 
+    // The subpixel layout is one of RGB (0), BGR (1), VRGB (2), or VBGR (3).
+    bool bgrLayout = filterParams1.a == 1.0 || filterParams1.a == 3.0;
+    bool verticalLayout = filterParams1.a >= 2.0;
+
     // Apply defringing if necessary.
     vec3 alpha;
     if (kernel.w == 0.0) {
@@ -163,7 +194,8 @@ vec4 filterText(vec2 colorTexCoord,
                              colorTexture,
                              colorTexCoord,
                              kernel,
-                             1.0 / colorTextureSize.x);
+                             verticalLayout ? vec2(0.0, 1.0 / colorTextureSize.y) :
+                                              vec2(1.0 / colorTextureSize.x, 0.0));
 
         float r = filterTextConvolve7Tap(alphaLeft, vec3(alphaCenter, alphaRight.xy), kernel);
         float g = filterTextConvolve7Tap(vec4(alphaLeft.yzw, alphaCenter), alphaRight.xyz, kernel);
@@ -171,7 +203,7 @@ vec4 filterText(vec2 colorTexCoord,
                                          alphaRight.yzw,
                                          kernel);
 
-        alpha = vec3(r, g, b);
+        alpha = bgrLayout ? vec3(b, g, r) : vec3(r, g, b);
     }
 
     // Apply gamma correction if necessary.
@@ -536,13 +568,34 @@ float sampleMask(float maskAlpha,
 
 // Main function
 
+// Paint masks
+
+// Returns how much of the paint a mask drawn from an image or render target lets through.
+float samplePaintMask(sampler2D paintMaskTexture,
+                      vec2 paintMaskTextureSize,
+                      vec2 paintMaskTexCoord,
+                      vec4 paintMaskTexRect,
+                      int paintMaskCtrl) {
+    vec2 texCoord = wrapColorTexCoord(paintMaskTexCoord, paintMaskTexRect, paintMaskTextureSize);
+    vec4 texel = texture(paintMaskTexture, texCoord);
+    if ((paintMaskCtrl & COMBINER_CTRL_PAINT_MASK_MASK) != COMBINER_CTRL_PAINT_MASK_LUMINANCE)
+        return texel.a;
+
+    // Unpremultiply render target texels, so that alpha only scales the luminance once.
+    if ((paintMaskCtrl & COMBINER_CTRL_PAINT_MASK_PREMULTIPLIED) != 0)
+        texel.rgb = texel.a > 0.0 ? texel.rgb / texel.a : vec3(0.0);
+    return dot(texel.rgb, vec3(0.2125, 0.7154, 0.0721)) * texel.a;
+}
+
 vec4 calculateColor(vec2 fragCoord,
                     sampler2D colorTexture0,
                     sampler2D maskTexture0,
+                    sampler2D paintMaskTexture,
                     sampler2D destTexture,
                     sampler2D gammaLUT,
                     vec2 colorTextureSize0,
                     vec2 maskTextureSize0,
+                    vec2 paintMaskTextureSize,
                     vec4 filterParams0,
                     vec4 filterParams1,
                     vec4 filterParams2,
@@ -550,6 +603,9 @@ vec4 calculateColor(vec2 fragCoord,
                     int ctrl,
                     vec3 maskTexCoord0,
                     vec2 colorTexCoord0,
+                    vec4 colorTexRect0,
+                    vec2 paintMaskTexCoord,
+                    vec4 paintMaskTexRect,
                     vec4 baseColor,
                     int tileCtrl) {
     // Sample mask.
@@ -557,13 +613,24 @@ vec4 calculateColor(vec2 fragCoord,
     float maskAlpha = 1.0;
     maskAlpha = sampleMask(maskAlpha, maskTexture0, maskTextureSize0, maskTexCoord0, maskCtrl0);
 
+    // Sample paint mask.
+    int paintMaskCtrl = ctrl >> COMBINER_CTRL_PAINT_MASK_SHIFT;
+    if ((paintMaskCtrl & COMBINER_CTRL_PAINT_MASK_MASK) != 0) {
+        maskAlpha *= samplePaintMask(paintMaskTexture,
+                                     paintMaskTextureSize,
+                                     paintMaskTexCoord,
+                                     paintMaskTexRect,
+                                     paintMaskCtrl);
+    }
+
     // Sample color.
     vec4 color = baseColor;
     int color0Combine = (ctrl >> COMBINER_CTRL_COLOR_COMBINE_SHIFT) &
         COMBINER_CTRL_COLOR_COMBINE_MASK;
     if (color0Combine != 0) {
         int color0Filter = (ctrl >> COMBINER_CTRL_COLOR_FILTER_SHIFT) & COMBINER_CTRL_FILTER_MASK;
-        vec4 color0 = filterColor(colorTexCoord0,
+        vec2 colorTexCoord = wrapColorTexCoord(colorTexCoord0, colorTexRect0, colorTextureSize0);
+        vec4 color0 = filterColor(colorTexCoord,
                                   colorTexture0,
                                   gammaLUT,
                                   colorTextureSize0,
@@ -17,13 +17,16 @@ void computeTileVaryings(vec2 position,
                          sampler2D textureMetadata,
                          ivec2 textureMetadataSize,
                          out vec2 outColorTexCoord0,
+                         out vec4 outColorTexRect0,
                          out vec4 outBaseColor,
                          out vec4 outFilterParams0,
                          out vec4 outFilterParams1,
                          out vec4 outFilterParams2,
+                         out vec2 outPaintMaskTexCoord,
+                         out vec4 outPaintMaskTexRect,
                          out int outCtrl) {
     vec2 metadataScale = vec2(1.0) / vec2(textureMetadataSize);
-    vec2 metadataEntryCoord = vec2(colorEntry % 128 * 8, colorEntry / 128);
+    vec2 metadataEntryCoord = vec2(colorEntry % 128 * 10, colorEntry / 128);
     vec4 colorTexMatrix0 = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 0);
     vec4 colorTexOffsets = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 1);
     vec4 baseColor       = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 2);
@@ -31,7 +34,13 @@ void computeTileVaryings(vec2 position,
     vec4 filterParams1   = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 4);
     vec4 filterParams2   = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 5);
     vec4 extra           = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 6);
+    vec4 colorTexRect0   = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 7);
+    vec4 paintMaskMatrix = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 8);
+    vec4 paintMaskRect   = fetchUnscaled(textureMetadata, metadataScale, metadataEntryCoord, 9);
     outColorTexCoord0 = mat2(colorTexMatrix0) * position + colorTexOffsets.xy;
+    outColorTexRect0 = colorTexRect0;
+    outPaintMaskTexCoord = mat2(paintMaskMatrix) * position + colorTexOffsets.zw;
+    outPaintMaskTexRect = paintMaskRect;
     outBaseColor = baseColor;
     outFilterParams0 = filterParams0;
     outFilterParams1 = filterParams1;
@@ -0,0 +1,183 @@
+// pathfinder/svg/src/diagnostic.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports about SVG content that could not be imported faithfully.
+
+use crate::BuildResultFlags;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use usvg::NodeKind;
+
+/// Describes one SVG element that uses a feature Pathfinder doesn't support.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ImportDiagnostic {
+    /// The `id` attribute of the offending element, or the empty string if it has none.
+    pub element_id: String,
+    /// The SVG element name, such as `path` or `linearGradient`.
+    pub node_kind: &'static str,
+    /// What was unsupported.
+    pub feature: UnsupportedFeature,
+    /// How badly the output is affected.
+    pub severity: Severity,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum UnsupportedFeature {
+    FilterNode,
+    ImageNode,
+    MaskNode,
+    PatternNode,
+    MultipleClipPaths,
+    LinkPaint,
+    FilterAttr,
+    MaskAttr,
+    GradientSpreadMethod,
+    TextRendering,
+    MissingElement,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Severity {
+    /// The element was rendered, but only approximately.
+    Warning,
+    /// Some of the element's content was dropped.
+    Error,
+}
+
+/// Whether unsupported features abort the import.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImportMode {
+    /// Unsupported features are skipped or approximated and reported as diagnostics.
+    Lenient,
+    /// Any unsupported feature causes the import to fail.
+    Strict,
+}
+
+impl Default for ImportMode {
+    #[inline]
+    fn default() -> ImportMode {
+        ImportMode::Lenient
+    }
+}
+
+/// The error returned when a strict import encounters unsupported features.
+#[derive(Clone, Debug)]
+pub struct SVGImportError {
+    pub diagnostics: Vec<ImportDiagnostic>,
+}
+
+impl ImportDiagnostic {
+    pub(crate) fn new(element_id: &str, node_kind: &'static str, feature: UnsupportedFeature)
+                      -> ImportDiagnostic {
+        ImportDiagnostic {
+            element_id: element_id.to_owned(),
+            node_kind,
+            feature,
+            severity: feature.severity(),
+        }
+    }
+}
+
+impl UnsupportedFeature {
+    pub fn severity(self) -> Severity {
+        match self {
+            UnsupportedFeature::MultipleClipPaths |
+            UnsupportedFeature::FilterAttr |
+            UnsupportedFeature::MaskAttr |
+            UnsupportedFeature::GradientSpreadMethod => Severity::Warning,
+            UnsupportedFeature::FilterNode |
+            UnsupportedFeature::ImageNode |
+            UnsupportedFeature::MaskNode |
+            UnsupportedFeature::PatternNode |
+            UnsupportedFeature::LinkPaint |
+            UnsupportedFeature::TextRendering |
+            UnsupportedFeature::MissingElement => Severity::Error,
+        }
+    }
+
+    /// Returns the legacy flag corresponding to this feature.
+    pub fn to_flag(self) -> BuildResultFlags {
+        match self {
+            UnsupportedFeature::FilterNode => BuildResultFlags::UNSUPPORTED_FILTER_NODE,
+            UnsupportedFeature::ImageNode => BuildResultFlags::UNSUPPORTED_IMAGE_NODE,
+            UnsupportedFeature::MaskNode => BuildResultFlags::UNSUPPORTED_MASK_NODE,
+            UnsupportedFeature::PatternNode => BuildResultFlags::UNSUPPORTED_PATTERN_NODE,
+            UnsupportedFeature::MultipleClipPaths => {
+                BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS
+            }
+            UnsupportedFeature::LinkPaint => BuildResultFlags::UNSUPPORTED_LINK_PAINT,
+            UnsupportedFeature::FilterAttr => BuildResultFlags::UNSUPPORTED_FILTER_ATTR,
+            UnsupportedFeature::MaskAttr => BuildResultFlags::UNSUPPORTED_MASK_ATTR,
+            UnsupportedFeature::GradientSpreadMethod => {
+                BuildResultFlags::UNSUPPORTED_GRADIENT_SPREAD_METHOD
+            }
+            UnsupportedFeature::TextRendering => BuildResultFlags::TEXT_RENDERING_FAILED,
+            UnsupportedFeature::MissingElement => BuildResultFlags::MISSING_ELEMENT,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            UnsupportedFeature::FilterNode => "<filter>",
+            UnsupportedFeature::ImageNode => "<image>",
+            UnsupportedFeature::MaskNode => "<mask>",
+            UnsupportedFeature::PatternNode => "<pattern>",
+            UnsupportedFeature::MultipleClipPaths => "multiple clip paths",
+            UnsupportedFeature::LinkPaint => "non-color paint",
+            UnsupportedFeature::FilterAttr => "filter attribute",
+            UnsupportedFeature::MaskAttr => "mask attribute",
+            UnsupportedFeature::GradientSpreadMethod => "gradient spread method",
+            UnsupportedFeature::TextRendering => "text rendering failure",
+            UnsupportedFeature::MissingElement => "missing element",
+        }
+    }
+}
+
+impl Display for ImportDiagnostic {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(formatter, "{}: {} (<{}", severity, self.feature.description(), self.node_kind)?;
+        if !self.element_id.is_empty() {
+            write!(formatter, " id=\"{}\"", self.element_id)?;
+        }
+        formatter.write_str(">)")
+    }
+}
+
+impl Display for SVGImportError {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        write!(formatter, "SVG uses {} unsupported feature(s)", self.diagnostics.len())?;
+        for diagnostic in &self.diagnostics {
+            write!(formatter, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for SVGImportError {}
+
+pub(crate) fn node_kind_name(node_kind: &NodeKind) -> &'static str {
+    match *node_kind {
+        NodeKind::Svg(..) => "svg",
+        NodeKind::Defs => "defs",
+        NodeKind::LinearGradient(..) => "linearGradient",
+        NodeKind::RadialGradient(..) => "radialGradient",
+        NodeKind::ClipPath(..) => "clipPath",
+        NodeKind::Mask(..) => "mask",
+        NodeKind::Pattern(..) => "pattern",
+        NodeKind::Filter(..) => "filter",
+        NodeKind::Path(..) => "path",
+        NodeKind::Image(..) => "image",
+        NodeKind::Group(..) => "g",
+    }
+}
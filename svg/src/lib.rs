@@ -19,6 +19,7 @@ use pathfinder_content::dash::OutlineDash;
 use pathfinder_content::fill::FillRule;
 use pathfinder_content::gradient::{ColorStop, Gradient};
 use pathfinder_content::outline::Outline;
+use pathfinder_content::render_target::RenderTargetId;
 use pathfinder_content::segment::{Segment, SegmentFlags};
 use pathfinder_content::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
 use pathfinder_content::transform::Transform2FPathIter;
@@ -27,21 +28,40 @@ use pathfinder_geometry::rect::RectF;
 use pathfinder_geometry::transform2d::Transform2F;
 use pathfinder_geometry::vector::{Vector2F, vec2f};
 use pathfinder_renderer::paint::Paint;
-use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, Scene};
+use pathfinder_renderer::scene::{ClipPath, ClipPathId, DrawPath, LayerOptions, Scene};
 use pathfinder_simd::default::F32x2;
 use std::fmt::{Display, Formatter, Result as FormatResult};
-use usvg::{BaseGradient, Color as SvgColor, FillRule as UsvgFillRule, LineCap as UsvgLineCap};
-use usvg::{LineJoin as UsvgLineJoin, Node, NodeExt, NodeKind, Opacity, Paint as UsvgPaint};
+use usvg::{Align, AspectRatio, BaseGradient, Color as SvgColor, FillRule as UsvgFillRule};
+use usvg::{LineCap as UsvgLineCap, LineJoin as UsvgLineJoin, Node, NodeExt, NodeKind, Opacity};
+use usvg::{Paint as UsvgPaint};
 use usvg::{PathSegment as UsvgPathSegment, Rect as UsvgRect, SpreadMethod, Stop};
 use usvg::{Transform as UsvgTransform, Tree, Visibility};
 
+use crate::diagnostic::node_kind_name;
+pub use crate::diagnostic::{ImportDiagnostic, ImportMode, SVGImportError, Severity};
+pub use crate::diagnostic::UnsupportedFeature;
+
+#[cfg(feature = "pf-text")]
+use crate::text::TextImporter;
+#[cfg(feature = "pf-text")]
+pub use crate::text::SVGTextOptions;
+
+mod diagnostic;
+#[cfg(feature = "pf-text")]
+mod text;
+
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 
 pub struct SVGScene {
     pub scene: Scene,
     pub result_flags: BuildResultFlags,
+    /// Every unsupported feature encountered, in document order.
+    pub diagnostics: Vec<ImportDiagnostic>,
     pub clip_paths: HashMap<String, ClipPathId>,
     gradients: HashMap<String, GradientInfo>,
+    root_state: State,
+    #[cfg(feature = "pf-text")]
+    text: Option<TextImporter>,
 }
 
 bitflags! {
@@ -57,39 +77,268 @@ bitflags! {
         const UNSUPPORTED_FILTER_ATTR            = 0x0040;
         const UNSUPPORTED_MASK_ATTR              = 0x0080;
         const UNSUPPORTED_GRADIENT_SPREAD_METHOD = 0x0100;
+        const TEXT_RENDERING_FAILED              = 0x0200;
+        const MISSING_ELEMENT                    = 0x0400;
+    }
+}
+
+/// Options that control how an SVG tree is converted to a scene.
+///
+/// Construct with `SVGImportOptions::new()` and chain the builder methods.
+#[derive(Clone, Default)]
+pub struct SVGImportOptions {
+    mode: ImportMode,
+    transform: Transform2F,
+    viewport: Option<RectF>,
+    aspect_ratio: Option<AspectRatio>,
+    element_id: Option<String>,
+    render_target: Option<RenderTargetId>,
+    clip_path: Option<ClipPathId>,
+    #[cfg(feature = "pf-text")]
+    text: Option<(String, SVGTextOptions)>,
+}
+
+impl SVGImportOptions {
+    #[inline]
+    pub fn new() -> SVGImportOptions {
+        SVGImportOptions::default()
+    }
+
+    /// Sets whether unsupported features are reported or cause the import to fail.
+    #[inline]
+    pub fn mode(mut self, mode: ImportMode) -> SVGImportOptions {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets a transform applied to all imported content, after viewport fitting.
+    #[inline]
+    pub fn transform(mut self, transform: Transform2F) -> SVGImportOptions {
+        self.transform = transform;
+        self
+    }
+
+    /// Scales and positions the content to fit `viewport`, honoring the document's
+    /// `preserveAspectRatio` attribute unless `aspect_ratio` overrides it. The scene's view box
+    /// is set to `viewport`.
+    #[inline]
+    pub fn viewport(mut self, viewport: RectF) -> SVGImportOptions {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    /// Overrides the `preserveAspectRatio` used for viewport fitting.
+    #[inline]
+    pub fn aspect_ratio(mut self, aspect_ratio: AspectRatio) -> SVGImportOptions {
+        self.aspect_ratio = Some(aspect_ratio);
+        self
+    }
+
+    /// Imports only the element with the given ID and its descendants, as for a sprite sheet or
+    /// symbol library. When fitting to a viewport, the element's bounds are fitted instead of
+    /// the document's view box.
+    #[inline]
+    pub fn element(mut self, element_id: &str) -> SVGImportOptions {
+        self.element_id = Some(element_id.to_owned());
+        self
+    }
+
+    /// Draws the imported content into an existing render target of the destination scene.
+    #[inline]
+    pub fn render_target(mut self, render_target: RenderTargetId) -> SVGImportOptions {
+        self.render_target = Some(render_target);
+        self
+    }
+
+    /// Clips all imported content to an existing clip path of the destination scene.
+    #[inline]
+    pub fn clip_path(mut self, clip_path: ClipPathId) -> SVGImportOptions {
+        self.clip_path = Some(clip_path);
+        self
+    }
+
+    /// Renders `<text>` elements as text runs with `pathfinder_text` instead of using the paths
+    /// that `usvg` converted them to. `svg_source` must be the source that the tree was parsed
+    /// from.
+    ///
+    /// Text elements are placed in the display list where `usvg` left a group with the same ID
+    /// (set `usvg::Options::keep_named_groups` to preserve them). When importing the whole
+    /// document, text without such a group is drawn on top of the rest of it; when importing a
+    /// single element, such text is dropped. Either way, the paths that `usvg` converted the text
+    /// into are skipped.
+    #[cfg(feature = "pf-text")]
+    #[inline]
+    pub fn text(mut self, svg_source: &str, text_options: SVGTextOptions) -> SVGImportOptions {
+        self.text = Some((svg_source.to_owned(), text_options));
+        self
     }
 }
 
 impl SVGScene {
-    // TODO(pcwalton): Allow a global transform to be set.
     #[inline]
     pub fn from_tree(tree: &Tree) -> SVGScene {
         SVGScene::from_tree_and_scene(tree, Scene::new())
     }
 
-    // TODO(pcwalton): Allow a global transform to be set.
     pub fn from_tree_and_scene(tree: &Tree, scene: Scene) -> SVGScene {
+        let mut built_svg = SVGScene::new(scene, State::new());
+        built_svg.scene.set_view_box(usvg_rect_to_euclid_rect(&tree.svg_node().view_box.rect));
+        built_svg.process_tree(tree);
+        built_svg.finish();
+        built_svg
+    }
+
+    /// Converts `tree` to a new scene according to `options`.
+    ///
+    /// In strict mode, this fails if any unsupported features are encountered.
+    #[inline]
+    pub fn from_tree_with_options(tree: &Tree, options: &SVGImportOptions)
+                                  -> Result<SVGScene, SVGImportError> {
+        SVGScene::from_tree_and_scene_with_options(tree, Scene::new(), options)
+    }
+
+    /// Converts `tree` according to `options`, appending the result to `scene`.
+    ///
+    /// The render target and clip path in `options`, if any, must belong to `scene`.
+    pub fn from_tree_and_scene_with_options(tree: &Tree,
+                                            mut scene: Scene,
+                                            options: &SVGImportOptions)
+                                            -> Result<SVGScene, SVGImportError> {
+        let (root, element_id) = match options.element_id {
+            None => (tree.root(), String::new()),
+            Some(ref element_id) => {
+                match tree.node_by_id(element_id) {
+                    Some(node) => (node, element_id.clone()),
+                    None => {
+                        let diagnostic = ImportDiagnostic::new(element_id,
+                                                               "svg",
+                                                               UnsupportedFeature::MissingElement);
+                        return Err(SVGImportError { diagnostics: vec![diagnostic] });
+                    }
+                }
+            }
+        };
+
+        let svg = tree.svg_node();
+        let mut transform = options.transform;
+        if let Some(viewport) = options.viewport {
+            let source_rect = if element_id.is_empty() {
+                usvg_rect_to_euclid_rect(&svg.view_box.rect)
+            } else {
+                // Measure the element by importing it once without any transform.
+                let mut measured_svg = SVGScene::new(Scene::new(), State::new());
+                measured_svg.process_element(tree, &root);
+                measured_svg.scene.bounds()
+            };
+            let aspect_ratio = options.aspect_ratio.unwrap_or(svg.view_box.aspect);
+            transform = transform * fit_to_viewport(source_rect, viewport, aspect_ratio);
+            scene.set_view_box(viewport);
+        } else if options.render_target.is_none() {
+            scene.set_view_box(transform * usvg_rect_to_euclid_rect(&svg.view_box.rect));
+        }
+
+        if let Some(render_target) = options.render_target {
+            scene.resume_render_target(render_target);
+        }
+
+        let mut root_state = State::new();
+        root_state.transform = transform;
+        root_state.clip_path = options.clip_path;
+
+        let mut built_svg = SVGScene::new(scene, root_state);
+        #[cfg(feature = "pf-text")]
+        {
+            if let Some((ref svg_source, ref text_options)) = options.text {
+                built_svg.text = Some(TextImporter::new(svg_source,
+                                                        text_options.clone(),
+                                                        &mut built_svg.diagnostics));
+            }
+        }
+        if element_id.is_empty() {
+            built_svg.process_tree(tree);
+            #[cfg(feature = "pf-text")]
+            {
+                built_svg.push_remaining_text();
+            }
+        } else {
+            built_svg.process_element(tree, &root);
+        }
+        built_svg.finish();
+
+        if options.render_target.is_some() {
+            built_svg.scene.pop_render_target();
+        }
+
+        if options.mode == ImportMode::Strict && !built_svg.diagnostics.is_empty() {
+            return Err(SVGImportError { diagnostics: built_svg.diagnostics });
+        }
+        Ok(built_svg)
+    }
+
+    fn new(scene: Scene, root_state: State) -> SVGScene {
         // TODO(pcwalton): Maybe have a `SVGBuilder` type to hold the clip path IDs and other
         // transient data separate from `SVGScene`?
-        let mut built_svg = SVGScene {
+        SVGScene {
             scene,
             result_flags: BuildResultFlags::empty(),
+            diagnostics: vec![],
             clip_paths: HashMap::new(),
             gradients: HashMap::new(),
-        };
+            root_state,
+            #[cfg(feature = "pf-text")]
+            text: None,
+        }
+    }
 
-        let root = &tree.root();
-        match *root.borrow() {
-            NodeKind::Svg(ref svg) => {
-                built_svg.scene.set_view_box(usvg_rect_to_euclid_rect(&svg.view_box.rect));
-                for kid in root.children() {
-                    built_svg.process_node(&kid, &State::new(), &mut None);
-                }
-            }
-            _ => unreachable!(),
+    /// Like `from_tree`, but renders `<text>` elements as text runs with `pathfinder_text` instead
+    /// of using the paths that `usvg` converted them to.
+    ///
+    /// This is shorthand for `from_tree_with_options()` with only `SVGImportOptions::text()` set;
+    /// use that to combine text rendering with other options.
+    #[cfg(feature = "pf-text")]
+    pub fn from_tree_with_text(tree: &Tree, svg_source: &str, text_options: SVGTextOptions)
+                               -> SVGScene {
+        let options = SVGImportOptions::new().text(svg_source, text_options);
+        match SVGScene::from_tree_with_options(tree, &options) {
+            Ok(built_svg) => built_svg,
+            Err(_) => unreachable!("Lenient imports of the whole document can't fail!"),
+        }
+    }
+
+    fn process_tree(&mut self, tree: &Tree) {
+        let root_state = self.root_state.clone();
+        for kid in tree.root().children() {
+            self.process_node(&kid, &root_state, &mut None);
         }
+    }
 
-        built_svg
+    // Imports one element, along with the definitions it may refer to.
+    fn process_element(&mut self, tree: &Tree, element: &Node) {
+        let mut defs_state = self.root_state.clone();
+        defs_state.path_destination = PathDestination::Defs;
+        self.process_node(&tree.defs(), &defs_state, &mut None);
+
+        // Accumulate the transforms of the element's ancestors, outermost first.
+        let mut state = self.root_state.clone();
+        let mut ancestors: Vec<Node> = element.ancestors().skip(1).collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            let ancestor_transform = usvg_transform_to_transform_2d(&ancestor.transform());
+            state.transform = state.transform * ancestor_transform;
+        }
+
+        self.process_node(element, &state, &mut None);
+    }
+
+    fn finish(&mut self) {
+        for diagnostic in &self.diagnostics {
+            self.result_flags.insert(diagnostic.feature.to_flag());
+        }
+    }
+
+    fn report(&mut self, node: &Node, feature: UnsupportedFeature) {
+        let node_kind = node_kind_name(&node.borrow());
+        self.diagnostics.push(ImportDiagnostic::new(&node.id(), node_kind, feature));
     }
 
     fn process_node(&mut self,
@@ -98,41 +347,65 @@ impl SVGScene {
                     clip_outline: &mut Option<Outline>) {
         let mut state = (*state).clone();
         let node_transform = usvg_transform_to_transform_2d(&node.transform());
-        state.transform = node_transform * state.transform;
+        state.transform = state.transform * node_transform;
+
+        #[cfg(feature = "pf-text")]
+        {
+            if state.path_destination == PathDestination::Draw && self.push_text_group(node) {
+                return;
+            }
+        }
 
         match *node.borrow() {
             NodeKind::Group(ref group) => {
                 if group.filter.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                    self.report(node, UnsupportedFeature::FilterAttr);
                 }
                 if group.mask.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
+                    self.report(node, UnsupportedFeature::MaskAttr);
                 }
 
                 if let Some(ref clip_path_name) = group.clip_path {
                     if let Some(clip_path_id) = self.clip_paths.get(clip_path_name) {
                         // TODO(pcwalton): Combine multiple clip paths if there's already one.
                         if state.clip_path.is_some() {
-                            self.result_flags
-                                .insert(BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS);
+                            self.report(node, UnsupportedFeature::MultipleClipPaths);
                         }
                         state.clip_path = Some(*clip_path_id);
                     }
                 }
 
+                // Group opacity applies to the group's contents as a whole.
+                let is_layer = state.path_destination == PathDestination::Draw &&
+                    group.opacity.value() < 1.0;
+                if is_layer {
+                    self.scene.push_layer(LayerOptions {
+                        opacity: group.opacity.value() as f32,
+                        ..LayerOptions::default()
+                    });
+                }
+
                 for kid in node.children() {
                     self.process_node(&kid, &state, clip_outline)
                 }
+
+                if is_layer {
+                    self.scene.pop_layer();
+                }
             }
             NodeKind::Path(ref path) if state.path_destination == PathDestination::Clip => {
                 // TODO(pcwalton): Multiple clip paths.
                 let path = UsvgPathToSegments::new(path.data.iter().cloned());
                 let path = Transform2FPathIter::new(path, &state.transform);
                 if clip_outline.is_some() {
-                    self.result_flags.insert(BuildResultFlags::UNSUPPORTED_MULTIPLE_CLIP_PATHS);
+                    self.report(node, UnsupportedFeature::MultipleClipPaths);
                 }
                 *clip_outline = Some(Outline::from_segments(path));
             }
+            #[cfg(feature = "pf-text")]
+            NodeKind::Path(ref path) if self.text.is_some() && path.text_bbox.is_some() => {
+                // `usvg` converted this from text, which is drawn as text runs instead.
+            }
             NodeKind::Path(ref path) if state.path_destination == PathDestination::Draw &&
                     path.visibility == Visibility::Visible => {
                 if let Some(ref fill) = path.fill {
@@ -140,7 +413,8 @@ impl SVGScene {
                     let outline = Outline::from_segments(path);
 
                     let name = format!("Fill({})", node.id());
-                    self.push_draw_path(outline,
+                    self.push_draw_path(node,
+                                        outline,
                                         name,
                                         &state,
                                         &fill.paint,
@@ -171,7 +445,8 @@ impl SVGScene {
                     let outline = stroke_to_fill.into_outline();
 
                     let name = format!("Stroke({})", node.id());
-                    self.push_draw_path(outline,
+                    self.push_draw_path(node,
+                                        outline,
                                         name,
                                         &state,
                                         &stroke.paint,
@@ -206,7 +481,8 @@ impl SVGScene {
                 let from = vec2f(svg_linear_gradient.x1 as f32, svg_linear_gradient.y1 as f32);
                 let to   = vec2f(svg_linear_gradient.x2 as f32, svg_linear_gradient.y2 as f32);
                 let gradient = Gradient::linear_from_points(from, to);
-                self.add_gradient(gradient,
+                self.add_gradient(node,
+                                  gradient,
                                   svg_linear_gradient.id.clone(),
                                   &svg_linear_gradient.base)
             }
@@ -215,31 +491,60 @@ impl SVGScene {
                 let to   = vec2f(svg_radial_gradient.cx as f32, svg_radial_gradient.cy as f32);
                 let radii = F32x2::new(0.0, svg_radial_gradient.r.value() as f32);
                 let gradient = Gradient::radial(LineSegment2F::new(from, to), radii);
-                self.add_gradient(gradient,
+                self.add_gradient(node,
+                                  gradient,
                                   svg_radial_gradient.id.clone(),
                                   &svg_radial_gradient.base)
             }
-            NodeKind::Filter(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
-            }
-            NodeKind::Image(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
-            }
-            NodeKind::Mask(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
-            }
-            NodeKind::Pattern(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
-            }
+            NodeKind::Filter(..) => self.report(node, UnsupportedFeature::FilterNode),
+            NodeKind::Image(..) => self.report(node, UnsupportedFeature::ImageNode),
+            NodeKind::Mask(..) => self.report(node, UnsupportedFeature::MaskNode),
+            NodeKind::Pattern(..) => self.report(node, UnsupportedFeature::PatternNode),
             NodeKind::Svg(..) => unreachable!(),
         }
     }
 
+    // Pushes the text elements that had no corresponding group in the `usvg` tree.
+    #[cfg(feature = "pf-text")]
+    fn push_remaining_text(&mut self) {
+        if let Some(ref mut text) = self.text {
+            text.push_remaining_elements(&self.root_state.transform,
+                                         self.root_state.clip_path,
+                                         &mut self.scene,
+                                         &self.clip_paths,
+                                         &mut self.diagnostics);
+        }
+    }
+
+    // If `node` is the group that `usvg` converted a `<text>` element into, draws the text
+    // instead and returns true.
+    #[cfg(feature = "pf-text")]
+    fn push_text_group(&mut self, node: &Node) -> bool {
+        match *node.borrow() {
+            NodeKind::Group(_) => {}
+            _ => return false,
+        }
+
+        let text = match self.text {
+            None => return false,
+            Some(ref mut text) => text,
+        };
+        match text.find_element(&node.id()) {
+            None => false,
+            Some(index) => {
+                text.push_element(index,
+                                  &self.root_state.transform,
+                                  self.root_state.clip_path,
+                                  &mut self.scene,
+                                  &self.clip_paths,
+                                  &mut self.diagnostics);
+                true
+            }
+        }
+    }
+
     fn add_gradient(&mut self,
+                    node: &Node,
                     mut gradient: Gradient,
                     id: String,
                     usvg_base_gradient: &BaseGradient) {
@@ -248,7 +553,7 @@ impl SVGScene {
         }
 
         if usvg_base_gradient.spread_method != SpreadMethod::Pad {
-            self.result_flags.insert(BuildResultFlags::UNSUPPORTED_GRADIENT_SPREAD_METHOD);
+            self.report(node, UnsupportedFeature::GradientSpreadMethod);
         }
 
         let transform = usvg_transform_to_transform_2d(&usvg_base_gradient.transform);
@@ -258,6 +563,7 @@ impl SVGScene {
     }
 
     fn push_draw_path(&mut self,
+                      node: &Node,
                       mut outline: Outline,
                       name: String,
                       state: &State,
@@ -265,11 +571,13 @@ impl SVGScene {
                       opacity: Opacity,
                       fill_rule: UsvgFillRule) {
         outline.transform(&state.transform);
-        let paint = Paint::from_svg_paint(paint,
-                                          &state.transform,
-                                          opacity,
-                                          &self.gradients,
-                                          &mut self.result_flags);
+        if let UsvgPaint::Link(ref id) = *paint {
+            if !self.gradients.contains_key(id) {
+                // TODO(pcwalton)
+                self.report(node, UnsupportedFeature::LinkPaint);
+            }
+        }
+        let paint = Paint::from_svg_paint(paint, &state.transform, opacity, &self.gradients);
         let style = self.scene.push_paint(&paint);
         let fill_rule = FillRule::from_usvg_fill_rule(fill_rule);
         let mut path = DrawPath::new(outline, style);
@@ -312,6 +620,8 @@ impl Display for BuildResultFlags {
             "filter attribute",
             "mask attribute",
             "gradient spread method",
+            "text rendering failure",
+            "missing element",
         ];
     }
 }
@@ -320,8 +630,7 @@ trait PaintExt {
     fn from_svg_paint(svg_paint: &UsvgPaint,
                       transform: &Transform2F,
                       opacity: Opacity,
-                      gradients: &HashMap<String, GradientInfo>,
-                      result_flags: &mut BuildResultFlags)
+                      gradients: &HashMap<String, GradientInfo>)
                       -> Self;
 }
 
@@ -330,8 +639,7 @@ impl PaintExt for Paint {
     fn from_svg_paint(svg_paint: &UsvgPaint,
                       transform: &Transform2F,
                       opacity: Opacity,
-                      gradients: &HashMap<String, GradientInfo>,
-                      result_flags: &mut BuildResultFlags)
+                      gradients: &HashMap<String, GradientInfo>)
                       -> Paint {
         let mut paint;
         match *svg_paint {
@@ -342,11 +650,7 @@ impl PaintExt for Paint {
                         paint = Paint::from_gradient(gradient_info.gradient.clone());
                         paint.apply_transform(&(*transform * gradient_info.transform));
                     }
-                    None => {
-                        // TODO(pcwalton)
-                        result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
-                        paint = Paint::from_color(ColorU::black());
-                    }
+                    None => paint = Paint::from_color(ColorU::black()),
                 }
             }
         }
@@ -359,6 +663,36 @@ impl PaintExt for Paint {
     }
 }
 
+// Implements the `preserveAspectRatio` attribute.
+fn fit_to_viewport(view_box: RectF, viewport: RectF, aspect_ratio: AspectRatio) -> Transform2F {
+    let mut scale = viewport.size() / view_box.size();
+    let align = match aspect_ratio.align {
+        Align::None => vec2f(0.0, 0.0),
+        Align::XMinYMin => vec2f(0.0, 0.0),
+        Align::XMidYMin => vec2f(0.5, 0.0),
+        Align::XMaxYMin => vec2f(1.0, 0.0),
+        Align::XMinYMid => vec2f(0.0, 0.5),
+        Align::XMidYMid => vec2f(0.5, 0.5),
+        Align::XMaxYMid => vec2f(1.0, 0.5),
+        Align::XMinYMax => vec2f(0.0, 1.0),
+        Align::XMidYMax => vec2f(0.5, 1.0),
+        Align::XMaxYMax => vec2f(1.0, 1.0),
+    };
+    if aspect_ratio.align != Align::None {
+        let uniform_scale = if aspect_ratio.slice {
+            f32::max(scale.x(), scale.y())
+        } else {
+            f32::min(scale.x(), scale.y())
+        };
+        scale = Vector2F::splat(uniform_scale);
+    }
+
+    let offset = viewport.origin() + (viewport.size() - view_box.size() * scale) * align;
+    Transform2F::from_translation(offset) *
+        Transform2F::from_scale(scale) *
+        Transform2F::from_translation(-view_box.origin())
+}
+
 fn usvg_rect_to_euclid_rect(rect: &UsvgRect) -> RectF {
     RectF::new(vec2f(rect.x() as f32, rect.y() as f32),
                vec2f(rect.width() as f32, rect.height() as f32))
@@ -550,3 +884,106 @@ struct GradientInfo {
     gradient: Gradient,
     transform: Transform2F,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{SVGImportOptions, SVGScene, UnsupportedFeature, fit_to_viewport};
+    use pathfinder_geometry::rect::RectF;
+    use pathfinder_geometry::vector::{Vector2F, vec2f};
+    use usvg::{Align, AspectRatio, Options, Tree};
+
+    const NESTED_GROUPS_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg"
+                                           width="600" height="100" viewBox="0 0 600 100">
+        <g id="outer" transform="translate(100 0)">
+            <g id="inner" transform="scale(2)">
+                <rect id="square" x="0" y="0" width="10" height="10" fill="red"/>
+            </g>
+        </g>
+        <rect id="other" x="500" y="50" width="10" height="10" fill="blue"/>
+    </svg>"#;
+
+    fn aspect_ratio(align: Align, slice: bool) -> AspectRatio {
+        AspectRatio { defer: false, align, slice }
+    }
+
+    fn parse_tree(svg_source: &str) -> Tree {
+        let options = Options { keep_named_groups: true, ..Options::default() };
+        Tree::from_str(svg_source, &options).unwrap()
+    }
+
+    fn assert_near(actual: Vector2F, expected: Vector2F) {
+        assert!((actual - expected).length() < 0.001, "{:?} != {:?}", actual, expected);
+    }
+
+    fn assert_rect_near(actual: RectF, expected: RectF) {
+        assert_near(actual.origin(), expected.origin());
+        assert_near(actual.lower_right(), expected.lower_right());
+    }
+
+    #[test]
+    fn test_fit_to_viewport() {
+        let view_box = RectF::new(vec2f(0.0, 0.0), vec2f(100.0, 50.0));
+        let viewport = RectF::new(vec2f(0.0, 0.0), vec2f(200.0, 200.0));
+
+        // Meet scales uniformly by the smaller factor and centers the leftover space.
+        let transform = fit_to_viewport(view_box, viewport, aspect_ratio(Align::XMidYMid, false));
+        assert_near(transform * vec2f(0.0, 0.0), vec2f(0.0, 50.0));
+        assert_near(transform * vec2f(100.0, 50.0), vec2f(200.0, 150.0));
+
+        // Slice scales uniformly by the larger factor and crops the overflow.
+        let transform = fit_to_viewport(view_box, viewport, aspect_ratio(Align::XMidYMid, true));
+        assert_near(transform * vec2f(0.0, 0.0), vec2f(-100.0, 0.0));
+        assert_near(transform * vec2f(100.0, 50.0), vec2f(300.0, 200.0));
+
+        // Max alignment pushes the leftover space to the start.
+        let transform = fit_to_viewport(view_box, viewport, aspect_ratio(Align::XMaxYMax, false));
+        assert_near(transform * vec2f(0.0, 0.0), vec2f(0.0, 100.0));
+
+        // `none` stretches each axis independently.
+        let transform = fit_to_viewport(view_box, viewport, aspect_ratio(Align::None, false));
+        assert_near(transform * vec2f(0.0, 0.0), vec2f(0.0, 0.0));
+        assert_near(transform * vec2f(100.0, 50.0), vec2f(200.0, 200.0));
+
+        // The view box origin maps to the viewport origin.
+        let view_box = RectF::new(vec2f(10.0, 20.0), vec2f(100.0, 50.0));
+        let viewport = RectF::new(vec2f(5.0, 5.0), vec2f(100.0, 50.0));
+        let transform = fit_to_viewport(view_box, viewport, aspect_ratio(Align::XMinYMin, false));
+        assert_near(transform * vec2f(10.0, 20.0), vec2f(5.0, 5.0));
+        assert_near(transform * vec2f(110.0, 70.0), vec2f(105.0, 55.0));
+    }
+
+    #[test]
+    fn test_nested_group_transforms() {
+        // The outer translation must apply after the inner scale, not before it.
+        let tree = parse_tree(NESTED_GROUPS_SVG);
+        let svg = SVGScene::from_tree_with_options(&tree, &SVGImportOptions::new()).unwrap();
+        assert_rect_near(svg.scene.bounds(),
+                         RectF::from_points(vec2f(100.0, 0.0), vec2f(510.0, 60.0)));
+    }
+
+    #[test]
+    fn test_element_selection() {
+        let tree = parse_tree(NESTED_GROUPS_SVG);
+
+        // Only the selected element is imported, with its ancestors' transforms applied.
+        let options = SVGImportOptions::new().element("inner");
+        let svg = SVGScene::from_tree_with_options(&tree, &options).unwrap();
+        assert_eq!(svg.scene.draw_paths().len(), 1);
+        assert_rect_near(svg.scene.bounds(),
+                         RectF::new(vec2f(100.0, 0.0), vec2f(20.0, 20.0)));
+
+        // Viewport fitting uses the element's bounds instead of the document's view box.
+        let viewport = RectF::new(vec2f(0.0, 0.0), vec2f(40.0, 40.0));
+        let options = SVGImportOptions::new().element("inner").viewport(viewport);
+        let svg = SVGScene::from_tree_with_options(&tree, &options).unwrap();
+        assert_rect_near(svg.scene.bounds(), viewport);
+        assert_eq!(svg.scene.view_box(), viewport);
+
+        // A missing element is an error.
+        let options = SVGImportOptions::new().element("missing");
+        let error = SVGScene::from_tree_with_options(&tree, &options).err().unwrap();
+        assert_eq!(error.diagnostics.len(), 1);
+        assert_eq!(error.diagnostics[0].element_id, "missing");
+        assert_eq!(error.diagnostics[0].feature, UnsupportedFeature::MissingElement);
+    }
+}
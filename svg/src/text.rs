@@ -0,0 +1,499 @@
+// pathfinder/svg/src/text.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Renders SVG `<text>` and `<tspan>` elements as real text runs via `pathfinder_text`.
+//!
+//! `usvg` converts text to paths (or drops it if it can't find the fonts), so the text content
+//! is recovered from the original SVG source instead.
+
+use crate::diagnostic::{ImportDiagnostic, UnsupportedFeature};
+use font_kit::hinting::HintingOptions;
+use font_kit::loader::Loader;
+use font_kit::loaders::default::Font as DefaultLoader;
+use hashbrown::HashMap;
+use pathfinder_color::ColorU;
+use pathfinder_content::effects::BlendMode;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, vec2f};
+use pathfinder_renderer::paint::Paint;
+use pathfinder_renderer::scene::{ClipPathId, Scene};
+use pathfinder_text::{FontContext, FontRenderOptions, TextRenderMode};
+use roxmltree::{Document, Node as XmlNode};
+use skribo::{FontCollection, Layout, TextStyle};
+use std::str::FromStr;
+use std::sync::Arc;
+use svgtypes::{Color as SvgTypesColor, Transform as SvgTypesTransform};
+
+const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+/// Elements whose children are never rendered directly.
+static NON_RENDERING_ELEMENTS: &'static [&'static str] = &[
+    "clipPath", "defs", "marker", "mask", "pattern", "symbol",
+];
+
+/// The attributes that position individual characters, in `PositionLists` order.
+static POSITION_ATTRIBUTES: [&'static str; 4] = ["x", "y", "dx", "dy"];
+
+/// Options for rendering SVG text with `pathfinder_text`.
+#[derive(Clone)]
+pub struct SVGTextOptions {
+    /// The fonts used to lay out all text in the document.
+    pub font_collection: Arc<FontCollection>,
+    /// The hinting to apply to glyph outlines.
+    pub hinting_options: HintingOptions,
+}
+
+impl SVGTextOptions {
+    #[inline]
+    pub fn new(font_collection: Arc<FontCollection>) -> SVGTextOptions {
+        SVGTextOptions { font_collection, hinting_options: HintingOptions::None }
+    }
+}
+
+pub(crate) struct TextImporter {
+    font_context: FontContext<DefaultLoader>,
+    options: SVGTextOptions,
+    elements: Vec<TextElement>,
+    pushed: Vec<bool>,
+}
+
+// A `<text>` element, flattened into runs.
+struct TextElement {
+    id: String,
+    transform: Transform2F,
+    clip_path: Option<String>,
+    // The element's own `opacity`. Ancestors' opacity belongs to their groups.
+    opacity: f32,
+    runs: Vec<TextRun>,
+}
+
+// A contiguous span of characters sharing one style. Only the first character is explicitly
+// positioned.
+struct TextRun {
+    text: String,
+    x: Option<f32>,
+    y: Option<f32>,
+    dx: f32,
+    dy: f32,
+    font_size: f32,
+    fill: Option<ColorU>,
+}
+
+// The positioning attributes of a `<text>` or `<tspan>` element. The values apply to successive
+// characters starting with the element's first one, which is `start` characters into the
+// enclosing `<text>`.
+struct PositionLists {
+    start: usize,
+    values: [Vec<f32>; 4],
+}
+
+impl TextImporter {
+    pub(crate) fn new(svg_source: &str,
+                      options: SVGTextOptions,
+                      diagnostics: &mut Vec<ImportDiagnostic>)
+                      -> TextImporter {
+        let mut elements = vec![];
+        match Document::parse(svg_source) {
+            Ok(document) => {
+                for node in document.descendants() {
+                    if node.is_element() && node.tag_name().name() == "text" &&
+                            !is_in_non_rendering_element(node) {
+                        elements.push(TextElement::from_xml_node(node));
+                    }
+                }
+            }
+            Err(_) => {
+                diagnostics.push(ImportDiagnostic::new("",
+                                                       "svg",
+                                                       UnsupportedFeature::TextRendering));
+            }
+        }
+
+        let pushed = vec![false; elements.len()];
+        TextImporter { font_context: FontContext::new(), options, elements, pushed }
+    }
+
+    /// Returns the index of the not-yet-pushed text element that `usvg` converted into the group
+    /// with the given ID, if any.
+    pub(crate) fn find_element(&self, id: &str) -> Option<usize> {
+        if id.is_empty() {
+            return None;
+        }
+        (0..self.elements.len()).find(|&index| {
+            !self.pushed[index] && self.elements[index].id == id
+        })
+    }
+
+    pub(crate) fn push_element(&mut self,
+                               index: usize,
+                               root_transform: &Transform2F,
+                               root_clip_path: Option<ClipPathId>,
+                               scene: &mut Scene,
+                               clip_paths: &HashMap<String, ClipPathId>,
+                               diagnostics: &mut Vec<ImportDiagnostic>) {
+        if self.pushed[index] {
+            return;
+        }
+        self.pushed[index] = true;
+
+        let element = &self.elements[index];
+        let clip_path = element.clip_path
+                               .as_ref()
+                               .and_then(|clip_path| clip_paths.get(clip_path))
+                               .cloned()
+                               .or(root_clip_path);
+
+        let mut pen = Vector2F::zero();
+        for run in &element.runs {
+            pen = vec2f(run.x.unwrap_or(pen.x()), run.y.unwrap_or(pen.y())) +
+                vec2f(run.dx, run.dy);
+
+            let style = TextStyle { size: run.font_size };
+            let layout = skribo::layout(&style, &self.options.font_collection, &run.text);
+
+            if let Some(mut fill) = run.fill {
+                fill.a = (fill.a as f32 * element.opacity).round() as u8;
+                let paint_id = scene.push_paint(&Paint::from_color(fill));
+                let render_options = FontRenderOptions {
+                    transform: *root_transform * element.transform *
+                        Transform2F::from_translation(pen),
+                    render_mode: TextRenderMode::Fill,
+                    hinting_options: self.options.hinting_options,
+                    clip_path,
+                    blend_mode: BlendMode::SrcOver,
+                    paint_id,
+                };
+                if self.font_context
+                       .push_layout(scene, &layout, &style, &render_options)
+                       .is_err() {
+                    diagnostics.push(ImportDiagnostic::new(&element.id,
+                                                           "text",
+                                                           UnsupportedFeature::TextRendering));
+                }
+            }
+
+            pen += vec2f(layout_advance(&layout), 0.0);
+        }
+    }
+
+    /// Pushes the text elements that had no corresponding group in the `usvg` tree, in document
+    /// order.
+    pub(crate) fn push_remaining_elements(&mut self,
+                                          root_transform: &Transform2F,
+                                          root_clip_path: Option<ClipPathId>,
+                                          scene: &mut Scene,
+                                          clip_paths: &HashMap<String, ClipPathId>,
+                                          diagnostics: &mut Vec<ImportDiagnostic>) {
+        for index in 0..self.elements.len() {
+            self.push_element(index,
+                              root_transform,
+                              root_clip_path,
+                              scene,
+                              clip_paths,
+                              diagnostics);
+        }
+    }
+}
+
+impl TextElement {
+    fn from_xml_node(node: XmlNode) -> TextElement {
+        // Accumulate the transforms of this element and all its ancestors.
+        let mut transform = Transform2F::default();
+        for ancestor in node.ancestors() {
+            if let Some(ancestor_transform) = ancestor.attribute("transform") {
+                if let Ok(ancestor_transform) = SvgTypesTransform::from_str(ancestor_transform) {
+                    transform = svgtypes_transform_to_transform_2d(&ancestor_transform) *
+                        transform;
+                }
+            }
+        }
+
+        let clip_path = node.ancestors()
+                            .filter_map(|ancestor| style_property(ancestor, "clip-path"))
+                            .next()
+                            .and_then(parse_func_iri)
+                            .map(|id| id.to_owned());
+
+        // Unlike `fill-opacity`, `opacity` isn't inherited.
+        let opacity = style_property(node, "opacity").and_then(parse_opacity).unwrap_or(1.0);
+
+        let mut element = TextElement {
+            id: node.attribute("id").unwrap_or("").to_owned(),
+            transform,
+            clip_path,
+            opacity,
+            runs: vec![],
+        };
+        element.push_runs(node, &mut vec![], &mut 0);
+        element
+    }
+
+    // Appends the runs of `node` and its `<tspan>` descendants. `ancestor_positions` holds the
+    // positioning attributes of the enclosing elements, and `char_index` counts the characters
+    // of the `<text>` element seen so far.
+    fn push_runs(&mut self,
+                 node: XmlNode,
+                 ancestor_positions: &mut Vec<PositionLists>,
+                 char_index: &mut usize) {
+        ancestor_positions.push(PositionLists::from_xml_node(node, *char_index));
+
+        for kid in node.children() {
+            if kid.is_element() {
+                if kid.tag_name().name() == "tspan" {
+                    self.push_runs(kid, ancestor_positions, char_index);
+                }
+                continue;
+            }
+
+            let text = match kid.text() {
+                Some(text) => collapse_white_space(text),
+                None => continue,
+            };
+            let (font_size, fill) = (inherited_font_size(node), inherited_fill(node));
+
+            // Every explicitly positioned character starts a new run.
+            for (chunk_index, character) in text.chars().enumerate() {
+                let position = |attribute| position_at(ancestor_positions, *char_index, attribute);
+                let (x, y, dx, dy) = (position(0), position(1), position(2), position(3));
+                if chunk_index == 0 || x.is_some() || y.is_some() || dx.is_some() || dy.is_some() {
+                    self.runs.push(TextRun {
+                        text: String::new(),
+                        x,
+                        y,
+                        dx: dx.unwrap_or(0.0),
+                        dy: dy.unwrap_or(0.0),
+                        font_size,
+                        fill,
+                    });
+                }
+                self.runs.last_mut().unwrap().text.push(character);
+                *char_index += 1;
+            }
+        }
+
+        ancestor_positions.pop();
+    }
+}
+
+impl PositionLists {
+    fn from_xml_node(node: XmlNode, start: usize) -> PositionLists {
+        let list = |attribute: usize| length_list(node, POSITION_ATTRIBUTES[attribute]);
+        PositionLists { start, values: [list(0), list(1), list(2), list(3)] }
+    }
+}
+
+// Looks up the value of one positioning attribute for a character. The innermost element that
+// has a value for the character wins.
+fn position_at(ancestor_positions: &[PositionLists], char_index: usize, attribute: usize)
+               -> Option<f32> {
+    ancestor_positions.iter().rev().filter_map(|positions| {
+        positions.values[attribute].get(char_index - positions.start).cloned()
+    }).next()
+}
+
+fn is_in_non_rendering_element(node: XmlNode) -> bool {
+    node.ancestors().any(|ancestor| {
+        ancestor.is_element() && NON_RENDERING_ELEMENTS.contains(&ancestor.tag_name().name())
+    })
+}
+
+// Looks up a presentation property, preferring the `style` attribute over the plain attribute.
+fn style_property<'a>(node: XmlNode<'a, '_>, name: &str) -> Option<&'a str> {
+    if let Some(style) = node.attribute("style") {
+        for declaration in style.split(';') {
+            let mut parts = declaration.splitn(2, ':');
+            if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                if key.trim() == name {
+                    return Some(value.trim());
+                }
+            }
+        }
+    }
+    node.attribute(name)
+}
+
+fn inherited_property<'a>(node: XmlNode<'a, '_>, name: &str) -> Option<&'a str> {
+    node.ancestors()
+        .filter_map(|ancestor| style_property(ancestor, name))
+        .find(|value| *value != "inherit")
+}
+
+fn inherited_font_size(node: XmlNode) -> f32 {
+    inherited_property(node, "font-size").and_then(parse_length).unwrap_or(DEFAULT_FONT_SIZE)
+}
+
+fn inherited_fill(node: XmlNode) -> Option<ColorU> {
+    let mut color = match inherited_property(node, "fill") {
+        None => ColorU::black(),
+        Some("none") => return None,
+        Some(fill) => match SvgTypesColor::from_str(fill) {
+            Ok(color) => ColorU { r: color.red, g: color.green, b: color.blue, a: !0 },
+            // TODO(pcwalton): Gradient and pattern fills on text.
+            Err(_) => ColorU::black(),
+        },
+    };
+
+    let opacity = inherited_property(node, "fill-opacity").and_then(parse_opacity).unwrap_or(1.0);
+    color.a = (opacity * 255.0).round() as u8;
+    Some(color)
+}
+
+// Parses a list of lengths. A list with any invalid lengths is ignored.
+fn length_list(node: XmlNode, name: &str) -> Vec<f32> {
+    let list = match node.attribute(name) {
+        None => return vec![],
+        Some(list) => list,
+    };
+    list.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(parse_length)
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or(vec![])
+}
+
+// Only absolute user units are supported.
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+fn parse_opacity(value: &str) -> Option<f32> {
+    parse_length(value).map(|opacity| opacity.max(0.0).min(1.0))
+}
+
+// Extracts `id` from `url(#id)`.
+fn parse_func_iri(value: &str) -> Option<&str> {
+    let value = value.trim();
+    if value.starts_with("url(#") && value.ends_with(')') {
+        Some(value["url(#".len()..(value.len() - 1)].trim())
+    } else {
+        None
+    }
+}
+
+// Implements `xml:space="default"` white space handling.
+fn collapse_white_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn layout_advance(layout: &Layout) -> f32 {
+    let last_glyph = match layout.glyphs.last() {
+        None => return 0.0,
+        Some(last_glyph) => last_glyph,
+    };
+    let font = &*last_glyph.font.font;
+    let scale = layout.size / font.metrics().units_per_em as f32;
+    let advance = font.advance(last_glyph.glyph_id).unwrap_or(Vector2F::zero());
+    last_glyph.offset.x() + advance.x() * scale
+}
+
+fn svgtypes_transform_to_transform_2d(transform: &SvgTypesTransform) -> Transform2F {
+    Transform2F::row_major(transform.a as f32, transform.c as f32, transform.e as f32,
+                           transform.b as f32, transform.d as f32, transform.f as f32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SVGTextOptions, TextElement, TextImporter};
+    use pathfinder_color::ColorU;
+    use roxmltree::Document;
+    use skribo::FontCollection;
+    use std::sync::Arc;
+
+    fn parse_text_element(svg_source: &str) -> TextElement {
+        let document = Document::parse(svg_source).unwrap();
+        let node = document.descendants()
+                           .find(|node| node.tag_name().name() == "text")
+                           .unwrap();
+        TextElement::from_xml_node(node)
+    }
+
+    #[test]
+    fn test_runs() {
+        let element = parse_text_element(r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <text id="t" x="1 5" y="2" font-size="8" fill="red">
+                    Hello   <tspan dx="3" fill="blue">big</tspan> world
+                </text>
+            </svg>"#);
+        assert_eq!(element.id, "t");
+        let texts: Vec<_> = element.runs.iter().map(|run| &run.text[..]).collect();
+        assert_eq!(texts, ["H", "ello", "big", "world"]);
+
+        // Each value in a position list applies to one character.
+        assert_eq!((element.runs[0].x, element.runs[0].y), (Some(1.0), Some(2.0)));
+        assert_eq!((element.runs[1].x, element.runs[1].y), (Some(5.0), None));
+        assert_eq!((element.runs[2].x, element.runs[2].dx), (None, 3.0));
+        assert_eq!((element.runs[3].x, element.runs[3].dx), (None, 0.0));
+
+        assert!(element.runs.iter().all(|run| run.font_size == 8.0));
+        assert_eq!(element.runs[0].fill, Some(ColorU::new(255, 0, 0, 255)));
+        assert_eq!(element.runs[2].fill, Some(ColorU::new(0, 0, 255, 255)));
+        assert_eq!(element.runs[3].fill, Some(ColorU::new(255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn test_position_lists() {
+        // A `<tspan>`'s values take precedence over its ancestors' for the characters they cover.
+        let element = parse_text_element(r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <text x="1 2 3 4" dy="5,6">ab<tspan x="10" dx="7">cde</tspan>f</text>
+            </svg>"#);
+        let runs: Vec<_> = element.runs.iter().map(|run| {
+            (&run.text[..], run.x, run.y, run.dx, run.dy)
+        }).collect();
+        assert_eq!(runs, [
+            ("a", Some(1.0), None, 0.0, 5.0),
+            ("b", Some(2.0), None, 0.0, 6.0),
+            ("c", Some(10.0), None, 7.0, 0.0),
+            ("de", Some(4.0), None, 0.0, 0.0),
+            ("f", None, None, 0.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_opacity() {
+        // `fill-opacity` is inherited, but a group's `opacity` belongs to the group's layer.
+        let element = parse_text_element(r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <g opacity="0.25" fill-opacity="0.5">
+                    <text style="opacity: 0.5">Hello</text>
+                </g>
+            </svg>"#);
+        assert_eq!(element.opacity, 0.5);
+        assert_eq!(element.runs[0].fill, Some(ColorU::new(0, 0, 0, 128)));
+
+        let element = parse_text_element(r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <g opacity="0.25"><text fill="none">Hello</text></g>
+            </svg>"#);
+        assert_eq!(element.opacity, 1.0);
+        assert_eq!(element.runs[0].fill, None);
+    }
+
+    #[test]
+    fn test_find_element() {
+        let svg_source = r#"
+            <svg xmlns="http://www.w3.org/2000/svg">
+                <defs><text id="hidden">Hidden</text></defs>
+                <text id="a">A</text>
+                <text>B</text>
+            </svg>"#;
+        let options = SVGTextOptions::new(Arc::new(FontCollection::new()));
+        let mut diagnostics = vec![];
+        let importer = TextImporter::new(svg_source, options, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+
+        // Text in non-rendering elements is never drawn, and anonymous text has no group.
+        assert_eq!(importer.elements.len(), 2);
+        assert_eq!(importer.find_element("a"), Some(0));
+        assert_eq!(importer.find_element("hidden"), None);
+        assert_eq!(importer.find_element(""), None);
+    }
+}
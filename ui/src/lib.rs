@@ -23,7 +23,7 @@ use pathfinder_geometry::vector::{Vector2F, Vector2I, vec2i};
 use pathfinder_gpu::allocator::{BufferTag, GPUMemoryAllocator};
 use pathfinder_gpu::{BlendFactor, BlendState, BufferTarget, Device, Primitive, RenderOptions};
 use pathfinder_gpu::{RenderState, RenderTarget, TextureFormat, UniformData, VertexAttrClass};
-use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
+use pathfinder_gpu::{DeviceError, VertexAttrDescriptor, VertexAttrType};
 use pathfinder_resources::ResourceLoader;
 use pathfinder_simd::default::F32x4;
 use serde_json;
@@ -82,23 +82,23 @@ pub struct UIPresenter<D> where D: Device {
 
 impl<D> UIPresenter<D> where D: Device {
     pub fn new(device: &D, resources: &dyn ResourceLoader, framebuffer_size: Vector2I)
-               -> UIPresenter<D> {
-        let texture_program = DebugTextureProgram::new(device, resources);
+               -> Result<UIPresenter<D>, DeviceError> {
+        let texture_program = DebugTextureProgram::new(device, resources)?;
         let font = DebugFont::load(resources);
 
-        let solid_program = DebugSolidProgram::new(device, resources);
+        let solid_program = DebugSolidProgram::new(device, resources)?;
 
         let font_texture = device.create_texture_from_png(resources,
                                                           FONT_PNG_NAME,
-                                                          TextureFormat::R8);
+                                                          TextureFormat::R8)?;
         let corner_fill_texture = device.create_texture_from_png(resources,
                                                                  CORNER_FILL_PNG_NAME,
-                                                                 TextureFormat::R8);
+                                                                 TextureFormat::R8)?;
         let corner_outline_texture = device.create_texture_from_png(resources,
                                                                     CORNER_OUTLINE_PNG_NAME,
-                                                                    TextureFormat::R8);
+                                                                    TextureFormat::R8)?;
 
-        UIPresenter {
+        Ok(UIPresenter {
             event_queue: UIEventQueue::new(),
             mouse_position: Vector2F::zero(),
 
@@ -111,7 +111,7 @@ impl<D> UIPresenter<D> where D: Device {
             font_texture,
             corner_fill_texture,
             corner_outline_texture,
-        }
+        })
     }
 
     pub fn framebuffer_size(&self) -> Vector2I {
@@ -188,31 +188,32 @@ impl<D> UIPresenter<D> where D: Device {
             let index_buffer = allocator.get_buffer(index_buffer_id);
             device.upload_to_buffer(&vertex_buffer, 0, vertex_data, BufferTarget::Vertex);
             device.upload_to_buffer(&index_buffer, 0, index_data, BufferTarget::Index);
-            let solid_vertex_array = DebugSolidVertexArray::new(device,
-                                                                &self.solid_program,
-                                                                vertex_buffer,
-                                                                index_buffer);
-
-            let primitive = if filled { Primitive::Triangles } else { Primitive::Lines };
-            device.draw_elements(index_data.len() as u32, &RenderState {
-                target: &RenderTarget::Default,
-                program: &self.solid_program.program,
-                vertex_array: &solid_vertex_array.vertex_array,
-                primitive,
-                uniforms: &[
-                    (&self.solid_program.framebuffer_size_uniform,
-                    UniformData::Vec2(self.framebuffer_size.0.to_f32x2())),
-                    (&self.solid_program.color_uniform, get_color_uniform(color)),
-                ],
-                textures: &[],
-                images: &[],
-                storage_buffers: &[],
-                viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
-                options: RenderOptions {
-                    blend: Some(alpha_blend_state()),
-                    ..RenderOptions::default()
-                },
-            });
+            // The debug UI isn't worth failing over, so it just isn't drawn if this fails.
+            if let Ok(solid_vertex_array) = DebugSolidVertexArray::new(device,
+                                                                       &self.solid_program,
+                                                                       vertex_buffer,
+                                                                       index_buffer) {
+                let primitive = if filled { Primitive::Triangles } else { Primitive::Lines };
+                device.draw_elements(index_data.len() as u32, &RenderState {
+                    target: &RenderTarget::Default,
+                    program: &self.solid_program.program,
+                    vertex_array: &solid_vertex_array.vertex_array,
+                    primitive,
+                    uniforms: &[
+                        (&self.solid_program.framebuffer_size_uniform,
+                        UniformData::Vec2(self.framebuffer_size.0.to_f32x2())),
+                        (&self.solid_program.color_uniform, get_color_uniform(color)),
+                    ],
+                    textures: &[],
+                    images: &[],
+                    storage_buffers: &[],
+                    viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
+                    options: RenderOptions {
+                        blend: Some(alpha_blend_state()),
+                        ..RenderOptions::default()
+                    },
+                });
+            }
         }
 
         allocator.free_buffer(index_buffer_id);
@@ -478,32 +479,33 @@ impl<D> UIPresenter<D> where D: Device {
             device.upload_to_buffer(&vertex_buffer, 0, vertex_data, BufferTarget::Vertex);
             device.upload_to_buffer(&index_buffer, 0, index_data, BufferTarget::Index);
 
-            let texture_vertex_array = DebugTextureVertexArray::new(device,
-                                                                    &self.texture_program,
-                                                                    vertex_buffer,
-                                                                    index_buffer);
-
-            device.draw_elements(index_data.len() as u32, &RenderState {
-                target: &RenderTarget::Default,
-                program: &self.texture_program.program,
-                vertex_array: &texture_vertex_array.vertex_array,
-                primitive: Primitive::Triangles,
-                textures: &[(&self.texture_program.texture, &texture)],
-                images: &[],
-                storage_buffers: &[],
-                uniforms: &[
-                    (&self.texture_program.framebuffer_size_uniform,
-                    UniformData::Vec2(self.framebuffer_size.0.to_f32x2())),
-                    (&self.texture_program.color_uniform, get_color_uniform(color)),
-                    (&self.texture_program.texture_size_uniform,
-                    UniformData::Vec2(device.texture_size(&texture).0.to_f32x2()))
-                ],
-                viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
-                options: RenderOptions {
-                    blend: Some(alpha_blend_state()),
-                    ..RenderOptions::default()
-                },
-            });
+            // The debug UI isn't worth failing over, so it just isn't drawn if this fails.
+            if let Ok(texture_vertex_array) = DebugTextureVertexArray::new(device,
+                                                                           &self.texture_program,
+                                                                           vertex_buffer,
+                                                                           index_buffer) {
+                device.draw_elements(index_data.len() as u32, &RenderState {
+                    target: &RenderTarget::Default,
+                    program: &self.texture_program.program,
+                    vertex_array: &texture_vertex_array.vertex_array,
+                    primitive: Primitive::Triangles,
+                    textures: &[(&self.texture_program.texture, &texture)],
+                    images: &[],
+                    storage_buffers: &[],
+                    uniforms: &[
+                        (&self.texture_program.framebuffer_size_uniform,
+                        UniformData::Vec2(self.framebuffer_size.0.to_f32x2())),
+                        (&self.texture_program.color_uniform, get_color_uniform(color)),
+                        (&self.texture_program.texture_size_uniform,
+                        UniformData::Vec2(device.texture_size(&texture).0.to_f32x2()))
+                    ],
+                    viewport: RectI::new(Vector2I::default(), self.framebuffer_size),
+                    options: RenderOptions {
+                        blend: Some(alpha_blend_state()),
+                        ..RenderOptions::default()
+                    },
+                });
+            }
         }
 
         allocator.free_buffer(index_buffer_id);
@@ -675,19 +677,20 @@ struct DebugTextureProgram<D> where D: Device {
 }
 
 impl<D> DebugTextureProgram<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> DebugTextureProgram<D> {
-        let program = device.create_raster_program(resources, "debug/texture");
+    fn new(device: &D, resources: &dyn ResourceLoader)
+           -> Result<DebugTextureProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "debug/texture")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let texture_size_uniform = device.get_uniform(&program, "TextureSize");
         let color_uniform = device.get_uniform(&program, "Color");
         let texture = device.get_texture_parameter(&program, "Texture");
-        DebugTextureProgram {
+        Ok(DebugTextureProgram {
             program,
             framebuffer_size_uniform,
             texture_size_uniform,
             color_uniform,
             texture,
-        }
+        })
     }
 }
 
@@ -700,8 +703,8 @@ impl<D> DebugTextureVertexArray<D> where D: Device {
            debug_texture_program: &DebugTextureProgram<D>,
            vertex_buffer: &D::Buffer,
            index_buffer: &D::Buffer)
-           -> DebugTextureVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+           -> Result<DebugTextureVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let position_attr = device.get_vertex_attr(&debug_texture_program.program, "Position")
                                   .unwrap();
@@ -729,7 +732,7 @@ impl<D> DebugTextureVertexArray<D> where D: Device {
             buffer_index: 0,
         });
 
-        DebugTextureVertexArray { vertex_array }
+        Ok(DebugTextureVertexArray { vertex_array })
     }
 }
 
@@ -742,8 +745,8 @@ impl<D> DebugSolidVertexArray<D> where D: Device {
            debug_solid_program: &DebugSolidProgram<D>,
            vertex_buffer: &D::Buffer,
            index_buffer: &D::Buffer)
-           -> DebugSolidVertexArray<D> {
-        let vertex_array = device.create_vertex_array();
+           -> Result<DebugSolidVertexArray<D>, DeviceError> {
+        let vertex_array = device.create_vertex_array()?;
 
         let position_attr =
             device.get_vertex_attr(&debug_solid_program.program, "Position").unwrap();
@@ -759,7 +762,7 @@ impl<D> DebugSolidVertexArray<D> where D: Device {
             buffer_index: 0,
         });
 
-        DebugSolidVertexArray { vertex_array }
+        Ok(DebugSolidVertexArray { vertex_array })
     }
 }
 
@@ -770,11 +773,12 @@ struct DebugSolidProgram<D> where D: Device {
 }
 
 impl<D> DebugSolidProgram<D> where D: Device {
-    fn new(device: &D, resources: &dyn ResourceLoader) -> DebugSolidProgram<D> {
-        let program = device.create_raster_program(resources, "debug/solid");
+    fn new(device: &D, resources: &dyn ResourceLoader)
+           -> Result<DebugSolidProgram<D>, DeviceError> {
+        let program = device.create_raster_program(resources, "debug/solid")?;
         let framebuffer_size_uniform = device.get_uniform(&program, "FramebufferSize");
         let color_uniform = device.get_uniform(&program, "Color");
-        DebugSolidProgram { program, framebuffer_size_uniform, color_uniform }
+        Ok(DebugSolidProgram { program, framebuffer_size_uniform, color_uniform })
     }
 }
 
@@ -2,18 +2,27 @@ use std::fs::File;
 use std::io::{Read, BufWriter};
 use std::error::Error;
 use std::path::PathBuf;
-use pathfinder_svg::SVGScene;
+use pathfinder_svg::{ImportMode, SVGImportOptions, SVGScene};
 use pathfinder_export::{Export, FileFormat};
 use usvg::{Tree, Options};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut args = std::env::args_os().skip(1);
+    let mode = match std::env::args_os().any(|arg| arg == "--strict") {
+        true => ImportMode::Strict,
+        false => ImportMode::Lenient,
+    };
+    let import_options = SVGImportOptions::new().mode(mode);
+    let mut args = std::env::args_os().skip(1).filter(|arg| arg != "--strict");
     let input = PathBuf::from(args.next().expect("no input given"));
     let output = PathBuf::from(args.next().expect("no output given"));
 
     let mut data = Vec::new();
     File::open(input)?.read_to_end(&mut data)?;
-    let svg = SVGScene::from_tree(&Tree::from_data(&data, &Options::default()).unwrap());
+    let tree = Tree::from_data(&data, &Options::default()).unwrap();
+    let svg = SVGScene::from_tree_with_options(&tree, &import_options)?;
+    for diagnostic in &svg.diagnostics {
+        eprintln!("{}", diagnostic);
+    }
 
     let scene = &svg.scene;
     let mut writer = BufWriter::new(File::create(&output)?);
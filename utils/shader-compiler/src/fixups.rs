@@ -0,0 +1,256 @@
+// pathfinder/utils/shader-compiler/src/fixups.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fixes for GLSL that naga's frontend doesn't support or translates in a way its backends reject.
+//!
+//! naga's GLSL frontend doesn't know about atomics, so the preprocessor declares `atomicAdd()` and
+//! friends as ordinary functions, and `lower_atomic_calls()` then turns calls to them into atomic
+//! statements and gives the buffers they operate on atomic element types. Plain reads and writes
+//! of those buffers become atomic loads and stores.
+
+use naga::{AtomicFunction, Block, Expression, Function, GlobalVariable, Handle, Module, Span};
+use naga::{Statement, Type, TypeInner};
+use std::collections::{BTreeSet, HashMap};
+use std::mem;
+
+const ATOMIC_FUNCTIONS: [(&str, AtomicFunction); 7] = [
+    ("atomicAdd", AtomicFunction::Add),
+    ("atomicAnd", AtomicFunction::And),
+    ("atomicExchange", AtomicFunction::Exchange { compare: None }),
+    ("atomicMax", AtomicFunction::Max),
+    ("atomicMin", AtomicFunction::Min),
+    ("atomicOr", AtomicFunction::InclusiveOr),
+    ("atomicXor", AtomicFunction::ExclusiveOr),
+];
+
+/// Returns GLSL declaring placeholder versions of the atomic functions, for both `int` and
+/// `uint`.
+pub fn atomic_function_declarations() -> String {
+    let mut declarations = String::new();
+    for &(name, _) in &ATOMIC_FUNCTIONS {
+        declarations.push_str(&format!("uint {}(uint mem, uint data) {{ return 0u; }}\n", name));
+        declarations.push_str(&format!("int {}(int mem, int data) {{ return 0; }}\n", name));
+    }
+    declarations
+}
+
+/// Replaces calls to the functions from `atomic_function_declarations()` with atomic statements
+/// and removes the functions.
+pub fn lower_atomic_calls(module: &mut Module) -> Result<(), String> {
+    let mut placeholders = HashMap::new();
+    for (handle, function) in module.functions.iter() {
+        let name = function.name.as_ref().map(|name| &name[..]);
+        if let Some(&(_, fun)) = ATOMIC_FUNCTIONS.iter().find(|&&(atomic_name, _)| {
+            name == Some(atomic_name)
+        }) {
+            placeholders.insert(handle, (fun, function.result.as_ref().unwrap().ty));
+        }
+    }
+
+    let mut atomic_globals = BTreeSet::new();
+    for (_, function) in module.functions.iter_mut() {
+        lower_calls(function, &placeholders, &mut atomic_globals)?;
+    }
+    for entry_point in module.entry_points.iter_mut() {
+        lower_calls(&mut entry_point.function, &placeholders, &mut atomic_globals)?;
+    }
+
+    // Drop the placeholders. This renumbers the remaining functions, so calls have to be updated.
+    let old_functions = mem::take(&mut module.functions);
+    let old_handles: Vec<_> = old_functions.iter().map(|(handle, _)| {
+        (handle, old_functions.get_span(handle))
+    }).collect();
+    let mut function_map = HashMap::new();
+    for ((old_handle, span), function) in old_handles.into_iter()
+                                                     .zip(old_functions.into_inner()) {
+        if !placeholders.contains_key(&old_handle) {
+            function_map.insert(old_handle, module.functions.append(function, span));
+        }
+    }
+    for (_, function) in module.functions.iter_mut() {
+        remap_calls(function, &function_map);
+    }
+    for entry_point in module.entry_points.iter_mut() {
+        remap_calls(&mut entry_point.function, &function_map);
+    }
+
+    for global in atomic_globals {
+        make_atomic(module, global)?;
+    }
+
+    // Get rid of the array types that were replaced.
+    naga::compact::compact(module);
+    Ok(())
+}
+
+/// Marks `switch` cases that end by leaving the function or loop as not falling through.
+///
+/// The frontend only looks for `break`, so cases ending in `return` appear to fall through, which
+/// the WGSL backend can't express.
+pub fn end_returning_cases(module: &mut Module) {
+    for (_, function) in module.functions.iter_mut() {
+        end_returning_cases_in_block(&mut function.body);
+    }
+    for entry_point in module.entry_points.iter_mut() {
+        end_returning_cases_in_block(&mut entry_point.function.body);
+    }
+}
+
+fn end_returning_cases_in_block(block: &mut Block) {
+    for_each_statement(block, &mut |statement| {
+        if let Statement::Switch { ref mut cases, .. } = *statement {
+            for case in cases {
+                match case.body.last() {
+                    Some(&Statement::Return { .. }) |
+                    Some(&Statement::Kill) |
+                    Some(&Statement::Break) |
+                    Some(&Statement::Continue) => case.fall_through = false,
+                    _ => {}
+                }
+            }
+        }
+    });
+}
+
+fn lower_calls(function: &mut Function,
+               placeholders: &HashMap<Handle<Function>, (AtomicFunction, Handle<Type>)>,
+               atomic_globals: &mut BTreeSet<Handle<GlobalVariable>>)
+               -> Result<(), String> {
+    let expressions = &mut function.expressions;
+    let mut error = None;
+    for_each_statement(&mut function.body, &mut |statement| {
+        let (fun, ty, arguments, result) = match *statement {
+            Statement::Call { function, ref arguments, result } => {
+                match placeholders.get(&function) {
+                    Some(&(fun, ty)) => (fun, ty, arguments.clone(), result.unwrap()),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        // The placeholder was passed the value of the buffer element; we want its address.
+        let pointer = match expressions[arguments[0]] {
+            Expression::Load { pointer } => pointer,
+            _ => {
+                error = Some("the first argument of an atomic function must be a variable");
+                return;
+            }
+        };
+        match global_variable(expressions, pointer) {
+            Some(global) => atomic_globals.insert(global),
+            None => {
+                error = Some("atomic functions can only be used on storage buffers");
+                return;
+            }
+        };
+
+        expressions[result] = Expression::AtomicResult { ty, comparison: false };
+        *statement = Statement::Atomic { pointer, fun, value: arguments[1], result };
+    });
+    match error {
+        None => Ok(()),
+        Some(error) => Err(error.to_owned()),
+    }
+}
+
+fn remap_calls(function: &mut Function,
+               function_map: &HashMap<Handle<Function>, Handle<Function>>) {
+    for_each_statement(&mut function.body, &mut |statement| {
+        if let Statement::Call { ref mut function, .. } = *statement {
+            *function = function_map[function];
+        }
+    });
+    for (_, expression) in function.expressions.iter_mut() {
+        if let Expression::CallResult(ref mut function) = *expression {
+            *function = function_map[function];
+        }
+    }
+}
+
+fn for_each_statement(block: &mut Block, callback: &mut dyn FnMut(&mut Statement)) {
+    for statement in block.iter_mut() {
+        callback(statement);
+        match *statement {
+            Statement::Block(ref mut block) => for_each_statement(block, callback),
+            Statement::If { ref mut accept, ref mut reject, .. } => {
+                for_each_statement(accept, callback);
+                for_each_statement(reject, callback);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases {
+                    for_each_statement(&mut case.body, callback);
+                }
+            }
+            Statement::Loop { ref mut body, ref mut continuing, .. } => {
+                for_each_statement(body, callback);
+                for_each_statement(continuing, callback);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn global_variable(expressions: &naga::Arena<Expression>, mut pointer: Handle<Expression>)
+                   -> Option<Handle<GlobalVariable>> {
+    loop {
+        match expressions[pointer] {
+            Expression::GlobalVariable(global) => return Some(global),
+            Expression::Access { base, .. } | Expression::AccessIndex { base, .. } => {
+                pointer = base
+            }
+            _ => return None,
+        }
+    }
+}
+
+// Changes the element type of a storage buffer's array to an atomic type.
+fn make_atomic(module: &mut Module, global: Handle<GlobalVariable>) -> Result<(), String> {
+    let buffer_type = module.global_variables[global].ty;
+    let (name, mut members, span) = match module.types[buffer_type] {
+        Type { ref name, inner: TypeInner::Struct { ref members, span } } => {
+            (name.clone(), members.clone(), span)
+        }
+        _ => return Err("atomic functions can only be used on storage buffers".to_owned()),
+    };
+
+    let old_members = members.clone();
+
+    // Our storage buffers all consist of a single runtime-sized array.
+    let member = members.last_mut().unwrap();
+    let (base, size, stride) = match module.types[member.ty].inner {
+        TypeInner::Array { base, size, stride } => (base, size, stride),
+        _ => return Err("atomic functions can only be used on arrays".to_owned()),
+    };
+    let scalar = match module.types[base].inner {
+        TypeInner::Scalar(scalar) => scalar,
+        _ => return Err("atomic functions can only be used on arrays of scalars".to_owned()),
+    };
+
+    let atomic = module.types.insert(Type { name: None, inner: TypeInner::Atomic(scalar) },
+                                     Span::UNDEFINED);
+    member.ty = module.types.insert(Type {
+        name: None,
+        inner: TypeInner::Array { base: atomic, size, stride },
+    }, Span::UNDEFINED);
+    let new_buffer_type = module.types.insert(Type {
+        name: name.clone(),
+        inner: TypeInner::Struct { members, span },
+    }, Span::UNDEFINED);
+    module.global_variables[global].ty = new_buffer_type;
+
+    // Unname the old type so that `compact()` drops it instead of leaving two types with the same
+    // name.
+    module.types.replace(buffer_type, Type {
+        name: None,
+        inner: TypeInner::Struct { members: old_members, span },
+    });
+    Ok(())
+}
@@ -0,0 +1,156 @@
+// pathfinder/utils/shader-compiler/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Translates the GLSL shaders in `shaders/` to WGSL and SPIR-V, validating them along the way.
+//!
+//! Usage: `shader-compiler SHADER_DIR OUTPUT_DIR`. The `cross` target of `shaders/Makefile` runs
+//! this with the output going to `resources/shaders/wgsl` and `resources/shaders/spirv`.
+
+use naga::back::{spv, wgsl};
+use naga::front::glsl::{Frontend, Options};
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+use naga::{FastHashMap, ShaderStage};
+use preprocess::PreprocessedShader;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod fixups;
+mod preprocess;
+
+const HEADER: &str = "// Automatically generated from files in pathfinder/shaders/. Do not edit!\n";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args_os().skip(1);
+    let usage = "usage: shader-compiler SHADER_DIR OUTPUT_DIR";
+    let shader_dir = PathBuf::from(args.next().expect(usage));
+    let output_dir = PathBuf::from(args.next().expect(usage));
+
+    let mut shader_paths = vec![];
+    find_shaders(&shader_dir, &mut shader_paths)?;
+    shader_paths.sort();
+
+    for path in &shader_paths {
+        let name = path.strip_prefix(&shader_dir)?.with_extension("");
+        let stage = shader_stage(path).unwrap();
+        if let Err(err) = compile(&shader_dir, path, stage, &output_dir, &name) {
+            eprintln!("{}: {}", path.display(), err);
+            process::exit(1);
+        }
+        println!("{}", name.display());
+    }
+    Ok(())
+}
+
+fn find_shaders(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_shaders(&path, paths)?;
+        } else if shader_stage(&path).is_some() {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn shader_stage(path: &Path) -> Option<ShaderStage> {
+    let file_name = path.file_name()?.to_str()?;
+    if file_name.ends_with(".vs.glsl") {
+        Some(ShaderStage::Vertex)
+    } else if file_name.ends_with(".fs.glsl") {
+        Some(ShaderStage::Fragment)
+    } else if file_name.ends_with(".cs.glsl") {
+        Some(ShaderStage::Compute)
+    } else {
+        None
+    }
+}
+
+fn compile(shader_dir: &Path,
+           path: &Path,
+           stage: ShaderStage,
+           output_dir: &Path,
+           name: &Path)
+           -> Result<(), Box<dyn Error>> {
+    // Match the fragment shader's inputs to the vertex shader's outputs.
+    let mut input_locations = HashMap::new();
+    if stage == ShaderStage::Fragment {
+        let vertex_shader_name = path.file_name().unwrap().to_str().unwrap()
+                                     .replace(".fs.glsl", ".vs.glsl");
+        let vertex_shader_path = path.with_file_name(vertex_shader_name);
+        if vertex_shader_path.exists() {
+            let empty = HashMap::new();
+            let vertex_shader = preprocess::preprocess(shader_dir,
+                                                       &vertex_shader_path,
+                                                       ShaderStage::Vertex,
+                                                       &empty)?;
+            input_locations = vertex_shader.output_locations;
+        }
+    }
+
+    let PreprocessedShader { source, .. } = preprocess::preprocess(shader_dir,
+                                                                   path,
+                                                                   stage,
+                                                                   &input_locations)?;
+
+    // WebGPU, Vulkan, and Metal all put the framebuffer origin at the upper left.
+    let mut defines = FastHashMap::default();
+    defines.insert("PF_ORIGIN_UPPER_LEFT".to_owned(), "1".to_owned());
+    let mut module = Frontend::default().parse(&Options { stage, defines }, &source)
+                                        .map_err(|errors| {
+        // Line numbers refer to the preprocessed source.
+        let messages: Vec<_> = errors.iter().map(|error| {
+            let location = error.meta.location(&source);
+            format!("{}:{}: {}", location.line_number, location.line_position, error.kind)
+        }).collect();
+        messages.join("\n")
+    })?;
+    fixups::lower_atomic_calls(&mut module)?;
+    fixups::end_returning_cases(&mut module);
+
+    let info = Validator::new(ValidationFlags::all(), Capabilities::empty())
+                   .validate(&module)
+                   .map_err(|error| error.emit_to_string(&source))?;
+
+    let wgsl = wgsl::write_string(&module, &info, wgsl::WriterFlags::empty())?;
+    write_output(&output_dir.join("wgsl").join(name).with_extension(extension(name, "wgsl")),
+                 format!("{}{}", HEADER, wgsl).as_bytes())?;
+
+    // Flip Y for Vulkan's clip space, and leave out debug info so that debug and release builds
+    // of this tool agree.
+    let spv_options = spv::Options {
+        flags: spv::WriterFlags::ADJUST_COORDINATE_SPACE | spv::WriterFlags::LABEL_VARYINGS |
+            spv::WriterFlags::CLAMP_FRAG_DEPTH,
+        ..spv::Options::default()
+    };
+    let pipeline_options = spv::PipelineOptions {
+        shader_stage: stage,
+        entry_point: "main".to_owned(),
+    };
+    let words = spv::write_vec(&module, &info, &spv_options, Some(&pipeline_options))?;
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes().to_vec()).collect();
+    write_output(&output_dir.join("spirv").join(name).with_extension(extension(name, "spv")),
+                 &bytes)
+}
+
+// `fill.fs` becomes `fill.fs.wgsl`, not `fill.wgsl`.
+fn extension(name: &Path, extension: &str) -> String {
+    format!("{}.{}", name.extension().unwrap().to_str().unwrap(), extension)
+}
+
+fn write_output(path: &Path, bytes: &[u8]) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(path.parent().unwrap())?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
@@ -0,0 +1,339 @@
+// pathfinder/utils/shader-compiler/src/preprocess.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rewrites the OpenGL-style GLSL in `shaders/` into the Vulkan-style GLSL that naga accepts.
+//!
+//! The rewrite is textual and relies on the conventions that the shaders already follow: global
+//! declarations start at column 0 and take up one line, and combined samplers are only ever
+//! passed to texture functions or to other functions.
+//!
+//! Resources end up in two bind groups:
+//!
+//! * Group 0 holds the storage buffers of the compute shaders, at the bindings they declare.
+//!
+//! * Group 1 holds everything else in declaration order: the `bUniforms` block containing all
+//!   loose uniforms (at the position of the first one), storage images, and each `sampler2D`,
+//!   which becomes a `texture2D` followed by a `sampler` with `Sampler` appended to its name.
+
+use crate::fixups;
+use naga::ShaderStage;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const UNIFORM_BLOCK_NAME: &str = "bUniforms";
+
+const TEXTURE_FUNCTIONS: [&str; 5] = ["texelFetch", "texture", "textureGrad", "textureLod",
+                                      "textureSize"];
+
+pub struct PreprocessedShader {
+    pub source: String,
+    /// The locations assigned to the `out` variables, by name.
+    pub output_locations: HashMap<String, u32>,
+}
+
+/// Preprocesses the shader at `path`.
+///
+/// `input_locations` gives the locations of varyings written by the previous stage, so that a
+/// fragment shader's inputs line up with its vertex shader's outputs. Inputs not in the map get
+/// locations in declaration order.
+pub fn preprocess(shader_dir: &Path,
+                  path: &Path,
+                  stage: ShaderStage,
+                  input_locations: &HashMap<String, u32>)
+                  -> Result<PreprocessedShader, Box<dyn Error>> {
+    let mut lines = vec![];
+    resolve_includes(shader_dir, path, &mut lines)?;
+    move_memory_qualifiers(&mut lines);
+
+    let mut output = String::from("#version 450\n");
+    if lines.iter().any(|line| line.contains("atomic")) {
+        output.push_str(&fixups::atomic_function_declarations());
+    }
+    let mut uniform_block: Option<(usize, u32, Vec<String>)> = None;
+    let mut samplers = HashSet::new();
+    let mut output_locations = HashMap::new();
+    let (mut next_binding, mut next_input_location) = (0, 0);
+
+    for line in &lines {
+        if line.starts_with("precision ") {
+            continue;
+        }
+
+        if let Some(declaration) = line.strip_prefix("uniform ") {
+            let (ty, name) = split_declaration(declaration)?;
+            if ty == "sampler2D" {
+                output.push_str(&format!("{} uniform texture2D {};\n",
+                                         group_1_layout(next_binding),
+                                         name));
+                output.push_str(&format!("{} uniform sampler {}Sampler;\n",
+                                         group_1_layout(next_binding + 1),
+                                         name));
+                samplers.insert(name.to_owned());
+                next_binding += 2;
+                continue;
+            }
+
+            let member = format!("    {};\n", declaration.trim_end_matches(';'));
+            match uniform_block {
+                Some((_, _, ref mut members)) => members.push(member),
+                None => {
+                    // Mark the place where the block goes; it's filled in at the end.
+                    uniform_block = Some((output.len(), next_binding, vec![member]));
+                    next_binding += 1;
+                }
+            }
+            continue;
+        }
+
+        if line.starts_with("layout(") && line.contains(" uniform image2D ") {
+            // `layout(rgba8) uniform image2D uDest;` keeps its format qualifier.
+            let line = line.replacen("layout(",
+                                     &format!("layout(set = 1, binding = {}, ", next_binding),
+                                     1);
+            output.push_str(&line);
+            output.push('\n');
+            next_binding += 1;
+            continue;
+        }
+
+        if let Some(declaration) = line.strip_prefix("in ") {
+            let (_, name) = split_declaration(declaration)?;
+            let location = match input_locations.get(name) {
+                Some(&location) => location,
+                None => {
+                    next_input_location += 1;
+                    next_input_location - 1
+                }
+            };
+            output.push_str(&format!("layout(location = {}) {}\n", location, line));
+            continue;
+        }
+
+        if let Some(declaration) = line.strip_prefix("out ") {
+            let (_, name) = split_declaration(declaration)?;
+            let location = output_locations.len() as u32;
+            output_locations.insert(name.to_owned(), location);
+            output.push_str(&format!("layout(location = {}) {}\n", location, line));
+            continue;
+        }
+
+        output.push_str(line);
+        output.push('\n');
+    }
+
+    if let Some((position, binding, members)) = uniform_block {
+        // naga can't lay out `mat2` with std140 rules.
+        let block = format!("layout(std430, set = 1, binding = {}) uniform {} {{\n{}}};\n",
+                            binding,
+                            UNIFORM_BLOCK_NAME,
+                            members.concat());
+        output.insert_str(position, &block);
+    }
+
+    let mut source = expand_mat2_constructors(&split_samplers(&output, &samplers));
+    if stage != ShaderStage::Fragment {
+        source = use_explicit_lod(&source);
+    }
+    Ok(PreprocessedShader { source, output_locations })
+}
+
+fn resolve_includes(shader_dir: &Path, path: &Path, lines: &mut Vec<String>)
+                    -> Result<(), Box<dyn Error>> {
+    let source = fs::read_to_string(path)
+                    .map_err(|err| format!("failed to read `{}`: {}", path.display(), err))?;
+    for line in source.lines() {
+        if line.starts_with("#version") || line.starts_with("#extension") {
+            continue;
+        }
+        if let Some(include) = line.strip_prefix("#include ") {
+            let include = include.trim().trim_matches('"');
+            // Like `glslangValidator -I.`, look next to the including file first.
+            let mut include_path = path.parent().unwrap().join(include);
+            if !include_path.exists() {
+                include_path = shader_dir.join(include);
+            }
+            resolve_includes(shader_dir, &include_path, lines)?;
+            continue;
+        }
+        lines.push(line.to_owned());
+    }
+    Ok(())
+}
+
+fn group_1_layout(binding: u32) -> String {
+    format!("layout(set = 1, binding = {})", binding)
+}
+
+// Moves the `readonly` qualifier of each storage buffer's member onto the buffer itself, since
+// naga doesn't support memory qualifiers on block members. `restrict` and `writeonly` are
+// dropped, the latter because WGSL has no write-only storage buffers.
+fn move_memory_qualifiers(lines: &mut [String]) {
+    for index in 0..lines.len() {
+        if !lines[index].starts_with("layout(") || !lines[index].contains(" buffer ") {
+            continue;
+        }
+        let member_index = match (index + 1..lines.len()).find(|&member_index| {
+            !lines[member_index].trim_start().starts_with("//")
+        }) {
+            None => continue,
+            Some(member_index) => member_index,
+        };
+        let member = lines[member_index].replacen("restrict ", "", 1)
+                                        .replacen("writeonly ", "", 1);
+        if member.contains(" readonly ") {
+            lines[index] = lines[index].replacen(" buffer ", " readonly buffer ", 1);
+        }
+        lines[member_index] = member.replacen("readonly ", "", 1);
+    }
+}
+
+// Splits a one-line declaration like `vec4 uColor;` into its type and name.
+fn split_declaration(declaration: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    let declaration = declaration.trim().trim_end_matches(';');
+    match declaration.rfind(' ') {
+        Some(index) => Ok((declaration[..index].trim(), &declaration[(index + 1)..])),
+        None => Err(format!("can't parse declaration `{}`", declaration).into()),
+    }
+}
+
+// Rewrites every use of a combined sampler in terms of its separate texture and sampler.
+//
+// Declarations of function parameters become two parameters, arguments to texture functions are
+// wrapped in a `sampler2D` constructor, and arguments to other functions become two arguments.
+fn split_samplers(source: &str, globals: &HashSet<String>) -> String {
+    let mut samplers = globals.clone();
+    let tokens = tokenize(source);
+    for pair in significant_tokens(&tokens).windows(2) {
+        if pair[0] == "sampler2D" && is_identifier(pair[1]) {
+            samplers.insert(pair[1].to_owned());
+        }
+    }
+
+    let mut output = String::with_capacity(source.len());
+    // The last two tokens that aren't whitespace or comments.
+    let mut previous: [&str; 2] = ["", ""];
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = tokens[index];
+        index += 1;
+        if token.trim().is_empty() || token.starts_with("//") || token.starts_with("/*") {
+            output.push_str(token);
+            continue;
+        }
+
+        if token == "sampler2D" && index + 1 < tokens.len() && tokens[index].trim().is_empty() &&
+                samplers.contains(tokens[index + 1]) {
+            let name = tokens[index + 1];
+            output.push_str(&format!("texture2D {}, sampler {}Sampler", name, name));
+            previous = ["sampler", name];
+            index += 2;
+            continue;
+        }
+
+        if !samplers.contains(token) || previous[1] == "texture2D" || previous[1] == "." {
+            output.push_str(token);
+            previous = [previous[1], token];
+            continue;
+        }
+
+        if previous[1] == "(" && TEXTURE_FUNCTIONS.contains(&previous[0]) {
+            output.push_str(&format!("sampler2D({}, {}Sampler)", token, token));
+        } else {
+            output.push_str(&format!("{}, {}Sampler", token, token));
+        }
+        previous = [previous[1], token];
+    }
+    output
+}
+
+// naga can't construct a `mat2` from a `vec4`, so `mat2(v)` becomes `mat2(v.xy, v.zw)` when `v`
+// is declared as a `vec4`.
+fn expand_mat2_constructors(source: &str) -> String {
+    let tokens = tokenize(source);
+    let vec4s: HashSet<&str> = significant_tokens(&tokens).windows(2)
+                                                         .filter(|pair| pair[0] == "vec4")
+                                                         .map(|pair| pair[1])
+                                                         .collect();
+
+    let mut output = String::with_capacity(source.len());
+    for (index, &token) in tokens.iter().enumerate() {
+        if index >= 2 && tokens[index - 2] == "mat2" && tokens[index - 1] == "(" &&
+                tokens.get(index + 1) == Some(&")") && vec4s.contains(token) {
+            output.push_str(&format!("{}.xy, {}.zw", token, token));
+        } else {
+            output.push_str(token);
+        }
+    }
+    output
+}
+
+// Outside fragment shaders there are no derivatives to pick a mipmap level with, so GLSL samples
+// level 0 in `texture()` calls, but naga rejects them. This turns them into `textureLod()` calls.
+fn use_explicit_lod(source: &str) -> String {
+    let mut tokens = tokenize(source);
+    let mut index = 0;
+    while index + 1 < tokens.len() {
+        if tokens[index] != "texture" || tokens[index + 1] != "(" {
+            index += 1;
+            continue;
+        }
+        tokens[index] = "textureLod";
+        let mut depth = 0;
+        for close_index in (index + 1)..tokens.len() {
+            match tokens[close_index] {
+                "(" => depth += 1,
+                ")" if depth == 1 => {
+                    tokens.insert(close_index, ", 0.0");
+                    break;
+                }
+                ")" => depth -= 1,
+                _ => {}
+            }
+        }
+        index += 2;
+    }
+    tokens.concat()
+}
+
+// Splits GLSL source into identifiers, numbers, runs of whitespace, comments, and single
+// punctuation characters. Concatenating the tokens gives back the source.
+fn tokenize(source: &str) -> Vec<&str> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut start = 0;
+    while start < bytes.len() {
+        let rest = &source[start..];
+        let length = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if rest.starts_with("/*") {
+            rest.find("*/").map_or(rest.len(), |end| end + 2)
+        } else if bytes[start].is_ascii_whitespace() {
+            rest.find(|c: char| !c.is_ascii_whitespace()).unwrap_or(rest.len())
+        } else if bytes[start].is_ascii_alphanumeric() || bytes[start] == b'_' {
+            rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_').unwrap_or(rest.len())
+        } else {
+            rest.chars().next().unwrap().len_utf8()
+        };
+        tokens.push(&rest[..length]);
+        start += length;
+    }
+    tokens
+}
+
+// Drops the whitespace tokens.
+fn significant_tokens<'a>(tokens: &[&'a str]) -> Vec<&'a str> {
+    tokens.iter().cloned().filter(|token| !token.trim().is_empty()).collect()
+}
+
+fn is_identifier(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
@@ -0,0 +1,78 @@
+// pathfinder/utils/trace-stats/src/main.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Prints per-command statistics for a render command trace.
+
+use pathfinder_renderer::gpu_data::RenderCommand;
+use pathfinder_renderer::trace::{self, RENDER_COMMAND_KIND_COUNT, TraceReader};
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+
+#[derive(Clone, Copy, Default)]
+struct CommandStats {
+    count: u64,
+    bytes: u64,
+    // Fills, tiles, texels, etc., depending on the command.
+    items: u64,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let path = env::args_os().nth(1).expect("usage: trace-stats TRACE");
+    let mut reader = TraceReader::new(BufReader::new(File::open(path)?))?;
+
+    let mut stats = [CommandStats::default(); RENDER_COMMAND_KIND_COUNT];
+    let mut frame_count = 0;
+    let mut last_bytes_read = reader.bytes_read();
+    while let Some(command) = reader.next_command()? {
+        let kind = trace::command_kind(&command);
+        stats[kind].count += 1;
+        stats[kind].bytes += reader.bytes_read() - last_bytes_read;
+        stats[kind].items += item_count(&command);
+        last_bytes_read = reader.bytes_read();
+
+        if let RenderCommand::Finish { .. } = command {
+            frame_count += 1;
+        }
+    }
+
+    println!("{} frame(s), {} bytes", frame_count, reader.bytes_read());
+    println!("{:<24}{:>10}{:>14}{:>14}", "command", "count", "bytes", "items");
+    for (kind, stats) in stats.iter().enumerate() {
+        if stats.count > 0 {
+            println!("{:<24}{:>10}{:>14}{:>14}",
+                     trace::command_kind_name(kind),
+                     stats.count,
+                     stats.bytes,
+                     stats.items);
+        }
+    }
+    Ok(())
+}
+
+fn item_count(command: &RenderCommand) -> u64 {
+    match *command {
+        RenderCommand::UploadTexelData { ref texels, .. } => texels.len() as u64,
+        RenderCommand::UploadImage { ref image, .. } => {
+            (image.size().x() * image.size().y()) as u64
+        }
+        RenderCommand::UploadTextureMetadata(ref metadata) => metadata.len() as u64,
+        RenderCommand::AddFillsD3D9(ref fills) => fills.len() as u64,
+        RenderCommand::UploadSceneD3D11 { ref draw_segments, ref clip_segments } => {
+            (draw_segments.indices.len() + clip_segments.indices.len()) as u64
+        }
+        RenderCommand::DrawTilesD3D9(ref batch) => batch.tiles.len() as u64,
+        RenderCommand::PrepareClipTilesD3D11(ref batch) => batch.tile_count as u64,
+        RenderCommand::DrawTilesD3D11(ref batch) => batch.tile_batch_data.tile_count as u64,
+        RenderCommand::Start { path_count, .. } => path_count as u64,
+        _ => 0,
+    }
+}
@@ -17,7 +17,8 @@ use js_sys::{Uint8Array, Uint16Array, Float32Array, Object};
 use pathfinder_geometry::rect::RectI;
 use pathfinder_geometry::vector::Vector2I;
 use pathfinder_gpu::{BlendFactor, BlendOp, BufferData, BufferTarget, BufferUploadMode, ClearOps};
-use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, FeatureLevel};
+use pathfinder_gpu::{ComputeDimensions, ComputeState, DepthFunc, Device, DeviceError};
+use pathfinder_gpu::FeatureLevel;
 use pathfinder_gpu::{ImageBinding, Primitive, ProgramKind, RenderOptions, RenderState};
 use pathfinder_gpu::{RenderTarget, ShaderKind, StencilFunc, TextureBinding, TextureData};
 use pathfinder_gpu::{TextureDataRef, TextureFormat, TextureSamplingFlags, UniformData};
@@ -43,34 +44,46 @@ impl WebGlDevice {
 
     // Error checking
 
-    #[cfg(debug_assertions)]
-    fn ck(&self) {
+    // Returns the first out-of-memory or lost-context error the context has recorded, for
+    // resource creation to report. Any other error is a bug, and panics in debug builds.
+    fn ck(&self) -> Result<(), DeviceError> {
+        let mut result = Ok(());
         let mut num_errors = 0;
         loop {
             let err = self.context.get_error();
-            println!(
-                "GL error: 0x{:x} ({})",
-                err,
-                match err {
-                    WebGl::NO_ERROR => break,
-                    WebGl::INVALID_ENUM => "INVALID_ENUM",
-                    WebGl::INVALID_VALUE => "INVALID_VALUE",
-                    WebGl::INVALID_OPERATION => "INVALID_OPERATION",
-                    WebGl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
-                    WebGl::OUT_OF_MEMORY => "OUT_OF_MEMORY",
-                    _ => "Unknown",
+            let name = match err {
+                WebGl::NO_ERROR => break,
+                WebGl::CONTEXT_LOST_WEBGL => return Err(DeviceError::DeviceLost),
+                WebGl::OUT_OF_MEMORY => {
+                    result = Err(DeviceError::OutOfMemory);
+                    continue;
                 }
-            );
-            num_errors += 1;
+                WebGl::INVALID_ENUM => "INVALID_ENUM",
+                WebGl::INVALID_VALUE => "INVALID_VALUE",
+                WebGl::INVALID_OPERATION => "INVALID_OPERATION",
+                WebGl::INVALID_FRAMEBUFFER_OPERATION => "INVALID_FRAMEBUFFER_OPERATION",
+                _ => "Unknown",
+            };
+            if cfg!(debug_assertions) {
+                println!("GL error: 0x{:x} ({})", err, name);
+                num_errors += 1;
+            }
         }
         if num_errors > 0 {
             panic!("aborting due to {} errors", num_errors);
         }
+        result
     }
 
-    #[cfg(not(debug_assertions))]
+    // Checks for errors after calls that can't fail, in debug builds only. Running out of memory
+    // and losing the context aren't bugs, so they're left to resource creation and
+    // `is_device_lost()` to report.
     #[inline]
-    fn ck(&self) {}
+    fn debug_ck(&self) {
+        if cfg!(debug_assertions) {
+            let _ = self.ck();
+        }
+    }
 
     #[inline]
     fn bind_texture(&self, texture: &WebGlTexture, unit: u32) {
@@ -99,13 +112,13 @@ impl WebGlDevice {
     fn bind_vertex_array(&self, vertex_array: &WebGlVertexArray) {
         self.context
             .bind_vertex_array(Some(&vertex_array.gl_vertex_array));
-        self.ck();
+        self.debug_ck();
     }
 
     #[inline]
     fn unbind_vertex_array(&self) {
         self.context.bind_vertex_array(None);
-        self.ck();
+        self.debug_ck();
     }
     #[inline]
     fn set_uniform(&self, uniform: &WebGlUniform, data: &UniformData) {
@@ -114,11 +127,11 @@ impl WebGlDevice {
         match *data {
             UniformData::Float(value) => {
                 self.context.uniform1f(location, value);
-                self.ck();
+                self.debug_ck();
             }
             UniformData::Int(value) => {
                 self.context.uniform1i(location, value);
-                self.ck();
+                self.debug_ck();
             }
             UniformData::Mat2(data) => {
                 self.context.uniform_matrix2fv_with_f32_array(
@@ -153,24 +166,24 @@ impl WebGlDevice {
             }
             UniformData::Vec2(data) => {
                 self.context.uniform2f(location, data.x(), data.y());
-                self.ck();
+                self.debug_ck();
             }
             UniformData::Vec3(data) => {
                 self.context.uniform3f(location, data[0], data[1], data[2]);
-                self.ck();
+                self.debug_ck();
             }
             UniformData::Vec4(data) => {
                 self.context
                     .uniform4f(location, data.x(), data.y(), data.z(), data.w());
-                self.ck();
+                self.debug_ck();
             }
             UniformData::IVec2(data) => {
                 self.context.uniform2i(location, data[0], data[1]);
-                self.ck();
+                self.debug_ck();
             }
             UniformData::IVec3(data) => {
                 self.context.uniform3i(location, data[0], data[1], data[2]);
-                self.ck();
+                self.debug_ck();
             }
         }
     }
@@ -182,6 +195,20 @@ impl WebGlDevice {
         self.context
             .viewport(origin.x(), origin.y(), size.x(), size.y());
 
+        // Set the scissor before clearing so that clears are restricted too.
+        match render_state.options.scissor {
+            None => self.context.disable(WebGl::SCISSOR_TEST),
+            Some(scissor) => {
+                // WebGL window coordinates have the origin at the lower left.
+                let bottom = origin.y() + size.y() - scissor.max_y();
+                self.context.scissor(origin.x() + scissor.origin_x(),
+                                     bottom,
+                                     scissor.width(),
+                                     scissor.height());
+                self.context.enable(WebGl::SCISSOR_TEST);
+            }
+        }
+
         if render_state.options.clear_ops.has_ops() {
             self.clear(&render_state.options.clear_ops);
         }
@@ -211,7 +238,7 @@ impl WebGlDevice {
         let parameters = program.parameters.borrow();
         for (texture_unit, uniform) in parameters.textures.iter().enumerate() {
             self.context.uniform1i(uniform.location.as_ref(), texture_unit as i32);
-            self.ck();
+            self.debug_ck();
         }
     }
 
@@ -219,7 +246,7 @@ impl WebGlDevice {
         match render_options.blend {
             None => {
                 self.context.disable(WebGl::BLEND);
-                self.ck();
+                self.debug_ck();
             }
             Some(blend) => {
                 let func = match blend.op {
@@ -230,7 +257,7 @@ impl WebGlDevice {
                     BlendOp::Min => WebGl::MIN,
                 };
                 self.context.blend_equation(func);
-                self.ck();
+                self.debug_ck();
 
                 let func = |f| match f {
                     BlendFactor::Zero => WebGl::ZERO,
@@ -249,7 +276,7 @@ impl WebGlDevice {
                     func(blend.dest_alpha_factor),
                 );
                 self.context.enable(WebGl::BLEND);
-                self.ck();
+                self.debug_ck();
             }
         }
 
@@ -257,15 +284,15 @@ impl WebGlDevice {
         match render_options.depth {
             None => {
                 self.context.disable(WebGl::DEPTH_TEST);
-                self.ck();
+                self.debug_ck();
             }
             Some(ref state) => {
                 self.context.depth_func(state.func.to_gl_depth_func());
-                self.ck();
+                self.debug_ck();
                 self.context.depth_mask(state.write as bool);
-                self.ck();
+                self.debug_ck();
                 self.context.enable(WebGl::DEPTH_TEST);
-                self.ck();
+                self.debug_ck();
             }
         }
 
@@ -273,7 +300,7 @@ impl WebGlDevice {
         match render_options.stencil {
             None => {
                 self.context.disable(WebGl::STENCIL_TEST);
-                self.ck();
+                self.debug_ck();
             }
             Some(ref state) => {
                 self.context.stencil_func(
@@ -281,7 +308,7 @@ impl WebGlDevice {
                     state.reference as i32,
                     state.mask,
                 );
-                self.ck();
+                self.debug_ck();
                 let (pass_action, write_mask) = if state.write {
                     (WebGl::REPLACE, state.mask)
                 } else {
@@ -289,10 +316,10 @@ impl WebGlDevice {
                 };
                 self.context
                     .stencil_op(WebGl::KEEP, WebGl::KEEP, pass_action);
-                self.ck();
+                self.debug_ck();
                 self.context.stencil_mask(write_mask);
                 self.context.enable(WebGl::STENCIL_TEST);
-                self.ck();
+                self.debug_ck();
             }
         }
 
@@ -300,7 +327,7 @@ impl WebGlDevice {
         let color_mask = render_options.color_mask as bool;
         self.context
             .color_mask(color_mask, color_mask, color_mask, color_mask);
-        self.ck();
+        self.debug_ck();
     }
 
     fn reset_render_state(&self, render_state: &RenderState<WebGlDevice>) {
@@ -326,8 +353,12 @@ impl WebGlDevice {
             self.context.disable(WebGl::STENCIL_TEST);
         }
 
+        if render_options.scissor.is_some() {
+            self.context.disable(WebGl::SCISSOR_TEST);
+        }
+
         self.context.color_mask(true, true, true, true);
-        self.ck();
+        self.debug_ck();
     }
 
     #[inline]
@@ -401,7 +432,8 @@ unsafe fn check_and_extract_data(
 ) -> Object {
     let channels = match (format, data_ref) {
         (TextureFormat::R8, TextureDataRef::U8(_)) => 1,
-        (TextureFormat::RGBA8, TextureDataRef::U8(_)) => 4,
+        (TextureFormat::RGBA8, TextureDataRef::U8(_)) |
+        (TextureFormat::BGRA8, TextureDataRef::U8(_)) => 4,
         (TextureFormat::RGBA16F, TextureDataRef::F16(_)) => 4,
         (TextureFormat::RGBA32F, TextureDataRef::F32(_)) => 4,
         _ => panic!("Unimplemented texture format!"),
@@ -427,7 +459,7 @@ unsafe fn check_and_extract_data(
 
 impl Device for WebGlDevice {
     type Buffer = WebGlBuffer;
-    type BufferDataReceiver = ();
+    type BufferDataReceiver = WebGlBufferDataReceiver;
     type Fence = ();
     type Framebuffer = WebGlFramebuffer;
     type ImageParameter = ();
@@ -435,7 +467,7 @@ impl Device for WebGlDevice {
     type Shader = WebGlShader;
     type StorageBuffer = ();
     type Texture = WebGlTexture;
-    type TextureDataReceiver = ();
+    type TextureDataReceiver = WebGlTextureDataReceiver;
     type TextureParameter = WebGlTextureParameter;
     type TimerQuery = WebGlTimerQuery;
     type Uniform = WebGlUniform;
@@ -447,8 +479,23 @@ impl Device for WebGlDevice {
         FeatureLevel::D3D10
     }
 
-    fn create_texture(&self, format: TextureFormat, size: Vector2I) -> WebGlTexture {
-        let texture = self.context.create_texture().unwrap();
+    #[inline]
+    fn supports_texture_format(&self, format: TextureFormat) -> bool {
+        // WebGL 2 has no BGRA uploads.
+        format != TextureFormat::BGRA8
+    }
+
+    #[inline]
+    fn is_device_lost(&self) -> bool {
+        self.context.is_context_lost()
+    }
+
+    fn create_texture(&self, format: TextureFormat, size: Vector2I)
+                      -> Result<WebGlTexture, DeviceError> {
+        if !self.supports_texture_format(format) {
+            return Err(DeviceError::Unsupported("BGRA textures"));
+        }
+        let texture = self.context.create_texture().ok_or(DeviceError::DeviceLost)?;
         let texture = WebGlTexture {
             texture,
             format,
@@ -468,10 +515,11 @@ impl Device for WebGlDevice {
                 format.gl_type(),
                 None,
             )
-            .unwrap();
+            .map_err(|_| DeviceError::OutOfMemory)?;
+        self.ck()?;
 
         self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
-        texture
+        Ok(texture)
     }
 
     fn create_texture_from_data(
@@ -479,12 +527,12 @@ impl Device for WebGlDevice {
         format: TextureFormat,
         size: Vector2I,
         data_ref: TextureDataRef,
-    ) -> WebGlTexture {
+    ) -> Result<WebGlTexture, DeviceError> {
         let data = unsafe {
             check_and_extract_data(data_ref, size, format)
         };
 
-        let texture = self.context.create_texture().unwrap();
+        let texture = self.context.create_texture().ok_or(DeviceError::DeviceLost)?;
         let texture = WebGlTexture {
             texture,
             format,
@@ -505,10 +553,11 @@ impl Device for WebGlDevice {
                 format.gl_type(),
                 Some(&data),
             )
-            .unwrap();
+            .map_err(|_| DeviceError::OutOfMemory)?;
+        self.ck()?;
 
         self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
-        texture
+        Ok(texture)
     }
 
     #[inline]
@@ -521,7 +570,7 @@ impl Device for WebGlDevice {
         name: &str,
         source: &[u8],
         kind: ShaderKind,
-    ) -> WebGlShader {
+    ) -> Result<WebGlShader, DeviceError> {
         let glsl_version_spec = "300 es";
 
         let source = self.preprocess(source, glsl_version_spec);
@@ -529,26 +578,30 @@ impl Device for WebGlDevice {
         let gl_shader_kind = match kind {
             ShaderKind::Vertex => WebGl::VERTEX_SHADER,
             ShaderKind::Fragment => WebGl::FRAGMENT_SHADER,
-            ShaderKind::Compute => panic!("Compute shaders are unsupported in WebGL!"),
+            ShaderKind::Compute => return Err(DeviceError::Unsupported("compute shaders in WebGL")),
         };
 
         let gl_shader = self
             .context
             .create_shader(gl_shader_kind)
-            .expect("could not create shader");
+            .ok_or(DeviceError::DeviceLost)?;
         self.context.shader_source(&gl_shader, &source);
         self.context.compile_shader(&gl_shader);
+        if let Err(err) = self.ck() {
+            self.context.delete_shader(Some(&gl_shader));
+            return Err(err);
+        }
         let compile_status = self
             .context
             .get_shader_parameter(&gl_shader, WebGl::COMPILE_STATUS);
         if !compile_status.as_bool().unwrap_or(false) {
-            if let Some(info_log) = self.context.get_shader_info_log(&gl_shader) {
-                info!("Shader info log:\n{}", info_log);
-            }
-            panic!("{:?} shader '{}' compilation failed", kind, name);
+            let log = self.context.get_shader_info_log(&gl_shader).unwrap_or_default();
+            info!("{:?} shader '{}' compilation failed:\n{}", kind, name, log);
+            self.context.delete_shader(Some(&gl_shader));
+            return Err(DeviceError::ShaderCompilationFailed { name: name.to_owned(), log });
         }
 
-        WebGlShader { gl_shader }
+        Ok(WebGlShader { gl_shader })
     }
 
     fn create_program_from_shaders(
@@ -556,11 +609,11 @@ impl Device for WebGlDevice {
         _resources: &dyn ResourceLoader,
         name: &str,
         shaders: ProgramKind<WebGlShader>,
-    ) -> WebGlProgram {
+    ) -> Result<WebGlProgram, DeviceError> {
         let gl_program = self
             .context
             .create_program()
-            .expect("unable to create program object");
+            .ok_or(DeviceError::DeviceLost)?;
         match shaders {
             ProgramKind::Raster { ref vertex, ref fragment } => {
                 self.context.attach_shader(&gl_program, &vertex.gl_shader);
@@ -571,25 +624,29 @@ impl Device for WebGlDevice {
             }
         }
         self.context.link_program(&gl_program);
+        if let Err(err) = self.ck() {
+            self.context.delete_program(Some(&gl_program));
+            return Err(err);
+        }
         if !self
             .context
             .get_program_parameter(&gl_program, WebGl::LINK_STATUS)
             .as_bool()
             .unwrap_or(false)
         {
-            if let Some(info_log) = self.context.get_program_info_log(&gl_program) {
-                info!("Program info log for {}:\n{}", name, info_log);
-            }
-            panic!("Program {:?} linking failed", name);
+            let log = self.context.get_program_info_log(&gl_program).unwrap_or_default();
+            info!("Program {:?} linking failed:\n{}", name, log);
+            self.context.delete_program(Some(&gl_program));
+            return Err(DeviceError::ProgramLinkingFailed { name: name.to_owned(), log });
         }
 
         let parameters = WebGlProgramParameters { textures: vec![] };
 
-        WebGlProgram {
+        Ok(WebGlProgram {
             context: self.context.clone(),
             gl_program,
             parameters: RefCell::new(parameters),
-        }
+        })
     }
 
     #[inline]
@@ -598,11 +655,13 @@ impl Device for WebGlDevice {
     }
 
     #[inline]
-    fn create_vertex_array(&self) -> WebGlVertexArray {
-        WebGlVertexArray {
+    fn create_vertex_array(&self) -> Result<WebGlVertexArray, DeviceError> {
+        let vertex_array = WebGlVertexArray {
             context: self.context.clone(),
-            gl_vertex_array: self.context.create_vertex_array().unwrap(),
-        }
+            gl_vertex_array: self.context.create_vertex_array().ok_or(DeviceError::DeviceLost)?,
+        };
+        self.ck()?;
+        Ok(vertex_array)
     }
 
     fn get_vertex_attr(&self, program: &WebGlProgram, name: &str) -> Option<WebGlVertexAttr> {
@@ -617,7 +676,7 @@ impl Device for WebGlDevice {
     fn get_uniform(&self, program: &WebGlProgram, name: &str) -> WebGlUniform {
         let name = format!("u{}", name);
         let location = self.context.get_uniform_location(&program.gl_program, &name);
-        self.ck();
+        self.debug_ck();
         WebGlUniform { location: location }
     }
 
@@ -684,12 +743,13 @@ impl Device for WebGlDevice {
         self.context.bind_vertex_array(None);
     }
 
-    fn create_framebuffer(&self, texture: WebGlTexture) -> WebGlFramebuffer {
+    fn create_framebuffer(&self, texture: WebGlTexture)
+                          -> Result<WebGlFramebuffer, DeviceError> {
         debug!(
             "texture size = {:?}, format = {:?}",
             texture.size, texture.format
         );
-        let gl_framebuffer = self.context.create_framebuffer().unwrap();
+        let gl_framebuffer = self.context.create_framebuffer().ok_or(DeviceError::DeviceLost)?;
         self.context
             .bind_framebuffer(WebGl::FRAMEBUFFER, Some(&gl_framebuffer));
         self.bind_texture(&texture, 0);
@@ -701,26 +761,39 @@ impl Device for WebGlDevice {
             Some(&texture.texture),
             0,
         );
-        self.ck();
-        match self.context.check_framebuffer_status(WebGl::FRAMEBUFFER) {
-            WebGl::FRAMEBUFFER_COMPLETE => {}
-            WebGl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => panic!("FRAMEBUFFER_INCOMPLETE_ATTACHMENT"),
+        if let Err(err) = self.ck() {
+            self.context.delete_framebuffer(Some(&gl_framebuffer));
+            return Err(err);
+        }
+        let status = match self.context.check_framebuffer_status(WebGl::FRAMEBUFFER) {
+            WebGl::FRAMEBUFFER_COMPLETE => None,
+            WebGl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => {
+                Some("FRAMEBUFFER_INCOMPLETE_ATTACHMENT".to_owned())
+            }
             WebGl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => {
-                panic!("FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT")
+                Some("FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT".to_owned())
             }
-            WebGl::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => panic!("FRAMEBUFFER_INCOMPLETE_DIMENSIONS"),
-            WebGl::FRAMEBUFFER_UNSUPPORTED => panic!("FRAMEBUFFER_UNSUPPORTED"),
+            WebGl::FRAMEBUFFER_INCOMPLETE_DIMENSIONS => {
+                Some("FRAMEBUFFER_INCOMPLETE_DIMENSIONS".to_owned())
+            }
+            WebGl::FRAMEBUFFER_UNSUPPORTED => Some("FRAMEBUFFER_UNSUPPORTED".to_owned()),
             WebGl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => {
-                panic!("FRAMEBUFFER_INCOMPLETE_MULTISAMPLE")
+                Some("FRAMEBUFFER_INCOMPLETE_MULTISAMPLE".to_owned())
+            }
+            code => Some(format!("unknown code {}", code)),
+        };
+        if let Some(status) = status {
+            self.context.delete_framebuffer(Some(&gl_framebuffer));
+            if self.context.is_context_lost() {
+                return Err(DeviceError::DeviceLost);
             }
-            WebGl::RENDERBUFFER_SAMPLES => panic!("RENDERBUFFER_SAMPLES"),
-            code => panic!("unknown code {}", code),
+            return Err(DeviceError::FramebufferIncomplete(status));
         }
 
-        WebGlFramebuffer {
+        Ok(WebGlFramebuffer {
             framebuffer: gl_framebuffer,
             texture,
-        }
+        })
     }
 
     fn destroy_framebuffer(&self, framebuffer: Self::Framebuffer) -> Self::Texture {
@@ -729,13 +802,14 @@ impl Device for WebGlDevice {
         framebuffer.texture
     }
 
-    fn create_buffer(&self, mode: BufferUploadMode) -> WebGlBuffer {
-        let buffer = self.context.create_buffer().unwrap();
-        WebGlBuffer {
-            buffer,
+    fn create_buffer(&self, mode: BufferUploadMode) -> Result<WebGlBuffer, DeviceError> {
+        let buffer = WebGlBuffer {
+            buffer: self.context.create_buffer().ok_or(DeviceError::DeviceLost)?,
             context: self.context.clone(),
             mode,
-        }
+        };
+        self.ck()?;
+        Ok(buffer)
     }
 
     fn allocate_buffer<T>(
@@ -750,7 +824,7 @@ impl Device for WebGlDevice {
             BufferTarget::Storage => panic!("Shader storage buffers are unsupported in WebGL!"),
         };
         self.context.bind_buffer(target, Some(&buffer.buffer));
-        self.ck();
+        self.debug_ck();
         let usage = buffer.mode.to_gl_usage();
         match data {
             BufferData::Uninitialized(len) => {
@@ -770,10 +844,10 @@ impl Device for WebGlDevice {
                            data: &[T],
                            target: BufferTarget) {
         let target = target.to_gl_target();
-        self.context.bind_buffer(target, Some(&buffer.buffer)); self.ck();
+        self.context.bind_buffer(target, Some(&buffer.buffer)); self.debug_ck();
         self.context.buffer_sub_data_with_i32_and_u8_array(target,
                                                             position as i32,
-                                                            slice_to_u8(data)); self.ck();
+                                                            slice_to_u8(data)); self.debug_ck();
     }
 
     #[inline]
@@ -865,8 +939,48 @@ impl Device for WebGlDevice {
         self.set_texture_sampling_mode(&texture, TextureSamplingFlags::empty());
     }
 
-    fn read_pixels(&self, _render_target: &RenderTarget<WebGlDevice>, _viewport: RectI) -> () {
-        panic!("read_pixels is not supported");
+    fn read_pixels(&self, render_target: &RenderTarget<WebGlDevice>, viewport: RectI)
+                   -> WebGlTextureDataReceiver {
+        let (origin, size) = (viewport.origin(), viewport.size());
+        let format = match *render_target {
+            RenderTarget::Default => TextureFormat::RGBA8,
+            RenderTarget::Framebuffer(framebuffer) => framebuffer.texture.format,
+        };
+        self.bind_render_target(render_target);
+        let pixel_count = size.x() as usize * size.y() as usize;
+
+        // WebGL has no pixel buffer objects to read into asynchronously, so this blocks.
+        let texture_data = match format {
+            TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => {
+                let mut pixels = vec![0; pixel_count * format.bytes_per_pixel()];
+                self.context
+                    .read_pixels_with_opt_u8_array(origin.x(),
+                                                   origin.y(),
+                                                   size.x(),
+                                                   size.y(),
+                                                   format.gl_format(),
+                                                   format.gl_type(),
+                                                   Some(&mut pixels))
+                    .map(|_| TextureData::U8(pixels))
+            }
+            _ => {
+                // Half-float framebuffers can only be read back as full floats in WebGL.
+                let mut pixels = vec![0.0; pixel_count * format.channels()];
+                let array: Object = unsafe { Float32Array::view(&mut pixels) }.into();
+                self.context
+                    .read_pixels_with_opt_array_buffer_view(origin.x(),
+                                                            origin.y(),
+                                                            size.x(),
+                                                            size.y(),
+                                                            format.gl_format(),
+                                                            WebGl::FLOAT,
+                                                            Some(&array))
+                    .map(|_| TextureData::F32(pixels))
+            }
+        };
+        self.debug_ck();
+
+        WebGlTextureDataReceiver(RefCell::new(texture_data.ok()))
     }
 
     fn begin_commands(&self) {
@@ -921,9 +1035,9 @@ impl Device for WebGlDevice {
     }
 
     #[inline]
-    fn create_timer_query(&self) -> WebGlTimerQuery {
+    fn create_timer_query(&self) -> Result<WebGlTimerQuery, DeviceError> {
         // FIXME use performance timers
-        WebGlTimerQuery {}
+        Err(DeviceError::Unsupported("timer queries in WebGL"))
     }
 
     #[inline]
@@ -946,24 +1060,25 @@ impl Device for WebGlDevice {
         Duration::from_millis(0)
     }
 
-    fn try_recv_buffer(&self, _: &()) -> Option<Vec<u8>> {
-        unimplemented!()
+    fn try_recv_buffer(&self, receiver: &WebGlBufferDataReceiver) -> Option<Vec<u8>> {
+        match *receiver {}
     }
 
-    fn recv_buffer(&self, _: &()) -> Vec<u8> {
-        unimplemented!()
+    fn recv_buffer(&self, receiver: &WebGlBufferDataReceiver) -> Vec<u8> {
+        match *receiver {}
     }
 
-    fn read_buffer(&self, _: &Self::Buffer, _: BufferTarget, _: Range<usize>) {
-        unimplemented!()
+    fn read_buffer(&self, _: &Self::Buffer, _: BufferTarget, _: Range<usize>)
+                   -> Result<WebGlBufferDataReceiver, DeviceError> {
+        Err(DeviceError::Unsupported("reading back buffers"))
     }
 
-    fn try_recv_texture_data(&self, _receiver: &Self::TextureDataReceiver) -> Option<TextureData> {
-        None
+    fn try_recv_texture_data(&self, receiver: &Self::TextureDataReceiver) -> Option<TextureData> {
+        receiver.0.borrow_mut().take()
     }
 
-    fn recv_texture_data(&self, _receiver: &Self::TextureDataReceiver) -> TextureData {
-        unimplemented!()
+    fn recv_texture_data(&self, receiver: &Self::TextureDataReceiver) -> TextureData {
+        self.try_recv_texture_data(receiver).expect("Failed to read pixels!")
     }
 
     #[inline]
@@ -985,14 +1100,15 @@ impl Device for WebGlDevice {
         resources: &dyn ResourceLoader,
         name: &str,
         kind: ShaderKind,
-    ) -> Self::Shader {
+    ) -> Result<Self::Shader, DeviceError> {
         let suffix = match kind {
             ShaderKind::Vertex => 'v',
             ShaderKind::Fragment => 'f',
             ShaderKind::Compute => 'c',
         };
         let path = format!("shaders/gl3/{}.{}s.glsl", name, suffix);
-        self.create_shader_from_source(name, &resources.slurp(&path).unwrap(), kind)
+        let source = resources.slurp(&path).map_err(|_| DeviceError::MissingResource(path))?;
+        self.create_shader_from_source(name, &source, kind)
     }
 
     fn add_fence(&self) -> Self::Fence {
@@ -1084,6 +1200,11 @@ impl Drop for WebGlTexture {
 
 pub struct WebGlTimerQuery {}
 
+pub struct WebGlTextureDataReceiver(RefCell<Option<TextureData>>);
+
+/// WebGL can't read back buffers, so `read_buffer()` fails and no receiver ever exists.
+pub enum WebGlBufferDataReceiver {}
+
 trait BufferTargetExt {
     fn to_gl_target(self) -> u32;
 }
@@ -1161,7 +1282,7 @@ impl TextureFormatExt for TextureFormat {
         match self {
             TextureFormat::R8 => WebGl::R8,
             TextureFormat::R16F => WebGl::R16F,
-            TextureFormat::RGBA8 => WebGl::RGBA,
+            TextureFormat::RGBA8 | TextureFormat::BGRA8 => WebGl::RGBA,
             TextureFormat::RGBA16F => WebGl::RGBA16F,
             TextureFormat::RGBA32F => WebGl::RGBA32F,
         }
@@ -1170,13 +1291,18 @@ impl TextureFormatExt for TextureFormat {
     fn gl_format(self) -> u32 {
         match self {
             TextureFormat::R8 | TextureFormat::R16F => WebGl::RED,
-            TextureFormat::RGBA8 | TextureFormat::RGBA16F | TextureFormat::RGBA32F => WebGl::RGBA,
+            TextureFormat::RGBA8 |
+            TextureFormat::BGRA8 |
+            TextureFormat::RGBA16F |
+            TextureFormat::RGBA32F => WebGl::RGBA,
         }
     }
 
     fn gl_type(self) -> u32 {
         match self {
-            TextureFormat::R8 | TextureFormat::RGBA8 => WebGl::UNSIGNED_BYTE,
+            TextureFormat::R8 | TextureFormat::RGBA8 | TextureFormat::BGRA8 => {
+                WebGl::UNSIGNED_BYTE
+            }
             TextureFormat::R16F | TextureFormat::RGBA16F => WebGl::HALF_FLOAT,
             TextureFormat::RGBA32F => WebGl::FLOAT,
         }